@@ -104,6 +104,27 @@ fn symbolize_dwarf_no_lines() {
     assert_eq!(result.code_info.as_ref(), None);
 }
 
+/// Symbolize an address in a DWARF file, end-to-end, i.e., including all
+/// necessary setup, reporting only the symbol name.
+fn symbolize_dwarf_names_only() {
+    let dwarf_vmlinux = Path::new(&env!("CARGO_MANIFEST_DIR"))
+        .join("data")
+        .join("vmlinux-5.17.12-100.fc34.x86_64.dwarf");
+    let src = Source::Elf(Elf::new(dwarf_vmlinux));
+    let symbolizer = Symbolizer::new();
+
+    let results = symbolizer
+        .symbolize_names_only(
+            black_box(&src),
+            black_box(Input::VirtOffset(&[0xffffffff8110ecb0])),
+        )
+        .unwrap();
+    let result = results.into_iter().next().unwrap().into_sym().unwrap();
+
+    assert_eq!(result.name, "abort_creds");
+    assert_eq!(result.code_info.as_ref(), None);
+}
+
 /// Symbolize an address in a DWARF file, end-to-end, i.e., including all
 /// necessary setup.
 fn symbolize_dwarf() {
@@ -229,6 +250,7 @@ where
     bench_fn!(group, symbolize_breakpad);
     bench_fn!(group, symbolize_elf);
     bench_fn!(group, symbolize_dwarf_no_lines);
+    bench_fn!(group, symbolize_dwarf_names_only);
     bench_fn!(group, symbolize_dwarf);
     bench_fn!(group, symbolize_gsym);
     bench_sub_fn!(group, symbolize_gsym_multi_no_setup);
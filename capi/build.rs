@@ -135,6 +135,21 @@ fn cc(src: &Path, dst: &str, options: &[&str]) {
     compile("cc", src, dst, options)
 }
 
+/// Retrieve the ABI version of the library, as used in its soname.
+///
+/// Following Cargo's semantic versioning rules, pre-1.0 releases may
+/// break compatibility with every minor version bump, so they are
+/// considered part of the ABI version.
+fn so_version() -> String {
+    let major = env::var("CARGO_PKG_VERSION_MAJOR").unwrap();
+    let minor = env::var("CARGO_PKG_VERSION_MINOR").unwrap();
+    if major == "0" {
+        format!("{major}.{minor}")
+    } else {
+        major
+    }
+}
+
 fn main() {
     let crate_dir = PathBuf::from(env::var_os("CARGO_MANIFEST_DIR").unwrap());
 
@@ -214,6 +229,15 @@ int main() {
         }
     }
 
+    if cfg!(feature = "versioned-soname") {
+        let target_os = env::var("CARGO_CFG_TARGET_OS").unwrap();
+        if target_os == "linux" || target_os == "android" {
+            let name = env::var("CARGO_PKG_NAME").unwrap().replace('-', "_");
+            let soname = format!("lib{name}.so.{}", so_version());
+            println!("cargo:rustc-cdylib-link-arg=-Wl,-soname,{soname}");
+        }
+    }
+
     if cfg!(feature = "check-doc-snippets") {
         let src = crate_dir.join("examples").join("input-struct-init.c");
         cc(
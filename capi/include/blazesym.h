@@ -83,6 +83,28 @@ typedef enum blaze_err {
   BLAZE_ERR_OTHER = -260,
 } blaze_err;
 
+/**
+ * The valid variant kind in [`blaze_kernel_meta`].
+ */
+typedef enum blaze_kernel_meta_kind {
+  /**
+   * [`blaze_kernel_meta_variant::unknown`] is valid.
+   */
+  BLAZE_KERNEL_META_UNKNOWN = 0,
+  /**
+   * [`blaze_kernel_meta_variant::kernel`] is valid.
+   */
+  BLAZE_KERNEL_META_KERNEL,
+  /**
+   * [`blaze_kernel_meta_variant::module`] is valid.
+   */
+  BLAZE_KERNEL_META_MODULE,
+  /**
+   * [`blaze_kernel_meta_variant::bpf_prog`] is valid.
+   */
+  BLAZE_KERNEL_META_BPF_PROG,
+} blaze_kernel_meta_kind;
+
 /**
  * The reason why normalization failed.
  *
@@ -99,7 +121,7 @@ enum blaze_normalize_reason
    * The absolute address was not found in the corresponding process' virtual
    * memory map.
    */
-  BLAZE_NORMALIZE_REASON_UNMAPPED,
+  BLAZE_NORMALIZE_REASON_UNMAPPED = 0,
   /**
    * The `/proc/<pid>/maps` entry corresponding to the address does not have
    * a component (file system path, object, ...) associated with it.
@@ -186,6 +208,29 @@ enum blaze_symbolize_reason
    * The address belonged to an entity that is currently unsupported.
    */
   BLAZE_SYMBOLIZE_REASON_UNSUPPORTED,
+  /**
+   * The file containing symbolization data for the address could not
+   * be found.
+   */
+  BLAZE_SYMBOLIZE_REASON_FILE_NOT_FOUND,
+  /**
+   * Permission to access the file containing symbolization data for
+   * the address was denied.
+   */
+  BLAZE_SYMBOLIZE_REASON_PERMISSION_DENIED,
+  /**
+   * The address belongs to a thread's stack (`[stack]`).
+   */
+  BLAZE_SYMBOLIZE_REASON_STACK,
+  /**
+   * The address belongs to the process' heap (`[heap]`).
+   */
+  BLAZE_SYMBOLIZE_REASON_HEAP,
+  /**
+   * The address belongs to a special mapping set up by the kernel
+   * (e.g., `[vvar]`) that does not contain symbolizable code.
+   */
+  BLAZE_SYMBOLIZE_REASON_SPECIAL_MAPPING,
 };
 #ifndef __cplusplus
 typedef uint8_t blaze_symbolize_reason;
@@ -227,7 +272,7 @@ typedef enum blaze_user_meta_kind {
   /**
    * [`blaze_user_meta_variant::unknown`] is valid.
    */
-  BLAZE_USER_META_UNKNOWN,
+  BLAZE_USER_META_UNKNOWN = 0,
   /**
    * [`blaze_user_meta_variant::apk`] is valid.
    */
@@ -236,6 +281,10 @@ typedef enum blaze_user_meta_kind {
    * [`blaze_user_meta_variant::elf`] is valid.
    */
   BLAZE_USER_META_ELF,
+  /**
+   * [`blaze_user_meta_variant::shared_mem`] is valid.
+   */
+  BLAZE_USER_META_SHARED_MEM,
 } blaze_user_meta_kind;
 
 /**
@@ -414,6 +463,29 @@ typedef struct blaze_user_meta_elf {
   uint8_t reserved[8];
 } blaze_user_meta_elf;
 
+/**
+ * C compatible version of [`SharedMem`].
+ */
+typedef struct blaze_user_meta_shared_mem {
+  /**
+   * The name of the mapping, as reported in `/proc/<pid>/maps`. This
+   * member is always present.
+   */
+  char *path;
+  /**
+   * The virtual address at which the mapping starts.
+   */
+  uint64_t addr;
+  /**
+   * The offset of the mapping into the shared memory object.
+   */
+  uint64_t offset;
+  /**
+   * Unused member available for future expansion.
+   */
+  uint8_t reserved[8];
+} blaze_user_meta_shared_mem;
+
 /**
  * C compatible version of [`Unknown`].
  */
@@ -443,6 +515,10 @@ typedef union blaze_user_meta_variant {
    * Valid on [`blaze_user_meta_kind::BLAZE_USER_META_ELF`].
    */
   struct blaze_user_meta_elf elf;
+  /**
+   * Valid on [`blaze_user_meta_kind::BLAZE_USER_META_SHARED_MEM`].
+   */
+  struct blaze_user_meta_shared_mem shared_mem;
   /**
    * Valid on [`blaze_user_meta_kind::BLAZE_USER_META_UNKNOWN`].
    */
@@ -465,8 +541,9 @@ typedef struct blaze_user_meta {
 
 /**
  * A file offset or non-normalized address along with an index into the
- * associated [`blaze_user_meta`] array (such as
- * [`blaze_normalized_user_output::metas`]).
+ * associated [`blaze_user_meta`] or [`blaze_kernel_meta`] array (such
+ * as [`blaze_normalized_user_output::metas`] or
+ * [`blaze_normalized_kernel_output::metas`]).
  */
 typedef struct blaze_normalized_output {
   /**
@@ -474,7 +551,8 @@ typedef struct blaze_normalized_output {
    */
   uint64_t output;
   /**
-   * The index into the associated [`blaze_user_meta`] array.
+   * The index into the associated [`blaze_user_meta`] or
+   * [`blaze_kernel_meta`] array.
    */
   size_t meta_idx;
 } blaze_normalized_output;
@@ -507,6 +585,125 @@ typedef struct blaze_normalized_user_output {
   uint8_t reserved[8];
 } blaze_normalized_user_output;
 
+/**
+ * C compatible version of [`Kernel`].
+ */
+typedef struct blaze_kernel_meta_kernel {
+  /**
+   * The KASLR offset that was applied to the kernel image.
+   */
+  uint64_t kaslr_offset;
+  /**
+   * Unused member available for future expansion.
+   */
+  uint8_t reserved[8];
+} blaze_kernel_meta_kernel;
+
+/**
+ * C compatible version of [`KernelModule`].
+ */
+typedef struct blaze_kernel_meta_module {
+  /**
+   * The name of the module. This member is always present.
+   */
+  char *name;
+  /**
+   * The address at which the module is loaded.
+   */
+  uint64_t addr;
+  /**
+   * Unused member available for future expansion.
+   */
+  uint8_t reserved[8];
+} blaze_kernel_meta_module;
+
+/**
+ * C compatible version of [`BpfProg`].
+ */
+typedef struct blaze_kernel_meta_bpf_prog {
+  /**
+   * The name of the program, which may be empty. This member is
+   * always present.
+   */
+  char *name;
+  /**
+   * The program's tag, which is a hash over its instructions.
+   */
+  uint8_t tag[8];
+  /**
+   * The address at which the program is located.
+   */
+  uint64_t addr;
+  /**
+   * Unused member available for future expansion.
+   */
+  uint8_t reserved[8];
+} blaze_kernel_meta_bpf_prog;
+
+/**
+ * The actual variant data in [`blaze_kernel_meta`].
+ */
+typedef union blaze_kernel_meta_variant {
+  /**
+   * Valid on [`blaze_kernel_meta_kind::BLAZE_KERNEL_META_KERNEL`].
+   */
+  struct blaze_kernel_meta_kernel kernel;
+  /**
+   * Valid on [`blaze_kernel_meta_kind::BLAZE_KERNEL_META_MODULE`].
+   */
+  struct blaze_kernel_meta_module module;
+  /**
+   * Valid on [`blaze_kernel_meta_kind::BLAZE_KERNEL_META_BPF_PROG`].
+   */
+  struct blaze_kernel_meta_bpf_prog bpf_prog;
+  /**
+   * Valid on [`blaze_kernel_meta_kind::BLAZE_KERNEL_META_UNKNOWN`].
+   */
+  struct blaze_user_meta_unknown unknown;
+} blaze_kernel_meta_variant;
+
+/**
+ * C ABI compatible version of [`KernelMeta`].
+ */
+typedef struct blaze_kernel_meta {
+  /**
+   * The variant kind that is present.
+   */
+  enum blaze_kernel_meta_kind kind;
+  /**
+   * The actual variant with its data.
+   */
+  union blaze_kernel_meta_variant variant;
+} blaze_kernel_meta;
+
+/**
+ * An object representing normalized kernel addresses.
+ *
+ * C ABI compatible version of [`KernelOutput`].
+ */
+typedef struct blaze_normalized_kernel_output {
+  /**
+   * The number of [`blaze_kernel_meta`] objects present in `metas`.
+   */
+  size_t meta_cnt;
+  /**
+   * An array of `meta_cnt` objects.
+   */
+  struct blaze_kernel_meta *metas;
+  /**
+   * The number of [`blaze_normalized_output`] objects present in `outputs`.
+   */
+  size_t output_cnt;
+  /**
+   * An array of `output_cnt` objects.
+   */
+  struct blaze_normalized_output *outputs;
+  /**
+   * Unused member available for future expansion.
+   */
+  uint8_t reserved[8];
+} blaze_normalized_kernel_output;
+
 /**
  * Options influencing the address normalization process.
  */
@@ -605,11 +802,21 @@ typedef struct blaze_symbolizer_opts {
    * the underlying language does not mangle symbols (such as C).
    */
   bool demangle;
+  /**
+   * Whether to report paths (such as `code_info.dir` and
+   * `code_info.file`) lossily converted to valid UTF-8.
+   *
+   * By default, paths are reported as the raw bytes found in the
+   * symbolization source, which may not be valid UTF-8. If set,
+   * invalid sequences are replaced with `U+FFFD REPLACEMENT
+   * CHARACTER`.
+   */
+  bool lossy_paths;
   /**
    * Unused member available for future expansion. Must be initialized
    * to zero.
    */
-  uint8_t reserved[4];
+  uint8_t reserved[3];
 } blaze_symbolizer_opts;
 
 /**
@@ -718,6 +925,12 @@ typedef struct blaze_sym {
    * If an address could not be symbolized, this member will be NULL.
    * Check the `reason` member for additional information pertaining
    * the failure.
+   *
+   * If the address could not be attributed to a symbol, but source
+   * code information for it is available, this member will be NULL
+   * as well, but `reason` will be
+   * [`blaze_symbolize_reason::BLAZE_SYMBOLIZE_REASON_SUCCESS`] and
+   * `code_info` will be populated.
    */
   const char *name;
   /**
@@ -762,8 +975,8 @@ typedef struct blaze_sym {
    */
   const struct blaze_symbolize_inlined_fn *inlined;
   /**
-   * On error (i.e., if `name` is NULL), a reason trying to explain
-   * why symbolization failed.
+   * On error (i.e., if `name` is NULL and `code_info.file` is NULL as
+   * well), a reason trying to explain why symbolization failed.
    */
   blaze_symbolize_reason reason;
   /**
@@ -980,6 +1193,20 @@ extern "C" {
  */
 enum blaze_err blaze_err_last(void);
 
+/**
+ * Retrieve a message describing the error reported by the last
+ * fallible API function invoked.
+ *
+ * Compared to [`blaze_err_str`], the message may contain additional
+ * details such as the path of a file that could not be opened. If no
+ * such details are available, the textual representation of the error
+ * code as returned by [`blaze_err_last`] is reported.
+ *
+ * The returned string is owned by the library and is only valid until
+ * the next fallible API function is invoked on the same thread.
+ */
+const char *blaze_err_last_msg(void);
+
 /**
  * Retrieve a textual representation of the error code.
  */
@@ -1194,6 +1421,39 @@ struct blaze_normalized_user_output *blaze_normalize_user_addrs_opts(const blaze
  */
 void blaze_user_output_free(struct blaze_normalized_user_output *output);
 
+/**
+ * Normalize a list of kernel addresses.
+ *
+ * C ABI compatible version of [`Normalizer::normalize_kernel_addrs`].
+ *
+ * Addresses that do not belong to the core kernel image, a kernel
+ * module, or a BPF program are reported as
+ * [`blaze_kernel_meta_kind::BLAZE_KERNEL_META_UNKNOWN`].
+ *
+ * On success, the function creates a new [`blaze_normalized_kernel_output`]
+ * object and returns it. The resulting object should be released using
+ * [`blaze_kernel_output_free`] once it is no longer needed.
+ *
+ * On error, the function returns `NULL` and sets the thread's last error to
+ * indicate the problem encountered. Use [`blaze_err_last`] to retrieve this
+ * error.
+ *
+ * # Safety
+ * - `addrs` needs to be a valid pointer to `addr_cnt` addresses
+ */
+struct blaze_normalized_kernel_output *blaze_normalize_kernel_addrs(const blaze_normalizer *normalizer,
+                                                                    const uint64_t *addrs,
+                                                                    size_t addr_cnt);
+
+/**
+ * Free an object as returned by [`blaze_normalize_kernel_addrs`].
+ *
+ * # Safety
+ * The provided object should have been created by
+ * [`blaze_normalize_kernel_addrs`].
+ */
+void blaze_kernel_output_free(struct blaze_normalized_kernel_output *output);
+
 /**
  * Retrieve a textual representation of the reason of a symbolization
  * failure.
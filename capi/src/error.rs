@@ -1,6 +1,9 @@
 use std::cell::Cell;
+use std::cell::RefCell;
 use std::ffi::c_char;
+use std::ffi::CString;
 
+use blazesym::Error;
 use blazesym::ErrorKind;
 
 
@@ -70,6 +73,9 @@ impl From<ErrorKind> for blaze_err {
 thread_local! {
     /// The error reported by the last fallible API function invoked.
     static LAST_ERR: Cell<blaze_err> = const { Cell::new(blaze_err::BLAZE_ERR_OK) };
+    /// The detailed message describing the error reported by the last
+    /// fallible API function invoked, if any.
+    static LAST_ERR_MSG: RefCell<Option<CString>> = const { RefCell::new(None) };
 }
 
 /// Retrieve the error reported by the last fallible API function invoked.
@@ -78,9 +84,41 @@ pub extern "C" fn blaze_err_last() -> blaze_err {
     LAST_ERR.with(|cell| cell.get())
 }
 
-/// Retrieve the error reported by the last fallible API function invoked.
+/// Retrieve a message describing the error reported by the last
+/// fallible API function invoked.
+///
+/// Compared to [`blaze_err_str`], the message may contain additional
+/// details such as the path of a file that could not be opened. If no
+/// such details are available, the textual representation of the error
+/// code as returned by [`blaze_err_last`] is reported.
+///
+/// The returned string is owned by the library and is only valid until
+/// the next fallible API function is invoked on the same thread.
+#[no_mangle]
+pub extern "C" fn blaze_err_last_msg() -> *const c_char {
+    LAST_ERR_MSG.with(|cell| {
+        cell.borrow()
+            .as_ref()
+            .map(|msg| msg.as_ptr())
+            .unwrap_or_else(|| blaze_err_str(blaze_err_last()))
+    })
+}
+
+/// Set the error reported by the last fallible API function invoked.
 pub(crate) fn set_last_err(err: blaze_err) {
-    LAST_ERR.with(|cell| cell.set(err))
+    let () = LAST_ERR.with(|cell| cell.set(err));
+    let _prev = LAST_ERR_MSG.with(|cell| cell.replace(None));
+}
+
+/// Set the error reported by the last fallible API function invoked,
+/// along with the detailed message of `err`.
+pub(crate) fn set_last_err_from(err: &Error) {
+    let () = LAST_ERR.with(|cell| cell.set(err.kind().into()));
+    // Our error messages should not contain NUL bytes, but in case they
+    // do we just fall back to the textual representation of the error
+    // code.
+    let msg = CString::new(format!("{err:#}")).ok();
+    let _prev = LAST_ERR_MSG.with(|cell| cell.replace(msg));
 }
 
 
@@ -135,6 +173,9 @@ mod tests {
     use super::*;
 
     use std::ffi::CStr;
+    use std::io;
+
+    use blazesym::ErrorExt as _;
 
 
     /// Check that we can convert `ErrorKind` instances into `blaze_err`.
@@ -172,4 +213,29 @@ mod tests {
             assert_eq!(cstr, expected);
         }
     }
+
+    /// Check that we report detailed error messages for the last error.
+    #[test]
+    fn last_error_message() {
+        let err = Error::from(io::Error::new(io::ErrorKind::NotFound, "file not found"))
+            .context("failed to open `/tmp/does-not-exist`");
+        let () = set_last_err_from(&err);
+        assert_eq!(blaze_err_last(), blaze_err::BLAZE_ERR_NOT_FOUND);
+        let msg = unsafe { CStr::from_ptr(blaze_err_last_msg()) };
+        assert_eq!(
+            msg.to_str().unwrap(),
+            "failed to open `/tmp/does-not-exist`: file not found"
+        );
+
+        // Errors without additional details just report the textual
+        // representation of the error code.
+        let () = set_last_err(blaze_err::BLAZE_ERR_INVALID_INPUT);
+        let msg = unsafe { CStr::from_ptr(blaze_err_last_msg()) };
+        let expected = unsafe { CStr::from_ptr(blaze_err_str(blaze_err::BLAZE_ERR_INVALID_INPUT)) };
+        assert_eq!(msg, expected);
+
+        let () = set_last_err(blaze_err::BLAZE_ERR_OK);
+        let msg = unsafe { CStr::from_ptr(blaze_err_last_msg()) };
+        assert_eq!(msg.to_str().unwrap(), "success");
+    }
 }
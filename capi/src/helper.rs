@@ -12,6 +12,7 @@ use crate::blaze_err;
 #[cfg(doc)]
 use crate::blaze_err_last;
 use crate::error::set_last_err;
+use crate::error::set_last_err_from;
 
 
 /// Check whether the `PROCMAP_QUERY` ioctl is supported by the system.
@@ -23,11 +24,10 @@ use crate::error::set_last_err;
 #[no_mangle]
 pub extern "C" fn blaze_supports_procmap_query() -> bool {
     let result = is_procmap_query_supported();
-    let err = result
-        .as_ref()
-        .map(|_| blaze_err::BLAZE_ERR_OK)
-        .unwrap_or_else(|err| err.kind().into());
-    let () = set_last_err(err);
+    let () = match &result {
+        Ok(_) => set_last_err(blaze_err::BLAZE_ERR_OK),
+        Err(err) => set_last_err_from(err),
+    };
     result.unwrap_or(false)
 }
 
@@ -77,11 +77,10 @@ pub unsafe extern "C" fn blaze_read_elf_build_id(path: *const c_char, len: *mut
     }
 
     let result = inner(path, len);
-    let err = result
-        .as_ref()
-        .map(|_| blaze_err::BLAZE_ERR_OK)
-        .unwrap_or_else(|err| err.kind().into());
-    let () = set_last_err(err);
+    let () = match &result {
+        Ok(_) => set_last_err(blaze_err::BLAZE_ERR_OK),
+        Err(err) => set_last_err_from(err),
+    };
 
     // NB: We don't specify what `len` is set to in case of
     //     error, so we don't even attempt to change it below.
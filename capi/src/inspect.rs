@@ -29,6 +29,7 @@ use crate::blaze_err;
 use crate::blaze_err_last;
 use crate::from_cstr;
 use crate::set_last_err;
+use crate::set_last_err_from;
 use crate::util::slice_from_user_array;
 
 
@@ -74,6 +75,8 @@ impl blaze_inspect_elf_src {
         let Elf {
             path,
             debug_syms,
+            code_info: _,
+            decl_file: _,
             _non_exhaustive: (),
         } = other;
 
@@ -100,6 +103,8 @@ impl blaze_inspect_elf_src {
                 unsafe { CString::from_raw(path as *mut _) }.into_bytes(),
             )),
             debug_syms,
+            code_info: false,
+            decl_file: None,
             _non_exhaustive: (),
         };
     }
@@ -117,6 +122,8 @@ impl From<blaze_inspect_elf_src> for Elf {
         Self {
             path: unsafe { from_cstr(path) },
             debug_syms,
+            code_info: false,
+            decl_file: None,
             _non_exhaustive: (),
         }
     }
@@ -219,6 +226,7 @@ fn convert_syms_list_to_c(syms_list: Vec<Vec<SymInfo>>) -> *const *const blaze_s
             sym_type,
             file_offset,
             obj_file_name,
+            code_info: _,
         } in syms
         {
             let name_ptr = str_ptr.cast();
@@ -327,7 +335,7 @@ pub unsafe extern "C" fn blaze_inspect_syms_elf(
             result
         }
         Err(err) => {
-            let () = set_last_err(err.kind().into());
+            let () = set_last_err_from(&err);
             ptr::null()
         }
     }
@@ -404,6 +412,7 @@ mod tests {
     use test_tag::tag;
 
     use crate::blaze_err_last;
+    use crate::blaze_err_last_msg;
 
 
     /// Check that various types have expected sizes.
@@ -546,6 +555,7 @@ mod tests {
             sym_type: SymType::Function,
             file_offset: Some(1337),
             obj_file_name: Some(Path::new("/tmp/foobar.so").into()),
+            code_info: None,
         }]];
         test(syms);
 
@@ -558,6 +568,7 @@ mod tests {
                 sym_type: SymType::Function,
                 file_offset: Some(1337),
                 obj_file_name: Some(Path::new("/tmp/foobar.so").into()),
+                code_info: None,
             },
             SymInfo {
                 name: "sym2".into(),
@@ -566,6 +577,7 @@ mod tests {
                 sym_type: SymType::Undefined,
                 file_offset: Some(1338),
                 obj_file_name: Some(Path::new("other.so").into()),
+                code_info: None,
             },
         ]];
         test(syms);
@@ -579,6 +591,7 @@ mod tests {
                 sym_type: SymType::Function,
                 file_offset: Some(1337),
                 obj_file_name: Some(Path::new("/tmp/foobar.so").into()),
+                code_info: None,
             }],
             vec![SymInfo {
                 name: "sym2".into(),
@@ -587,6 +600,7 @@ mod tests {
                 sym_type: SymType::Undefined,
                 file_offset: Some(1338),
                 obj_file_name: Some(Path::new("other.so").into()),
+                code_info: None,
             }],
         ];
         test(syms);
@@ -599,6 +613,7 @@ mod tests {
             sym_type: SymType::Function,
             file_offset: Some(1337),
             obj_file_name: Some(Path::new("/tmp/foobar.so").into()),
+            code_info: None,
         };
         let syms = vec![(0..200).map(|_| sym.clone()).collect()];
         test(syms);
@@ -660,6 +675,8 @@ mod tests {
         let () = unsafe { ManuallyDrop::into_inner(src).free() };
         assert_eq!(result, ptr::null());
         assert_eq!(blaze_err_last(), blaze_err::BLAZE_ERR_NOT_FOUND);
+        let msg = unsafe { CStr::from_ptr(blaze_err_last_msg()) };
+        assert!(msg.to_str().unwrap().contains("does-not-exist"), "{msg:?}");
 
         let () = unsafe { blaze_inspector_free(inspector) };
     }
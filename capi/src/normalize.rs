@@ -13,10 +13,16 @@ use std::ptr;
 use std::slice;
 
 use blazesym::normalize::Apk;
+use blazesym::normalize::BpfProg;
 use blazesym::normalize::Elf;
+use blazesym::normalize::Kernel;
+use blazesym::normalize::KernelMeta;
+use blazesym::normalize::KernelModule;
+use blazesym::normalize::KernelOutput;
 use blazesym::normalize::NormalizeOpts;
 use blazesym::normalize::Normalizer;
 use blazesym::normalize::Reason;
+use blazesym::normalize::SharedMem;
 use blazesym::normalize::Unknown;
 use blazesym::normalize::UserMeta;
 use blazesym::normalize::UserOutput;
@@ -26,6 +32,7 @@ use crate::blaze_err;
 #[cfg(doc)]
 use crate::blaze_err_last;
 use crate::set_last_err;
+use crate::set_last_err_from;
 use crate::util::slice_from_user_array;
 
 
@@ -238,14 +245,16 @@ pub unsafe extern "C" fn blaze_normalizer_free(normalizer: *mut blaze_normalizer
 
 
 /// A file offset or non-normalized address along with an index into the
-/// associated [`blaze_user_meta`] array (such as
-/// [`blaze_normalized_user_output::metas`]).
+/// associated [`blaze_user_meta`] or [`blaze_kernel_meta`] array (such
+/// as [`blaze_normalized_user_output::metas`] or
+/// [`blaze_normalized_kernel_output::metas`]).
 #[repr(C)]
 #[derive(Debug)]
 pub struct blaze_normalized_output {
     /// The file offset or non-normalized address.
     pub output: u64,
-    /// The index into the associated [`blaze_user_meta`] array.
+    /// The index into the associated [`blaze_user_meta`] or
+    /// [`blaze_kernel_meta`] array.
     pub meta_idx: usize,
 }
 
@@ -261,11 +270,13 @@ impl From<(u64, usize)> for blaze_normalized_output {
 #[derive(Debug, PartialEq)]
 pub enum blaze_user_meta_kind {
     /// [`blaze_user_meta_variant::unknown`] is valid.
-    BLAZE_USER_META_UNKNOWN,
+    BLAZE_USER_META_UNKNOWN = 0,
     /// [`blaze_user_meta_variant::apk`] is valid.
     BLAZE_USER_META_APK,
     /// [`blaze_user_meta_variant::elf`] is valid.
     BLAZE_USER_META_ELF,
+    /// [`blaze_user_meta_variant::shared_mem`] is valid.
+    BLAZE_USER_META_SHARED_MEM,
 }
 
 
@@ -380,6 +391,61 @@ impl blaze_user_meta_elf {
 }
 
 
+/// C compatible version of [`SharedMem`].
+#[repr(C)]
+#[derive(Debug)]
+pub struct blaze_user_meta_shared_mem {
+    /// The name of the mapping, as reported in `/proc/<pid>/maps`. This
+    /// member is always present.
+    pub path: *mut c_char,
+    /// The virtual address at which the mapping starts.
+    pub addr: Addr,
+    /// The offset of the mapping into the shared memory object.
+    pub offset: u64,
+    /// Unused member available for future expansion.
+    pub reserved: [u8; 8],
+}
+
+impl blaze_user_meta_shared_mem {
+    fn from(other: SharedMem) -> ManuallyDrop<Self> {
+        let SharedMem {
+            path,
+            addr,
+            offset,
+            _non_exhaustive: (),
+        } = other;
+
+        let slf = Self {
+            path: CString::new(path.into_os_string().into_vec())
+                .expect("encountered path with NUL bytes")
+                .into_raw(),
+            addr,
+            offset,
+            reserved: [0u8; 8],
+        };
+        ManuallyDrop::new(slf)
+    }
+
+    unsafe fn free(self) {
+        let Self {
+            path,
+            addr,
+            offset,
+            reserved: _,
+        } = self;
+
+        let _shared_mem = SharedMem {
+            path: PathBuf::from(OsString::from_vec(
+                unsafe { CString::from_raw(path) }.into_bytes(),
+            )),
+            addr,
+            offset,
+            _non_exhaustive: (),
+        };
+    }
+}
+
+
 /// The reason why normalization failed.
 ///
 /// The reason is generally only meant as a hint. Reasons reported may change
@@ -390,7 +456,7 @@ impl blaze_user_meta_elf {
 pub enum blaze_normalize_reason {
     /// The absolute address was not found in the corresponding process' virtual
     /// memory map.
-    BLAZE_NORMALIZE_REASON_UNMAPPED,
+    BLAZE_NORMALIZE_REASON_UNMAPPED = 0,
     /// The `/proc/<pid>/maps` entry corresponding to the address does not have
     /// a component (file system path, object, ...) associated with it.
     BLAZE_NORMALIZE_REASON_MISSING_COMPONENT,
@@ -475,6 +541,8 @@ pub union blaze_user_meta_variant {
     pub apk: ManuallyDrop<blaze_user_meta_apk>,
     /// Valid on [`blaze_user_meta_kind::BLAZE_USER_META_ELF`].
     pub elf: ManuallyDrop<blaze_user_meta_elf>,
+    /// Valid on [`blaze_user_meta_kind::BLAZE_USER_META_SHARED_MEM`].
+    pub shared_mem: ManuallyDrop<blaze_user_meta_shared_mem>,
     /// Valid on [`blaze_user_meta_kind::BLAZE_USER_META_UNKNOWN`].
     pub unknown: ManuallyDrop<blaze_user_meta_unknown>,
 }
@@ -511,6 +579,12 @@ impl blaze_user_meta {
                     elf: blaze_user_meta_elf::from(elf),
                 },
             },
+            UserMeta::SharedMem(shared_mem) => Self {
+                kind: blaze_user_meta_kind::BLAZE_USER_META_SHARED_MEM,
+                variant: blaze_user_meta_variant {
+                    shared_mem: blaze_user_meta_shared_mem::from(shared_mem),
+                },
+            },
             UserMeta::Unknown(unknown) => Self {
                 kind: blaze_user_meta_kind::BLAZE_USER_META_UNKNOWN,
                 variant: blaze_user_meta_variant {
@@ -530,6 +604,9 @@ impl blaze_user_meta {
             blaze_user_meta_kind::BLAZE_USER_META_ELF => unsafe {
                 ManuallyDrop::into_inner(self.variant.elf).free()
             },
+            blaze_user_meta_kind::BLAZE_USER_META_SHARED_MEM => unsafe {
+                ManuallyDrop::into_inner(self.variant.shared_mem).free()
+            },
             blaze_user_meta_kind::BLAZE_USER_META_UNKNOWN => {
                 ManuallyDrop::into_inner(unsafe { self.variant.unknown }).free()
             }
@@ -618,7 +695,7 @@ unsafe fn blaze_normalize_user_addrs_impl(
             Box::into_raw(output_box)
         }
         Err(err) => {
-            let () = set_last_err(err.kind().into());
+            let () = set_last_err_from(&err);
             ptr::null_mut()
         }
     }
@@ -730,6 +807,365 @@ pub unsafe extern "C" fn blaze_user_output_free(output: *mut blaze_normalized_us
 }
 
 
+/// The valid variant kind in [`blaze_kernel_meta`].
+#[repr(C)]
+#[derive(Debug, PartialEq)]
+pub enum blaze_kernel_meta_kind {
+    /// [`blaze_kernel_meta_variant::unknown`] is valid.
+    BLAZE_KERNEL_META_UNKNOWN = 0,
+    /// [`blaze_kernel_meta_variant::kernel`] is valid.
+    BLAZE_KERNEL_META_KERNEL,
+    /// [`blaze_kernel_meta_variant::module`] is valid.
+    BLAZE_KERNEL_META_MODULE,
+    /// [`blaze_kernel_meta_variant::bpf_prog`] is valid.
+    BLAZE_KERNEL_META_BPF_PROG,
+}
+
+
+/// C compatible version of [`Kernel`].
+#[repr(C)]
+#[derive(Debug)]
+pub struct blaze_kernel_meta_kernel {
+    /// The KASLR offset that was applied to the kernel image.
+    pub kaslr_offset: u64,
+    /// Unused member available for future expansion.
+    pub reserved: [u8; 8],
+}
+
+impl blaze_kernel_meta_kernel {
+    fn from(other: Kernel) -> ManuallyDrop<Self> {
+        let Kernel {
+            kaslr_offset,
+            _non_exhaustive: (),
+        } = other;
+
+        let slf = Self {
+            kaslr_offset,
+            reserved: [0u8; 8],
+        };
+        ManuallyDrop::new(slf)
+    }
+
+    fn free(self) {
+        let Self {
+            kaslr_offset: _,
+            reserved: _,
+        } = self;
+    }
+}
+
+
+/// C compatible version of [`KernelModule`].
+#[repr(C)]
+#[derive(Debug)]
+pub struct blaze_kernel_meta_module {
+    /// The name of the module. This member is always present.
+    pub name: *mut c_char,
+    /// The address at which the module is loaded.
+    pub addr: Addr,
+    /// Unused member available for future expansion.
+    pub reserved: [u8; 8],
+}
+
+impl blaze_kernel_meta_module {
+    fn from(other: KernelModule) -> ManuallyDrop<Self> {
+        let KernelModule {
+            name,
+            addr,
+            _non_exhaustive: (),
+        } = other;
+
+        let slf = Self {
+            name: CString::new(name)
+                .expect("encountered module name with NUL bytes")
+                .into_raw(),
+            addr,
+            reserved: [0u8; 8],
+        };
+        ManuallyDrop::new(slf)
+    }
+
+    unsafe fn free(self) {
+        let Self {
+            name,
+            addr: _,
+            reserved: _,
+        } = self;
+
+        let _name = unsafe { CString::from_raw(name) };
+    }
+}
+
+
+/// C compatible version of [`BpfProg`].
+#[repr(C)]
+#[derive(Debug)]
+pub struct blaze_kernel_meta_bpf_prog {
+    /// The name of the program, which may be empty. This member is
+    /// always present.
+    pub name: *mut c_char,
+    /// The program's tag, which is a hash over its instructions.
+    pub tag: [u8; 8],
+    /// The address at which the program is located.
+    pub addr: Addr,
+    /// Unused member available for future expansion.
+    pub reserved: [u8; 8],
+}
+
+impl blaze_kernel_meta_bpf_prog {
+    fn from(other: BpfProg) -> ManuallyDrop<Self> {
+        let BpfProg {
+            name,
+            tag,
+            addr,
+            _non_exhaustive: (),
+        } = other;
+
+        let slf = Self {
+            name: CString::new(name)
+                .expect("encountered BPF program name with NUL bytes")
+                .into_raw(),
+            tag,
+            addr,
+            reserved: [0u8; 8],
+        };
+        ManuallyDrop::new(slf)
+    }
+
+    unsafe fn free(self) {
+        let Self {
+            name,
+            tag: _,
+            addr: _,
+            reserved: _,
+        } = self;
+
+        let _name = unsafe { CString::from_raw(name) };
+    }
+}
+
+
+/// The actual variant data in [`blaze_kernel_meta`].
+#[repr(C)]
+pub union blaze_kernel_meta_variant {
+    /// Valid on [`blaze_kernel_meta_kind::BLAZE_KERNEL_META_KERNEL`].
+    pub kernel: ManuallyDrop<blaze_kernel_meta_kernel>,
+    /// Valid on [`blaze_kernel_meta_kind::BLAZE_KERNEL_META_MODULE`].
+    pub module: ManuallyDrop<blaze_kernel_meta_module>,
+    /// Valid on [`blaze_kernel_meta_kind::BLAZE_KERNEL_META_BPF_PROG`].
+    pub bpf_prog: ManuallyDrop<blaze_kernel_meta_bpf_prog>,
+    /// Valid on [`blaze_kernel_meta_kind::BLAZE_KERNEL_META_UNKNOWN`].
+    pub unknown: ManuallyDrop<blaze_user_meta_unknown>,
+}
+
+impl Debug for blaze_kernel_meta_variant {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        f.debug_struct(stringify!(blaze_kernel_meta_variant))
+            .finish()
+    }
+}
+
+
+/// C ABI compatible version of [`KernelMeta`].
+#[repr(C)]
+#[derive(Debug)]
+pub struct blaze_kernel_meta {
+    /// The variant kind that is present.
+    pub kind: blaze_kernel_meta_kind,
+    /// The actual variant with its data.
+    pub variant: blaze_kernel_meta_variant,
+}
+
+impl blaze_kernel_meta {
+    fn from(other: KernelMeta) -> ManuallyDrop<Self> {
+        let slf = match other {
+            KernelMeta::Kernel(kernel) => Self {
+                kind: blaze_kernel_meta_kind::BLAZE_KERNEL_META_KERNEL,
+                variant: blaze_kernel_meta_variant {
+                    kernel: blaze_kernel_meta_kernel::from(kernel),
+                },
+            },
+            KernelMeta::Module(module) => Self {
+                kind: blaze_kernel_meta_kind::BLAZE_KERNEL_META_MODULE,
+                variant: blaze_kernel_meta_variant {
+                    module: blaze_kernel_meta_module::from(module),
+                },
+            },
+            KernelMeta::BpfProg(bpf_prog) => Self {
+                kind: blaze_kernel_meta_kind::BLAZE_KERNEL_META_BPF_PROG,
+                variant: blaze_kernel_meta_variant {
+                    bpf_prog: blaze_kernel_meta_bpf_prog::from(bpf_prog),
+                },
+            },
+            KernelMeta::Unknown(unknown) => Self {
+                kind: blaze_kernel_meta_kind::BLAZE_KERNEL_META_UNKNOWN,
+                variant: blaze_kernel_meta_variant {
+                    unknown: blaze_user_meta_unknown::from(unknown),
+                },
+            },
+            _ => unreachable!(),
+        };
+        ManuallyDrop::new(slf)
+    }
+
+    unsafe fn free(self) {
+        match self.kind {
+            blaze_kernel_meta_kind::BLAZE_KERNEL_META_KERNEL => {
+                ManuallyDrop::into_inner(unsafe { self.variant.kernel }).free()
+            }
+            blaze_kernel_meta_kind::BLAZE_KERNEL_META_MODULE => unsafe {
+                ManuallyDrop::into_inner(self.variant.module).free()
+            },
+            blaze_kernel_meta_kind::BLAZE_KERNEL_META_BPF_PROG => unsafe {
+                ManuallyDrop::into_inner(self.variant.bpf_prog).free()
+            },
+            blaze_kernel_meta_kind::BLAZE_KERNEL_META_UNKNOWN => {
+                ManuallyDrop::into_inner(unsafe { self.variant.unknown }).free()
+            }
+        }
+    }
+}
+
+
+/// An object representing normalized kernel addresses.
+///
+/// C ABI compatible version of [`KernelOutput`].
+#[repr(C)]
+#[derive(Debug)]
+pub struct blaze_normalized_kernel_output {
+    /// The number of [`blaze_kernel_meta`] objects present in `metas`.
+    pub meta_cnt: usize,
+    /// An array of `meta_cnt` objects.
+    pub metas: *mut blaze_kernel_meta,
+    /// The number of [`blaze_normalized_output`] objects present in `outputs`.
+    pub output_cnt: usize,
+    /// An array of `output_cnt` objects.
+    pub outputs: *mut blaze_normalized_output,
+    /// Unused member available for future expansion.
+    pub reserved: [u8; 8],
+}
+
+impl blaze_normalized_kernel_output {
+    fn from(other: KernelOutput) -> ManuallyDrop<Self> {
+        let slf = Self {
+            meta_cnt: other.meta.len(),
+            metas: unsafe {
+                Box::into_raw(
+                    other
+                        .meta
+                        .into_iter()
+                        .map(blaze_kernel_meta::from)
+                        .map(ManuallyDrop::into_inner)
+                        .collect::<Vec<_>>()
+                        .into_boxed_slice(),
+                )
+                .as_mut()
+                .unwrap()
+                .as_mut_ptr()
+            },
+            output_cnt: other.outputs.len(),
+            outputs: unsafe {
+                Box::into_raw(
+                    other
+                        .outputs
+                        .into_iter()
+                        .map(blaze_normalized_output::from)
+                        .collect::<Vec<_>>()
+                        .into_boxed_slice(),
+                )
+                .as_mut()
+                .unwrap()
+                .as_mut_ptr()
+            },
+            reserved: [0u8; 8],
+        };
+        ManuallyDrop::new(slf)
+    }
+}
+
+
+/// Normalize a list of kernel addresses.
+///
+/// C ABI compatible version of [`Normalizer::normalize_kernel_addrs`].
+///
+/// Addresses that do not belong to the core kernel image, a kernel
+/// module, or a BPF program are reported as
+/// [`blaze_kernel_meta_kind::BLAZE_KERNEL_META_UNKNOWN`].
+///
+/// On success, the function creates a new [`blaze_normalized_kernel_output`]
+/// object and returns it. The resulting object should be released using
+/// [`blaze_kernel_output_free`] once it is no longer needed.
+///
+/// On error, the function returns `NULL` and sets the thread's last error to
+/// indicate the problem encountered. Use [`blaze_err_last`] to retrieve this
+/// error.
+///
+/// # Safety
+/// - `addrs` needs to be a valid pointer to `addr_cnt` addresses
+#[no_mangle]
+pub unsafe extern "C" fn blaze_normalize_kernel_addrs(
+    normalizer: *const blaze_normalizer,
+    addrs: *const Addr,
+    addr_cnt: usize,
+) -> *mut blaze_normalized_kernel_output {
+    // SAFETY: The caller needs to ensure that `normalizer` is a valid
+    //         pointer.
+    let normalizer = unsafe { &*normalizer };
+    // SAFETY: The caller needs to ensure that `addrs` is a valid pointer and
+    //         that it points to `addr_cnt` elements.
+    let addrs = unsafe { slice_from_user_array(addrs, addr_cnt) };
+    let result = normalizer.normalize_kernel_addrs(&addrs);
+    match result {
+        Ok(output) => {
+            let output_box = Box::new(ManuallyDrop::into_inner(
+                blaze_normalized_kernel_output::from(output),
+            ));
+            let () = set_last_err(blaze_err::BLAZE_ERR_OK);
+            Box::into_raw(output_box)
+        }
+        Err(err) => {
+            let () = set_last_err_from(&err);
+            ptr::null_mut()
+        }
+    }
+}
+
+
+/// Free an object as returned by [`blaze_normalize_kernel_addrs`].
+///
+/// # Safety
+/// The provided object should have been created by
+/// [`blaze_normalize_kernel_addrs`].
+#[no_mangle]
+pub unsafe extern "C" fn blaze_kernel_output_free(output: *mut blaze_normalized_kernel_output) {
+    if output.is_null() {
+        return
+    }
+
+    // SAFETY: The caller should make sure that `output` was created by one of
+    //         our blessed functions.
+    let kernel_output = unsafe { Box::from_raw(output) };
+    let metas = unsafe {
+        Box::<[blaze_kernel_meta]>::from_raw(slice::from_raw_parts_mut(
+            kernel_output.metas,
+            kernel_output.meta_cnt,
+        ))
+    }
+    .into_vec();
+    let _norm_addrs = unsafe {
+        Box::<[blaze_normalized_output]>::from_raw(slice::from_raw_parts_mut(
+            kernel_output.outputs,
+            kernel_output.output_cnt,
+        ))
+    }
+    .into_vec();
+
+    for meta in metas {
+        let () = unsafe { meta.free() };
+    }
+}
+
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -754,7 +1190,11 @@ mod tests {
         assert_eq!(size_of::<blaze_normalize_opts>(), 16);
         assert_eq!(size_of::<blaze_user_meta_apk>(), 16);
         assert_eq!(size_of::<blaze_user_meta_elf>(), 32);
+        assert_eq!(size_of::<blaze_user_meta_shared_mem>(), 32);
         assert_eq!(size_of::<blaze_user_meta_unknown>(), 8);
+        assert_eq!(size_of::<blaze_kernel_meta_kernel>(), 16);
+        assert_eq!(size_of::<blaze_kernel_meta_module>(), 24);
+        assert_eq!(size_of::<blaze_kernel_meta_bpf_prog>(), 32);
     }
 
     /// Exercise the `Debug` representation of various types.
@@ -827,6 +1267,42 @@ mod tests {
             format!("{user_addrs:?}"),
             "blaze_normalized_user_output { meta_cnt: 0, metas: 0x0, output_cnt: 0, outputs: 0x0, reserved: [0, 0, 0, 0, 0, 0, 0, 0] }",
         );
+
+        let module = blaze_kernel_meta_module {
+            name: ptr::null_mut(),
+            addr: 0xffffffffc0000000,
+            reserved: [0u8; 8],
+        };
+        assert_eq!(
+            format!("{module:?}"),
+            "blaze_kernel_meta_module { name: 0x0, addr: 18446744072635809792, reserved: [0, 0, 0, 0, 0, 0, 0, 0] }",
+        );
+
+        let meta = blaze_kernel_meta {
+            kind: blaze_kernel_meta_kind::BLAZE_KERNEL_META_KERNEL,
+            variant: blaze_kernel_meta_variant {
+                kernel: ManuallyDrop::new(blaze_kernel_meta_kernel {
+                    kaslr_offset: 0,
+                    reserved: [0u8; 8],
+                }),
+            },
+        };
+        assert_eq!(
+            format!("{meta:?}"),
+            "blaze_kernel_meta { kind: BLAZE_KERNEL_META_KERNEL, variant: blaze_kernel_meta_variant }",
+        );
+
+        let kernel_addrs = blaze_normalized_kernel_output {
+            meta_cnt: 0,
+            metas: ptr::null_mut(),
+            output_cnt: 0,
+            outputs: ptr::null_mut(),
+            reserved: [0u8; 8],
+        };
+        assert_eq!(
+            format!("{kernel_addrs:?}"),
+            "blaze_normalized_kernel_output { meta_cnt: 0, metas: 0x0, output_cnt: 0, outputs: 0x0, reserved: [0, 0, 0, 0, 0, 0, 0, 0] }",
+        );
     }
 
     /// Make sure that we can stringify normalization reasons as expected.
@@ -852,6 +1328,33 @@ mod tests {
         }
     }
 
+    /// Make sure that enum values exposed via the C API are stable.
+    #[tag(miri)]
+    #[test]
+    fn stable_tags() {
+        assert_eq!(
+            blaze_normalize_reason::BLAZE_NORMALIZE_REASON_UNMAPPED as u8,
+            0
+        );
+        assert_eq!(
+            blaze_normalize_reason::BLAZE_NORMALIZE_REASON_MISSING_COMPONENT as u8,
+            1
+        );
+        assert_eq!(
+            blaze_normalize_reason::BLAZE_NORMALIZE_REASON_UNSUPPORTED as u8,
+            2
+        );
+
+        assert_eq!(blaze_user_meta_kind::BLAZE_USER_META_UNKNOWN as u32, 0);
+        assert_eq!(blaze_user_meta_kind::BLAZE_USER_META_APK as u32, 1);
+        assert_eq!(blaze_user_meta_kind::BLAZE_USER_META_ELF as u32, 2);
+
+        assert_eq!(blaze_kernel_meta_kind::BLAZE_KERNEL_META_UNKNOWN as u32, 0);
+        assert_eq!(blaze_kernel_meta_kind::BLAZE_KERNEL_META_KERNEL as u32, 1);
+        assert_eq!(blaze_kernel_meta_kind::BLAZE_KERNEL_META_MODULE as u32, 2);
+        assert_eq!(blaze_kernel_meta_kind::BLAZE_KERNEL_META_BPF_PROG as u32, 3);
+    }
+
     /// Check that we can convert an [`Unknown`] into a
     /// [`blaze_user_meta_unknown`] and back.
     #[tag(miri)]
@@ -907,6 +1410,59 @@ mod tests {
         let () = unsafe { ManuallyDrop::into_inner(meta_c).free() };
     }
 
+    /// Check that we can convert a [`SharedMem`] into a
+    /// [`blaze_user_meta_shared_mem`] and back.
+    #[tag(miri)]
+    #[test]
+    fn shared_mem_conversion() {
+        let shared_mem = SharedMem {
+            path: PathBuf::from("/dev/shm/jit"),
+            addr: 0x7f2d3cc00000,
+            offset: 0x200000,
+            _non_exhaustive: (),
+        };
+
+        let shared_mem_c = blaze_user_meta_shared_mem::from(shared_mem.clone());
+        let () = unsafe { ManuallyDrop::into_inner(shared_mem_c).free() };
+
+        let meta = UserMeta::SharedMem(shared_mem);
+        let meta_c = blaze_user_meta::from(meta);
+        let () = unsafe { ManuallyDrop::into_inner(meta_c).free() };
+    }
+
+    /// Check that we can convert the various [`KernelMeta`] variants into
+    /// [`blaze_kernel_meta`] objects and back.
+    #[tag(miri)]
+    #[test]
+    fn kernel_meta_conversion() {
+        let metas = [
+            KernelMeta::Kernel(Kernel {
+                kaslr_offset: 0x1c000000,
+                _non_exhaustive: (),
+            }),
+            KernelMeta::Module(KernelModule {
+                name: "ext4".to_string(),
+                addr: 0xffffffffc0000000,
+                _non_exhaustive: (),
+            }),
+            KernelMeta::BpfProg(BpfProg {
+                name: "sd_devices".to_string(),
+                tag: [0x6d, 0xee, 0xf7, 0x35, 0x7e, 0x7b, 0x45, 0x30],
+                addr: 0xffffffffa0001000,
+                _non_exhaustive: (),
+            }),
+            KernelMeta::Unknown(Unknown {
+                reason: Reason::Unmapped,
+                _non_exhaustive: (),
+            }),
+        ];
+
+        for meta in metas {
+            let meta_c = blaze_kernel_meta::from(meta);
+            let () = unsafe { ManuallyDrop::into_inner(meta_c).free() };
+        }
+    }
+
     /// Make sure that we can create and free a normalizer instance.
     #[tag(miri)]
     #[test]
@@ -1052,6 +1608,35 @@ mod tests {
         let () = unsafe { blaze_normalizer_free(normalizer) };
     }
 
+    /// Check that we can normalize kernel addresses.
+    #[test]
+    fn normalize_kernel_addrs() {
+        let addrs = [0x1337 as Addr, 0xffffffff81000000];
+
+        let normalizer = blaze_normalizer_new();
+        assert_ne!(normalizer, ptr::null_mut());
+
+        let result = unsafe {
+            blaze_normalize_kernel_addrs(normalizer, addrs.as_slice().as_ptr(), addrs.len())
+        };
+        assert_ne!(result, ptr::null_mut());
+
+        let kernel_addrs = unsafe { &*result };
+        assert_eq!(kernel_addrs.output_cnt, 2);
+
+        let output = unsafe { &*kernel_addrs.outputs.add(0) };
+        assert_eq!(output.output, 0x1337);
+        let meta = unsafe { &*kernel_addrs.metas.add(output.meta_idx) };
+        assert_eq!(meta.kind, blaze_kernel_meta_kind::BLAZE_KERNEL_META_UNKNOWN);
+        assert_eq!(
+            unsafe { meta.variant.unknown.reason },
+            blaze_normalize_reason::BLAZE_NORMALIZE_REASON_UNMAPPED
+        );
+
+        let () = unsafe { blaze_kernel_output_free(result) };
+        let () = unsafe { blaze_normalizer_free(normalizer) };
+    }
+
     /// Check that we can enable/disable the reading of build IDs.
     #[test]
     fn normalize_build_id_reading() {
@@ -33,6 +33,7 @@ use crate::blaze_err;
 #[cfg(doc)]
 use crate::blaze_err_last;
 use crate::set_last_err;
+use crate::set_last_err_from;
 use crate::util::slice_from_aligned_user_array;
 use crate::util::slice_from_user_array;
 
@@ -149,6 +150,8 @@ impl From<blaze_symbolize_src_elf> for Elf {
         Self {
             path: unsafe { from_cstr(path) },
             debug_syms,
+            base_addr: None,
+            bolt_original: None,
             _non_exhaustive: (),
         }
     }
@@ -307,6 +310,7 @@ impl From<blaze_symbolize_src_process> for Process {
             debug_syms,
             perf_map,
             map_files,
+            proc_root: true,
             _non_exhaustive: (),
         }
     }
@@ -429,6 +433,19 @@ pub enum blaze_symbolize_reason {
     BLAZE_SYMBOLIZE_REASON_UNKNOWN_ADDR,
     /// The address belonged to an entity that is currently unsupported.
     BLAZE_SYMBOLIZE_REASON_UNSUPPORTED,
+    /// The file containing symbolization data for the address could not
+    /// be found.
+    BLAZE_SYMBOLIZE_REASON_FILE_NOT_FOUND,
+    /// Permission to access the file containing symbolization data for
+    /// the address was denied.
+    BLAZE_SYMBOLIZE_REASON_PERMISSION_DENIED,
+    /// The address belongs to a thread's stack (`[stack]`).
+    BLAZE_SYMBOLIZE_REASON_STACK,
+    /// The address belongs to the process' heap (`[heap]`).
+    BLAZE_SYMBOLIZE_REASON_HEAP,
+    /// The address belongs to a special mapping set up by the kernel
+    /// (e.g., `[vvar]`) that does not contain symbolizable code.
+    BLAZE_SYMBOLIZE_REASON_SPECIAL_MAPPING,
 }
 
 impl From<Reason> for blaze_symbolize_reason {
@@ -442,6 +459,11 @@ impl From<Reason> for blaze_symbolize_reason {
             Reason::MissingSyms => BLAZE_SYMBOLIZE_REASON_MISSING_SYMS,
             Reason::Unsupported => BLAZE_SYMBOLIZE_REASON_UNSUPPORTED,
             Reason::UnknownAddr => BLAZE_SYMBOLIZE_REASON_UNKNOWN_ADDR,
+            Reason::FileNotFound => BLAZE_SYMBOLIZE_REASON_FILE_NOT_FOUND,
+            Reason::PermissionDenied => BLAZE_SYMBOLIZE_REASON_PERMISSION_DENIED,
+            Reason::Stack => BLAZE_SYMBOLIZE_REASON_STACK,
+            Reason::Heap => BLAZE_SYMBOLIZE_REASON_HEAP,
+            Reason::SpecialMapping => BLAZE_SYMBOLIZE_REASON_SPECIAL_MAPPING,
             _ => unreachable!(),
         }
     }
@@ -474,6 +496,17 @@ pub extern "C" fn blaze_symbolize_reason_str(err: blaze_symbolize_reason) -> *co
         e if e == BLAZE_SYMBOLIZE_REASON_UNSUPPORTED as i32 => {
             Reason::Unsupported.as_bytes().as_ptr().cast()
         }
+        e if e == BLAZE_SYMBOLIZE_REASON_FILE_NOT_FOUND as i32 => {
+            Reason::FileNotFound.as_bytes().as_ptr().cast()
+        }
+        e if e == BLAZE_SYMBOLIZE_REASON_PERMISSION_DENIED as i32 => {
+            Reason::PermissionDenied.as_bytes().as_ptr().cast()
+        }
+        e if e == BLAZE_SYMBOLIZE_REASON_STACK as i32 => Reason::Stack.as_bytes().as_ptr().cast(),
+        e if e == BLAZE_SYMBOLIZE_REASON_HEAP as i32 => Reason::Heap.as_bytes().as_ptr().cast(),
+        e if e == BLAZE_SYMBOLIZE_REASON_SPECIAL_MAPPING as i32 => {
+            Reason::SpecialMapping.as_bytes().as_ptr().cast()
+        }
         _ => b"unknown reason\0".as_ptr().cast(),
     }
 }
@@ -527,6 +560,12 @@ pub struct blaze_sym {
     /// If an address could not be symbolized, this member will be NULL.
     /// Check the `reason` member for additional information pertaining
     /// the failure.
+    ///
+    /// If the address could not be attributed to a symbol, but source
+    /// code information for it is available, this member will be NULL
+    /// as well, but `reason` will be
+    /// [`blaze_symbolize_reason::BLAZE_SYMBOLIZE_REASON_SUCCESS`] and
+    /// `code_info` will be populated.
     pub name: *const c_char,
     /// The address at which the symbol is located (i.e., its "start").
     ///
@@ -557,8 +596,8 @@ pub struct blaze_sym {
     pub inlined_cnt: usize,
     /// An array of `inlined_cnt` symbolized inlined function calls.
     pub inlined: *const blaze_symbolize_inlined_fn,
-    /// On error (i.e., if `name` is NULL), a reason trying to explain
-    /// why symbolization failed.
+    /// On error (i.e., if `name` is NULL and `code_info.file` is NULL as
+    /// well), a reason trying to explain why symbolization failed.
     pub reason: blaze_symbolize_reason,
     /// Unused member available for future expansion.
     pub reserved: [u8; 7],
@@ -630,9 +669,17 @@ pub struct blaze_symbolizer_opts {
     /// languages are Rust and C++ and the flag will have no effect if
     /// the underlying language does not mangle symbols (such as C).
     pub demangle: bool,
+    /// Whether to report paths (such as `code_info.dir` and
+    /// `code_info.file`) lossily converted to valid UTF-8.
+    ///
+    /// By default, paths are reported as the raw bytes found in the
+    /// symbolization source, which may not be valid UTF-8. If set,
+    /// invalid sequences are replaced with `U+FFFD REPLACEMENT
+    /// CHARACTER`.
+    pub lossy_paths: bool,
     /// Unused member available for future expansion. Must be initialized
     /// to zero.
-    pub reserved: [u8; 4],
+    pub reserved: [u8; 3],
 }
 
 impl Default for blaze_symbolizer_opts {
@@ -645,7 +692,8 @@ impl Default for blaze_symbolizer_opts {
             code_info: false,
             inlined_fns: false,
             demangle: false,
-            reserved: [0; 4],
+            lossy_paths: false,
+            reserved: [0; 3],
         }
     }
 }
@@ -701,6 +749,7 @@ pub unsafe extern "C" fn blaze_symbolizer_new_opts(
         code_info,
         inlined_fns,
         demangle,
+        lossy_paths,
         reserved: _,
     } = opts;
 
@@ -708,7 +757,8 @@ pub unsafe extern "C" fn blaze_symbolizer_new_opts(
         .enable_auto_reload(auto_reload)
         .enable_code_info(code_info)
         .enable_inlined_fns(inlined_fns)
-        .enable_demangling(demangle);
+        .enable_demangling(demangle)
+        .enable_lossy_paths(lossy_paths);
 
     let builder = if debug_dirs.is_null() {
         builder
@@ -783,31 +833,27 @@ pub unsafe extern "C" fn blaze_symbolize_cache_process(
     // SAFETY: The caller ensures that the pointer is valid.
     let symbolizer = unsafe { &*symbolizer };
     let result = symbolizer.cache(&cache);
-    let err = result
-        .map(|()| blaze_err::BLAZE_ERR_OK)
-        .unwrap_or_else(|err| err.kind().into());
-    let () = set_last_err(err);
+    let () = match result {
+        Ok(()) => set_last_err(blaze_err::BLAZE_ERR_OK),
+        Err(err) => set_last_err_from(&err),
+    };
 }
 
-fn code_info_strtab_size(code_info: &Option<CodeInfo>) -> usize {
+fn code_info_strtab_size(code_info: Option<&CodeInfo>) -> usize {
     code_info
-        .as_ref()
         .and_then(|info| info.dir.as_ref().map(|d| d.as_os_str().len() + 1))
         .unwrap_or(0)
-        + code_info
-            .as_ref()
-            .map(|info| info.file.len() + 1)
-            .unwrap_or(0)
+        + code_info.map(|info| info.file.len() + 1).unwrap_or(0)
 }
 
 fn inlined_fn_strtab_size(inlined_fn: &InlinedFn) -> usize {
-    inlined_fn.name.len() + 1 + code_info_strtab_size(&inlined_fn.code_info)
+    inlined_fn.name.len() + 1 + code_info_strtab_size(inlined_fn.code_info.as_ref())
 }
 
 fn sym_strtab_size(sym: &Sym) -> usize {
     sym.name.len()
         + 1
-        + code_info_strtab_size(&sym.code_info)
+        + code_info_strtab_size(sym.code_info.as_ref())
         + sym
             .inlined
             .iter()
@@ -816,26 +862,18 @@ fn sym_strtab_size(sym: &Sym) -> usize {
 }
 
 fn convert_code_info(
-    code_info_in: &Option<CodeInfo>,
+    code_info_in: Option<&CodeInfo>,
     code_info_out: &mut blaze_symbolize_code_info,
     mut make_cstr: impl FnMut(&OsStr) -> *mut c_char,
 ) {
     code_info_out.dir = code_info_in
-        .as_ref()
         .and_then(|info| info.dir.as_ref().map(|d| make_cstr(d.as_os_str())))
         .unwrap_or_else(ptr::null_mut);
     code_info_out.file = code_info_in
-        .as_ref()
         .map(|info| make_cstr(&info.file))
         .unwrap_or_else(ptr::null_mut);
-    code_info_out.line = code_info_in
-        .as_ref()
-        .and_then(|info| info.line)
-        .unwrap_or(0);
-    code_info_out.column = code_info_in
-        .as_ref()
-        .and_then(|info| info.column)
-        .unwrap_or(0);
+    code_info_out.line = code_info_in.and_then(|info| info.line).unwrap_or(0);
+    code_info_out.column = code_info_in.and_then(|info| info.column).unwrap_or(0);
 }
 
 /// Convert [`Sym`] objects to [`blaze_syms`] ones.
@@ -847,6 +885,7 @@ fn convert_symbolizedresults_to_c(results: Vec<Symbolized>) -> *const blaze_syms
     // blaze_sym, and C strings of symbol and path.
     let (strtab_size, inlined_fn_cnt) = results.iter().fold((0, 0), |acc, sym| match sym {
         Symbolized::Sym(sym) => (acc.0 + sym_strtab_size(sym), acc.1 + sym.inlined.len()),
+        Symbolized::Code(code_info) => (acc.0 + code_info_strtab_size(Some(code_info)), acc.1),
         Symbolized::Unknown(..) => acc,
     });
 
@@ -903,7 +942,11 @@ fn convert_symbolizedresults_to_c(results: Vec<Symbolized>) -> *const blaze_syms
                     .size
                     .map(|size| isize::try_from(size).unwrap_or(isize::MAX))
                     .unwrap_or(-1);
-                convert_code_info(&sym.code_info, &mut sym_ref.code_info, &mut make_cstr);
+                convert_code_info(
+                    sym.code_info.as_ref(),
+                    &mut sym_ref.code_info,
+                    &mut make_cstr,
+                );
                 sym_ref.inlined_cnt = sym.inlined.len();
                 sym_ref.inlined = inlined_last;
                 sym_ref.reason = blaze_symbolize_reason::BLAZE_SYMBOLIZE_REASON_SUCCESS;
@@ -914,7 +957,7 @@ fn convert_symbolizedresults_to_c(results: Vec<Symbolized>) -> *const blaze_syms
                     let name_ptr = make_cstr(OsStr::new(inlined.name.as_ref()));
                     inlined_ref.name = name_ptr;
                     convert_code_info(
-                        &inlined.code_info,
+                        inlined.code_info.as_ref(),
                         &mut inlined_ref.code_info,
                         &mut make_cstr,
                     );
@@ -922,6 +965,17 @@ fn convert_symbolizedresults_to_c(results: Vec<Symbolized>) -> *const blaze_syms
                     inlined_last = unsafe { inlined_last.add(1) };
                 }
             }
+            Symbolized::Code(code_info) => {
+                // Addresses not attributed to a symbol but with source
+                // code information available have all fields set to
+                // zero, except for the code information.
+                // SAFETY: `syms_last` is pointing to a writable and properly
+                //         aligned `blaze_sym` object.
+                let () = unsafe { syms_last.write_bytes(0, 1) };
+                let sym_ref = unsafe { &mut *syms_last };
+                convert_code_info(Some(&code_info), &mut sym_ref.code_info, &mut make_cstr);
+                sym_ref.reason = blaze_symbolize_reason::BLAZE_SYMBOLIZE_REASON_SUCCESS;
+            }
             Symbolized::Unknown(reason) => {
                 // Unknown symbols/addresses are just represented with all
                 // fields set to zero (except for reason).
@@ -973,7 +1027,7 @@ unsafe fn blaze_symbolize_impl(
             result
         }
         Err(err) => {
-            let () = set_last_err(err.kind().into());
+            let () = set_last_err_from(&err);
             ptr::null_mut()
         }
     }
@@ -1365,7 +1419,7 @@ mod tests {
         };
         assert_eq!(
             format!("{opts:?}"),
-            "blaze_symbolizer_opts { type_size: 16, debug_dirs: 0x0, debug_dirs_len: 0, auto_reload: false, code_info: false, inlined_fns: false, demangle: true, reserved: [0, 0, 0, 0] }"
+            "blaze_symbolizer_opts { type_size: 16, debug_dirs: 0x0, debug_dirs_len: 0, auto_reload: false, code_info: false, inlined_fns: false, demangle: true, lossy_paths: false, reserved: [0, 0, 0] }"
         );
     }
 
@@ -1388,6 +1442,17 @@ mod tests {
             (Reason::MissingSyms, BLAZE_SYMBOLIZE_REASON_MISSING_SYMS),
             (Reason::Unsupported, BLAZE_SYMBOLIZE_REASON_UNSUPPORTED),
             (Reason::UnknownAddr, BLAZE_SYMBOLIZE_REASON_UNKNOWN_ADDR),
+            (Reason::FileNotFound, BLAZE_SYMBOLIZE_REASON_FILE_NOT_FOUND),
+            (
+                Reason::PermissionDenied,
+                BLAZE_SYMBOLIZE_REASON_PERMISSION_DENIED,
+            ),
+            (Reason::Stack, BLAZE_SYMBOLIZE_REASON_STACK),
+            (Reason::Heap, BLAZE_SYMBOLIZE_REASON_HEAP),
+            (
+                Reason::SpecialMapping,
+                BLAZE_SYMBOLIZE_REASON_SPECIAL_MAPPING,
+            ),
         ];
 
         for (reason, expected) in data {
@@ -1551,6 +1616,13 @@ mod tests {
                 _non_exhaustive: (),
             }]
             .into_boxed_slice(),
+            inlined_truncated: false,
+            section: None,
+            perm: None,
+            addr_adjust: 0,
+            module: None,
+            module_base: None,
+            file_offset: None,
             _non_exhaustive: (),
         })];
         let syms = convert_symbolizedresults_to_c(results);
@@ -1573,6 +1645,13 @@ mod tests {
                     _non_exhaustive: (),
                 }]
                 .into_boxed_slice(),
+                inlined_truncated: false,
+                section: None,
+                perm: None,
+                addr_adjust: 0,
+                module: None,
+                module_base: None,
+                file_offset: None,
                 _non_exhaustive: (),
             }),
             Symbolized::Unknown(Reason::InvalidFileOffset),
@@ -1581,6 +1660,30 @@ mod tests {
         assert!(!syms.is_null());
         let () = touch_syms(syms);
         let () = unsafe { blaze_syms_free(syms) };
+
+        // Source code information without a symbol.
+        let results = vec![Symbolized::Code(CodeInfo {
+            dir: Some(Path::new("/some/dir").into()),
+            file: OsStr::new("a-file").into(),
+            line: Some(42),
+            column: None,
+            _non_exhaustive: (),
+        })];
+        let syms = convert_symbolizedresults_to_c(results);
+        assert!(!syms.is_null());
+        let () = touch_syms(syms);
+
+        let sym = unsafe { &*(*syms).syms.as_ptr() };
+        assert!(sym.name.is_null());
+        assert_eq!(
+            sym.reason,
+            blaze_symbolize_reason::BLAZE_SYMBOLIZE_REASON_SUCCESS
+        );
+        let file = unsafe { CStr::from_ptr(sym.code_info.file) };
+        assert_eq!(file.to_bytes(), b"a-file");
+        assert_eq!(sym.code_info.line, 42);
+        assert_eq!(sym.inlined_cnt, 0);
+        let () = unsafe { blaze_syms_free(syms) };
     }
 
     /// Make sure that we can create and free a symbolizer instance.
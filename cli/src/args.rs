@@ -27,7 +27,7 @@ fn parse_pid(s: &str) -> Result<Pid> {
 }
 
 /// Parse an address from a string.
-fn parse_addr(s: &str) -> Result<Addr> {
+pub fn parse_addr(s: &str) -> Result<Addr> {
     // In our world addresses are always represented in hex, with or without 0x
     // prefix.
     Addr::from_str_radix(s.trim_start_matches("0x"), 16)
@@ -44,6 +44,9 @@ pub struct Args {
     /// Increase verbosity (can be supplied multiple times).
     #[arg(short = 'v', long = "verbose", global = true, action = ArgAction::Count)]
     pub verbosity: u8,
+    /// Print results as JSON, one object per line.
+    #[arg(long, global = true)]
+    pub json: bool,
 }
 
 
@@ -83,6 +86,8 @@ pub mod inspect {
         Breakpad(BreakpadLookup),
         /// Lookup symbols in a ELF file by name.
         Elf(ElfLookup),
+        /// Lookup symbols in a Gsym file by name.
+        Gsym(GsymLookup),
     }
 
     /// A type representing the `inspect dump` sub-command.
@@ -92,6 +97,8 @@ pub mod inspect {
         Breakpad(BreakpadDump),
         /// Dump all symbols in an ELF file.
         Elf(ElfDump),
+        /// Dump all symbols in a Gsym file.
+        Gsym(GsymDump),
     }
 
     /// A type representing the `inspect buildid` sub-command.
@@ -135,6 +142,22 @@ pub mod inspect {
         #[arg(long)]
         pub no_debug_syms: bool,
     }
+
+    #[derive(Debug, Arguments)]
+    pub struct GsymLookup {
+        /// The path to the Gsym file.
+        #[arg(short, long)]
+        pub path: PathBuf,
+        /// A list of names of symbols.
+        pub names: Vec<String>,
+    }
+
+    #[derive(Debug, Arguments)]
+    pub struct GsymDump {
+        /// The path to the Gsym file.
+        #[arg(short, long)]
+        pub path: PathBuf,
+    }
 }
 
 
@@ -147,6 +170,8 @@ pub mod normalize {
     pub enum Normalize {
         /// Normalize user space addresses.
         User(User),
+        /// Normalize kernel addresses.
+        Kernel(Kernel),
     }
 
     #[derive(Debug, Arguments)]
@@ -155,6 +180,9 @@ pub mod normalize {
         #[arg(short, long, value_parser = parse_pid)]
         pub pid: Pid,
         /// The addresses to normalize.
+        ///
+        /// If no addresses are provided, they are read from standard
+        /// input.
         #[arg(value_parser = parse_addr)]
         pub addrs: Vec<Addr>,
         /// Disable the reading of build IDs of the corresponding binaries.
@@ -169,6 +197,16 @@ pub mod normalize {
         #[arg(long)]
         pub procmap_query: bool,
     }
+
+    #[derive(Debug, Arguments)]
+    pub struct Kernel {
+        /// The addresses to normalize.
+        ///
+        /// If no addresses are provided, they are read from standard
+        /// input.
+        #[arg(value_parser = parse_addr)]
+        pub addrs: Vec<Addr>,
+    }
 }
 
 
@@ -194,7 +232,8 @@ pub mod symbolize {
         /// The addresses to symbolize.
         ///
         /// Addresses are assumed to be file offsets as they would be used on
-        /// the original (ELF/DWARF/...) source file.
+        /// the original (ELF/DWARF/...) source file. If no addresses are
+        /// provided, they are read from standard input.
         #[arg(value_parser = parse_addr)]
         pub addrs: Vec<Addr>,
     }
@@ -222,7 +261,8 @@ pub mod symbolize {
         ///
         /// Addresses are assumed to already be normalized to the file
         /// itself (i.e., with relocation and address randomization effects
-        /// removed).
+        /// removed). If no addresses are provided, they are read from
+        /// standard input.
         #[arg(value_parser = parse_addr)]
         pub addrs: Vec<Addr>,
     }
@@ -236,7 +276,8 @@ pub mod symbolize {
         ///
         /// Addresses are assumed to already be normalized to the file
         /// itself (i.e., with relocation and address randomization effects
-        /// removed).
+        /// removed). If no addresses are provided, they are read from
+        /// standard input.
         #[arg(value_parser = parse_addr)]
         pub addrs: Vec<Addr>,
     }
@@ -247,12 +288,19 @@ pub mod symbolize {
         #[arg(short, long, value_parser = parse_pid)]
         pub pid: Pid,
         /// The addresses to symbolize.
+        ///
+        /// If no addresses are provided, they are read from standard
+        /// input.
         #[arg(value_parser = parse_addr)]
         pub addrs: Vec<Addr>,
         /// Disable the use of `/proc/<pid>/map_files/` entries and use
         /// symbolic paths instead.
         #[arg(long)]
         pub no_map_files: bool,
+        /// Disable the resolution of symbolic paths relative to
+        /// `/proc/<pid>/root/`.
+        #[arg(long)]
+        pub no_proc_root: bool,
     }
 
     #[derive(Debug, Arguments)]
@@ -269,6 +317,9 @@ pub mod symbolize {
         #[arg(long, value_hint = ValueHint::FilePath)]
         pub vmlinux: Option<OsString>,
         /// The addresses to symbolize.
+        ///
+        /// If no addresses are provided, they are read from standard
+        /// input.
         #[arg(value_parser = parse_addr)]
         pub addrs: Vec<Addr>,
     }
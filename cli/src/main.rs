@@ -5,6 +5,8 @@ mod trace;
 
 use std::cmp::max;
 use std::io::stderr;
+use std::io::stdin;
+use std::io::Read as _;
 use std::ops::ControlFlow;
 
 use anyhow::Context;
@@ -24,6 +26,8 @@ use blazesym::SymType;
 
 use clap::Parser as _;
 
+use serde_json::json;
+
 use tracing::subscriber::set_global_default as set_global_subscriber;
 use tracing::Level;
 use tracing_subscriber::fmt::time::SystemTime;
@@ -33,6 +37,10 @@ use tracing_subscriber::Registry;
 const ADDR_WIDTH: usize = 16;
 
 
+/// A function creating the symbolization input for a list of addresses.
+type MakeInput = fn(&[Addr]) -> symbolize::Input<&[Addr]>;
+
+
 fn format_build_id_bytes(build_id: &[u8]) -> String {
     build_id
         .iter()
@@ -51,7 +59,29 @@ fn format_build_id(build_id: Option<&[u8]>) -> String {
 }
 
 
-fn print_sym_infos(sym_infos: &[inspect::SymInfo]) {
+/// Retrieve the addresses to work with, reading them from standard
+/// input if none were provided on the command line.
+fn read_addrs(addrs: Vec<Addr>) -> Result<Vec<Addr>> {
+    if !addrs.is_empty() {
+        return Ok(addrs)
+    }
+
+    let mut input = String::new();
+    let _count = stdin()
+        .read_to_string(&mut input)
+        .context("failed to read addresses from stdin")?;
+    input.split_whitespace().map(args::parse_addr).collect()
+}
+
+
+fn print_sym_infos(sym_infos: &[inspect::SymInfo], json: bool) -> Result<()> {
+    if json {
+        for sym in sym_infos {
+            println!("{}", serde_json::to_string(sym)?);
+        }
+        return Ok(())
+    }
+
     let name_width = sym_infos
         .iter()
         .map(|sym| sym.name.len())
@@ -76,9 +106,10 @@ fn print_sym_infos(sym_infos: &[inspect::SymInfo]) {
             ),
         );
     }
+    Ok(())
 }
 
-fn inspect(inspect: args::inspect::Inspect) -> Result<()> {
+fn inspect(inspect: args::inspect::Inspect, json: bool) -> Result<()> {
     let inspector = Inspector::new();
     match inspect {
         args::inspect::Inspect::Lookup(lookup) => {
@@ -94,6 +125,10 @@ fn inspect(inspect: args::inspect::Inspect) -> Result<()> {
                     let src = inspect::source::Source::from(inspect::source::Elf::new(path));
                     (src, names)
                 }
+                args::inspect::Lookup::Gsym(args::inspect::GsymLookup { path, ref names }) => {
+                    let src = inspect::source::Source::from(inspect::source::Gsym::new(path));
+                    (src, names)
+                }
             };
 
             let names = names.iter().map(|s| s.as_ref()).collect::<Vec<&str>>();
@@ -106,8 +141,7 @@ fn inspect(inspect: args::inspect::Inspect) -> Result<()> {
                 })
                 .collect::<Vec<_>>();
 
-            let () = print_sym_infos(&sym_infos);
-            Ok(())
+            print_sym_infos(&sym_infos, json)
         }
         args::inspect::Inspect::Dump(dump) => {
             let src = match dump {
@@ -122,6 +156,9 @@ fn inspect(inspect: args::inspect::Inspect) -> Result<()> {
                     elf.debug_syms = !no_debug_syms;
                     inspect::source::Source::from(elf)
                 }
+                args::inspect::Dump::Gsym(args::inspect::GsymDump { path }) => {
+                    inspect::source::Source::from(inspect::source::Gsym::new(path))
+                }
             };
             let mut sym_infos = Vec::new();
             let () = inspector.for_each(&src, |sym| {
@@ -129,15 +166,15 @@ fn inspect(inspect: args::inspect::Inspect) -> Result<()> {
                 ControlFlow::Continue(())
             })?;
             let () = sym_infos.sort_by_key(|sym| sym.addr);
-            let () = print_sym_infos(&sym_infos);
-            Ok(())
+            print_sym_infos(&sym_infos, json)
         }
         args::inspect::Inspect::BuildId(args::inspect::BuildId::Elf { path }) => {
             let build_id = read_elf_build_id(&path)?;
-            if let Some(build_id) = build_id {
-                println!("{}", format_build_id_bytes(&build_id));
+            let build_id = build_id.as_deref().map(format_build_id_bytes);
+            if json {
+                println!("{}", json!({ "build_id": build_id }));
             } else {
-                println!("N/A");
+                println!("{}", build_id.as_deref().unwrap_or("N/A"));
             }
             Ok(())
         }
@@ -145,7 +182,7 @@ fn inspect(inspect: args::inspect::Inspect) -> Result<()> {
 }
 
 
-fn normalize(normalize: args::normalize::Normalize) -> Result<()> {
+fn normalize(normalize: args::normalize::Normalize, json: bool) -> Result<()> {
     match normalize {
         args::normalize::Normalize::User(args::normalize::User {
             pid,
@@ -162,13 +199,21 @@ fn normalize(normalize: args::normalize::Normalize) -> Result<()> {
                 map_files,
                 ..Default::default()
             };
+            let addrs = read_addrs(addrs)?;
             let normalized = normalizer
                 .normalize_user_addrs_opts(pid, addrs.as_slice(), &opts)
                 .context("failed to normalize addresses")?;
             for (addr, (output, meta_idx)) in addrs.iter().zip(&normalized.outputs) {
-                print!("{addr:#016x}: ");
-
                 let meta = &normalized.meta[*meta_idx];
+                if json {
+                    println!(
+                        "{}",
+                        json!({ "addr": addr, "output": output, "meta": meta })
+                    );
+                    continue
+                }
+
+                print!("{addr:#016x}: ");
                 match meta {
                     normalize::UserMeta::Apk(normalize::Apk { path, .. }) => {
                         println!("file offset {output:#x} in {}", path.display())
@@ -177,6 +222,9 @@ fn normalize(normalize: args::normalize::Normalize) -> Result<()> {
                         let build_id = format_build_id(build_id.as_deref());
                         println!("file offset {output:#x} in {}{build_id}", path.display())
                     }
+                    normalize::UserMeta::SharedMem(normalize::SharedMem { path, .. }) => {
+                        println!("shared memory offset {output:#x} in {}", path.display())
+                    }
                     normalize::UserMeta::Unknown(normalize::Unknown { .. }) => {
                         println!("<unknown>")
                     }
@@ -185,56 +233,56 @@ fn normalize(normalize: args::normalize::Normalize) -> Result<()> {
                 }
             }
         }
-    }
-    Ok(())
-}
+        args::normalize::Normalize::Kernel(args::normalize::Kernel { addrs }) => {
+            let normalizer = Normalizer::new();
+            let addrs = read_addrs(addrs)?;
+            let normalized = normalizer
+                .normalize_kernel_addrs(addrs.as_slice())
+                .context("failed to normalize addresses")?;
+            for (addr, (output, meta_idx)) in addrs.iter().zip(&normalized.outputs) {
+                let meta = &normalized.meta[*meta_idx];
+                if json {
+                    println!(
+                        "{}",
+                        json!({ "addr": addr, "output": output, "meta": meta })
+                    );
+                    continue
+                }
 
-fn print_frame(
-    name: &str,
-    addr_info: Option<(Addr, Addr, usize)>,
-    code_info: &Option<symbolize::CodeInfo>,
-) {
-    let code_info = code_info.as_ref().map(|code_info| {
-        let path = code_info.to_path();
-        let path = path.display();
-
-        match (code_info.line, code_info.column) {
-            (Some(line), Some(col)) => format!(" {path}:{line}:{col}"),
-            (Some(line), None) => format!(" {path}:{line}"),
-            (None, _) => format!(" {path}"),
-        }
-    });
+                print!("{addr:#016x}: ");
 
-    if let Some((input_addr, addr, offset)) = addr_info {
-        // If we have various address information bits we have a new symbol.
-        println!(
-            "{input_addr:#0width$x}: {name} @ {addr:#x}+{offset:#x}{code_info}",
-            code_info = code_info.as_deref().unwrap_or(""),
-            width = ADDR_WIDTH
-        )
-    } else {
-        // Otherwise we are dealing with an inlined call.
-        println!(
-            "{:width$}  {name}{code_info} [inlined]",
-            " ",
-            code_info = code_info
-                .map(|info| format!(" @{info}"))
-                .as_deref()
-                .unwrap_or(""),
-            width = ADDR_WIDTH
-        )
+                match meta {
+                    normalize::KernelMeta::Kernel(normalize::Kernel { kaslr_offset, .. }) => {
+                        println!("kernel address {output:#x} (KASLR offset: {kaslr_offset:#x})")
+                    }
+                    normalize::KernelMeta::Module(normalize::KernelModule { name, .. }) => {
+                        println!("offset {output:#x} in module {name}")
+                    }
+                    normalize::KernelMeta::BpfProg(normalize::BpfProg { name, tag, .. }) => {
+                        println!(
+                            "offset {output:#x} in BPF program {name} (tag: {})",
+                            format_build_id_bytes(tag)
+                        )
+                    }
+                    normalize::KernelMeta::Unknown(normalize::Unknown { .. }) => {
+                        println!("<unknown>")
+                    }
+                    // This is a bug and should be reported as such.
+                    _ => panic!("encountered unsupported kernel meta data: {meta:?}"),
+                }
+            }
+        }
     }
+    Ok(())
 }
 
 /// The handler for the 'symbolize' command.
-fn symbolize(symbolize: args::symbolize::Symbolize) -> Result<()> {
+fn symbolize(symbolize: args::symbolize::Symbolize, json: bool) -> Result<()> {
     let mut builder = Symbolizer::builder();
-    let (src, input, addrs) = match symbolize {
-        args::symbolize::Symbolize::Breakpad(args::symbolize::Breakpad { path, ref addrs }) => {
+    let (src, input, addrs): (_, MakeInput, _) = match symbolize {
+        args::symbolize::Symbolize::Breakpad(args::symbolize::Breakpad { path, addrs }) => {
             let src = symbolize::source::Source::from(symbolize::source::Breakpad::new(path));
-            let addrs = addrs.as_slice();
-            let input = symbolize::Input::FileOffset(addrs);
-            (src, input, addrs)
+            (src, |addrs| symbolize::Input::FileOffset(addrs), addrs)
         }
         args::symbolize::Symbolize::Elf(args::symbolize::Elf {
             path,
@@ -243,39 +291,35 @@ fn symbolize(symbolize: args::symbolize::Symbolize) -> Result<()> {
                     debug_dirs,
                     no_debug_syms,
                 },
-            ref addrs,
+            addrs,
         }) => {
             builder = builder.set_debug_dirs(debug_dirs);
 
             let mut elf = symbolize::source::Elf::new(path);
             elf.debug_syms = !no_debug_syms;
             let src = symbolize::source::Source::from(elf);
-            let addrs = addrs.as_slice();
-            let input = symbolize::Input::VirtOffset(addrs);
-            (src, input, addrs)
+            (src, |addrs| symbolize::Input::VirtOffset(addrs), addrs)
         }
-        args::symbolize::Symbolize::Gsym(args::symbolize::Gsym { path, ref addrs }) => {
+        args::symbolize::Symbolize::Gsym(args::symbolize::Gsym { path, addrs }) => {
             let src = symbolize::source::Source::from(symbolize::source::GsymFile::new(path));
-            let addrs = addrs.as_slice();
-            let input = symbolize::Input::VirtOffset(addrs);
-            (src, input, addrs)
+            (src, |addrs| symbolize::Input::VirtOffset(addrs), addrs)
         }
         args::symbolize::Symbolize::Process(args::symbolize::Process {
             pid,
-            ref addrs,
+            addrs,
             no_map_files,
+            no_proc_root,
         }) => {
             let mut process = symbolize::source::Process::new(pid);
             process.map_files = !no_map_files;
+            process.proc_root = !no_proc_root;
             let src = symbolize::source::Source::from(process);
-            let addrs = addrs.as_slice();
-            let input = symbolize::Input::AbsAddr(addrs);
-            (src, input, addrs)
+            (src, |addrs| symbolize::Input::AbsAddr(addrs), addrs)
         }
         args::symbolize::Symbolize::Kernel(args::symbolize::Kernel {
             kallsyms,
             vmlinux,
-            ref addrs,
+            addrs,
         }) => {
             let kernel = symbolize::source::Kernel {
                 kallsyms: match kallsyms {
@@ -291,36 +335,32 @@ fn symbolize(symbolize: args::symbolize::Symbolize) -> Result<()> {
                 ..Default::default()
             };
             let src = symbolize::source::Source::from(kernel);
-            let addrs = addrs.as_slice();
-            let input = symbolize::Input::AbsAddr(addrs);
-            (src, input, addrs)
+            (src, |addrs| symbolize::Input::AbsAddr(addrs), addrs)
         }
     };
 
+    let addrs = read_addrs(addrs)?;
     let symbolizer = builder.build();
     let syms = symbolizer
-        .symbolize(&src, input)
+        .symbolize(&src, input(&addrs))
         .context("failed to symbolize addresses")?;
 
-    for (input_addr, sym) in addrs.iter().copied().zip(syms) {
-        match sym {
-            symbolize::Symbolized::Sym(symbolize::Sym {
-                name,
-                addr,
-                offset,
-                code_info,
-                inlined,
-                ..
-            }) => {
-                print_frame(&name, Some((input_addr, addr, offset)), &code_info);
-                for frame in inlined.iter() {
-                    print_frame(&frame.name, None, &frame.code_info);
+    for (input_addr, sym) in addrs.iter().copied().zip(syms.iter()) {
+        if json {
+            let value = match sym {
+                symbolize::Symbolized::Sym(sym) => json!({ "addr": input_addr, "sym": sym }),
+                symbolize::Symbolized::Code(code_info) => {
+                    json!({ "addr": input_addr, "code_info": code_info })
                 }
-            }
-            symbolize::Symbolized::Unknown(..) => {
-                println!("{input_addr:#0width$x}: <no-symbol>", width = ADDR_WIDTH)
-            }
+                symbolize::Symbolized::Unknown(reason) => {
+                    json!({ "addr": input_addr, "reason": reason.to_string() })
+                }
+            };
+            println!("{value}");
+            continue
         }
+
+        println!("{}", symbolize::fmt::frames(input_addr, sym));
     }
     Ok(())
 }
@@ -344,8 +384,8 @@ fn main() -> Result<()> {
         set_global_subscriber(subscriber).with_context(|| "failed to set tracing subscriber")?;
 
     match args.command {
-        args::Command::Inspect(inspect) => self::inspect(inspect),
-        args::Command::Normalize(normalize) => self::normalize(normalize),
-        args::Command::Symbolize(symbolize) => self::symbolize(symbolize),
+        args::Command::Inspect(inspect) => self::inspect(inspect, args.json),
+        args::Command::Normalize(normalize) => self::normalize(normalize, args.json),
+        args::Command::Symbolize(symbolize) => self::symbolize(symbolize, args.json),
     }
 }
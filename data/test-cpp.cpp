@@ -0,0 +1,31 @@
+namespace outer {
+namespace inner {
+
+class Class {
+public:
+  static int method(int x);
+};
+
+int Class::method(int x) {
+  return x * 2;
+}
+
+} // namespace inner
+
+namespace {
+
+int hidden(int x) {
+  return x + 1;
+}
+
+} // namespace
+
+int func(int x) {
+  return inner::Class::method(x) + hidden(x);
+}
+
+} // namespace outer
+
+int main(int argc, char *argv[]) {
+  return outer::func(argc);
+}
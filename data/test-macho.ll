@@ -0,0 +1,43 @@
+; A minimal macOS program with DWARF debug information, used for
+; testing Mach-O and dSYM symbolization. We provide LLVM IR instead of
+; C source so that no compiler targeting macOS is required to generate
+; it. The target is provided when compiling.
+;
+; Equivalent C source (test-macho.c):
+;   int add(int a, int b) {
+;     return a + b;
+;   }
+;
+;   int main(void) {
+;     return add(1, 2);
+;   }
+
+define i32 @add(i32 %a, i32 %b) noinline optnone !dbg !8 {
+entry:
+  %sum = add nsw i32 %a, %b, !dbg !12
+  ret i32 %sum, !dbg !13
+}
+
+define i32 @main() noinline optnone !dbg !14 {
+entry:
+  %call = call i32 @add(i32 1, i32 2), !dbg !16
+  ret i32 %call, !dbg !17
+}
+
+!llvm.dbg.cu = !{!0}
+!llvm.module.flags = !{!3, !4}
+
+!0 = distinct !DICompileUnit(language: DW_LANG_C99, file: !1, producer: "handwritten", isOptimized: false, runtimeVersion: 0, emissionKind: FullDebug)
+!1 = !DIFile(filename: "test-macho.c", directory: "/blazesym/data")
+!3 = !{i32 7, !"Dwarf Version", i32 4}
+!4 = !{i32 2, !"Debug Info Version", i32 3}
+!5 = !DISubroutineType(types: !6)
+!6 = !{!7, !7, !7}
+!7 = !DIBasicType(name: "int", size: 32, encoding: DW_ATE_signed)
+!8 = distinct !DISubprogram(name: "add", scope: !1, file: !1, line: 1, type: !5, scopeLine: 1, spFlags: DISPFlagDefinition, unit: !0)
+!12 = !DILocation(line: 2, column: 12, scope: !8)
+!13 = !DILocation(line: 2, column: 3, scope: !8)
+!14 = distinct !DISubprogram(name: "main", scope: !1, file: !1, line: 5, type: !15, scopeLine: 5, spFlags: DISPFlagDefinition, unit: !0)
+!15 = !DISubroutineType(types: !{!7})
+!16 = !DILocation(line: 6, column: 10, scope: !14)
+!17 = !DILocation(line: 6, column: 3, scope: !14)
@@ -14,6 +14,7 @@ use std::io::Error;
 use std::io::ErrorKind;
 use std::io::Result;
 use std::io::Write as _;
+use std::iter;
 use std::ops::Deref;
 use std::path::Path;
 use std::path::PathBuf;
@@ -235,6 +236,21 @@ fn gsym(src: &Path, dst: impl AsRef<OsStr>) {
     let () = adjust_mtime(&dst).unwrap();
 }
 
+/// Package the split DWARF files referenced by `src` into the DWARF
+/// package `dst` using `llvm-dwp`.
+fn dwp(src: &Path, dst: impl AsRef<OsStr>) {
+    let dst = src.with_file_name(dst);
+    println!("cargo:rerun-if-changed={}", src.display());
+    println!("cargo:rerun-if-changed={}", dst.display());
+    println!("cargo:rerun-if-env-changed=LLVM_DWP");
+
+    let dwp = env::var_os("LLVM_DWP").unwrap_or_else(|| OsString::from("llvm-dwp"));
+
+    let () = run(dwp, ["-e".as_ref(), src, "-o".as_ref(), &dst]).expect("failed to run `llvm-dwp`");
+
+    let () = adjust_mtime(&dst).unwrap();
+}
+
 /// Invoke `strip` on a copy of `src` placed at `dst`.
 fn strip(src: &Path, dst: impl AsRef<OsStr>, options: &[&str]) {
     toolize_o("strip", src, dst, options)
@@ -257,6 +273,142 @@ fn objcopy(src: &Path, dst: impl AsRef<OsStr>, options: &[&str]) {
     toolize("objcopy", src, dst, options)
 }
 
+/// Compile the LLVM IR in `src` into a macOS Mach-O executable `dst` for
+/// the architecture `arch`, using `llc` and `ld64.lld`. If `dsym` is
+/// true, a dSYM bundle is created next to it using `dsymutil`.
+fn macho(src: &Path, dst: impl AsRef<OsStr>, arch: &str, dsym: bool) {
+    let dst = src.with_file_name(dst);
+    let obj = change_ext(&dst, "o");
+    let mut bundle = dst.clone().into_os_string();
+    let () = bundle.push(".dSYM");
+    let bundle = PathBuf::from(bundle);
+    let dwarf = bundle
+        .join("Contents")
+        .join("Resources")
+        .join("DWARF")
+        .join(dst.file_name().unwrap());
+    println!("cargo:rerun-if-changed={}", src.display());
+    println!("cargo:rerun-if-changed={}", dst.display());
+    println!("cargo:rerun-if-env-changed=LLC");
+    println!("cargo:rerun-if-env-changed=LD64_LLD");
+    println!("cargo:rerun-if-env-changed=DSYMUTIL");
+
+    let llc = env::var_os("LLC").unwrap_or_else(|| OsString::from("llc"));
+    let () = run(
+        llc,
+        [
+            "-filetype=obj".as_ref(),
+            OsStr::new(&format!("-mtriple={arch}-apple-macosx11.0.0")),
+            src.as_os_str(),
+            "-o".as_ref(),
+            obj.as_os_str(),
+        ],
+    )
+    .expect("failed to run `llc`");
+
+    let ld64_lld = env::var_os("LD64_LLD").unwrap_or_else(|| OsString::from("ld64.lld"));
+    let () = run(
+        ld64_lld,
+        [
+            "-arch".as_ref(),
+            arch.as_ref(),
+            "-platform_version".as_ref(),
+            "macos".as_ref(),
+            "11.0".as_ref(),
+            "11.0".as_ref(),
+            "-e".as_ref(),
+            "_main".as_ref(),
+            "-o".as_ref(),
+            dst.as_os_str(),
+            obj.as_os_str(),
+        ],
+    )
+    .expect("failed to run `ld64.lld`");
+
+    if dsym {
+        println!("cargo:rerun-if-changed={}", dwarf.display());
+
+        // `dsymutil` reads debug information from the object file
+        // referenced by the executable, so it has to run before we
+        // remove said file.
+        let dsymutil = env::var_os("DSYMUTIL").unwrap_or_else(|| OsString::from("dsymutil"));
+        let () = run(
+            dsymutil,
+            [dst.as_os_str(), "-o".as_ref(), bundle.as_os_str()],
+        )
+        .expect("failed to run `dsymutil`");
+        let () = adjust_mtime(&dwarf).unwrap();
+    }
+    let () = remove_file(&obj).unwrap();
+
+    let () = adjust_mtime(&dst).unwrap();
+}
+
+/// Combine the Mach-O files `srcs` into a universal binary `dst`, using
+/// `llvm-lipo`.
+fn lipo(srcs: &[&Path], dst: impl AsRef<OsStr>) {
+    let dst = srcs[0].with_file_name(dst);
+    println!("cargo:rerun-if-changed={}", dst.display());
+    println!("cargo:rerun-if-env-changed=LLVM_LIPO");
+
+    let lipo = env::var_os("LLVM_LIPO").unwrap_or_else(|| OsString::from("llvm-lipo"));
+    let () = run(
+        lipo,
+        iter::once(OsStr::new("-create"))
+            .chain(srcs.iter().map(|src| src.as_os_str()))
+            .chain(["-output".as_ref(), dst.as_os_str()]),
+    )
+    .expect("failed to run `llvm-lipo`");
+
+    let () = adjust_mtime(&dst).unwrap();
+}
+
+/// Compile the LLVM IR in `src` into a Windows PE executable `dst`,
+/// accompanied by a PDB file of the same name, using `llc` and
+/// `lld-link`.
+fn pe(src: &Path, dst: impl AsRef<OsStr>) {
+    let dst = src.with_file_name(dst);
+    let obj = change_ext(&dst, "obj");
+    let pdb = change_ext(&dst, "pdb");
+    println!("cargo:rerun-if-changed={}", src.display());
+    println!("cargo:rerun-if-changed={}", dst.display());
+    println!("cargo:rerun-if-changed={}", pdb.display());
+    println!("cargo:rerun-if-env-changed=LLC");
+    println!("cargo:rerun-if-env-changed=LLD_LINK");
+
+    let llc = env::var_os("LLC").unwrap_or_else(|| OsString::from("llc"));
+    let () = run(
+        llc,
+        [
+            "-filetype=obj".as_ref(),
+            "-mtriple=x86_64-pc-windows-msvc".as_ref(),
+            src.as_os_str(),
+            "-o".as_ref(),
+            obj.as_os_str(),
+        ],
+    )
+    .expect("failed to run `llc`");
+
+    let lld_link = env::var_os("LLD_LINK").unwrap_or_else(|| OsString::from("lld-link"));
+    let () = run(
+        lld_link,
+        [
+            "/debug".as_ref(),
+            "/entry:main".as_ref(),
+            "/subsystem:console".as_ref(),
+            "/nodefaultlib".as_ref(),
+            OsStr::new(&format!("/out:{}", dst.display())),
+            OsStr::new(&format!("/pdb:{}", pdb.display())),
+            obj.as_os_str(),
+        ],
+    )
+    .expect("failed to run `lld-link`");
+    let () = remove_file(&obj).unwrap();
+
+    let () = adjust_mtime(&dst).unwrap();
+    let () = adjust_mtime(&pdb).unwrap();
+}
+
 /// Generate a Breakpad .sym file for the given source.
 #[cfg(feature = "dump_syms")]
 fn syms(src: &Path, dst: impl AsRef<OsStr>) {
@@ -309,6 +461,37 @@ fn unpack_xz(_src: &Path, _dst: &Path) {
 }
 
 
+/// Compress a file using zstd.
+#[cfg(feature = "zstd")]
+fn compress_zstd(src: &Path, dst: impl AsRef<OsStr>) {
+    use std::io::copy;
+    use zstd::stream::write::Encoder;
+
+    let dst = src.with_file_name(dst);
+    println!("cargo:rerun-if-changed={}", src.display());
+    println!("cargo:rerun-if-changed={}", dst.display());
+
+    let mut src_file = File::options().create(false).read(true).open(src).unwrap();
+    let dst_file = File::options()
+        .create(true)
+        .truncate(true)
+        .read(false)
+        .write(true)
+        .open(&dst)
+        .unwrap();
+
+    let mut encoder = Encoder::new(dst_file, 0).unwrap();
+    let _bytes = copy(&mut src_file, &mut encoder).unwrap();
+    let _file = encoder.finish().unwrap();
+    let () = adjust_mtime(&dst).unwrap();
+}
+
+#[cfg(not(feature = "zstd"))]
+fn compress_zstd(_src: &Path, _dst: impl AsRef<OsStr>) {
+    unimplemented!()
+}
+
+
 /// Put files in a zip archive, uncompressed.
 #[cfg(feature = "zip")]
 fn zip(files: &[PathBuf], dst: &Path) {
@@ -472,6 +655,9 @@ fn prepare_test_files() {
         );
     }
 
+    let src = data_dir.join("test-cpp.cpp");
+    cc(&src, "test-cpp.bin", &["-g", "-O0"]);
+
     let src = data_dir.join("test-wait.c");
     cc(&src, "test-wait.bin", &[]);
 
@@ -504,6 +690,23 @@ fn prepare_test_files() {
             &["-gdwarf-4", "-Wl,--build-id=none", "-gz=zstd"],
         );
     }
+    cc_stable_addrs(
+        "test-stable-addrs-dwarf5.bin",
+        &["-gdwarf-5", "-Wl,--build-id=none", "-O0"],
+    );
+    cc_stable_addrs(
+        "test-stable-addrs-split-dwarf.bin",
+        &["-gdwarf-5", "-gsplit-dwarf", "-Wl,--build-id=none", "-O0"],
+    );
+    // NB: `llvm-dwp` does not seem to cope with split DWARF 5 data as
+    //     emitted by GCC, so we use the GNU extension to DWARF 4 for
+    //     the package.
+    cc_stable_addrs(
+        "test-stable-addrs-dwp.bin",
+        &["-gdwarf-4", "-gsplit-dwarf", "-Wl,--build-id=none", "-O0"],
+    );
+    let src = data_dir.join("test-stable-addrs-dwp.bin");
+    dwp(&src, "test-stable-addrs-dwp.bin.dwp");
     cc_stable_addrs(
         "test-stable-addrs-no-dwarf.bin",
         &["-g0", "-Wl,--build-id=none"],
@@ -522,6 +725,10 @@ fn prepare_test_files() {
 
     let src = data_dir.join("test-stable-addrs.bin");
     gsym(&src, "test-stable-addrs.gsym");
+    if cfg!(feature = "zstd") {
+        let gsym = src.with_file_name("test-stable-addrs.gsym");
+        compress_zstd(&gsym, "test-stable-addrs.gsym.zst");
+    }
     // Poor man's stripping of ELF stuff, mostly just to have an alternative to
     // `--only-keep-debug` which actually keeps in tact executable bits.
     strip(
@@ -580,6 +787,23 @@ fn prepare_test_files() {
     );
     let () = remove_file(&dbg).unwrap();
 
+    let src = data_dir.join("test-pe.ll");
+    pe(&src, "test-pe.exe");
+    // A second link of the same program, whose PDB file carries a
+    // different GUID.
+    pe(&src, "test-pe-other.exe");
+
+    let src = data_dir.join("test-macho.ll");
+    macho(&src, "test-macho", "x86_64", true);
+    macho(&src, "test-macho-arm64", "arm64", false);
+    lipo(
+        &[
+            &data_dir.join("test-macho-arm64"),
+            &data_dir.join("test-macho"),
+        ],
+        "test-macho-fat",
+    );
+
     let src = data_dir.join("kallsyms.xz");
     unpack_xz(&src, &change_ext(&src, ""));
 
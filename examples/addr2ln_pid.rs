@@ -91,6 +91,17 @@ print its symbol, the file name of the source, and the line number.",
                     print_frame(&frame.name, None, &frame.code_info);
                 }
             }
+            Symbolized::Code(code_info) => {
+                let line = code_info
+                    .line
+                    .map(|line| format!(":{line}"))
+                    .unwrap_or_default();
+                println!(
+                    "{input_addr:#0width$x}: <no-symbol> {path}{line}",
+                    path = code_info.to_path().display(),
+                    width = ADDR_WIDTH
+                )
+            }
             Symbolized::Unknown(..) => {
                 println!("{input_addr:#0width$x}: <no-symbol>", width = ADDR_WIDTH)
             }
@@ -0,0 +1,171 @@
+use crate::Addr;
+
+
+/// A 64 bit CPU architecture, as relevant for the classification of
+/// addresses.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[non_exhaustive]
+pub enum Arch {
+    /// The x86_64 (amd64) architecture.
+    X86_64,
+    /// The AArch64 (arm64) architecture.
+    Aarch64,
+}
+
+impl Arch {
+    /// Retrieve the architecture the library was compiled for, if it
+    /// is supported.
+    pub const fn host() -> Option<Self> {
+        if cfg!(target_arch = "x86_64") {
+            Some(Self::X86_64)
+        } else if cfg!(target_arch = "aarch64") {
+            Some(Self::Aarch64)
+        } else {
+            None
+        }
+    }
+}
+
+
+/// The address space a raw address belongs to.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[non_exhaustive]
+pub enum AddrSpace {
+    /// The address is a canonical user space address.
+    User,
+    /// The address is a canonical kernel address.
+    Kernel,
+    /// The address is not canonical and can neither belong to user
+    /// space nor to the kernel.
+    NonCanonical,
+}
+
+impl AddrSpace {
+    /// Classify `addr` as belonging to the user or kernel address
+    /// space of the given architecture.
+    ///
+    /// The classification is based solely on the address itself and
+    /// does not take into account the actual configuration of the
+    /// system (e.g., the number of page table levels in use). As such,
+    /// it is deliberately permissive and accepts any address that is
+    /// canonical for the largest address width supported by the
+    /// architecture:
+    /// - on x86_64 that is 57 bits, as used with 5-level paging
+    /// - on AArch64 that is 52 bits, with bit 55 selecting between the two
+    ///   translation table base registers (`TTBR0` for user space and `TTBR1`
+    ///   for the kernel); the top byte of user space addresses is ignored, to
+    ///   accommodate pointer tagging
+    pub fn classify(addr: Addr, arch: Arch) -> Self {
+        match arch {
+            Arch::X86_64 => match addr >> 56 {
+                0x00 => Self::User,
+                0xff => Self::Kernel,
+                _ => Self::NonCanonical,
+            },
+            Arch::Aarch64 => {
+                if addr & (1 << 55) == 0 {
+                    if (addr & 0x00ff_ffff_ffff_ffff) >> 52 == 0 {
+                        Self::User
+                    } else {
+                        Self::NonCanonical
+                    }
+                } else if addr >> 52 == 0xfff {
+                    Self::Kernel
+                } else {
+                    Self::NonCanonical
+                }
+            }
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+
+    /// Check that we classify x86_64 addresses correctly.
+    #[test]
+    fn x86_64_classification() {
+        let arch = Arch::X86_64;
+        assert_eq!(AddrSpace::classify(0x0, arch), AddrSpace::User);
+        assert_eq!(AddrSpace::classify(0x5555_5555_4000, arch), AddrSpace::User);
+        assert_eq!(AddrSpace::classify(0x7fff_ffff_f000, arch), AddrSpace::User);
+        // A user space address only valid with 5-level paging.
+        assert_eq!(
+            AddrSpace::classify(0x00ff_ffff_ffff_f000, arch),
+            AddrSpace::User
+        );
+        assert_eq!(
+            AddrSpace::classify(0xffff_ffff_8100_0000, arch),
+            AddrSpace::Kernel
+        );
+        assert_eq!(
+            AddrSpace::classify(0xffff_ffff_ff60_0000, arch),
+            AddrSpace::Kernel
+        );
+        assert_eq!(
+            AddrSpace::classify(0xff00_0000_0000_0000, arch),
+            AddrSpace::Kernel
+        );
+        assert_eq!(
+            AddrSpace::classify(0x0100_0000_0000_0000, arch),
+            AddrSpace::NonCanonical
+        );
+        assert_eq!(
+            AddrSpace::classify(0xdead_beef_dead_beef, arch),
+            AddrSpace::NonCanonical
+        );
+    }
+
+    /// Check that we classify AArch64 addresses correctly.
+    #[test]
+    fn aarch64_classification() {
+        let arch = Arch::Aarch64;
+        assert_eq!(AddrSpace::classify(0x0, arch), AddrSpace::User);
+        assert_eq!(AddrSpace::classify(0xaaaa_aaaa_0000, arch), AddrSpace::User);
+        assert_eq!(
+            AddrSpace::classify(0x000f_ffff_ffff_f000, arch),
+            AddrSpace::User
+        );
+        // A tagged user space pointer.
+        assert_eq!(
+            AddrSpace::classify(0xb400_007f_1234_5678, arch),
+            AddrSpace::User
+        );
+        assert_eq!(
+            AddrSpace::classify(0xffff_8000_1000_0000, arch),
+            AddrSpace::Kernel
+        );
+        assert_eq!(
+            AddrSpace::classify(0xfff0_0000_0000_0000, arch),
+            AddrSpace::Kernel
+        );
+        assert_eq!(
+            AddrSpace::classify(0x0010_0000_0000_0000, arch),
+            AddrSpace::NonCanonical
+        );
+        assert_eq!(
+            AddrSpace::classify(0x0080_0000_0000_0000, arch),
+            AddrSpace::NonCanonical
+        );
+        assert_eq!(
+            AddrSpace::classify(0xff7f_ffff_ffff_ffff, arch),
+            AddrSpace::NonCanonical
+        );
+    }
+
+    /// Make sure that we can detect the host architecture.
+    #[test]
+    fn host_arch() {
+        let arch = Arch::host();
+        if cfg!(target_arch = "x86_64") {
+            assert_eq!(arch, Some(Arch::X86_64));
+        } else if cfg!(target_arch = "aarch64") {
+            assert_eq!(arch, Some(Arch::Aarch64));
+        } else {
+            assert_eq!(arch, None);
+        }
+    }
+}
@@ -0,0 +1,377 @@
+//! Functionality for exporting symbol information of local binaries
+//! as Breakpad symbol files.
+
+use std::borrow::Cow;
+#[cfg(feature = "dwarf")]
+use std::collections::HashMap;
+use std::fmt::Write as _;
+use std::fs;
+use std::ops::ControlFlow;
+use std::path::Path;
+use std::path::PathBuf;
+use std::rc::Rc;
+
+use crate::elf::types::EM_386;
+use crate::elf::types::EM_AARCH64;
+use crate::elf::types::EM_ARM;
+use crate::elf::types::EM_MIPS;
+use crate::elf::types::EM_PPC;
+use crate::elf::types::EM_PPC64;
+use crate::elf::types::EM_RISCV;
+use crate::elf::types::EM_S390;
+use crate::elf::types::EM_X86_64;
+use crate::elf::types::PT_LOAD;
+use crate::elf::ElfParser;
+#[cfg(feature = "dwarf")]
+use crate::elf::ElfResolver;
+#[cfg(feature = "dwarf")]
+use crate::elf::DEFAULT_DEBUG_DIRS;
+use crate::inspect::FindAddrOpts;
+use crate::normalize::buildid::breakpad_debug_id;
+use crate::symbolize::maybe_demangle;
+use crate::symbolize::SrcLang;
+use crate::Addr;
+use crate::Error;
+use crate::ErrorExt as _;
+#[cfg(feature = "dwarf")]
+use crate::ErrorKind;
+use crate::IntoError as _;
+use crate::Result;
+use crate::SymType;
+
+
+/// The size of a Breakpad module identifier, in bytes.
+const MODULE_ID_LEN: usize = 16;
+
+
+/// A function as exported to a symbol file.
+#[derive(Debug)]
+struct Func {
+    /// The function's address, relative to the module's load address.
+    addr: Addr,
+    /// The function's size, if known.
+    size: Option<usize>,
+    /// The function's (demangled) name.
+    name: String,
+}
+
+
+/// A line table entry as exported to a symbol file.
+#[derive(Debug)]
+struct Line {
+    /// The address of the first instruction covered by the entry,
+    /// relative to the module's load address.
+    addr: Addr,
+    /// The number of bytes covered by the entry.
+    size: u64,
+    /// The line number.
+    line: u32,
+    /// The index of the source file in the file table.
+    file: usize,
+}
+
+
+/// Map an ELF machine type to the name Breakpad uses for the
+/// architecture.
+fn arch_name(machine: u16, is_32bit: bool) -> Option<&'static str> {
+    let name = match machine {
+        EM_386 => "x86",
+        EM_AARCH64 => "arm64",
+        EM_ARM => "arm",
+        EM_MIPS => "mips",
+        EM_PPC => "ppc",
+        EM_PPC64 => "ppc64",
+        EM_RISCV if is_32bit => "riscv32",
+        EM_RISCV => "riscv64",
+        EM_S390 => "s390",
+        EM_X86_64 => "x86_64",
+        _ => return None,
+    };
+    Some(name)
+}
+
+/// Format `bytes` as upper case hexadecimal string.
+fn hex(bytes: &[u8]) -> String {
+    bytes
+        .iter()
+        .fold(String::with_capacity(bytes.len() * 2), |mut s, b| {
+            let _result = write!(s, "{b:02X}");
+            s
+        })
+}
+
+/// Create a file identifier by hashing the beginning of the `.text`
+/// section, the way Breakpad does for files without a build ID.
+fn text_hash(parser: &ElfParser) -> Result<[u8; MODULE_ID_LEN]> {
+    let idx = parser
+        .find_section(".text")?
+        .ok_or_invalid_data(|| "ELF file has neither build ID nor .text section")?;
+    let data = parser.section_data(idx)?;
+    let data = &data[..data.len().min(4096)];
+
+    let mut hash = [0u8; MODULE_ID_LEN];
+    let () = data.chunks(MODULE_ID_LEN).for_each(|chunk| {
+        let () = hash
+            .iter_mut()
+            .zip(chunk)
+            .for_each(|(hash, byte)| *hash ^= byte);
+    });
+    Ok(hash)
+}
+
+/// Determine the address at which the module is considered loaded,
+/// which Breakpad reports all addresses relative to.
+fn load_addr(parser: &ElfParser) -> Result<Addr> {
+    let phdrs = parser.program_headers()?;
+    let addr = phdrs
+        .iter(0)
+        .map(|phdr| phdr.to_64bit())
+        .find(|phdr| phdr.p_type == PT_LOAD)
+        .map(|phdr| phdr.p_vaddr as Addr)
+        .unwrap_or(0);
+    Ok(addr)
+}
+
+/// Gather the functions contained in the ELF file, sorted by address.
+fn funcs(parser: &ElfParser, load_addr: Addr) -> Result<Vec<Func>> {
+    let opts = FindAddrOpts {
+        sym_type: SymType::Function,
+        ..Default::default()
+    };
+    let mut funcs = Vec::new();
+    let () = parser.for_each(&opts, &mut |sym| {
+        if let Some(addr) = sym.addr.checked_sub(load_addr) {
+            let name = maybe_demangle(Cow::Borrowed(&sym.name), SrcLang::Unknown);
+            let () = funcs.push(Func {
+                addr,
+                size: sym.size.filter(|size| *size != 0),
+                name: name.into_owned(),
+            });
+        }
+        ControlFlow::Continue(())
+    })?;
+
+    // Symbol tables commonly contain aliases for the same function. We
+    // keep only one of them, preferring ones with size information.
+    let () = funcs.sort_by(|func1, func2| {
+        func1
+            .addr
+            .cmp(&func2.addr)
+            .then_with(|| func2.size.cmp(&func1.size))
+            .then_with(|| func1.name.cmp(&func2.name))
+    });
+    let () = funcs.dedup_by_key(|func| func.addr);
+    Ok(funcs)
+}
+
+/// Gather line table information for the ELF file from its DWARF debug
+/// information, sorted by address.
+#[cfg(feature = "dwarf")]
+fn lines(parser: Rc<ElfParser>, load_addr: Addr, files: &mut Vec<PathBuf>) -> Result<Vec<Line>> {
+    let debug_dirs = DEFAULT_DEBUG_DIRS
+        .iter()
+        .map(PathBuf::from)
+        .collect::<Vec<_>>();
+    let resolver = ElfResolver::from_parser(parser, Some(&debug_dirs), false, false)?;
+
+    let mut file_idxs = HashMap::<PathBuf, usize>::new();
+    let mut lines = Vec::new();
+    let mut prev = None::<(Addr, u32, usize)>;
+    let result = resolver.for_each_line_row(0..Addr::MAX, |row| {
+        if let Some((addr, line, file)) = prev.take() {
+            if let Some(size) = row.addr.checked_sub(addr).filter(|size| *size != 0) {
+                if let Some(addr) = addr.checked_sub(load_addr) {
+                    let () = lines.push(Line {
+                        addr,
+                        size,
+                        line,
+                        file,
+                    });
+                }
+            }
+        }
+
+        if !row.end_sequence {
+            if let Some(code_info) = &row.code_info {
+                let path = code_info.to_path().into_owned();
+                let file = *file_idxs.entry(path).or_insert_with_key(|path| {
+                    let () = files.push(path.clone());
+                    files.len() - 1
+                });
+                prev = Some((row.addr, code_info.line.unwrap_or(0), file));
+            }
+        }
+        ControlFlow::Continue(())
+    });
+
+    match result {
+        Ok(()) => (),
+        // The file does not contain DWARF information.
+        Err(err) if err.kind() == ErrorKind::Unsupported => return Ok(Vec::new()),
+        Err(err) => return Err(err),
+    }
+
+    let () = lines.sort_by_key(|line| line.addr);
+    Ok(lines)
+}
+
+#[cfg(not(feature = "dwarf"))]
+fn lines(_parser: Rc<ElfParser>, _load_addr: Addr, _files: &mut Vec<PathBuf>) -> Result<Vec<Line>> {
+    Ok(Vec::new())
+}
+
+/// Create the contents of a Breakpad symbol file for the ELF file at
+/// `path`, returning the module's name and identifier along with them.
+fn create_sym_file(path: &Path) -> Result<(String, String, String)> {
+    let parser = Rc::new(ElfParser::open(path)?);
+    let name = path
+        .file_name()
+        .and_then(|name| name.to_str())
+        .ok_or_invalid_input(|| format!("path `{}` has no valid file name", path.display()))?
+        .to_string();
+
+    let machine = parser.machine()?;
+    let arch = arch_name(machine, parser.is_32bit()?).ok_or_else(|| {
+        Error::with_unsupported(format!(
+            "ELF file `{}` is of unsupported architecture {machine}",
+            path.display()
+        ))
+    })?;
+    let build_id = parser.build_id()?.map(<[u8]>::to_vec);
+    let id = match &build_id {
+        Some(build_id) => breakpad_debug_id(build_id),
+        None => breakpad_debug_id(&text_hash(&parser)?),
+    };
+
+    let load_addr = load_addr(&parser)?;
+    let funcs = funcs(&parser, load_addr)?;
+    let mut files = Vec::new();
+    let lines = lines(parser, load_addr, &mut files)?;
+
+    let mut sym = String::new();
+    let _result = writeln!(sym, "MODULE Linux {arch} {id} {name}");
+    if let Some(build_id) = &build_id {
+        let _result = writeln!(sym, "INFO CODE_ID {}", hex(build_id));
+    }
+    for (idx, file) in files.iter().enumerate() {
+        let _result = writeln!(sym, "FILE {idx} {}", file.display());
+    }
+
+    let mut lines = lines.as_slice();
+    for func in &funcs {
+        match func.size {
+            Some(size) => {
+                let _result = writeln!(sym, "FUNC {:x} {size:x} 0 {}", func.addr, func.name);
+                let end = func.addr + size as Addr;
+                let start = lines.partition_point(|line| line.addr < func.addr);
+                lines = &lines[start..];
+                let count = lines.partition_point(|line| line.addr < end);
+                for line in &lines[..count] {
+                    let size = line.size.min(end - line.addr);
+                    let _result =
+                        writeln!(sym, "{:x} {size:x} {} {}", line.addr, line.line, line.file);
+                }
+            }
+            None => {
+                let _result = writeln!(sym, "PUBLIC {:x} 0 {}", func.addr, func.name);
+            }
+        }
+    }
+    Ok((name, id, sym))
+}
+
+/// Export the symbol information of the ELF file at `path` as a
+/// Breakpad symbol file, placed in the symbol server directory layout
+/// rooted at `dir`.
+///
+/// The symbol file is written to `<dir>/<module>/<id>/<module>.sym`,
+/// where `<module>` is the file name of the ELF file and `<id>` its
+/// Breakpad module identifier, as derived from its build ID (or, in its
+/// absence, from the contents of the `.text` section). This layout is
+/// understood by symbol servers as well as Breakpad based symbolization
+/// tools. Symbols are read from the ELF file's symbol tables and, if
+/// the `dwarf` feature is enabled and debug information present, line
+/// information from its DWARF data.
+///
+/// On success, the path of the written symbol file is returned.
+pub fn export_breakpad_sym(path: &Path, dir: &Path) -> Result<PathBuf> {
+    let (name, id, sym) = create_sym_file(path)
+        .with_context(|| format!("failed to create symbol file for `{}`", path.display()))?;
+
+    let dir = dir.join(&name).join(&id);
+    let () = fs::create_dir_all(&dir)
+        .with_context(|| format!("failed to create directory `{}`", dir.display()))?;
+    let sym_path = dir.join(format!("{name}.sym"));
+    let () = fs::write(&sym_path, sym)
+        .map_err(Error::from)
+        .with_context(|| format!("failed to write symbol file `{}`", sym_path.display()))?;
+    Ok(sym_path)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::io::Write as _;
+
+    use tempfile::tempdir;
+    use tempfile::NamedTempFile;
+
+    use crate::breakpad::BreakpadResolver;
+    use crate::symbolize::FindSymOpts;
+    use crate::symbolize::Symbolize as _;
+    use crate::test_utils::ElfBuilder;
+
+
+    /// Check that we format bytes as upper case hexadecimal strings.
+    #[test]
+    fn hex_formatting() {
+        assert_eq!(hex(&[0x01, 0x02, 0x03]), "010203");
+        assert_eq!(hex(&[0xab, 0xcd]), "ABCD");
+        assert_eq!(hex(&[]), "");
+    }
+
+    /// Check that we can export a synthetic ELF file as a Breakpad
+    /// symbol file and use it for symbolization.
+    #[test]
+    fn export_synthetic_elf() {
+        let mut builder = ElfBuilder::new();
+        let text = builder.add_section(".text", 0x4000, vec![0xcc; 0x100]);
+        let () = builder.add_symbol("first", text, 0x4000, 0x20, SymType::Function);
+        let () = builder.add_symbol("second", text, 0x4020, 0x80, SymType::Function);
+        let () = builder.add_symbol("unsized", text, 0x40a0, 0, SymType::Function);
+
+        let mut file = NamedTempFile::new().unwrap();
+        let () = file.write_all(&builder.build()).unwrap();
+
+        let dir = tempdir().unwrap();
+        let sym_path = export_breakpad_sym(file.path(), dir.path()).unwrap();
+        let name = file.path().file_name().unwrap();
+        assert_eq!(
+            sym_path.parent().unwrap().parent().unwrap(),
+            dir.path().join(name)
+        );
+        assert_eq!(
+            sym_path.file_name().unwrap(),
+            &*format!("{}.sym", name.to_str().unwrap())
+        );
+
+        let contents = fs::read_to_string(&sym_path).unwrap();
+        let mut records = contents.lines();
+        let module = records.next().unwrap();
+        assert!(module.starts_with("MODULE Linux x86_64 "), "{module}");
+        // Addresses are reported relative to the start of the first
+        // loadable segment.
+        assert!(contents.contains("FUNC 20 80 0 second"), "{contents}");
+        assert!(contents.contains("PUBLIC a0 0 unsized"), "{contents}");
+
+        let resolver = BreakpadResolver::open(&sym_path).unwrap();
+        let sym = resolver
+            .find_sym(0x42, &FindSymOpts::Basic)
+            .unwrap()
+            .unwrap();
+        assert_eq!(sym.name, "second");
+        assert_eq!(sym.addr, 0x20);
+    }
+}
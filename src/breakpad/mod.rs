@@ -1,6 +1,8 @@
+mod export;
 mod file;
 mod parser;
 mod resolver;
 mod types;
 
+pub use export::export_breakpad_sym;
 pub use resolver::BreakpadResolver;
@@ -42,6 +42,7 @@ impl<'func> From<&'func Function> for SymInfo<'func> {
             sym_type: SymType::Function,
             file_offset: None,
             obj_file_name: None,
+            code_info: None,
         }
     }
 }
@@ -198,6 +199,9 @@ impl Symbolize for BreakpadResolver {
             lang: SrcLang::Unknown,
             code_info: None,
             inlined: Box::new([]),
+            inlined_truncated: false,
+            section: None,
+            module: None,
         };
         let () = self.fill_code_info(&mut sym, addr, opts, func)?;
 
@@ -27,3 +27,23 @@ macro_rules! cfg_gsym {
         )*
     }
 }
+
+macro_rules! cfg_macho {
+    ($($item:item)*) => {
+        $(
+            #[cfg(feature = "macho")]
+            #[cfg_attr(docsrs, doc(cfg(feature = "macho")))]
+            $item
+        )*
+    }
+}
+
+macro_rules! cfg_windows {
+    ($($item:item)*) => {
+        $(
+            #[cfg(feature = "windows")]
+            #[cfg_attr(docsrs, doc(cfg(feature = "windows")))]
+            $item
+        )*
+    }
+}
@@ -0,0 +1,148 @@
+use std::sync::Mutex;
+use std::sync::PoisonError;
+use std::time::Instant;
+
+use crate::kernel::find_kalsr_offset;
+use crate::log;
+use crate::normalize::ioctl::probe_procmap_query;
+use crate::ErrorExt as _;
+use crate::Result;
+
+
+/// A lazily initialized value shared across the process.
+///
+/// Initialization happens at most once and concurrent accessors block
+/// until it has concluded. A failed initialization is not remembered
+/// and will be retried on next access.
+#[derive(Debug)]
+struct Lazy<T> {
+    value: Mutex<Option<T>>,
+}
+
+impl<T> Lazy<T>
+where
+    T: Copy,
+{
+    const fn new() -> Self {
+        Self {
+            value: Mutex::new(None),
+        }
+    }
+
+    fn get_or_try_init<F>(&self, init: F) -> Result<T>
+    where
+        F: FnOnce() -> Result<T>,
+    {
+        // A panicking initializer never stores a value, so we can
+        // safely ignore poisoning.
+        let mut value = self.value.lock().unwrap_or_else(PoisonError::into_inner);
+        if let Some(value) = *value {
+            return Ok(value)
+        }
+
+        let new = init()?;
+        *value = Some(new);
+        Ok(new)
+    }
+}
+
+
+/// The system's KASLR offset, if it could be determined.
+static KASLR_OFFSET: Lazy<Option<u64>> = Lazy::new();
+/// Whether the `PROCMAP_QUERY` ioctl is supported by the system.
+static PROCMAP_QUERY: Lazy<bool> = Lazy::new();
+
+
+/// Retrieve the system's KASLR offset, determining it on first use.
+pub(crate) fn kaslr_offset() -> Result<Option<u64>> {
+    KASLR_OFFSET.get_or_try_init(find_kalsr_offset)
+}
+
+/// Check whether the `PROCMAP_QUERY` ioctl is supported, probing for
+/// it on first use.
+pub(crate) fn procmap_query_supported() -> Result<bool> {
+    PROCMAP_QUERY.get_or_try_init(probe_procmap_query)
+}
+
+
+/// Initialize process wide state used by the library.
+///
+/// Certain system properties needed during normalization and
+/// symbolization are expensive to determine, but constant for the
+/// life time of the process. By default, they are determined lazily on
+/// first use, which may be on a latency sensitive path. Calling this
+/// function front-loads their initialization instead. It is safe to
+/// call concurrently and repeatedly; only the first successful
+/// initialization of each property performs any work.
+///
+/// Properties that could not be determined are reported by means of
+/// the returned error. The error is not fatal: the library will try to
+/// determine them again when they are needed.
+pub fn init() -> Result<()> {
+    let start = Instant::now();
+    let kaslr = kaslr_offset()
+        .map(|_offset| ())
+        .context("failed to determine KASLR offset");
+    #[cfg(linux)]
+    let procmap_query = procmap_query_supported()
+        .map(|_supported| ())
+        .context("failed to check for PROCMAP_QUERY ioctl support");
+    #[cfg(not(linux))]
+    let procmap_query = Ok(());
+    log::debug!("initialized process wide state in {:?}", start.elapsed());
+
+    kaslr.and(procmap_query)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::cell::Cell;
+
+    use test_log::test;
+
+    use crate::Error;
+    use crate::ErrorKind;
+
+
+    /// Check that a `Lazy` is initialized exactly once and that
+    /// initialization errors are not remembered.
+    #[test]
+    fn lazy_initialization() {
+        let lazy = Lazy::<u32>::new();
+        let calls = Cell::new(0);
+
+        let err = lazy
+            .get_or_try_init(|| {
+                let () = calls.set(calls.get() + 1);
+                Err(Error::with_unsupported("induced failure"))
+            })
+            .unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::Unsupported);
+
+        for _ in 0..2 {
+            let value = lazy
+                .get_or_try_init(|| {
+                    let () = calls.set(calls.get() + 1);
+                    Ok(42)
+                })
+                .unwrap();
+            assert_eq!(value, 42);
+        }
+        assert_eq!(calls.get(), 2);
+    }
+
+    /// Check that explicit initialization can be performed repeatedly
+    /// and leaves us with consistent state.
+    #[test]
+    fn repeated_init() {
+        let result1 = init().map_err(|err| err.kind());
+        let result2 = init().map_err(|err| err.kind());
+        if result1.is_ok() {
+            assert_eq!(result2, Ok(()));
+            assert_eq!(kaslr_offset().unwrap(), kaslr_offset().unwrap());
+        }
+    }
+}
@@ -0,0 +1,259 @@
+//! Support for transparently decompressing data, such as compressed
+//! copies of kernel symbol sources.
+
+use crate::util::ReadRaw as _;
+use crate::Error;
+use crate::IntoError as _;
+use crate::Result;
+
+
+/// The maximum number of bytes we are willing to decompress data to.
+///
+/// This limit bounds memory usage in the presence of corrupted or
+/// malicious input ("decompression bombs"), while still accommodating
+/// large kernel images with debug information.
+const MAX_DECOMPRESSED_LEN: u64 = 4 << 30;
+
+/// The magic bytes at the start of a gzip member.
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+/// The magic bytes at the start of an xz stream.
+const XZ_MAGIC: [u8; 6] = [0xfd, b'7', b'z', b'X', b'Z', 0x00];
+/// The magic bytes at the start of a zstd frame.
+const ZSTD_MAGIC: [u8; 4] = [0x28, 0xb5, 0x2f, 0xfd];
+
+
+fn max_decompressed_len() -> usize {
+    usize::try_from(MAX_DECOMPRESSED_LEN).unwrap_or(usize::MAX)
+}
+
+
+/// A compression format.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) enum Compression {
+    /// gzip, as described by RFC 1952.
+    Gzip,
+    /// xz.
+    Xz,
+    /// zstd.
+    Zstd,
+}
+
+impl Compression {
+    /// Detect the compression format used for `data` based on its
+    /// magic bytes, if any.
+    pub(crate) fn detect(data: &[u8]) -> Option<Self> {
+        if data.starts_with(&GZIP_MAGIC) {
+            Some(Self::Gzip)
+        } else if data.starts_with(&XZ_MAGIC) {
+            Some(Self::Xz)
+        } else if data.starts_with(&ZSTD_MAGIC) {
+            Some(Self::Zstd)
+        } else {
+            None
+        }
+    }
+}
+
+
+/// Retrieve the raw deflate stream of a gzip member, along with the
+/// size of the uncompressed data as recorded in its trailer.
+fn gzip_payload(mut data: &[u8]) -> Result<(&[u8], u32)> {
+    const FHCRC: u8 = 1 << 1;
+    const FEXTRA: u8 = 1 << 2;
+    const FNAME: u8 = 1 << 3;
+    const FCOMMENT: u8 = 1 << 4;
+    /// The "deflate" compression method.
+    const CM_DEFLATE: u8 = 8;
+
+    let header = data
+        .read_slice(10)
+        .ok_or_invalid_data(|| "gzip header is truncated")?;
+    if header[2] != CM_DEFLATE {
+        return Err(Error::with_unsupported(format!(
+            "gzip compression method {} is unsupported",
+            header[2]
+        )))
+    }
+
+    let flags = header[3];
+    if flags & FEXTRA != 0 {
+        let len = data
+            .read_slice(2)
+            .ok_or_invalid_data(|| "gzip extra field length is truncated")?;
+        let len = u16::from_le_bytes([len[0], len[1]]);
+        let () = data
+            .advance(len.into())
+            .ok_or_invalid_data(|| "gzip extra field is truncated")?;
+    }
+    if flags & FNAME != 0 {
+        let _name = data
+            .read_cstr()
+            .ok_or_invalid_data(|| "gzip file name is truncated")?;
+    }
+    if flags & FCOMMENT != 0 {
+        let _comment = data
+            .read_cstr()
+            .ok_or_invalid_data(|| "gzip comment is truncated")?;
+    }
+    if flags & FHCRC != 0 {
+        let () = data
+            .advance(2)
+            .ok_or_invalid_data(|| "gzip header CRC is truncated")?;
+    }
+
+    // The member ends with a trailer consisting of the CRC32 of the
+    // uncompressed data followed by its size (modulo 2^32).
+    let trailer = data
+        .len()
+        .checked_sub(8)
+        .and_then(|offset| data.get(offset..))
+        .ok_or_invalid_data(|| "gzip trailer is truncated")?;
+    let size = u32::from_le_bytes([trailer[4], trailer[5], trailer[6], trailer[7]]);
+    Ok((data, size))
+}
+
+#[cfg(feature = "zlib")]
+fn decompress_gzip(data: &[u8]) -> Result<Vec<u8>> {
+    use miniz_oxide::inflate::decompress_to_vec_with_limit;
+
+    let (payload, size) = gzip_payload(data)?;
+    let data = decompress_to_vec_with_limit(payload, max_decompressed_len())
+        .map_err(|err| Error::with_invalid_data(format!("gzip decompression failed: {err}")))?;
+    // We only support single member files. If the size recorded in the
+    // trailer does not match, there are likely more members or the file
+    // is corrupted.
+    if data.len() as u32 != size {
+        return Err(Error::with_invalid_data(format!(
+            "gzip decompressed size ({}) does not match recorded size ({size})",
+            data.len()
+        )))
+    }
+    Ok(data)
+}
+
+#[cfg(not(feature = "zlib"))]
+fn decompress_gzip(data: &[u8]) -> Result<Vec<u8>> {
+    let _payload = gzip_payload(data)?;
+    Err(Error::with_unsupported(
+        "data are gzip compressed but zlib compression support is not enabled",
+    ))
+}
+
+#[cfg(feature = "zstd")]
+fn decompress_zstd(data: &[u8]) -> Result<Vec<u8>> {
+    use std::io::Read as _;
+
+    use zstd::stream::read::Decoder;
+
+    use crate::ErrorExt as _;
+
+    let limit = max_decompressed_len();
+    let decoder = Decoder::with_buffer(data).context("failed to create zstd decoder")?;
+    let mut decompressed = Vec::new();
+    let _cnt = decoder
+        .take(limit as u64 + 1)
+        .read_to_end(&mut decompressed)
+        .context("zstd decompression failed")?;
+    if decompressed.len() > limit {
+        return Err(Error::with_invalid_data(format!(
+            "zstd decompressed data exceed maximum supported size of {limit} bytes"
+        )))
+    }
+    Ok(decompressed)
+}
+
+#[cfg(not(feature = "zstd"))]
+fn decompress_zstd(_data: &[u8]) -> Result<Vec<u8>> {
+    Err(Error::with_unsupported(
+        "data are zstd compressed but zstd compression support is not enabled",
+    ))
+}
+
+/// Decompress `data`, compressed using the provided format.
+pub(crate) fn decompress(data: &[u8], compression: Compression) -> Result<Vec<u8>> {
+    match compression {
+        Compression::Gzip => decompress_gzip(data),
+        Compression::Xz => Err(Error::with_unsupported("xz decompression is not supported")),
+        Compression::Zstd => decompress_zstd(data),
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::ErrorKind;
+
+
+    /// Check that we can detect the compression format of data.
+    #[test]
+    fn compression_detection() {
+        assert_eq!(Compression::detect(b""), None);
+        assert_eq!(Compression::detect(b"\x7fELF"), None);
+        assert_eq!(
+            Compression::detect(b"\x1f\x8b\x08\x00"),
+            Some(Compression::Gzip)
+        );
+        assert_eq!(
+            Compression::detect(b"\xfd7zXZ\x00\x00"),
+            Some(Compression::Xz)
+        );
+        assert_eq!(
+            Compression::detect(b"\x28\xb5\x2f\xfd\x00"),
+            Some(Compression::Zstd)
+        );
+    }
+
+    /// Check that we can decompress gzip data.
+    #[cfg(feature = "zlib")]
+    #[test]
+    fn gzip_decompression() {
+        use miniz_oxide::deflate::compress_to_vec;
+
+        let data = b"ffffffff81000000 T _stext\n".repeat(64);
+        let deflated = compress_to_vec(&data, 6);
+
+        let mut gzip = vec![0x1f, 0x8b, 0x08, 1 << 3, 0, 0, 0, 0, 0, 3];
+        let () = gzip.extend_from_slice(b"kallsyms\0");
+        let () = gzip.extend_from_slice(&deflated);
+        // The CRC32 is not checked.
+        let () = gzip.extend_from_slice(&[0; 4]);
+        let () = gzip.extend_from_slice(&(data.len() as u32).to_le_bytes());
+
+        let compression = Compression::detect(&gzip).unwrap();
+        assert_eq!(compression, Compression::Gzip);
+        let decompressed = decompress(&gzip, compression).unwrap();
+        assert_eq!(decompressed, data);
+
+        // A size mismatch in the trailer should be detected.
+        let len = gzip.len();
+        gzip[len - 4] ^= 1;
+        let err = decompress(&gzip, compression).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidData);
+
+        let err = decompress(&gzip[..12], compression).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidData);
+    }
+
+    /// Check that we can decompress zstd data.
+    #[cfg(feature = "zstd")]
+    #[test]
+    fn zstd_decompression() {
+        use zstd::stream::encode_all;
+
+        let data = b"ffffffff81000000 T _stext\n".repeat(64);
+        let compressed = encode_all(data.as_slice(), 0).unwrap();
+        let compression = Compression::detect(&compressed).unwrap();
+        assert_eq!(compression, Compression::Zstd);
+        let decompressed = decompress(&compressed, compression).unwrap();
+        assert_eq!(decompressed, data);
+    }
+
+    /// Make sure that xz data are reported as unsupported.
+    #[test]
+    fn xz_decompression() {
+        let err = decompress(b"\xfd7zXZ\x00", Compression::Xz).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::Unsupported);
+    }
+}
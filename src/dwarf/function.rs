@@ -26,9 +26,11 @@
 // > DEALINGS IN THE SOFTWARE.
 
 use std::cmp::Ordering;
+use std::collections::HashMap;
 use std::fmt::Debug;
 use std::fmt::Formatter;
 use std::fmt::Result as FmtResult;
+use std::rc::Rc;
 use std::vec;
 
 use gimli::Error;
@@ -40,6 +42,14 @@ use super::reader::R;
 use super::units::Units;
 
 
+/// The maximum depth of inlined function chains that we parse.
+///
+/// Legitimate debug information rarely nests inlined calls more than a
+/// few dozen levels deep, but pathological or adversarial input may do
+/// so arbitrarily. Bounding the depth bounds our recursion as well as
+/// the time spent parsing.
+const MAX_INLINED_DEPTH: usize = 128;
+
 fn name_entry<'dwarf>(
     unit: gimli::UnitRef<'_, R<'dwarf>>,
     offset: gimli::UnitOffset<<R<'_> as gimli::Reader>::Offset>,
@@ -89,7 +99,7 @@ fn name_entry<'dwarf>(
 }
 
 
-fn name_attr<'dwarf>(
+pub(super) fn name_attr<'dwarf>(
     attr: gimli::AttributeValue<R>,
     unit: gimli::UnitRef<'_, R<'dwarf>>,
     units: &Units<'dwarf>,
@@ -102,7 +112,7 @@ fn name_attr<'dwarf>(
     match attr {
         gimli::AttributeValue::UnitRef(offset) => name_entry(unit, offset, units, recursion_limit),
         gimli::AttributeValue::DebugInfoRef(offset) => {
-            let (unit, offset) = units.find_unit(offset)?;
+            let (unit, offset) = units.find_unit(unit, offset)?;
             name_entry(unit, offset, units, recursion_limit)
         }
         // TODO: Need to handle `AttributeValue::DebugInfoRefSup`.
@@ -111,6 +121,73 @@ fn name_attr<'dwarf>(
 }
 
 
+/// The maximum number of `DW_AT_specification` and
+/// `DW_AT_abstract_origin` references we follow when looking for
+/// declaration information.
+pub(super) const MAX_DECL_DEPTH: usize = 16;
+
+
+/// Retrieve the declaration file index and line of the entry at
+/// `offset`, following `DW_AT_specification` and `DW_AT_abstract_origin`
+/// references for attributes the entry does not carry itself.
+///
+/// Only references within `unit` are followed, because file indexes
+/// are relative to the line number program of the unit containing the
+/// entry. An error is reported if more than `recursion_limit` entries
+/// would have to be visited, as is the case for reference cycles.
+pub(super) fn decl_entry<'dwarf>(
+    unit: gimli::UnitRef<'_, R<'dwarf>>,
+    offset: gimli::UnitOffset<<R<'dwarf> as gimli::Reader>::Offset>,
+    recursion_limit: usize,
+) -> Result<(Option<u64>, Option<u32>), Error> {
+    if recursion_limit == 0 {
+        return Err(gimli::Error::TooManyIterations)
+    }
+
+    let mut entries = unit.entries_raw(Some(offset))?;
+    let abbrev = if let Some(abbrev) = entries.read_abbreviation()? {
+        abbrev
+    } else {
+        return Err(gimli::Error::NoEntryAtGivenOffset)
+    };
+
+    let mut decl_file = None;
+    let mut decl_line = None;
+    let mut next = None;
+    for spec in abbrev.attributes() {
+        let attr = entries.read_attribute(*spec)?;
+        match attr.name() {
+            gimli::DW_AT_decl_file => {
+                // See `InlinedFunction::parse` for details on file
+                // index 0 handling.
+                if let gimli::AttributeValue::FileIndex(fi) = attr.value() {
+                    if fi > 0 || unit.header.version() >= 5 {
+                        decl_file = Some(fi);
+                    }
+                }
+            }
+            gimli::DW_AT_decl_line => {
+                decl_line = attr.udata_value().and_then(|l| u32::try_from(l).ok());
+            }
+            gimli::DW_AT_abstract_origin | gimli::DW_AT_specification => {
+                if let gimli::AttributeValue::UnitRef(offset) = attr.value() {
+                    next = Some(offset);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    match next {
+        Some(next) if decl_file.is_none() || decl_line.is_none() => {
+            let (next_file, next_line) = decl_entry(unit, next, recursion_limit - 1)?;
+            Ok((decl_file.or(next_file), decl_line.or(next_line)))
+        }
+        _ => Ok((decl_file, decl_line)),
+    }
+}
+
+
 pub(super) struct InlinedFunction<'dwarf> {
     pub(crate) name: Option<R<'dwarf>>,
     pub(crate) call_file: Option<u64>,
@@ -134,6 +211,9 @@ pub(super) struct InlinedFunctions<'dwarf> {
     /// List of `DW_TAG_inlined_subroutine` address ranges in this
     /// function.
     inlined_addresses: Box<[InlinedFunctionAddress]>,
+    /// Whether inlined functions nested deeper than
+    /// `MAX_INLINED_DEPTH` were skipped.
+    truncated: bool,
 }
 
 impl<'dwarf> InlinedFunctions<'dwarf> {
@@ -154,7 +234,7 @@ impl<'dwarf> InlinedFunctions<'dwarf> {
 
         let mut inlined_functions = Vec::new();
         let mut inlined_addresses = Vec::new();
-        Function::parse_children(
+        let truncated = Function::parse_children(
             &mut entries,
             depth,
             unit,
@@ -183,14 +263,18 @@ impl<'dwarf> InlinedFunctions<'dwarf> {
         Ok(Self {
             inlined_functions: inlined_functions.into_boxed_slice(),
             inlined_addresses: inlined_addresses.into_boxed_slice(),
+            truncated,
         })
     }
 
     /// Build the list of inlined functions that contain `probe`.
+    ///
+    /// The returned flag indicates whether the list may be incomplete,
+    /// because nesting exceeded the supported depth.
     pub(super) fn find_inlined_functions(
         &self,
         probe: u64,
-    ) -> vec::IntoIter<&InlinedFunction<'dwarf>> {
+    ) -> (vec::IntoIter<&InlinedFunction<'dwarf>>, bool) {
         // `inlined_functions` is ordered from outside to inside.
         let mut inlined_functions = Vec::new();
         let mut inlined_addresses = &self.inlined_addresses[..];
@@ -221,8 +305,40 @@ impl<'dwarf> InlinedFunctions<'dwarf> {
                 break
             }
         }
-        inlined_functions.into_iter()
+        let truncated = self.truncated && inlined_functions.len() >= MAX_INLINED_DEPTH;
+        (inlined_functions.into_iter(), truncated)
     }
+
+    /// Retrieve all address ranges of inlined function calls.
+    ///
+    /// Each range is reported along with the nesting depth of the call
+    /// and the index of the call, which is shared by all of its
+    /// ranges.
+    pub(super) fn ranges(
+        &self,
+    ) -> impl Iterator<Item = (&gimli::Range, usize, usize, &InlinedFunction<'dwarf>)> {
+        self.inlined_addresses.iter().map(|address| {
+            (
+                &address.range,
+                address.call_depth,
+                address.function,
+                &self.inlined_functions[address.function],
+            )
+        })
+    }
+}
+
+
+/// Check whether a DIE with the given tag introduces a scope that
+/// contributes to the qualified name of the functions it contains.
+fn is_scope_tag(tag: gimli::DwTag) -> bool {
+    matches!(
+        tag,
+        gimli::DW_TAG_namespace
+            | gimli::DW_TAG_class_type
+            | gimli::DW_TAG_structure_type
+            | gimli::DW_TAG_union_type
+    )
 }
 
 
@@ -243,8 +359,19 @@ pub(crate) struct Function<'dwarf> {
     pub(crate) dw_die_offset: gimli::UnitOffset<<R<'dwarf> as gimli::Reader>::Offset>,
     /// The function's name, if present.
     pub(crate) name: Option<R<'dwarf>>,
+    /// The function's unqualified name as per `DW_AT_name`, if present.
+    pub(crate) short_name: Option<R<'dwarf>>,
+    /// The namespaces and classes enclosing the function's
+    /// declaration, e.g., `ns::Class`.
+    pub(crate) scope: Option<Rc<str>>,
+    /// The function's lazily constructed fully qualified name.
+    qualified_name: OnceCell<Box<str>>,
     /// The function's range (begin and end address).
     pub(crate) range: Option<gimli::Range>,
+    /// The index of the file in which the function is declared.
+    pub(crate) decl_file: Option<u64>,
+    /// The line at which the function is declared.
+    pub(crate) decl_line: Option<u32>,
     /// List of inlined function calls.
     pub(super) inlined_functions: OnceCell<InlinedFunctions<'dwarf>>,
 }
@@ -254,7 +381,12 @@ impl Debug for Function<'_> {
         let Self {
             dw_die_offset,
             name,
+            short_name: _,
+            scope,
+            qualified_name: _,
             range,
+            decl_file,
+            decl_line,
             inlined_functions: _,
         } = self;
 
@@ -267,7 +399,10 @@ impl Debug for Function<'_> {
                     None => &name,
                 },
             )
+            .field("scope", scope)
             .field("range", range)
+            .field("decl_file", decl_file)
+            .field("decl_line", decl_line)
             .finish()
     }
 }
@@ -288,62 +423,133 @@ impl<'dwarf> Functions<'dwarf> {
     ) -> Result<Self, Error> {
         let mut functions = Vec::new();
         let mut addresses = Vec::new();
+        // The stack of namespace and class scopes enclosing the entry
+        // currently being parsed, along with the depth at which each was
+        // entered.
+        let mut scopes = Vec::<(isize, Rc<str>)>::new();
+        // Unqualified names and scopes of the subprograms declared so
+        // far, for resolving `DW_AT_specification` and
+        // `DW_AT_abstract_origin` references within the unit.
+        let mut decls = HashMap::<_, (Option<R<'dwarf>>, Option<Rc<str>>)>::new();
         let mut entries = unit.entries_raw(None)?;
         while !entries.is_empty() {
+            let depth = entries.next_depth();
             let dw_die_offset = entries.next_offset();
             if let Some(abbrev) = entries.read_abbreviation()? {
-                if abbrev.tag() == gimli::DW_TAG_subprogram {
+                while matches!(scopes.last(), Some((scope_depth, _)) if *scope_depth >= depth) {
+                    let _scope = scopes.pop();
+                }
+
+                if is_scope_tag(abbrev.tag()) {
+                    let mut scope_name = None;
+                    for spec in abbrev.attributes() {
+                        let attr = entries.read_attribute(*spec)?;
+                        if attr.name() == gimli::DW_AT_name {
+                            scope_name = unit.attr_string(attr.value()).ok();
+                        }
+                    }
+
+                    if abbrev.has_children() {
+                        let parent = scopes
+                            .last()
+                            .map(|(_depth, scope)| scope)
+                            .filter(|scope| !scope.is_empty());
+                        let scope_name = match scope_name {
+                            Some(name) => Some(name.to_string_lossy().into_owned()),
+                            None if abbrev.tag() == gimli::DW_TAG_namespace => {
+                                Some("(anonymous namespace)".to_string())
+                            }
+                            None => None,
+                        };
+                        let scope = match (parent, scope_name) {
+                            (Some(parent), Some(name)) => Rc::from(format!("{parent}::{name}")),
+                            (None, Some(name)) => Rc::from(name),
+                            (Some(parent), None) => parent.clone(),
+                            (None, None) => Rc::from(""),
+                        };
+                        let () = scopes.push((depth, scope));
+                    }
+                } else if abbrev.tag() == gimli::DW_TAG_subprogram {
                     let mut name = None;
+                    let mut short_name = None;
+                    let mut decl = None;
                     let mut ranges = RangeAttributes::default();
+                    let mut decl_file = None;
+                    let mut decl_line = None;
                     for spec in abbrev.attributes() {
-                        match entries.read_attribute(*spec) {
-                            Ok(ref attr) => {
-                                match attr.name() {
-                                    gimli::DW_AT_linkage_name | gimli::DW_AT_MIPS_linkage_name => {
-                                        if let Ok(val) = unit.attr_string(attr.value()) {
-                                            name = Some(val);
-                                        }
-                                    }
-                                    gimli::DW_AT_name => {
-                                        if name.is_none() {
-                                            name = unit.attr_string(attr.value()).ok();
-                                        }
-                                    }
-                                    gimli::DW_AT_abstract_origin | gimli::DW_AT_specification => {
-                                        if name.is_none() {
-                                            name = name_attr(attr.value(), unit, units, 16)?;
-                                        }
-                                    }
-                                    gimli::DW_AT_low_pc => match attr.value() {
-                                        gimli::AttributeValue::Addr(val) => {
-                                            ranges.low_pc = Some(val)
-                                        }
-                                        gimli::AttributeValue::DebugAddrIndex(index) => {
-                                            ranges.low_pc = Some(unit.address(index)?);
-                                        }
-                                        _ => {}
-                                    },
-                                    gimli::DW_AT_high_pc => match attr.value() {
-                                        gimli::AttributeValue::Addr(val) => {
-                                            ranges.high_pc = Some(val)
-                                        }
-                                        gimli::AttributeValue::DebugAddrIndex(index) => {
-                                            ranges.high_pc = Some(unit.address(index)?);
-                                        }
-                                        gimli::AttributeValue::Udata(val) => {
-                                            ranges.size = Some(val)
-                                        }
-                                        _ => {}
-                                    },
-                                    gimli::DW_AT_ranges => {
-                                        ranges.ranges_offset =
-                                            unit.attr_ranges_offset(attr.value())?;
+                        let attr = entries.read_attribute(*spec)?;
+                        match attr.name() {
+                            gimli::DW_AT_linkage_name | gimli::DW_AT_MIPS_linkage_name => {
+                                if let Ok(val) = unit.attr_string(attr.value()) {
+                                    name = Some(val);
+                                }
+                            }
+                            gimli::DW_AT_name => {
+                                short_name = unit.attr_string(attr.value()).ok();
+                                if name.is_none() {
+                                    name = short_name;
+                                }
+                            }
+                            gimli::DW_AT_abstract_origin | gimli::DW_AT_specification => {
+                                if let gimli::AttributeValue::UnitRef(offset) = attr.value() {
+                                    decl = decls.get(&offset).cloned();
+                                }
+                                if name.is_none() {
+                                    name = name_attr(attr.value(), unit, units, 16)?;
+                                }
+                            }
+                            gimli::DW_AT_low_pc => match attr.value() {
+                                gimli::AttributeValue::Addr(val) => ranges.low_pc = Some(val),
+                                gimli::AttributeValue::DebugAddrIndex(index) => {
+                                    ranges.low_pc = Some(unit.address(index)?);
+                                }
+                                _ => {}
+                            },
+                            gimli::DW_AT_high_pc => match attr.value() {
+                                gimli::AttributeValue::Addr(val) => ranges.high_pc = Some(val),
+                                gimli::AttributeValue::DebugAddrIndex(index) => {
+                                    ranges.high_pc = Some(unit.address(index)?);
+                                }
+                                gimli::AttributeValue::Udata(val) => ranges.size = Some(val),
+                                _ => {}
+                            },
+                            gimli::DW_AT_ranges => {
+                                ranges.ranges_offset = unit.attr_ranges_offset(attr.value())?;
+                            }
+                            gimli::DW_AT_decl_file => {
+                                // See `InlinedFunction::parse` for
+                                // details on file index 0 handling.
+                                if let gimli::AttributeValue::FileIndex(fi) = attr.value() {
+                                    if fi > 0 || unit.header.version() >= 5 {
+                                        decl_file = Some(fi);
                                     }
-                                    _ => {}
-                                };
+                                }
+                            }
+                            gimli::DW_AT_decl_line => {
+                                decl_line = attr.udata_value().and_then(|l| u32::try_from(l).ok());
                             }
-                            Err(e) => return Err(e),
+                            _ => {}
+                        };
+                    }
+
+                    // An out-of-line definition refers to its declaration
+                    // (e.g., inside of a class), which determines the
+                    // scope the function belongs to.
+                    let scope = match decl {
+                        Some((decl_name, decl_scope)) => {
+                            if short_name.is_none() {
+                                short_name = decl_name;
+                            }
+                            decl_scope
                         }
+                        None => scopes
+                            .last()
+                            .map(|(_depth, scope)| scope.clone())
+                            .filter(|scope| !scope.is_empty()),
+                    };
+
+                    if scope.is_some() || short_name.is_some() {
+                        let _prev = decls.insert(dw_die_offset, (short_name, scope.clone()));
                     }
 
                     let function_index = functions.len();
@@ -358,7 +564,12 @@ impl<'dwarf> Functions<'dwarf> {
                         let function = Function {
                             dw_die_offset,
                             name,
+                            short_name,
+                            scope,
+                            qualified_name: OnceCell::new(),
                             range: ranges.bounds(),
+                            decl_file,
+                            decl_line,
                             inlined_functions: OnceCell::new(),
                         };
                         functions.push(function);
@@ -398,6 +609,18 @@ impl<'dwarf> Functions<'dwarf> {
         Ok(())
     }
 
+    /// Retrieve all address ranges of the function at `function`, an
+    /// index into `functions`.
+    pub(crate) fn function_ranges(
+        &self,
+        function: usize,
+    ) -> impl Iterator<Item = gimli::Range> + '_ {
+        self.addresses
+            .iter()
+            .filter(move |address| address.function == function)
+            .map(|address| address.range)
+    }
+
     pub(crate) fn find_address(&self, probe: u64) -> Option<usize> {
         self.addresses
             .binary_search_by(|address| {
@@ -414,6 +637,35 @@ impl<'dwarf> Functions<'dwarf> {
 }
 
 impl<'dwarf> Function<'dwarf> {
+    /// Retrieve the function's name, qualified with the namespaces and
+    /// classes it is declared in (e.g., `ns::Class::method`).
+    ///
+    /// `None` is returned if the function does not have a `DW_AT_name`
+    /// attribute.
+    pub(crate) fn qualified_name(&self) -> Result<Option<&str>, Error> {
+        let short_name = if let Some(short_name) = &self.short_name {
+            short_name
+        } else {
+            return Ok(None)
+        };
+
+        match &self.scope {
+            Some(scope) => {
+                let name = self.qualified_name.get_or_try_init(|| {
+                    let short_name = short_name.to_string()?;
+                    Result::<_, Error>::Ok(Box::from(format!("{scope}::{short_name}")))
+                })?;
+                Ok(Some(name))
+            }
+            None => short_name.to_string().map(Some),
+        }
+    }
+
+    /// Parse the children of the entry at `depth`, collecting inlined
+    /// functions.
+    ///
+    /// The returned flag indicates whether any inlined functions were
+    /// skipped because they were nested too deeply.
     fn parse_children(
         entries: &mut gimli::EntriesRaw<'_, '_, R<'dwarf>>,
         depth: isize,
@@ -422,19 +674,24 @@ impl<'dwarf> Function<'dwarf> {
         inlined_functions: &mut Vec<InlinedFunction<'dwarf>>,
         inlined_addresses: &mut Vec<InlinedFunctionAddress>,
         inlined_depth: usize,
-    ) -> Result<(), Error> {
+    ) -> Result<bool, Error> {
+        let mut truncated = false;
         loop {
             let next_depth = entries.next_depth();
             if next_depth <= depth {
-                return Ok(())
+                return Ok(truncated)
             }
             if let Some(abbrev) = entries.read_abbreviation()? {
                 match abbrev.tag() {
                     gimli::DW_TAG_subprogram => {
                         Function::skip(entries, abbrev, next_depth)?;
                     }
+                    gimli::DW_TAG_inlined_subroutine if inlined_depth >= MAX_INLINED_DEPTH => {
+                        Function::skip(entries, abbrev, next_depth)?;
+                        truncated = true;
+                    }
                     gimli::DW_TAG_inlined_subroutine => {
-                        InlinedFunction::parse(
+                        truncated |= InlinedFunction::parse(
                             entries,
                             abbrev,
                             next_depth,
@@ -490,75 +747,73 @@ impl<'dwarf> InlinedFunction<'dwarf> {
         inlined_functions: &mut Vec<InlinedFunction<'dwarf>>,
         inlined_addresses: &mut Vec<InlinedFunctionAddress>,
         inlined_depth: usize,
-    ) -> Result<(), Error> {
+    ) -> Result<bool, Error> {
         let mut ranges = RangeAttributes::default();
         let mut name = None;
         let mut call_file = None;
         let mut call_line = 0;
         let mut call_column = 0;
         for spec in abbrev.attributes() {
-            match entries.read_attribute(*spec) {
-                Ok(ref attr) => match attr.name() {
-                    gimli::DW_AT_low_pc => match attr.value() {
-                        gimli::AttributeValue::Addr(val) => ranges.low_pc = Some(val),
-                        gimli::AttributeValue::DebugAddrIndex(index) => {
-                            ranges.low_pc = Some(unit.address(index)?);
-                        }
-                        _ => {}
-                    },
-                    gimli::DW_AT_high_pc => match attr.value() {
-                        gimli::AttributeValue::Addr(val) => ranges.high_pc = Some(val),
-                        gimli::AttributeValue::DebugAddrIndex(index) => {
-                            ranges.high_pc = Some(unit.address(index)?);
-                        }
-                        gimli::AttributeValue::Udata(val) => ranges.size = Some(val),
-                        _ => {}
-                    },
-                    gimli::DW_AT_ranges => {
-                        ranges.ranges_offset = unit.attr_ranges_offset(attr.value())?;
+            let attr = entries.read_attribute(*spec)?;
+            match attr.name() {
+                gimli::DW_AT_low_pc => match attr.value() {
+                    gimli::AttributeValue::Addr(val) => ranges.low_pc = Some(val),
+                    gimli::AttributeValue::DebugAddrIndex(index) => {
+                        ranges.low_pc = Some(unit.address(index)?);
                     }
-                    gimli::DW_AT_linkage_name | gimli::DW_AT_MIPS_linkage_name => {
-                        if let Ok(val) = unit.attr_string(attr.value()) {
-                            name = Some(val);
-                        }
-                    }
-                    gimli::DW_AT_name => {
-                        if name.is_none() {
-                            name = unit.attr_string(attr.value()).ok();
-                        }
+                    _ => {}
+                },
+                gimli::DW_AT_high_pc => match attr.value() {
+                    gimli::AttributeValue::Addr(val) => ranges.high_pc = Some(val),
+                    gimli::AttributeValue::DebugAddrIndex(index) => {
+                        ranges.high_pc = Some(unit.address(index)?);
                     }
-                    gimli::DW_AT_abstract_origin | gimli::DW_AT_specification => {
-                        if name.is_none() {
-                            name = name_attr(attr.value(), unit, units, 16)?;
-                        }
+                    gimli::AttributeValue::Udata(val) => ranges.size = Some(val),
+                    _ => {}
+                },
+                gimli::DW_AT_ranges => {
+                    ranges.ranges_offset = unit.attr_ranges_offset(attr.value())?;
+                }
+                gimli::DW_AT_linkage_name | gimli::DW_AT_MIPS_linkage_name => {
+                    if let Ok(val) = unit.attr_string(attr.value()) {
+                        name = Some(val);
                     }
-                    gimli::DW_AT_call_file => {
-                        // There is a spec issue [1] with how DW_AT_call_file is
-                        // specified in DWARF 5. Before, a file index of 0 would
-                        // indicate no source file, however in DWARF 5 this could
-                        // be a valid index into the file table.
-                        //
-                        // Implementations such as LLVM generates a file index
-                        // of 0 when DWARF 5 is used.
-                        //
-                        // Thus, if we see a version of 5 or later, treat a file
-                        // index of 0 as such.
-                        // [1]: http://wiki.dwarfstd.org/index.php?title=DWARF5_Line_Table_File_Numbers
-                        if let gimli::AttributeValue::FileIndex(fi) = attr.value() {
-                            if fi > 0 || unit.header.version() >= 5 {
-                                call_file = Some(fi);
-                            }
-                        }
+                }
+                gimli::DW_AT_name => {
+                    if name.is_none() {
+                        name = unit.attr_string(attr.value()).ok();
                     }
-                    gimli::DW_AT_call_line => {
-                        call_line = attr.udata_value().unwrap_or(0) as u32;
+                }
+                gimli::DW_AT_abstract_origin | gimli::DW_AT_specification => {
+                    if name.is_none() {
+                        name = name_attr(attr.value(), unit, units, 16)?;
                     }
-                    gimli::DW_AT_call_column => {
-                        call_column = attr.udata_value().unwrap_or(0) as u32;
+                }
+                gimli::DW_AT_call_file => {
+                    // There is a spec issue [1] with how DW_AT_call_file is
+                    // specified in DWARF 5. Before, a file index of 0 would
+                    // indicate no source file, however in DWARF 5 this could
+                    // be a valid index into the file table.
+                    //
+                    // Implementations such as LLVM generates a file index
+                    // of 0 when DWARF 5 is used.
+                    //
+                    // Thus, if we see a version of 5 or later, treat a file
+                    // index of 0 as such.
+                    // [1]: http://wiki.dwarfstd.org/index.php?title=DWARF5_Line_Table_File_Numbers
+                    if let gimli::AttributeValue::FileIndex(fi) = attr.value() {
+                        if fi > 0 || unit.header.version() >= 5 {
+                            call_file = Some(fi);
+                        }
                     }
-                    _ => {}
-                },
-                Err(e) => return Err(e),
+                }
+                gimli::DW_AT_call_line => {
+                    call_line = attr.udata_value().unwrap_or(0) as u32;
+                }
+                gimli::DW_AT_call_column => {
+                    call_column = attr.udata_value().unwrap_or(0) as u32;
+                }
+                _ => {}
             }
         }
 
@@ -612,7 +867,12 @@ mod tests {
         let func = Function {
             dw_die_offset: gimli::UnitOffset(24),
             name: None,
+            short_name: None,
+            scope: None,
+            qualified_name: OnceCell::new(),
             range: None,
+            decl_file: None,
+            decl_line: None,
             inlined_functions: OnceCell::new(),
         };
         assert_ne!(format!("{func:?}"), "");
@@ -59,8 +59,10 @@ fn render_file<'dwarf>(
     };
 
     // The directory index 0 is defined to correspond to the compilation unit
-    // directory.
-    let dir = if file.directory_index() != 0 {
+    // directory. Starting with DWARF 5 it is an explicit entry in the
+    // directory table (which, if relative, is still relative to the
+    // compilation directory).
+    let dir = if file.directory_index() != 0 || header.version() >= 5 {
         if let Some(directory) = file.directory(header) {
             let d = unit.attr_string(directory)?;
             path_push(dir, bytes_to_path(d.slice())?)
@@ -68,7 +70,7 @@ fn render_file<'dwarf>(
             Cow::default()
         }
     } else {
-        Cow::default()
+        Cow::Borrowed(dir)
     };
 
     let f = unit.attr_string(file.path_name())?;
@@ -77,12 +79,15 @@ fn render_file<'dwarf>(
 }
 
 
+/// A contiguous sequence of rows of a line table.
 pub(crate) struct LineSequence {
     pub(crate) start: u64,
     pub(crate) end: u64,
     pub(crate) rows: Box<[LineRow]>,
 }
 
+/// A single row of a line table, mapping an address to a source code
+/// location.
 pub(crate) struct LineRow {
     pub(crate) address: u64,
     pub(crate) file_index: u64,
@@ -90,12 +95,41 @@ pub(crate) struct LineRow {
     pub(crate) column: u32,
 }
 
+/// The line table of a compilation unit, indexed for address lookups.
+///
+/// The table is built once per unit and cached, so that queries don't
+/// have to re-run the line number program. Sequences are sorted by
+/// start address and rows within a sequence by address, allowing for
+/// binary searching of both.
 pub(crate) struct Lines<'dwarf> {
     pub(crate) files: Box<[(Cow<'dwarf, Path>, &'dwarf OsStr)]>,
     pub(crate) sequences: Box<[LineSequence]>,
 }
 
 impl<'dwarf> Lines<'dwarf> {
+    /// Determine the address ranges covered by the sequences of a line
+    /// program, without building up the line table itself.
+    pub(crate) fn parse_ranges(
+        ilnp: gimli::IncompleteLineProgram<R<'dwarf>, <R<'dwarf> as gimli::Reader>::Offset>,
+    ) -> Result<Vec<gimli::Range>, gimli::Error> {
+        let mut ranges = Vec::new();
+        let mut start = None;
+        let mut rows = ilnp.rows();
+        while let Some((_, row)) = rows.next_row()? {
+            if row.end_sequence() {
+                if let Some(begin) = start.take() {
+                    let () = ranges.push(gimli::Range {
+                        begin,
+                        end: row.address(),
+                    });
+                }
+            } else if start.is_none() {
+                start = Some(row.address());
+            }
+        }
+        Ok(ranges)
+    }
+
     pub(crate) fn parse(
         unit: gimli::UnitRef<'_, R<'dwarf>>,
         ilnp: gimli::IncompleteLineProgram<R<'dwarf>, <R<'dwarf> as gimli::Reader>::Offset>,
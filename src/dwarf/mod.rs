@@ -5,8 +5,13 @@ mod location;
 mod range;
 mod reader;
 mod resolver;
+mod split;
 mod unit;
 mod units;
+mod variable;
 
 
 pub(crate) use self::resolver::DwarfResolver;
+pub(crate) use self::resolver::InlinedRange;
+pub use self::resolver::LineTableRow;
+pub(crate) use self::units::Units;
@@ -15,17 +15,31 @@ type Endianess = gimli::BigEndian;
 pub(crate) type R<'dat> = EndianSlice<'dat, Endianess>;
 
 
-pub(super) fn load_section(parser: &ElfParser, id: SectionId) -> Result<R<'_>> {
+/// Create a reader for the provided DWARF section data.
+pub(super) fn reader(data: &[u8]) -> R<'_> {
+    #[cfg(target_endian = "little")]
+    let reader = EndianSlice::new(data, gimli::LittleEndian);
+    #[cfg(target_endian = "big")]
+    let reader = EndianSlice::new(data, gimli::BigEndian);
+    reader
+}
+
+
+/// Retrieve the data of the DWARF section with the given ID from an
+/// ELF file.
+pub(super) fn section_data(parser: &ElfParser, id: SectionId) -> Result<&[u8]> {
     let result = parser.find_section(id.name())?;
     let data = match result {
         Some(idx) => parser.section_data(idx)?,
         // Make sure to return empty data if a section does not exist.
         None => &[],
     };
+    Ok(data)
+}
 
-    #[cfg(target_endian = "little")]
-    let reader = EndianSlice::new(data, gimli::LittleEndian);
-    #[cfg(target_endian = "big")]
-    let reader = EndianSlice::new(data, gimli::BigEndian);
-    Ok(reader)
+
+#[cfg(test)]
+pub(super) fn load_section(parser: &ElfParser, id: SectionId) -> Result<R<'_>> {
+    let data = section_data(parser, id)?;
+    Ok(reader(data))
 }
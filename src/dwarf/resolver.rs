@@ -9,23 +9,28 @@ use std::mem;
 use std::mem::swap;
 use std::ops::ControlFlow;
 use std::ops::Deref as _;
+use std::ops::Range;
 use std::path::Path;
 use std::path::PathBuf;
 use std::rc::Rc;
 
 use gimli::AbbreviationsCacheStrategy;
 use gimli::Dwarf;
+use gimli::SectionId;
 
 use crate::elf::ElfParser;
 #[cfg(test)]
 use crate::elf::DEFAULT_DEBUG_DIRS;
 use crate::error::IntoCowStr;
+use crate::file_cache::open_file;
 use crate::inspect::FindAddrOpts;
 use crate::inspect::ForEachFn;
 use crate::inspect::Inspect;
 use crate::inspect::SymInfo;
+use crate::inspect::TargetAccess;
 use crate::log::debug;
 use crate::log::warn;
+use crate::normalize::buildid::build_id_debug_path;
 use crate::symbolize::CodeInfo;
 use crate::symbolize::FindSymOpts;
 use crate::symbolize::InlinedFn;
@@ -48,6 +53,9 @@ use super::location::Location;
 use super::reader;
 use super::unit::Unit;
 use super::units::Units;
+use super::variable::eval_location;
+use super::variable::VarLocation;
+use super::variable::Variable;
 
 
 impl ErrorExt for gimli::Error {
@@ -100,6 +108,13 @@ fn find_debug_file(file: &OsStr, linker: Option<&Path>, debug_dirs: &[PathBuf])
 
     for path in it {
         if path.exists() {
+            // A file can't be its own debug file. Binaries stored below
+            // `.build-id/` directories commonly have a debug link named
+            // like themselves, though.
+            if canonical_linker.is_some() && path.canonicalize().ok() == canonical_linker {
+                continue
+            }
+
             debug!("found debug info at `{}`", path.display());
             return Some(path)
         }
@@ -112,16 +127,75 @@ fn find_debug_file(file: &OsStr, linker: Option<&Path>, debug_dirs: &[PathBuf])
 }
 
 
+/// Find a debug file for the binary with the given build ID in the
+/// `.build-id` directories below the provided debug directories.
+///
+/// If `strict` is `true`, debug files are opened subject to the checks
+/// of strict file access.
+///
+/// # Notes
+/// This function ignores any errors encountered.
+fn find_debug_file_by_build_id(
+    build_id: &[u8],
+    debug_dirs: &[PathBuf],
+    strict: bool,
+) -> Option<ElfParser> {
+    for dir in debug_dirs {
+        let path = build_id_debug_path(dir, build_id)?;
+        if !path.exists() {
+            continue
+        }
+
+        let result = open_file(&path, strict).and_then(|file| ElfParser::open_file(&file, &path));
+        let parser = match result {
+            Ok(parser) => parser,
+            Err(err) => {
+                warn!("failed to open debug file `{}`: {err}", path.display());
+                continue
+            }
+        };
+
+        match parser.build_id() {
+            Ok(Some(id)) if id == build_id => {
+                debug!("found debug info by build ID at `{}`", path.display());
+                return Some(parser)
+            }
+            _ => warn!(
+                "debug file `{}` does not have expected build ID; ignoring",
+                path.display()
+            ),
+        }
+    }
+    None
+}
+
+
+/// Find the file containing separate debug information for the binary
+/// represented by `parser`, if any.
+///
+/// A debug file matching the binary's build ID is preferred. If none
+/// is found, the binary's debug link, if present, is followed. If
+/// `strict` is `true`, debug files are opened subject to the checks of
+/// strict file access.
 fn try_deref_debug_link(
     parser: &ElfParser,
     debug_dirs: &[PathBuf],
+    strict: bool,
 ) -> Result<Option<Rc<ElfParser>>> {
+    if let Ok(Some(build_id)) = parser.build_id() {
+        if let Some(dst_parser) = find_debug_file_by_build_id(build_id, debug_dirs, strict) {
+            return Ok(Some(Rc::new(dst_parser)))
+        }
+    }
+
     if let Some((file, checksum)) = read_debug_link(parser)? {
         match find_debug_file(file, parser.path(), debug_dirs) {
             Some(path) => {
-                let mmap = Mmap::builder().open(&path).with_context(|| {
-                    format!("failed to open debug link destination `{}`", path.display())
-                })?;
+                let mmap = open_file(&path, strict)
+                    .and_then(|file| Mmap::map(&file))
+                    .with_context(|| {
+                        format!("failed to open debug link destination `{}`", path.display())
+                    })?;
                 let crc = debug_link_crc32(&mmap);
                 if crc != checksum {
                     return Err(Error::with_invalid_data(format!(
@@ -142,6 +216,56 @@ fn try_deref_debug_link(
 }
 
 
+/// A row of a DWARF line table, as emitted by the line number program.
+#[derive(Clone, Debug, PartialEq)]
+pub struct LineTableRow<'src> {
+    /// The address of the first instruction that the row describes.
+    pub addr: Addr,
+    /// Source code location information of the instruction.
+    ///
+    /// This member is `None` if the row references an invalid file.
+    pub code_info: Option<CodeInfo<'src>>,
+    /// Whether the instruction is a recommended breakpoint location
+    /// (i.e., the beginning of a statement).
+    pub is_stmt: bool,
+    /// Whether the instruction is the beginning of a basic block.
+    pub basic_block: bool,
+    /// Whether the row terminates a sequence of instructions.
+    ///
+    /// The address of such a row refers to the first byte after the
+    /// sequence.
+    pub end_sequence: bool,
+    /// Whether the instruction is one where execution should be
+    /// suspended for a breakpoint at the entry of a function.
+    pub prologue_end: bool,
+    /// Whether the instruction is one where execution should be
+    /// suspended for a breakpoint just prior to the exit of a
+    /// function.
+    pub epilogue_begin: bool,
+    /// The struct is non-exhaustive and open to extension.
+    #[doc(hidden)]
+    pub _non_exhaustive: (),
+}
+
+
+/// An address range covered by a function call inlined into another
+/// function.
+#[derive(Debug)]
+pub(crate) struct InlinedRange<'src> {
+    /// The range of addresses covered.
+    pub range: Range<Addr>,
+    /// The nesting depth of the call, with zero representing a call
+    /// made directly by the containing function.
+    pub depth: usize,
+    /// An identifier of the call, which is shared by all of its ranges.
+    pub call: usize,
+    /// The name of the inlined function.
+    pub name: &'src str,
+    /// The source code location of the call, if known.
+    pub code_info: Option<CodeInfo<'src>>,
+}
+
+
 /// DwarfResolver provides abilities to query DWARF information of binaries.
 pub(crate) struct DwarfResolver {
     /// The lazily parsed compilation units of the DWARF file.
@@ -155,6 +279,8 @@ pub(crate) struct DwarfResolver {
     /// If the source file contains a valid debug link, this parser
     /// represents it.
     linkee_parser: Option<Rc<ElfParser>>,
+    /// Whether to report fully qualified function names.
+    qualified_names: bool,
 }
 
 impl DwarfResolver {
@@ -163,11 +289,26 @@ impl DwarfResolver {
         &self.parser
     }
 
+    /// Retrieve the `ElfParser` for the file containing the DWARF
+    /// information being used, which may be a debug link target.
+    pub(crate) fn debug_parser(&self) -> &ElfParser {
+        self.linkee_parser.as_ref().unwrap_or(&self.parser)
+    }
+
+    /// Eagerly index the DWARF debug information, which otherwise
+    /// happens lazily on first symbolization.
+    pub(crate) fn preload(&self) -> Result<()> {
+        let () = self.units.preload()?;
+        Ok(())
+    }
+
     pub(crate) fn from_parser(
         parser: Rc<ElfParser>,
         debug_dirs: &[PathBuf],
+        qualified_names: bool,
+        strict: bool,
     ) -> Result<Self, Error> {
-        let linkee_parser = try_deref_debug_link(&parser, debug_dirs)?;
+        let linkee_parser = try_deref_debug_link(&parser, debug_dirs, strict)?;
 
         // SAFETY: We own the `ElfParser` and make sure that it stays
         //         around while the `Units` object uses it. As such, it
@@ -177,20 +318,15 @@ impl DwarfResolver {
                 linkee_parser.as_ref().unwrap_or(&parser).deref(),
             )
         };
-        let mut load_section = |section| reader::load_section(static_parser, section);
-        let mut dwarf = Dwarf::load(&mut load_section)?;
-        // Cache abbreviations (which will cause them to be
-        // automatically reused across compilation units), which can
-        // speed up parsing of debug information potentially
-        // dramatically, depending on debug information layout and how
-        // much effort the linker spent on optimizing it.
-        let () = dwarf.populate_abbreviations_cache(AbbreviationsCacheStrategy::Duplicates);
-
-        let units = Units::parse(dwarf)?;
+        let mut units = Units::load(static_parser.path(), |section| {
+            reader::section_data(static_parser, section)
+        })?;
+        let () = units.enable_strict_file_access(strict);
         let slf = Self {
             units,
             parser,
             linkee_parser,
+            qualified_names,
         };
         Ok(slf)
     }
@@ -206,18 +342,20 @@ impl DwarfResolver {
             .iter()
             .map(PathBuf::from)
             .collect::<Vec<_>>();
-        Self::from_parser(Rc::new(parser), debug_dirs.as_slice())
+        Self::from_parser(Rc::new(parser), debug_dirs.as_slice(), false, false)
     }
 
     /// Try converting a `Function` into a `SymInfo`.
     ///
     /// # Notes
     /// This method only returns `None` if `function` does not have the `name`
-    /// attribute set.
+    /// attribute set or if it is not declared in the file requested by
+    /// `opts`.
     fn function_to_sym_info<'slf>(
         &'slf self,
-        function: &'slf Function,
-        offset_in_file: bool,
+        function: &'slf Function<'static>,
+        unit: &'slf Unit<'static>,
+        opts: &FindAddrOpts,
     ) -> Result<Option<SymInfo<'slf>>> {
         let name = if let Some(name) = function.name {
             name.to_string().unwrap()
@@ -235,43 +373,190 @@ impl DwarfResolver {
             .and_then(|range| range.end.checked_sub(range.begin))
             .map(|size| usize::try_from(size).unwrap_or(usize::MAX))
             .unwrap_or(0);
+        let code_info = if opts.code_info || opts.decl_file.is_some() {
+            self.units
+                .find_decl_location(function, unit)?
+                .map(decl_code_info)
+        } else {
+            None
+        };
         let info = SymInfo {
             name: Cow::Borrowed(name),
             addr,
             size: Some(size),
             sym_type: SymType::Function,
-            file_offset: offset_in_file
+            file_offset: opts
+                .offset_in_file
                 .then(|| self.parser.find_file_offset(addr))
                 .transpose()?
                 .flatten(),
             obj_file_name: self.parser.path().map(Cow::Borrowed),
+            code_info,
         };
-        Ok(Some(info))
+        Ok(filter_decl_file(info, opts))
+    }
+
+    /// Try converting a `Variable` into a `SymInfo`.
+    ///
+    /// # Notes
+    /// This method returns `None` if `variable` does not reside at a
+    /// fixed address or is not declared in the file requested by
+    /// `opts`.
+    fn variable_to_sym_info<'slf>(
+        &'slf self,
+        variable: &'slf Variable<'static>,
+        unit: &'slf Unit<'static>,
+        opts: &FindAddrOpts,
+    ) -> Result<Option<SymInfo<'slf>>> {
+        match variable.location {
+            VarLocation::Addr(addr) => self.variable_to_sym_info_at(variable, unit, addr, opts),
+            VarLocation::Expr(..) => Ok(None),
+        }
+    }
+
+    /// Try converting a `Variable` residing at `addr` into a `SymInfo`.
+    ///
+    /// # Notes
+    /// This method only returns `None` if `variable` is not declared in
+    /// the file requested by `opts`.
+    fn variable_to_sym_info_at<'slf>(
+        &'slf self,
+        variable: &'slf Variable<'static>,
+        unit: &'slf Unit<'static>,
+        addr: Addr,
+        opts: &FindAddrOpts,
+    ) -> Result<Option<SymInfo<'slf>>> {
+        let name = variable.name.to_string()?;
+        let size = variable
+            .size
+            .map(|size| usize::try_from(size).unwrap_or(usize::MAX));
+        let code_info = if opts.code_info || opts.decl_file.is_some() {
+            self.units
+                .find_var_decl_location(variable, unit)?
+                .map(decl_code_info)
+        } else {
+            None
+        };
+        let info = SymInfo {
+            name: Cow::Borrowed(name),
+            addr,
+            size,
+            sym_type: SymType::Variable,
+            file_offset: opts
+                .offset_in_file
+                .then(|| self.parser.find_file_offset(addr))
+                .transpose()?
+                .flatten(),
+            obj_file_name: self.parser.path().map(Cow::Borrowed),
+            code_info,
+        };
+        Ok(filter_decl_file(info, opts))
+    }
+}
+
+
+/// Apply the declaration file filter of `opts` to `info`.
+///
+/// `None` is returned if `info` does not match the filter. Declaration
+/// information is only retained if requested.
+fn filter_decl_file<'src>(mut info: SymInfo<'src>, opts: &FindAddrOpts) -> Option<SymInfo<'src>> {
+    if let Some(path) = &opts.decl_file {
+        if !info.is_declared_in(path) {
+            return None
+        }
+    }
+
+    if !opts.code_info {
+        info.code_info = None;
+    }
+    Some(info)
+}
+
+
+/// Convert the declaration location of a symbol into a [`CodeInfo`].
+fn decl_code_info(location: Location<'_>) -> CodeInfo<'_> {
+    let Location {
+        dir,
+        file,
+        line,
+        column: _,
+    } = location;
+
+    CodeInfo {
+        dir: Some(Cow::Borrowed(dir)),
+        file: Cow::Borrowed(file),
+        line,
+        column: None,
+        _non_exhaustive: (),
+    }
+}
+
+impl DwarfResolver {
+    /// Invoke `f` for each line table row with an address in `range`.
+    pub(crate) fn for_each_line_row(
+        &self,
+        range: Range<Addr>,
+        f: &mut dyn FnMut(&LineTableRow<'_>) -> ControlFlow<()>,
+    ) -> Result<()> {
+        let () = self.units.for_each_line_row(range, |row, file| {
+            let code_info = file.map(|(dir, file)| CodeInfo {
+                dir: Some(Cow::Borrowed(dir.as_ref())),
+                file: Cow::Borrowed(*file),
+                line: row
+                    .line()
+                    .map(|line| u32::try_from(line.get()).unwrap_or(u32::MAX)),
+                column: match row.column() {
+                    gimli::ColumnType::LeftEdge => None,
+                    gimli::ColumnType::Column(col) => {
+                        Some(u16::try_from(col.get()).unwrap_or(u16::MAX))
+                    }
+                },
+                _non_exhaustive: (),
+            });
+
+            let row = LineTableRow {
+                addr: row.address(),
+                code_info,
+                is_stmt: row.is_stmt(),
+                basic_block: row.basic_block(),
+                end_sequence: row.end_sequence(),
+                prologue_end: row.prologue_end(),
+                epilogue_begin: row.epilogue_begin(),
+                _non_exhaustive: (),
+            };
+            f(&row)
+        })?;
+        Ok(())
+    }
+}
+
+impl DwarfResolver {
+    /// Invoke `f` for each address range of a function call inlined
+    /// into the function containing `addr`.
+    pub(crate) fn for_each_inlined_range(
+        &self,
+        addr: Addr,
+        f: &mut dyn FnMut(&InlinedRange<'_>),
+    ) -> Result<()> {
+        let () =
+            self.units
+                .for_each_inlined_range(addr, |range, depth, call, name, location| {
+                    let range = InlinedRange {
+                        range,
+                        depth,
+                        call,
+                        name,
+                        code_info: location.map(decl_code_info),
+                    };
+                    f(&range)
+                })?;
+        Ok(())
     }
 }
 
 impl Symbolize for DwarfResolver {
     fn find_sym(&self, addr: Addr, opts: &FindSymOpts) -> Result<Result<ResolvedSym<'_>, Reason>> {
-        let data = self.units.find_function(addr)?;
-        let mut sym = if let Some((function, unit)) = data {
-            let name = function
-                .name
-                .map(|name| name.to_string())
-                .transpose()?
-                .unwrap_or("");
-            let fn_addr = function.range.map(|range| range.begin).unwrap_or(0);
-            let size = function
-                .range
-                .map(|range| usize::try_from(range.end - range.begin).unwrap_or(usize::MAX));
-            ResolvedSym {
-                name,
-                addr: fn_addr,
-                size,
-                lang: unit.language().into(),
-                code_info: None,
-                inlined: Box::new([]),
-            }
-        } else {
+        self.units.find_sym(addr, opts, self.qualified_names, || {
             // Fall back to checking ELF for the symbol corresponding to
             // the address. This is to mimic behavior of various tools
             // (e.g., `addr2line`). Basically, what can happen is that a
@@ -281,46 +566,53 @@ impl Symbolize for DwarfResolver {
             // symbol, and we amend its information with the source code
             // information from DWARF.
             let parser = self.linkee_parser.as_ref().unwrap_or(&self.parser).deref();
-            match parser.find_sym(addr, opts)? {
-                Ok(sym) => sym,
-                Err(reason) => return Ok(Err(reason)),
-            }
-        };
-
-        let () = self.units.fill_code_info(&mut sym, addr, opts, data)?;
+            parser.find_sym(addr, opts)
+        })
+    }
 
-        Ok(Ok(sym))
+    fn find_code_info(&self, addr: Addr, opts: &FindSymOpts) -> Result<Option<CodeInfo<'_>>> {
+        self.units.find_code_info(addr, opts)
     }
 }
 
 impl Inspect for DwarfResolver {
     /// Find information about a symbol given its name.
-    ///
-    /// # Notes
-    /// - lookup of variables is not currently supported
     fn find_addr<'slf>(&'slf self, name: &str, opts: &FindAddrOpts) -> Result<Vec<SymInfo<'slf>>> {
-        if let SymType::Variable = opts.sym_type {
-            return Err(Error::with_unsupported("not implemented"))
+        let mut syms = Vec::new();
+        if opts.sym_type != SymType::Variable {
+            let () = self.units.find_name(name).try_for_each(|result| {
+                let (function, unit) = result?;
+                // We found the function by name, so it must have the
+                // name attribute set. `function_to_sym_info` may still
+                // filter it out based on its declaration file, though.
+                if let Some(info) = self.function_to_sym_info(function, unit, opts)? {
+                    let () = syms.push(info);
+                }
+                Result::<_, Error>::Ok(())
+            })?;
         }
 
-        let syms = self
-            .units
-            .find_name(name)
-            .map(|result| {
-                match result {
-                    Ok(function) => {
-                        // SANITY: We found the function by name, so it must have the
-                        //         name attribute set. `function_to_sym_info`
-                        //         only returns `None` if no name is present.
-                        let info = self
-                            .function_to_sym_info(function, opts.offset_in_file)?
-                            .unwrap();
-                        Ok(info)
+        if opts.sym_type != SymType::Function {
+            let mut overall_result = Ok(());
+            let () = self.units.for_each_variable(|variable, unit| {
+                if variable.name.slice() != name.as_bytes() {
+                    return ControlFlow::Continue(())
+                }
+
+                match self.variable_to_sym_info(variable, unit, opts) {
+                    Ok(Some(info)) => {
+                        let () = syms.push(info);
+                        ControlFlow::Continue(())
+                    }
+                    Ok(None) => ControlFlow::Continue(()),
+                    Err(err) => {
+                        overall_result = Err(err);
+                        ControlFlow::Break(())
                     }
-                    Err(err) => Err(Error::from(err)),
                 }
-            })
-            .collect::<Result<Vec<_>>>()?;
+            })?;
+            let () = overall_result?;
+        }
 
         if syms.is_empty() {
             let parser = self.linkee_parser.as_ref().unwrap_or(&self.parser).deref();
@@ -331,15 +623,83 @@ impl Inspect for DwarfResolver {
     }
 
     fn for_each(&self, opts: &FindAddrOpts, f: &mut ForEachFn<'_>) -> Result<()> {
-        if let SymType::Variable = opts.sym_type {
-            return Err(Error::with_unsupported("not implemented"))
+        let mut overall_result = Ok(());
+        let mut stop = false;
+
+        if opts.sym_type != SymType::Variable {
+            let () = self.units.for_each_function(|func, unit| {
+                let result = self.function_to_sym_info(func, unit, opts);
+                let flow = match result {
+                    Ok(Some(sym_info)) => f(&sym_info),
+                    Ok(None) => ControlFlow::Continue(()),
+                    Err(err) => {
+                        overall_result = Err(err);
+                        ControlFlow::Break(())
+                    }
+                };
+                stop = flow.is_break();
+                flow
+            })?;
         }
 
+        if !stop && opts.sym_type != SymType::Function {
+            let () = self.units.for_each_variable(|variable, unit| {
+                match self.variable_to_sym_info(variable, unit, opts) {
+                    Ok(Some(sym_info)) => f(&sym_info),
+                    Ok(None) => ControlFlow::Continue(()),
+                    Err(err) => {
+                        overall_result = Err(err);
+                        ControlFlow::Break(())
+                    }
+                }
+            })?;
+        }
+        overall_result
+    }
+
+    fn find_func_ranges(&self, addr: Addr) -> Result<Vec<Range<Addr>>> {
+        if let Some(ranges) = self.units.find_function_ranges(addr)? {
+            return Ok(ranges)
+        }
+
+        let parser = self.linkee_parser.as_ref().unwrap_or(&self.parser).deref();
+        parser.find_func_ranges(addr)
+    }
+
+    fn find_line_addrs(&self, file: &Path, line: u32) -> Result<Vec<Addr>> {
+        let addrs = self.units.find_line_addrs(file, line)?;
+        Ok(addrs)
+    }
+
+    fn find_var_locations<'slf>(
+        &'slf self,
+        name: &str,
+        opts: &FindAddrOpts,
+        target: &dyn TargetAccess,
+    ) -> Result<Vec<SymInfo<'slf>>> {
+        let mut syms = Vec::new();
         let mut overall_result = Ok(());
-        let () = self.units.for_each_function(|func| {
-            let result = self.function_to_sym_info(func, opts.offset_in_file);
+        let () = self.units.for_each_variable(|variable, unit| {
+            if variable.name.slice() != name.as_bytes() {
+                return ControlFlow::Continue(())
+            }
+
+            let result = match variable.location {
+                VarLocation::Addr(addr) => target.relocate(addr).map(Some),
+                VarLocation::Expr(expr) => {
+                    eval_location(unit.dwarf_and_unit(&self.units), expr, target)
+                }
+            }
+            .and_then(|addr| match addr {
+                Some(addr) => self.variable_to_sym_info_at(variable, unit, addr, opts),
+                None => Ok(None),
+            });
+
             match result {
-                Ok(Some(sym_info)) => f(&sym_info),
+                Ok(Some(info)) => {
+                    let () = syms.push(info);
+                    ControlFlow::Continue(())
+                }
                 Ok(None) => ControlFlow::Continue(()),
                 Err(err) => {
                     overall_result = Err(err);
@@ -347,7 +707,8 @@ impl Inspect for DwarfResolver {
                 }
             }
         })?;
-        overall_result
+        let () = overall_result?;
+        Ok(syms)
     }
 }
 
@@ -360,8 +721,135 @@ impl Debug for DwarfResolver {
 
 // Conceptually this block belongs to the `DwarfResolver` type, but because it
 // uses a `Units` object with 'static lifetime we have to impl on `Units`
-// directly.
+// directly. Doing so also makes the functionality available to resolvers
+// for other object file formats carrying DWARF data (e.g., Mach-O).
 impl<'dwarf> Units<'dwarf> {
+    /// Load and parse DWARF debug information, retrieving the data of
+    /// individual sections using `section_data`.
+    ///
+    /// `section_data` should report empty data for sections that do
+    /// not exist. `path` is the path to the file containing the data,
+    /// if any, and is used for locating split DWARF data.
+    pub(crate) fn load<F>(path: Option<&Path>, mut section_data: F) -> Result<Self>
+    where
+        F: FnMut(SectionId) -> Result<&'dwarf [u8]>,
+    {
+        let mut load_section = |section| section_data(section).map(reader::reader);
+        let mut dwarf = Dwarf::load(&mut load_section)?;
+        // Cache abbreviations (which will cause them to be
+        // automatically reused across compilation units), which can
+        // speed up parsing of debug information potentially
+        // dramatically, depending on debug information layout and how
+        // much effort the linker spent on optimizing it.
+        let () = dwarf.populate_abbreviations_cache(AbbreviationsCacheStrategy::Duplicates);
+
+        let mut units = Units::parse(dwarf)?;
+        if let Some(path) = path {
+            let () = units.set_path(path.to_path_buf());
+        }
+        Ok(units)
+    }
+
+    /// Find the symbol containing `addr`, along with its source code
+    /// information as requested by `opts`.
+    ///
+    /// If `qualified_names` is `true`, the reported name is qualified
+    /// with the namespaces and classes the function is declared in, as
+    /// opposed to being the (potentially mangled) linkage name.
+    ///
+    /// If DWARF does not contain a function for `addr`, `fallback` is
+    /// used for looking up the symbol instead, with source code
+    /// information still being taken from DWARF.
+    pub(crate) fn find_sym<'slf, F>(
+        &'slf self,
+        addr: Addr,
+        opts: &FindSymOpts,
+        qualified_names: bool,
+        fallback: F,
+    ) -> Result<Result<ResolvedSym<'slf>, Reason>>
+    where
+        F: FnOnce() -> Result<Result<ResolvedSym<'slf>, Reason>>,
+    {
+        let data = self.find_function(addr)?;
+        let sym = if let Some((function, unit)) = data {
+            let qualified_name = if qualified_names {
+                function.qualified_name()?
+            } else {
+                None
+            };
+            let name = match qualified_name {
+                Some(name) => Some(name),
+                None => function.name.map(|name| name.to_string()).transpose()?,
+            };
+            // A function without a name is not a meaningful symbol. We
+            // treat it as if no function was found, so that we give the
+            // fall back a chance to come up with one.
+            name.map(|name| {
+                let fn_addr = function.range.map(|range| range.begin).unwrap_or(0);
+                let size = function
+                    .range
+                    .map(|range| usize::try_from(range.end - range.begin).unwrap_or(usize::MAX));
+                ResolvedSym {
+                    name,
+                    addr: fn_addr,
+                    size,
+                    lang: unit.language().into(),
+                    code_info: None,
+                    inlined: Box::new([]),
+                    inlined_truncated: false,
+                    section: None,
+                    module: None,
+                }
+            })
+        } else {
+            None
+        };
+
+        let mut sym = if let Some(sym) = sym {
+            sym
+        } else {
+            match fallback()? {
+                Ok(sym) => sym,
+                Err(reason) => return Ok(Err(reason)),
+            }
+        };
+
+        let () = self.fill_code_info(&mut sym, addr, opts, data)?;
+        Ok(Ok(sym))
+    }
+
+    /// Find source code location information for an address, without
+    /// regard to the function (if any) covering it.
+    ///
+    /// `addr` is a normalized address.
+    pub(crate) fn find_code_info(
+        &self,
+        addr: Addr,
+        opts: &FindSymOpts,
+    ) -> Result<Option<CodeInfo<'_>>> {
+        if !opts.code_info() {
+            return Ok(None)
+        }
+
+        let code_info = self.find_location(addr)?.map(|location| {
+            let Location {
+                dir,
+                file,
+                line,
+                column,
+            } = location;
+
+            CodeInfo {
+                dir: Some(Cow::Borrowed(dir)),
+                file: Cow::Borrowed(file),
+                line,
+                column: column.map(|col| col.try_into().unwrap_or(u16::MAX)),
+                _non_exhaustive: (),
+            }
+        });
+        Ok(code_info)
+    }
+
     /// Fill in source code information for an address to the provided
     /// `ResolvedSym`.
     ///
@@ -400,7 +888,10 @@ impl<'dwarf> Units<'dwarf> {
 
         let inlined = if opts.inlined_fns() {
             if let Some((function, unit)) = data {
-                if let Some(inline_stack) = self.find_inlined_functions(addr, function, unit)? {
+                if let Some((inline_stack, truncated)) =
+                    self.find_inlined_functions(addr, function, unit)?
+                {
+                    sym.inlined_truncated = truncated;
                     let mut inlined = Vec::<InlinedFn>::with_capacity(inline_stack.len());
                     for result in inline_stack {
                         let (name, location) = result?;
@@ -466,8 +957,14 @@ mod tests {
     use std::ops::ControlFlow;
     use std::path::PathBuf;
 
+    use std::fs::create_dir_all;
+    use std::fs::remove_file;
+    use std::fs::write;
+
+    use tempfile::tempdir;
     use test_log::test;
 
+    use crate::test_utils::ElfBuilder;
     use crate::ErrorKind;
 
 
@@ -512,24 +1009,159 @@ mod tests {
         );
     }
 
+    /// Check that we find debug files by build ID.
+    #[test]
+    fn build_id_debug_file_resolution() {
+        fn elf_with_build_id(build_id: &[u8]) -> Vec<u8> {
+            let mut builder = ElfBuilder::new();
+            let _text = builder.add_section(".text", 0x1000, vec![0xcc; 0x10]);
+            // NT_GNU_BUILD_ID
+            let () = builder.add_note("GNU", 3, build_id.to_vec());
+            builder.build()
+        }
+
+        let build_id = [0xab, 0xcd, 0xef, 0x01];
+        let tmpdir = tempdir().unwrap();
+        let bin = tmpdir.path().join("test.bin");
+        let () = write(&bin, elf_with_build_id(&build_id)).unwrap();
+
+        let debug_dir = tmpdir.path().join("debug");
+        let debug_file = debug_dir.join(".build-id").join("ab").join("cdef01.debug");
+        let () = create_dir_all(debug_file.parent().unwrap()).unwrap();
+        let () = write(&debug_file, elf_with_build_id(&build_id)).unwrap();
+
+        let parser = Rc::new(ElfParser::open(&bin).unwrap());
+        let dirs = [tmpdir.path().join("does-not-exist"), debug_dir.clone()];
+        let resolver = DwarfResolver::from_parser(Rc::clone(&parser), &dirs, false, false).unwrap();
+        assert_eq!(
+            resolver.linkee_parser.as_ref().unwrap().path(),
+            Some(debug_file.as_path())
+        );
+
+        // A debug file with a different build ID should be ignored.
+        let () = write(&debug_file, elf_with_build_id(&[0xab, 0xcd, 0xef, 0x02])).unwrap();
+        let resolver = DwarfResolver::from_parser(parser, &dirs, false, false).unwrap();
+        assert!(resolver.linkee_parser.is_none());
+    }
+
+    /// Check that strict file access applies to debug files found in
+    /// debug directories.
+    #[cfg(unix)]
+    #[test]
+    fn strict_debug_file_lookup() {
+        use std::os::unix::fs::symlink;
+
+        use crate::util::mkfifo;
+
+        let build_id = [0xab, 0xcd, 0xef, 0x01];
+        let elf = {
+            let mut builder = ElfBuilder::new();
+            let _text = builder.add_section(".text", 0x1000, vec![0xcc; 0x10]);
+            // NT_GNU_BUILD_ID
+            let () = builder.add_note("GNU", 3, build_id.to_vec());
+            builder.build()
+        };
+
+        let tmpdir = tempdir().unwrap();
+        let bin = tmpdir.path().join("test.bin");
+        let () = write(&bin, &elf).unwrap();
+
+        let debug_dir = tmpdir.path().join("debug");
+        let debug_file = debug_dir.join(".build-id").join("ab").join("cdef01.debug");
+        let () = create_dir_all(debug_file.parent().unwrap()).unwrap();
+        let dirs = [debug_dir];
+        let parser = Rc::new(ElfParser::open(&bin).unwrap());
+
+        // A FIFO must neither be waited on nor be used.
+        let () = mkfifo(&debug_file).unwrap();
+        let resolver = DwarfResolver::from_parser(Rc::clone(&parser), &dirs, false, true).unwrap();
+        assert!(resolver.linkee_parser.is_none());
+
+        // A symbolic link is not followed in strict mode only.
+        let () = remove_file(&debug_file).unwrap();
+        let target = tmpdir.path().join("target.debug");
+        let () = write(&target, &elf).unwrap();
+        let () = symlink(&target, &debug_file).unwrap();
+        let resolver = DwarfResolver::from_parser(Rc::clone(&parser), &dirs, false, true).unwrap();
+        assert!(resolver.linkee_parser.is_none());
+        let resolver = DwarfResolver::from_parser(parser, &dirs, false, false).unwrap();
+        assert!(resolver.linkee_parser.is_some());
+    }
+
     /// Check that we can find the source code location of an address.
     #[test]
     fn source_location_finding() {
+        for file in ["test-stable-addrs.bin", "test-stable-addrs-dwarf5.bin"] {
+            let bin_name = Path::new(&env!("CARGO_MANIFEST_DIR"))
+                .join("data")
+                .join(file);
+            let resolver = DwarfResolver::open(bin_name.as_ref()).unwrap();
+
+            let info = resolver
+                .find_sym(0x2000200, &FindSymOpts::CodeInfo)
+                .unwrap()
+                .unwrap()
+                .code_info
+                .unwrap();
+            assert_ne!(info.dir, Some(Cow::Owned(PathBuf::new())));
+            assert!(
+                info.to_path().ends_with("data/test-stable-addrs.c"),
+                "{info:?}"
+            );
+            assert_eq!(info.file, OsStr::new("test-stable-addrs.c"));
+            assert_eq!(info.line, Some(10));
+            assert!(info.column.is_some());
+        }
+    }
+
+    /// Check that we can iterate over line table rows for an address
+    /// range.
+    #[test]
+    fn line_row_iteration() {
         let bin_name = Path::new(&env!("CARGO_MANIFEST_DIR"))
             .join("data")
             .join("test-stable-addrs.bin");
         let resolver = DwarfResolver::open(bin_name.as_ref()).unwrap();
 
-        let info = resolver
-            .find_sym(0x2000200, &FindSymOpts::CodeInfo)
-            .unwrap()
+        let sym = resolver
+            .find_sym(0x2000200, &FindSymOpts::Basic)
             .unwrap()
-            .code_info
             .unwrap();
-        assert_ne!(info.dir, Some(Cow::Owned(PathBuf::new())));
-        assert_eq!(info.file, OsStr::new("test-stable-addrs.c"));
-        assert_eq!(info.line, Some(10));
-        assert!(info.column.is_some());
+        let range = sym.addr..sym.addr + sym.size.unwrap() as Addr;
+
+        let mut rows = Vec::new();
+        let () = resolver
+            .for_each_line_row(range.clone(), &mut |row| {
+                let () = rows.push((
+                    row.addr,
+                    row.is_stmt,
+                    row.code_info.as_ref().map(CodeInfo::to_owned),
+                ));
+                ControlFlow::Continue(())
+            })
+            .unwrap();
+
+        assert!(!rows.is_empty());
+        assert!(rows
+            .iter()
+            .all(|(addr, ..)| range.start <= *addr && *addr <= range.end));
+
+        let (addr, is_stmt, code_info) = rows.first().unwrap();
+        assert_eq!(*addr, 0x2000200);
+        assert!(is_stmt);
+        let code_info = code_info.as_ref().unwrap();
+        assert_eq!(code_info.file, OsStr::new("test-stable-addrs.c"));
+        assert_eq!(code_info.line, Some(10));
+
+        // Check that we can stop iteration early.
+        let mut count = 0;
+        let () = resolver
+            .for_each_line_row(range, &mut |_row| {
+                count += 1;
+                ControlFlow::Break(())
+            })
+            .unwrap();
+        assert_eq!(count, 1);
     }
 
     /// Check that we can look up a symbol in DWARF debug information.
@@ -541,6 +1173,8 @@ mod tests {
         let opts = FindAddrOpts {
             offset_in_file: false,
             sym_type: SymType::Function,
+            code_info: true,
+            decl_file: None,
         };
         let resolver = DwarfResolver::open(test_dwarf.as_ref()).unwrap();
 
@@ -550,26 +1184,297 @@ mod tests {
         // `factorial` resides at address 0x2000200.
         let symbol = symbols.first().unwrap();
         assert_eq!(symbol.addr, 0x2000200);
+
+        let code_info = symbol.code_info.as_ref().unwrap();
+        assert_eq!(code_info.file, OsStr::new("test-stable-addrs.c"));
+        assert_eq!(code_info.line, Some(10));
+        assert!(symbol.is_declared_in(Path::new("test-stable-addrs.c")));
+        assert!(!symbol.is_declared_in(Path::new("test-stable-addrs-cu2.c")));
     }
 
-    /// Check that we fail to look up variables.
+    /// Check that symbol lookup honors the declaration file filter.
     #[test]
-    fn unsupported_ops() {
+    fn lookup_symbol_by_decl_file() {
+        let test_dwarf = Path::new(&env!("CARGO_MANIFEST_DIR"))
+            .join("data")
+            .join("test-stable-addrs-stripped-elf-with-dwarf.bin");
+        let resolver = DwarfResolver::open(test_dwarf.as_ref()).unwrap();
+        let mut opts = FindAddrOpts {
+            offset_in_file: false,
+            sym_type: SymType::Function,
+            code_info: false,
+            decl_file: Some(PathBuf::from("test-stable-addrs.c")),
+        };
+
+        let symbols = resolver.find_addr("factorial", &opts).unwrap();
+        assert_eq!(symbols.len(), 1);
+        let symbol = symbols.first().unwrap();
+        assert_eq!(symbol.addr, 0x2000200);
+        // Declaration information was used for filtering, but it was
+        // not requested.
+        assert_eq!(symbol.code_info, None);
+
+        // `i_exist_twice` is defined in both compilation units, but
+        // only one of them matches.
+        let symbols = resolver.find_addr("i_exist_twice", &opts).unwrap();
+        assert_eq!(symbols.len(), 1);
+
+        let mut count = 0;
+        let () = resolver
+            .for_each(&opts, &mut |sym| {
+                if sym.name == "i_exist_twice" {
+                    count += 1;
+                }
+                ControlFlow::Continue(())
+            })
+            .unwrap();
+        assert_eq!(count, 1);
+
+        opts.decl_file = Some(PathBuf::from("test-stable-addrs-cu2.c"));
+        let symbols = resolver.find_addr("factorial", &opts).unwrap();
+        assert_eq!(symbols, Vec::new());
+    }
+
+    /// Check that declaration information is retrieved via
+    /// `DW_AT_specification` references.
+    #[test]
+    fn lookup_symbol_decl_via_specification() {
+        let test_dwarf = Path::new(&env!("CARGO_MANIFEST_DIR"))
+            .join("data")
+            .join("test-cpp.bin");
+        let resolver = DwarfResolver::open(test_dwarf.as_ref()).unwrap();
+        let opts = FindAddrOpts {
+            offset_in_file: false,
+            sym_type: SymType::Function,
+            code_info: true,
+            decl_file: Some(PathBuf::from("test-cpp.cpp")),
+        };
+
+        // The definition of `outer::func` carries no declaration
+        // attributes itself; they all stem from the specification.
+        let symbols = resolver.find_addr("_ZN5outer4funcEi", &opts).unwrap();
+        assert_eq!(symbols.len(), 1);
+        let code_info = symbols[0].code_info.as_ref().unwrap();
+        assert_eq!(code_info.file, OsStr::new("test-cpp.cpp"));
+        assert_eq!(code_info.line, Some(23));
+
+        // The definition of `Class::method` has its own declaration
+        // line, but the file is only set on the specification.
+        let symbols = resolver
+            .find_addr("_ZN5outer5inner5Class6methodEi", &opts)
+            .unwrap();
+        assert_eq!(symbols.len(), 1);
+        let code_info = symbols[0].code_info.as_ref().unwrap();
+        assert_eq!(code_info.file, OsStr::new("test-cpp.cpp"));
+        assert_eq!(code_info.line, Some(9));
+    }
+
+    /// Check that a cycle of `DW_AT_specification` references is
+    /// reported as an error when looking up declaration information.
+    #[test]
+    fn lookup_symbol_decl_reference_cycle() {
+        #[rustfmt::skip]
+        let abbrev = [
+            // Abbreviation 1: a compile unit with children.
+            1, 0x11, 1, 0x03, 0x08, 0, 0,
+            // Abbreviation 2: a subprogram with a name, an address
+            // range, and a specification.
+            2, 0x2e, 0, 0x03, 0x08, 0x11, 0x01, 0x12, 0x06, 0x47, 0x13, 0, 0,
+            // End of the abbreviation table.
+            0,
+        ];
+
+        // The unit header is 11 bytes in size and the compile unit
+        // entry following it another eight, which is where the
+        // subprogram entry starts.
+        let offset = 19u32;
+        let mut dies = vec![1];
+        let () = dies.extend_from_slice(b"test.c\0");
+        let () = dies.push(2);
+        let () = dies.extend_from_slice(b"cyclic\0");
+        let () = dies.extend_from_slice(&0x1000u64.to_ne_bytes());
+        let () = dies.extend_from_slice(&0x10u32.to_ne_bytes());
+        // The subprogram refers to itself as its specification.
+        let () = dies.extend_from_slice(&offset.to_ne_bytes());
+        let () = dies.push(0);
+
+        let mut info = Vec::new();
+        // Unit length, excluding the length field itself.
+        let len = u32::try_from(2 + 4 + 1 + dies.len()).unwrap();
+        let () = info.extend_from_slice(&len.to_ne_bytes());
+        // DWARF version.
+        let () = info.extend_from_slice(&4u16.to_ne_bytes());
+        // Offset into `.debug_abbrev`.
+        let () = info.extend_from_slice(&0u32.to_ne_bytes());
+        // Address size.
+        let () = info.push(8);
+        let () = info.extend_from_slice(&dies);
+
+        let mut builder = ElfBuilder::new();
+        let _idx = builder.add_section(".debug_abbrev", 0, abbrev.to_vec());
+        let _idx = builder.add_section(".debug_info", 0, info);
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("cycle.bin");
+        let () = write(&path, builder.build()).unwrap();
+
+        let resolver = DwarfResolver::open(&path).unwrap();
+        let mut opts = FindAddrOpts {
+            offset_in_file: false,
+            sym_type: SymType::Function,
+            code_info: false,
+            decl_file: None,
+        };
+        // Without declaration information being requested, the
+        // cycle is never followed.
+        let symbols = resolver.find_addr("cyclic", &opts).unwrap();
+        assert_eq!(symbols.len(), 1);
+        assert_eq!(symbols[0].addr, 0x1000);
+
+        opts.code_info = true;
+        let err = resolver.find_addr("cyclic", &opts).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidDwarf);
+    }
+
+    /// Check that we can look up symbols in split DWARF debug
+    /// information, stored in `.dwo` files or a `.dwp` package.
+    #[test]
+    fn lookup_split_dwarf() {
+        let opts = FindAddrOpts {
+            offset_in_file: false,
+            sym_type: SymType::Undefined,
+            code_info: true,
+            decl_file: None,
+        };
+
+        for file in [
+            "test-stable-addrs-split-dwarf.bin",
+            "test-stable-addrs-dwp.bin",
+        ] {
+            let test_dwarf = Path::new(&env!("CARGO_MANIFEST_DIR"))
+                .join("data")
+                .join(file);
+            let resolver = DwarfResolver::open(test_dwarf.as_ref()).unwrap();
+
+            let symbols = resolver.find_addr("factorial", &opts).unwrap();
+            assert_eq!(symbols.len(), 1, "{file}");
+
+            let symbol = symbols.first().unwrap();
+            assert_eq!(symbol.addr, 0x2000200);
+            let code_info = symbol.code_info.as_ref().unwrap();
+            assert_eq!(code_info.file, OsStr::new("test-stable-addrs.c"));
+            assert_eq!(code_info.line, Some(10));
+
+            let symbols = resolver.find_addr("a_variable", &opts).unwrap();
+            assert_eq!(symbols.len(), 1, "{file}");
+            assert_eq!(symbols[0].size, Some(8));
+
+            let ranges = resolver.find_func_ranges(0x2000210).unwrap();
+            assert_eq!(ranges, vec![0x2000200..0x200022b]);
+        }
+    }
+
+    /// Check that we can look up and iterate over variables in DWARF
+    /// debug information.
+    #[test]
+    fn lookup_variable() {
         let test_dwarf = Path::new(&env!("CARGO_MANIFEST_DIR"))
             .join("data")
             .join("test-stable-addrs-stripped-elf-with-dwarf.bin");
         let opts = FindAddrOpts {
             offset_in_file: false,
             sym_type: SymType::Variable,
+            code_info: true,
+            decl_file: None,
         };
         let resolver = DwarfResolver::open(test_dwarf.as_ref()).unwrap();
 
-        let err = resolver.find_addr("factorial", &opts).unwrap_err();
-        assert_eq!(err.kind(), ErrorKind::Unsupported);
+        let symbols = resolver.find_addr("a_variable", &opts).unwrap();
+        assert_eq!(symbols.len(), 1);
 
-        let err = resolver
-            .for_each(&opts, &mut |_| ControlFlow::Continue(()))
-            .unwrap_err();
-        assert_eq!(err.kind(), ErrorKind::Unsupported);
+        // `a_variable` is declared as `volatile char a_variable[8]`.
+        let symbol = symbols.first().unwrap();
+        assert_eq!(symbol.name, "a_variable");
+        assert_eq!(symbol.sym_type, SymType::Variable);
+        assert_eq!(symbol.size, Some(8));
+        assert_ne!(symbol.addr, 0);
+
+        let code_info = symbol.code_info.as_ref().unwrap();
+        assert_eq!(code_info.file, OsStr::new("test-stable-addrs.c"));
+        assert_eq!(code_info.line, Some(7));
+
+        // Variable lookup should not report any functions.
+        let symbols = resolver.find_addr("factorial", &opts).unwrap();
+        assert!(symbols.is_empty(), "{symbols:?}");
+
+        let mut syms = Vec::new();
+        let () = resolver
+            .for_each(&opts, &mut |sym| {
+                let () = syms.push(sym.to_owned());
+                ControlFlow::Continue(())
+            })
+            .unwrap();
+        assert!(syms.iter().all(|sym| sym.sym_type == SymType::Variable));
+        let sym = syms.iter().find(|sym| sym.name == "a_variable").unwrap();
+        assert_eq!(sym.size, Some(8));
+
+        let opts = FindAddrOpts {
+            sym_type: SymType::Undefined,
+            ..opts
+        };
+        let mut syms = Vec::new();
+        let () = resolver
+            .for_each(&opts, &mut |sym| {
+                let () = syms.push(sym.to_owned());
+                ControlFlow::Continue(())
+            })
+            .unwrap();
+        assert!(syms.iter().any(|sym| sym.name == "a_variable"));
+        assert!(syms.iter().any(|sym| sym.name == "factorial"));
+    }
+
+    /// Check that we can look up the address ranges of a function.
+    #[test]
+    fn find_function_ranges() {
+        let test_dwarf = Path::new(&env!("CARGO_MANIFEST_DIR"))
+            .join("data")
+            .join("test-stable-addrs-stripped-elf-with-dwarf.bin");
+        let resolver = DwarfResolver::open(test_dwarf.as_ref()).unwrap();
+
+        let ranges = resolver.find_func_ranges(0x2000210).unwrap();
+        assert_eq!(ranges, vec![0x2000200..0x200022b]);
+
+        let ranges = resolver.find_func_ranges(0x2000000 - 1).unwrap();
+        assert_eq!(ranges, Vec::new());
+    }
+
+    /// Check that we can find the addresses of the code for a given
+    /// source code line.
+    #[test]
+    fn find_line_addresses() {
+        let test_dwarf = Path::new(&env!("CARGO_MANIFEST_DIR"))
+            .join("data")
+            .join("test-stable-addrs-stripped-elf-with-dwarf.bin");
+        let resolver = DwarfResolver::open(test_dwarf.as_ref()).unwrap();
+
+        let file = Path::new("test-stable-addrs.c");
+        let addrs = resolver.find_line_addrs(file, 10).unwrap();
+        assert_eq!(addrs, vec![0x2000200]);
+
+        // Line 13 is covered by two consecutive rows, but we only
+        // expect the first one to be reported.
+        let addrs = resolver.find_line_addrs(file, 13).unwrap();
+        assert_eq!(addrs, vec![0x2000218]);
+
+        // Code for line 23 got inlined into `factorial_inline_test`.
+        let addrs = resolver.find_line_addrs(file, 23).unwrap();
+        assert_eq!(addrs, vec![0x2000304]);
+
+        let addrs = resolver.find_line_addrs(file, 15).unwrap();
+        assert_eq!(addrs, Vec::<Addr>::new());
+
+        let addrs = resolver
+            .find_line_addrs(Path::new("data/other.c"), 10)
+            .unwrap();
+        assert_eq!(addrs, Vec::<Addr>::new());
     }
 }
@@ -0,0 +1,309 @@
+//! Support for split DWARF ("Fission"), where the bulk of the debug
+//! information of a compilation unit is stored in a separate `.dwo`
+//! file or `.dwp` package and the binary only contains a skeleton
+//! unit referencing it.
+
+use std::cell::RefCell;
+use std::ffi::OsString;
+use std::mem;
+use std::path::Path;
+use std::path::PathBuf;
+use std::rc::Rc;
+
+use gimli::SectionId;
+
+use crate::elf::ElfParser;
+use crate::file_cache::open_file;
+use crate::log::debug;
+use crate::log::warn;
+use crate::once::OnceCell;
+use crate::util::bytes_to_path;
+use crate::Result;
+
+use super::reader;
+use super::reader::R;
+
+
+/// Retrieve the data of the split DWARF section with the given ID from
+/// an ELF file.
+fn dwo_section_data(parser: &ElfParser, id: SectionId) -> Result<&[u8]> {
+    let name = if let Some(name) = id.dwo_name() {
+        name
+    } else {
+        return Ok(&[])
+    };
+
+    let data = match parser.find_section(name)? {
+        Some(idx) => parser.section_data(idx)?,
+        // Make sure to return empty data if a section does not exist.
+        None => &[],
+    };
+    Ok(data)
+}
+
+
+/// A split compilation unit along with the DWARF data it lives in.
+pub(super) struct DwoUnit<'dwarf> {
+    /// The DWARF data of the `.dwo` file or the unit's contribution to
+    /// a `.dwp` package.
+    pub dwarf: gimli::Dwarf<R<'dwarf>>,
+    /// The split compilation unit.
+    pub dw_unit: gimli::Unit<R<'dwarf>>,
+    /// The source code language of the unit, if known.
+    pub lang: Option<gimli::DwLang>,
+}
+
+
+/// A loader for split DWARF data referenced by skeleton units.
+pub(super) struct DwoLoader<'dwarf> {
+    /// The path to the file containing the skeleton units, if known.
+    path: Option<PathBuf>,
+    /// Whether to open split DWARF files subject to the checks of
+    /// strict file access.
+    strict: bool,
+    /// The lazily loaded DWARF package accompanying the file, if any.
+    dwp: OnceCell<Option<gimli::DwarfPackage<R<'dwarf>>>>,
+    /// Parsers for all split DWARF files loaded so far.
+    // SAFETY: Data handed out by the loader reference these parsers,
+    //         which is why they have to stay alive for as long as the
+    //         loader does. This member has to be listed last.
+    parsers: RefCell<Vec<Rc<ElfParser>>>,
+}
+
+impl<'dwarf> DwoLoader<'dwarf> {
+    /// Create a new loader for split DWARF data referenced by the file
+    /// at `path`.
+    pub(super) fn new(path: Option<PathBuf>) -> Self {
+        Self {
+            path,
+            strict: false,
+            dwp: OnceCell::new(),
+            parsers: RefCell::new(Vec::new()),
+        }
+    }
+
+    /// Enable/disable strict file access for split DWARF files.
+    pub(super) fn enable_strict_file_access(&mut self, enable: bool) {
+        self.strict = enable;
+    }
+
+    /// Open the ELF file at `path`, keeping it alive for as long as
+    /// `self` is.
+    fn open(&self, path: &Path) -> Result<&'dwarf ElfParser> {
+        let file = open_file(path, self.strict)?;
+        let parser = Rc::new(ElfParser::open_file(&file, path)?);
+        // SAFETY: We keep the `ElfParser` around for as long as `self`
+        //         is alive and it is heap allocated, meaning it won't
+        //         move. Objects referencing its data are never handed
+        //         out with a lifetime exceeding that of `self`.
+        let parser_ref = unsafe { mem::transmute::<&ElfParser, &'dwarf ElfParser>(&*parser) };
+        let () = self.parsers.borrow_mut().push(parser);
+        Ok(parser_ref)
+    }
+
+    /// Load the DWARF package (`<file>.dwp`) accompanying the file, if
+    /// present.
+    fn load_dwp(&self) -> Result<Option<gimli::DwarfPackage<R<'dwarf>>>> {
+        let path = if let Some(path) = &self.path {
+            path
+        } else {
+            return Ok(None)
+        };
+
+        let mut dwp_path = OsString::from(path.as_os_str());
+        let () = dwp_path.push(".dwp");
+        let dwp_path = PathBuf::from(dwp_path);
+        if !dwp_path.exists() {
+            return Ok(None)
+        }
+
+        debug!("using DWARF package `{}`", dwp_path.display());
+        let parser = self.open(&dwp_path)?;
+        let dwp = gimli::DwarfPackage::load(
+            |section| dwo_section_data(parser, section).map(reader::reader),
+            reader::reader(&[]),
+        )?;
+        Ok(Some(dwp))
+    }
+
+    /// Load the `.dwo` file referenced by `skeleton`.
+    fn load_dwo(
+        &self,
+        skeleton: &gimli::Unit<R<'dwarf>>,
+        parent: &gimli::Dwarf<R<'dwarf>>,
+    ) -> Result<Option<gimli::Dwarf<R<'dwarf>>>> {
+        let name = if let Some(attr) = skeleton.dwo_name()? {
+            parent.attr_string(skeleton, attr)?
+        } else {
+            return Ok(None)
+        };
+        let name = bytes_to_path(name.slice())?;
+
+        // The name is relative to the unit's compilation directory.
+        // However, the build directory may not be around anymore or
+        // may have been moved along with the binary, so we also check
+        // next to the latter.
+        let mut candidates = Vec::with_capacity(2);
+        if let Some(comp_dir) = &skeleton.comp_dir {
+            let () = candidates.push(bytes_to_path(comp_dir.slice())?.join(name));
+        } else {
+            let () = candidates.push(name.to_path_buf());
+        }
+        if let (Some(dir), Some(file)) = (
+            self.path.as_deref().and_then(Path::parent),
+            name.file_name(),
+        ) {
+            let () = candidates.push(dir.join(file));
+        }
+
+        for path in candidates {
+            if !path.exists() {
+                continue
+            }
+
+            debug!("using split DWARF file `{}`", path.display());
+            let parser = self.open(&path)?;
+            let mut dwarf = gimli::Dwarf::load(|section| {
+                dwo_section_data(parser, section).map(reader::reader)
+            })?;
+            let () = dwarf.make_dwo(parent);
+            return Ok(Some(dwarf))
+        }
+        Ok(None)
+    }
+
+    /// Load the split unit corresponding to the skeleton unit
+    /// `skeleton` from `parent`.
+    pub(super) fn load(
+        &self,
+        skeleton: &gimli::Unit<R<'dwarf>>,
+        parent: &gimli::Dwarf<R<'dwarf>>,
+    ) -> Result<Option<DwoUnit<'dwarf>>> {
+        let dwo_id = if let Some(dwo_id) = skeleton.dwo_id {
+            dwo_id
+        } else {
+            return Ok(None)
+        };
+
+        let dwp = self.dwp.get_or_init(|| {
+            self.load_dwp().unwrap_or_else(|err| {
+                warn!("failed to load DWARF package: {err}");
+                None
+            })
+        });
+        let dwarf = if let Some(dwp) = dwp {
+            dwp.find_cu(dwo_id, parent)?
+        } else {
+            None
+        };
+        let dwarf = if let Some(dwarf) = dwarf {
+            dwarf
+        } else if let Some(dwarf) = self.load_dwo(skeleton, parent)? {
+            dwarf
+        } else {
+            return Ok(None)
+        };
+
+        let mut headers = dwarf.units();
+        while let Some(header) = headers.next()? {
+            let mut dw_unit = dwarf.unit(header)?;
+            if dw_unit.dwo_id != Some(dwo_id) {
+                continue
+            }
+            let () = dw_unit.copy_relocated_attributes(skeleton);
+
+            let lang = {
+                let mut entries = dw_unit.entries();
+                let _entry = entries.next_dfs()?;
+                entries
+                    .current()
+                    .map(|entry| entry.attr_value(gimli::DW_AT_language))
+                    .transpose()?
+                    .flatten()
+                    .and_then(|value| match value {
+                        gimli::AttributeValue::Language(lang) => Some(lang),
+                        _ => None,
+                    })
+            };
+
+            let unit = DwoUnit {
+                dwarf,
+                dw_unit,
+                lang,
+            };
+            return Ok(Some(unit))
+        }
+
+        warn!(
+            "split DWARF data do not contain unit with DWO ID {:#x}",
+            dwo_id.0
+        );
+        Ok(None)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::env;
+
+    use test_log::test;
+
+
+    /// Load the DWARF data of the ELF file at `path`.
+    fn load_dwarf(parser: &ElfParser) -> gimli::Dwarf<R<'_>> {
+        gimli::Dwarf::load(|section| reader::load_section(parser, section)).unwrap()
+    }
+
+    /// Check that we can load split units from `.dwo` files as well
+    /// as from a `.dwp` package.
+    #[test]
+    fn split_unit_loading() {
+        for (file, dwp) in [
+            ("test-stable-addrs-split-dwarf.bin", false),
+            ("test-stable-addrs-dwp.bin", true),
+        ] {
+            let path = Path::new(&env!("CARGO_MANIFEST_DIR"))
+                .join("data")
+                .join(file);
+            let parser = ElfParser::open(&path).unwrap();
+            let dwarf = load_dwarf(&parser);
+            let loader = DwoLoader::new(Some(path));
+            assert_eq!(loader.load_dwp().unwrap().is_some(), dwp, "{file}");
+
+            let mut count = 0;
+            let mut headers = dwarf.units();
+            while let Some(header) = headers.next().unwrap() {
+                let unit = dwarf.unit(header).unwrap();
+                assert!(unit.dwo_id.is_some());
+
+                let dwo = loader.load(&unit, &dwarf).unwrap().unwrap();
+                assert_eq!(dwo.dw_unit.dwo_id, unit.dwo_id);
+                assert_eq!(dwo.dw_unit.low_pc, unit.low_pc);
+                assert!(dwo.lang.is_some());
+                count += 1;
+            }
+            assert_eq!(count, 2, "{file}");
+        }
+    }
+
+    /// Make sure that we do not attempt to load split DWARF data for
+    /// regular compilation units.
+    #[test]
+    fn non_skeleton_unit() {
+        let path = Path::new(&env!("CARGO_MANIFEST_DIR"))
+            .join("data")
+            .join("test-stable-addrs.bin");
+        let parser = ElfParser::open(&path).unwrap();
+        let dwarf = load_dwarf(&parser);
+        let loader = DwoLoader::new(Some(path));
+
+        let mut headers = dwarf.units();
+        let header = headers.next().unwrap().unwrap();
+        let unit = dwarf.unit(header).unwrap();
+        assert!(loader.load(&unit, &dwarf).unwrap().is_none());
+        assert!(loader.parsers.borrow().is_empty());
+    }
+}
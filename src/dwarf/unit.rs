@@ -25,6 +25,7 @@
 // > IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
 // > DEALINGS IN THE SOFTWARE.
 
+use crate::log::warn;
 use crate::once::OnceCell;
 
 use super::function::Function;
@@ -33,7 +34,9 @@ use super::lines::Lines;
 use super::location::Location;
 use super::location::LocationRangeUnitIter;
 use super::reader::R;
+use super::split::DwoUnit;
 use super::units::Units;
+use super::variable::Variables;
 
 
 pub(super) struct UnitRange {
@@ -49,6 +52,10 @@ pub(super) struct Unit<'dwarf> {
     lang: Option<gimli::DwLang>,
     lines: OnceCell<Lines<'dwarf>>,
     funcs: OnceCell<Functions<'dwarf>>,
+    vars: OnceCell<Variables<'dwarf>>,
+    /// The split unit, if this is a skeleton unit and the split DWARF
+    /// data could be found.
+    dwo: OnceCell<Option<Box<DwoUnit<'dwarf>>>>,
 }
 
 impl<'dwarf> Unit<'dwarf> {
@@ -56,14 +63,51 @@ impl<'dwarf> Unit<'dwarf> {
         offset: gimli::DebugInfoOffset<<R<'dwarf> as gimli::Reader>::Offset>,
         unit: gimli::Unit<R<'dwarf>>,
         lang: Option<gimli::DwLang>,
-        lines: OnceCell<Lines<'dwarf>>,
     ) -> Self {
         Self {
             offset,
             dw_unit: unit,
             lang,
-            lines,
+            lines: OnceCell::new(),
             funcs: OnceCell::new(),
+            vars: OnceCell::new(),
+            dwo: OnceCell::new(),
+        }
+    }
+
+    /// Retrieve the split unit belonging to this unit, if it is a
+    /// skeleton unit.
+    fn dwo(&self, units: &Units<'dwarf>) -> Option<&DwoUnit<'dwarf>> {
+        // Only skeleton units reference split DWARF data.
+        let _dwo_id = self.dw_unit.dwo_id?;
+
+        self.dwo
+            .get_or_init(|| match units.load_dwo(&self.dw_unit) {
+                Ok(dwo) => dwo.map(Box::new),
+                Err(err) => {
+                    warn!(
+                        "failed to load split DWARF data for unit @ .debug_info+0x{:08x}: {err}",
+                        self.offset.0
+                    );
+                    None
+                }
+            })
+            .as_deref()
+    }
+
+    /// Retrieve a [`gimli::UnitRef`] for the unit containing the bulk
+    /// of the debug information, i.e., functions and variables.
+    ///
+    /// For skeleton units that is the corresponding split unit, if it
+    /// could be loaded.
+    pub(super) fn dwarf_and_unit<'unit>(
+        &'unit self,
+        units: &'unit Units<'dwarf>,
+    ) -> gimli::UnitRef<'unit, R<'dwarf>> {
+        if let Some(dwo) = self.dwo(units) {
+            gimli::UnitRef::new(&dwo.dwarf, &dwo.dw_unit)
+        } else {
+            units.unit_ref(&self.dw_unit)
         }
     }
 
@@ -71,7 +115,7 @@ impl<'dwarf> Unit<'dwarf> {
         &'unit self,
         units: &Units<'dwarf>,
     ) -> Result<&'unit Functions<'dwarf>, gimli::Error> {
-        let unit = units.unit_ref(&self.dw_unit);
+        let unit = self.dwarf_and_unit(units);
         let functions = self.parse_functions_dwarf_and_unit(unit, units)?;
         Ok(functions)
     }
@@ -83,7 +127,7 @@ impl<'dwarf> Unit<'dwarf> {
         units: &Units<'dwarf>,
     ) -> Result<&'unit Functions<'dwarf>, gimli::Error> {
         self.funcs.get_or_try_init(|| {
-            let unit = units.unit_ref(&self.dw_unit);
+            let unit = self.dwarf_and_unit(units);
             let funcs = Functions::parse(unit, units)?;
             let () = funcs.parse_inlined_functions(unit, units)?;
             Ok(funcs)
@@ -105,6 +149,13 @@ impl<'dwarf> Unit<'dwarf> {
             .map(Some)
     }
 
+    /// Retrieve the line table of this unit, if it has been parsed
+    /// already.
+    #[cfg(test)]
+    pub(super) fn cached_lines(&self) -> Option<&Lines<'dwarf>> {
+        self.lines.get()
+    }
+
     pub(super) fn find_location(
         &self,
         probe: u64,
@@ -133,7 +184,7 @@ impl<'dwarf> Unit<'dwarf> {
         probe: u64,
         units: &Units<'dwarf>,
     ) -> Result<Option<&Function<'dwarf>>, gimli::Error> {
-        let unit = units.unit_ref(&self.dw_unit);
+        let unit = self.dwarf_and_unit(units);
         let functions = self.parse_functions_dwarf_and_unit(unit, units)?;
         let function = match functions.find_address(probe) {
             Some(address) => {
@@ -146,12 +197,27 @@ impl<'dwarf> Unit<'dwarf> {
         Ok(function)
     }
 
+    /// Find all address ranges of the function containing `probe`.
+    pub(super) fn find_function_ranges(
+        &self,
+        probe: u64,
+        units: &Units<'dwarf>,
+    ) -> Result<Option<Vec<gimli::Range>>, gimli::Error> {
+        let unit = self.dwarf_and_unit(units);
+        let functions = self.parse_functions_dwarf_and_unit(unit, units)?;
+        let ranges = functions.find_address(probe).map(|address| {
+            let function_index = functions.addresses[address].function;
+            functions.function_ranges(function_index).collect()
+        });
+        Ok(ranges)
+    }
+
     pub(super) fn find_name<'slf>(
         &'slf self,
         name: &str,
         units: &Units<'dwarf>,
     ) -> Result<Option<&'slf Function<'dwarf>>, gimli::Error> {
-        let unit = units.unit_ref(&self.dw_unit);
+        let unit = self.dwarf_and_unit(units);
         let functions = self.parse_functions_dwarf_and_unit(unit, units)?;
         for func in functions.functions.iter() {
             let name = Some(name.as_bytes());
@@ -162,6 +228,16 @@ impl<'dwarf> Unit<'dwarf> {
         Ok(None)
     }
 
+    pub(super) fn parse_variables<'unit>(
+        &'unit self,
+        units: &Units<'dwarf>,
+    ) -> Result<&'unit Variables<'dwarf>, gimli::Error> {
+        self.vars.get_or_try_init(|| {
+            let unit = self.dwarf_and_unit(units);
+            Variables::parse(unit, units)
+        })
+    }
+
     /// Retrieve the unit's debug info offset.
     #[inline]
     pub(super) fn offset(&self) -> gimli::DebugInfoOffset<<R<'dwarf> as gimli::Reader>::Offset> {
@@ -177,6 +253,13 @@ impl<'dwarf> Unit<'dwarf> {
     /// Attempt to retrieve the compilation unit's source code language.
     #[inline]
     pub(super) fn language(&self) -> Option<gimli::DwLang> {
-        self.lang
+        // Skeleton units may not carry the language attribute, in
+        // which case we consult the split unit, if loaded already.
+        self.lang.or_else(|| {
+            self.dwo
+                .get()
+                .and_then(|dwo| dwo.as_ref())
+                .and_then(|dwo| dwo.lang)
+        })
     }
 }
@@ -25,20 +25,30 @@
 // > IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
 // > DEALINGS IN THE SOFTWARE.
 
+use std::borrow::Cow;
+use std::ffi::OsStr;
 use std::ops::ControlFlow;
+use std::ops::Range;
+use std::path::Path;
+use std::path::PathBuf;
 
 use crate::log::warn;
-use crate::once::OnceCell;
 use crate::ErrorExt as _;
 use crate::Result;
 
+use super::function::decl_entry;
 use super::function::Function;
+use super::function::InlinedFunction;
+use super::function::MAX_DECL_DEPTH;
 use super::lines::Lines;
 use super::location::Location;
 use super::range::RangeAttributes;
 use super::reader::R;
+use super::split::DwoLoader;
+use super::split::DwoUnit;
 use super::unit::Unit;
 use super::unit::UnitRange;
+use super::variable::Variable;
 
 
 fn format_offset(offset: gimli::UnitSectionOffset<usize>) -> String {
@@ -60,6 +70,10 @@ pub(crate) struct Units<'dwarf> {
     unit_ranges: Box<[UnitRange]>,
     /// All units along with meta-data.
     units: Box<[Unit<'dwarf>]>,
+    /// The loader for split DWARF data referenced by skeleton units.
+    // NB: This member has to be listed after `units`, because the
+    //     latter may reference data owned by it.
+    dwo_loader: DwoLoader<'dwarf>,
 }
 
 impl<'dwarf> Units<'dwarf> {
@@ -188,21 +202,17 @@ impl<'dwarf> Units<'dwarf> {
                 }
             }
 
-            let lines = OnceCell::new();
             if !have_unit_range {
                 // The unit did not declare any ranges.
                 // Try to get some ranges from the line program sequences.
+                // Note that we deliberately do not build up the line
+                // table at this point, as it may never be needed (e.g.,
+                // if no source code information is requested).
                 if let Some(ref ilnp) = dw_unit.line_program {
-                    if let Ok(lines) = lines.get_or_try_init(|| {
-                        let unit = gimli::UnitRef::new(&sections, &dw_unit);
-                        Lines::parse(unit, ilnp.clone())
-                    }) {
-                        for sequence in lines.sequences.iter() {
+                    if let Ok(ranges) = Lines::parse_ranges(ilnp.clone()) {
+                        for range in ranges {
                             unit_ranges.push(UnitRange {
-                                range: gimli::Range {
-                                    begin: sequence.start,
-                                    end: sequence.end,
-                                },
+                                range,
                                 unit_id,
                                 max_end: 0,
                             })
@@ -211,7 +221,7 @@ impl<'dwarf> Units<'dwarf> {
                 }
             }
 
-            res_units.push(Unit::new(offset, dw_unit, lang, lines))
+            res_units.push(Unit::new(offset, dw_unit, lang))
         }
 
         // Sort this for faster lookups.
@@ -229,22 +239,46 @@ impl<'dwarf> Units<'dwarf> {
             dwarf: sections,
             unit_ranges: unit_ranges.into_boxed_slice(),
             units: res_units.into_boxed_slice(),
+            dwo_loader: DwoLoader::new(None),
         };
         Ok(slf)
     }
 
-    /// Find the unit containing the given offset, and convert the
-    /// offset into a unit offset.
-    pub(super) fn find_unit(
-        &self,
+    /// Set the path to the file the DWARF data were loaded from, which
+    /// is used for locating split DWARF data.
+    pub(super) fn set_path(&mut self, path: PathBuf) {
+        self.dwo_loader = DwoLoader::new(Some(path));
+    }
+
+    /// Enable/disable strict file access for split DWARF files opened
+    /// on behalf of the units.
+    pub(super) fn enable_strict_file_access(&mut self, enable: bool) {
+        let () = self.dwo_loader.enable_strict_file_access(enable);
+    }
+
+    /// Find the unit containing the given offset, as referenced from
+    /// within `unit`, and convert the offset into a unit offset.
+    pub(super) fn find_unit<'unit>(
+        &'unit self,
+        unit: gimli::UnitRef<'unit, R<'dwarf>>,
         offset: gimli::DebugInfoOffset<<R<'_> as gimli::Reader>::Offset>,
     ) -> Result<
         (
-            gimli::UnitRef<'_, R<'dwarf>>,
+            gimli::UnitRef<'unit, R<'dwarf>>,
             gimli::UnitOffset<<R<'dwarf> as gimli::Reader>::Offset>,
         ),
         gimli::Error,
     > {
+        if let Some(unit_offset) = offset.to_unit_offset(&unit.header) {
+            return Ok((unit, unit_offset))
+        }
+
+        // Offsets in split DWARF data refer to the `.dwo` file, which
+        // only contains a single compilation unit.
+        if unit.dwarf.file_type == gimli::DwarfFileType::Dwo {
+            return Err(gimli::Error::NoEntryAtGivenOffset)
+        }
+
         let unit = match self
             .units
             .binary_search_by_key(&offset.0, |unit| unit.offset().0)
@@ -340,54 +374,296 @@ impl<'dwarf> Units<'dwarf> {
         Ok(None)
     }
 
+    /// Find all address ranges of the function containing `probe`.
+    ///
+    /// Functions may consist of multiple, non-contiguous ranges (as
+    /// described by `DW_AT_ranges`), e.g., if the compiler moved rarely
+    /// executed code into a separate section. Ranges are reported in
+    /// ascending order.
+    pub(super) fn find_function_ranges(
+        &self,
+        probe: u64,
+    ) -> Result<Option<Vec<Range<u64>>>, gimli::Error> {
+        for unit in self.find_units(probe) {
+            if let Some(ranges) = unit.find_function_ranges(probe, self)? {
+                let mut ranges = ranges
+                    .into_iter()
+                    .map(|range| range.begin..range.end)
+                    .collect::<Vec<_>>();
+                let () = ranges.sort_by_key(|range| (range.start, range.end));
+                return Ok(Some(ranges))
+            }
+        }
+        Ok(None)
+    }
+
     /// Find the list of inlined functions that contain `probe`.
+    ///
+    /// The returned flag indicates whether the list was truncated,
+    /// because inlined calls were nested too deeply.
     pub(super) fn find_inlined_functions<'slf>(
         &'slf self,
         probe: u64,
         function: &'slf Function<'dwarf>,
         unit: &'slf Unit<'dwarf>,
     ) -> Result<
-        Option<
+        Option<(
             impl ExactSizeIterator<
                     Item = Result<(&'dwarf str, Option<Location<'slf>>), gimli::Error>,
                 > + 'slf,
-        >,
+            bool,
+        )>,
         gimli::Error,
     > {
-        let unit_ref = gimli::UnitRef::new(&self.dwarf, unit.dw_unit());
-        let inlined_fns = function.parse_inlined_functions(unit_ref, self)?;
-        let iter = inlined_fns.find_inlined_functions(probe).map(move |inlined_fn| {
+        let inlined_fns = function.parse_inlined_functions(unit.dwarf_and_unit(self), self)?;
+        let (iter, truncated) = inlined_fns.find_inlined_functions(probe);
+        let iter = iter.map(move |inlined_fn| {
             let name = inlined_fn
                 .name
                 .map(|name| name.to_string())
                 .transpose()?
                 .unwrap_or("");
+            let code_info = self.find_call_location(inlined_fn, unit)?;
+            Ok((name, code_info))
+        });
+        Ok(Some((iter, truncated)))
+    }
 
-            let code_info = if let Some(call_file) = inlined_fn.call_file {
-                if let Some(lines) = unit.parse_lines(unit_ref)? {
-                    if let Some((dir, file)) = lines.files.get(call_file as usize) {
-                        let code_info = Location {
-                            dir,
-                            file,
-                            line: Some(inlined_fn.call_line),
-                            column: Some(inlined_fn.call_column),
-                        };
-                        Some(code_info)
-                    } else {
-                        warn!(
-                            "encountered invalid inlined function `call_file` index ({call_file}); ignoring..."
-                        );
-                        None
-                    }
+    /// Find the location from which `inlined_fn` is called.
+    fn find_call_location<'slf>(
+        &'slf self,
+        inlined_fn: &InlinedFunction<'dwarf>,
+        unit: &'slf Unit<'dwarf>,
+    ) -> Result<Option<Location<'slf>>, gimli::Error> {
+        let call_file = if let Some(call_file) = inlined_fn.call_file {
+            call_file
+        } else {
+            return Ok(None)
+        };
+
+        // Line information is always stored with the skeleton unit.
+        let unit_ref = self.unit_ref(unit.dw_unit());
+        if let Some(lines) = unit.parse_lines(unit_ref)? {
+            if let Some((dir, file)) = lines.files.get(call_file as usize) {
+                let code_info = Location {
+                    dir,
+                    file,
+                    line: Some(inlined_fn.call_line),
+                    column: Some(inlined_fn.call_column),
+                };
+                Ok(Some(code_info))
+            } else {
+                warn!(
+                    "encountered invalid inlined function `call_file` index ({call_file}); ignoring..."
+                );
+                Ok(None)
+            }
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Invoke `f` for each address range of a function call inlined
+    /// into the function containing `probe`.
+    ///
+    /// Besides the range, `f` receives the nesting depth of the call,
+    /// an identifier of the call that is shared by all of its ranges,
+    /// the name of the inlined function, and the location of the call.
+    pub(super) fn for_each_inlined_range<F>(&self, probe: u64, mut f: F) -> Result<(), gimli::Error>
+    where
+        F: FnMut(Range<u64>, usize, usize, &'dwarf str, Option<Location<'_>>),
+    {
+        let (function, unit) = if let Some(result) = self.find_function(probe)? {
+            result
+        } else {
+            return Ok(())
+        };
+
+        let inlined_fns = function.parse_inlined_functions(unit.dwarf_and_unit(self), self)?;
+        for (range, depth, call, inlined_fn) in inlined_fns.ranges() {
+            let name = inlined_fn
+                .name
+                .map(|name| name.to_string())
+                .transpose()?
+                .unwrap_or("");
+            let location = self.find_call_location(inlined_fn, unit)?;
+            let () = f(range.begin..range.end, depth, call, name, location);
+        }
+        Ok(())
+    }
+
+    /// Find the location at which `function` is declared.
+    pub(super) fn find_decl_location<'slf>(
+        &'slf self,
+        function: &Function<'dwarf>,
+        unit: &'slf Unit<'dwarf>,
+    ) -> Result<Option<Location<'slf>>, gimli::Error> {
+        let (decl_file, decl_line) = match (function.decl_file, function.decl_line) {
+            (Some(decl_file), Some(decl_line)) => (Some(decl_file), Some(decl_line)),
+            // Out-of-line definitions and concrete instances of inlined
+            // functions may only carry part of the declaration
+            // information themselves, referring to the entry they
+            // specify for the remainder.
+            _ => decl_entry(
+                unit.dwarf_and_unit(self),
+                function.dw_die_offset,
+                MAX_DECL_DEPTH,
+            )?,
+        };
+        self.find_decl_location_impl(decl_file, decl_line, unit)
+    }
+
+    /// Find the location at which `variable` is declared.
+    pub(super) fn find_var_decl_location<'slf>(
+        &'slf self,
+        variable: &Variable<'dwarf>,
+        unit: &'slf Unit<'dwarf>,
+    ) -> Result<Option<Location<'slf>>, gimli::Error> {
+        self.find_decl_location_impl(variable.decl_file, variable.decl_line, unit)
+    }
+
+    fn find_decl_location_impl<'slf>(
+        &'slf self,
+        decl_file: Option<u64>,
+        decl_line: Option<u32>,
+        unit: &'slf Unit<'dwarf>,
+    ) -> Result<Option<Location<'slf>>, gimli::Error> {
+        let decl_file = if let Some(decl_file) = decl_file {
+            decl_file
+        } else {
+            return Ok(None)
+        };
+
+        let unit_ref = gimli::UnitRef::new(&self.dwarf, unit.dw_unit());
+        let location = if let Some(lines) = unit.parse_lines(unit_ref)? {
+            if let Some((dir, file)) = lines.files.get(decl_file as usize) {
+                Some(Location {
+                    dir,
+                    file,
+                    line: decl_line,
+                    column: None,
+                })
+            } else {
+                warn!("encountered invalid `decl_file` index ({decl_file}); ignoring...");
+                None
+            }
+        } else {
+            None
+        };
+        Ok(location)
+    }
+
+    /// Run the line number programs of all units covering `range` and
+    /// invoke `f` for each emitted row with an address inside of it.
+    ///
+    /// Contrary to the cached line information used for address
+    /// lookups, rows are reported as emitted by the line program's
+    /// state machine, including all flags. `f` is additionally provided
+    /// the directory and file the row refers to, if valid.
+    pub(super) fn for_each_line_row<F>(
+        &self,
+        range: Range<u64>,
+        mut f: F,
+    ) -> Result<(), gimli::Error>
+    where
+        F: FnMut(&gimli::LineRow, Option<&(Cow<'dwarf, Path>, &'dwarf OsStr)>) -> ControlFlow<()>,
+    {
+        // A unit may cover multiple ranges overlapping with the
+        // provided one, but we only want to visit it once.
+        let mut visited = Vec::new();
+        for (unit, _range) in self.find_units_range(range.start, range.end) {
+            let offset = unit.offset();
+            if visited.contains(&offset) {
+                continue
+            }
+            let () = visited.push(offset);
+
+            let unit_ref = self.unit_ref(unit.dw_unit());
+            let lines = if let Some(lines) = unit.parse_lines(unit_ref)? {
+                lines
+            } else {
+                continue
+            };
+            // SANITY: `parse_lines` only reports line information if a
+            //         line program is present.
+            let ilnp = unit.dw_unit().line_program.clone().unwrap();
+            let mut rows = ilnp.rows();
+            while let Some((_header, row)) = rows.next_row()? {
+                let addr = row.address();
+                // A row ending a sequence does not describe an
+                // instruction, but marks the first byte after the
+                // sequence. As such, it belongs to our range if the end
+                // of the sequence falls into it.
+                let contained = if row.end_sequence() {
+                    range.start < addr && addr <= range.end
                 } else {
-                    None
+                    range.contains(&addr)
+                };
+                if !contained {
+                    continue
                 }
+
+                let file = lines.files.get(row.file_index() as usize);
+                if let ControlFlow::Break(()) = f(row, file) {
+                    return Ok(())
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Find the addresses at which the code generated for `line` in
+    /// `file` starts.
+    ///
+    /// A file in the line table matches if `file` is a (component wise)
+    /// suffix of its full path. Only rows marked as recommended
+    /// breakpoint locations (`is_stmt`) are considered and, out of
+    /// consecutive rows for the line, only the first one is reported.
+    /// Addresses are reported in ascending order.
+    pub(super) fn find_line_addrs(&self, file: &Path, line: u32) -> Result<Vec<u64>, gimli::Error> {
+        let mut addrs = Vec::new();
+        for unit in self.units.iter() {
+            let unit_ref = self.unit_ref(unit.dw_unit());
+            let lines = if let Some(lines) = unit.parse_lines(unit_ref)? {
+                lines
             } else {
-                None
+                continue
             };
-            Ok((name, code_info))
-        });
-        Ok(Some(iter))
+
+            let file_idxs = lines
+                .files
+                .iter()
+                .enumerate()
+                .filter(|(_idx, (dir, name))| dir.join(name).ends_with(file))
+                .map(|(idx, _file)| idx as u64)
+                .collect::<Vec<_>>();
+            if file_idxs.is_empty() {
+                continue
+            }
+
+            // SANITY: `parse_lines` only reports line information if a
+            //         line program is present.
+            let ilnp = unit.dw_unit().line_program.clone().unwrap();
+            let mut rows = ilnp.rows();
+            // Whether we already reported an address for the block of
+            // rows for `line` that we are currently in.
+            let mut reported = false;
+            while let Some((_header, row)) = rows.next_row()? {
+                let matches = !row.end_sequence()
+                    && row.line().map(|line| line.get()) == Some(u64::from(line))
+                    && file_idxs.contains(&row.file_index());
+                if !matches {
+                    reported = false;
+                } else if !reported && row.is_stmt() {
+                    let () = addrs.push(row.address());
+                    reported = true;
+                }
+            }
+        }
+
+        let () = addrs.sort_unstable();
+        let () = addrs.dedup();
+        Ok(addrs)
     }
 
     /// Find the source file and line corresponding to the given virtual memory
@@ -401,24 +677,43 @@ impl<'dwarf> Units<'dwarf> {
         Ok(None)
     }
 
-    pub(crate) fn find_name<'s, 'slf: 's>(
+    pub(super) fn find_name<'s, 'slf: 's>(
         &'slf self,
         name: &'s str,
-    ) -> impl Iterator<Item = Result<&'slf Function<'dwarf>, gimli::Error>> + 's {
-        self.units
-            .iter()
-            .filter_map(move |unit| unit.find_name(name, self).transpose())
+    ) -> impl Iterator<Item = Result<(&'slf Function<'dwarf>, &'slf Unit<'dwarf>), gimli::Error>> + 's
+    {
+        self.units.iter().filter_map(move |unit| {
+            unit.find_name(name, self)
+                .map(|function| function.map(|function| (function, unit)))
+                .transpose()
+        })
     }
 
-    pub(crate) fn for_each_function<F>(&self, mut f: F) -> Result<(), gimli::Error>
+    pub(super) fn for_each_function<F>(&self, mut f: F) -> Result<(), gimli::Error>
     where
-        F: FnMut(&Function<'dwarf>) -> ControlFlow<()>,
+        F: FnMut(&Function<'dwarf>, &Unit<'dwarf>) -> ControlFlow<()>,
     {
         for unit in self.units.iter() {
             let functions = unit.parse_functions(self)?;
 
             for function in functions.functions.iter() {
-                if let ControlFlow::Break(()) = f(function) {
+                if let ControlFlow::Break(()) = f(function, unit) {
+                    return Ok(())
+                }
+            }
+        }
+        Ok(())
+    }
+
+    pub(super) fn for_each_variable<'slf, F>(&'slf self, mut f: F) -> Result<(), gimli::Error>
+    where
+        F: FnMut(&'slf Variable<'dwarf>, &'slf Unit<'dwarf>) -> ControlFlow<()>,
+    {
+        for unit in self.units.iter() {
+            let variables = unit.parse_variables(self)?;
+
+            for variable in variables.variables.iter() {
+                if let ControlFlow::Break(()) = f(variable, unit) {
                     return Ok(())
                 }
             }
@@ -435,6 +730,25 @@ impl<'dwarf> Units<'dwarf> {
         gimli::UnitRef::new(&self.dwarf, unit)
     }
 
+    /// Load the split unit corresponding to the skeleton unit `unit`.
+    pub(super) fn load_dwo(
+        &self,
+        unit: &gimli::Unit<R<'dwarf>>,
+    ) -> Result<Option<DwoUnit<'dwarf>>> {
+        self.dwo_loader.load(unit, &self.dwarf)
+    }
+
+    /// Eagerly parse the function and line information of all units,
+    /// which otherwise happens lazily on first use.
+    pub(super) fn preload(&self) -> Result<(), gimli::Error> {
+        for unit in self.units.iter() {
+            let _functions = unit.parse_functions(self)?;
+            let unit_ref = self.unit_ref(unit.dw_unit());
+            let _lines = unit.parse_lines(unit_ref)?;
+        }
+        Ok(())
+    }
+
     /// Initialize all function data structures. This is used for benchmarks.
     #[cfg(test)]
     #[cfg(feature = "nightly")]
@@ -478,6 +792,7 @@ mod tests {
     #[cfg(feature = "nightly")]
     use std::hint::black_box;
     use std::path::Path;
+    use std::ptr;
 
     use gimli::Dwarf;
 
@@ -528,9 +843,13 @@ mod tests {
             // by checking that we can find a function that we know
             // should exist.
             let mut funcs = units.find_name("fibonacci");
-            let func = funcs.next().unwrap().unwrap();
+            let (func, unit) = funcs.next().unwrap().unwrap();
             assert_eq!(func.name.unwrap().to_string().unwrap(), "fibonacci");
 
+            let decl = units.find_decl_location(func, unit).unwrap().unwrap();
+            assert_eq!(decl.file, OsStr::new("test-exe.c"));
+            assert_eq!(decl.line, Some(4));
+
             let addr = func.range.as_ref().unwrap().begin;
             let loc = units.find_location(addr).unwrap().unwrap();
             assert_ne!(loc.dir, Path::new(""));
@@ -541,6 +860,76 @@ mod tests {
         }
     }
 
+    /// Check that the line program sequence ranges we determine
+    /// without building a line table match those of the line table.
+    #[test]
+    fn line_sequence_ranges() {
+        let bin_name = Path::new(&env!("CARGO_MANIFEST_DIR"))
+            .join("data")
+            .join("test-dwarf-v5.bin");
+
+        let parser = ElfParser::open(bin_name.as_ref()).unwrap();
+        let mut load_section = |section| reader::load_section(&parser, section);
+        let dwarf = Dwarf::<R>::load(&mut load_section).unwrap();
+        let units = Units::parse(dwarf).unwrap();
+
+        let mut checked = 0;
+        for unit in units.units.iter() {
+            let ilnp = match unit.dw_unit().line_program {
+                Some(ref ilnp) => ilnp,
+                None => continue,
+            };
+            let ranges = Lines::parse_ranges(ilnp.clone()).unwrap();
+            let unit_ref = units.unit_ref(unit.dw_unit());
+            let lines = Lines::parse(unit_ref, ilnp.clone()).unwrap();
+            let mut expected = lines
+                .sequences
+                .iter()
+                .map(|sequence| (sequence.start, sequence.end))
+                .collect::<Vec<_>>();
+            let mut ranges = ranges
+                .into_iter()
+                .map(|range| (range.begin, range.end))
+                .collect::<Vec<_>>();
+            let () = expected.sort();
+            let () = ranges.sort();
+            assert_eq!(ranges, expected);
+            assert!(!ranges.is_empty());
+            checked += 1;
+        }
+        assert_ne!(checked, 0);
+    }
+
+    /// Check that a unit's line table is built once and then reused by
+    /// subsequent location lookups, instead of re-running the line
+    /// number program for each.
+    #[test]
+    fn line_table_caching() {
+        let bin_name = Path::new(&env!("CARGO_MANIFEST_DIR"))
+            .join("data")
+            .join("test-stable-addrs.bin");
+
+        let parser = ElfParser::open(bin_name.as_ref()).unwrap();
+        let mut load_section = |section| reader::load_section(&parser, section);
+        let dwarf = Dwarf::<R>::load(&mut load_section).unwrap();
+        let units = Units::parse(dwarf).unwrap();
+
+        let mut funcs = units.find_name("factorial");
+        let (func, unit) = funcs.next().unwrap().unwrap();
+        let range = func.range.as_ref().unwrap();
+        assert!(unit.cached_lines().is_none());
+
+        let loc = units.find_location(range.begin).unwrap().unwrap();
+        assert_eq!(loc.file, OsStr::new("test-stable-addrs.c"));
+        let lines = unit.cached_lines().unwrap() as *const Lines<'_>;
+
+        for addr in range.begin..range.end {
+            let loc = units.find_location(addr).unwrap().unwrap();
+            assert_eq!(loc.file, OsStr::new("test-stable-addrs.c"));
+            assert!(ptr::eq(unit.cached_lines().unwrap(), lines));
+        }
+    }
+
     /// Check that we fail to find any data for an address not
     /// represented.
     #[test]
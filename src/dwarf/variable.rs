@@ -0,0 +1,465 @@
+use gimli::Endianity as _;
+use gimli::Error;
+use gimli::EvaluationResult;
+use gimli::Reader as _;
+use gimli::ValueType;
+
+use crate::inspect::TargetAccess;
+
+use super::function::decl_entry;
+use super::function::name_attr;
+use super::function::MAX_DECL_DEPTH;
+use super::reader::R;
+use super::units::Units;
+
+
+/// The maximum number of type entries we follow when determining the
+/// size of a variable.
+const MAX_TYPE_DEPTH: usize = 16;
+/// The maximum number of operations we evaluate for a single location
+/// expression, guarding against endless loops.
+const MAX_EVAL_ITERATIONS: u32 = 4096;
+
+
+type UnitOffset<'dwarf> = gimli::UnitOffset<<R<'dwarf> as gimli::Reader>::Offset>;
+
+
+/// Resolve a reference to a debugging information entry.
+fn resolve_ref<'unit, 'dwarf>(
+    attr: gimli::AttributeValue<R<'dwarf>>,
+    unit: gimli::UnitRef<'unit, R<'dwarf>>,
+    units: &'unit Units<'dwarf>,
+) -> Result<Option<(gimli::UnitRef<'unit, R<'dwarf>>, UnitOffset<'dwarf>)>, Error> {
+    match attr {
+        gimli::AttributeValue::UnitRef(offset) => Ok(Some((unit, offset))),
+        gimli::AttributeValue::DebugInfoRef(offset) => units.find_unit(unit, offset).map(Some),
+        _ => Ok(None),
+    }
+}
+
+
+/// Determine the size of the type described by the entry at `offset`,
+/// in bytes.
+fn type_size<'dwarf>(
+    unit: gimli::UnitRef<'_, R<'dwarf>>,
+    offset: UnitOffset<'dwarf>,
+    units: &Units<'dwarf>,
+    recursion_limit: usize,
+) -> Result<Option<u64>, Error> {
+    if recursion_limit == 0 {
+        return Ok(None)
+    }
+
+    let entry = unit.entry(offset)?;
+    if let Some(size) = entry
+        .attr_value(gimli::DW_AT_byte_size)?
+        .and_then(|value| value.udata_value())
+    {
+        return Ok(Some(size))
+    }
+
+    let inner_size = |entry: &gimli::DebuggingInformationEntry<'_, '_, R<'dwarf>>| {
+        if let Some(attr) = entry.attr_value(gimli::DW_AT_type)? {
+            if let Some((unit, offset)) = resolve_ref(attr, unit, units)? {
+                return type_size(unit, offset, units, recursion_limit - 1)
+            }
+        }
+        Ok(None)
+    };
+
+    match entry.tag() {
+        gimli::DW_TAG_typedef
+        | gimli::DW_TAG_const_type
+        | gimli::DW_TAG_volatile_type
+        | gimli::DW_TAG_restrict_type
+        | gimli::DW_TAG_atomic_type => inner_size(&entry),
+        gimli::DW_TAG_pointer_type
+        | gimli::DW_TAG_reference_type
+        | gimli::DW_TAG_rvalue_reference_type => Ok(Some(u64::from(unit.encoding().address_size))),
+        gimli::DW_TAG_array_type => {
+            let elem_size = if let Some(elem_size) = inner_size(&entry)? {
+                elem_size
+            } else {
+                return Ok(None)
+            };
+
+            let mut count = 1u64;
+            let mut tree = unit.entries_tree(Some(offset))?;
+            let mut children = tree.root()?.children();
+            while let Some(child) = children.next()? {
+                let child = child.entry();
+                if child.tag() != gimli::DW_TAG_subrange_type {
+                    continue
+                }
+
+                let dim_count = if let Some(dim_count) = child
+                    .attr_value(gimli::DW_AT_count)?
+                    .and_then(|value| value.udata_value())
+                {
+                    Some(dim_count)
+                } else if let Some(upper) = child
+                    .attr_value(gimli::DW_AT_upper_bound)?
+                    .and_then(|value| value.udata_value())
+                {
+                    let lower = child
+                        .attr_value(gimli::DW_AT_lower_bound)?
+                        .and_then(|value| value.udata_value())
+                        .unwrap_or(0);
+                    upper
+                        .checked_sub(lower)
+                        .and_then(|count| count.checked_add(1))
+                } else {
+                    None
+                };
+
+                match dim_count.and_then(|dim_count| count.checked_mul(dim_count)) {
+                    Some(new_count) => count = new_count,
+                    // Flexible and variable length arrays do not have a
+                    // size we could report.
+                    None => return Ok(None),
+                }
+            }
+            Ok(elem_size.checked_mul(count))
+        }
+        _ => Ok(None),
+    }
+}
+
+
+/// Evaluate a location expression, returning the address it refers
+/// to if it describes a variable at a fixed address.
+fn static_addr(
+    unit: gimli::UnitRef<'_, R<'_>>,
+    expr: gimli::Expression<R<'_>>,
+) -> Result<Option<u64>, Error> {
+    let mut bytes = expr.0;
+    let op = gimli::Operation::parse(&mut bytes, unit.encoding())?;
+    // Anything but a sole address (e.g., a thread local variable's
+    // offset followed by `DW_OP_form_tls_address`) does not describe
+    // a fixed location.
+    if !bytes.is_empty() {
+        return Ok(None)
+    }
+
+    let addr = match op {
+        gimli::Operation::Address { address } => address,
+        gimli::Operation::AddressIndex { index } => unit.address(index)?,
+        _ => return Ok(None),
+    };
+    Ok(Some(addr))
+}
+
+
+/// Check whether a location expression describes a location that is
+/// independent of any particular stack frame, given access to the
+/// target (i.e., a process) the variable lives in.
+///
+/// Such expressions may, for example, describe thread-local variables
+/// or variables referenced through a pointer.
+fn is_frame_independent(
+    unit: gimli::UnitRef<'_, R<'_>>,
+    expr: gimli::Expression<R<'_>>,
+) -> Result<bool, Error> {
+    let mut ops = expr.operations(unit.encoding());
+    let mut empty = true;
+    while let Some(op) = ops.next()? {
+        empty = false;
+        match op {
+            gimli::Operation::FrameOffset { .. }
+            | gimli::Operation::CallFrameCFA
+            | gimli::Operation::EntryValue { .. }
+            | gimli::Operation::ParameterRef { .. }
+            | gimli::Operation::PushObjectAddress
+            | gimli::Operation::Call { .. } => return Ok(false),
+            _ => (),
+        }
+    }
+    Ok(!empty)
+}
+
+
+/// Determine the value type of the base type entry at `offset`.
+fn value_type(unit: gimli::UnitRef<'_, R<'_>>, offset: UnitOffset<'_>) -> crate::Result<ValueType> {
+    if offset.0 == 0 {
+        return Ok(ValueType::Generic)
+    }
+
+    let entry = unit.entry(offset)?;
+    let value_type = ValueType::from_entry(&entry)?.ok_or_else(|| {
+        crate::Error::with_invalid_data(format!(
+            "DWARF entry at offset {:#x} is not a supported base type",
+            offset.0
+        ))
+    })?;
+    Ok(value_type)
+}
+
+
+/// Evaluate a location expression using `target`, returning the
+/// address at which the variable it describes resides in the target.
+///
+/// `None` is returned if the variable does not reside in memory or if
+/// its location depends on a stack frame.
+pub(crate) fn eval_location(
+    unit: gimli::UnitRef<'_, R<'_>>,
+    expr: gimli::Expression<R<'_>>,
+    target: &dyn TargetAccess,
+) -> crate::Result<Option<u64>> {
+    let endian = expr.0.endian();
+    let mut eval = expr.evaluation(unit.encoding());
+    let () = eval.set_max_iterations(MAX_EVAL_ITERATIONS);
+
+    let mut result = eval.evaluate()?;
+    loop {
+        result = match result {
+            EvaluationResult::Complete => break,
+            EvaluationResult::RequiresMemory {
+                address,
+                size,
+                space: _,
+                base_type,
+            } => {
+                let size = usize::from(size);
+                let mut data = [0; 8];
+                let bytes = data.get_mut(..size).ok_or_else(|| {
+                    crate::Error::with_unsupported(format!(
+                        "memory reads of {size} bytes are unsupported"
+                    ))
+                })?;
+                let () = target.read_memory(address, bytes)?;
+                let value = if endian.is_big_endian() {
+                    let () = data.rotate_right(8 - size);
+                    u64::from_be_bytes(data)
+                } else {
+                    u64::from_le_bytes(data)
+                };
+                let value = gimli::Value::from_u64(value_type(unit, base_type)?, value)?;
+                eval.resume_with_memory(value)?
+            }
+            EvaluationResult::RequiresRegister {
+                register,
+                base_type,
+            } => {
+                let value = target.register(register.0)?;
+                let value = gimli::Value::from_u64(value_type(unit, base_type)?, value)?;
+                eval.resume_with_register(value)?
+            }
+            EvaluationResult::RequiresTls(offset) => {
+                let addr = target.tls_addr(offset)?;
+                eval.resume_with_tls(addr)?
+            }
+            EvaluationResult::RequiresRelocatedAddress(addr) => {
+                let addr = target.relocate(addr)?;
+                eval.resume_with_relocated_address(addr)?
+            }
+            EvaluationResult::RequiresIndexedAddress { index, relocate } => {
+                let addr = unit.address(index)?;
+                let addr = if relocate {
+                    target.relocate(addr)?
+                } else {
+                    addr
+                };
+                eval.resume_with_indexed_address(addr)?
+            }
+            EvaluationResult::RequiresBaseType(offset) => {
+                eval.resume_with_base_type(value_type(unit, offset)?)?
+            }
+            // We do not know about any stack frames.
+            EvaluationResult::RequiresFrameBase
+            | EvaluationResult::RequiresCallFrameCfa
+            | EvaluationResult::RequiresAtLocation(..)
+            | EvaluationResult::RequiresEntryValue(..)
+            | EvaluationResult::RequiresParameterRef(..) => return Ok(None),
+        };
+    }
+
+    let addr = match eval.result().as_slice() {
+        [gimli::Piece {
+            size_in_bits: None,
+            bit_offset: None,
+            location: gimli::Location::Address { address },
+        }] => Some(*address),
+        // Variables split into multiple pieces or not residing in
+        // memory at all don't have an address we could report.
+        _ => None,
+    };
+    Ok(addr)
+}
+
+
+/// The location of a variable.
+pub(crate) enum VarLocation<'dwarf> {
+    /// The variable resides at a fixed address.
+    Addr(u64),
+    /// The variable's location is described by an expression that can
+    /// only be evaluated with access to the target.
+    Expr(gimli::Expression<R<'dwarf>>),
+}
+
+
+/// A variable that is not bound to a stack frame.
+pub(crate) struct Variable<'dwarf> {
+    /// The variable's name.
+    pub(crate) name: R<'dwarf>,
+    /// The variable's location.
+    pub(crate) location: VarLocation<'dwarf>,
+    /// The variable's size, in bytes, if it could be determined.
+    pub(crate) size: Option<u64>,
+    /// The index of the file in which the variable is declared.
+    pub(crate) decl_file: Option<u64>,
+    /// The line at which the variable is declared.
+    pub(crate) decl_line: Option<u32>,
+}
+
+
+pub(crate) struct Variables<'dwarf> {
+    /// List of all `DW_TAG_variable` entries in the unit that describe
+    /// variables at a fixed address or at a location that can be
+    /// determined with access to the target.
+    pub(crate) variables: Box<[Variable<'dwarf>]>,
+}
+
+impl<'dwarf> Variables<'dwarf> {
+    pub(crate) fn parse(
+        unit: gimli::UnitRef<'_, R<'dwarf>>,
+        units: &Units<'dwarf>,
+    ) -> Result<Self, Error> {
+        let mut variables = Vec::new();
+        let mut entries = unit.entries_raw(None)?;
+        while !entries.is_empty() {
+            let abbrev = if let Some(abbrev) = entries.read_abbreviation()? {
+                abbrev
+            } else {
+                continue
+            };
+
+            if abbrev.tag() != gimli::DW_TAG_variable {
+                let () = entries.skip_attributes(abbrev.attributes())?;
+                continue
+            }
+
+            let mut name = None;
+            let mut decl = None;
+            let mut type_ = None;
+            let mut addr = None;
+            let mut location_expr = None;
+            let mut decl_file = None;
+            let mut decl_line = None;
+            for spec in abbrev.attributes() {
+                let attr = entries.read_attribute(*spec)?;
+                match attr.name() {
+                    gimli::DW_AT_linkage_name | gimli::DW_AT_MIPS_linkage_name => {
+                        if let Ok(val) = unit.attr_string(attr.value()) {
+                            name = Some(val);
+                        }
+                    }
+                    gimli::DW_AT_name if name.is_none() => {
+                        name = unit.attr_string(attr.value()).ok();
+                    }
+                    gimli::DW_AT_specification | gimli::DW_AT_abstract_origin => {
+                        decl = Some(attr.value());
+                    }
+                    gimli::DW_AT_type => {
+                        type_ = Some(attr.value());
+                    }
+                    gimli::DW_AT_location => match attr.value() {
+                        gimli::AttributeValue::Exprloc(expr) => {
+                            addr = static_addr(unit, expr)?;
+                            if addr.is_none() && is_frame_independent(unit, expr)? {
+                                location_expr = Some(expr);
+                            }
+                        }
+                        value => {
+                            // The location may also be described by a
+                            // location list (`.debug_loc` or, with
+                            // DWARF 5, `.debug_loclists`), in which
+                            // case we use the first fixed address
+                            // listed.
+                            if let Some(offset) = unit.attr_locations_offset(value)? {
+                                let mut locations = unit.locations(offset)?;
+                                while let Some(location) = locations.next()? {
+                                    addr = static_addr(unit, location.data)?;
+                                    if addr.is_some() {
+                                        break
+                                    }
+                                }
+                            }
+                        }
+                    },
+                    gimli::DW_AT_decl_file => {
+                        // See `InlinedFunction::parse` for details on
+                        // file index 0 handling.
+                        if let gimli::AttributeValue::FileIndex(fi) = attr.value() {
+                            if fi > 0 || unit.header.version() >= 5 {
+                                decl_file = Some(fi);
+                            }
+                        }
+                    }
+                    gimli::DW_AT_decl_line => {
+                        decl_line = attr.udata_value().and_then(|l| u32::try_from(l).ok());
+                    }
+                    _ => {}
+                }
+            }
+
+            // Variables without a location independent of a stack
+            // frame (e.g., ones allocated on the stack or declarations
+            // of variables defined elsewhere) are of no interest.
+            // Neither are those that were discarded by the linker and
+            // left at address zero.
+            let location = match (addr, location_expr) {
+                (Some(addr), _) if addr != 0 => VarLocation::Addr(addr),
+                (None, Some(expr)) => VarLocation::Expr(expr),
+                _ => continue,
+            };
+
+            // Out-of-line definitions (e.g., of static class members)
+            // may only refer to their declaration, which carries the
+            // name and type.
+            if let Some(decl) = decl {
+                if name.is_none() {
+                    name = name_attr(decl, unit, units, MAX_TYPE_DEPTH)?;
+                }
+                if type_.is_none() {
+                    if let Some((unit, offset)) = resolve_ref(decl, unit, units)? {
+                        type_ = unit.entry(offset)?.attr_value(gimli::DW_AT_type)?;
+                    }
+                }
+                if decl_file.is_none() || decl_line.is_none() {
+                    if let gimli::AttributeValue::UnitRef(offset) = decl {
+                        let (file, line) = decl_entry(unit, offset, MAX_DECL_DEPTH)?;
+                        decl_file = decl_file.or(file);
+                        decl_line = decl_line.or(line);
+                    }
+                }
+            }
+
+            let name = match name {
+                Some(name) => name,
+                None => continue,
+            };
+
+            let size = if let Some(type_) = type_ {
+                match resolve_ref(type_, unit, units)? {
+                    Some((unit, offset)) => type_size(unit, offset, units, MAX_TYPE_DEPTH)?,
+                    None => None,
+                }
+            } else {
+                None
+            };
+
+            let variable = Variable {
+                name,
+                location,
+                size,
+                decl_file,
+                decl_line,
+            };
+            let () = variables.push(variable);
+        }
+
+        Ok(Self {
+            variables: variables.into_boxed_slice(),
+        })
+    }
+}
@@ -0,0 +1,237 @@
+//! Support for BOLT address translation (BAT) data.
+//!
+//! The BOLT post-link optimizer moves and splits functions. When asked
+//! to (`--enable-bat`), it records how it did so in a note section,
+//! which allows for translating addresses in the optimized binary back
+//! to the corresponding ones in the input binary. Addresses are mapped
+//! on a per-function basis: for each output function BOLT records
+//! pairs of output and input offsets relative to the function's start.
+//! Offsets in cold fragments (split off parts of functions) are
+//! relative to the start of the fragment, while input offsets always
+//! refer to the original, unsplit function.
+
+use crate::util::align_up_u32;
+use crate::util::ReadRaw as _;
+use crate::Addr;
+use crate::Error;
+use crate::IntoError as _;
+use crate::Result;
+
+use super::types::ElfN_Nhdr;
+use super::types::NT_BOLT_BAT;
+
+
+/// The name of the section containing BOLT address translation data.
+pub(crate) const BAT_SECTION: &str = ".note.bolt_bat";
+/// The flag marking an input offset as belonging to a branch
+/// instruction.
+const BRANCH_ENTRY: u32 = 0x8000_0000;
+
+
+/// The address translation data of a single (output) function or
+/// function fragment.
+#[derive(Debug)]
+struct FuncMap {
+    /// The address of the function in the optimized binary.
+    addr: Addr,
+    /// Pairs of output and input offsets, sorted by output offset.
+    entries: Box<[(u32, u32)]>,
+}
+
+
+/// Parsed BOLT address translation data.
+#[derive(Debug)]
+pub(crate) struct BoltAddrTranslation {
+    /// Per-function translation data, sorted by address.
+    funcs: Box<[FuncMap]>,
+    /// Pairs of cold fragment addresses and the address of the
+    /// function they were split off from, sorted by the former.
+    cold: Box<[(Addr, Addr)]>,
+}
+
+impl BoltAddrTranslation {
+    /// Parse BOLT address translation data from the contents of the
+    /// note section housing it.
+    pub(crate) fn parse(mut data: &[u8]) -> Result<Self> {
+        let nhdr = data
+            .read_pod::<ElfN_Nhdr>()
+            .ok_or_invalid_data(|| "failed to read BOLT address translation note header")?;
+        if nhdr.n_type != NT_BOLT_BAT {
+            return Err(Error::with_invalid_data(format!(
+                "encountered unexpected BOLT note type {}",
+                nhdr.n_type
+            )))
+        }
+
+        let name = data
+            .read_slice(align_up_u32(nhdr.n_namesz, 4) as _)
+            .ok_or_invalid_data(|| "failed to read BOLT address translation note name")?;
+        if !name.starts_with(b"BOLT") {
+            return Err(Error::with_invalid_data(
+                "BOLT address translation note has unexpected name",
+            ))
+        }
+
+        let desc = data
+            .read_slice(nhdr.n_descsz as _)
+            .ok_or_invalid_data(|| "failed to read BOLT address translation note descriptor")?;
+        Self::parse_desc(desc)
+    }
+
+    fn parse_desc(mut data: &[u8]) -> Result<Self> {
+        let count = data
+            .read_u32()
+            .ok_or_invalid_data(|| "failed to read BOLT function count")?;
+        let mut funcs = (0..count)
+            .map(|_| {
+                let addr = data
+                    .read_u64()
+                    .ok_or_invalid_data(|| "failed to read BOLT function address")?;
+                let count = data
+                    .read_u32()
+                    .ok_or_invalid_data(|| "failed to read BOLT entry count")?;
+                let mut entries = (0..count)
+                    .map(|_| {
+                        let output = data.read_u32()?;
+                        let input = data.read_u32()?;
+                        Some((output, input))
+                    })
+                    .collect::<Option<Box<[_]>>>()
+                    .ok_or_invalid_data(|| "failed to read BOLT translation entries")?;
+                let () = entries.sort_unstable_by_key(|(output, _input)| *output);
+                Ok(FuncMap {
+                    addr: addr as Addr,
+                    entries,
+                })
+            })
+            .collect::<Result<Box<[_]>>>()?;
+        let () = funcs.sort_unstable_by_key(|func| func.addr);
+
+        let count = data
+            .read_u32()
+            .ok_or_invalid_data(|| "failed to read BOLT cold fragment count")?;
+        let mut cold = (0..count)
+            .map(|_| {
+                let cold = data.read_u64()?;
+                let hot = data.read_u64()?;
+                Some((cold as Addr, hot as Addr))
+            })
+            .collect::<Option<Box<[_]>>>()
+            .ok_or_invalid_data(|| "failed to read BOLT cold fragment entries")?;
+        let () = cold.sort_unstable_by_key(|(cold, _hot)| *cold);
+
+        Ok(Self { funcs, cold })
+    }
+
+    /// Translate `addr`, which is part of the function (or function
+    /// fragment) starting at `func_addr` in the optimized binary.
+    ///
+    /// On success, the address of the (parent) function in the
+    /// optimized binary is returned, along with the offset of the
+    /// corresponding instruction from the start of the function in
+    /// the input binary. `None` is returned if the function was not
+    /// touched by BOLT.
+    pub(crate) fn translate(&self, func_addr: Addr, addr: Addr) -> Option<(Addr, u64)> {
+        let idx = self
+            .funcs
+            .binary_search_by_key(&func_addr, |func| func.addr)
+            .ok()?;
+        let func = &self.funcs[idx];
+        let offset = u32::try_from(addr.checked_sub(func_addr)?).ok()?;
+
+        let idx = func
+            .entries
+            .partition_point(|(output, _input)| *output <= offset);
+        let input = if let Some(idx) = idx.checked_sub(1) {
+            let (output, input) = func.entries[idx];
+            // Branches are mapped exactly, as BOLT may have rewritten
+            // the instructions surrounding them.
+            if input & BRANCH_ENTRY != 0 {
+                input & !BRANCH_ENTRY
+            } else {
+                (offset - output).wrapping_add(input)
+            }
+        } else {
+            offset
+        };
+
+        let parent = match self
+            .cold
+            .binary_search_by_key(&func_addr, |(cold, _hot)| *cold)
+        {
+            Ok(idx) => self.cold[idx].1,
+            Err(_idx) => func_addr,
+        };
+        Some((parent, u64::from(input)))
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+
+    /// Create the contents of a BOLT address translation note section
+    /// with the given descriptor.
+    fn note(desc: &[u8]) -> Vec<u8> {
+        let mut data = Vec::new();
+        let () = data.extend_from_slice(&5u32.to_ne_bytes());
+        let () = data.extend_from_slice(&(desc.len() as u32).to_ne_bytes());
+        let () = data.extend_from_slice(&NT_BOLT_BAT.to_ne_bytes());
+        let () = data.extend_from_slice(b"BOLT\0\0\0\0");
+        let () = data.extend_from_slice(desc);
+        data
+    }
+
+    /// Check that we can parse BOLT address translation data and
+    /// translate addresses using it.
+    #[test]
+    fn translation() {
+        let mut desc = Vec::new();
+        // Two functions: a hot one at 0x2000 and a cold fragment of it
+        // at 0x9000.
+        let () = desc.extend_from_slice(&2u32.to_ne_bytes());
+        let () = desc.extend_from_slice(&0x9000u64.to_ne_bytes());
+        let () = desc.extend_from_slice(&1u32.to_ne_bytes());
+        let () = desc.extend_from_slice(&0x0u32.to_ne_bytes());
+        let () = desc.extend_from_slice(&0x40u32.to_ne_bytes());
+        let () = desc.extend_from_slice(&0x2000u64.to_ne_bytes());
+        let () = desc.extend_from_slice(&3u32.to_ne_bytes());
+        for (output, input) in [(0x0u32, 0x0u32), (0x10, 0x60), (0x18, 0x24 | BRANCH_ENTRY)] {
+            let () = desc.extend_from_slice(&output.to_ne_bytes());
+            let () = desc.extend_from_slice(&input.to_ne_bytes());
+        }
+        let () = desc.extend_from_slice(&1u32.to_ne_bytes());
+        let () = desc.extend_from_slice(&0x9000u64.to_ne_bytes());
+        let () = desc.extend_from_slice(&0x2000u64.to_ne_bytes());
+
+        let bat = BoltAddrTranslation::parse(&note(&desc)).unwrap();
+        assert_ne!(format!("{bat:?}"), "");
+
+        assert_eq!(bat.translate(0x2000, 0x2004), Some((0x2000, 0x4)));
+        assert_eq!(bat.translate(0x2000, 0x2014), Some((0x2000, 0x64)));
+        assert_eq!(bat.translate(0x2000, 0x201c), Some((0x2000, 0x24)));
+        assert_eq!(bat.translate(0x9000, 0x9008), Some((0x2000, 0x48)));
+        assert_eq!(bat.translate(0x3000, 0x3008), None);
+        assert_eq!(bat.translate(0x2000, 0x1000), None);
+    }
+
+    /// Make sure that we reject malformed BOLT address translation
+    /// data.
+    #[test]
+    fn malformed_data() {
+        let err = BoltAddrTranslation::parse(&[]).unwrap_err();
+        assert!(err.to_string().contains("note header"), "{err}");
+
+        let mut data = note(&[]);
+        data[0..4].copy_from_slice(&3u32.to_ne_bytes());
+        data[12..16].copy_from_slice(b"GNU\0");
+        let err = BoltAddrTranslation::parse(&data).unwrap_err();
+        assert!(err.to_string().contains("unexpected name"), "{err}");
+
+        let desc = 1u32.to_ne_bytes();
+        let err = BoltAddrTranslation::parse(&note(&desc)).unwrap_err();
+        assert!(err.to_string().contains("function address"), "{err}");
+    }
+}
@@ -0,0 +1,278 @@
+//! Functionality for reconstructing the memory map of a process from
+//! an ELF core dump.
+
+use std::mem::size_of;
+use std::ops::Range;
+use std::path::Path;
+use std::path::PathBuf;
+
+use crate::util::align_up_u32;
+use crate::util::bytes_to_path;
+use crate::util::find_range_by_key;
+use crate::util::ReadRaw as _;
+use crate::Addr;
+use crate::Error;
+use crate::IntoError as _;
+use crate::Result;
+
+use super::types::ElfN_Nhdr;
+use super::types::AT_ENTRY;
+use super::types::AT_NULL;
+use super::types::ET_CORE;
+use super::types::NT_AUXV;
+use super::types::NT_FILE;
+use super::types::PT_NOTE;
+use super::ElfParser;
+
+
+fn read_word(data: &mut &[u8], is_32bit: bool) -> Option<u64> {
+    if is_32bit {
+        data.read_u32().map(u64::from)
+    } else {
+        data.read_u64()
+    }
+}
+
+
+/// A file backed memory mapping, as recorded in a core dump.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct CoreMapping {
+    /// The virtual address range covered by the mapping.
+    pub range: Range<Addr>,
+    /// The offset into the mapped file at which the mapping starts.
+    pub file_off: u64,
+    /// The path of the mapped file, as recorded at the time of the
+    /// dump.
+    pub path: PathBuf,
+}
+
+
+/// Parse the descriptor of an `NT_FILE` note.
+fn parse_nt_file(mut desc: &[u8], is_32bit: bool) -> Result<Vec<CoreMapping>> {
+    let count = read_word(&mut desc, is_32bit)
+        .ok_or_invalid_data(|| "failed to read NT_FILE entry count")?;
+    let page_size =
+        read_word(&mut desc, is_32bit).ok_or_invalid_data(|| "failed to read NT_FILE page size")?;
+
+    let word_size = if is_32bit {
+        size_of::<u32>()
+    } else {
+        size_of::<u64>()
+    };
+    let count = usize::try_from(count)
+        .ok()
+        .filter(|count| count.saturating_mul(3 * word_size) <= desc.len())
+        .ok_or_invalid_data(|| format!("NT_FILE entry count ({count}) is invalid"))?;
+
+    let mut ranges = Vec::with_capacity(count);
+    for _ in 0..count {
+        let start = read_word(&mut desc, is_32bit);
+        let end = read_word(&mut desc, is_32bit);
+        let page_off = read_word(&mut desc, is_32bit);
+        let (start, end, page_off) = start
+            .zip(end)
+            .zip(page_off)
+            .map(|((start, end), page_off)| (start, end, page_off))
+            .ok_or_invalid_data(|| "failed to read NT_FILE entry")?;
+        let file_off = page_off
+            .checked_mul(page_size)
+            .ok_or_invalid_data(|| format!("NT_FILE file offset ({page_off:#x}) is invalid"))?;
+        let () = ranges.push((start..end, file_off));
+    }
+
+    ranges
+        .into_iter()
+        .map(|(range, file_off)| {
+            let name = desc
+                .read_cstr()
+                .ok_or_invalid_data(|| "failed to read NT_FILE entry path")?;
+            let path = bytes_to_path(name.to_bytes())?.to_path_buf();
+            Ok(CoreMapping {
+                range,
+                file_off,
+                path,
+            })
+        })
+        .collect()
+}
+
+/// Parse the descriptor of an `NT_AUXV` note and extract the entry
+/// point address of the program, if present.
+fn parse_auxv_entry(mut desc: &[u8], is_32bit: bool) -> Option<Addr> {
+    loop {
+        let type_ = read_word(&mut desc, is_32bit);
+        let value = read_word(&mut desc, is_32bit);
+        match type_.zip(value) {
+            Some((AT_NULL, _)) | None => break None,
+            Some((AT_ENTRY, value)) => break Some(value),
+            Some(_) => continue,
+        }
+    }
+}
+
+
+/// Memory map information reconstructed from a core dump.
+#[derive(Debug, Default)]
+pub(crate) struct CoreMaps {
+    /// The file backed memory mappings, sorted by start address.
+    maps: Vec<CoreMapping>,
+    /// The entry point address of the program, if known.
+    entry: Option<Addr>,
+}
+
+impl CoreMaps {
+    /// Parse the memory map information contained in the `PT_NOTE`
+    /// segments of a core dump.
+    pub(crate) fn parse(parser: &ElfParser) -> Result<Self> {
+        let file_type = parser.file_type()?;
+        if file_type != ET_CORE {
+            return Err(Error::with_invalid_input(format!(
+                "ELF file is not a core dump (type: {file_type})"
+            )))
+        }
+
+        let is_32bit = parser.is_32bit()?;
+        let phdrs = parser.program_headers()?;
+        let mut maps = Vec::new();
+        let mut entry = None;
+
+        for (idx, phdr) in phdrs.iter(0).enumerate() {
+            if phdr.type_() != PT_NOTE {
+                continue
+            }
+
+            let data = parser.segment_data(idx)?;
+            let mut bytes = &*data;
+
+            while bytes.len() >= size_of::<ElfN_Nhdr>() {
+                let nhdr = bytes
+                    .read_pod::<ElfN_Nhdr>()
+                    .ok_or_invalid_data(|| "failed to read ELF note header")?;
+                let () = bytes
+                    .advance(align_up_u32(nhdr.n_namesz, 4) as _)
+                    .ok_or_invalid_data(|| "failed to skip over ELF note name")?;
+                let desc = bytes
+                    .read_slice(nhdr.n_descsz as _)
+                    .ok_or_invalid_data(|| "failed to read ELF note descriptor")?;
+                let pad = align_up_u32(nhdr.n_descsz, 4) - nhdr.n_descsz;
+                // The descriptor of the last note may not be padded, in
+                // which case we are done.
+                let skipped = bytes.advance(pad as _);
+
+                match nhdr.n_type {
+                    NT_FILE => {
+                        let () = maps.extend(parse_nt_file(desc, is_32bit)?);
+                    }
+                    NT_AUXV => {
+                        entry = parse_auxv_entry(desc, is_32bit);
+                    }
+                    _ => (),
+                }
+
+                if skipped.is_none() {
+                    break
+                }
+            }
+        }
+
+        let () = maps.sort_by_key(|map| map.range.start);
+        Ok(Self { maps, entry })
+    }
+
+    /// Find the memory mapping containing `addr`.
+    pub(crate) fn find(&self, addr: Addr) -> Option<&CoreMapping> {
+        let idx = find_range_by_key(&self.maps, addr, |map| &map.range)?;
+        self.maps.get(idx)
+    }
+
+    /// Retrieve the path of the main executable, as recorded in the
+    /// core dump, if it could be determined.
+    pub(crate) fn exe_path(&self) -> Option<&Path> {
+        let entry = self.entry?;
+        self.find(entry).map(|map| map.path.as_path())
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+
+    fn push_word(data: &mut Vec<u8>, word: u64, is_32bit: bool) {
+        if is_32bit {
+            let () = data.extend_from_slice(&u32::try_from(word).unwrap().to_ne_bytes());
+        } else {
+            let () = data.extend_from_slice(&word.to_ne_bytes());
+        }
+    }
+
+    /// Create the descriptor of an `NT_FILE` note.
+    fn nt_file(entries: &[(u64, u64, u64, &str)], is_32bit: bool) -> Vec<u8> {
+        let mut data = Vec::new();
+        let () = push_word(&mut data, entries.len() as _, is_32bit);
+        let () = push_word(&mut data, 0x1000, is_32bit);
+        for (start, end, page_off, _name) in entries {
+            let () = push_word(&mut data, *start, is_32bit);
+            let () = push_word(&mut data, *end, is_32bit);
+            let () = push_word(&mut data, *page_off, is_32bit);
+        }
+        for (_start, _end, _page_off, name) in entries {
+            let () = data.extend_from_slice(name.as_bytes());
+            let () = data.push(b'\0');
+        }
+        data
+    }
+
+
+    /// Check that we can parse `NT_FILE` note descriptors.
+    #[test]
+    fn nt_file_parsing() {
+        let entries = [
+            (0x400000, 0x401000, 0, "/usr/bin/true"),
+            (0x401000, 0x403000, 1, "/usr/bin/true"),
+            (0x7f0000, 0x7f2000, 3, "/usr/lib/libc.so.6"),
+        ];
+
+        for is_32bit in [false, true] {
+            let desc = nt_file(&entries, is_32bit);
+            let maps = parse_nt_file(&desc, is_32bit).unwrap();
+            assert_eq!(maps.len(), 3);
+            assert_eq!(maps[1].range, 0x401000..0x403000);
+            assert_eq!(maps[1].file_off, 0x1000);
+            assert_eq!(maps[1].path, Path::new("/usr/bin/true"));
+            assert_eq!(maps[2].file_off, 0x3000);
+            assert_eq!(maps[2].path, Path::new("/usr/lib/libc.so.6"));
+
+            let maps = CoreMaps {
+                maps,
+                entry: Some(0x401234),
+            };
+            assert_eq!(maps.find(0x3fffff), None);
+            assert_eq!(maps.find(0x402fff).unwrap().file_off, 0x1000);
+            assert_eq!(maps.find(0x403000), None);
+            assert_eq!(maps.exe_path(), Some(Path::new("/usr/bin/true")));
+
+            // A truncated descriptor should be reported as an error.
+            let _err = parse_nt_file(&desc[..desc.len() / 2], is_32bit).unwrap_err();
+        }
+    }
+
+    /// Check that we can extract the entry point from an `NT_AUXV`
+    /// note descriptor.
+    #[test]
+    fn auxv_entry_parsing() {
+        let mut desc = Vec::new();
+        let () = push_word(&mut desc, 6, false);
+        let () = push_word(&mut desc, 0x1000, false);
+        let () = push_word(&mut desc, AT_ENTRY, false);
+        let () = push_word(&mut desc, 0x401040, false);
+        let () = push_word(&mut desc, AT_NULL, false);
+        let () = push_word(&mut desc, 0, false);
+        let entry = parse_auxv_entry(&desc, false);
+        assert_eq!(entry, Some(0x401040));
+
+        let entry = parse_auxv_entry(&desc[..16], false);
+        assert_eq!(entry, None);
+    }
+}
@@ -1,3 +1,5 @@
+mod bat;
+mod core;
 mod parser;
 #[cfg_attr(not(feature = "dwarf"), allow(unused_variables))]
 mod resolver;
@@ -11,6 +13,7 @@ pub(crate) mod types;
 //       of concerns that is not a workable location.
 pub(crate) static DEFAULT_DEBUG_DIRS: &[&str] = &["/usr/lib/debug", "/lib/debug/"];
 
+pub(crate) use self::core::CoreMaps;
 pub(crate) use parser::BackendImpl;
 pub(crate) use parser::ElfParser;
 pub(crate) use resolver::ElfResolverData;
@@ -9,16 +9,25 @@ use std::io::SeekFrom;
 use std::mem;
 use std::mem::MaybeUninit;
 use std::ops::ControlFlow;
-use std::ops::Deref as _;
+use std::ops::Range;
 use std::path::Path;
 use std::path::PathBuf;
 use std::slice;
 use std::str;
 
+use crate::decompress::decompress;
+use crate::decompress::Compression;
+use crate::index_cache::load_index;
+use crate::index_cache::store_index;
+use crate::index_cache::IndexCache;
+use crate::index_cache::SymIndex;
 use crate::inspect::FindAddrOpts;
 use crate::inspect::ForEachFn;
+use crate::inspect::PltEntry;
 use crate::inspect::SymInfo;
+use crate::log;
 use crate::mmap::Mmap;
+use crate::normalize::buildid::find_build_id_note;
 use crate::once::OnceCell;
 use crate::symbolize::FindSymOpts;
 use crate::symbolize::Reason;
@@ -34,34 +43,54 @@ use crate::IntoError as _;
 use crate::Result;
 use crate::SymType;
 
+use super::bat::BoltAddrTranslation;
+use super::bat::BAT_SECTION;
 use super::types::Elf32_Chdr;
 use super::types::Elf32_Ehdr;
 use super::types::Elf32_Phdr;
+use super::types::Elf32_Rel;
+use super::types::Elf32_Rela;
 use super::types::Elf32_Shdr;
 use super::types::Elf32_Sym;
 use super::types::Elf64_Chdr;
 use super::types::Elf64_Ehdr;
 use super::types::Elf64_Phdr;
+use super::types::Elf64_Rel;
+use super::types::Elf64_Rela;
 use super::types::Elf64_Shdr;
 use super::types::Elf64_Sym;
+use super::types::ElfNSlice;
 use super::types::ElfN_Ehdr;
 use super::types::ElfN_Phdrs;
 use super::types::ElfN_Shdr;
 use super::types::ElfN_Shdrs;
 use super::types::ElfN_Sym;
 use super::types::ElfN_Syms;
+use super::types::Has32BitTy;
 use super::types::EI_NIDENT;
 use super::types::ELFCLASS32;
 use super::types::ELFCLASS64;
 use super::types::ELFCOMPRESS_ZLIB;
 use super::types::ELFCOMPRESS_ZSTD;
+use super::types::ELFDATA2LSB;
+use super::types::ELFDATA2MSB;
+use super::types::EM_386;
+use super::types::EM_AARCH64;
+use super::types::EM_X86_64;
 use super::types::PN_XNUM;
 use super::types::PT_LOAD;
+use super::types::SHF_ALLOC;
 use super::types::SHF_COMPRESSED;
 use super::types::SHN_LORESERVE;
 use super::types::SHN_UNDEF;
 use super::types::SHN_XINDEX;
+use super::types::SHT_DYNSYM;
 use super::types::SHT_NOBITS;
+use super::types::SHT_NOTE;
+use super::types::SHT_REL;
+use super::types::SHT_RELA;
+use super::types::SHT_STRTAB;
+use super::types::SHT_SYMTAB;
 
 
 fn symbol_name<'elf>(strtab: &'elf [u8], sym: &Elf64_Sym) -> Result<&'elf str> {
@@ -126,6 +155,9 @@ fn find_sym<'elf>(
                         // information.
                         code_info: None,
                         inlined: Box::new([]),
+                        inlined_truncated: false,
+                        section: None,
+                        module: None,
                     };
                     return Ok(Some(sym))
                 }
@@ -136,6 +168,22 @@ fn find_sym<'elf>(
 }
 
 
+/// Check whether `name` is the name of a cold fragment split off a
+/// function, returning the function's name if that is the case.
+///
+/// Compilers and post-link optimizers such as BOLT move rarely
+/// executed parts of functions into separate fragments, which are
+/// named `<name>.cold` or `<name>.cold.<n>`.
+fn cold_fragment_base(name: &str) -> Option<&str> {
+    let (base, suffix) = name.rsplit_once(".cold")?;
+    let is_cold = match suffix.strip_prefix('.') {
+        Some(n) => !n.is_empty() && n.bytes().all(|b| b.is_ascii_digit()),
+        None => suffix.is_empty(),
+    };
+    (is_cold && !base.is_empty()).then_some(base)
+}
+
+
 #[cfg(feature = "zlib")]
 fn decompress_zlib(data: &[u8]) -> Result<Vec<u8>> {
     use miniz_oxide::inflate::decompress_to_vec_zlib;
@@ -170,9 +218,9 @@ fn decompress_zstd(_data: &[u8]) -> Result<Vec<u8>> {
 
 
 #[derive(Debug)]
-struct EhdrExt<'elf> {
+struct EhdrExt {
     /// The ELF header.
-    ehdr: ElfN_Ehdr<'elf>,
+    ehdr: ElfN_Ehdr<'static>,
     /// Override of `ehdr.e_shnum`, handling of which is special-cased by
     /// the ELF standard.
     shnum: usize,
@@ -181,19 +229,96 @@ struct EhdrExt<'elf> {
     phnum: usize,
 }
 
-impl EhdrExt<'_> {
+impl EhdrExt {
     fn is_32bit(&self) -> bool {
         self.ehdr.is_32bit()
     }
+
+    /// Retrieve the size of a symbol table entry for the ELF class at
+    /// hand.
+    fn sym_size(&self) -> u64 {
+        if self.is_32bit() {
+            mem::size_of::<Elf32_Sym>() as u64
+        } else {
+            mem::size_of::<Elf64_Sym>() as u64
+        }
+    }
 }
 
 
+/// A contiguous run of objects of type `T` as read from a [`Backend`].
+///
+/// Memory mapped data is referenced by its location in the mapping
+/// instead of by reference, so that the [`Cache`] never refers to the
+/// backend it owns.
 #[derive(Debug)]
-struct SymName {
+pub(crate) enum Region<T> {
+    /// `count` objects located at `offset` in the backend's mapping.
+    Mapped { offset: u64, count: usize },
+    /// Data owned by the region itself.
+    Owned(Box<[T]>),
+}
+
+impl<T> Region<T> {
+    fn empty() -> Self {
+        Self::Owned(Box::default())
+    }
+}
+
+
+/// The 32 bit or 64 bit variant of a [`Region`] of ELF objects.
+#[derive(Debug)]
+enum ElfNRegion<T>
+where
+    T: Has32BitTy,
+{
+    B32(Region<T::Ty32Bit>),
+    B64(Region<T>),
+}
+
+impl<T> ElfNRegion<T>
+where
+    T: Pod + Has32BitTy,
+    T::Ty32Bit: Pod,
+{
+    fn empty(tybit32: bool) -> Self {
+        if tybit32 {
+            Self::B32(Region::empty())
+        } else {
+            Self::B64(Region::empty())
+        }
+    }
+
+    /// Retrieve the objects referenced by this region from `backend`.
+    fn resolve<'slf, B>(&'slf self, backend: &'slf B) -> ElfNSlice<'slf, T>
+    where
+        B: Backend,
+    {
+        match self {
+            Self::B32(region) => ElfNSlice::B32(Cow::Borrowed(backend.resolve(region))),
+            Self::B64(region) => ElfNSlice::B64(Cow::Borrowed(backend.resolve(region))),
+        }
+    }
+}
+
+
+/// A reference to a symbol name in the string table.
+#[derive(Clone, Copy, Debug)]
+struct SymName<I = usize> {
     /// The index of the first byte of the name.
-    idx: usize,
+    idx: I,
     /// The length of the name.
-    len: usize,
+    len: I,
+}
+
+impl SymName<u32> {
+    #[inline]
+    fn widen(&self) -> SymName {
+        SymName {
+            idx: self.idx as usize,
+            len: self.len as usize,
+        }
+    }
 }
 
 impl SymName {
@@ -210,32 +335,118 @@ impl SymName {
 }
 
 
+/// A name to symbol index table, in dictionary order.
 #[derive(Debug)]
-struct SymbolTableCache<'elf> {
+enum Str2Sym {
+    /// A table storing offsets as `u32`, to keep it compact. It may
+    /// contain hundreds of thousands of entries for large binaries
+    /// such as `vmlinux`.
+    Compact(Box<[(SymName<u32>, u32)]>),
+    /// A table storing offsets as `usize`, used if not all of them
+    /// fit into a `u32`.
+    Wide(Box<[(SymName, usize)]>),
+}
+
+impl Str2Sym {
+    /// Retrieve the number of entries in the table.
+    #[inline]
+    fn len(&self) -> usize {
+        match self {
+            Self::Compact(entries) => entries.len(),
+            Self::Wide(entries) => entries.len(),
+        }
+    }
+
+    /// Retrieve the name and symbol index of the entry at `idx`.
+    #[inline]
+    fn get(&self, idx: usize) -> Option<(SymName, usize)> {
+        match self {
+            Self::Compact(entries) => entries
+                .get(idx)
+                .map(|(name, sym_i)| (name.widen(), *sym_i as usize)),
+            Self::Wide(entries) => entries.get(idx).copied(),
+        }
+    }
+
+    /// Find the index of the first entry whose name does not satisfy
+    /// `pred`, akin to [`slice::partition_point`].
+    fn partition_point<P>(&self, mut pred: P) -> usize
+    where
+        P: FnMut(&SymName) -> bool,
+    {
+        match self {
+            Self::Compact(entries) => entries.partition_point(|(name, _i)| pred(&name.widen())),
+            Self::Wide(entries) => entries.partition_point(|(name, _i)| pred(name)),
+        }
+    }
+
+    /// Iterate over all entries starting at index `idx`.
+    fn iter_from(&self, idx: usize) -> impl Iterator<Item = (SymName, usize)> + '_ {
+        // SANITY: All indexes are within the table's bounds.
+        (idx..self.len()).map(|idx| self.get(idx).unwrap())
+    }
+}
+
+
+#[derive(Debug)]
+struct SymbolTableCache {
     /// The cached symbols.
     ///
     /// Note that these are the symbols as they appear in the file,
     /// without any filtering applied.
-    syms: ElfN_Syms<'elf>,
+    syms: ElfNRegion<Elf64_Sym>,
     /// An index over `syms` that is sorted by address and that only
     /// contains a relevant subset of symbols.
-    by_addr_idx: OnceCell<Box<[usize]>>,
+    by_addr_idx: OnceCell<SymIndex>,
+    /// The path to the file `by_addr_idx` is persisted in, if any.
+    by_addr_idx_path: Option<PathBuf>,
     /// The string table.
-    strs: Cow<'elf, [u8]>,
+    strs: Region<u8>,
     /// The cached name to symbol index table (in dictionary order).
-    str2sym: OnceCell<Box<[(SymName, usize)]>>,
+    str2sym: OnceCell<Str2Sym>,
 }
 
-impl<'elf> SymbolTableCache<'elf> {
-    fn new(syms: ElfN_Syms<'elf>, strs: Cow<'elf, [u8]>) -> Self {
+impl SymbolTableCache {
+    fn new(
+        syms: ElfNRegion<Elf64_Sym>,
+        strs: Region<u8>,
+        by_addr_idx_path: Option<PathBuf>,
+    ) -> Self {
         Self {
             syms,
             by_addr_idx: OnceCell::new(),
+            by_addr_idx_path,
             strs,
             str2sym: OnceCell::new(),
         }
     }
 
+    /// Resolve the cached symbol and string table data against
+    /// `backend`.
+    fn resolve<'slf, B>(&'slf self, backend: &'slf B) -> SymbolTable<'slf>
+    where
+        B: Backend,
+    {
+        SymbolTable {
+            syms: self.syms.resolve(backend),
+            strs: backend.resolve(&self.strs),
+            cache: self,
+        }
+    }
+}
+
+
+/// A symbol table, along with its string table and cached indexes.
+struct SymbolTable<'elf> {
+    /// The symbols.
+    syms: ElfN_Syms<'elf>,
+    /// The string table.
+    strs: &'elf [u8],
+    /// The cache holding indexes over `syms`.
+    cache: &'elf SymbolTableCache,
+}
+
+impl<'elf> SymbolTable<'elf> {
     fn create_by_addr_idx(&self) -> Box<[usize]> {
         let mut by_addr_idx = self
             .syms
@@ -261,19 +472,46 @@ impl<'elf> SymbolTableCache<'elf> {
         by_addr_idx
     }
 
-    fn ensure_by_addr_idx(&self) -> &[usize] {
-        self.by_addr_idx.get_or_init(|| self.create_by_addr_idx())
+    /// Load the address index from or create and persist it to the
+    /// on-disk index cache.
+    fn load_or_create_by_addr_idx(&self, path: &Path) -> SymIndex {
+        match load_index(path, self.syms.len()) {
+            Ok(Some(index)) => return index,
+            Ok(None) => (),
+            Err(err) => {
+                log::debug!("ignoring unusable index `{}`: {err}", path.display());
+            }
+        }
+
+        let by_addr_idx = self.create_by_addr_idx();
+        if let Err(err) = store_index(path, self.syms.len(), &by_addr_idx) {
+            log::warn!("failed to persist symbol index: {err:#}");
+        }
+        SymIndex::Owned(by_addr_idx)
     }
 
-    fn create_str2sym<F>(&self, mut filter: F) -> Result<Box<[(SymName, usize)]>>
+    fn ensure_by_addr_idx(&self) -> &'elf [usize] {
+        self.cache
+            .by_addr_idx
+            .get_or_init(|| match &self.cache.by_addr_idx_path {
+                Some(path) if !self.syms.is_empty() => self.load_or_create_by_addr_idx(path),
+                _ => SymIndex::Owned(self.create_by_addr_idx()),
+            })
+    }
+
+    /// Create the name to symbol index table with offsets of type `I`.
+    ///
+    /// `None` is returned if an offset does not fit into `I`.
+    fn create_str2sym_with<I, F>(&self, mut filter: F) -> Result<Option<Box<[(SymName<I>, I)]>>>
     where
+        I: TryFrom<usize>,
         F: FnMut(&ElfN_Sym<'_>) -> bool,
     {
         let by_addr_idx = self.ensure_by_addr_idx();
 
         // We use `by_addr_idx` as the base, because we want the
         // filtering that had been applied to it to be in effect.
-        let mut str2sym = by_addr_idx
+        let str2sym = by_addr_idx
             .iter()
             // SANITY: The index originates in our code and is known to
             //         be in bounds.
@@ -287,58 +525,80 @@ impl<'elf> SymbolTableCache<'elf> {
                     .ok_or_invalid_input(|| "ELF string table index out of bounds")?
                     .read_cstr()
                     .ok_or_invalid_input(|| "no valid string found in ELF string table")?;
-                let name = SymName {
-                    idx: name_idx,
-                    // TODO: May want to use `CStr::count_bytes` once
-                    //       our MSRV is >=1.79.
-                    len: cname.to_bytes().len(),
-                };
-                Ok((name, idx))
+                // TODO: May want to use `CStr::count_bytes` once
+                //       our MSRV is >=1.79.
+                let name_len = cname.to_bytes().len();
+                let entry = I::try_from(name_idx)
+                    .ok()
+                    .zip(I::try_from(name_len).ok())
+                    .zip(I::try_from(idx).ok())
+                    .map(|((idx, len), sym_i)| (SymName { idx, len }, sym_i));
+                Ok(entry)
             })
-            .collect::<Result<Box<[_]>>>()?;
+            .collect::<Result<Option<Box<[_]>>>>()?;
+        Ok(str2sym)
+    }
 
-        let () = str2sym.sort_by_key(|(name, _i)| name.bytes(&self.strs));
+    fn create_str2sym<F>(&self, mut filter: F) -> Result<Str2Sym>
+    where
+        F: FnMut(&ElfN_Sym<'_>) -> bool,
+    {
+        let mut str2sym = match self.create_str2sym_with::<u32, _>(&mut filter)? {
+            Some(str2sym) => Str2Sym::Compact(str2sym),
+            None => {
+                // SANITY: Any offset fits into a `usize`.
+                let str2sym = self.create_str2sym_with::<usize, _>(filter)?.unwrap();
+                Str2Sym::Wide(str2sym)
+            }
+        };
+
+        let strs = self.strs;
+        let () = match &mut str2sym {
+            Str2Sym::Compact(entries) => entries.sort_by_key(|(name, _i)| name.widen().bytes(strs)),
+            Str2Sym::Wide(entries) => entries.sort_by_key(|(name, _i)| name.bytes(strs)),
+        };
         Ok(str2sym)
     }
 
-    fn ensure_str2sym<F>(&self, filter: F) -> Result<&[(SymName, usize)]>
+    fn ensure_str2sym<F>(&self, filter: F) -> Result<&'elf Str2Sym>
     where
         F: FnMut(&ElfN_Sym<'_>) -> bool,
     {
-        let str2sym = self
-            .str2sym
-            .get_or_try_init(|| {
-                let str2sym = self.create_str2sym(filter)?;
-                Result::<_, Error>::Ok(str2sym)
-            })?
-            .deref();
+        let str2sym = self.cache.str2sym.get_or_try_init(|| {
+            let str2sym = self.create_str2sym(filter)?;
+            Result::<_, Error>::Ok(str2sym)
+        })?;
 
         Ok(str2sym)
     }
 }
 
 
-struct Cache<'elf, B> {
+struct Cache<B> {
     /// The backend being used for reading ELF data.
     backend: B,
     /// The cached ELF header.
-    ehdr: OnceCell<EhdrExt<'elf>>,
+    ehdr: OnceCell<EhdrExt>,
     /// The cached ELF section headers.
-    shdrs: OnceCell<ElfN_Shdrs<'elf>>,
-    shstrtab: OnceCell<Cow<'elf, [u8]>>,
+    shdrs: OnceCell<ElfNRegion<Elf64_Shdr>>,
+    shstrtab: OnceCell<Region<u8>>,
     /// The cached ELF program headers.
-    phdrs: OnceCell<ElfN_Phdrs<'elf>>,
+    phdrs: OnceCell<ElfNRegion<Elf64_Phdr>>,
     /// The cached symbol table.
-    symtab: OnceCell<SymbolTableCache<'elf>>,
+    symtab: OnceCell<SymbolTableCache>,
     /// The cached dynamic symbol table.
-    dynsym: OnceCell<SymbolTableCache<'elf>>,
+    dynsym: OnceCell<SymbolTableCache>,
     /// The section data.
-    section_data: OnceCell<Box<[OnceCell<Cow<'elf, [u8]>>]>>,
+    section_data: OnceCell<Box<[OnceCell<Region<u8>>]>>,
+    /// The cache persisting symbol table indexes, if any.
+    index_cache: Option<IndexCache>,
+    /// The parsed BOLT address translation data, if any.
+    bat: OnceCell<Option<BoltAddrTranslation>>,
 }
 
-impl<'elf, B> Cache<'elf, B>
+impl<B> Cache<B>
 where
-    B: BackendImpl<'elf>,
+    B: Backend,
 {
     /// Create a new `Cache` using the provided raw ELF object data.
     fn new(backend: B) -> Self {
@@ -351,33 +611,34 @@ where
             symtab: OnceCell::new(),
             dynsym: OnceCell::new(),
             section_data: OnceCell::new(),
+            index_cache: None,
+            bat: OnceCell::new(),
         }
     }
 
     /// A convenience helper for retrieving a given ELF section header.
-    fn section_hdr(&self, idx: usize) -> Result<ElfN_Shdr<'_>> {
+    fn section_hdr(&self, idx: usize) -> Result<ElfN_Shdr<'static>> {
         let shdrs = self.ensure_shdrs()?;
         let shdr = shdrs
             .get(idx)
             .ok_or_invalid_input(|| format!("ELF section index ({idx}) out of bounds"))?;
-        Ok(shdr)
+        Ok(shdr.into_owned())
     }
 
     /// Retrieve the raw section data for the ELF section at index
     /// `idx`.
     ///
     /// # Notes
-    /// This method returns potentially compressed data, but adheres is
-    /// able to do so with 'elf lifetime. To transparently decompress,
-    /// use [`Cache::section_data`] instead.
-    fn section_data_raw(&self, idx: usize) -> Result<Cow<'elf, [u8]>> {
+    /// This method returns potentially compressed data. To
+    /// transparently decompress, use [`Cache::section_data`] instead.
+    fn section_region_raw(&self, idx: usize) -> Result<Region<u8>> {
         let shdr = self.section_hdr(idx)?;
         if shdr.type_() != SHT_NOBITS {
             self.backend
-                .read_pod_slice::<u8>(shdr.offset(), shdr.size() as usize)
+                .read_pod_region::<u8>(shdr.offset(), shdr.size() as usize)
                 .context("failed to read ELF section data")
         } else {
-            Ok(Cow::Borrowed(&[]))
+            Ok(Region::empty())
         }
     }
 
@@ -400,14 +661,14 @@ where
             datas
                 .get(idx)
                 .ok_or_invalid_input(|| format!("ELF section index ({idx}) out of bounds"))?
-                .get_or_try_init(|| -> Result<Cow<'elf, [u8]>> {
-                    let data = self
+                .get_or_try_init(|| -> Result<Region<u8>> {
+                    let region = self
                                 .backend
-                                .read_pod_slice::<u8>(shdr.offset(), shdr.size() as usize)
+                                .read_pod_region::<u8>(shdr.offset(), shdr.size() as usize)
                                 .context("failed to read ELF section data")?;
 
                     if shdr.flags() & SHF_COMPRESSED != 0 {
-                        let mut data = data.deref();
+                        let mut data = self.backend.resolve(&region);
                         // Compression header is contained in the actual section
                         // data.
                         let (ch_type, ch_size) = if shdr.is_32bit() {
@@ -434,11 +695,11 @@ where
                             ch_size as usize,
                             "decompressed ELF section data does not have expected length"
                         );
-                        Ok(Cow::Owned(decompressed))
+                        Ok(Region::Owned(decompressed.into_boxed_slice()))
                     } else {
-                        Ok(data)
+                        Ok(region)
                     }
-                }).map(Cow::deref)
+                }).map(|region| self.backend.resolve(region))
         } else {
             Ok(&[])
         }
@@ -450,7 +711,7 @@ where
     /// of certain member variables to reference data from this header,
     /// which otherwise is zeroed out.
     #[inline]
-    fn read_first_shdr(&self, ehdr: &ElfN_Ehdr<'_>) -> Result<ElfN_Shdr<'elf>> {
+    fn read_first_shdr(&self, ehdr: &ElfN_Ehdr<'_>) -> Result<ElfN_Shdr<'static>> {
         let shdr = if ehdr.is_32bit() {
             self.backend
                 .read_pod_obj::<Elf32_Shdr>(ehdr.shoff() as _)
@@ -462,10 +723,10 @@ where
         }
         .context("failed to read ELF section header")?;
 
-        Ok(shdr)
+        Ok(shdr.into_owned())
     }
 
-    fn parse_ehdr(&self) -> Result<EhdrExt<'elf>> {
+    fn parse_ehdr(&self) -> Result<EhdrExt> {
         let e_ident = self
             .backend
             .read_pod_slice::<u8>(0, EI_NIDENT)
@@ -484,6 +745,18 @@ where
             )))
         }
 
+        let data = e_ident[5];
+        let native = if cfg!(target_endian = "little") {
+            ELFDATA2LSB
+        } else {
+            ELFDATA2MSB
+        };
+        if data != native {
+            return Err(Error::with_unsupported(format!(
+                "ELF data encoding ({data}) does not match host byte order"
+            )))
+        }
+
         let bit32 = class == ELFCLASS32;
         let ehdr = if bit32 {
             self.backend
@@ -496,6 +769,7 @@ where
                 .map(ElfN_Ehdr::B64)
                 .context("failed to read ELF header")?
         };
+        let ehdr = ehdr.into_owned();
 
         // "If the number of entries in the section header table is larger than
         // or equal to SHN_LORESERVE, e_shnum holds the value zero and the real
@@ -534,52 +808,65 @@ where
         Ok(ehdr)
     }
 
-    fn ensure_ehdr(&self) -> Result<&EhdrExt<'elf>> {
+    fn ensure_ehdr(&self) -> Result<&EhdrExt> {
         self.ehdr.get_or_try_init(|| self.parse_ehdr())
     }
 
-    fn parse_shdrs(&self) -> Result<ElfN_Shdrs<'elf>> {
+    fn parse_shdrs(&self) -> Result<ElfNRegion<Elf64_Shdr>> {
         let ehdr = self.ensure_ehdr()?;
         let e_shoff = ehdr.ehdr.shoff();
 
         let shdrs = if ehdr.is_32bit() {
             self.backend
-                .read_pod_slice::<Elf32_Shdr>(e_shoff, ehdr.shnum)
-                .map(ElfN_Shdrs::B32)
+                .read_pod_region::<Elf32_Shdr>(e_shoff, ehdr.shnum)
+                .map(ElfNRegion::B32)
         } else {
             self.backend
-                .read_pod_slice::<Elf64_Shdr>(e_shoff, ehdr.shnum)
-                .map(ElfN_Shdrs::B64)
+                .read_pod_region::<Elf64_Shdr>(e_shoff, ehdr.shnum)
+                .map(ElfNRegion::B64)
         }
         .context("failed to read ELF section headers")?;
 
         Ok(shdrs)
     }
 
-    fn ensure_shdrs(&self) -> Result<&ElfN_Shdrs<'elf>> {
-        self.shdrs.get_or_try_init(|| self.parse_shdrs())
+    fn ensure_shdrs(&self) -> Result<ElfN_Shdrs<'_>> {
+        let shdrs = self.shdrs.get_or_try_init(|| self.parse_shdrs())?;
+        Ok(shdrs.resolve(&self.backend))
     }
 
-    fn parse_phdrs(&self) -> Result<ElfN_Phdrs<'elf>> {
+    fn parse_phdrs(&self) -> Result<ElfNRegion<Elf64_Phdr>> {
         let ehdr = self.ensure_ehdr()?;
         let e_phoff = ehdr.ehdr.phoff();
 
         let phdrs = if ehdr.is_32bit() {
             self.backend
-                .read_pod_slice::<Elf32_Phdr>(e_phoff, ehdr.phnum)
-                .map(ElfN_Phdrs::B32)
+                .read_pod_region::<Elf32_Phdr>(e_phoff, ehdr.phnum)
+                .map(ElfNRegion::B32)
         } else {
             self.backend
-                .read_pod_slice::<Elf64_Phdr>(e_phoff, ehdr.phnum)
-                .map(ElfN_Phdrs::B64)
+                .read_pod_region::<Elf64_Phdr>(e_phoff, ehdr.phnum)
+                .map(ElfNRegion::B64)
         }
         .context("failed to read ELF program headers")?;
 
         Ok(phdrs)
     }
 
-    fn ensure_phdrs(&self) -> Result<&ElfN_Phdrs<'elf>> {
-        self.phdrs.get_or_try_init(|| self.parse_phdrs())
+    fn ensure_phdrs(&self) -> Result<ElfN_Phdrs<'_>> {
+        let phdrs = self.phdrs.get_or_try_init(|| self.parse_phdrs())?;
+        Ok(phdrs.resolve(&self.backend))
+    }
+
+    /// Retrieve the raw data of the ELF segment at index `idx`.
+    fn segment_data(&self, idx: usize) -> Result<Cow<'_, [u8]>> {
+        let phdrs = self.ensure_phdrs()?;
+        let phdr = phdrs
+            .get(idx)
+            .ok_or_invalid_input(|| format!("ELF segment index ({idx}) out of bounds"))?;
+        self.backend
+            .read_pod_slice::<u8>(phdr.offset(), phdr.file_size() as usize)
+            .context("failed to read ELF segment data")
     }
 
     fn shstrndx(&self, ehdr: &ElfN_Ehdr<'_>) -> Result<usize> {
@@ -601,21 +888,33 @@ where
         Ok(shstrndx)
     }
 
-    fn parse_shstrtab(&self) -> Result<Cow<'elf, [u8]>> {
+    fn parse_shstrtab(&self) -> Result<Region<u8>> {
         let ehdr = self.ensure_ehdr()?;
         let shstrndx = self.shstrndx(&ehdr.ehdr)?;
-        let shstrtab = self.section_data_raw(shstrndx)?;
+        // Some linkers don't emit a section name string table or
+        // reference one that does not exist. All sections are
+        // considered unnamed in that case.
+        if shstrndx == usize::from(SHN_UNDEF) || shstrndx >= ehdr.shnum {
+            log::debug!("ELF file has no usable section name string table ({shstrndx})");
+            return Ok(Region::empty())
+        }
+
+        let shstrtab = self.section_region_raw(shstrndx)?;
         Ok(shstrtab)
     }
 
-    fn ensure_shstrtab(&self) -> Result<&Cow<'elf, [u8]>> {
-        self.shstrtab.get_or_try_init(|| self.parse_shstrtab())
+    fn ensure_shstrtab(&self) -> Result<&[u8]> {
+        let shstrtab = self.shstrtab.get_or_try_init(|| self.parse_shstrtab())?;
+        Ok(self.backend.resolve(shstrtab))
     }
 
     /// Get the name of the section at a given index.
     fn section_name(&self, idx: usize) -> Result<&str> {
         let shdr = self.section_hdr(idx)?;
         let shstrtab = self.ensure_shstrtab()?;
+        if shstrtab.is_empty() {
+            return Ok("")
+        }
 
         let name = shstrtab
             .get(shdr.name() as usize..)
@@ -629,13 +928,13 @@ where
     }
 
     #[cfg(test)]
-    fn symbol(&self, idx: usize) -> Result<ElfN_Sym<'_>> {
+    fn symbol(&self, idx: usize) -> Result<ElfN_Sym<'static>> {
         let symtab = self.ensure_symtab()?;
         let symbol = symtab
             .get(idx)
             .ok_or_invalid_input(|| format!("ELF symbol index ({idx}) out of bounds"))?;
 
-        Ok(symbol)
+        Ok(symbol.into_owned())
     }
 
     /// Find the section of a given name.
@@ -651,13 +950,81 @@ where
         Ok(None)
     }
 
-    fn parse_syms(&self, section: &str) -> Result<ElfN_Syms<'elf>> {
+    /// Iterate over all note sections to find one of type
+    /// `NT_GNU_BUILD_ID` and return its contents.
+    fn build_id(&self) -> Result<Option<&[u8]>> {
+        let shdrs = self.ensure_shdrs()?;
+        for (idx, shdr) in shdrs.iter(0).enumerate() {
+            if shdr.type_() == SHT_NOTE {
+                // SANITY: We just found the index so the section data should always
+                //         be found.
+                let bytes = self.section_data(idx).unwrap();
+                if let Some(build_id) = find_build_id_note(bytes)? {
+                    return Ok(Some(build_id))
+                }
+            }
+        }
+        Ok(None)
+    }
+
+    /// Retrieve the path at which the address index for the symbol
+    /// table in `section` is persisted, if index persistence is
+    /// enabled and the file has a build ID.
+    fn by_addr_idx_path(&self, section: &str) -> Option<PathBuf> {
+        let index_cache = self.index_cache.as_ref()?;
+        match self.build_id() {
+            Ok(build_id) => build_id.map(|build_id| index_cache.index_path(build_id, section)),
+            Err(err) => {
+                log::debug!("failed to read build ID for symbol index lookup: {err}");
+                None
+            }
+        }
+    }
+
+    /// Find the symbol table section of type `type_`.
+    ///
+    /// Symbol tables are identified by their type, so that they are
+    /// found even if named unconventionally or if section names are
+    /// unavailable altogether. Only if no such section exists do we
+    /// fall back to looking one up by `name`.
+    fn find_symtab_section(&self, type_: u32, name: &str) -> Result<Option<usize>> {
+        let shdrs = self.ensure_shdrs()?;
+        let idx = shdrs.iter(0).position(|shdr| shdr.type_() == type_);
+
+        match idx {
+            Some(idx) => Ok(Some(idx)),
+            None => self.find_section(name),
+        }
+    }
+
+    /// Find the string table associated with the symbol table at index
+    /// `symtab_idx`, if any.
+    ///
+    /// The string table is the one referenced by the symbol table's
+    /// `sh_link`, if that refers to a string table section. Otherwise
+    /// we fall back to looking it up by `name`.
+    fn find_strtab_section(&self, symtab_idx: Option<usize>, name: &str) -> Result<Option<usize>> {
+        if let Some(symtab_idx) = symtab_idx {
+            let link = self.section_hdr(symtab_idx)?.link() as usize;
+            if link != usize::from(SHN_UNDEF) {
+                let shdrs = self.ensure_shdrs()?;
+                if let Some(shdr) = shdrs.get(link) {
+                    if shdr.type_() == SHT_STRTAB {
+                        return Ok(Some(link))
+                    }
+                }
+            }
+        }
+        self.find_section(name)
+    }
+
+    fn parse_syms(&self, idx: Option<usize>, section: &str) -> Result<ElfNRegion<Elf64_Sym>> {
         let ehdr = self.ensure_ehdr()?;
-        let idx = if let Some(idx) = self.find_section(section)? {
+        let idx = if let Some(idx) = idx {
             idx
         } else {
             // The symbol table does not exists. Fake an empty one.
-            return Ok(ElfN_Syms::empty(ehdr.is_32bit()))
+            return Ok(ElfNRegion::empty(ehdr.is_32bit()))
         };
 
         let shdr = self.section_hdr(idx)?;
@@ -665,17 +1032,21 @@ where
         // which may only preserve (some) meta data but no section
         // contents.
         if shdr.type_() == SHT_NOBITS {
-            return Ok(ElfN_Syms::empty(ehdr.is_32bit()))
+            return Ok(ElfNRegion::empty(ehdr.is_32bit()))
         }
 
         let sh_size = shdr.size();
         let sh_offset = shdr.offset();
+        let sym_size = ehdr.sym_size();
 
-        let sym_size = if ehdr.is_32bit() {
-            mem::size_of::<Elf32_Sym>()
-        } else {
-            mem::size_of::<Elf64_Sym>()
-        } as u64;
+        // An entry size of zero is not standard conforming, but emitted
+        // by some linkers nevertheless.
+        let entsize = shdr.entsize();
+        if entsize != 0 && entsize != sym_size {
+            return Err(Error::with_invalid_data(format!(
+                "ELF {section} symbol table has unexpected entry size ({entsize})"
+            )))
+        }
 
         if sh_size % sym_size != 0 {
             return Err(Error::with_invalid_data(
@@ -687,66 +1058,74 @@ where
         // Short-circuit if there are no symbols. The data may not actually be
         // properly aligned in this case either, so don't attempt to even read.
         if count == 0 {
-            return Ok(ElfN_Syms::empty(ehdr.is_32bit()))
+            return Ok(ElfNRegion::empty(ehdr.is_32bit()))
         }
 
         let syms = if ehdr.is_32bit() {
             self.backend
-                .read_pod_slice::<Elf32_Sym>(sh_offset, count)
-                .map(ElfN_Syms::B32)
+                .read_pod_region::<Elf32_Sym>(sh_offset, count)
+                .map(ElfNRegion::B32)
         } else {
             self.backend
-                .read_pod_slice::<Elf64_Sym>(sh_offset, count)
-                .map(ElfN_Syms::B64)
+                .read_pod_region::<Elf64_Sym>(sh_offset, count)
+                .map(ElfNRegion::B64)
         }
         .with_context(|| format!("failed to read ELF {section} symbol table contents"))?;
 
         Ok(syms)
     }
 
-    fn ensure_symtab_cache(&self) -> Result<&SymbolTableCache<'elf>> {
-        self.symtab.get_or_try_init(|| {
-            let syms = self.parse_syms(".symtab")?;
-            let strtab = self.parse_strs(".strtab")?;
-            let cache = SymbolTableCache::new(syms, strtab);
-            Ok(cache)
-        })
+    fn ensure_symtab_cache(&self) -> Result<SymbolTable<'_>> {
+        let cache = self.symtab.get_or_try_init(|| {
+            let idx = self.find_symtab_section(SHT_SYMTAB, ".symtab")?;
+            let syms = self.parse_syms(idx, ".symtab")?;
+            let strtab_idx = self.find_strtab_section(idx, ".strtab")?;
+            let strtab = self.parse_strs(strtab_idx)?;
+            let path = self.by_addr_idx_path(".symtab");
+            let cache = SymbolTableCache::new(syms, strtab, path);
+            Result::<_, Error>::Ok(cache)
+        })?;
+        Ok(cache.resolve(&self.backend))
     }
 
-    fn ensure_dynsym_cache(&self) -> Result<&SymbolTableCache<'elf>> {
-        self.dynsym.get_or_try_init(|| {
+    fn ensure_dynsym_cache(&self) -> Result<SymbolTable<'_>> {
+        let cache = self.dynsym.get_or_try_init(|| {
             // TODO: We really should check the `.dynamic` section for
             //       information on what symbol and string tables to
             //       use instead of hard coding names here.
-            let syms = self.parse_syms(".dynsym")?;
-            let dynstr = self.parse_strs(".dynstr")?;
-            let cache = SymbolTableCache::new(syms, dynstr);
-            Ok(cache)
-        })
+            let idx = self.find_symtab_section(SHT_DYNSYM, ".dynsym")?;
+            let syms = self.parse_syms(idx, ".dynsym")?;
+            let dynstr_idx = self.find_strtab_section(idx, ".dynstr")?;
+            let dynstr = self.parse_strs(dynstr_idx)?;
+            let path = self.by_addr_idx_path(".dynsym");
+            let cache = SymbolTableCache::new(syms, dynstr, path);
+            Result::<_, Error>::Ok(cache)
+        })?;
+        Ok(cache.resolve(&self.backend))
     }
 
     #[cfg(test)]
-    fn ensure_symtab(&self) -> Result<&ElfN_Syms<'_>> {
+    fn ensure_symtab(&self) -> Result<ElfN_Syms<'_>> {
         let symtab = self.ensure_symtab_cache()?;
-        Ok(&symtab.syms)
+        Ok(symtab.syms)
     }
 
-    fn parse_strs(&self, section: &str) -> Result<Cow<'elf, [u8]>> {
-        let strs = if let Some(idx) = self.find_section(section)? {
-            self.section_data_raw(idx)?
+    fn parse_strs(&self, idx: Option<usize>) -> Result<Region<u8>> {
+        let strs = if let Some(idx) = idx {
+            self.section_region_raw(idx)?
         } else {
-            Cow::Borrowed([].as_slice())
+            Region::empty()
         };
         Ok(strs)
     }
 
-    fn ensure_str2symtab(&self) -> Result<&[(SymName, usize)]> {
+    fn ensure_str2symtab(&self) -> Result<&Str2Sym> {
         let symtab = self.ensure_symtab_cache()?;
         let str2sym = symtab.ensure_str2sym(|_sym| true)?;
         Ok(str2sym)
     }
 
-    fn ensure_str2dynsym(&self) -> Result<&[(SymName, usize)]> {
+    fn ensure_str2dynsym(&self) -> Result<&Str2Sym> {
         let symtab = self.ensure_symtab_cache()?;
         let symtab_by_addr_idx = symtab.ensure_by_addr_idx();
 
@@ -757,7 +1136,7 @@ where
             let result = find_sym(
                 &symtab.syms,
                 symtab_by_addr_idx,
-                &symtab.strs,
+                symtab.strs,
                 sym.value(),
                 // SANITY: We filter out all unsupported symbol types,
                 //         so this conversion should always succeed.
@@ -769,43 +1148,92 @@ where
     }
 }
 
-impl<B> Debug for Cache<'_, B> {
+impl<B> Debug for Cache<B> {
     fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
         write!(f, "Cache")
     }
 }
 
 
-pub(crate) trait Backend {
-    type ObjTy;
-    type ImplTy<'bcknd>: BackendImpl<'bcknd>;
+/// A source of ELF data that can hand out [`Region`]s.
+pub(crate) trait Backend: BackendImpl {
+    /// Read `count` objects of type `T` starting at `offset` into a
+    /// [`Region`].
+    fn read_pod_region<T>(&self, offset: u64, count: usize) -> Result<Region<T>, Error>
+    where
+        T: Pod;
+
+    /// Retrieve the data referenced by `region`, which has to have been
+    /// created by this backend.
+    fn resolve<'slf, T>(&'slf self, region: &'slf Region<T>) -> &'slf [T]
+    where
+        T: Pod;
 }
 
 impl Backend for Mmap {
-    type ObjTy = Mmap;
-    type ImplTy<'bcknd> = &'bcknd [u8];
+    fn read_pod_region<T>(&self, offset: u64, count: usize) -> Result<Region<T>, Error>
+    where
+        T: Pod,
+    {
+        // Make sure that the region is valid, so that we can later
+        // resolve it without further checks.
+        let _slice = self.read_pod_slice::<T>(offset, count)?;
+        Ok(Region::Mapped { offset, count })
+    }
+
+    fn resolve<'slf, T>(&'slf self, region: &'slf Region<T>) -> &'slf [T]
+    where
+        T: Pod,
+    {
+        match region {
+            // SANITY: The region got validated when it was created.
+            Region::Mapped { offset, count } => self
+                .get(*offset as usize..)
+                .unwrap()
+                .read_pod_slice_ref::<T>(*count)
+                .unwrap(),
+            Region::Owned(data) => data,
+        }
+    }
 }
 
 impl Backend for File {
-    type ObjTy = Box<File>;
-    type ImplTy<'bcknd> = &'bcknd File;
+    fn read_pod_region<T>(&self, offset: u64, count: usize) -> Result<Region<T>, Error>
+    where
+        T: Pod,
+    {
+        let data = self.read_pod_slice::<T>(offset, count)?;
+        Ok(Region::Owned(data.into_owned().into_boxed_slice()))
+    }
+
+    fn resolve<'slf, T>(&'slf self, region: &'slf Region<T>) -> &'slf [T]
+    where
+        T: Pod,
+    {
+        match region {
+            Region::Mapped { .. } => {
+                unreachable!("file backend does not create mapped regions")
+            }
+            Region::Owned(data) => data,
+        }
+    }
 }
 
 
-pub(crate) trait BackendImpl<'elf> {
-    fn read_pod_obj<T>(&self, offset: u64) -> Result<Cow<'elf, T>, Error>
+pub(crate) trait BackendImpl {
+    fn read_pod_obj<T>(&self, offset: u64) -> Result<Cow<'_, T>, Error>
     where
-        T: Pod + 'elf;
+        T: Pod;
 
-    fn read_pod_slice<T>(&self, offset: u64, count: usize) -> Result<Cow<'elf, [T]>, Error>
+    fn read_pod_slice<T>(&self, offset: u64, count: usize) -> Result<Cow<'_, [T]>, Error>
     where
-        T: Pod + 'elf;
+        T: Pod;
 }
 
-impl<'elf> BackendImpl<'elf> for &'elf [u8] {
-    fn read_pod_obj<T>(&self, offset: u64) -> Result<Cow<'elf, T>, Error>
+impl BackendImpl for Mmap {
+    fn read_pod_obj<T>(&self, offset: u64) -> Result<Cow<'_, T>, Error>
     where
-        T: Pod + 'elf,
+        T: Pod,
     {
         let value = self
             .get(offset as _..)
@@ -815,25 +1243,25 @@ impl<'elf> BackendImpl<'elf> for &'elf [u8] {
         Ok(Cow::Borrowed(value))
     }
 
-    fn read_pod_slice<T>(&self, offset: u64, count: usize) -> Result<Cow<'elf, [T]>, Error>
+    fn read_pod_slice<T>(&self, offset: u64, count: usize) -> Result<Cow<'_, [T]>, Error>
     where
-        T: Pod + 'elf,
+        T: Pod,
     {
         let value = self
             .get(offset as _..)
-            .unwrap()
+            .ok_or_invalid_data(|| "failed to read data: invalid offset")?
             .read_pod_slice_ref::<T>(count)
             .ok_or_invalid_data(|| "failed to read slice from mmap")?;
         Ok(Cow::Borrowed(value))
     }
 }
 
-impl<'elf> BackendImpl<'elf> for &File {
-    fn read_pod_obj<T>(&self, offset: u64) -> Result<Cow<'elf, T>, Error>
+impl BackendImpl for File {
+    fn read_pod_obj<T>(&self, offset: u64) -> Result<Cow<'_, T>, Error>
     where
-        T: Pod + 'elf,
+        T: Pod,
     {
-        let mut slf = *self;
+        let mut slf = self;
         let _pos = slf.seek(SeekFrom::Start(offset))?;
 
         let mut value = MaybeUninit::<T>::zeroed();
@@ -848,11 +1276,11 @@ impl<'elf> BackendImpl<'elf> for &File {
         Ok(Cow::Owned(unsafe { value.assume_init() }))
     }
 
-    fn read_pod_slice<T>(&self, offset: u64, count: usize) -> Result<Cow<'elf, [T]>, Error>
+    fn read_pod_slice<T>(&self, offset: u64, count: usize) -> Result<Cow<'_, [T]>, Error>
     where
-        T: Pod + 'elf,
+        T: Pod,
     {
-        let mut slf = *self;
+        let mut slf = self;
         let _pos = slf.seek(SeekFrom::Start(offset))?;
         let mut vec = Vec::<T>::new();
         // SAFETY: `T` is a `Pod` and hence valid for any bit pattern,
@@ -870,21 +1298,30 @@ impl<'elf> BackendImpl<'elf> for &File {
 
 
 /// A parser for ELF64 files.
+///
+/// # Tolerances
+/// Binaries produced by less common linkers (e.g., Go's internal
+/// linker, TinyGo, or some `busybox` builds) don't always adhere to
+/// conventions followed by GNU tools. The parser accepts the following
+/// deviations:
+/// - a missing or out-of-bounds section name string table; all sections are
+///   then treated as unnamed
+/// - symbol tables with unconventional names; `.symtab` and `.dynsym` are
+///   located by section type (`SHT_SYMTAB` and `SHT_DYNSYM`, respectively) and
+///   only looked up by name if no section of that type exists
+/// - a symbol table `sh_entsize` of zero; a non-zero entry size that does not
+///   match the size of a symbol is reported as invalid data
+/// - a symbol table `sh_link` not referring to a string table; the string table
+///   is looked up by its conventional name (`.strtab` or `.dynstr`) instead
 #[derive(Debug)]
 pub(crate) struct ElfParser<B = Mmap>
 where
     B: Backend,
 {
     /// A cache for relevant parts of the ELF file.
-    // SAFETY: We must not hand out references with a 'static lifetime to
-    //         this member. Rather, they should never outlive `self`.
-    //         Furthermore, this member has to be listed before `_mmap`
-    //         to make sure we never end up with a dangling reference.
-    cache: Cache<'static, B::ImplTy<'static>>,
+    cache: Cache<B>,
     /// The path to the ELF file being worked on, if available.
     path: Option<PathBuf>,
-    /// The backend used.
-    _backend: B::ObjTy,
 }
 
 impl ElfParser<File> {
@@ -892,13 +1329,9 @@ impl ElfParser<File> {
     where
         P: Into<PathBuf>,
     {
-        let _backend = Box::new(file);
-        let file_ref = unsafe { mem::transmute::<&File, &'static File>(_backend.deref()) };
-
         let parser = Self {
-            cache: Cache::new(file_ref),
+            cache: Cache::new(file),
             path: Some(path.into()),
-            _backend,
         };
         parser
     }
@@ -917,7 +1350,7 @@ impl ElfParser<File> {
 
     /// Retrieve a reference to the backend in use.
     pub(crate) fn backend(&self) -> &File {
-        &self._backend
+        &self.cache.backend
     }
 }
 
@@ -931,19 +1364,27 @@ impl ElfParser<Mmap> {
         Ok(Self::from_mmap(mmap, Some(path.into())))
     }
 
+    /// Create an `ElfParser` from an open file, transparently
+    /// decompressing its contents if they are gzip or zstd compressed.
+    pub(crate) fn open_file_decompressed<P>(file: &File, path: P) -> Result<Self>
+    where
+        P: Into<PathBuf>,
+    {
+        let mmap = Mmap::map(file).context("failed to memory map file")?;
+        let mmap = if let Some(compression) = Compression::detect(&mmap) {
+            let data = decompress(&mmap, compression).context("failed to decompress file")?;
+            Mmap::from_bytes(&data)?
+        } else {
+            mmap
+        };
+        Ok(Self::from_mmap(mmap, Some(path.into())))
+    }
+
     /// Create an `ElfParser` from mmap'ed data.
     pub(crate) fn from_mmap(mmap: Mmap, path: Option<PathBuf>) -> Self {
-        // We transmute the mmap's lifetime to static here as that is a
-        // necessity for self-referentiality.
-        // SAFETY: We never hand out any 'static references to cache
-        //         data.
-        let data = unsafe { mem::transmute::<&[u8], &'static [u8]>(mmap.deref()) };
-        let _backend = mmap;
-
         let parser = ElfParser {
-            cache: Cache::new(data),
+            cache: Cache::new(mmap),
             path,
-            _backend,
         };
         parser
     }
@@ -974,6 +1415,58 @@ where
         Ok(index)
     }
 
+    /// Retrieve the virtual address of the section at index `idx`.
+    pub(crate) fn section_addr(&self, idx: usize) -> Result<Option<Addr>> {
+        let shdrs = self.cache.ensure_shdrs()?;
+        Ok(shdrs.get(idx).map(|shdr| shdr.addr()))
+    }
+
+    /// Find the name of the allocated section containing `addr`, if
+    /// any.
+    pub(crate) fn find_section_by_addr(&self, addr: Addr) -> Result<Option<&str>> {
+        let shdrs = self.cache.ensure_shdrs()?;
+        let idx = shdrs.iter(0).position(|shdr| {
+            shdr.flags() & SHF_ALLOC != 0 && addr >= shdr.addr() && addr - shdr.addr() < shdr.size()
+        });
+        idx.map(|idx| self.cache.section_name(idx)).transpose()
+    }
+
+    /// Read the GNU build ID of the ELF file, if present.
+    pub(crate) fn build_id(&self) -> Result<Option<&[u8]>> {
+        self.cache.build_id()
+    }
+
+    /// Retrieve the BOLT address translation data contained in the
+    /// file, if any.
+    pub(crate) fn bolt_addr_translation(&self) -> Result<Option<&BoltAddrTranslation>> {
+        let bat = self.cache.bat.get_or_try_init(|| {
+            let idx = match self.cache.find_section(BAT_SECTION)? {
+                Some(idx) => idx,
+                None => return Ok(None),
+            };
+            let data = self.cache.section_data(idx)?;
+            BoltAddrTranslation::parse(data).map(Some)
+        })?;
+        Ok(bat.as_ref())
+    }
+
+    /// Set the cache used for persisting symbol table indexes across
+    /// runs.
+    ///
+    /// This method has to be invoked before any symbol lookups are
+    /// performed in order to be effective.
+    pub(crate) fn set_index_cache(&mut self, index_cache: Option<IndexCache>) {
+        self.cache.index_cache = index_cache;
+    }
+
+    /// Eagerly parse the symbol tables and create their by-address
+    /// indexes, which otherwise happens on first lookup.
+    pub(crate) fn preload(&self) -> Result<()> {
+        let _idx = self.cache.ensure_symtab_cache()?.ensure_by_addr_idx();
+        let _idx = self.cache.ensure_dynsym_cache()?.ensure_by_addr_idx();
+        Ok(())
+    }
+
     pub(crate) fn find_sym(
         &self,
         addr: Addr,
@@ -988,7 +1481,7 @@ where
         if let Some(sym) = find_sym(
             &symtab_cache.syms,
             symtab_by_addr_idx,
-            &symtab_cache.strs,
+            symtab_cache.strs,
             addr,
             SymType::Undefined,
         )? {
@@ -1000,7 +1493,7 @@ where
         if let Some(sym) = find_sym(
             &dynsym_cache.syms,
             dynsym_by_addr_idx,
-            &dynsym_cache.strs,
+            dynsym_cache.strs,
             addr,
             SymType::Undefined,
         )? {
@@ -1050,19 +1543,17 @@ where
         shdrs: &ElfN_Shdrs<'_>,
         syms: &ElfN_Syms,
         strs: &'slf [u8],
-        str2sym: &'slf [(SymName, usize)],
+        str2sym: &'slf Str2Sym,
     ) -> Result<Vec<SymInfo<'slf>>> {
-        let r = find_match_or_lower_bound_by_key(str2sym, name.as_bytes(), |(name, _i)| {
-            name.bytes(strs)
-        });
-        match r {
-            Some(idx) => {
+        let idx = str2sym.partition_point(|name_visit| name_visit.bytes(strs) < name.as_bytes());
+        match idx {
+            idx if idx < str2sym.len() => {
                 let mut found = vec![];
-                for (name_visit, sym_i) in str2sym.iter().skip(idx) {
+                for (name_visit, sym_i) in str2sym.iter_from(idx) {
                     if name_visit.bytes(strs) != name.as_bytes() {
                         break
                     }
-                    let sym_ref = &syms.get(*sym_i).ok_or_invalid_input(|| {
+                    let sym_ref = &syms.get(sym_i).ok_or_invalid_input(|| {
                         format!("ELF symbol table index ({sym_i}) out of bounds")
                     })?;
                     let sym = sym_ref.to_64bit();
@@ -1081,12 +1572,13 @@ where
                                 .transpose()?
                                 .flatten(),
                             obj_file_name: self.path().map(Cow::Borrowed),
+                            code_info: None,
                         });
                     }
                 }
                 Ok(found)
             }
-            None => Ok(vec![]),
+            _ => Ok(vec![]),
         }
     }
 
@@ -1095,37 +1587,131 @@ where
         name: &str,
         opts: &FindAddrOpts,
     ) -> Result<Vec<SymInfo<'slf>>> {
+        // ELF symbol tables do not carry declaration information and so
+        // no symbol can match a declaration file filter.
+        if opts.decl_file.is_some() {
+            return Ok(Vec::new())
+        }
+
         let shdrs = self.cache.ensure_shdrs()?;
         let cache = self.cache.ensure_symtab_cache()?;
         let symtab = &cache.syms;
-        let strs = &cache.strs;
+        let strs = cache.strs;
         let str2symtab = self.cache.ensure_str2symtab()?;
-        let syms = self.find_addr_impl(name, opts, shdrs, symtab, strs, str2symtab)?;
+        let syms = self.find_addr_impl(name, opts, &shdrs, symtab, strs, str2symtab)?;
         if !syms.is_empty() {
             return Ok(syms)
         }
 
         let cache = self.cache.ensure_dynsym_cache()?;
         let dynsym = &cache.syms;
-        let strs = &cache.strs;
+        let strs = cache.strs;
         let str2dynsym = self.cache.ensure_str2dynsym()?;
-        let syms = self.find_addr_impl(name, opts, shdrs, dynsym, strs, str2dynsym)?;
+        let syms = self.find_addr_impl(name, opts, &shdrs, dynsym, strs, str2dynsym)?;
         Ok(syms)
     }
 
+    /// Find the address ranges of the function containing `addr`.
+    ///
+    /// In addition to the range covered by the function's symbol,
+    /// those of cold fragments split off of it (`<name>.cold` and
+    /// `<name>.cold.<n>` symbols) are reported. Fragments are only
+    /// attributed to a function if its name is unique in the symbol
+    /// table, because there is no telling which of multiple identically
+    /// named functions they belong to otherwise. Ranges are sorted by
+    /// start address. If no function contains `addr`, an empty vector
+    /// is returned.
+    pub(crate) fn find_func_ranges(&self, addr: Addr) -> Result<Vec<Range<Addr>>> {
+        fn sym_range(sym: &ResolvedSym<'_>) -> Range<Addr> {
+            sym.addr..sym.addr + sym.size.unwrap_or(0) as Addr
+        }
+
+        let cache = self.cache.ensure_symtab_cache()?;
+        let by_addr_idx = cache.ensure_by_addr_idx();
+        let sym = find_sym(
+            &cache.syms,
+            by_addr_idx,
+            cache.strs,
+            addr,
+            SymType::Function,
+        )?;
+        let sym = if let Some(sym) = sym {
+            sym
+        } else {
+            // Cold fragments are local symbols and, hence, never part
+            // of `.dynsym`. So all we can report is the symbol's own
+            // range.
+            let cache = self.cache.ensure_dynsym_cache()?;
+            let by_addr_idx = cache.ensure_by_addr_idx();
+            let sym = find_sym(
+                &cache.syms,
+                by_addr_idx,
+                cache.strs,
+                addr,
+                SymType::Function,
+            )?;
+            return Ok(sym.as_ref().map(sym_range).into_iter().collect())
+        };
+
+        let base = cold_fragment_base(sym.name).unwrap_or(sym.name);
+        let strs = cache.strs;
+        let str2symtab = self.cache.ensure_str2symtab()?;
+        // All names starting with `base` form a contiguous sequence in
+        // the sorted name table.
+        let idx = str2symtab.partition_point(|name| name.bytes(strs) < base.as_bytes());
+
+        let mut funcs = Vec::new();
+        let mut fragments = Vec::new();
+        for (name, sym_i) in str2symtab.iter_from(idx) {
+            let name = name.bytes(strs);
+            if !name.starts_with(base.as_bytes()) {
+                break
+            }
+
+            let func = cache
+                .syms
+                .get(sym_i)
+                .ok_or_invalid_input(|| format!("ELF symbol table index ({sym_i}) out of bounds"))?
+                .to_64bit();
+            if !func.matches(SymType::Function) || func.st_shndx == SHN_UNDEF {
+                continue
+            }
+
+            let range = func.st_value as Addr..(func.st_value + func.st_size) as Addr;
+            if name == base.as_bytes() {
+                let () = funcs.push(range);
+            } else if str::from_utf8(name).ok().and_then(cold_fragment_base) == Some(base) {
+                let () = fragments.push(range);
+            }
+        }
+
+        let () = funcs.sort_by_key(|range| (range.start, range.end));
+        let () = funcs.dedup();
+
+        let mut ranges = if funcs.len() == 1 {
+            let () = funcs.append(&mut fragments);
+            funcs
+        } else {
+            vec![sym_range(&sym)]
+        };
+        let () = ranges.sort_by_key(|range| (range.start, range.end));
+        let () = ranges.dedup();
+        Ok(ranges)
+    }
+
     fn for_each_sym_impl(
         &self,
         opts: &FindAddrOpts,
         syms: &ElfN_Syms<'_>,
         strs: &[u8],
-        str2sym: &[(SymName, usize)],
+        str2sym: &Str2Sym,
         f: &mut ForEachFn<'_>,
     ) -> Result<()> {
         let shdrs = self.cache.ensure_shdrs()?;
 
-        for (name, idx) in str2sym {
+        for (name, idx) in str2sym.iter_from(0) {
             let sym = &syms
-                .get(*idx)
+                .get(idx)
                 .ok_or_invalid_input(|| format!("symbol table index ({idx}) out of bounds"))?;
             let sym = sym.to_64bit();
 
@@ -1140,10 +1726,11 @@ where
                     sym_type: SymType::try_from(&sym).unwrap(),
                     file_offset: opts
                         .offset_in_file
-                        .then(|| self.file_offset(shdrs, &sym))
+                        .then(|| self.file_offset(&shdrs, &sym))
                         .transpose()?
                         .flatten(),
                     obj_file_name: None,
+                    code_info: None,
                 };
                 if let ControlFlow::Break(()) = f(&sym_info) {
                     return Ok(())
@@ -1157,15 +1744,20 @@ where
     /// Perform an operation on each symbol.
     #[allow(clippy::needless_borrows_for_generic_args)]
     pub(crate) fn for_each(&self, opts: &FindAddrOpts, f: &mut ForEachFn) -> Result<()> {
+        // See `find_addr` for details.
+        if opts.decl_file.is_some() {
+            return Ok(())
+        }
+
         let cache = self.cache.ensure_symtab_cache()?;
         let symtab = &cache.syms;
-        let strs = &cache.strs;
+        let strs = cache.strs;
         let str2symtab = self.cache.ensure_str2symtab()?;
         let () = self.for_each_sym_impl(opts, symtab, strs, str2symtab, f)?;
 
         let cache = self.cache.ensure_dynsym_cache()?;
         let dynsym = &cache.syms;
-        let strs = &cache.strs;
+        let strs = cache.strs;
         let str2dynsym = self.cache.ensure_str2dynsym()?;
         let () = self.for_each_sym_impl(opts, dynsym, strs, str2dynsym, f)?;
 
@@ -1190,24 +1782,158 @@ where
         Ok(offset)
     }
 
+    /// Retrieve the range of virtual offsets covered by the file's
+    /// loadable segments, if any.
+    pub(crate) fn load_range(&self) -> Result<Option<Range<Addr>>> {
+        let phdrs = self.program_headers()?;
+        let range = phdrs
+            .iter(0)
+            .map(|phdr| phdr.to_64bit())
+            .filter(|phdr| phdr.p_type == PT_LOAD)
+            .map(|phdr| phdr.p_vaddr..phdr.p_vaddr.saturating_add(phdr.p_memsz))
+            .reduce(|range, other| range.start.min(other.start)..range.end.max(other.end));
+        Ok(range)
+    }
+
+    /// Retrieve the stubs of the procedure linkage table, along with
+    /// the names of the imported symbols they resolve to.
+    ///
+    /// Stubs are associated with symbols based on the relocations in
+    /// `.rela.plt` (or `.rel.plt`), which are ordered just like the
+    /// stubs themselves.
+    pub(crate) fn plt_entries(&self) -> Result<Vec<PltEntry<'_>>> {
+        let rel_idx = match self.cache.find_section(".rela.plt")? {
+            Some(idx) => idx,
+            None => match self.cache.find_section(".rel.plt")? {
+                Some(idx) => idx,
+                None => return Ok(Vec::new()),
+            },
+        };
+
+        // With Indirect Branch Tracking enabled, the stubs proper live
+        // in `.plt.sec`, while `.plt` only contains trampolines.
+        let (plt_idx, first_offset, stub_size) = match self.cache.find_section(".plt.sec")? {
+            Some(idx) => (idx, 0, 16),
+            None => {
+                let idx = match self.cache.find_section(".plt")? {
+                    Some(idx) => idx,
+                    None => return Ok(Vec::new()),
+                };
+
+                // The first entry of `.plt` is reserved for the dynamic
+                // linker's lazy binding logic and it differs in size
+                // between architectures.
+                match self.machine()? {
+                    EM_386 | EM_X86_64 => (idx, 16, 16),
+                    EM_AARCH64 => (idx, 32, 16),
+                    machine => {
+                        return Err(Error::with_unsupported(format!(
+                            "PLT parsing is unsupported for ELF machine type {machine}"
+                        )))
+                    }
+                }
+            }
+        };
+
+        let plt_shdr = self.cache.section_hdr(plt_idx)?;
+        let rel_shdr = self.cache.section_hdr(rel_idx)?;
+        let is_rela = match rel_shdr.type_() {
+            SHT_RELA => true,
+            SHT_REL => false,
+            type_ => {
+                return Err(Error::with_invalid_data(format!(
+                    "ELF PLT relocation section has unexpected type ({type_})"
+                )))
+            }
+        };
+        let is_32bit = self.is_32bit()?;
+        let dynsym = self.cache.ensure_dynsym_cache()?;
+        let mut data = self.cache.section_data(rel_idx)?;
+        let mut entries = Vec::new();
+        let mut addr = plt_shdr.addr() + first_offset;
+
+        while !data.is_empty() {
+            let (got_addr, sym_idx) = match (is_32bit, is_rela) {
+                (true, false) => data
+                    .read_pod::<Elf32_Rel>()
+                    .map(|rel| (u64::from(rel.r_offset), rel.r_info >> 8)),
+                (true, true) => data
+                    .read_pod::<Elf32_Rela>()
+                    .map(|rel| (u64::from(rel.r_offset), rel.r_info >> 8)),
+                (false, false) => data
+                    .read_pod::<Elf64_Rel>()
+                    .map(|rel| (rel.r_offset, (rel.r_info >> 32) as u32)),
+                (false, true) => data
+                    .read_pod::<Elf64_Rela>()
+                    .map(|rel| (rel.r_offset, (rel.r_info >> 32) as u32)),
+            }
+            .ok_or_invalid_data(|| "failed to read ELF PLT relocation")?;
+
+            // Relocations without a symbol (e.g., `R_X86_64_IRELATIVE`)
+            // still occupy a stub, but there is no name we could report.
+            if sym_idx != 0 {
+                let sym = dynsym.syms.get(sym_idx as usize).ok_or_invalid_data(|| {
+                    format!("ELF dynamic symbol index ({sym_idx}) out of bounds")
+                })?;
+                let name = symbol_name(dynsym.strs, &sym.to_64bit())?;
+                let entry = PltEntry {
+                    name: Cow::Borrowed(name),
+                    addr,
+                    size: stub_size as usize,
+                    got_addr,
+                    file_offset: (plt_shdr.type_() != SHT_NOBITS)
+                        .then(|| addr - plt_shdr.addr() + plt_shdr.offset()),
+                };
+                let () = entries.push(entry);
+            }
+            addr += stub_size;
+        }
+
+        Ok(entries)
+    }
+
     #[cfg(test)]
     fn get_symbol_name(&self, idx: usize) -> Result<&str> {
         let symtab_cache = self.cache.ensure_symtab_cache()?;
         let sym = self.cache.symbol(idx)?;
-        let name = symbol_name(&symtab_cache.strs, &sym.to_64bit())?;
+        let name = symbol_name(symtab_cache.strs, &sym.to_64bit())?;
         Ok(name)
     }
 
-    pub(crate) fn section_headers(&self) -> Result<&ElfN_Shdrs<'_>> {
+    #[cfg(test)]
+    pub(crate) fn section_headers(&self) -> Result<ElfN_Shdrs<'_>> {
         let shdrs = self.cache.ensure_shdrs()?;
         Ok(shdrs)
     }
 
-    pub(crate) fn program_headers(&self) -> Result<&ElfN_Phdrs<'_>> {
+    pub(crate) fn program_headers(&self) -> Result<ElfN_Phdrs<'_>> {
         let phdrs = self.cache.ensure_phdrs()?;
         Ok(phdrs)
     }
 
+    /// Retrieve the raw data of the ELF segment at index `idx`.
+    pub(crate) fn segment_data(&self, idx: usize) -> Result<Cow<'_, [u8]>> {
+        self.cache.segment_data(idx)
+    }
+
+    /// Check whether the ELF file is a 32 bit one.
+    pub(crate) fn is_32bit(&self) -> Result<bool> {
+        let ehdr = self.cache.ensure_ehdr()?;
+        Ok(ehdr.is_32bit())
+    }
+
+    /// Retrieve the type of the ELF file (`e_type`).
+    pub(crate) fn file_type(&self) -> Result<u16> {
+        let ehdr = self.cache.ensure_ehdr()?;
+        Ok(ehdr.ehdr.type_())
+    }
+
+    /// Retrieve the architecture of the ELF file (`e_machine`).
+    pub(crate) fn machine(&self) -> Result<u16> {
+        let ehdr = self.cache.ensure_ehdr()?;
+        Ok(ehdr.ehdr.machine())
+    }
+
     /// Translate a file offset into a virtual offset.
     pub(crate) fn file_offset_to_virt_offset(&self, offset: u64) -> Result<Option<Addr>> {
         let phdrs = self.program_headers()?;
@@ -1224,6 +1950,33 @@ where
         Ok(addr)
     }
 
+    /// Translate the file offset at which a memory mapping of the file
+    /// starts into a virtual offset.
+    ///
+    /// Contrary to
+    /// [`file_offset_to_virt_offset`][Self::file_offset_to_virt_offset],
+    /// this method takes into account that loadable segments are mapped
+    /// at page granularity, meaning that a mapping may start before the
+    /// file offset of the segment it represents.
+    pub(crate) fn mapping_offset_to_virt_offset(&self, offset: u64) -> Result<Option<Addr>> {
+        let phdrs = self.program_headers()?;
+        let addr = phdrs.iter(0).find_map(|phdr| {
+            let phdr = phdr.to_64bit();
+            if phdr.p_type == PT_LOAD && offset <= phdr.p_offset {
+                let delta = phdr.p_offset - offset;
+                if delta < phdr.p_align.max(1) {
+                    return phdr.p_vaddr.checked_sub(delta)
+                }
+            }
+            None
+        });
+
+        match addr {
+            Some(addr) => Ok(Some(addr)),
+            None => self.file_offset_to_virt_offset(offset),
+        }
+    }
+
     #[cfg(test)]
     fn pick_symtab_addr(&self) -> (&str, Addr, usize) {
         let cache = self.cache.ensure_symtab_cache().unwrap();
@@ -1275,6 +2028,7 @@ where
 mod tests {
     use super::*;
 
+    use super::super::types::NT_GNU_BUILD_ID;
     use super::super::types::SHN_LORESERVE;
 
     use std::env;
@@ -1285,10 +2039,18 @@ mod tests {
     use std::mem::size_of;
     use std::slice;
 
+    use rand::Rng as _;
+
+    use tempfile::tempdir;
     use tempfile::NamedTempFile;
 
     use test_log::test;
 
+    use crate::test_utils::Class;
+    use crate::test_utils::ElfBuilder;
+    use crate::test_utils::Endianness;
+    use crate::ErrorKind;
+
     #[cfg(feature = "nightly")]
     use test::Bencher;
 
@@ -1313,7 +2075,7 @@ mod tests {
             e_shstrndx: 29,
         };
         let ehdr = EhdrExt {
-            ehdr: ElfN_Ehdr::B64(Cow::Borrowed(&ehdr)),
+            ehdr: ElfN_Ehdr::B64(Cow::Owned(ehdr)),
             shnum: 42,
             phnum: 0,
         };
@@ -1506,6 +2268,62 @@ mod tests {
         assert!(sym.size.is_none() && size == 0 || sym.size.unwrap() == size);
     }
 
+    /// Check that we correctly identify the names of cold fragments.
+    #[test]
+    fn cold_fragment_name_parsing() {
+        assert_eq!(cold_fragment_base("foo.cold"), Some("foo"));
+        assert_eq!(cold_fragment_base("foo.cold.12"), Some("foo"));
+        assert_eq!(cold_fragment_base("foo.part.0.cold"), Some("foo.part.0"));
+        assert_eq!(cold_fragment_base("foo"), None);
+        assert_eq!(cold_fragment_base("foo.cold."), None);
+        assert_eq!(cold_fragment_base("foo.cold.x"), None);
+        assert_eq!(cold_fragment_base("foo.colder"), None);
+        assert_eq!(cold_fragment_base(".cold"), None);
+    }
+
+    /// Check that cold fragments are attributed to the functions they
+    /// were split off of.
+    #[test]
+    fn func_ranges_with_cold_fragments() {
+        let mut builder = ElfBuilder::new();
+        let text = builder.add_section(".text", 0x4000, vec![0xcc; 0x100]);
+        let unlikely = builder.add_section(".text.unlikely", 0x3000, vec![0xcc; 0x100]);
+        let () = builder.add_symbol("foo", text, 0x4000, 0x20, SymType::Function);
+        let () = builder.add_symbol("foo.cold", unlikely, 0x3000, 0x10, SymType::Function);
+        let () = builder.add_symbol("foo.cold.1", unlikely, 0x3010, 0x8, SymType::Function);
+        let () = builder.add_symbol("foobar", text, 0x4020, 0x20, SymType::Function);
+        let () = builder.add_symbol("foobar.cold", unlikely, 0x3020, 0x10, SymType::Function);
+        let () = builder.add_symbol("dup", text, 0x4040, 0x10, SymType::Function);
+        let () = builder.add_symbol("dup", text, 0x4050, 0x10, SymType::Function);
+        let () = builder.add_symbol("dup.cold", unlikely, 0x3030, 0x10, SymType::Function);
+        let () = builder.add_symbol("var", text, 0x4060, 0x10, SymType::Variable);
+
+        let mut file = NamedTempFile::new().unwrap();
+        let () = file.write_all(&builder.build()).unwrap();
+        let parser = ElfParser::open(file.path()).unwrap();
+
+        let foo = vec![0x3000..0x3010, 0x3010..0x3018, 0x4000..0x4020];
+        assert_eq!(parser.find_func_ranges(0x4010).unwrap(), foo);
+        assert_eq!(parser.find_func_ranges(0x3000).unwrap(), foo);
+        assert_eq!(parser.find_func_ranges(0x3017).unwrap(), foo);
+
+        let foobar = vec![0x3020..0x3030, 0x4020..0x4040];
+        assert_eq!(parser.find_func_ranges(0x4020).unwrap(), foobar);
+
+        // `dup` is ambiguous, so its cold fragment can't be attributed.
+        assert_eq!(
+            parser.find_func_ranges(0x4058).unwrap(),
+            vec![0x4050..0x4060]
+        );
+        assert_eq!(
+            parser.find_func_ranges(0x3030).unwrap(),
+            vec![0x3030..0x3040]
+        );
+
+        assert_eq!(parser.find_func_ranges(0x4060).unwrap(), Vec::new());
+        assert_eq!(parser.find_func_ranges(0x5000).unwrap(), Vec::new());
+    }
+
     #[test]
     fn elf64_lookup_symbol_random() {
         let bin_name = Path::new(&env!("CARGO_MANIFEST_DIR"))
@@ -1534,6 +2352,8 @@ mod tests {
             let opts = FindAddrOpts {
                 offset_in_file: true,
                 sym_type: SymType::Function,
+                code_info: false,
+                decl_file: None,
             };
             let parser = ElfParser::open(path).unwrap();
             let () = parser
@@ -1791,7 +2611,7 @@ mod tests {
             e_shstrndx: 1,
         };
         let ehdr = EhdrExt {
-            ehdr: ElfN_Ehdr::B64(Cow::Borrowed(&ehdr)),
+            ehdr: ElfN_Ehdr::B64(Cow::Owned(ehdr)),
             shnum: 3,
             phnum: 0,
         };
@@ -1838,18 +2658,21 @@ mod tests {
                 sh_entsize: 0,
             },
         ];
-        let mut aligned_data = [0u8; 1024].as_slice();
-        let () = aligned_data.align(8).unwrap();
+        let mmap = Mmap::from_bytes(&[0u8; 1024]).unwrap();
 
         let cache = Cache {
-            backend: aligned_data,
+            backend: mmap,
             ehdr: OnceCell::from(ehdr),
-            shdrs: OnceCell::from(ElfN_Shdrs::B64(Cow::Borrowed(shdrs.as_slice()))),
-            shstrtab: OnceCell::from(Cow::Borrowed(b".shstrtab\x00.symtab\x00".as_slice())),
+            shdrs: OnceCell::from(ElfNRegion::B64(Region::Owned(Box::new(shdrs)))),
+            shstrtab: OnceCell::from(Region::Owned(Box::from(
+                b".shstrtab\x00.symtab\x00".as_slice(),
+            ))),
             phdrs: OnceCell::new(),
             symtab: OnceCell::new(),
             dynsym: OnceCell::new(),
             section_data: OnceCell::new(),
+            index_cache: None,
+            bat: OnceCell::new(),
         };
 
         assert_eq!(cache.find_section(".symtab").unwrap(), Some(2));
@@ -1858,6 +2681,347 @@ mod tests {
         assert!(symtab.is_empty());
     }
 
+    /// Check that `ElfParser` objects can be sent to other threads.
+    #[test]
+    fn parser_send() {
+        fn test<T>()
+        where
+            T: Send,
+        {
+        }
+
+        let () = test::<ElfParser<Mmap>>();
+        let () = test::<ElfParser<File>>();
+    }
+
+    /// Check that section data can be retrieved while other cached
+    /// data is borrowed, on both backends.
+    #[test]
+    fn interleaved_cache_access() {
+        fn test<B>(parser: ElfParser<B>)
+        where
+            B: Backend,
+        {
+            let shdrs = parser.section_headers().unwrap();
+            let name = parser.cache.section_name(1).unwrap();
+            let idx = parser.find_section(name).unwrap().unwrap();
+            let data = parser.section_data(idx).unwrap();
+            let shdr = shdrs.get(idx).unwrap();
+            assert_eq!(data.len() as u64, shdr.size());
+            assert_eq!(parser.cache.section_name(1).unwrap(), name);
+        }
+
+        let exe = current_exe().unwrap();
+        let parser_mmap = ElfParser::open(&exe).unwrap();
+        let () = test(parser_mmap);
+
+        let parser_io = ElfParser::open_non_mmap(exe).unwrap();
+        let () = test(parser_io);
+    }
+
+    /// Write the provided ELF data to a temporary file.
+    fn synthetic_elf_file(elf: &[u8]) -> NamedTempFile {
+        let mut file = NamedTempFile::new().unwrap();
+        let () = file.write_all(elf).unwrap();
+        file
+    }
+
+    /// Check that symbol lookups in both directions work as expected on
+    /// randomly generated ELF files, including symbols aliasing each
+    /// other.
+    #[test]
+    fn synthetic_elf_symbol_lookup() {
+        let mut rng = rand::rng();
+
+        for _ in 0..32 {
+            for class in [Class::Elf32, Class::Elf64] {
+                let base = 0x10000 + rng.random_range(0..0x100) * 0x1000;
+                let count = rng.random_range(1..64);
+                let mut funcs = Vec::new();
+                let mut addr = base;
+                for i in 0..count {
+                    let size = rng.random_range(1..0x100);
+                    let alias = rng.random_bool(0.25).then(|| format!("alias{i}"));
+                    let () = funcs.push((format!("func{i}"), alias, addr, size));
+                    addr += size;
+                }
+
+                let mut builder = ElfBuilder::new();
+                let _builder = builder.set_class(class);
+                let text = builder.add_section(".text", base, vec![0xcc; (addr - base) as usize]);
+                for (name, alias, addr, size) in &funcs {
+                    let () = builder.add_symbol(name, text, *addr, *size, SymType::Function);
+                    if let Some(alias) = alias {
+                        let () = builder.add_symbol(alias, text, *addr, *size, SymType::Function);
+                    }
+                }
+                let file = synthetic_elf_file(&builder.build());
+                let parser = ElfParser::open(file.path()).unwrap();
+                assert_eq!(parser.is_32bit().unwrap(), class == Class::Elf32);
+
+                let opts = FindAddrOpts {
+                    offset_in_file: true,
+                    sym_type: SymType::Function,
+                    code_info: false,
+                    decl_file: None,
+                };
+                for (name, alias, addr, size) in &funcs {
+                    let lookup = addr + rng.random_range(0..*size);
+                    let sym = parser
+                        .find_sym(lookup, &FindSymOpts::Basic)
+                        .unwrap()
+                        .unwrap();
+                    assert_eq!(sym.addr, *addr);
+                    assert_eq!(sym.size, Some(*size as usize));
+                    assert!(
+                        sym.name == name || Some(sym.name) == alias.as_deref(),
+                        "{} != {name}",
+                        sym.name
+                    );
+
+                    for name in [Some(name), alias.as_ref()].into_iter().flatten() {
+                        let infos = parser.find_addr(name, &opts).unwrap();
+                        assert_eq!(infos.len(), 1);
+                        assert_eq!(infos[0].addr, *addr);
+                        let file_offset = infos[0].file_offset.unwrap();
+                        let virt_offset = parser.file_offset_to_virt_offset(file_offset).unwrap();
+                        assert_eq!(virt_offset, Some(*addr));
+                    }
+                }
+
+                let result = parser.find_sym(addr, &FindSymOpts::Basic).unwrap();
+                assert_eq!(result, Err(Reason::UnknownAddr));
+            }
+        }
+    }
+
+    /// Check that we reject ELF files not matching the host's byte
+    /// order.
+    #[test]
+    fn synthetic_elf_foreign_endianness() {
+        let foreign = match Endianness::native() {
+            Endianness::Little => Endianness::Big,
+            Endianness::Big => Endianness::Little,
+        };
+
+        for class in [Class::Elf32, Class::Elf64] {
+            let mut builder = ElfBuilder::new();
+            let _builder = builder.set_class(class).set_endianness(foreign);
+            let text = builder.add_section(".text", 0x1000, vec![0xcc; 0x10]);
+            let () = builder.add_symbol("func", text, 0x1000, 0x10, SymType::Function);
+            let file = synthetic_elf_file(&builder.build());
+            let parser = ElfParser::open(file.path()).unwrap();
+            let err = parser.find_sym(0x1000, &FindSymOpts::Basic).unwrap_err();
+            assert_eq!(err.kind(), ErrorKind::Unsupported);
+
+            let _builder = builder.set_endianness(Endianness::native());
+            let file = synthetic_elf_file(&builder.build());
+            let parser = ElfParser::open(file.path()).unwrap();
+            let sym = parser
+                .find_sym(0x1000, &FindSymOpts::Basic)
+                .unwrap()
+                .unwrap();
+            assert_eq!(sym.name, "func");
+        }
+    }
+
+    /// Check that we can work with generated ELF files containing more
+    /// sections than can be represented in the ELF header and that
+    /// notes are found.
+    #[test]
+    fn synthetic_elf_excessive_sections() {
+        for class in [Class::Elf32, Class::Elf64] {
+            let mut builder = ElfBuilder::new();
+            let _builder = builder
+                .set_class(class)
+                .add_padding_sections(SHN_LORESERVE.into());
+            let text = builder.add_section(".text", 0x2000, vec![0xcc; 0x40]);
+            let () = builder.add_symbol("func", text, 0x2010, 0x10, SymType::Function);
+            let () = builder.add_note("GNU", NT_GNU_BUILD_ID, vec![0x12, 0x34, 0x56]);
+            let file = synthetic_elf_file(&builder.build());
+
+            let parser = ElfParser::open(file.path()).unwrap();
+            let shnum = parser.section_headers().unwrap().len();
+            assert!(shnum > usize::from(SHN_LORESERVE), "{shnum}");
+            let sym = parser
+                .find_sym(0x2018, &FindSymOpts::Basic)
+                .unwrap()
+                .unwrap();
+            assert_eq!(sym.name, "func");
+
+            let idx = parser.find_section(".note").unwrap().unwrap();
+            let data = parser.section_data(idx).unwrap();
+            assert_eq!(data.len(), 12 + 4 + 4);
+        }
+    }
+
+    /// Check that we can symbolize addresses in generated ELF files
+    /// exhibiting the deviations from conventions that we tolerate.
+    #[test]
+    fn synthetic_elf_tolerances() {
+        fn test<F>(f: F)
+        where
+            F: Fn(&mut ElfBuilder),
+        {
+            for class in [Class::Elf32, Class::Elf64] {
+                let mut builder = ElfBuilder::new();
+                let _builder = builder.set_class(class);
+                let text = builder.add_section(".text", 0x1000, vec![0xcc; 0x40]);
+                let () = builder.add_symbol("func", text, 0x1010, 0x10, SymType::Function);
+                let () = f(&mut builder);
+                let file = synthetic_elf_file(&builder.build());
+
+                let parser = ElfParser::open(file.path()).unwrap();
+                let sym = parser
+                    .find_sym(0x1018, &FindSymOpts::Basic)
+                    .unwrap()
+                    .unwrap();
+                assert_eq!(sym.name, "func");
+
+                let syms = parser.find_addr("func", &FindAddrOpts::default()).unwrap();
+                assert_eq!(syms.len(), 1);
+                assert_eq!(syms[0].addr, 0x1010);
+            }
+        }
+
+        let () = test(|_builder| ());
+        let () = test(|builder| {
+            let _builder = builder.omit_shstrtab();
+        });
+        let () = test(|builder| {
+            let _builder = builder.set_symtab_names(".syms", ".strs");
+        });
+        let () = test(|builder| {
+            let _builder = builder.set_symtab_entsize(0);
+        });
+        let () = test(|builder| {
+            let _builder = builder.set_symtab_link(0);
+        });
+        let () = test(|builder| {
+            let _builder = builder.omit_shstrtab().set_symtab_entsize(0);
+        });
+    }
+
+    /// Check that a symbol table with an entry size not matching that
+    /// of a symbol is reported as invalid.
+    #[test]
+    fn synthetic_elf_invalid_symtab_entsize() {
+        let mut builder = ElfBuilder::new();
+        let text = builder.add_section(".text", 0x1000, vec![0xcc; 0x40]);
+        let () = builder.add_symbol("func", text, 0x1010, 0x10, SymType::Function);
+        let _builder = builder.set_symtab_entsize(16);
+        let file = synthetic_elf_file(&builder.build());
+
+        let parser = ElfParser::open(file.path()).unwrap();
+        let err = parser.find_sym(0x1018, &FindSymOpts::Basic).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidData);
+    }
+
+    /// Check that we can look up the section containing an address.
+    #[test]
+    fn section_lookup_by_addr() {
+        let mut builder = ElfBuilder::new();
+        let _text = builder.add_section(".text", 0x2000, vec![0xcc; 0x40]);
+        let _plt = builder.add_section(".plt", 0x3000, vec![0xcc; 0x10]);
+        let file = synthetic_elf_file(&builder.build());
+
+        let parser = ElfParser::open(file.path()).unwrap();
+        assert_eq!(parser.find_section_by_addr(0x2000).unwrap(), Some(".text"));
+        assert_eq!(parser.find_section_by_addr(0x203f).unwrap(), Some(".text"));
+        assert_eq!(parser.find_section_by_addr(0x3008).unwrap(), Some(".plt"));
+        assert_eq!(parser.find_section_by_addr(0x2040).unwrap(), None);
+        assert_eq!(parser.find_section_by_addr(0x1000).unwrap(), None);
+    }
+
+    /// Check that symbol table indexes are persisted to and loaded
+    /// from an index cache.
+    #[test]
+    fn index_persistence() {
+        let mut builder = ElfBuilder::new();
+        let text = builder.add_section(".text", 0x2000, vec![0xcc; 0x40]);
+        let () = builder.add_symbol("func2", text, 0x2020, 0x10, SymType::Function);
+        let () = builder.add_symbol("func1", text, 0x2010, 0x10, SymType::Function);
+        let () = builder.add_note("GNU", NT_GNU_BUILD_ID, vec![0xab, 0xcd]);
+        let file = synthetic_elf_file(&builder.build());
+        let dir = tempdir().unwrap();
+        let index_cache = IndexCache::new(dir.path().to_path_buf());
+
+        for _ in 0..2 {
+            let mut parser = ElfParser::open(file.path()).unwrap();
+            assert_eq!(parser.build_id().unwrap(), Some([0xab, 0xcd].as_slice()));
+            let () = parser.set_index_cache(Some(index_cache.clone()));
+
+            let sym = parser
+                .find_sym(0x2018, &FindSymOpts::Basic)
+                .unwrap()
+                .unwrap();
+            assert_eq!(sym.name, "func1");
+            let sym = parser
+                .find_sym(0x2028, &FindSymOpts::Basic)
+                .unwrap()
+                .unwrap();
+            assert_eq!(sym.name, "func2");
+            assert!(index_cache.index_path(&[0xab, 0xcd], ".symtab").exists());
+        }
+
+        let mut parser = ElfParser::open(file.path()).unwrap();
+        let () = parser.set_index_cache(Some(index_cache));
+        let symtab = parser.cache.ensure_symtab_cache().unwrap();
+        let _idx = symtab.ensure_by_addr_idx();
+        assert!(matches!(
+            symtab.cache.by_addr_idx.get(),
+            Some(SymIndex::Mapped(..))
+        ));
+    }
+
+    /// Check that symbol lookups by name work with a name table using
+    /// `usize` offsets, as used if offsets exceed the range of `u32`.
+    #[test]
+    fn wide_str2sym_lookup() {
+        let mut builder = ElfBuilder::new();
+        let text = builder.add_section(".text", 0x1000, vec![0xcc; 0x100]);
+        let () = builder.add_symbol("foo", text, 0x1000, 0x20, SymType::Function);
+        let () = builder.add_symbol("bar", text, 0x1020, 0x20, SymType::Function);
+        let () = builder.add_symbol("foo.cold", text, 0x10e0, 0x10, SymType::Function);
+        let file = synthetic_elf_file(&builder.build());
+        let parser = ElfParser::open(file.path()).unwrap();
+
+        let symtab = parser.cache.ensure_symtab_cache().unwrap();
+        let wide = match symtab.create_str2sym(|_sym| true).unwrap() {
+            Str2Sym::Compact(entries) => entries
+                .iter()
+                .map(|(name, sym_i)| (name.widen(), *sym_i as usize))
+                .collect(),
+            Str2Sym::Wide(..) => panic!("unexpectedly created wide name table"),
+        };
+        let () = symtab.cache.str2sym.set(Str2Sym::Wide(wide)).unwrap();
+
+        let opts = FindAddrOpts {
+            sym_type: SymType::Function,
+            ..Default::default()
+        };
+        let infos = parser.find_addr("bar", &opts).unwrap();
+        assert_eq!(infos.len(), 1);
+        assert_eq!(infos[0].addr, 0x1020);
+        assert!(parser.find_addr("baz", &opts).unwrap().is_empty());
+
+        let ranges = parser.find_func_ranges(0x1010).unwrap();
+        assert_eq!(ranges, vec![0x1000..0x1020, 0x10e0..0x10f0]);
+
+        let mut names = Vec::new();
+        let () = parser
+            .for_each(&opts, &mut |sym| {
+                let () = names.push(sym.name.to_string());
+                ControlFlow::Continue(())
+            })
+            .unwrap();
+        assert_eq!(names, vec!["bar", "foo", "foo.cold"]);
+        assert!(matches!(
+            symtab.cache.str2sym.get(),
+            Some(Str2Sym::Wide(..))
+        ));
+    }
+
     /// Benchmark creation of our "str2symtab" table.
     ///
     /// Creating this table exercises a lot of the parser code paths and
@@ -1870,7 +3034,7 @@ mod tests {
             .join("data")
             .join("vmlinux-5.17.12-100.fc34.x86_64.elf");
         let parser = ElfParser::open(&path).unwrap();
-        let mmap = &parser._backend;
+        let mmap = &parser.cache.backend;
 
         // Our memory mapping is created only once and criterion does a
         // few warm up runs that should make sure that everything is
@@ -1878,7 +3042,7 @@ mod tests {
         // traversing performance here.
 
         let () = b.iter(|| {
-            let cache = Cache::new(mmap.deref());
+            let cache = Cache::new(mmap.clone());
             let syms = cache.ensure_str2symtab().unwrap();
             let _syms = black_box(syms);
         });
@@ -1,31 +1,58 @@
 use std::fmt::Debug;
 use std::fmt::Formatter;
 use std::fmt::Result as FmtResult;
+use std::fs;
+use std::ops::ControlFlow;
+use std::ops::Range;
+#[cfg(unix)]
+use std::os::unix::fs::MetadataExt as _;
 use std::path::Path;
 use std::path::PathBuf;
 use std::rc::Rc;
 
 #[cfg(feature = "dwarf")]
 use crate::dwarf::DwarfResolver;
+#[cfg(feature = "dwarf")]
+use crate::dwarf::LineTableRow;
 use crate::elf::DEFAULT_DEBUG_DIRS;
 use crate::file_cache::FileCache;
+use crate::index_cache::IndexCache;
 use crate::inspect::FindAddrOpts;
 use crate::inspect::ForEachFn;
 use crate::inspect::Inspect;
+use crate::inspect::PltEntry;
 use crate::inspect::SymInfo;
+#[cfg(feature = "dwarf")]
+use crate::inspect::TargetAccess;
+use crate::maps;
+use crate::maps::MapsEntry;
+use crate::maps::PathName;
 use crate::once::OnceCell;
+use crate::symbolize::source::BaseAddr;
+use crate::symbolize::CodeInfo;
 use crate::symbolize::FindSymOpts;
 use crate::symbolize::Reason;
 use crate::symbolize::ResolvedSym;
+use crate::symbolize::SourceInfo;
 use crate::symbolize::Symbolize;
 use crate::symbolize::TranslateFileOffset;
 use crate::Addr;
 use crate::Error;
+use crate::ErrorExt as _;
+use crate::IntoError as _;
+use crate::Pid;
 use crate::Result;
+use crate::SymType;
 
+use super::types::ET_DYN;
+use super::types::ET_EXEC;
 use super::ElfParser;
 
 
+/// The smallest page size of any supported architecture.
+const MIN_PAGE_SIZE: Addr = 0x1000;
+
+
 #[derive(Clone, Debug)]
 enum ElfBackend {
     #[cfg(feature = "dwarf")]
@@ -50,10 +77,42 @@ impl FileCache<ElfResolverData> {
     /// and the provided list of debug directories consulted when
     /// following debug links.
     /// If `debug_dirs` is `None` only ELF symbols will be consulted.
+    /// `qualified_names` controls whether names of functions found in
+    /// debug information are reported fully qualified.
+    /// If `index_cache` is `Some`, symbol table indexes are persisted
+    /// to and loaded from it.
     pub(crate) fn elf_resolver<'slf>(
         &'slf self,
         path: &Path,
         debug_dirs: Option<&[PathBuf]>,
+        qualified_names: bool,
+        index_cache: Option<&IndexCache>,
+    ) -> Result<&'slf Rc<ElfResolver>> {
+        self.elf_resolver_impl(path, debug_dirs, qualified_names, index_cache, false)
+    }
+
+    /// Create an `ElfResolver` for a file that may be compressed.
+    ///
+    /// This method works like [`elf_resolver`][Self::elf_resolver],
+    /// but gzip and zstd compressed files are transparently
+    /// decompressed into memory.
+    pub(crate) fn compressed_elf_resolver<'slf>(
+        &'slf self,
+        path: &Path,
+        debug_dirs: Option<&[PathBuf]>,
+        qualified_names: bool,
+        index_cache: Option<&IndexCache>,
+    ) -> Result<&'slf Rc<ElfResolver>> {
+        self.elf_resolver_impl(path, debug_dirs, qualified_names, index_cache, true)
+    }
+
+    fn elf_resolver_impl<'slf>(
+        &'slf self,
+        path: &Path,
+        debug_dirs: Option<&[PathBuf]>,
+        qualified_names: bool,
+        index_cache: Option<&IndexCache>,
+        decompress: bool,
     ) -> Result<&'slf Rc<ElfResolver>> {
         let (file, cell) = self.entry(path)?;
         let resolver = if let Some(data) = cell.get() {
@@ -64,7 +123,12 @@ impl FileCache<ElfResolverData> {
                     //         initializing the `dwarf` part of it, the
                     //         `elf` part *must* be present.
                     let parser = data.elf.get().unwrap().parser().clone();
-                    let resolver = ElfResolver::from_parser(parser, debug_dirs)?;
+                    let resolver = ElfResolver::from_parser(
+                        parser,
+                        debug_dirs,
+                        qualified_names,
+                        self.strict(),
+                    )?;
                     let resolver = Rc::new(resolver);
                     Result::<_, Error>::Ok(resolver)
                 })?
@@ -75,15 +139,27 @@ impl FileCache<ElfResolverData> {
                     //         initializing the `elf` part of it, the
                     //         `dwarf` part *must* be present.
                     let parser = data.dwarf.get().unwrap().parser().clone();
-                    let resolver = ElfResolver::from_parser(parser, debug_dirs)?;
+                    let resolver = ElfResolver::from_parser(
+                        parser,
+                        debug_dirs,
+                        qualified_names,
+                        self.strict(),
+                    )?;
                     let resolver = Rc::new(resolver);
                     Result::<_, Error>::Ok(resolver)
                 })?
             }
             .clone()
         } else {
-            let parser = Rc::new(ElfParser::open_file(file, path)?);
-            let resolver = ElfResolver::from_parser(parser, debug_dirs)?;
+            let mut parser = if decompress {
+                ElfParser::open_file_decompressed(&*file.get()?, path)?
+            } else {
+                ElfParser::open_file(&*file.get()?, path)?
+            };
+            let () = parser.set_index_cache(index_cache.cloned());
+            let parser = Rc::new(parser);
+            let resolver =
+                ElfResolver::from_parser(parser, debug_dirs, qualified_names, self.strict())?;
             Rc::new(resolver)
         };
 
@@ -133,20 +209,27 @@ impl ElfResolver {
                     .map(PathBuf::from)
                     .collect::<Vec<_>>(),
             ),
+            false,
+            false,
         )
     }
 
     /// Create a new [`ElfResolver`] using `parser`.
     ///
     /// If `debug_dirs` is `Some`, interpret DWARF debug information. If it is
-    /// `None`, just look at ELF symbols.
+    /// `None`, just look at ELF symbols. `qualified_names` only has an
+    /// effect when DWARF debug information is used. `strict` controls
+    /// whether separate debug files are opened subject to the checks
+    /// of strict file access.
     pub(crate) fn from_parser(
         parser: Rc<ElfParser>,
         debug_dirs: Option<&[PathBuf]>,
+        qualified_names: bool,
+        strict: bool,
     ) -> Result<Self> {
         #[cfg(feature = "dwarf")]
         let backend = if let Some(debug_dirs) = debug_dirs {
-            let dwarf = DwarfResolver::from_parser(parser, debug_dirs)?;
+            let dwarf = DwarfResolver::from_parser(parser, debug_dirs, qualified_names, strict)?;
             let backend = ElfBackend::Dwarf(Rc::new(dwarf));
             backend
         } else {
@@ -168,18 +251,362 @@ impl ElfResolver {
         }
     }
 
+    /// Invoke `f` for each row of the DWARF line table that has an
+    /// address within `range`.
+    ///
+    /// Rows ending a sequence of instructions are reported if the
+    /// address they mark, i.e., the first byte after the sequence, is
+    /// within `range` or equal to its end.
+    ///
+    /// Addresses are normalized ones, in the same address space as used
+    /// for symbolization. Rows are reported in the order in which they
+    /// are emitted by the line number programs of the compilation units
+    /// covering `range`. This functionality can be used for building
+    /// custom address to line mappings, e.g., on a basic block level,
+    /// without having to parse DWARF separately.
+    ///
+    /// Line table information is only available if the resolver uses
+    /// DWARF debug information; otherwise an error is returned.
+    #[cfg(feature = "dwarf")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "dwarf")))]
+    pub fn for_each_line_row<F>(&self, range: Range<Addr>, mut f: F) -> Result<()>
+    where
+        F: FnMut(&LineTableRow<'_>) -> ControlFlow<()>,
+    {
+        match &self.backend {
+            ElfBackend::Dwarf(dwarf) => dwarf.for_each_line_row(range, &mut f),
+            ElfBackend::Elf(_) => Err(Error::with_unsupported(
+                "resolver does not use DWARF debug information",
+            )),
+        }
+    }
+
+    /// Report what information this resolver is able to provide.
+    pub(crate) fn probe(&self) -> Result<SourceInfo> {
+        /// Determine the range of addresses covered by the function
+        /// symbols reported by `for_each`.
+        fn sym_range<F>(for_each: F) -> Result<Option<Range<Addr>>>
+        where
+            F: FnOnce(&FindAddrOpts, &mut ForEachFn<'_>) -> Result<()>,
+        {
+            let opts = FindAddrOpts {
+                sym_type: SymType::Function,
+                ..Default::default()
+            };
+            let mut range = None::<Range<Addr>>;
+            let () = for_each(&opts, &mut |sym| {
+                let start = sym.addr;
+                let end = start + sym.size.unwrap_or(0) as Addr;
+                range = Some(match range.take() {
+                    Some(range) => range.start.min(start)..range.end.max(end),
+                    None => start..end,
+                });
+                ControlFlow::Continue(())
+            })?;
+            Ok(range)
+        }
+
+        let parser = self.parser();
+        let build_id = parser.build_id()?.map(<[u8]>::to_vec);
+        // We prefer ELF symbols for determining the address range, as
+        // they are cheaper to work with.
+        #[cfg_attr(not(feature = "dwarf"), allow(unused_mut))]
+        let mut addr_range = sym_range(|opts, f| parser.for_each(opts, f))?;
+
+        let (code_info, inlined_fns) = match &self.backend {
+            #[cfg(feature = "dwarf")]
+            ElfBackend::Dwarf(dwarf) => {
+                let debug_parser = dwarf.debug_parser();
+                let code_info = debug_parser.find_section(".debug_line")?.is_some();
+                let inlined_fns = debug_parser.find_section(".debug_info")?.is_some();
+                if addr_range.is_none() {
+                    addr_range = sym_range(|opts, f| dwarf.for_each(opts, f))?;
+                }
+                (code_info, inlined_fns)
+            }
+            ElfBackend::Elf(_) => (false, false),
+        };
+
+        let info = SourceInfo {
+            syms: addr_range.is_some(),
+            code_info,
+            inlined_fns,
+            build_id,
+            addr_range,
+            _non_exhaustive: (),
+        };
+        Ok(info)
+    }
+
+    /// Check whether the resolver consults DWARF debug information.
+    ///
+    /// That is only the case if debug symbols were requested and debug
+    /// information is actually present, either in the ELF file itself
+    /// or in a separate debug file.
+    pub(crate) fn uses_debug_info(&self) -> Result<bool> {
+        match &self.backend {
+            #[cfg(feature = "dwarf")]
+            ElfBackend::Dwarf(dwarf) => {
+                let debug_parser = dwarf.debug_parser();
+                let debug_info = debug_parser.find_section(".debug_info")?.is_some();
+                Ok(debug_info)
+            }
+            ElfBackend::Elf(_) => Ok(false),
+        }
+    }
+
+    /// Eagerly perform work that otherwise happens lazily on first
+    /// symbolization.
+    pub(crate) fn preload(&self) -> Result<()> {
+        self.parser().preload()
+    }
+
+    /// Eagerly index DWARF debug information, if in use, which
+    /// otherwise happens lazily on first symbolization.
+    pub(crate) fn preload_debug_info(&self) -> Result<()> {
+        match &self.backend {
+            #[cfg(feature = "dwarf")]
+            ElfBackend::Dwarf(dwarf) => dwarf.preload(),
+            ElfBackend::Elf(_) => Ok(()),
+        }
+    }
+
+    /// Translate `addr`, a virtual offset in the BOLT optimized ELF
+    /// file represented by this resolver, into the corresponding one in
+    /// `original`, the file that BOLT was run on.
+    ///
+    /// Addresses not part of functions touched by BOLT are reported
+    /// unchanged. `None` is returned if a function could not be found
+    /// in `original`.
+    pub(crate) fn translate_bolt_addr(
+        &self,
+        addr: Addr,
+        original: &ElfResolver,
+    ) -> Result<Option<Addr>> {
+        let parser = self.parser();
+        let bat = parser.bolt_addr_translation()?.ok_or_invalid_input(|| {
+            format!(
+                "ELF file `{}` does not contain BOLT address translation data",
+                self.path()
+                    .unwrap_or_else(|| Path::new("<unknown-path>"))
+                    .display()
+            )
+        })?;
+        let sym = match parser.find_sym(addr, &FindSymOpts::Basic)? {
+            Ok(sym) => sym,
+            Err(_reason) => return Ok(Some(addr)),
+        };
+        let (func_addr, offset) = match bat.translate(sym.addr, addr) {
+            Some(translated) => translated,
+            None => return Ok(Some(addr)),
+        };
+
+        // Cold fragments are translated relative to the function they
+        // were split off from, which we have to look up separately.
+        let name = if func_addr == sym.addr {
+            sym.name
+        } else {
+            match parser.find_sym(func_addr, &FindSymOpts::Basic)? {
+                Ok(sym) if sym.addr == func_addr => sym.name,
+                _ => return Ok(None),
+            }
+        };
+
+        // BOLT preserves function names, so that's what we use for
+        // correlating functions between the two files.
+        let opts = FindAddrOpts {
+            sym_type: SymType::Function,
+            ..Default::default()
+        };
+        let addr = original
+            .parser()
+            .find_addr(name, &opts)?
+            .first()
+            .map(|info| info.addr + offset);
+        Ok(addr)
+    }
+
     /// Retrieve the path to the ELF file represented by this resolver.
     pub(crate) fn path(&self) -> Option<&Path> {
         self.parser().path()
     }
+
+    /// Retrieve the PLT stubs of the ELF file, along with the names
+    /// of the imported symbols they resolve to.
+    pub(crate) fn plt_entries(&self) -> Result<Vec<PltEntry<'_>>> {
+        self.parser().plt_entries()
+    }
+
+    /// Translate the virtual offset `addr` into a file offset.
+    pub(crate) fn virt_offset_to_file_offset(&self, addr: Addr) -> Result<Option<u64>> {
+        self.parser().find_file_offset(addr)
+    }
+
+    /// Retrieve the range of virtual offsets covered by the file's
+    /// loadable segments, if any.
+    pub(crate) fn load_range(&self) -> Result<Option<Range<Addr>>> {
+        self.parser().load_range()
+    }
+
+    /// Calculate the load bias of the ELF file as described by
+    /// `base_addr`.
+    ///
+    /// The bias is validated against the file's type and segment layout
+    /// and an error is returned if it can't possibly be correct.
+    pub(crate) fn load_bias(&self, base_addr: &BaseAddr) -> Result<Addr> {
+        let parser = self.parser();
+        let path = self.path().unwrap_or_else(|| Path::new("<unknown-path>"));
+
+        let from_mapping = |addr: Addr, file_offset: u64| -> Result<Addr> {
+            let virt_offset = parser
+                .mapping_offset_to_virt_offset(file_offset)?
+                .ok_or_invalid_input(|| {
+                    format!(
+                        "file offset {file_offset:#x} is not covered by any loadable segment of `{}`",
+                        path.display()
+                    )
+                })?;
+            addr.checked_sub(virt_offset).ok_or_invalid_input(|| {
+                format!(
+                    "mapping address {addr:#x} is inconsistent with virtual offset {virt_offset:#x} of file offset {file_offset:#x}"
+                )
+            })
+        };
+
+        let bias = match base_addr {
+            BaseAddr::Addr(addr) => *addr,
+            BaseAddr::Mapping { addr, file_offset } => from_mapping(*addr, *file_offset)?,
+            BaseAddr::Process(pid) => {
+                let entry = find_file_mapping(pid, path)?.ok_or_not_found(|| {
+                    format!("`{}` is not mapped in process {pid}", path.display())
+                })?;
+                from_mapping(entry.range.start, entry.offset)?
+            }
+            BaseAddr::Symbol { name, addr } => {
+                let syms = self.find_addr(name, &FindAddrOpts::default())?;
+                let sym = syms.first().ok_or_not_found(|| {
+                    format!("symbol `{name}` not found in `{}`", path.display())
+                })?;
+                if syms.iter().any(|other| other.addr != sym.addr) {
+                    return Err(Error::with_invalid_input(format!(
+                        "symbol `{name}` is ambiguous in `{}`",
+                        path.display()
+                    )))
+                }
+                addr.checked_sub(sym.addr).ok_or_invalid_input(|| {
+                    format!(
+                        "symbol address {addr:#x} is inconsistent with virtual address {:#x} of `{name}`",
+                        sym.addr
+                    )
+                })?
+            }
+        };
+
+        match parser.file_type()? {
+            ET_EXEC => {
+                if bias != 0 {
+                    return Err(Error::with_invalid_input(format!(
+                        "`{}` is a non-relocatable ET_EXEC binary, but a base address of {bias:#x} was provided; use 0 instead",
+                        path.display()
+                    )))
+                }
+            }
+            ET_DYN => {
+                // Segments are always mapped at page granularity and so
+                // the bias of a relocatable binary has to be page
+                // aligned as well.
+                if bias % MIN_PAGE_SIZE != 0 {
+                    return Err(Error::with_invalid_input(format!(
+                        "base address {bias:#x} of `{}` is not page aligned",
+                        path.display()
+                    )))
+                }
+            }
+            ty => {
+                return Err(Error::with_unsupported(format!(
+                    "`{}` is of unsupported ELF type {ty} for absolute address symbolization",
+                    path.display()
+                )))
+            }
+        }
+        Ok(bias)
+    }
+}
+
+
+/// Find the lowest mapping of the file at `path` in the process with
+/// the given PID.
+///
+/// Files are identified by their device and inode numbers, so that
+/// differences in the path used for opening them do not matter.
+#[cfg(unix)]
+fn find_file_mapping(pid: &Pid, path: &Path) -> Result<Option<MapsEntry>> {
+    let meta = fs::metadata(path)
+        .with_context(|| format!("failed to retrieve metadata for `{}`", path.display()))?;
+
+    for entry in maps::parse(pid)? {
+        let entry = entry?;
+        if entry.inode != meta.ino() {
+            continue
+        }
+
+        let entry_path = match entry.path_name.as_ref().and_then(PathName::as_path) {
+            Some(entry_path) => entry_path,
+            None => continue,
+        };
+        // Accessing `map_files` requires additional privileges for
+        // other processes, so fall back to the symbolic path.
+        let entry_meta = match fs::metadata(&entry_path.maps_file) {
+            Ok(entry_meta) => entry_meta,
+            Err(_) => match fs::metadata(&entry_path.symbolic_path) {
+                Ok(entry_meta) => entry_meta,
+                Err(_) => continue,
+            },
+        };
+
+        if (entry_meta.dev(), entry_meta.ino()) == (meta.dev(), meta.ino()) {
+            return Ok(Some(entry))
+        }
+    }
+    Ok(None)
 }
 
+/// Find the lowest mapping of the file at `path` in the process with
+/// the given PID.
+///
+/// Files cannot be identified reliably on this platform and so the
+/// lookup is unsupported.
+#[cfg(not(unix))]
+fn find_file_mapping(_pid: &Pid, _path: &Path) -> Result<Option<MapsEntry>> {
+    Err(Error::with_unsupported(
+        "looking up file mappings is unsupported on this platform",
+    ))
+}
+
+
 impl Symbolize for ElfResolver {
     fn find_sym(&self, addr: Addr, opts: &FindSymOpts) -> Result<Result<ResolvedSym<'_>, Reason>> {
-        match &self.backend {
+        let result = match &self.backend {
             #[cfg(feature = "dwarf")]
             ElfBackend::Dwarf(dwarf) => dwarf.find_sym(addr, opts),
             ElfBackend::Elf(parser) => parser.find_sym(addr, opts),
+        }?;
+
+        match result {
+            Ok(mut sym) => {
+                sym.section = self.parser().find_section_by_addr(addr)?;
+                Ok(Ok(sym))
+            }
+            Err(reason) => Ok(Err(reason)),
+        }
+    }
+
+    fn find_code_info(&self, addr: Addr, opts: &FindSymOpts) -> Result<Option<CodeInfo<'_>>> {
+        match &self.backend {
+            #[cfg(feature = "dwarf")]
+            ElfBackend::Dwarf(dwarf) => dwarf.find_code_info(addr, opts),
+            // ELF symbol tables do not carry any source code information.
+            ElfBackend::Elf(..) => Ok(None),
         }
     }
 }
@@ -207,6 +634,39 @@ impl Inspect for ElfResolver {
             ElfBackend::Elf(parser) => parser.for_each(opts, f),
         }
     }
+
+    fn find_func_ranges(&self, addr: Addr) -> Result<Vec<Range<Addr>>> {
+        match &self.backend {
+            #[cfg(feature = "dwarf")]
+            ElfBackend::Dwarf(dwarf) => dwarf.find_func_ranges(addr),
+            ElfBackend::Elf(parser) => parser.find_func_ranges(addr),
+        }
+    }
+
+    fn find_line_addrs(&self, file: &Path, line: u32) -> Result<Vec<Addr>> {
+        match &self.backend {
+            #[cfg(feature = "dwarf")]
+            ElfBackend::Dwarf(dwarf) => dwarf.find_line_addrs(file, line),
+            // Without debug information there is no line information to
+            // consult.
+            ElfBackend::Elf(_parser) => Ok(Vec::new()),
+        }
+    }
+
+    #[cfg(feature = "dwarf")]
+    fn find_var_locations(
+        &self,
+        name: &str,
+        opts: &FindAddrOpts,
+        target: &dyn TargetAccess,
+    ) -> Result<Vec<SymInfo<'_>>> {
+        match &self.backend {
+            ElfBackend::Dwarf(dwarf) => dwarf.find_var_locations(name, opts, target),
+            // Without debug information there are no location
+            // descriptions to evaluate.
+            ElfBackend::Elf(_parser) => Ok(Vec::new()),
+        }
+    }
 }
 
 impl Debug for ElfResolver {
@@ -226,6 +686,12 @@ impl Debug for ElfResolver {
 mod tests {
     use super::*;
 
+    use std::env;
+
+    use test_log::test;
+
+    use crate::ErrorKind;
+
 
     /// Exercise the `Debug` representation of various types.
     #[test]
@@ -235,12 +701,12 @@ mod tests {
             .join("test-stable-addrs.bin");
 
         let parser = Rc::new(ElfParser::open(&path).unwrap());
-        let resolver = ElfResolver::from_parser(parser.clone(), None).unwrap();
+        let resolver = ElfResolver::from_parser(parser.clone(), None, false, false).unwrap();
         let dbg = format!("{resolver:?}");
         assert!(dbg.starts_with("Elf("), "{dbg}");
         assert!(dbg.ends_with("test-stable-addrs.bin\")"), "{dbg}");
 
-        let resolver = ElfResolver::from_parser(parser, Some(&[])).unwrap();
+        let resolver = ElfResolver::from_parser(parser, Some(&[]), false, false).unwrap();
         let dbg = format!("{resolver:?}");
         assert!(dbg.starts_with("Dwarf("), "{dbg}");
         assert!(dbg.ends_with("test-stable-addrs.bin\")"), "{dbg}");
@@ -258,4 +724,54 @@ mod tests {
         assert_eq!(parser.find_file_offset(0x0).unwrap(), None);
         assert_eq!(parser.find_file_offset(0xffffffffffffffff).unwrap(), None);
     }
+
+    /// Check that we can derive the load bias of an ELF file from the
+    /// runtime address of a symbol.
+    #[test]
+    fn load_bias_from_symbol() {
+        let path = Path::new(&env!("CARGO_MANIFEST_DIR"))
+            .join("data")
+            .join("test-stable-addrs.bin");
+        let parser = Rc::new(ElfParser::open(&path).unwrap());
+        let resolver = ElfResolver::from_parser(parser, None, false, false).unwrap();
+
+        let base_addr = BaseAddr::Symbol {
+            name: "factorial".to_string(),
+            addr: 0x2000200,
+        };
+        assert_eq!(resolver.load_bias(&base_addr).unwrap(), 0);
+
+        let base_addr = BaseAddr::Symbol {
+            name: "does_not_exist".to_string(),
+            addr: 0x2000200,
+        };
+        let err = resolver.load_bias(&base_addr).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::NotFound);
+
+        // `test-stable-addrs.bin` is a non-relocatable binary and so
+        // any non-zero bias is rejected.
+        let base_addr = BaseAddr::Symbol {
+            name: "factorial".to_string(),
+            addr: 0x3000200,
+        };
+        let err = resolver.load_bias(&base_addr).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidInput);
+    }
+
+    /// Check that we can derive the load bias of an ELF file from the
+    /// memory mappings of a process.
+    #[test]
+    fn load_bias_from_process() {
+        let path = env::current_exe().unwrap();
+        let parser = Rc::new(ElfParser::open(&path).unwrap());
+        let resolver = ElfResolver::from_parser(parser, None, false, false).unwrap();
+
+        let bias = resolver.load_bias(&BaseAddr::Process(Pid::Slf)).unwrap();
+        let addr = load_bias_from_process as *const () as Addr;
+        let sym = resolver
+            .find_sym(addr - bias, &FindSymOpts::Basic)
+            .unwrap()
+            .unwrap();
+        assert!(sym.name.contains("load_bias_from_process"), "{sym:?}");
+    }
 }
@@ -23,11 +23,25 @@ type Elf32_Section = u16;
 
 pub(crate) const ET_EXEC: u16 = 2;
 pub(crate) const ET_DYN: u16 = 3;
+pub(crate) const ET_CORE: u16 = 4;
+
+pub(crate) const EM_386: u16 = 3;
+pub(crate) const EM_MIPS: u16 = 8;
+pub(crate) const EM_PPC: u16 = 20;
+pub(crate) const EM_PPC64: u16 = 21;
+pub(crate) const EM_S390: u16 = 22;
+pub(crate) const EM_ARM: u16 = 40;
+pub(crate) const EM_X86_64: u16 = 62;
+pub(crate) const EM_AARCH64: u16 = 183;
+pub(crate) const EM_RISCV: u16 = 243;
 
 pub(crate) const ELFCLASSNONE: u8 = 0;
 pub(crate) const ELFCLASS32: u8 = 1;
 pub(crate) const ELFCLASS64: u8 = 2;
 
+pub(crate) const ELFDATA2LSB: u8 = 1;
+pub(crate) const ELFDATA2MSB: u8 = 2;
+
 
 #[derive(Debug)]
 pub(crate) enum ElfN<'elf, T>
@@ -48,6 +62,14 @@ where
         matches!(self, Self::B32(..))
     }
 
+    /// Convert the object into one that owns its data.
+    pub fn into_owned(self) -> ElfN<'static, T> {
+        match self {
+            Self::B32(ty) => ElfN::B32(Cow::Owned(ty.into_owned())),
+            Self::B64(ty) => ElfN::B64(Cow::Owned(ty.into_owned())),
+        }
+    }
+
     pub fn to_64bit(&self) -> T
     where
         T: Copy + for<'ty> From<&'ty T::Ty32Bit>,
@@ -175,6 +197,22 @@ impl Has32BitTy for Elf64_Ehdr {
 pub(crate) type ElfN_Ehdr<'elf> = ElfN<'elf, Elf64_Ehdr>;
 
 impl ElfN_Ehdr<'_> {
+    #[inline]
+    pub fn type_(&self) -> Elf64_Half {
+        match self {
+            ElfN::B32(ehdr) => ehdr.e_type,
+            ElfN::B64(ehdr) => ehdr.e_type,
+        }
+    }
+
+    #[inline]
+    pub fn machine(&self) -> Elf64_Half {
+        match self {
+            ElfN::B32(ehdr) => ehdr.e_machine,
+            ElfN::B64(ehdr) => ehdr.e_machine,
+        }
+    }
+
     #[inline]
     pub fn shnum(&self) -> Elf64_Half {
         match self {
@@ -425,17 +463,31 @@ impl ElfN_Shdr<'_> {
             ElfN::B64(shdr) => shdr.sh_link,
         }
     }
+
+    #[inline]
+    pub fn entsize(&self) -> Elf64_Xword {
+        match self {
+            ElfN::B32(shdr) => shdr.sh_entsize.into(),
+            ElfN::B64(shdr) => shdr.sh_entsize,
+        }
+    }
 }
 
 
+pub(crate) const SHF_ALLOC: u64 = 0x2;
 pub(crate) const SHF_COMPRESSED: u64 = 0x800;
 
 pub(crate) const SHN_UNDEF: u16 = 0;
 pub(crate) const SHN_LORESERVE: u16 = 0xff00;
 pub(crate) const SHN_XINDEX: u16 = 0xffff;
 
+pub(crate) const SHT_SYMTAB: Elf64_Word = 2;
+pub(crate) const SHT_STRTAB: Elf64_Word = 3;
+pub(crate) const SHT_RELA: Elf64_Word = 4;
 pub(crate) const SHT_NOTE: Elf64_Word = 7;
 pub(crate) const SHT_NOBITS: Elf64_Word = 8;
+pub(crate) const SHT_REL: Elf64_Word = 9;
+pub(crate) const SHT_DYNSYM: Elf64_Word = 11;
 
 pub(crate) const STT_OBJECT: u8 = 1;
 pub(crate) const STT_FUNC: u8 = 2;
@@ -611,7 +663,59 @@ impl ElfN_Sym<'_> {
 }
 
 
+#[derive(Clone, Copy, Debug, Default)]
+#[repr(C)]
+pub(crate) struct Elf32_Rel {
+    pub r_offset: Elf32_Addr, /* Location at which to apply the action */
+    pub r_info: Elf32_Word,   /* Relocation type and symbol index */
+}
+
+// SAFETY: `Elf32_Rel` is valid for any bit pattern.
+unsafe impl Pod for Elf32_Rel {}
+
+
+#[derive(Clone, Copy, Debug, Default)]
+#[repr(C)]
+pub(crate) struct Elf32_Rela {
+    pub r_offset: Elf32_Addr, /* Location at which to apply the action */
+    pub r_info: Elf32_Word,   /* Relocation type and symbol index */
+    pub r_addend: i32,        /* Constant addend used to compute value */
+}
+
+// SAFETY: `Elf32_Rela` is valid for any bit pattern.
+unsafe impl Pod for Elf32_Rela {}
+
+
+#[derive(Clone, Copy, Debug, Default)]
+#[repr(C)]
+pub(crate) struct Elf64_Rel {
+    pub r_offset: Elf64_Addr, /* Location at which to apply the action */
+    pub r_info: Elf64_Xword,  /* Relocation type and symbol index */
+}
+
+// SAFETY: `Elf64_Rel` is valid for any bit pattern.
+unsafe impl Pod for Elf64_Rel {}
+
+
+#[derive(Clone, Copy, Debug, Default)]
+#[repr(C)]
+pub(crate) struct Elf64_Rela {
+    pub r_offset: Elf64_Addr, /* Location at which to apply the action */
+    pub r_info: Elf64_Xword,  /* Relocation type and symbol index */
+    pub r_addend: i64,        /* Constant addend used to compute value */
+}
+
+// SAFETY: `Elf64_Rela` is valid for any bit pattern.
+unsafe impl Pod for Elf64_Rela {}
+
+
 pub(crate) const NT_GNU_BUILD_ID: Elf64_Word = 3;
+pub(crate) const NT_AUXV: Elf64_Word = 6;
+pub(crate) const NT_FILE: Elf64_Word = 0x46494c45;
+pub(crate) const NT_BOLT_BAT: Elf64_Word = 1;
+
+pub(crate) const AT_NULL: u64 = 0;
+pub(crate) const AT_ENTRY: u64 = 9;
 
 
 #[derive(Clone, Debug, Default)]
@@ -31,6 +31,8 @@ mod private {
     impl Sealed for (&[u8], nom::Err<nom::error::VerboseError<&[u8]>>) {}
     #[cfg(feature = "dwarf")]
     impl Sealed for gimli::Error {}
+    #[cfg(feature = "windows")]
+    impl Sealed for pdb::Error {}
 }
 
 /// A `str` replacement whose owned representation is a `Box<str>` and
@@ -499,6 +501,22 @@ impl Error {
         Self::with_io_error(io::ErrorKind::InvalidInput, error)
     }
 
+    #[inline]
+    pub(crate) fn with_out_of_memory<E>(error: E) -> Self
+    where
+        E: ToString,
+    {
+        Self::with_io_error(io::ErrorKind::OutOfMemory, error)
+    }
+
+    #[inline]
+    pub(crate) fn with_permission_denied<E>(error: E) -> Self
+    where
+        E: ToString,
+    {
+        Self::with_io_error(io::ErrorKind::PermissionDenied, error)
+    }
+
     #[inline]
     pub(crate) fn with_unsupported<E>(error: E) -> Self
     where
@@ -569,6 +587,16 @@ impl From<gimli::Error> for Error {
     }
 }
 
+#[cfg(feature = "windows")]
+impl From<pdb::Error> for Error {
+    fn from(other: pdb::Error) -> Self {
+        match other {
+            pdb::Error::IoError(error) => Self::from(error),
+            other => Self::with_invalid_data(other),
+        }
+    }
+}
+
 impl From<io::Error> for Error {
     fn from(other: io::Error) -> Self {
         Self {
@@ -686,6 +714,27 @@ impl ErrorExt for io::Error {
 }
 
 
+#[cfg(feature = "windows")]
+impl ErrorExt for pdb::Error {
+    type Output = Error;
+
+    fn context<C>(self, context: C) -> Self::Output
+    where
+        C: IntoCowStr,
+    {
+        Error::from(self).context(context)
+    }
+
+    fn with_context<C, F>(self, f: F) -> Self::Output
+    where
+        C: IntoCowStr,
+        F: FnOnce() -> C,
+    {
+        Error::from(self).with_context(f)
+    }
+}
+
+
 /// A trait providing conversion shortcuts for creating [`Error`]
 /// instances.
 pub trait IntoError<T>: private::Sealed
@@ -1,20 +1,182 @@
 use std::fs::File;
+use std::fs::Metadata;
+use std::fs::OpenOptions;
+#[cfg(unix)]
+use std::io;
 use std::marker::PhantomData;
+#[cfg(unix)]
+use std::os::fd::AsRawFd as _;
+#[cfg(unix)]
+use std::os::unix::fs::MetadataExt as _;
+#[cfg(unix)]
+use std::os::unix::fs::OpenOptionsExt as _;
+use std::path::Component;
 use std::path::Path;
 use std::path::PathBuf;
+use std::ptr;
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::sync::PoisonError;
+use std::sync::Weak;
+#[cfg(not(unix))]
+use std::time::UNIX_EPOCH;
 
 use crate::insert_map::InsertMap;
+use crate::log;
+use crate::normalize::buildid::read_elf_build_id_cached;
+use crate::normalize::buildid::read_elf_build_id_from_mmap;
+use crate::normalize::buildid::BuildId;
 use crate::once::OnceCell;
 use crate::util::stat;
+use crate::Error;
 use crate::ErrorExt as _;
+use crate::Mmap;
 use crate::Result;
 
 
-#[derive(Debug, Eq, Hash, PartialEq)]
+/// Check whether `path` refers to an entry below
+/// `/proc/<pid>/map_files/`.
+#[cfg(unix)]
+fn is_map_files_entry(path: &Path) -> bool {
+    let mut components = path.components();
+    matches!(
+        (
+            components.next(),
+            components.next(),
+            components.next(),
+            components.next(),
+            components.next(),
+            components.next(),
+        ),
+        (
+            Some(Component::RootDir),
+            Some(Component::Normal(proc)),
+            Some(Component::Normal(_)),
+            Some(Component::Normal(map_files)),
+            Some(Component::Normal(_)),
+            None,
+        ) if proc == "proc" && map_files == "map_files"
+    )
+}
+
+
+/// Open the file at `path` for reading, applying additional checks
+/// suitable for operating on untrusted paths.
+///
+/// Specifically, a symbolic link as the final path component is not
+/// followed and only regular files owned by the super user or the
+/// effective user are accepted.
+///
+/// The file is opened with `O_NONBLOCK` and `O_NOCTTY`, so that
+/// opening a FIFO or a device placed at `path` cannot block or
+/// acquire a controlling terminal, and is rejected before any data is
+/// read from it. `O_NONBLOCK` is cleared again once the file is known
+/// to be a regular one.
+///
+/// `/proc/<pid>/map_files/` entries are exempt from the symbolic link
+/// check: they are "magic" links provided by the kernel that always
+/// refer to the actually mapped file and cannot be redirected.
+#[cfg(unix)]
+fn open_strict(path: &Path) -> Result<File> {
+    let mut flags = libc::O_NONBLOCK | libc::O_NOCTTY;
+    if !is_map_files_entry(path) {
+        flags |= libc::O_NOFOLLOW;
+    }
+    let file = OpenOptions::new()
+        .read(true)
+        .custom_flags(flags)
+        .open(path)?;
+    let meta = file.metadata()?;
+    if !meta.file_type().is_file() {
+        return Err(Error::with_invalid_input("file is not a regular file"))
+    }
+
+    let uid = meta.uid();
+    // SAFETY: `geteuid` is always safe to call.
+    let euid = unsafe { libc::geteuid() };
+    if uid != 0 && uid != euid {
+        return Err(Error::with_permission_denied(format!(
+            "file is owned by untrusted user {uid}"
+        )))
+    }
+
+    let fd = file.as_raw_fd();
+    // SAFETY: `fd` is a valid file descriptor for the lifetime of
+    //         `file`.
+    let flags = unsafe { libc::fcntl(fd, libc::F_GETFL) };
+    if flags < 0 {
+        return Err(Error::from(io::Error::last_os_error()))
+    }
+    // SAFETY: `fd` is a valid file descriptor for the lifetime of
+    //         `file`.
+    let rc = unsafe { libc::fcntl(fd, libc::F_SETFL, flags & !libc::O_NONBLOCK) };
+    if rc < 0 {
+        return Err(Error::from(io::Error::last_os_error()))
+    }
+    Ok(file)
+}
+
+/// Open the file at `path` for reading, applying additional checks
+/// suitable for operating on untrusted paths.
+///
+/// The necessary checks are not available on this platform and so
+/// strict file access is unsupported.
+#[cfg(not(unix))]
+fn open_strict(_path: &Path) -> Result<File> {
+    Err(Error::with_unsupported(
+        "strict file access is unsupported on this platform",
+    ))
+}
+
+
+/// Open the file at `path` for reading, applying additional checks if
+/// `strict` is `true`.
+///
+/// See [`open_strict`] for the checks performed.
+pub(crate) fn open_file(path: &Path, strict: bool) -> Result<File> {
+    if strict {
+        open_strict(path)
+    } else {
+        File::open(path).map_err(Error::from)
+    }
+    .with_context(|| format!("failed to open file {}", path.display()))
+}
+
+
+/// The file meta data used for identifying cached file data.
+///
+/// When auto reloading is enabled, cached data associated with a file
+/// are considered stale and the file re-parsed once its key changes.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+#[non_exhaustive]
+pub enum CacheKey {
+    /// Identify files by device, inode number, size, and modification
+    /// time.
+    #[default]
+    FileMeta,
+    /// Identify files by device, inode number, and size, ignoring
+    /// their modification time.
+    ///
+    /// This policy is meant for read-only system paths on file systems
+    /// that exhibit modification time jitter.
+    FileMetaNoMtime,
+    /// Identify files by their ELF build ID only.
+    ///
+    /// Reading the build ID requires opening the file, but it is
+    /// considerably cheaper than re-parsing it. Files without a build
+    /// ID (including non-ELF files) are identified as per
+    /// [`CacheKey::FileMeta`].
+    BuildId,
+}
+
+
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
 // `libc` has deprecated `time_t` usage on `musl`. See
 // https://github.com/rust-lang/libc/issues/1848
 #[cfg_attr(target_env = "musl", allow(deprecated))]
-struct FileMeta {
+pub(crate) struct FileMeta {
     dev: libc::dev_t,
     inode: libc::ino_t,
     size: libc::off_t,
@@ -23,6 +185,60 @@ struct FileMeta {
     mtime_nsec: i64,
 }
 
+impl FileMeta {
+    /// Retrieve the device and inode number identifying the file
+    /// independent of its contents.
+    #[inline]
+    pub(crate) fn file_id(&self) -> (libc::dev_t, libc::ino_t) {
+        (self.dev, self.inode)
+    }
+
+    /// Strip the file modification time from the meta data.
+    fn without_mtime(self) -> Self {
+        Self {
+            mtime_sec: 0,
+            #[cfg(linux)]
+            mtime_nsec: 0,
+            ..self
+        }
+    }
+}
+
+#[cfg(unix)]
+impl From<&Metadata> for FileMeta {
+    fn from(other: &Metadata) -> Self {
+        Self {
+            dev: other.dev() as _,
+            inode: other.ino() as _,
+            size: other.size() as _,
+            mtime_sec: other.mtime() as _,
+            #[cfg(linux)]
+            mtime_nsec: other.mtime_nsec(),
+        }
+    }
+}
+
+#[cfg(not(unix))]
+impl From<&Metadata> for FileMeta {
+    fn from(other: &Metadata) -> Self {
+        // Device and inode numbers are not available on this platform,
+        // so files are identified by size and modification time only.
+        let mtime_sec = other
+            .modified()
+            .ok()
+            .and_then(|mtime| mtime.duration_since(UNIX_EPOCH).ok())
+            .map(|mtime| mtime.as_secs())
+            .unwrap_or(0);
+
+        Self {
+            dev: 0,
+            inode: 0,
+            size: other.len() as _,
+            mtime_sec: mtime_sec as _,
+        }
+    }
+}
+
 impl From<&libc::stat> for FileMeta {
     fn from(other: &libc::stat) -> Self {
         // Casts are necessary because on Android some libc types do not
@@ -39,19 +255,347 @@ impl From<&libc::stat> for FileMeta {
 }
 
 
+#[derive(Debug, Eq, Hash, PartialEq)]
+enum EntryKey {
+    /// The entry is identified by file system meta data.
+    Meta(FileMeta),
+    /// The entry is identified by the file's build ID.
+    BuildId(BuildId<'static>),
+}
+
+
 #[derive(Debug, Eq, Hash, PartialEq)]
 struct EntryMeta {
     path: PathBuf,
-    meta: Option<FileMeta>,
+    key: Option<EntryKey>,
 }
 
 impl EntryMeta {
-    /// Create a new [`EntryMeta`] object. If `stat` is [`None`] file
+    /// Create a new [`EntryMeta`] object. If `key` is [`None`] file
     /// modification times and other meta data are effectively ignored.
-    fn new(path: PathBuf, stat: Option<&libc::stat>) -> Self {
+    fn new(path: PathBuf, key: Option<EntryKey>) -> Self {
+        Self { path, key }
+    }
+}
+
+
+/// Determine the identity of the given open `file`, for later
+/// verification that a reopened file is still the same.
+///
+/// ELF files are identified by their build ID, if present, all others
+/// by their meta data.
+fn file_identity(file: &File) -> Result<EntryKey> {
+    let mmap = Mmap::map(file)?;
+    if let Ok(Some(build_id)) = read_elf_build_id_from_mmap(&mmap) {
+        return Ok(EntryKey::BuildId(build_id))
+    }
+
+    let meta = file.metadata()?;
+    Ok(EntryKey::Meta(FileMeta::from(&meta)))
+}
+
+
+/// A budget for the number of files kept open by a set of
+/// [`FileCache`] objects.
+///
+/// Once the budget is exceeded, the least recently used files are
+/// closed. They are transparently reopened by path when needed again.
+#[derive(Debug, Default)]
+pub(crate) struct FdBudget {
+    /// The maximum number of files to keep open, if any.
+    max_open: Option<usize>,
+    /// All files tracked, irrespective of whether they are open.
+    files: Mutex<Vec<Weak<CachedFile>>>,
+    /// The number of currently open files.
+    open: AtomicUsize,
+    /// The number of times a file got closed to stay within budget.
+    closed: AtomicUsize,
+    /// The number of times a previously closed file got reopened.
+    reopened: AtomicUsize,
+    /// A counter used for tracking how recently files got used.
+    tick: AtomicUsize,
+}
+
+impl FdBudget {
+    /// Create a new [`FdBudget`] allowing for at most `max_open` open
+    /// files.
+    pub(crate) fn new(max_open: Option<usize>) -> Self {
         Self {
+            max_open,
+            ..Default::default()
+        }
+    }
+
+    /// Retrieve the maximum number of files to keep open, if any.
+    #[inline]
+    pub(crate) fn max_open(&self) -> Option<usize> {
+        self.max_open
+    }
+
+    /// Retrieve the number of currently open files.
+    #[inline]
+    pub(crate) fn open(&self) -> usize {
+        self.open.load(Ordering::Relaxed)
+    }
+
+    /// Retrieve the number of times a file got closed to stay within
+    /// budget.
+    #[inline]
+    pub(crate) fn closed(&self) -> usize {
+        self.closed.load(Ordering::Relaxed)
+    }
+
+    /// Retrieve the number of times a previously closed file got
+    /// reopened.
+    #[inline]
+    pub(crate) fn reopened(&self) -> usize {
+        self.reopened.load(Ordering::Relaxed)
+    }
+
+    /// Mark `file` as used just now.
+    fn touch(&self, file: &CachedFile) {
+        let tick = self.tick.fetch_add(1, Ordering::Relaxed);
+        let () = file.last_use.store(tick, Ordering::Relaxed);
+    }
+
+    /// Start tracking `file`, which was just opened.
+    fn track(&self, file: &Arc<CachedFile>) {
+        let () = self
+            .files
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .push(Arc::downgrade(file));
+        let () = self.opened(file);
+    }
+
+    /// Account for `file` having been (re-)opened, closing the least
+    /// recently used other files if we exceed the budget as a result.
+    fn opened(&self, file: &CachedFile) {
+        let () = self.touch(file);
+        let open = self.open.fetch_add(1, Ordering::Relaxed) + 1;
+        let max_open = match self.max_open {
+            Some(max_open) if open > max_open => max_open,
+            _ => return,
+        };
+
+        // Nothing in here can panic while leaving the list in an
+        // inconsistent state, so we can safely ignore poisoning.
+        let mut files = self.files.lock().unwrap_or_else(PoisonError::into_inner);
+        let () = files.retain(|other| other.strong_count() > 0);
+
+        let mut candidates = files
+            .iter()
+            .filter_map(Weak::upgrade)
+            .filter(|other| !ptr::eq(Arc::as_ptr(other), file) && other.is_open())
+            .collect::<Vec<_>>();
+        let () = candidates.sort_by_key(|other| other.last_use.load(Ordering::Relaxed));
+
+        let excess = self.open().saturating_sub(max_open);
+        for candidate in candidates.iter().take(excess) {
+            if candidate.close() {
+                let _open = self.open.fetch_sub(1, Ordering::Relaxed);
+                let _closed = self.closed.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+    }
+}
+
+
+/// A budget for the number of files a set of [`FileCache`] objects
+/// may cache data for.
+///
+/// References to cached data may be handed out at any point, so
+/// entries are never evicted to stay within budget. Rather, caching
+/// data for yet another file fails once the budget is exhausted.
+#[derive(Debug, Default)]
+pub(crate) struct EntryBudget {
+    /// The maximum number of cache entries, if any.
+    max_entries: Option<usize>,
+    /// The number of current cache entries.
+    entries: AtomicUsize,
+}
+
+impl EntryBudget {
+    /// Create a new [`EntryBudget`] allowing for at most `max_entries`
+    /// cache entries.
+    pub(crate) fn new(max_entries: Option<usize>) -> Self {
+        Self {
+            max_entries,
+            ..Default::default()
+        }
+    }
+
+    /// Retrieve the maximum number of cache entries, if any.
+    #[inline]
+    pub(crate) fn max_entries(&self) -> Option<usize> {
+        self.max_entries
+    }
+
+    /// Retrieve the number of current cache entries.
+    #[inline]
+    pub(crate) fn entries(&self) -> usize {
+        self.entries.load(Ordering::Relaxed)
+    }
+
+    /// Reserve room for an additional cache entry.
+    fn reserve(self: &Arc<Self>) -> Result<EntrySlot> {
+        let result = self
+            .entries
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |entries| {
+                match self.max_entries {
+                    Some(max_entries) if entries >= max_entries => None,
+                    _ => Some(entries + 1),
+                }
+            });
+
+        match (result, self.max_entries) {
+            (Ok(_entries), _) => Ok(EntrySlot {
+                budget: Arc::clone(self),
+            }),
+            (Err(_entries), Some(max_entries)) => Err(Error::with_out_of_memory(format!(
+                "maximum number of cached files ({max_entries}) reached"
+            ))),
+            // SANITY: Without a limit the update always succeeds.
+            (Err(_entries), None) => unreachable!(),
+        }
+    }
+}
+
+
+/// A reservation of room for a single entry in an [`EntryBudget`],
+/// released when dropped.
+#[derive(Debug)]
+struct EntrySlot {
+    budget: Arc<EntryBudget>,
+}
+
+impl Drop for EntrySlot {
+    fn drop(&mut self) {
+        let _entries = self.budget.entries.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+
+/// The state of a file managed by a [`FileCache`].
+#[derive(Debug)]
+enum FileState {
+    /// The file is open.
+    Open(Arc<File>),
+    /// The file got closed to stay within budget. We remember its
+    /// identity, in order to verify it once reopened.
+    Closed(EntryKey),
+}
+
+
+/// A file associated with an entry of a [`FileCache`].
+///
+/// If the cache is subject to an [`FdBudget`], the file may get closed
+/// when not in use and is transparently reopened when accessed again.
+#[derive(Debug)]
+pub(crate) struct CachedFile {
+    /// The path to the file.
+    path: PathBuf,
+    /// Whether to apply additional checks when reopening the file.
+    strict: bool,
+    /// The file's state.
+    state: Mutex<FileState>,
+    /// The "time" of the last use of the file, as per the budget's
+    /// counter.
+    last_use: AtomicUsize,
+    /// The budget the file is subject to, if any.
+    budget: Option<Arc<FdBudget>>,
+}
+
+impl CachedFile {
+    fn new(path: PathBuf, strict: bool, file: File, budget: Option<Arc<FdBudget>>) -> Arc<Self> {
+        let slf = Arc::new(Self {
             path,
-            meta: stat.map(FileMeta::from),
+            strict,
+            state: Mutex::new(FileState::Open(Arc::new(file))),
+            last_use: AtomicUsize::new(0),
+            budget,
+        });
+
+        if let Some(budget) = &slf.budget {
+            let () = budget.track(&slf);
+        }
+        slf
+    }
+
+    /// Retrieve the file, reopening it if it had been closed.
+    ///
+    /// An error is reported if the file at the original path is no
+    /// longer the one that was closed.
+    pub(crate) fn get(&self) -> Result<Arc<File>> {
+        // Nothing in here can panic while leaving the state
+        // inconsistent, so we can safely ignore poisoning.
+        let mut state = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+        let file = match &*state {
+            FileState::Open(file) => {
+                let file = Arc::clone(file);
+                let () = drop(state);
+                if let Some(budget) = &self.budget {
+                    let () = budget.touch(self);
+                }
+                return Ok(file)
+            }
+            FileState::Closed(key) => {
+                let file = open_file(&self.path, self.strict)?;
+                let identity = file_identity(&file)?;
+                if identity != *key {
+                    return Err(Error::with_invalid_data(format!(
+                        "file {} changed since it was closed",
+                        self.path.display()
+                    )))
+                }
+                let file = Arc::new(file);
+                *state = FileState::Open(Arc::clone(&file));
+                file
+            }
+        };
+        let () = drop(state);
+
+        if let Some(budget) = &self.budget {
+            let _reopened = budget.reopened.fetch_add(1, Ordering::Relaxed);
+            let () = budget.opened(self);
+        }
+        Ok(file)
+    }
+
+    /// Check whether the file is currently open.
+    fn is_open(&self) -> bool {
+        let state = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+        matches!(&*state, FileState::Open(..))
+    }
+
+    /// Close the file, if it is open.
+    ///
+    /// Returns `true` if the file got closed.
+    fn close(&self) -> bool {
+        let mut state = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+        let key = match &*state {
+            FileState::Open(file) => match file_identity(file) {
+                Ok(key) => key,
+                Err(err) => {
+                    log::debug!(
+                        "failed to determine identity of {}: {err}; keeping it open",
+                        self.path.display()
+                    );
+                    return false
+                }
+            },
+            FileState::Closed(..) => return false,
+        };
+        *state = FileState::Closed(key);
+        true
+    }
+}
+
+impl Drop for CachedFile {
+    fn drop(&mut self) {
+        let state = self.state.get_mut().unwrap_or_else(PoisonError::into_inner);
+        if let (FileState::Open(..), Some(budget)) = (&*state, &self.budget) {
+            let _open = budget.open.fetch_sub(1, Ordering::Relaxed);
         }
     }
 }
@@ -59,15 +603,19 @@ impl EntryMeta {
 
 #[derive(Debug)]
 struct Entry<T> {
-    file: File,
+    file: Arc<CachedFile>,
     value: OnceCell<T>,
+    /// The room reserved for the entry in the cache's
+    /// [`EntryBudget`], if any.
+    _slot: Option<EntrySlot>,
 }
 
 impl<T> Entry<T> {
-    fn new(file: File) -> Self {
+    fn new(file: Arc<CachedFile>, slot: Option<EntrySlot>) -> Self {
         Self {
             file,
             value: OnceCell::new(),
+            _slot: slot,
         }
     }
 }
@@ -83,6 +631,14 @@ pub(crate) struct Builder<T> {
     /// This setting implies usage of debug symbols and forces the corresponding
     /// flag to `true`.
     auto_reload: bool,
+    /// Whether to apply additional checks when opening files.
+    strict: bool,
+    /// The keys to use for files below certain path prefixes.
+    keys: Vec<(PathBuf, CacheKey)>,
+    /// The budget for open files, if any.
+    budget: Option<Arc<FdBudget>>,
+    /// The budget for cache entries, if any.
+    entry_budget: Option<Arc<EntryBudget>>,
     /// Phantom data for our otherwise "unused" generic argument.
     _phantom: PhantomData<T>,
 }
@@ -94,16 +650,59 @@ impl<T> Builder<T> {
         self
     }
 
+    /// Enable/disable additional checks when opening files.
+    ///
+    /// See [`open_strict`] for details.
+    pub(crate) fn enable_strict(mut self, enable: bool) -> Self {
+        self.strict = enable;
+        self
+    }
+
+    /// Set the keys to use for identifying files below the given path
+    /// prefixes.
+    ///
+    /// Files not below any of the prefixes are identified as per
+    /// [`CacheKey::FileMeta`]. If multiple prefixes match, the longest
+    /// one wins.
+    pub(crate) fn set_keys(mut self, keys: Vec<(PathBuf, CacheKey)>) -> Self {
+        self.keys = keys;
+        self
+    }
+
+    /// Set the budget for open files that the cache is subject to.
+    ///
+    /// The budget may be shared with other caches.
+    pub(crate) fn set_fd_budget(mut self, budget: Option<Arc<FdBudget>>) -> Self {
+        self.budget = budget;
+        self
+    }
+
+    /// Set the budget for cache entries that the cache is subject to.
+    ///
+    /// The budget may be shared with other caches.
+    pub(crate) fn set_entry_budget(mut self, budget: Option<Arc<EntryBudget>>) -> Self {
+        self.entry_budget = budget;
+        self
+    }
+
     /// Create the [`FileCache`] object.
     pub(crate) fn build(self) -> FileCache<T> {
         let Builder {
             auto_reload,
+            strict,
+            keys,
+            budget,
+            entry_budget,
             _phantom: _,
         } = self;
 
         FileCache {
             cache: InsertMap::new(),
             auto_reload,
+            strict,
+            keys,
+            budget,
+            entry_budget,
         }
     }
 }
@@ -112,6 +711,10 @@ impl<T> Default for Builder<T> {
     fn default() -> Self {
         Self {
             auto_reload: true,
+            strict: false,
+            keys: Vec::new(),
+            budget: None,
+            entry_budget: None,
             _phantom: PhantomData,
         }
     }
@@ -123,7 +726,8 @@ impl<T> Default for Builder<T> {
 /// The cache transparently checks whether the file contents have
 /// changed based on file system meta data and creates and hands out a
 /// new entry if so.
-/// Note that stale/old entries are never evicted.
+/// Note that stale/old entries are never evicted automatically, but
+/// they can be removed explicitly using [`FileCache::remove`].
 #[derive(Debug)]
 pub(crate) struct FileCache<T> {
     /// The map we use for associating file meta data with user-defined
@@ -132,6 +736,14 @@ pub(crate) struct FileCache<T> {
     /// Whether or not to automatically reload files that were updated
     /// since the last open.
     auto_reload: bool,
+    /// Whether to apply additional checks when opening files.
+    strict: bool,
+    /// The keys to use for files below certain path prefixes.
+    keys: Vec<(PathBuf, CacheKey)>,
+    /// The budget for open files, if any.
+    budget: Option<Arc<FdBudget>>,
+    /// The budget for cache entries, if any.
+    entry_budget: Option<Arc<EntryBudget>>,
 }
 
 impl<T> FileCache<T> {
@@ -141,29 +753,106 @@ impl<T> FileCache<T> {
         Builder::<T>::default()
     }
 
+    /// Check whether files are automatically reloaded once updated.
+    #[inline]
+    pub(crate) fn auto_reload(&self) -> bool {
+        self.auto_reload
+    }
+
+    /// Check whether additional checks are applied when opening files.
+    #[inline]
+    pub(crate) fn strict(&self) -> bool {
+        self.strict
+    }
+
+    /// Look up the key to use for the file at the given `path`.
+    fn key_for(&self, path: &Path) -> CacheKey {
+        self.keys
+            .iter()
+            .filter(|(prefix, _key)| path.starts_with(prefix))
+            .max_by_key(|(prefix, _key)| prefix.components().count())
+            .map(|(_prefix, key)| *key)
+            .unwrap_or_default()
+    }
+
+    /// Create the [`EntryKey`] identifying the current state of the
+    /// file at `path`.
+    fn entry_key(&self, path: &Path) -> Result<EntryKey> {
+        let key = self.key_for(path);
+        if key == CacheKey::BuildId {
+            // Failure to read a build ID is not fatal: we just fall
+            // back to using file meta data and let errors surface
+            // when the file is actually opened.
+            //
+            // In strict mode the build ID has to be read from a file
+            // opened subject to the same checks as the file proper.
+            let build_id = if self.strict {
+                open_strict(path)
+                    .and_then(|file| Mmap::map(&file))
+                    .and_then(|mmap| read_elf_build_id_from_mmap(&mmap))
+            } else {
+                read_elf_build_id_cached(path)
+            };
+            if let Ok(Some(build_id)) = build_id {
+                return Ok(EntryKey::BuildId(build_id))
+            }
+        }
+
+        let stat = stat(path).with_context(|| format!("failed to stat {}", path.display()))?;
+        let meta = FileMeta::from(&stat);
+        let meta = match key {
+            CacheKey::FileMetaNoMtime => meta.without_mtime(),
+            CacheKey::FileMeta | CacheKey::BuildId => meta,
+        };
+        Ok(EntryKey::Meta(meta))
+    }
+
     /// Retrieve an entry for the file at the given `path`.
-    pub(crate) fn entry(&self, path: &Path) -> Result<(&File, &OnceCell<T>)> {
-        let stat = if self.auto_reload {
-            let stat = stat(path).with_context(|| format!("failed to stat {}", path.display()))?;
-            Some(stat)
+    pub(crate) fn entry(&self, path: &Path) -> Result<(&CachedFile, &OnceCell<T>)> {
+        let key = if self.auto_reload {
+            Some(self.entry_key(path)?)
         } else {
             None
         };
 
-        let meta = EntryMeta::new(path.to_path_buf(), stat.as_ref());
+        let meta = EntryMeta::new(path.to_path_buf(), key);
         let entry = self.cache.get_or_try_insert(meta, || {
             // We may end up associating this file with a potentially
             // outdated `stat` (which could have changed), but the only
             // consequence is that we'd create a new entry again in the
             // future. On the bright side, we save one `stat` call.
-            let file = File::open(path)
-                .with_context(|| format!("failed to open file {}", path.display()))?;
-            let entry = Entry::new(file);
+            let slot = self
+                .entry_budget
+                .as_ref()
+                .map(EntryBudget::reserve)
+                .transpose()?;
+            let file = open_file(path, self.strict)?;
+            let file = CachedFile::new(path.to_path_buf(), self.strict, file, self.budget.clone());
+            let entry = Entry::new(file, slot);
             Ok(entry)
         })?;
 
         Ok((&entry.file, &entry.value))
     }
+
+    /// Remove all entries for the file at the given `path`, including
+    /// stale ones.
+    ///
+    /// This method returns `true` if at least one entry was removed.
+    pub(crate) fn remove(&mut self, path: &Path) -> bool {
+        let mut removed = false;
+        let () = self.cache.retain(|meta, _entry| {
+            let keep = meta.path != path;
+            removed |= !keep;
+            keep
+        });
+        removed
+    }
+
+    /// Remove all entries.
+    pub(crate) fn clear(&mut self) {
+        let () = self.cache.retain(|_meta, _entry| false);
+    }
 }
 
 impl<T> Default for FileCache<T> {
@@ -177,15 +866,20 @@ impl<T> Default for FileCache<T> {
 mod tests {
     use super::*;
 
+    use std::fs::copy;
+    use std::fs::remove_file;
     use std::io::Read as _;
     use std::io::Write as _;
     use std::thread::sleep;
     use std::time::Duration;
+    use std::time::SystemTime;
 
     use tempfile::tempdir;
     use tempfile::tempfile;
     use tempfile::NamedTempFile;
 
+    use crate::ErrorKind;
+
 
     /// Exercise the `Debug` representation of various types.
     #[test]
@@ -194,7 +888,8 @@ mod tests {
         assert_ne!(format!("{cache:?}"), "");
 
         let tmpfile = tempfile().unwrap();
-        let entry = Entry::<usize>::new(tmpfile);
+        let file = CachedFile::new(PathBuf::new(), false, tmpfile, None);
+        let entry = Entry::<usize>::new(file, None);
         assert_ne!(format!("{entry:?}"), "");
     }
 
@@ -217,6 +912,27 @@ mod tests {
         }
     }
 
+    /// Check that we can remove entries from the cache.
+    #[test]
+    fn removal() {
+        let mut cache = FileCache::<usize>::default();
+        let tmpfile1 = NamedTempFile::new().unwrap();
+        let tmpfile2 = NamedTempFile::new().unwrap();
+
+        let (_file, cell) = cache.entry(tmpfile1.path()).unwrap();
+        let () = cell.set(1).unwrap();
+        let (_file, cell) = cache.entry(tmpfile2.path()).unwrap();
+        let () = cell.set(2).unwrap();
+
+        assert!(cache.remove(tmpfile1.path()));
+        assert!(!cache.remove(tmpfile1.path()));
+
+        let (_file, cell) = cache.entry(tmpfile1.path()).unwrap();
+        assert_eq!(cell.get(), None);
+        let (_file, cell) = cache.entry(tmpfile2.path()).unwrap();
+        assert_eq!(cell.get(), Some(&2));
+    }
+
     /// Check that our `FileCache` does not represent symbolic links
     /// pointing to the same file as equal entries.
     #[cfg(linux)]
@@ -237,7 +953,84 @@ mod tests {
         let (file2, cell) = cache.entry(&link).unwrap();
         assert_eq!(cell.get(), None);
 
-        assert_ne!(file1.as_raw_fd(), file2.as_raw_fd());
+        assert_ne!(
+            file1.get().unwrap().as_raw_fd(),
+            file2.get().unwrap().as_raw_fd()
+        );
+    }
+
+    /// Check that strict mode refuses to follow symbolic links and to
+    /// open non-regular files.
+    #[cfg(linux)]
+    #[test]
+    fn strict_open() {
+        use std::os::unix::fs::symlink;
+
+        let tmpfile = NamedTempFile::new().unwrap();
+        let tmpdir = tempdir().unwrap();
+        let link = tmpdir.path().join("symlink");
+        let () = symlink(tmpfile.path(), &link).unwrap();
+
+        let cache = FileCache::<usize>::builder().enable_strict(true).build();
+        let _entry = cache.entry(tmpfile.path()).unwrap();
+
+        let err = cache.entry(&link).unwrap_err();
+        assert!(format!("{err:#}").contains("symbolic links"), "{err:#}");
+
+        let err = cache.entry(tmpdir.path()).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidInput);
+    }
+
+    /// Check that strict mode rejects FIFOs without blocking on them.
+    #[cfg(unix)]
+    #[test]
+    fn strict_open_fifo() {
+        use std::os::fd::AsRawFd as _;
+
+        use crate::util::mkfifo;
+
+        let tmpdir = tempdir().unwrap();
+        let fifo = tmpdir.path().join("fifo");
+        let () = mkfifo(&fifo).unwrap();
+
+        let cache = FileCache::<usize>::builder().enable_strict(true).build();
+        let err = cache.entry(&fifo).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidInput);
+
+        // Build ID based keys require reading the file before opening
+        // it proper, which has to be subject to the same checks.
+        let cache = FileCache::<usize>::builder()
+            .enable_strict(true)
+            .set_keys(vec![(tmpdir.path().to_path_buf(), CacheKey::BuildId)])
+            .build();
+        let err = cache.entry(&fifo).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidInput);
+
+        // Regular files should end up being opened in blocking mode.
+        let tmpfile = NamedTempFile::new().unwrap();
+        let (file, _cell) = cache.entry(tmpfile.path()).unwrap();
+        let fd = file.get().unwrap().as_raw_fd();
+        // SAFETY: `fd` is a valid file descriptor.
+        let flags = unsafe { libc::fcntl(fd, libc::F_GETFL) };
+        assert!(flags >= 0);
+        assert_eq!(flags & libc::O_NONBLOCK, 0);
+    }
+
+    /// Check that we correctly identify `/proc/<pid>/map_files/`
+    /// entries.
+    #[cfg(unix)]
+    #[test]
+    fn map_files_entry_detection() {
+        assert!(is_map_files_entry(Path::new(
+            "/proc/1234/map_files/7f0000-7f1000"
+        )));
+        assert!(is_map_files_entry(Path::new("/proc/self/map_files/1-2")));
+        assert!(!is_map_files_entry(Path::new("/proc/self/map_files")));
+        assert!(!is_map_files_entry(Path::new(
+            "/proc/self/root/usr/lib/libc.so"
+        )));
+        assert!(!is_map_files_entry(Path::new("/tmp/proc/1/map_files/1-2")));
+        assert!(!is_map_files_entry(Path::new("proc/1/map_files/1-2")));
     }
 
     /// Make sure that a changed file purges the cache entry .
@@ -253,7 +1046,7 @@ mod tests {
                 assert_eq!(cell.get(), None);
 
                 let () = cell.set(42).unwrap();
-                file.metadata().unwrap().modified().unwrap()
+                file.get().unwrap().metadata().unwrap().modified().unwrap()
             };
 
             // Sleep briefly to make sure that file times will end up being
@@ -269,14 +1062,15 @@ mod tests {
             }
 
             {
-                let (mut file, entry) = cache.entry(&path).unwrap();
+                let (file, entry) = cache.entry(&path).unwrap();
+                let file = file.get().unwrap();
 
                 if auto_reload {
                     assert_eq!(entry.get(), None);
                     assert_ne!(file.metadata().unwrap().modified().unwrap(), modified);
 
                     let mut content = Vec::new();
-                    let _count = file.read_to_end(&mut content);
+                    let _count = (&*file).read_to_end(&mut content);
                     assert_eq!(content, b"foobar");
                 } else {
                     assert_eq!(entry.get(), Some(&42));
@@ -289,4 +1083,160 @@ mod tests {
             let () = test(auto_reload);
         }
     }
+
+    /// Check that we pick the key of the longest matching path prefix.
+    #[test]
+    fn key_selection() {
+        let cache = FileCache::<()>::builder()
+            .set_keys(vec![
+                (PathBuf::from("/usr"), CacheKey::FileMetaNoMtime),
+                (PathBuf::from("/usr/lib/debug"), CacheKey::BuildId),
+            ])
+            .build();
+
+        assert_eq!(cache.key_for(Path::new("/tmp/foo")), CacheKey::FileMeta);
+        assert_eq!(cache.key_for(Path::new("/usrfoo")), CacheKey::FileMeta);
+        assert_eq!(
+            cache.key_for(Path::new("/usr/lib/libc.so")),
+            CacheKey::FileMetaNoMtime
+        );
+        assert_eq!(
+            cache.key_for(Path::new("/usr/lib/debug/libc.so.debug")),
+            CacheKey::BuildId
+        );
+    }
+
+    /// Make sure that modification time changes do not purge cache
+    /// entries when the corresponding key is used.
+    #[test]
+    fn outdated_mtime_ignored() {
+        fn test(key: CacheKey) {
+            let tmpfile = NamedTempFile::new().unwrap();
+            let cache = FileCache::<usize>::builder()
+                .set_keys(vec![(tmpfile.path().to_path_buf(), key)])
+                .build();
+
+            {
+                let (_file, cell) = cache.entry(tmpfile.path()).unwrap();
+                let () = cell.set(42).unwrap();
+            }
+
+            let modified = SystemTime::now() - Duration::from_secs(60);
+            let () = tmpfile.as_file().set_modified(modified).unwrap();
+
+            {
+                let (_file, cell) = cache.entry(tmpfile.path()).unwrap();
+                if key == CacheKey::FileMetaNoMtime {
+                    assert_eq!(cell.get(), Some(&42));
+                } else {
+                    assert_eq!(cell.get(), None);
+                }
+            }
+        }
+
+        for key in [CacheKey::FileMeta, CacheKey::FileMetaNoMtime] {
+            let () = test(key);
+        }
+    }
+
+    /// Check that files are identified by build ID when asked to.
+    #[test]
+    fn build_id_key() {
+        let elf = Path::new(&env!("CARGO_MANIFEST_DIR"))
+            .join("data")
+            .join("libtest-so.so");
+        let tmpdir = tempdir().unwrap();
+        let path = tmpdir.path().join("libtest-so.so");
+        let _count = copy(&elf, &path).unwrap();
+
+        let cache = FileCache::<usize>::builder()
+            .set_keys(vec![(tmpdir.path().to_path_buf(), CacheKey::BuildId)])
+            .build();
+        {
+            let (_file, cell) = cache.entry(&path).unwrap();
+            let () = cell.set(42).unwrap();
+        }
+
+        // Replace the file with a copy, which will have different
+        // file system meta data but the same build ID.
+        let () = remove_file(&path).unwrap();
+        let _count = copy(&elf, &path).unwrap();
+
+        let (_file, cell) = cache.entry(&path).unwrap();
+        assert_eq!(cell.get(), Some(&42));
+    }
+
+    /// Check that files are closed once the budget for open files is
+    /// exceeded and reopened on demand.
+    #[test]
+    fn fd_budget() {
+        let budget = Arc::new(FdBudget::new(Some(1)));
+        let cache = FileCache::<usize>::builder()
+            .set_fd_budget(Some(Arc::clone(&budget)))
+            .build();
+        let mut tmpfile1 = NamedTempFile::new().unwrap();
+        let () = tmpfile1.write_all(b"foo").unwrap();
+        let mut tmpfile2 = NamedTempFile::new().unwrap();
+        let () = tmpfile2.write_all(b"bar").unwrap();
+
+        let (file1, _cell) = cache.entry(tmpfile1.path()).unwrap();
+        assert_eq!(budget.open(), 1);
+        let (file2, _cell) = cache.entry(tmpfile2.path()).unwrap();
+        assert_eq!(budget.open(), 1);
+        assert_eq!(budget.closed(), 1);
+        assert!(!file1.is_open());
+        assert!(file2.is_open());
+
+        // Accessing the first file reopens it, closing the second one.
+        let mut content = Vec::new();
+        let _count = (&*file1.get().unwrap()).read_to_end(&mut content);
+        assert_eq!(content, b"foo");
+        assert_eq!(budget.open(), 1);
+        assert_eq!(budget.closed(), 2);
+        assert_eq!(budget.reopened(), 1);
+        assert!(!file2.is_open());
+
+        // A file that got changed in the meantime should be refused.
+        let () = tmpfile2.write_all(b"baz").unwrap();
+        let err = file2.get().unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidData);
+
+        let () = drop(cache);
+        assert_eq!(budget.open(), 0);
+    }
+
+    /// Check that the number of cache entries is limited as per the
+    /// entry budget and that removed entries free up room.
+    #[test]
+    fn entry_budget() {
+        let budget = Arc::new(EntryBudget::new(Some(1)));
+        let mut cache = FileCache::<usize>::builder()
+            .set_entry_budget(Some(Arc::clone(&budget)))
+            .build();
+        let tmpfile1 = NamedTempFile::new().unwrap();
+        let tmpfile2 = NamedTempFile::new().unwrap();
+
+        let _entry = cache.entry(tmpfile1.path()).unwrap();
+        assert_eq!(budget.entries(), 1);
+        // Looking up an existing entry does not count again.
+        let _entry = cache.entry(tmpfile1.path()).unwrap();
+        assert_eq!(budget.entries(), 1);
+
+        let err = cache.entry(tmpfile2.path()).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::OutOfMemory);
+        assert_eq!(budget.entries(), 1);
+
+        assert!(cache.remove(tmpfile1.path()));
+        assert_eq!(budget.entries(), 0);
+        let _entry = cache.entry(tmpfile2.path()).unwrap();
+        assert_eq!(budget.entries(), 1);
+
+        let () = cache.clear();
+        assert_eq!(budget.entries(), 0);
+
+        // A failure to open the file should not leak any room.
+        let err = cache.entry(Path::new("/does-not-exist")).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::NotFound);
+        assert_eq!(budget.entries(), 0);
+    }
 }
@@ -0,0 +1,980 @@
+//! Functionality for creating Gsym data from ELF files and their DWARF
+//! debug information.
+//!
+//! The created data follow the layout described in the documentation
+//! of the parser module, that is, a header is followed by the address
+//! table, the address data offset table, the file table, the string
+//! table, and finally the address data of all functions.
+
+use std::collections::HashMap;
+use std::fs;
+use std::mem::size_of;
+use std::ops::ControlFlow;
+use std::ops::Range;
+use std::path::Path;
+#[cfg(feature = "dwarf")]
+use std::path::PathBuf;
+use std::rc::Rc;
+
+#[cfg(feature = "dwarf")]
+use crate::dwarf::DwarfResolver;
+#[cfg(feature = "dwarf")]
+use crate::dwarf::InlinedRange;
+use crate::elf::ElfParser;
+#[cfg(feature = "dwarf")]
+use crate::elf::DEFAULT_DEBUG_DIRS;
+use crate::inspect::FindAddrOpts;
+#[cfg(feature = "dwarf")]
+use crate::inspect::Inspect as _;
+#[cfg(feature = "dwarf")]
+use crate::symbolize::CodeInfo;
+use crate::util::align_up_usize;
+#[cfg(feature = "dwarf")]
+use crate::util::path_to_bytes;
+use crate::Addr;
+use crate::Error;
+use crate::ErrorExt as _;
+use crate::Result;
+use crate::SymType;
+
+use super::linetab::ADVANCE_LINE;
+use super::linetab::ADVANCE_PC;
+use super::linetab::END_SEQUENCE;
+use super::linetab::FIRST_SPECIAL;
+use super::linetab::SET_FILE;
+use super::types::GSYM_MAGIC;
+use super::types::GSYM_VERSION;
+use super::types::INFO_TYPE_END_OF_LIST;
+use super::types::INFO_TYPE_INLINE_INFO;
+use super::types::INFO_TYPE_LINE_TABLE_INFO;
+
+
+/// The maximum size of the UUID stored in the header, in bytes.
+const MAX_UUID_LEN: usize = 20;
+/// The size of the Gsym header, in bytes.
+const HEADER_LEN: usize = 28 + MAX_UUID_LEN;
+/// The smallest line delta we attempt to encode using special
+/// opcodes.
+const MIN_LINE_DELTA: i64 = -4;
+/// The largest line delta we attempt to encode using special opcodes.
+const MAX_LINE_DELTA: i64 = 10;
+
+
+fn write_uleb128(buf: &mut Vec<u8>, mut value: u64) {
+    loop {
+        let byte = (value & 0x7f) as u8;
+        value >>= 7;
+        if value == 0 {
+            let () = buf.push(byte);
+            break
+        }
+        let () = buf.push(byte | 0x80);
+    }
+}
+
+fn write_sleb128(buf: &mut Vec<u8>, mut value: i64) {
+    loop {
+        let byte = (value & 0x7f) as u8;
+        value >>= 7;
+        let done = (value == 0 && byte & 0x40 == 0) || (value == -1 && byte & 0x40 != 0);
+        if done {
+            let () = buf.push(byte);
+            break
+        }
+        let () = buf.push(byte | 0x80);
+    }
+}
+
+fn write_u32(buf: &mut Vec<u8>, value: u32) {
+    let () = buf.extend_from_slice(&value.to_ne_bytes());
+}
+
+
+/// A function as stored in Gsym data.
+#[derive(Debug)]
+struct Func {
+    /// The function's start address.
+    addr: Addr,
+    /// The function's size, which may be zero if unknown.
+    size: u64,
+    /// The function's name.
+    name: String,
+    /// Whether the function is described by debug information.
+    debug_info: bool,
+}
+
+
+/// A line table row as stored in Gsym data.
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Line {
+    /// The address of the first instruction covered by the row.
+    addr: Addr,
+    /// The index of the source file in the file table.
+    file: u32,
+    /// The line number.
+    line: u32,
+}
+
+
+/// A call of an inlined function, as stored in Gsym data.
+#[derive(Debug)]
+struct InlinedCall {
+    /// The address ranges covered by the call, in ascending order.
+    ranges: Vec<Range<Addr>>,
+    /// The offset of the function's name in the string table.
+    name: u32,
+    /// The index of the file containing the call in the file table.
+    call_file: u32,
+    /// The line of the call.
+    call_line: u32,
+    /// Calls inlined into this one.
+    children: Vec<InlinedCall>,
+}
+
+impl InlinedCall {
+    /// Check whether the call covers `addr`.
+    fn contains(&self, addr: Addr) -> bool {
+        self.ranges.iter().any(|range| range.contains(&addr))
+    }
+
+    /// Encode the call and all its children, with address ranges
+    /// being relative to `base_addr`.
+    fn encode(&self, buf: &mut Vec<u8>, base_addr: Addr) {
+        let () = write_uleb128(buf, self.ranges.len() as u64);
+        for range in &self.ranges {
+            let () = write_uleb128(buf, range.start - base_addr);
+            let () = write_uleb128(buf, range.end - range.start);
+        }
+        let () = buf.push(u8::from(!self.children.is_empty()));
+        let () = write_u32(buf, self.name);
+        let () = write_uleb128(buf, self.call_file.into());
+        let () = write_uleb128(buf, self.call_line.into());
+
+        if let Some(range) = self.ranges.first() {
+            if !self.children.is_empty() {
+                for child in &self.children {
+                    let () = child.encode(buf, range.start);
+                }
+                // A call without address ranges terminates the list of
+                // children.
+                let () = write_uleb128(buf, 0);
+            }
+        }
+    }
+}
+
+
+/// A string table, deduplicating the strings added to it.
+#[derive(Debug)]
+struct StrTab {
+    data: Vec<u8>,
+    offsets: HashMap<Vec<u8>, u32>,
+}
+
+impl StrTab {
+    fn new() -> Self {
+        Self {
+            // By convention, offset zero refers to the empty string.
+            data: vec![0],
+            offsets: HashMap::from([(Vec::new(), 0)]),
+        }
+    }
+
+    /// Add a string to the table, returning its offset.
+    fn insert(&mut self, s: &[u8]) -> Result<u32> {
+        if let Some(offset) = self.offsets.get(s) {
+            return Ok(*offset)
+        }
+
+        let offset = u32::try_from(self.data.len())
+            .map_err(|_err| Error::with_invalid_input("Gsym string table is too large"))?;
+        let () = self.data.extend_from_slice(s);
+        let () = self.data.push(0);
+        let _prev = self.offsets.insert(s.to_vec(), offset);
+        Ok(offset)
+    }
+}
+
+
+/// The tables shared by the address data of all functions.
+#[derive(Debug)]
+struct Tables {
+    strtab: StrTab,
+    /// The file table, in the form of directory and file name string
+    /// table offsets.
+    files: Vec<(u32, u32)>,
+    /// A map from file table entries to their index.
+    file_idxs: HashMap<(u32, u32), u32>,
+}
+
+impl Tables {
+    fn new() -> Self {
+        Self {
+            strtab: StrTab::new(),
+            // By convention, index zero refers to an invalid file.
+            files: vec![(0, 0)],
+            file_idxs: HashMap::from([((0, 0), 0)]),
+        }
+    }
+
+    /// Add the file referenced by `code_info` to the file table,
+    /// returning its index.
+    #[cfg(feature = "dwarf")]
+    fn insert_file(&mut self, code_info: &CodeInfo<'_>) -> Result<u32> {
+        let dir = if let Some(dir) = &code_info.dir {
+            self.strtab.insert(path_to_bytes(dir)?)?
+        } else {
+            0
+        };
+        let file = self
+            .strtab
+            .insert(path_to_bytes(Path::new(&code_info.file))?)?;
+
+        if let Some(idx) = self.file_idxs.get(&(dir, file)) {
+            return Ok(*idx)
+        }
+
+        let idx = u32::try_from(self.files.len())
+            .map_err(|_err| Error::with_invalid_input("Gsym file table is too large"))?;
+        let () = self.files.push((dir, file));
+        let _prev = self.file_idxs.insert((dir, file), idx);
+        Ok(idx)
+    }
+}
+
+
+/// Gather the functions contained in the ELF file as well as in its
+/// debug information, sorted by address.
+fn funcs(
+    parser: &ElfParser,
+    #[cfg(feature = "dwarf")] dwarf: Option<&DwarfResolver>,
+) -> Result<Vec<Func>> {
+    let opts = FindAddrOpts {
+        sym_type: SymType::Function,
+        ..Default::default()
+    };
+    let mut funcs = Vec::new();
+    let () = parser.for_each(&opts, &mut |sym| {
+        let () = funcs.push(Func {
+            addr: sym.addr,
+            size: sym.size.unwrap_or(0) as u64,
+            name: sym.name.to_string(),
+            debug_info: false,
+        });
+        ControlFlow::Continue(())
+    })?;
+
+    // Debug information may describe functions not present in the
+    // symbol table, e.g., if the latter got stripped.
+    #[cfg(feature = "dwarf")]
+    if let Some(dwarf) = dwarf {
+        let () = dwarf.for_each(&opts, &mut |sym| {
+            // Functions that got inlined everywhere do not have an
+            // address.
+            if sym.size.unwrap_or(0) != 0 {
+                let () = funcs.push(Func {
+                    addr: sym.addr,
+                    size: sym.size.unwrap_or(0) as u64,
+                    name: sym.name.to_string(),
+                    debug_info: true,
+                });
+            }
+            ControlFlow::Continue(())
+        })?;
+    }
+
+    // Symbol tables commonly contain aliases for the same function and
+    // the same functions are described by symbols and debug
+    // information. We keep only one of them, preferring ones with size
+    // information and names from debug information.
+    let () = funcs.sort_by(|func1, func2| {
+        func1
+            .addr
+            .cmp(&func2.addr)
+            .then_with(|| func2.size.cmp(&func1.size))
+            .then_with(|| func2.debug_info.cmp(&func1.debug_info))
+            .then_with(|| func1.name.cmp(&func2.name))
+    });
+    let () = funcs.dedup_by_key(|func| func.addr);
+    Ok(funcs)
+}
+
+/// Gather the line table rows contained in the DWARF debug information,
+/// sorted by address.
+///
+/// Rows without location information mark the end of a sequence.
+#[cfg(feature = "dwarf")]
+fn lines(dwarf: &DwarfResolver, tables: &mut Tables) -> Result<Vec<(Addr, Option<(u32, u32)>)>> {
+    let mut overall_result = Ok(());
+    let mut rows = Vec::new();
+    let () = dwarf.for_each_line_row(0..Addr::MAX, &mut |row| {
+        let location = match &row.code_info {
+            Some(code_info) if !row.end_sequence => match tables.insert_file(code_info) {
+                Ok(file) => Some((file, code_info.line.unwrap_or(0))),
+                Err(err) => {
+                    overall_result = Err(err);
+                    return ControlFlow::Break(())
+                }
+            },
+            _ => None,
+        };
+        let () = rows.push((row.addr, location));
+        ControlFlow::Continue(())
+    })?;
+    let () = overall_result?;
+
+    // Sequences may be adjacent to each other, in which case we want
+    // the start of the next sequence to take precedence over the end
+    // of the previous one.
+    let () = rows.sort_by_key(|(addr, location)| (*addr, location.is_some()));
+    Ok(rows)
+}
+
+/// Retrieve the line table rows covering `func`.
+#[cfg(feature = "dwarf")]
+fn func_lines(func: &Func, rows: &[(Addr, Option<(u32, u32)>)]) -> Vec<Line> {
+    let end = func.addr.saturating_add(func.size);
+    let start = rows.partition_point(|(addr, _location)| *addr <= func.addr);
+    let count = rows[start..].partition_point(|(addr, _location)| *addr < end);
+
+    let mut lines = Vec::<Line>::new();
+    // The row covering the function's start may precede it, which is
+    // why we start with the last row at or before the start address.
+    for (addr, location) in rows[start.saturating_sub(1)..start + count].iter() {
+        let (file, line) = if let Some(location) = location {
+            *location
+        } else {
+            // We only report rows of a sequence up to its end.
+            if !lines.is_empty() {
+                break
+            }
+            continue
+        };
+        let line = Line {
+            addr: (*addr).max(func.addr),
+            file,
+            line,
+        };
+
+        match lines.last_mut() {
+            // Later rows at the same address supersede earlier ones.
+            Some(last) if last.addr == line.addr => *last = line,
+            // Rows not changing the location are redundant.
+            Some(last) if last.file == line.file && last.line == line.line => (),
+            _ => lines.push(line),
+        }
+    }
+    lines
+}
+
+/// Encode the line table `lines` of a function starting at
+/// `func_addr`.
+fn encode_lines(buf: &mut Vec<u8>, func_addr: Addr, lines: &[Line]) {
+    let first_line = if let Some(line) = lines.first() {
+        line.line
+    } else {
+        return
+    };
+
+    let (min_delta, max_delta) = lines
+        .windows(2)
+        .map(|lines| i64::from(lines[1].line) - i64::from(lines[0].line))
+        .fold((0, 0), |(min, max), delta| (delta.min(min), delta.max(max)));
+    let min_delta = min_delta.max(MIN_LINE_DELTA);
+    let max_delta = max_delta.min(MAX_LINE_DELTA);
+    let range = max_delta - min_delta + 1;
+
+    let () = write_sleb128(buf, min_delta);
+    let () = write_sleb128(buf, max_delta);
+    let () = write_uleb128(buf, first_line.into());
+
+    // The initial state of the line table state machine.
+    let mut prev = Line {
+        addr: func_addr,
+        file: 1,
+        line: first_line,
+    };
+    for line in lines {
+        if line.file != prev.file {
+            let () = buf.push(SET_FILE);
+            let () = write_uleb128(buf, line.file.into());
+        }
+
+        let addr_delta = line.addr - prev.addr;
+        let line_delta = i64::from(line.line) - i64::from(prev.line);
+        let special = (min_delta..=max_delta)
+            .contains(&line_delta)
+            .then(|| {
+                i64::try_from(addr_delta)
+                    .ok()
+                    .and_then(|addr_delta| addr_delta.checked_mul(range))
+                    .and_then(|op| {
+                        op.checked_add(line_delta - min_delta + i64::from(FIRST_SPECIAL))
+                    })
+            })
+            .flatten()
+            .and_then(|op| u8::try_from(op).ok());
+
+        if let Some(op) = special {
+            let () = buf.push(op);
+        } else {
+            if line_delta != 0 {
+                let () = buf.push(ADVANCE_LINE);
+                let () = write_sleb128(buf, line_delta);
+            }
+            let () = buf.push(ADVANCE_PC);
+            let () = write_uleb128(buf, addr_delta);
+        }
+        prev = *line;
+    }
+    let () = buf.push(END_SEQUENCE);
+}
+
+/// Record the inlined call range `range` of `func` in `calls`, keyed by
+/// call identifier.
+#[cfg(feature = "dwarf")]
+fn add_inlined_range(
+    func: &Func,
+    range: &InlinedRange<'_>,
+    calls: &mut HashMap<usize, (usize, InlinedCall)>,
+    tables: &mut Tables,
+) -> Result<()> {
+    let InlinedRange {
+        range,
+        depth,
+        call,
+        name,
+        code_info,
+    } = range;
+
+    // Ranges not contained in the function cannot be represented.
+    let end = func.addr.saturating_add(func.size);
+    if range.start < func.addr || range.end > end || range.is_empty() {
+        return Ok(())
+    }
+
+    if let Some((_depth, call)) = calls.get_mut(call) {
+        let () = call.ranges.push(range.clone());
+        return Ok(())
+    }
+
+    let (call_file, call_line) = if let Some(code_info) = code_info {
+        (tables.insert_file(code_info)?, code_info.line.unwrap_or(0))
+    } else {
+        (0, 0)
+    };
+    let inlined = InlinedCall {
+        ranges: vec![range.clone()],
+        name: tables.strtab.insert(name.as_bytes())?,
+        call_file,
+        call_line,
+        children: Vec::new(),
+    };
+    let _prev = calls.insert(*call, (*depth, inlined));
+    Ok(())
+}
+
+/// Build the tree of calls inlined into `func`.
+#[cfg(feature = "dwarf")]
+fn func_inlined_calls(
+    func: &Func,
+    dwarf: &DwarfResolver,
+    tables: &mut Tables,
+) -> Result<Vec<InlinedCall>> {
+    let mut overall_result = Ok(());
+    // All calls along with their depth, keyed by their identifier.
+    let mut calls = HashMap::<usize, (usize, InlinedCall)>::new();
+    let () = dwarf.for_each_inlined_range(func.addr, &mut |range| {
+        if overall_result.is_ok() {
+            overall_result = add_inlined_range(func, range, &mut calls, tables);
+        }
+    })?;
+    let () = overall_result?;
+
+    // Attach calls to their parents, starting with the most deeply
+    // nested ones.
+    let mut calls = calls.into_values().collect::<Vec<_>>();
+    let () = calls
+        .iter_mut()
+        .for_each(|(_depth, call)| call.ranges.sort_by_key(|range| range.start));
+    let () = calls.sort_by_key(|(depth, call)| (*depth, call.ranges[0].start));
+
+    while let Some((depth, call)) = calls.pop() {
+        if depth == 0 {
+            let () = calls.push((depth, call));
+            break
+        }
+
+        let start = call.ranges[0].start;
+        let parent = calls.iter_mut().rev().find(|(parent_depth, parent)| {
+            *parent_depth + 1 == depth && parent.contains(start) && parent.ranges[0].start <= start
+        });
+        if let Some((_depth, parent)) = parent {
+            let () = parent.children.push(call);
+        }
+    }
+
+    let mut calls = calls
+        .into_iter()
+        .map(|(_depth, call)| call)
+        .collect::<Vec<_>>();
+    let () = sort_calls(&mut calls);
+    Ok(calls)
+}
+
+/// Sort a list of calls along with their children by address.
+#[cfg(feature = "dwarf")]
+fn sort_calls(calls: &mut [InlinedCall]) {
+    let () = calls.sort_by_key(|call| call.ranges[0].start);
+    let () = calls
+        .iter_mut()
+        .for_each(|call| sort_calls(&mut call.children));
+}
+
+
+/// A builder for creating Gsym data from an ELF file.
+///
+/// Symbols are read from the ELF file's symbol tables as well as its
+/// DWARF debug information, if present. The latter also provides
+/// line table and inlined function information. The created data can
+/// subsequently be used for symbolization via
+/// [`Source::Gsym`][crate::symbolize::source::Source::Gsym].
+///
+/// By default all information is included.
+#[derive(Clone, Debug)]
+pub struct Builder {
+    /// Whether to include source code location information.
+    code_info: bool,
+    /// Whether to include inlined function information.
+    inlined_fns: bool,
+    /// List of additional directories in which split debug information
+    /// is looked for.
+    #[cfg(feature = "dwarf")]
+    debug_dirs: Vec<PathBuf>,
+}
+
+impl Builder {
+    /// Create a new [`Builder`] with default settings.
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Enable/disable inclusion of source code location information
+    /// (line numbers, file names etc.).
+    pub fn enable_code_info(mut self, enable: bool) -> Self {
+        self.code_info = enable;
+        self
+    }
+
+    /// Enable/disable inclusion of inlined function information.
+    ///
+    /// This option only has an effect if `code_info` is `true`.
+    pub fn enable_inlined_fns(mut self, enable: bool) -> Self {
+        self.inlined_fns = enable;
+        self
+    }
+
+    /// Set debug directories to search for split debug information.
+    ///
+    /// These directories will be consulted (in given order) when
+    /// resolving debug links in the ELF file. By default
+    /// `/usr/lib/debug` and `/lib/debug/` will be searched.
+    ///
+    /// A value of `None` reverts to using the default set of
+    /// directories.
+    #[cfg(feature = "dwarf")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "dwarf")))]
+    pub fn set_debug_dirs<D, P>(mut self, debug_dirs: Option<D>) -> Self
+    where
+        D: IntoIterator<Item = P>,
+        P: AsRef<Path>,
+    {
+        if let Some(debug_dirs) = debug_dirs {
+            self.debug_dirs = debug_dirs
+                .into_iter()
+                .map(|p| p.as_ref().to_path_buf())
+                .collect();
+        } else {
+            self.debug_dirs = DEFAULT_DEBUG_DIRS
+                .iter()
+                .map(PathBuf::from)
+                .collect::<Vec<_>>();
+        }
+        self
+    }
+
+    /// Create the address data of `func`.
+    fn func_data(
+        &self,
+        func: &Func,
+        tables: &mut Tables,
+        #[cfg(feature = "dwarf")] dwarf: Option<&DwarfResolver>,
+        #[cfg(feature = "dwarf")] rows: &[(Addr, Option<(u32, u32)>)],
+    ) -> Result<Vec<u8>> {
+        let mut data = Vec::new();
+        let () = write_u32(&mut data, u32::try_from(func.size).unwrap_or(u32::MAX));
+        let () = write_u32(&mut data, tables.strtab.insert(func.name.as_bytes())?);
+
+        #[cfg(feature = "dwarf")]
+        if let Some(dwarf) = dwarf.filter(|_dwarf| self.code_info && func.size != 0) {
+            let lines = func_lines(func, rows);
+            if !lines.is_empty() {
+                let mut buf = Vec::new();
+                let () = encode_lines(&mut buf, func.addr, &lines);
+                let () = write_u32(&mut data, INFO_TYPE_LINE_TABLE_INFO);
+                let () = write_u32(&mut data, buf.len() as u32);
+                let () = data.extend_from_slice(&buf);
+            }
+
+            if self.inlined_fns {
+                let children = func_inlined_calls(func, dwarf, tables)?;
+                if !children.is_empty() {
+                    let range = func.addr..func.addr + func.size;
+                    let call = InlinedCall {
+                        ranges: vec![range],
+                        name: tables.strtab.insert(func.name.as_bytes())?,
+                        call_file: 0,
+                        call_line: 0,
+                        children,
+                    };
+                    let mut buf = Vec::new();
+                    let () = call.encode(&mut buf, func.addr);
+                    let () = write_u32(&mut data, INFO_TYPE_INLINE_INFO);
+                    let () = write_u32(&mut data, buf.len() as u32);
+                    let () = data.extend_from_slice(&buf);
+                }
+            }
+        }
+
+        let () = write_u32(&mut data, INFO_TYPE_END_OF_LIST);
+        let () = write_u32(&mut data, 0);
+        Ok(data)
+    }
+
+    fn build_impl(&self, path: &Path) -> Result<Vec<u8>> {
+        let parser = Rc::new(ElfParser::open(path)?);
+        let mut uuid = parser
+            .build_id()?
+            .map(|build_id| build_id.to_vec())
+            .unwrap_or_default();
+        let () = uuid.truncate(MAX_UUID_LEN);
+
+        #[cfg(feature = "dwarf")]
+        let dwarf = if self.code_info {
+            Some(DwarfResolver::from_parser(
+                Rc::clone(&parser),
+                &self.debug_dirs,
+                false,
+                false,
+            )?)
+        } else {
+            None
+        };
+
+        let funcs = funcs(
+            &parser,
+            #[cfg(feature = "dwarf")]
+            dwarf.as_ref(),
+        )?;
+        let mut tables = Tables::new();
+        #[cfg(feature = "dwarf")]
+        let rows = if let Some(dwarf) = &dwarf {
+            lines(dwarf, &mut tables)?
+        } else {
+            Vec::new()
+        };
+
+        let mut addr_data = Vec::with_capacity(funcs.len());
+        for func in &funcs {
+            let data = self.func_data(
+                func,
+                &mut tables,
+                #[cfg(feature = "dwarf")]
+                dwarf.as_ref(),
+                #[cfg(feature = "dwarf")]
+                &rows,
+            )?;
+            let () = addr_data.push(data);
+        }
+
+        let base_addr = funcs.first().map(|func| func.addr).unwrap_or(0);
+        let max_offset = funcs.last().map(|func| func.addr).unwrap_or(0) - base_addr;
+        let addr_off_size = if max_offset <= u8::MAX.into() {
+            1
+        } else if max_offset <= u16::MAX.into() {
+            2
+        } else if max_offset <= u32::MAX.into() {
+            4
+        } else {
+            8
+        };
+        let num_addrs = u32::try_from(funcs.len())
+            .map_err(|_err| Error::with_invalid_input("too many functions for Gsym file"))?;
+
+        let addr_tab_len = funcs.len() * addr_off_size;
+        let addr_data_off_tab_off = align_up_usize(HEADER_LEN + addr_tab_len, size_of::<u32>());
+        let file_tab_off = addr_data_off_tab_off + funcs.len() * size_of::<u32>();
+        let strtab_off =
+            file_tab_off + size_of::<u32>() + tables.files.len() * 2 * size_of::<u32>();
+        let strtab = &tables.strtab.data;
+        let addr_data_off = align_up_usize(strtab_off + strtab.len(), size_of::<u32>());
+
+        let too_large = || Error::with_invalid_input("Gsym file is too large");
+        let mut gsym =
+            Vec::with_capacity(addr_data_off + addr_data.iter().map(Vec::len).sum::<usize>());
+        let () = write_u32(&mut gsym, GSYM_MAGIC);
+        let () = gsym.extend_from_slice(&GSYM_VERSION.to_ne_bytes());
+        let () = gsym.push(addr_off_size as u8);
+        let () = gsym.push(uuid.len() as u8);
+        let () = gsym.extend_from_slice(&base_addr.to_ne_bytes());
+        let () = write_u32(&mut gsym, num_addrs);
+        let () = write_u32(
+            &mut gsym,
+            u32::try_from(strtab_off).map_err(|_err| too_large())?,
+        );
+        let () = write_u32(
+            &mut gsym,
+            u32::try_from(strtab.len()).map_err(|_err| too_large())?,
+        );
+        let () = gsym.extend_from_slice(&uuid);
+        let () = gsym.resize(HEADER_LEN, 0);
+
+        for func in &funcs {
+            let offset = func.addr - base_addr;
+            let () = match addr_off_size {
+                1 => gsym.push(offset as u8),
+                2 => gsym.extend_from_slice(&(offset as u16).to_ne_bytes()),
+                4 => gsym.extend_from_slice(&(offset as u32).to_ne_bytes()),
+                _ => gsym.extend_from_slice(&offset.to_ne_bytes()),
+            };
+        }
+        let () = gsym.resize(addr_data_off_tab_off, 0);
+
+        let mut offset = addr_data_off;
+        for data in &addr_data {
+            let () = write_u32(
+                &mut gsym,
+                u32::try_from(offset).map_err(|_err| too_large())?,
+            );
+            offset = align_up_usize(offset + data.len(), size_of::<u32>());
+        }
+
+        let () = write_u32(&mut gsym, tables.files.len() as u32);
+        for (dir, file) in &tables.files {
+            let () = write_u32(&mut gsym, *dir);
+            let () = write_u32(&mut gsym, *file);
+        }
+        debug_assert_eq!(gsym.len(), strtab_off);
+        let () = gsym.extend_from_slice(strtab);
+
+        for data in &addr_data {
+            let () = gsym.resize(align_up_usize(gsym.len(), size_of::<u32>()), 0);
+            let () = gsym.extend_from_slice(data);
+        }
+        Ok(gsym)
+    }
+
+    /// Create Gsym data for the ELF file at `path`.
+    pub fn build(&self, path: &Path) -> Result<Vec<u8>> {
+        self.build_impl(path)
+            .with_context(|| format!("failed to create Gsym data for `{}`", path.display()))
+    }
+
+    /// Create Gsym data for the ELF file at `path` and write them to
+    /// the file at `dst`.
+    pub fn write(&self, path: &Path, dst: &Path) -> Result<()> {
+        let gsym = self.build(path)?;
+        let () = fs::write(dst, gsym)
+            .map_err(Error::from)
+            .with_context(|| format!("failed to write Gsym file `{}`", dst.display()))?;
+        Ok(())
+    }
+}
+
+impl Default for Builder {
+    fn default() -> Self {
+        Self {
+            code_info: true,
+            inlined_fns: true,
+            #[cfg(feature = "dwarf")]
+            debug_dirs: DEFAULT_DEBUG_DIRS
+                .iter()
+                .map(PathBuf::from)
+                .collect::<Vec<_>>(),
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::env;
+    use std::ffi::OsStr;
+
+    use tempfile::NamedTempFile;
+    use test_log::test;
+
+    use crate::symbolize::FindSymOpts;
+    use crate::symbolize::Symbolize as _;
+    use crate::util::ReadRaw as _;
+
+    use super::super::linetab::run_op;
+    use super::super::linetab::LineTableHeader;
+    use super::super::linetab::LineTableRow;
+    use super::super::linetab::RunResult;
+    use super::super::GsymResolver;
+
+
+    /// Check that our LEB128 encoding can be decoded again.
+    #[test]
+    fn leb128_round_trip() {
+        for value in [0, 1, 0x7f, 0x80, 0x3fff, 0x4000, u64::MAX] {
+            let mut buf = Vec::new();
+            let () = write_uleb128(&mut buf, value);
+            assert_eq!(buf.as_slice().read_u64_leb128(), Some(value));
+        }
+
+        for value in [0, 1, -1, 63, 64, -64, -65, i64::MIN, i64::MAX] {
+            let mut buf = Vec::new();
+            let () = write_sleb128(&mut buf, value);
+            assert_eq!(buf.as_slice().read_i64_leb128(), Some(value));
+        }
+    }
+
+    /// Check that encoded line tables decode to the original rows.
+    #[test]
+    fn line_table_round_trip() {
+        let lines = [
+            Line {
+                addr: 0x1000,
+                file: 1,
+                line: 10,
+            },
+            Line {
+                addr: 0x1004,
+                file: 1,
+                line: 11,
+            },
+            Line {
+                addr: 0x1008,
+                file: 1,
+                line: 8,
+            },
+            Line {
+                addr: 0x1800,
+                file: 1,
+                line: 500,
+            },
+            Line {
+                addr: 0x1810,
+                file: 2,
+                line: 3,
+            },
+            Line {
+                addr: 0x1810,
+                file: 2,
+                line: 4,
+            },
+        ];
+
+        let mut buf = Vec::new();
+        let () = encode_lines(&mut buf, 0x1000, &lines);
+
+        let mut data = buf.as_slice();
+        let header = LineTableHeader::parse(&mut data).unwrap();
+        let mut row = LineTableRow::from_header(&header, 0x1000);
+        let mut decoded = Vec::new();
+        loop {
+            match run_op(&mut row, &header, &mut data).unwrap() {
+                RunResult::Ok => (),
+                RunResult::NewRow => decoded.push(Line {
+                    addr: row.addr,
+                    file: row.file_idx,
+                    line: row.file_line,
+                }),
+                RunResult::End => break,
+            }
+        }
+        assert_eq!(decoded, lines);
+        assert!(data.is_empty());
+    }
+
+    /// Check that we can create Gsym data for an ELF file and use them
+    /// for symbolization, with line and inlined function information
+    /// matching that of the reference Gsym file.
+    #[test]
+    fn build_and_symbolize() {
+        let path = Path::new(&env!("CARGO_MANIFEST_DIR"))
+            .join("data")
+            .join("test-stable-addrs.bin");
+        let gsym = Builder::new().build(&path).unwrap();
+        let resolver = GsymResolver::with_data(&gsym, false).unwrap();
+
+        let sym = resolver
+            .find_sym(0x2000200, &FindSymOpts::CodeInfoAndInlined)
+            .unwrap()
+            .unwrap();
+        assert_eq!(sym.name, "factorial");
+        assert_eq!(sym.addr, 0x2000200);
+        assert!(sym.inlined.is_empty());
+        let info = sym.code_info.unwrap();
+        assert_eq!(info.line, Some(10));
+        assert_eq!(info.file, OsStr::new("test-stable-addrs.c"));
+
+        let sym = resolver
+            .find_sym(0x200030a, &FindSymOpts::CodeInfoAndInlined)
+            .unwrap()
+            .unwrap();
+        assert_eq!(sym.name, "factorial_inline_test");
+        assert_eq!(sym.inlined.len(), 2);
+        let info = sym.code_info.unwrap();
+        assert_eq!(info.line, Some(34));
+        assert_eq!(info.file, OsStr::new("test-stable-addrs.c"));
+
+        let name = &sym.inlined[0].name;
+        assert_eq!(*name, "factorial_inline_wrapper");
+        let frame = sym.inlined[0].code_info.as_ref().unwrap();
+        assert_eq!(frame.file, OsStr::new("test-stable-addrs.c"));
+        assert_eq!(frame.line, Some(28));
+
+        let name = &sym.inlined[1].name;
+        assert_eq!(*name, "factorial_2nd_layer_inline_wrapper");
+        let frame = sym.inlined[1].code_info.as_ref().unwrap();
+        assert_eq!(frame.file, OsStr::new("test-stable-addrs.c"));
+        assert_eq!(frame.line, Some(23));
+
+        let sym = resolver
+            .find_sym(0x200030a, &FindSymOpts::CodeInfo)
+            .unwrap()
+            .unwrap();
+        assert_eq!(sym.name, "factorial_inline_test");
+        let info = sym.code_info.unwrap();
+        assert_eq!(info.line, Some(23));
+    }
+
+    /// Check that we can exclude source code information from created
+    /// Gsym data.
+    #[test]
+    fn build_without_code_info() {
+        let path = Path::new(&env!("CARGO_MANIFEST_DIR"))
+            .join("data")
+            .join("test-stable-addrs.bin");
+        let file = NamedTempFile::new().unwrap();
+        let () = Builder::new()
+            .enable_code_info(false)
+            .write(&path, file.path())
+            .unwrap();
+        let resolver = GsymResolver::open(file.path()).unwrap();
+
+        let sym = resolver
+            .find_sym(0x2000200, &FindSymOpts::CodeInfoAndInlined)
+            .unwrap()
+            .unwrap();
+        assert_eq!(sym.name, "factorial");
+        assert_eq!(sym.code_info, None);
+        assert!(sym.inlined.is_empty());
+    }
+}
@@ -4,15 +4,15 @@ use crate::util::ReadRaw as _;
 use crate::Addr;
 
 /// End of the line table
-const END_SEQUENCE: u8 = 0x00;
+pub(super) const END_SEQUENCE: u8 = 0x00;
 /// Set [`LineTableRow.file_idx`], don't push a row.
-const SET_FILE: u8 = 0x01;
+pub(super) const SET_FILE: u8 = 0x01;
 /// Increment [`LineTableRow.address`], and push a row.
-const ADVANCE_PC: u8 = 0x02;
+pub(super) const ADVANCE_PC: u8 = 0x02;
 /// Set [`LineTableRow.file_line`], don't push a row.
-const ADVANCE_LINE: u8 = 0x03;
+pub(super) const ADVANCE_LINE: u8 = 0x03;
 /// All special opcodes push a row.
-const FIRST_SPECIAL: u8 = 0x04;
+pub(super) const FIRST_SPECIAL: u8 = 0x04;
 
 
 #[derive(Debug)]
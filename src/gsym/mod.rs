@@ -1,7 +1,17 @@
+//! Functionality for working with Gsym data.
+//!
+//! Gsym is a compact symbol file format, designed for fast address
+//! lookup, which also carries source code location and inlined
+//! function information. Gsym data can be used for symbolization via
+//! [`Source::Gsym`][crate::symbolize::source::Source::Gsym] and
+//! created from ELF files using [`Builder`].
+
+mod builder;
 mod inline;
 mod linetab;
 mod parser;
 mod resolver;
 mod types;
 
+pub use builder::Builder;
 pub(crate) use resolver::GsymResolver;
@@ -80,19 +80,23 @@ impl GsymContext<'_> {
     /// # Arguments
     ///
     /// * `data` - is the content of a standalone GSYM.
+    /// * `forward_compat` - whether to accept versions newer than
+    ///   [`GSYM_VERSION`], assuming that they only extend the format.
     ///
     /// Returns a GsymContext, which includes the Header and other important
     /// tables.
-    pub(crate) fn parse_header(data: &[u8]) -> Result<GsymContext> {
-        fn parse_header_impl(mut data: &[u8]) -> Option<Result<GsymContext>> {
+    pub(crate) fn parse_header(data: &[u8], forward_compat: bool) -> Result<GsymContext> {
+        fn parse_header_impl(mut data: &[u8], forward_compat: bool) -> Option<Result<GsymContext>> {
             let head = data;
             let magic = data.read_u32()?;
             if magic != GSYM_MAGIC {
                 return Some(Err(Error::with_invalid_data("invalid magic number")))
             }
             let version = data.read_u16()?;
-            if version != GSYM_VERSION {
-                return Some(Err(Error::with_invalid_data("unknown version number")))
+            if version != GSYM_VERSION && !(forward_compat && version > GSYM_VERSION) {
+                return Some(Err(Error::with_invalid_data(format!(
+                    "unknown version number: {version}"
+                ))))
             }
 
             let addr_off_size = data.read_u8()?;
@@ -135,7 +139,7 @@ impl GsymContext<'_> {
             Some(Ok(slf))
         }
 
-        parse_header_impl(data)
+        parse_header_impl(data, forward_compat)
             .ok_or_invalid_data(|| "GSYM data does not contain sufficient bytes")?
     }
 
@@ -166,6 +170,12 @@ impl GsymContext<'_> {
         }
     }
 
+    /// Retrieve the number of entries in the Address Table.
+    #[inline]
+    pub(crate) fn num_addrs(&self) -> usize {
+        self.header.num_addrs as usize
+    }
+
     /// Get the address of an entry in the Address Table.
     pub(crate) fn addr_at(&self, idx: usize) -> Option<Addr> {
         let addr_off_size = self.header.addr_off_size as usize;
@@ -260,7 +270,7 @@ mod tests {
         let mut data = vec![];
 
         gsym_fo.read_to_end(&mut data).unwrap();
-        let ctx = GsymContext::parse_header(&data).unwrap();
+        let ctx = GsymContext::parse_header(&data, false).unwrap();
 
         let idx = ctx.find_addr(0x2000000).unwrap();
         let addrinfo = ctx.addr_info(idx).unwrap();
@@ -271,6 +281,35 @@ mod tests {
         assert_eq!(ctx.get_str(addrinfo.name as usize).unwrap(), "factorial");
     }
 
+    /// Check that Gsym data of a newer version is only accepted in
+    /// forward compatibility mode.
+    #[test]
+    fn parse_newer_version() {
+        let test_gsym = Path::new(&env!("CARGO_MANIFEST_DIR"))
+            .join("data")
+            .join("test-stable-addrs.gsym");
+        let mut data = vec![];
+        let _cnt = File::open(test_gsym)
+            .unwrap()
+            .read_to_end(&mut data)
+            .unwrap();
+
+        // The version immediately follows the four byte magic.
+        let () = data[4..6].copy_from_slice(&(GSYM_VERSION + 1).to_ne_bytes());
+
+        let err = GsymContext::parse_header(&data, false).err().unwrap();
+        assert!(err.to_string().contains("unknown version number"), "{err}");
+
+        let ctx = GsymContext::parse_header(&data, true).unwrap();
+        let idx = ctx.find_addr(0x2000000).unwrap();
+        let addrinfo = ctx.addr_info(idx).unwrap();
+        assert_eq!(ctx.get_str(addrinfo.name as usize).unwrap(), "main");
+
+        // Older versions are never supported.
+        let () = data[4..6].copy_from_slice(&(GSYM_VERSION - 1).to_ne_bytes());
+        assert!(GsymContext::parse_header(&data, true).is_err());
+    }
+
     #[test]
     fn test_find_addr() {
         let test_gsym = Path::new(&env!("CARGO_MANIFEST_DIR"))
@@ -317,7 +356,7 @@ mod tests {
 
             for addr in 0..(TEST_SIZE * 2) {
                 let addr_tab = addr_tab.clone();
-                let mut ctx = GsymContext::parse_header(&data).unwrap();
+                let mut ctx = GsymContext::parse_header(&data, false).unwrap();
                 ctx.header.num_addrs = TEST_SIZE as u32;
                 ctx.header.addr_off_size = 4;
                 ctx.header.base_address = 0;
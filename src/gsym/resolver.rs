@@ -1,16 +1,27 @@
 use std::borrow::Cow;
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
 use std::ffi::OsStr;
 use std::fmt::Debug;
 use std::fmt::Formatter;
 use std::fmt::Result as FmtResult;
 use std::fs::File;
 use std::mem;
+use std::mem::size_of;
 use std::mem::swap;
+use std::ops::ControlFlow;
+use std::ops::Range;
 use std::path::Path;
 use std::path::PathBuf;
+use std::slice;
 
+use crate::inspect::FindAddrOpts;
+use crate::inspect::ForEachFn;
+use crate::inspect::Inspect;
+use crate::inspect::SymInfo;
 use crate::log::warn;
 use crate::mmap::Mmap;
+use crate::once::OnceCell;
 use crate::symbolize::CodeInfo;
 use crate::symbolize::FindSymOpts;
 use crate::symbolize::InlinedFn;
@@ -18,9 +29,14 @@ use crate::symbolize::Reason;
 use crate::symbolize::ResolvedSym;
 use crate::symbolize::SrcLang;
 use crate::symbolize::Symbolize;
+use crate::util::align_up_usize;
+use crate::util::find_lowest_match_by_key;
 use crate::Addr;
+use crate::Error;
+use crate::ErrorExt as _;
 use crate::IntoError as _;
 use crate::Result;
+use crate::SymType;
 
 use super::inline::InlineInfo;
 use super::linetab::run_op;
@@ -34,10 +50,63 @@ use super::types::INFO_TYPE_INLINE_INFO;
 use super::types::INFO_TYPE_LINE_TABLE_INFO;
 
 
+/// The magic bytes at the start of a zstd frame.
+const ZSTD_MAGIC: [u8; 4] = [0x28, 0xb5, 0x2f, 0xfd];
+
+
+#[cfg(feature = "zstd")]
+fn decompress_zstd(data: &[u8]) -> Result<Vec<u8>> {
+    use zstd::stream::decode_all;
+    decode_all(data).context("zstd decompression failed")
+}
+
+#[cfg(not(feature = "zstd"))]
+fn decompress_zstd(_data: &[u8]) -> Result<Vec<u8>> {
+    Err(Error::with_unsupported(
+        "Gsym file is zstd compressed but zstd compression support is not enabled",
+    ))
+}
+
+
+/// Heap allocated data with an alignment suitable for Gsym parsing.
+///
+/// Gsym data is laid out assuming that the start of the file is
+/// sufficiently aligned, which memory mappings are by virtue of being
+/// page aligned, but a plain `Vec<u8>` is not.
+struct AlignedData {
+    buf: Box<[u64]>,
+    len: usize,
+}
+
+impl AlignedData {
+    fn new(data: &[u8]) -> Self {
+        let mut buf = vec![0u64; align_up_usize(data.len(), size_of::<u64>()) / size_of::<u64>()]
+            .into_boxed_slice();
+        // SAFETY: `buf` is at least `data.len()` bytes in size and any
+        //         bit pattern is a valid `u64`.
+        let () = unsafe { slice::from_raw_parts_mut(buf.as_mut_ptr().cast::<u8>(), data.len()) }
+            .copy_from_slice(data);
+
+        Self {
+            buf,
+            len: data.len(),
+        }
+    }
+
+    fn as_slice(&self) -> &[u8] {
+        // SAFETY: `buf` is at least `len` bytes in size and fully
+        //         initialized.
+        unsafe { slice::from_raw_parts(self.buf.as_ptr().cast::<u8>(), self.len) }
+    }
+}
+
+
 #[allow(dead_code)]
 enum Data<'dat> {
     Mmap(Mmap),
     Slice(&'dat [u8]),
+    /// Decompressed Gsym data.
+    Owned(AlignedData),
 }
 
 
@@ -48,6 +117,11 @@ pub(crate) struct GsymResolver<'dat> {
     //         end up with dangling references.
     _data: Data<'dat>,
     ctx: GsymContext<'dat>,
+    /// Whether we operate in forward compatibility mode, in which data
+    /// of newer Gsym versions is accepted.
+    forward_compat: bool,
+    /// An index of address table entries sorted by symbol name.
+    by_name_idx: OnceCell<Box<[usize]>>,
 }
 
 impl GsymResolver<'static> {
@@ -58,16 +132,22 @@ impl GsymResolver<'static> {
     {
         let path = path.as_ref();
         let mmap = Mmap::builder().open(path)?;
-        Self::from_mmap(path.to_path_buf(), mmap)
+        Self::from_mmap(path.to_path_buf(), mmap, false)
     }
 
-    pub(crate) fn from_file(path: PathBuf, file: &File) -> Result<Self> {
+    pub(crate) fn from_file(path: PathBuf, file: &File, forward_compat: bool) -> Result<Self> {
         let mmap = Mmap::map(file)?;
-        Self::from_mmap(path, mmap)
+        Self::from_mmap(path, mmap, forward_compat)
     }
 
-    fn from_mmap(path: PathBuf, mmap: Mmap) -> Result<Self> {
-        let ctx = GsymContext::parse_header(&mmap)?;
+    fn from_mmap(path: PathBuf, mmap: Mmap, forward_compat: bool) -> Result<Self> {
+        if mmap.starts_with(&ZSTD_MAGIC) {
+            let data = decompress_zstd(&mmap)
+                .with_context(|| format!("failed to decompress Gsym file `{}`", path.display()))?;
+            return Self::from_owned(path, AlignedData::new(&data), forward_compat)
+        }
+
+        let ctx = GsymContext::parse_header(&mmap, forward_compat)?;
         let slf = Self {
             file_name: Some(path),
             // SAFETY: We own the underlying `Mmap` object and never hand out
@@ -75,6 +155,25 @@ impl GsymResolver<'static> {
             //         to transmute the lifetime.
             ctx: unsafe { mem::transmute::<GsymContext<'_>, GsymContext<'static>>(ctx) },
             _data: Data::Mmap(mmap),
+            forward_compat,
+            by_name_idx: OnceCell::new(),
+        };
+
+        Ok(slf)
+    }
+
+    fn from_owned(path: PathBuf, data: AlignedData, forward_compat: bool) -> Result<Self> {
+        let ctx = GsymContext::parse_header(data.as_slice(), forward_compat)?;
+        let slf = Self {
+            file_name: Some(path),
+            // SAFETY: We own the underlying heap allocation, which does
+            //         not move when `data` does, and never hand out any
+            //         'static references to its data. So it is safe for
+            //         us to transmute the lifetime.
+            ctx: unsafe { mem::transmute::<GsymContext<'_>, GsymContext<'static>>(ctx) },
+            _data: Data::Owned(data),
+            forward_compat,
+            by_name_idx: OnceCell::new(),
         };
 
         Ok(slf)
@@ -83,12 +182,14 @@ impl GsymResolver<'static> {
 
 impl<'dat> GsymResolver<'dat> {
     /// Create a `GsymResolver` that works on the provided "raw" Gsym data.
-    pub(crate) fn with_data(data: &'dat [u8]) -> Result<Self> {
-        let ctx = GsymContext::parse_header(data)?;
+    pub(crate) fn with_data(data: &'dat [u8], forward_compat: bool) -> Result<Self> {
+        let ctx = GsymContext::parse_header(data, forward_compat)?;
         let slf = Self {
             file_name: None,
             ctx,
             _data: Data::Slice(data),
+            forward_compat,
+            by_name_idx: OnceCell::new(),
         };
 
         Ok(slf)
@@ -203,6 +304,9 @@ impl Symbolize for GsymResolver<'_> {
                 lang,
                 code_info: None,
                 inlined: Box::new([]),
+                inlined_truncated: false,
+                section: None,
+                module: None,
             };
             let () = self.fill_code_info(&mut sym, addr, opts, sym_addr, &info)?;
 
@@ -214,6 +318,76 @@ impl Symbolize for GsymResolver<'_> {
 }
 
 impl GsymResolver<'_> {
+    /// Retrieve the name of the symbol at the given index in the
+    /// address table, if valid.
+    fn name_at(&self, idx: usize) -> Option<&OsStr> {
+        let info = self.ctx.addr_info(idx)?;
+        self.ctx.get_str(info.name as usize)
+    }
+
+    /// Retrieve the index of address table entries sorted by symbol
+    /// name, creating it if necessary.
+    fn by_name_idx(&self) -> &[usize] {
+        self.by_name_idx.get_or_init(|| {
+            let mut by_name_idx = (0..self.ctx.num_addrs()).collect::<Vec<_>>();
+            // Entries are sorted by address already, so sorting stably
+            // orders symbols of the same name by address.
+            let () = by_name_idx.sort_by_cached_key(|idx| self.name_at(*idx));
+            by_name_idx.into_boxed_slice()
+        })
+    }
+
+    /// Find the source code location of the start of the function at
+    /// `sym_addr`.
+    fn start_code_info(&self, sym_addr: Addr, info: &AddrInfo) -> Result<Option<CodeInfo<'_>>> {
+        for addr_ent in parse_address_data(info.data) {
+            if addr_ent.typ == INFO_TYPE_LINE_TABLE_INFO {
+                if let Some(row) = self.parse_line_tab_info(addr_ent.data, sym_addr, sym_addr)? {
+                    let code_info =
+                        self.query_frame_code_info(row.file_idx, Some(row.file_line))?;
+                    return Ok(Some(code_info))
+                }
+            }
+        }
+        Ok(None)
+    }
+
+    /// Retrieve the symbol at the given index in the address table.
+    fn sym_info(&self, idx: usize, opts: &FindAddrOpts) -> Result<SymInfo<'_>> {
+        let addr = self
+            .ctx
+            .addr_at(idx)
+            .ok_or_invalid_data(|| format!("failed to read address table entry {idx}"))?;
+        let info = self
+            .ctx
+            .addr_info(idx)
+            .ok_or_invalid_data(|| format!("failed to read address information entry {idx}"))?;
+        let name = self
+            .ctx
+            .get_str(info.name as usize)
+            .and_then(|s| s.to_str())
+            .ok_or_invalid_data(|| {
+                format!("failed to read string table entry at offset {}", info.name)
+            })?;
+
+        let code_info = if opts.code_info {
+            self.start_code_info(addr, &info)?
+        } else {
+            None
+        };
+
+        let sym = SymInfo {
+            name: Cow::Borrowed(name),
+            addr,
+            size: Some(usize::try_from(info.size).unwrap_or(usize::MAX)),
+            sym_type: SymType::Function,
+            file_offset: None,
+            obj_file_name: self.file_name.as_deref().map(Cow::Borrowed),
+            code_info,
+        };
+        Ok(sym)
+    }
+
     fn fill_code_info<'slf>(
         &'slf self,
         sym: &mut ResolvedSym<'slf>,
@@ -243,7 +417,12 @@ impl GsymResolver<'_> {
                     }
                 }
                 typ => {
-                    warn!("encountered unknown info type: {typ}; ignoring...");
+                    // Newer format versions may legitimately contain
+                    // info types we do not know about, so only warn
+                    // about them if they are unexpected.
+                    if !self.forward_compat {
+                        warn!("encountered unknown info type: {typ}; ignoring...");
+                    }
                     continue
                 }
             }
@@ -319,6 +498,122 @@ impl GsymResolver<'_> {
     }
 }
 
+impl Inspect for GsymResolver<'_> {
+    fn find_addr<'slf>(&'slf self, name: &str, opts: &FindAddrOpts) -> Result<Vec<SymInfo<'slf>>> {
+        if let SymType::Variable = opts.sym_type {
+            return Err(Error::with_unsupported(
+                "Gsym logic does not currently support variable lookup",
+            ))
+        }
+
+        // Gsym data are sorted by address and do not contain any name
+        // based index, so we create one on demand.
+        let by_name_idx = self.by_name_idx();
+        let name = Some(OsStr::new(name));
+        let idx = if let Some(idx) =
+            find_lowest_match_by_key(by_name_idx, &name, |idx| self.name_at(*idx))
+        {
+            idx
+        } else {
+            return Ok(Vec::new())
+        };
+
+        let syms = by_name_idx[idx..]
+            .iter()
+            .take_while(|idx| self.name_at(**idx) == name)
+            .map(|idx| self.sym_info(*idx, opts))
+            .collect::<Result<Vec<_>>>()?;
+        Ok(syms)
+    }
+
+    fn for_each(&self, opts: &FindAddrOpts, f: &mut ForEachFn<'_>) -> Result<()> {
+        if let SymType::Variable = opts.sym_type {
+            return Err(Error::with_unsupported(
+                "Gsym logic does not currently support variable iteration",
+            ))
+        }
+
+        for idx in 0..self.ctx.num_addrs() {
+            let sym = self.sym_info(idx, opts)?;
+            if let ControlFlow::Break(()) = f(&sym) {
+                return Ok(())
+            }
+        }
+        Ok(())
+    }
+
+    fn find_func_ranges(&self, addr: Addr) -> Result<Vec<Range<Addr>>> {
+        // Gsym does not describe functions consisting of multiple
+        // ranges, so all we can report is the symbol's own range.
+        let ranges = match self.find_sym(addr, &FindSymOpts::Basic)? {
+            Ok(sym) => {
+                let range = sym.addr..sym.addr + sym.size.unwrap_or(0) as Addr;
+                vec![range]
+            }
+            Err(_reason) => Vec::new(),
+        };
+        Ok(ranges)
+    }
+
+    fn find_line_addrs(&self, file: &Path, line: u32) -> Result<Vec<Addr>> {
+        // Cache of whether a given file table entry refers to `file`.
+        let mut file_matches = HashMap::<u32, bool>::new();
+        let mut addrs = Vec::new();
+
+        for idx in 0..self.ctx.num_addrs() {
+            let sym_addr = self
+                .ctx
+                .addr_at(idx)
+                .ok_or_invalid_data(|| format!("failed to read address table entry {idx}"))?;
+            let info = self
+                .ctx
+                .addr_info(idx)
+                .ok_or_invalid_data(|| format!("failed to read address information entry {idx}"))?;
+
+            for addr_ent in parse_address_data(info.data) {
+                if addr_ent.typ != INFO_TYPE_LINE_TABLE_INFO {
+                    continue
+                }
+
+                let mut data = addr_ent.data;
+                let lntab_hdr = LineTableHeader::parse(&mut data)
+                    .ok_or_invalid_data(|| "failed to parse line table header")?;
+                let mut lntab_row = LineTableRow::from_header(&lntab_hdr, sym_addr);
+                // Whether we already reported an address for the block
+                // of rows for `line` that we are currently in.
+                let mut reported = false;
+                while !data.is_empty() {
+                    match run_op(&mut lntab_row, &lntab_hdr, &mut data) {
+                        Some(RunResult::Ok) => {}
+                        Some(RunResult::NewRow) => {
+                            let matches = lntab_row.file_line == line
+                                && match file_matches.entry(lntab_row.file_idx) {
+                                    Entry::Occupied(entry) => *entry.get(),
+                                    Entry::Vacant(entry) => {
+                                        let code_info =
+                                            self.query_frame_code_info(lntab_row.file_idx, None)?;
+                                        *entry.insert(code_info.to_path().ends_with(file))
+                                    }
+                                };
+                            if !matches {
+                                reported = false;
+                            } else if !reported {
+                                let () = addrs.push(lntab_row.addr);
+                                reported = true;
+                            }
+                        }
+                        Some(RunResult::End) | None => break,
+                    }
+                }
+            }
+        }
+
+        let () = addrs.sort_unstable();
+        let () = addrs.dedup();
+        Ok(addrs)
+    }
+}
+
 impl Debug for GsymResolver<'_> {
     fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
         let path = self
@@ -339,6 +634,8 @@ mod tests {
 
     use test_log::test;
 
+    use crate::ErrorKind;
+
 
     /// Exercise the `Debug` representation of various types.
     #[test]
@@ -361,10 +658,144 @@ mod tests {
             .join("test-stable-addrs.gsym");
         let data = read_file(test_gsym).unwrap();
 
-        let resolver = GsymResolver::with_data(&data).unwrap();
+        let resolver = GsymResolver::with_data(&data, false).unwrap();
         assert_eq!(resolver.file_name, None);
     }
 
+    /// Check that we can look up and iterate over symbols in a Gsym
+    /// file.
+    #[test]
+    fn inspect_symbols() {
+        let test_gsym = Path::new(&env!("CARGO_MANIFEST_DIR"))
+            .join("data")
+            .join("test-stable-addrs.gsym");
+        let resolver = GsymResolver::open(&test_gsym).unwrap();
+        // The name index is only created on demand.
+        assert!(resolver.by_name_idx.get().is_none());
+
+        let opts = FindAddrOpts::default();
+        let syms = resolver.find_addr("factorial", &opts).unwrap();
+        assert_eq!(syms.len(), 1);
+        let sym = &syms[0];
+        assert_eq!(sym.name, "factorial");
+        assert_eq!(sym.addr, 0x2000200);
+        assert_eq!(sym.sym_type, SymType::Function);
+        assert_eq!(sym.file_offset, None);
+        assert_eq!(sym.obj_file_name.as_deref(), Some(test_gsym.as_path()));
+        assert_eq!(sym.code_info, None);
+        assert!(resolver.by_name_idx.get().is_some());
+
+        // Every symbol should be found by its name.
+        for idx in 0..resolver.ctx.num_addrs() {
+            let sym = resolver.sym_info(idx, &opts).unwrap();
+            let syms = resolver.find_addr(&sym.name, &opts).unwrap();
+            assert!(syms.iter().any(|other| other.addr == sym.addr), "{sym:?}");
+        }
+
+        let syms = resolver.find_addr("does-not-exist", &opts).unwrap();
+        assert!(syms.is_empty());
+
+        let mut names = Vec::new();
+        let () = resolver
+            .for_each(&opts, &mut |sym| {
+                let () = names.push(sym.name.to_string());
+                ControlFlow::Continue(())
+            })
+            .unwrap();
+        assert!(names.contains(&"main".to_string()), "{names:?}");
+        assert!(names.contains(&"factorial".to_string()), "{names:?}");
+
+        let mut cnt = 0;
+        let () = resolver
+            .for_each(&opts, &mut |_sym| {
+                cnt += 1;
+                ControlFlow::Break(())
+            })
+            .unwrap();
+        assert_eq!(cnt, 1);
+
+        let opts = FindAddrOpts {
+            sym_type: SymType::Variable,
+            ..Default::default()
+        };
+        let err = resolver.find_addr("a_variable", &opts).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::Unsupported);
+    }
+
+    /// Check that we report the source code location of a function's
+    /// start when asked to.
+    #[test]
+    fn inspect_symbols_code_info() {
+        let test_gsym = Path::new(&env!("CARGO_MANIFEST_DIR"))
+            .join("data")
+            .join("test-stable-addrs.gsym");
+        let resolver = GsymResolver::open(test_gsym).unwrap();
+
+        let opts = FindAddrOpts {
+            code_info: true,
+            ..Default::default()
+        };
+        let syms = resolver.find_addr("factorial", &opts).unwrap();
+        assert_eq!(syms.len(), 1);
+        let code_info = syms[0].code_info.as_ref().unwrap();
+        assert_eq!(code_info.file, OsStr::new("test-stable-addrs.c"));
+        assert_eq!(code_info.line, Some(10));
+
+        let mut code_infos = Vec::new();
+        let () = resolver
+            .for_each(&opts, &mut |sym| {
+                let () = code_infos.push(sym.code_info.is_some());
+                ControlFlow::Continue(())
+            })
+            .unwrap();
+        assert!(code_infos.iter().any(|code_info| *code_info));
+    }
+
+    /// Check that we can look up the address range of a function.
+    #[test]
+    fn find_function_ranges() {
+        let test_gsym = Path::new(&env!("CARGO_MANIFEST_DIR"))
+            .join("data")
+            .join("test-stable-addrs.gsym");
+        let resolver = GsymResolver::open(test_gsym).unwrap();
+
+        let sym = resolver
+            .find_sym(0x2000200, &FindSymOpts::Basic)
+            .unwrap()
+            .unwrap();
+        let size = sym.size.unwrap() as Addr;
+        let ranges = resolver.find_func_ranges(0x2000201).unwrap();
+        assert_eq!(ranges, vec![0x2000200..0x2000200 + size]);
+
+        let ranges = resolver.find_func_ranges(0x1000).unwrap();
+        assert_eq!(ranges, Vec::<Range<Addr>>::new());
+    }
+
+    /// Check that we can find the addresses of the code for a given
+    /// source code line.
+    #[test]
+    fn find_line_addresses() {
+        let test_gsym = Path::new(&env!("CARGO_MANIFEST_DIR"))
+            .join("data")
+            .join("test-stable-addrs.gsym");
+        let resolver = GsymResolver::open(test_gsym).unwrap();
+
+        let file = Path::new("test-stable-addrs.c");
+        let addrs = resolver.find_line_addrs(file, 10).unwrap();
+        assert_eq!(addrs, vec![0x2000200]);
+
+        // Line 13 is covered by two consecutive rows, but we only
+        // expect the first one to be reported.
+        let addrs = resolver.find_line_addrs(file, 13).unwrap();
+        assert_eq!(addrs, vec![0x2000218]);
+
+        let addrs = resolver.find_line_addrs(file, 15).unwrap();
+        assert_eq!(addrs, Vec::<Addr>::new());
+
+        let addrs = resolver.find_line_addrs(Path::new("other.c"), 10).unwrap();
+        assert_eq!(addrs, Vec::<Addr>::new());
+    }
+
     /// Make sure that we can find file line information for a function, if
     /// available.
     #[test]
@@ -0,0 +1,249 @@
+use std::fs;
+use std::fs::File;
+use std::io;
+use std::io::Write as _;
+use std::mem::size_of;
+use std::mem::size_of_val;
+use std::ops::Deref;
+use std::path::Path;
+use std::path::PathBuf;
+use std::process;
+
+use crate::mmap::Mmap;
+use crate::normalize::buildid::build_id_to_hex;
+use crate::util::ReadRaw as _;
+use crate::Error;
+use crate::ErrorExt as _;
+use crate::IntoError as _;
+use crate::Result;
+
+
+/// The magic bytes identifying a persisted index file.
+const MAGIC: [u8; 8] = *b"BLZIDX\0\0";
+/// The version of the on-disk format.
+const VERSION: u32 = 1;
+/// The size of the header preceding the index entries.
+///
+/// The header consists of the magic bytes, the format version, the
+/// size of an index entry, the number of symbols in the indexed table,
+/// and the number of index entries. It is a multiple of the entry size,
+/// keeping entries properly aligned in a memory mapped file.
+const HEADER_LEN: usize = 32;
+
+
+/// An index into a symbol table, sorted by some criterion.
+#[derive(Debug)]
+pub(crate) enum SymIndex {
+    /// An index that was computed in memory.
+    Owned(Box<[usize]>),
+    /// An index loaded from a memory mapped index file.
+    Mapped(Mmap),
+}
+
+impl Deref for SymIndex {
+    type Target = [usize];
+
+    fn deref(&self) -> &Self::Target {
+        match self {
+            Self::Owned(index) => index,
+            Self::Mapped(mmap) => {
+                let mut data = mmap.deref();
+                // SANITY: The mapping has been validated on load to
+                //         cover a properly aligned number of entries.
+                data.read_pod_slice_ref::<usize>(data.len() / size_of::<usize>())
+                    .unwrap()
+            }
+        }
+    }
+}
+
+
+/// A persistent cache of symbol table indexes, stored in a directory
+/// on disk.
+///
+/// Indexes are keyed by the build ID of the binary they belong to and
+/// the name of the indexed table. Hence, they are only ever persisted
+/// for binaries that carry a build ID.
+#[derive(Clone, Debug)]
+pub(crate) struct IndexCache {
+    /// The directory housing index files.
+    dir: PathBuf,
+}
+
+impl IndexCache {
+    /// Create a new `IndexCache` storing data in `dir`.
+    pub(crate) fn new(dir: PathBuf) -> Self {
+        Self { dir }
+    }
+
+    /// Retrieve the path to the index file for the table with the
+    /// provided name of the binary with the given build ID.
+    pub(crate) fn index_path(&self, build_id: &[u8], table: &str) -> PathBuf {
+        let mut name = build_id_to_hex(build_id);
+        let () = name.push('.');
+        let () = name.push_str(table.trim_start_matches('.'));
+        let () = name.push_str(".idx");
+        self.dir.join(name)
+    }
+}
+
+
+/// Load a persisted index over a table of `sym_count` symbols.
+///
+/// `Ok(None)` is returned if no index file exists at `path`.
+pub(crate) fn load_index(path: &Path, sym_count: usize) -> Result<Option<SymIndex>> {
+    let file = match File::open(path) {
+        Ok(file) => file,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(None),
+        Err(err) => {
+            return Err(Error::from(err))
+                .with_context(|| format!("failed to open index file `{}`", path.display()))
+        }
+    };
+    let mmap = Mmap::map(&file)
+        .with_context(|| format!("failed to memory map index file `{}`", path.display()))?;
+
+    let mut data = mmap.deref();
+    let magic = data
+        .read_array::<8>()
+        .ok_or_invalid_data(|| "failed to read index file magic")?;
+    if magic != MAGIC {
+        return Err(Error::with_invalid_data(format!(
+            "index file `{}` has invalid magic",
+            path.display()
+        )))
+    }
+
+    let version = data
+        .read_u32()
+        .ok_or_invalid_data(|| "failed to read index file version")?;
+    let entry_size = data
+        .read_u32()
+        .ok_or_invalid_data(|| "failed to read index entry size")?;
+    if version != VERSION || entry_size as usize != size_of::<usize>() {
+        return Err(Error::with_unsupported(format!(
+            "index file `{}` has unsupported format (version {version}, entry size {entry_size})",
+            path.display()
+        )))
+    }
+
+    let count = data
+        .read_u64()
+        .ok_or_invalid_data(|| "failed to read index symbol count")?;
+    let len = data
+        .read_u64()
+        .ok_or_invalid_data(|| "failed to read index length")?;
+    if count != sym_count as u64 {
+        return Err(Error::with_invalid_data(format!(
+            "index file `{}` covers {count} symbols, but table has {sym_count}",
+            path.display()
+        )))
+    }
+
+    let len = usize::try_from(len)
+        .ok()
+        .ok_or_invalid_data(|| "index length is too large")?;
+    let entries = data
+        .read_pod_slice_ref::<usize>(len)
+        .ok_or_invalid_data(|| "failed to read index entries")?;
+    if entries.iter().any(|&idx| idx >= sym_count) {
+        return Err(Error::with_invalid_data(format!(
+            "index file `{}` contains out of bounds entries",
+            path.display()
+        )))
+    }
+
+    // SANITY: We just read `len` entries following the header, so the
+    //         range has to be covered by the mapping.
+    let mmap = mmap
+        .constrain(HEADER_LEN as u64..(HEADER_LEN + len * size_of::<usize>()) as u64)
+        .unwrap();
+    Ok(Some(SymIndex::Mapped(mmap)))
+}
+
+/// Persist `index`, covering a table of `sym_count` symbols, to `path`.
+///
+/// The file is replaced atomically, so that concurrent readers never
+/// observe partially written data.
+pub(crate) fn store_index(path: &Path, sym_count: usize, index: &[usize]) -> Result<()> {
+    if let Some(dir) = path.parent() {
+        let () = fs::create_dir_all(dir)
+            .with_context(|| format!("failed to create directory `{}`", dir.display()))?;
+    }
+
+    let mut data = Vec::with_capacity(HEADER_LEN + size_of_val(index));
+    let () = data.extend_from_slice(&MAGIC);
+    let () = data.extend_from_slice(&VERSION.to_ne_bytes());
+    let () = data.extend_from_slice(&(size_of::<usize>() as u32).to_ne_bytes());
+    let () = data.extend_from_slice(&(sym_count as u64).to_ne_bytes());
+    let () = data.extend_from_slice(&(index.len() as u64).to_ne_bytes());
+    debug_assert_eq!(data.len(), HEADER_LEN);
+    let () = index
+        .iter()
+        .for_each(|idx| data.extend_from_slice(&idx.to_ne_bytes()));
+
+    let mut tmp = path.as_os_str().to_os_string();
+    let () = tmp.push(format!(".{}.tmp", process::id()));
+    let tmp = PathBuf::from(tmp);
+
+    let result = File::create(&tmp)
+        .and_then(|mut file| file.write_all(&data))
+        .and_then(|()| fs::rename(&tmp, path));
+    if let Err(err) = result {
+        let _result = fs::remove_file(&tmp);
+        return Err(Error::from(err))
+            .with_context(|| format!("failed to write index file `{}`", path.display()))
+    }
+    Ok(())
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use tempfile::tempdir;
+    use test_log::test;
+
+    use crate::ErrorKind;
+
+
+    /// Check that we can persist and load an index.
+    #[test]
+    fn index_round_trip() {
+        let dir = tempdir().unwrap();
+        let cache = IndexCache::new(dir.path().join("cache"));
+        assert_ne!(format!("{cache:?}"), "");
+
+        let path = cache.index_path(&[0xde, 0xad, 0x0f], ".symtab");
+        assert_eq!(path, dir.path().join("cache").join("dead0f.symtab.idx"));
+        assert!(load_index(&path, 5).unwrap().is_none());
+
+        let () = store_index(&path, 5, &[4, 0, 2, 1]).unwrap();
+        let index = load_index(&path, 5).unwrap().unwrap();
+        assert!(matches!(index, SymIndex::Mapped(..)));
+        assert_eq!(index.deref(), &[4, 0, 2, 1]);
+
+        let () = store_index(&path, 5, &[]).unwrap();
+        let index = load_index(&path, 5).unwrap().unwrap();
+        assert_eq!(index.deref(), &[] as &[usize]);
+    }
+
+    /// Make sure that we reject index files not matching the symbol
+    /// table at hand.
+    #[test]
+    fn index_validation() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("index");
+
+        let () = store_index(&path, 3, &[2, 1, 0]).unwrap();
+        let err = load_index(&path, 4).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidData);
+        let err = load_index(&path, 2).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidData);
+
+        let () = fs::write(&path, b"garbage").unwrap();
+        let err = load_index(&path, 3).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidData);
+    }
+}
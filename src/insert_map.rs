@@ -56,6 +56,17 @@ impl<K, V> InsertMap<K, V> {
             }
         }
     }
+
+    /// Retain only the key-value pairs for which `f` returns `true`.
+    ///
+    /// Removal requires exclusive access, ensuring that no references
+    /// to values handed out earlier are still alive.
+    pub(crate) fn retain<F>(&mut self, f: F)
+    where
+        F: FnMut(&K, &mut V) -> bool,
+    {
+        let () = self.map.get_mut().retain(f);
+    }
 }
 
 impl<K, V> Default for InsertMap<K, V> {
@@ -100,6 +111,22 @@ mod tests {
     }
 
 
+    /// Check that we can remove values from an `InsertMap`.
+    #[tag(miri)]
+    #[test]
+    fn retention() {
+        let mut map = InsertMap::<usize, &'static str>::new();
+        let _s = map.get_or_try_insert(1, || Ok("one")).unwrap();
+        let _s = map.get_or_try_insert(2, || Ok("two")).unwrap();
+
+        let () = map.retain(|key, _value| *key != 1);
+        let s = map.get_or_try_insert(2, || panic!()).unwrap();
+        assert_eq!(s, &"two");
+        let s = map.get_or_try_insert(1, || Ok("uno")).unwrap();
+        assert_eq!(s, &"uno");
+    }
+
+
     /// Make sure that `InsertMap` does not allow for recursive
     /// access as part of initialization.
     #[tag(miri)]
@@ -1,27 +1,55 @@
-#[cfg(feature = "breakpad")]
+use std::cell::RefCell;
+use std::collections::HashSet;
+#[cfg(any(feature = "breakpad", feature = "gsym"))]
 use std::fs::File;
 use std::ops::ControlFlow;
 use std::ops::Deref as _;
-#[cfg(feature = "breakpad")]
+use std::ops::Range;
 use std::path::Path;
 use std::path::PathBuf;
+use std::rc::Rc;
 
 #[cfg(feature = "breakpad")]
 use crate::breakpad::BreakpadResolver;
+use crate::elf::ElfResolver;
 use crate::elf::ElfResolverData;
 use crate::elf::DEFAULT_DEBUG_DIRS;
 use crate::file_cache::FileCache;
+#[cfg(feature = "gsym")]
+use crate::gsym::GsymResolver;
 use crate::inspect::ForEachFn;
+use crate::kernel::KsymResolver;
+#[cfg(feature = "macho")]
+use crate::macho::MachOResolver;
+use crate::maps;
+use crate::maps::PathName;
+use crate::maps::Perm;
+#[cfg(feature = "windows")]
+use crate::pe::PeResolver;
+use crate::symbolize::source::BaseAddr;
+use crate::Addr;
+use crate::Error;
 use crate::Result;
 
 #[cfg(feature = "breakpad")]
 use super::source::Breakpad;
 use super::source::Elf;
+#[cfg(feature = "gsym")]
+use super::source::Gsym;
+use super::source::Kallsyms;
+#[cfg(feature = "macho")]
+use super::source::MachO;
+#[cfg(feature = "windows")]
+use super::source::Pe;
+use super::source::Process;
 use super::source::Source;
 use super::FindAddrOpts;
 use super::Inspect;
+use super::PltEntry;
 use super::SymInfo;
 use super::SymType;
+#[cfg(feature = "dwarf")]
+use super::TargetAccess;
 
 
 /// An inspector of various "sources".
@@ -42,6 +70,13 @@ pub struct Inspector {
     #[cfg(feature = "breakpad")]
     breakpad_cache: FileCache<Box<BreakpadResolver>>,
     elf_cache: FileCache<ElfResolverData>,
+    #[cfg(feature = "gsym")]
+    gsym_cache: FileCache<Box<GsymResolver<'static>>>,
+    ksym_cache: FileCache<RefCell<Rc<KsymResolver>>>,
+    #[cfg(feature = "macho")]
+    macho_cache: FileCache<Box<MachOResolver>>,
+    #[cfg(feature = "windows")]
+    pe_cache: FileCache<Box<PeResolver>>,
 }
 
 impl Inspector {
@@ -52,6 +87,13 @@ impl Inspector {
             breakpad_cache: FileCache::builder().enable_auto_reload(true).build(),
             // TODO: Make auto reloading configurable by clients.
             elf_cache: FileCache::builder().enable_auto_reload(true).build(),
+            #[cfg(feature = "gsym")]
+            gsym_cache: FileCache::builder().enable_auto_reload(true).build(),
+            ksym_cache: FileCache::builder().enable_auto_reload(true).build(),
+            #[cfg(feature = "macho")]
+            macho_cache: FileCache::builder().enable_auto_reload(true).build(),
+            #[cfg(feature = "windows")]
+            pe_cache: FileCache::builder().enable_auto_reload(true).build(),
         }
     }
 
@@ -64,10 +106,164 @@ impl Inspector {
     #[cfg(feature = "breakpad")]
     fn breakpad_resolver<'slf>(&'slf self, path: &Path) -> Result<&'slf BreakpadResolver> {
         let (file, cell) = self.breakpad_cache.entry(path)?;
-        let resolver = cell.get_or_try_init(|| self.create_breakpad_resolver(path, file))?;
+        let resolver =
+            cell.get_or_try_init(|| self.create_breakpad_resolver(path, &*file.get()?))?;
+        Ok(resolver)
+    }
+
+    #[cfg(feature = "gsym")]
+    fn create_gsym_resolver(&self, path: &Path, file: &File) -> Result<Box<GsymResolver<'static>>> {
+        let resolver = GsymResolver::from_file(path.to_path_buf(), file, false)?;
+        Ok(Box::new(resolver))
+    }
+
+    #[cfg(feature = "gsym")]
+    fn gsym_resolver<'slf>(&'slf self, path: &Path) -> Result<&'slf GsymResolver<'static>> {
+        let (file, cell) = self.gsym_cache.entry(path)?;
+        let resolver = cell.get_or_try_init(|| self.create_gsym_resolver(path, &*file.get()?))?;
+        Ok(resolver)
+    }
+
+    #[cfg(feature = "macho")]
+    fn macho_resolver<'slf>(
+        &'slf self,
+        path: &Path,
+        dsym: Option<&Path>,
+    ) -> Result<&'slf MachOResolver> {
+        let (_file, cell) = self.macho_cache.entry(path)?;
+        let resolver = cell.get_or_try_init(|| {
+            let resolver = match dsym {
+                Some(dsym) => MachOResolver::open_with_dsym(path, dsym),
+                None => MachOResolver::open(path),
+            };
+            resolver.map(Box::new)
+        })?;
         Ok(resolver)
     }
 
+    #[cfg(feature = "windows")]
+    fn pe_resolver<'slf>(&'slf self, path: &Path, pdb: Option<&Path>) -> Result<&'slf PeResolver> {
+        let (_file, cell) = self.pe_cache.entry(path)?;
+        let resolver = cell.get_or_try_init(|| PeResolver::open_impl(path, pdb).map(Box::new))?;
+        Ok(resolver)
+    }
+
+    /// Invoke `f` for each ELF file mapped executable into the process
+    /// described by `process`, along with the load bias to apply to
+    /// addresses reported for it.
+    fn for_each_module<F>(&self, process: &Process, mut f: F) -> Result<()>
+    where
+        F: FnMut(&ElfResolver, Addr) -> Result<ControlFlow<()>>,
+    {
+        let Process {
+            pid,
+            debug_syms,
+            file_relative,
+            map_files,
+            proc_root,
+            _non_exhaustive: (),
+        } = process;
+
+        let root = if *proc_root {
+            maps::proc_root(pid)
+        } else {
+            None
+        };
+        let debug_dirs = DEFAULT_DEBUG_DIRS
+            .iter()
+            .map(PathBuf::from)
+            .collect::<Vec<_>>();
+        let mut seen = HashSet::new();
+
+        for result in maps::parse(pid)? {
+            let entry = result?;
+            if entry.perm & Perm::X == Perm::default() {
+                continue
+            }
+
+            let path = match &entry.path_name {
+                Some(PathName::Path(path)) if !maps::is_shared_mem(&path.symbolic_path) => path,
+                _ => continue,
+            };
+            // A file may be mapped executable more than once, but we
+            // only want to report its symbols once.
+            if !seen.insert((path.symbolic_path.clone(), entry.inode)) {
+                continue
+            }
+
+            let access_path = path.access_path(*map_files, root.as_deref());
+            let resolver = self.elf_cache.elf_resolver(
+                &access_path,
+                debug_syms.then_some(debug_dirs.as_slice()),
+                false,
+                None,
+            )?;
+            let bias = if *file_relative {
+                0
+            } else {
+                resolver.load_bias(&BaseAddr::Mapping {
+                    addr: entry.range.start,
+                    file_offset: entry.offset,
+                })?
+            };
+
+            if let ControlFlow::Break(()) = f(resolver, bias)? {
+                break
+            }
+        }
+        Ok(())
+    }
+
+    fn lookup_process(
+        &self,
+        process: &Process,
+        names: &[&str],
+    ) -> Result<Vec<Vec<SymInfo<'static>>>> {
+        let opts = FindAddrOpts {
+            offset_in_file: true,
+            sym_type: SymType::Undefined,
+            code_info: false,
+            decl_file: None,
+        };
+
+        let mut syms = vec![Vec::new(); names.len()];
+        let () = self.for_each_module(process, |resolver, bias| {
+            for (name, syms) in names.iter().zip(syms.iter_mut()) {
+                for sym in resolver.find_addr(name, &opts)? {
+                    let () = syms.push(relocate(sym, bias)?.to_owned());
+                }
+            }
+            Ok(ControlFlow::Continue(()))
+        })?;
+        Ok(syms)
+    }
+
+    fn for_each_process(&self, process: &Process, f: &mut ForEachFn<'_>) -> Result<()> {
+        let opts = FindAddrOpts {
+            offset_in_file: true,
+            sym_type: SymType::Undefined,
+            code_info: false,
+            decl_file: None,
+        };
+
+        self.for_each_module(process, |resolver, bias| {
+            let mut result = Ok(());
+            let mut flow = ControlFlow::Continue(());
+            let () = resolver.for_each(&opts, &mut |sym| match relocate(sym.clone(), bias) {
+                Ok(sym) => {
+                    flow = f(&sym);
+                    flow
+                }
+                Err(err) => {
+                    result = Err(err);
+                    ControlFlow::Break(())
+                }
+            })?;
+            let () = result?;
+            Ok(flow)
+        })
+    }
+
     /// Look up information (address etc.) about a list of symbols,
     /// given their names.
     ///
@@ -83,35 +279,71 @@ impl Inspector {
     /// # Notes
     /// - no symbol name demangling is performed and data is reported as it
     ///   appears in the symbol source
+    /// - for the [`Elf`](Source::Elf) source:
+    ///   - declaration source code information is only reported if `code_info`
+    ///     is set and debug symbols are used
+    ///   - if `decl_file` is set, only symbols declared in said file are
+    ///     reported, which requires debug symbols to be used
     /// - for the [`Breakpad`](Source::Breakpad) source:
     ///   - no variable support is present
     ///   - file offsets won't be reported
     ///   - addresses are reported as they appear in the symbol source
+    /// - for the [`Gsym`](Source::Gsym) source:
+    ///   - no variable support is present
+    ///   - file offsets won't be reported
+    ///   - source code information refers to the first line of the function,
+    ///     not its declaration, and is only reported if `code_info` is set
+    /// - for the [`Kallsyms`](Source::Kallsyms) source:
+    ///   - no variable support is present
+    ///   - symbol sizes, file offsets, and declaration source code information
+    ///     won't be reported
+    /// - for the [`MachO`](Source::MachO) source:
+    ///   - symbols are looked up in the symbol table only
+    ///   - symbol sizes are inferred from symbol addresses
+    /// - for the [`Pe`](Source::Pe) source:
+    ///   - no variable support is present
+    ///   - addresses are reported as relative virtual addresses
+    /// - for the [`Process`](Source::Process) source:
+    ///   - symbols are looked up in all ELF files mapped executable into the
+    ///     process
+    ///   - addresses are relocated, unless `file_relative` is set
+    ///   - declaration source code information won't be reported
     #[cfg_attr(feature = "tracing", crate::log::instrument(skip_all, fields(src = ?src, names = ?names), err))]
     pub fn lookup<'slf>(
         &'slf self,
         src: &Source,
         names: &[&str],
     ) -> Result<Vec<Vec<SymInfo<'slf>>>> {
-        let opts = FindAddrOpts {
-            offset_in_file: true,
-            sym_type: SymType::Undefined,
-        };
-
-        let resolver = match src {
+        let ksym_resolver;
+        let module_ksyms;
+        let (resolver, opts) = match src {
             #[cfg(feature = "breakpad")]
             Source::Breakpad(Breakpad {
                 path,
                 _non_exhaustive: (),
             }) => {
+                let opts = FindAddrOpts {
+                    offset_in_file: true,
+                    sym_type: SymType::Undefined,
+                    code_info: false,
+                    decl_file: None,
+                };
                 let resolver = self.breakpad_resolver(path)?;
-                resolver as &dyn Inspect
+                (resolver as &dyn Inspect, opts)
             }
             Source::Elf(Elf {
                 path,
                 debug_syms,
+                code_info,
+                decl_file,
                 _non_exhaustive: (),
             }) => {
+                let opts = FindAddrOpts {
+                    offset_in_file: true,
+                    sym_type: SymType::Undefined,
+                    code_info: *code_info,
+                    decl_file: decl_file.clone(),
+                };
                 let debug_dirs;
                 let resolver = self.elf_cache.elf_resolver(
                     path,
@@ -124,9 +356,76 @@ impl Inspector {
                     } else {
                         None
                     },
+                    false,
+                    None,
                 )?;
-                resolver.deref() as &dyn Inspect
+                (resolver.deref() as &dyn Inspect, opts)
+            }
+            #[cfg(feature = "gsym")]
+            Source::Gsym(Gsym {
+                path,
+                _non_exhaustive: (),
+            }) => {
+                let opts = FindAddrOpts {
+                    offset_in_file: false,
+                    sym_type: SymType::Undefined,
+                    code_info: false,
+                    decl_file: None,
+                };
+                let resolver = self.gsym_resolver(path)?;
+                (resolver as &dyn Inspect, opts)
+            }
+            Source::Kallsyms(Kallsyms {
+                path,
+                module,
+                _non_exhaustive: (),
+            }) => {
+                let opts = FindAddrOpts {
+                    offset_in_file: false,
+                    sym_type: SymType::Undefined,
+                    code_info: false,
+                    decl_file: None,
+                };
+                ksym_resolver = self.ksym_cache.ksym_resolver(path)?;
+                let resolver = if let Some(module) = module {
+                    module_ksyms = ksym_resolver.for_module(module);
+                    &module_ksyms as &dyn Inspect
+                } else {
+                    ksym_resolver.deref() as &dyn Inspect
+                };
+                (resolver, opts)
+            }
+            #[cfg(feature = "macho")]
+            Source::MachO(MachO {
+                path,
+                dsym,
+                _non_exhaustive: (),
+            }) => {
+                let opts = FindAddrOpts {
+                    offset_in_file: true,
+                    sym_type: SymType::Undefined,
+                    code_info: false,
+                    decl_file: None,
+                };
+                let resolver = self.macho_resolver(path, dsym.as_deref())?;
+                (resolver as &dyn Inspect, opts)
+            }
+            #[cfg(feature = "windows")]
+            Source::Pe(Pe {
+                path,
+                pdb,
+                _non_exhaustive: (),
+            }) => {
+                let opts = FindAddrOpts {
+                    offset_in_file: true,
+                    sym_type: SymType::Undefined,
+                    code_info: false,
+                    decl_file: None,
+                };
+                let resolver = self.pe_resolver(path, pdb.as_deref())?;
+                (resolver as &dyn Inspect, opts)
             }
+            Source::Process(process) => return self.lookup_process(process, names),
         };
 
         let syms = names
@@ -159,18 +458,44 @@ impl Inspector {
     /// - undefined symbols (such as ones referencing a different shared object)
     ///   are not reported
     /// - for the [`Elf`](Source::Elf) source:
-    ///   - if `debug_syms` is set, *only* debug symbols are consulted and no
-    ///     support for variables is present
+    ///   - if `debug_syms` is set, *only* debug symbols are consulted and only
+    ///     variables residing at a fixed address are reported
+    ///   - declaration source code information is only reported if `code_info`
+    ///     is set and debug symbols are used
+    ///   - if `decl_file` is set, only symbols declared in said file are
+    ///     reported, which requires debug symbols to be used
     /// - for the [`Breakpad`](Source::Breakpad) source:
     ///   - no variable support is present
     ///   - file offsets won't be reported
     ///   - addresses are reported as they appear in the symbol source
+    /// - for the [`Gsym`](Source::Gsym) source:
+    ///   - no variable support is present
+    ///   - file offsets won't be reported
+    ///   - source code information refers to the first line of the function,
+    ///     not its declaration, and is only reported if `code_info` is set
+    /// - for the [`Kallsyms`](Source::Kallsyms) source:
+    ///   - no variable support is present
+    ///   - symbol sizes, file offsets, and declaration source code information
+    ///     won't be reported
+    /// - for the [`MachO`](Source::MachO) source:
+    ///   - symbols are looked up in the symbol table only
+    ///   - symbol sizes are inferred from symbol addresses
+    /// - for the [`Pe`](Source::Pe) source:
+    ///   - no variable support is present
+    ///   - addresses are reported as relative virtual addresses
+    /// - for the [`Process`](Source::Process) source:
+    ///   - symbols of all ELF files mapped executable into the process are
+    ///     reported
+    ///   - addresses are relocated, unless `file_relative` is set
+    ///   - declaration source code information won't be reported
     #[cfg_attr(feature = "tracing", crate::log::instrument(skip_all, fields(src = ?src), err))]
     pub fn for_each<F>(&self, src: &Source, mut f: F) -> Result<()>
     where
         F: FnMut(&SymInfo<'_>) -> ControlFlow<()>,
     {
         fn for_each_impl(slf: &Inspector, src: &Source, f: &mut ForEachFn<'_>) -> Result<()> {
+            let ksym_resolver;
+            let module_ksyms;
             let (resolver, opts) = match src {
                 #[cfg(feature = "breakpad")]
                 Source::Breakpad(Breakpad {
@@ -181,6 +506,8 @@ impl Inspector {
                         // Breakpad logic doesn't support file offsets.
                         offset_in_file: false,
                         sym_type: SymType::Undefined,
+                        code_info: false,
+                        decl_file: None,
                     };
                     let resolver = slf.breakpad_resolver(path)?;
                     (resolver as &dyn Inspect, opts)
@@ -188,11 +515,15 @@ impl Inspector {
                 Source::Elf(Elf {
                     path,
                     debug_syms,
+                    code_info,
+                    decl_file,
                     _non_exhaustive: (),
                 }) => {
                     let opts = FindAddrOpts {
                         offset_in_file: true,
                         sym_type: SymType::Undefined,
+                        code_info: *code_info,
+                        decl_file: decl_file.clone(),
                     };
                     let debug_dirs;
                     let resolver = slf.elf_cache.elf_resolver(
@@ -206,9 +537,76 @@ impl Inspector {
                         } else {
                             None
                         },
+                        false,
+                        None,
                     )?;
                     (resolver.deref() as &dyn Inspect, opts)
                 }
+                #[cfg(feature = "gsym")]
+                Source::Gsym(Gsym {
+                    path,
+                    _non_exhaustive: (),
+                }) => {
+                    let opts = FindAddrOpts {
+                        offset_in_file: false,
+                        sym_type: SymType::Undefined,
+                        code_info: false,
+                        decl_file: None,
+                    };
+                    let resolver = slf.gsym_resolver(path)?;
+                    (resolver as &dyn Inspect, opts)
+                }
+                Source::Kallsyms(Kallsyms {
+                    path,
+                    module,
+                    _non_exhaustive: (),
+                }) => {
+                    let opts = FindAddrOpts {
+                        offset_in_file: false,
+                        sym_type: SymType::Undefined,
+                        code_info: false,
+                        decl_file: None,
+                    };
+                    ksym_resolver = slf.ksym_cache.ksym_resolver(path)?;
+                    let resolver = if let Some(module) = module {
+                        module_ksyms = ksym_resolver.for_module(module);
+                        &module_ksyms as &dyn Inspect
+                    } else {
+                        ksym_resolver.deref() as &dyn Inspect
+                    };
+                    (resolver, opts)
+                }
+                #[cfg(feature = "macho")]
+                Source::MachO(MachO {
+                    path,
+                    dsym,
+                    _non_exhaustive: (),
+                }) => {
+                    let opts = FindAddrOpts {
+                        offset_in_file: true,
+                        sym_type: SymType::Undefined,
+                        code_info: false,
+                        decl_file: None,
+                    };
+                    let resolver = slf.macho_resolver(path, dsym.as_deref())?;
+                    (resolver as &dyn Inspect, opts)
+                }
+                #[cfg(feature = "windows")]
+                Source::Pe(Pe {
+                    path,
+                    pdb,
+                    _non_exhaustive: (),
+                }) => {
+                    let opts = FindAddrOpts {
+                        offset_in_file: true,
+                        sym_type: SymType::Undefined,
+                        code_info: false,
+                        decl_file: None,
+                    };
+                    let resolver = slf.pe_resolver(path, pdb.as_deref())?;
+                    (resolver as &dyn Inspect, opts)
+                }
+                Source::Process(process) => return slf.for_each_process(process, f),
             };
 
             resolver.for_each(&opts, f)
@@ -216,8 +614,291 @@ impl Inspector {
 
         for_each_impl(self, src, &mut f)
     }
+
+    /// Look up the addresses at which variables reside in a target
+    /// (e.g., a running process), given their names.
+    ///
+    /// In contrast to [`Inspector::lookup`], which only reports
+    /// variables at fixed addresses, this method evaluates the DWARF
+    /// expressions describing the variables' locations, consulting
+    /// `target` for register values, memory contents, and the like.
+    /// That allows for locating, for example, thread-local variables.
+    /// All reported addresses are in the target's address space, i.e.,
+    /// have been converted using [`TargetAccess::relocate`].
+    ///
+    /// Similar to [`Inspector::lookup`], a vector of symbol information
+    /// is returned for each symbol name. Errors reported by `target`
+    /// are propagated.
+    ///
+    /// # Notes
+    /// - only the [`Elf`](Source::Elf) source is supported and variables are
+    ///   only reported if `debug_syms` is set
+    /// - variables whose location depends on a stack frame (such as function
+    ///   locals) or that are not stored in memory are not reported
+    /// - file offsets won't be reported
+    #[cfg(feature = "dwarf")]
+    #[cfg_attr(feature = "tracing", crate::log::instrument(skip_all, fields(src = ?src, names = ?names), err))]
+    pub fn locate_vars<'slf>(
+        &'slf self,
+        src: &Source,
+        names: &[&str],
+        target: &dyn TargetAccess,
+    ) -> Result<Vec<Vec<SymInfo<'slf>>>> {
+        let (resolver, opts) = match src {
+            Source::Elf(Elf {
+                path,
+                debug_syms,
+                code_info,
+                decl_file,
+                _non_exhaustive: (),
+            }) => {
+                let debug_dirs;
+                let resolver = self.elf_cache.elf_resolver(
+                    path,
+                    if *debug_syms {
+                        debug_dirs = DEFAULT_DEBUG_DIRS
+                            .iter()
+                            .map(PathBuf::from)
+                            .collect::<Vec<_>>();
+                        Some(debug_dirs.as_slice())
+                    } else {
+                        None
+                    },
+                    false,
+                    None,
+                )?;
+                let opts = FindAddrOpts {
+                    offset_in_file: false,
+                    sym_type: SymType::Variable,
+                    code_info: *code_info,
+                    decl_file: decl_file.clone(),
+                };
+                (resolver.deref() as &dyn Inspect, opts)
+            }
+            #[allow(unreachable_patterns)]
+            _ => {
+                return Err(Error::with_unsupported(
+                    "variable location lookup is only supported for ELF sources",
+                ))
+            }
+        };
+
+        names
+            .iter()
+            .map(|name| resolver.find_var_locations(name, &opts, target))
+            .collect()
+    }
+
+    /// Find the addresses at which the code generated for a source code
+    /// line starts.
+    ///
+    /// This is the inverse of symbolization with source code information
+    /// and can be used to, for example, attach probes to a given
+    /// `file:line` location. A file is considered a match if `file` is a
+    /// suffix of its path, as reported during symbolization. E.g.,
+    /// `main.c` and `src/main.c` both match `/home/user/src/main.c`.
+    ///
+    /// Out of multiple consecutive line table rows for `line`, only the
+    /// first one is considered, but the same line may map to multiple
+    /// disjoint address ranges (e.g., because a function got inlined).
+    /// Hence, more than one address may be reported. Addresses are
+    /// reported in ascending order and an empty vector indicates that no
+    /// code is associated with the line.
+    ///
+    /// # Notes
+    /// - only the [`Elf`](Source::Elf) and [`Gsym`](Source::Gsym) sources are
+    ///   supported
+    /// - for the [`Elf`](Source::Elf) source:
+    ///   - line information is only available if `debug_syms` is set
+    ///   - only line table rows marked as recommended breakpoint locations are
+    ///     considered
+    ///   - addresses are reported as they appear in the symbol source
+    #[cfg_attr(feature = "tracing", crate::log::instrument(skip_all, fields(src = ?src, file = ?file, line = line), err))]
+    pub fn find_addr_for_line(&self, src: &Source, file: &Path, line: u32) -> Result<Vec<Addr>> {
+        let resolver = match src {
+            Source::Elf(Elf {
+                path,
+                debug_syms,
+                code_info: _,
+                decl_file: _,
+                _non_exhaustive: (),
+            }) => {
+                let debug_dirs;
+                let resolver = self.elf_cache.elf_resolver(
+                    path,
+                    if *debug_syms {
+                        debug_dirs = DEFAULT_DEBUG_DIRS
+                            .iter()
+                            .map(PathBuf::from)
+                            .collect::<Vec<_>>();
+                        Some(debug_dirs.as_slice())
+                    } else {
+                        None
+                    },
+                    false,
+                    None,
+                )?;
+                resolver.deref() as &dyn Inspect
+            }
+            #[cfg(feature = "gsym")]
+            Source::Gsym(Gsym {
+                path,
+                _non_exhaustive: (),
+            }) => self.gsym_resolver(path)? as &dyn Inspect,
+            #[allow(unreachable_patterns)]
+            _ => {
+                return Err(Error::with_unsupported(
+                    "source line lookup is only supported for ELF and Gsym sources",
+                ))
+            }
+        };
+
+        resolver.find_line_addrs(file, line)
+    }
+
+    fn func_ranges_resolver<'slf>(&'slf self, src: &Source) -> Result<&'slf dyn Inspect> {
+        match src {
+            Source::Elf(Elf {
+                path,
+                debug_syms,
+                code_info: _,
+                decl_file: _,
+                _non_exhaustive: (),
+            }) => {
+                let debug_dirs;
+                let resolver = self.elf_cache.elf_resolver(
+                    path,
+                    if *debug_syms {
+                        debug_dirs = DEFAULT_DEBUG_DIRS
+                            .iter()
+                            .map(PathBuf::from)
+                            .collect::<Vec<_>>();
+                        Some(debug_dirs.as_slice())
+                    } else {
+                        None
+                    },
+                    false,
+                    None,
+                )?;
+                Ok(resolver.deref() as &dyn Inspect)
+            }
+            #[cfg(feature = "gsym")]
+            Source::Gsym(Gsym {
+                path,
+                _non_exhaustive: (),
+            }) => Ok(self.gsym_resolver(path)? as &dyn Inspect),
+            _ => Err(Error::with_unsupported(
+                "function range lookup is only supported for ELF and Gsym sources",
+            )),
+        }
+    }
+
+    /// Find all address ranges of the function containing `addr`.
+    ///
+    /// Functions are not necessarily contiguous in memory: compilers
+    /// and post-link optimizers (such as BOLT) may split off rarely
+    /// executed parts into separate sections (e.g., `.text.unlikely`).
+    /// This method reports all parts of a function, which allows for
+    /// attributing samples falling into any of them to the function.
+    /// Ranges are reported in ascending order and an empty vector
+    /// indicates that no function contains `addr`.
+    ///
+    /// # Notes
+    /// - only the [`Elf`](Source::Elf) and [`Gsym`](Source::Gsym) sources are
+    ///   supported
+    /// - for the [`Gsym`](Source::Gsym) source, only the range of the
+    ///   function's symbol is reported
+    /// - if debug symbols are used, ranges are reported as described by DWARF
+    ///   (`DW_AT_low_pc`/`DW_AT_high_pc` or `DW_AT_ranges`)
+    /// - otherwise, symbols of cold fragments (`<name>.cold` and
+    ///   `<name>.cold.<n>`) are attributed to the function `<name>`, but only
+    ///   if it is unambiguous
+    /// - `addr` and the reported ranges are as they appear in the symbol source
+    #[cfg_attr(feature = "tracing", crate::log::instrument(skip_all, fields(src = ?src, addr = format_args!("{addr:#x}")), err))]
+    pub fn func_ranges(&self, src: &Source, addr: Addr) -> Result<Vec<Range<Addr>>> {
+        let resolver = self.func_ranges_resolver(src)?;
+        resolver.find_func_ranges(addr)
+    }
+
+    /// Find all address ranges of the functions with the given name.
+    ///
+    /// This method is similar to [`Inspector::func_ranges`], but looks
+    /// up functions by name. Because multiple functions may share the
+    /// same name (e.g., `static` functions in different compilation
+    /// units), one list of ranges is reported for each of them. For the
+    /// [`Elf`](Source::Elf) source, `decl_file` can be used to restrict
+    /// the functions considered to those declared in a given file.
+    #[cfg_attr(feature = "tracing", crate::log::instrument(skip_all, fields(src = ?src, name = name), err))]
+    pub fn func_ranges_by_name(&self, src: &Source, name: &str) -> Result<Vec<Vec<Range<Addr>>>> {
+        let resolver = self.func_ranges_resolver(src)?;
+        let decl_file = match src {
+            Source::Elf(elf) => elf.decl_file.clone(),
+            #[allow(unreachable_patterns)]
+            _ => None,
+        };
+        let opts = FindAddrOpts {
+            offset_in_file: false,
+            sym_type: SymType::Function,
+            code_info: false,
+            decl_file,
+        };
+
+        let mut funcs = Vec::<Vec<Range<Addr>>>::new();
+        for sym in resolver.find_addr(name, &opts)? {
+            let ranges = resolver.find_func_ranges(sym.addr)?;
+            if !ranges.is_empty() && !funcs.contains(&ranges) {
+                let () = funcs.push(ranges);
+            }
+        }
+        Ok(funcs)
+    }
+
+    /// Retrieve the stubs in the procedure linkage table (PLT) of a
+    /// binary, along with the imported symbols they resolve to.
+    ///
+    /// This information can be used to attach to the library call
+    /// boundaries of a binary, e.g., for tracing purposes. Stubs are
+    /// reported in the order in which they appear in the binary.
+    ///
+    /// # Notes
+    /// - only the [`Elf`](Source::Elf) source is supported
+    /// - stubs for relocations without an associated symbol (such as those for
+    ///   `IFUNC`s in statically linked binaries) are not reported
+    /// - only x86, x86_64, and AArch64 binaries are supported
+    #[cfg_attr(feature = "tracing", crate::log::instrument(skip_all, fields(src = ?src), err))]
+    pub fn plt_entries<'slf>(&'slf self, src: &Source) -> Result<Vec<PltEntry<'slf>>> {
+        match src {
+            Source::Elf(Elf {
+                path,
+                debug_syms: _,
+                code_info: _,
+                decl_file: _,
+                _non_exhaustive: (),
+            }) => {
+                let resolver = self.elf_cache.elf_resolver(path, None, false, None)?;
+                let entries = resolver.plt_entries()?;
+                Ok(entries.into_iter().map(|entry| entry.to_owned()).collect())
+            }
+            #[allow(unreachable_patterns)]
+            _ => Err(Error::with_unsupported(
+                "PLT inspection is only supported for ELF sources",
+            )),
+        }
+    }
+}
+
+/// Apply the load bias `bias` to the address of `sym`.
+fn relocate(mut sym: SymInfo<'_>, bias: Addr) -> Result<SymInfo<'_>> {
+    sym.addr = sym.addr.checked_add(bias).ok_or_else(|| {
+        Error::with_invalid_data(format!(
+            "relocated address of symbol `{}` is out of range",
+            sym.name
+        ))
+    })?;
+    Ok(sym)
 }
 
+
 impl Default for Inspector {
     fn default() -> Self {
         Self::new()
@@ -229,11 +910,10 @@ impl Default for Inspector {
 mod tests {
     use super::*;
 
-    #[cfg(not(feature = "breakpad"))]
-    use std::path::Path;
     use std::rc::Rc;
 
     use crate::ErrorKind;
+    use crate::Pid;
 
 
     /// Exercise the `Debug` representation of various types.
@@ -307,4 +987,186 @@ mod tests {
             data3.elf.get().unwrap()
         ));
     }
+
+    /// Check that we can locate variables described by DWARF location
+    /// expressions with access to the target.
+    #[cfg(feature = "dwarf")]
+    #[test]
+    fn locate_vars() {
+        use std::fs::write;
+
+        use tempfile::tempdir;
+
+        use crate::test_utils::ElfBuilder;
+
+        struct Target;
+
+        impl TargetAccess for Target {
+            fn register(&self, register: u16) -> Result<u64> {
+                assert_eq!(register, 5);
+                Ok(0x10000)
+            }
+
+            fn read_memory(&self, addr: u64, data: &mut [u8]) -> Result<()> {
+                assert_eq!(addr, 0x14008);
+                let () = data.copy_from_slice(&0x20000u64.to_ne_bytes());
+                Ok(())
+            }
+
+            fn tls_addr(&self, offset: u64) -> Result<u64> {
+                Ok(0x30000 + offset)
+            }
+
+            fn relocate(&self, addr: u64) -> Result<u64> {
+                Ok(addr + 0x10000)
+            }
+        }
+
+        struct NoAccess;
+
+        impl TargetAccess for NoAccess {}
+
+        let abbrev = [
+            // Abbreviation 1: a compile unit with children.
+            1, 0x11, 1, 0x03, 0x08, 0, 0,
+            // Abbreviation 2: a variable with a name and a location,
+            // followed by the end of the table.
+            2, 0x34, 0, 0x03, 0x08, 0x02, 0x18, 0, 0, 0,
+        ];
+
+        let mut dies = vec![1];
+        let () = dies.extend_from_slice(b"test.c\0");
+        let mut add_var = |name: &str, expr: &[u8]| {
+            let () = dies.push(2);
+            let () = dies.extend_from_slice(name.as_bytes());
+            let () = dies.push(0);
+            let () = dies.push(u8::try_from(expr.len()).unwrap());
+            let () = dies.extend_from_slice(expr);
+        };
+        // DW_OP_addr 0x4000
+        let mut expr = vec![0x03];
+        let () = expr.extend_from_slice(&0x4000u64.to_ne_bytes());
+        let () = add_var("static_var", &expr);
+        // DW_OP_const8u 0x10; DW_OP_form_tls_address
+        let mut expr = vec![0x0e];
+        let () = expr.extend_from_slice(&0x10u64.to_ne_bytes());
+        let () = expr.push(0x9b);
+        let () = add_var("tls_var", &expr);
+        // DW_OP_addr 0x4008; DW_OP_deref
+        let mut expr = vec![0x03];
+        let () = expr.extend_from_slice(&0x4008u64.to_ne_bytes());
+        let () = expr.push(0x06);
+        let () = add_var("indirect_var", &expr);
+        // DW_OP_breg5 8
+        let () = add_var("reg_var", &[0x75, 0x08]);
+        // DW_OP_fbreg -8
+        let () = add_var("local_var", &[0x91, 0x78]);
+        let () = dies.push(0);
+
+        let mut info = Vec::new();
+        // Unit length, excluding the length field itself.
+        let len = u32::try_from(2 + 4 + 1 + dies.len()).unwrap();
+        let () = info.extend_from_slice(&len.to_ne_bytes());
+        // DWARF version.
+        let () = info.extend_from_slice(&4u16.to_ne_bytes());
+        // Offset into `.debug_abbrev`.
+        let () = info.extend_from_slice(&0u32.to_ne_bytes());
+        // Address size.
+        let () = info.push(8);
+        let () = info.extend_from_slice(&dies);
+
+        let mut builder = ElfBuilder::new();
+        let _idx = builder.add_section(".debug_abbrev", 0, abbrev.to_vec());
+        let _idx = builder.add_section(".debug_info", 0, info);
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("vars.bin");
+        let () = write(&path, builder.build()).unwrap();
+
+        let src = Source::Elf(Elf::new(&path));
+        let inspector = Inspector::new();
+        let names = [
+            "static_var",
+            "tls_var",
+            "indirect_var",
+            "reg_var",
+            "local_var",
+        ];
+        let results = inspector.locate_vars(&src, &names, &Target).unwrap();
+        let addrs = results
+            .iter()
+            .map(|syms| syms.iter().map(|sym| sym.addr).collect::<Vec<_>>())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            addrs,
+            vec![
+                vec![0x14000],
+                vec![0x30010],
+                vec![0x20000],
+                vec![0x10008],
+                vec![],
+            ]
+        );
+        assert!(results
+            .iter()
+            .flatten()
+            .all(|sym| sym.sym_type == SymType::Variable));
+
+        // Regular lookups only report variables at fixed addresses.
+        let results = inspector.lookup(&src, &["static_var", "tls_var"]).unwrap();
+        assert_eq!(results[0].len(), 1);
+        assert_eq!(results[0][0].addr, 0x4000);
+        assert_eq!(results[1], Vec::new());
+
+        // Variables at fixed addresses can be located without any
+        // access to the target, but others can't.
+        let results = inspector
+            .locate_vars(&src, &["static_var"], &NoAccess)
+            .unwrap();
+        assert_eq!(results[0][0].addr, 0x4000);
+        let err = inspector
+            .locate_vars(&src, &["tls_var"], &NoAccess)
+            .unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::Unsupported);
+    }
+
+    /// Check that we can look up symbols in a process and that
+    /// reported addresses are relocated as requested.
+    #[test]
+    fn process_lookup() {
+        #[no_mangle]
+        #[inline(never)]
+        extern "C" fn blazesym_inspect_process_lookup() {}
+
+        let addr = blazesym_inspect_process_lookup as *const () as Addr;
+        let name = "blazesym_inspect_process_lookup";
+        let inspector = Inspector::new();
+        let mut process = Process::new(Pid::Slf);
+        let src = Source::Process(process.clone());
+        let results = inspector.lookup(&src, &[name]).unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].len(), 1);
+        let sym = &results[0][0];
+        assert_eq!(sym.addr, addr);
+        assert_eq!(sym.sym_type, SymType::Function);
+
+        let mut found = None;
+        let () = inspector
+            .for_each(&src, |sym| {
+                if sym.name == name {
+                    found = Some(sym.addr);
+                    ControlFlow::Break(())
+                } else {
+                    ControlFlow::Continue(())
+                }
+            })
+            .unwrap();
+        assert_eq!(found, Some(addr));
+
+        process.file_relative = true;
+        let src = Source::Process(process);
+        let results = inspector.lookup(&src, &[name]).unwrap();
+        assert_eq!(results[0].len(), 1);
+        assert_ne!(results[0][0].addr, addr);
+        assert!(results[0][0].addr < addr);
+    }
 }
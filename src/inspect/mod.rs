@@ -24,9 +24,13 @@ pub mod source;
 use std::borrow::Cow;
 use std::fmt::Debug;
 use std::ops::ControlFlow;
+use std::ops::Range;
 use std::path::Path;
+use std::path::PathBuf;
 
+use crate::symbolize::CodeInfo;
 use crate::Addr;
+use crate::Error;
 use crate::Result;
 use crate::SymType;
 
@@ -35,6 +39,7 @@ pub use inspector::Inspector;
 
 /// Information about a symbol.
 #[derive(Clone, Debug, Eq, Hash, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
 pub struct SymInfo<'src> {
     /// The name of the symbol; for example, a function name.
     pub name: Cow<'src, str>,
@@ -54,6 +59,14 @@ pub struct SymInfo<'src> {
     pub file_offset: Option<u64>,
     /// The file name of the shared object.
     pub obj_file_name: Option<Cow<'src, Path>>,
+    /// Source code location information about the symbol's
+    /// declaration.
+    ///
+    /// This information is only reported if requested and available.
+    /// Note that, contrary to symbolization, the line number refers to
+    /// the line at which the symbol is declared and no column
+    /// information is reported.
+    pub code_info: Option<CodeInfo<'src>>,
 }
 
 impl SymInfo<'_> {
@@ -71,8 +84,114 @@ impl SymInfo<'_> {
                 .obj_file_name
                 .as_deref()
                 .map(|path| Cow::Owned(path.to_path_buf())),
+            code_info: self.code_info.as_ref().map(CodeInfo::to_owned),
         }
     }
+
+    /// Check whether the symbol is declared in the source file at
+    /// `path`.
+    ///
+    /// `path` is matched against trailing components of the
+    /// declaring file's path, meaning that both a plain file name
+    /// (e.g., `main.c`) and a longer path suffix (e.g., `src/main.c`)
+    /// can be used. Symbols without declaration information never
+    /// match.
+    ///
+    /// This method can be used to disambiguate identically named
+    /// symbols, such as `static` functions in different compilation
+    /// units.
+    pub fn is_declared_in(&self, path: &Path) -> bool {
+        self.code_info
+            .as_ref()
+            .map(|code_info| code_info.to_path().ends_with(path))
+            .unwrap_or(false)
+    }
+}
+
+
+/// Information about a stub in the procedure linkage table (PLT).
+///
+/// A PLT stub is the code through which calls to a function imported
+/// from a different shared object are routed.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub struct PltEntry<'src> {
+    /// The name of the imported symbol that the stub resolves to.
+    pub name: Cow<'src, str>,
+    /// Start address (the first byte) of the stub.
+    pub addr: Addr,
+    /// The size of the stub.
+    pub size: usize,
+    /// The address of the global offset table (GOT) slot that the stub
+    /// jumps through.
+    pub got_addr: Addr,
+    /// The offset of the stub in the object file.
+    pub file_offset: Option<u64>,
+}
+
+impl PltEntry<'_> {
+    /// Clone the object ensuring that references are converted to owned
+    /// objects.
+    #[inline]
+    pub fn to_owned(&self) -> PltEntry<'static> {
+        PltEntry {
+            name: Cow::Owned(self.name.to_string()),
+            addr: self.addr,
+            size: self.size,
+            got_addr: self.got_addr,
+            file_offset: self.file_offset,
+        }
+    }
+}
+
+
+/// Access to the state of a target, such as a running process or a
+/// core dump, as required by [`Inspector::locate_vars`].
+///
+/// Locations of variables are described by DWARF expressions that may
+/// refer to registers or memory of the target. Implementations only
+/// need to provide the parts they have access to: all methods but
+/// [`relocate`][TargetAccess::relocate] report that the operation is
+/// unsupported by default, in which case variables whose location
+/// depends on it cannot be located.
+#[cfg(feature = "dwarf")]
+pub trait TargetAccess {
+    /// Retrieve the value of the register with the given DWARF
+    /// register number.
+    ///
+    /// Register numbers are architecture specific.
+    fn register(&self, register: u16) -> Result<u64> {
+        Err(Error::with_unsupported(format!(
+            "reading register {register} is not supported"
+        )))
+    }
+
+    /// Read `data.len()` bytes of the target's memory at `addr`.
+    fn read_memory(&self, addr: u64, data: &mut [u8]) -> Result<()> {
+        let _ = data;
+        Err(Error::with_unsupported(format!(
+            "reading memory at {addr:#x} is not supported"
+        )))
+    }
+
+    /// Convert `offset` into the thread-local storage block of the
+    /// module being inspected into an address in the target.
+    fn tls_addr(&self, offset: u64) -> Result<u64> {
+        Err(Error::with_unsupported(format!(
+            "thread-local storage offset {offset:#x} cannot be converted to an address"
+        )))
+    }
+
+    /// Convert an address as it appears in the module being inspected
+    /// into one in the target's address space.
+    ///
+    /// For a module that is loaded at an address different from its
+    /// link-time address (e.g., a shared object), that typically
+    /// amounts to adding the module's load bias. The default
+    /// implementation reports `addr` unchanged.
+    fn relocate(&self, addr: u64) -> Result<u64> {
+        Ok(addr)
+    }
 }
 
 
@@ -88,6 +207,12 @@ pub(crate) struct FindAddrOpts {
     /// [`Undefined`][SymType::Undefined] indicates that all supported
     /// symbols are of interest.
     pub sym_type: SymType,
+    /// Whether to report source code information about symbols'
+    /// declarations, if available.
+    pub code_info: bool,
+    /// If set, only report symbols declared in the source file at
+    /// this path, as per [`SymInfo::is_declared_in`].
+    pub decl_file: Option<PathBuf>,
 }
 
 
@@ -105,4 +230,71 @@ where
 
     /// Perform an operation on each symbol.
     fn for_each(&self, opts: &FindAddrOpts, f: &mut ForEachFn<'_>) -> Result<()>;
+
+    /// Find all address ranges of the function containing `addr`.
+    ///
+    /// The default implementation reports that the operation is not
+    /// supported.
+    fn find_func_ranges(&self, addr: Addr) -> Result<Vec<Range<Addr>>> {
+        let _ = addr;
+        Err(Error::with_unsupported(
+            "function range lookup is not supported by this source",
+        ))
+    }
+
+    /// Find the addresses at which the code generated for the given
+    /// source code line starts.
+    ///
+    /// The default implementation reports that the operation is not
+    /// supported.
+    fn find_line_addrs(&self, file: &Path, line: u32) -> Result<Vec<Addr>> {
+        let _ = (file, line);
+        Err(Error::with_unsupported(
+            "source line lookup is not supported by this source",
+        ))
+    }
+
+    /// Find the addresses at which the variables with the given name
+    /// reside in `target`.
+    ///
+    /// The default implementation reports that the operation is not
+    /// supported.
+    #[cfg(feature = "dwarf")]
+    fn find_var_locations(
+        &self,
+        name: &str,
+        opts: &FindAddrOpts,
+        target: &dyn TargetAccess,
+    ) -> Result<Vec<SymInfo<'_>>> {
+        let _ = (name, opts, target);
+        Err(Error::with_unsupported(
+            "variable location lookup is not supported by this source",
+        ))
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+
+    /// Check that a [`SymInfo`] survives a round trip through `serde`.
+    #[cfg(feature = "serde")]
+    #[test]
+    fn sym_info_serde_roundtrip() {
+        let sym_info = SymInfo {
+            name: Cow::Borrowed("factorial"),
+            addr: 0x2000200,
+            size: Some(0x42),
+            sym_type: SymType::Function,
+            file_offset: Some(0x200),
+            obj_file_name: Some(Cow::Borrowed(Path::new("/tmp/test.bin"))),
+            code_info: None,
+        };
+
+        let json = serde_json::to_string(&sym_info).unwrap();
+        let deserialized = serde_json::from_str::<SymInfo>(&json).unwrap();
+        assert_eq!(deserialized, sym_info);
+    }
 }
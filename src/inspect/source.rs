@@ -6,6 +6,9 @@ use std::fmt::Result as FmtResult;
 use std::path::Path;
 use std::path::PathBuf;
 
+use crate::kernel::KALLSYMS;
+use crate::Pid;
+
 #[cfg(doc)]
 use super::Inspector;
 
@@ -58,6 +61,21 @@ pub struct Elf {
     /// Whether or not to consult debug symbols to satisfy the request
     /// (if present).
     pub debug_syms: bool,
+    /// Whether or not to report source code information about the
+    /// declaration of symbols (file and line).
+    ///
+    /// This information is only available from debug symbols and
+    /// retrieving it may incur additional cost.
+    pub code_info: bool,
+    /// If set, only report symbols declared in the source file at
+    /// this path.
+    ///
+    /// The path is matched as per [`SymInfo::is_declared_in`]. As
+    /// declaration information is only available from debug symbols,
+    /// no symbols are reported if those are not used.
+    ///
+    /// [`SymInfo::is_declared_in`]: crate::inspect::SymInfo::is_declared_in
+    pub decl_file: Option<PathBuf>,
     /// The struct is non-exhaustive and open to extension.
     #[doc(hidden)]
     pub _non_exhaustive: (),
@@ -66,11 +84,14 @@ pub struct Elf {
 impl Elf {
     /// Create a new [`Elf`] object, referencing the provided path.
     ///
-    /// `debug_syms` defaults to `true` when using this constructor.
+    /// `debug_syms` defaults to `true`, `code_info` to `false`, and
+    /// `decl_file` to `None` when using this constructor.
     pub fn new(path: impl Into<PathBuf>) -> Self {
         Self {
             path: path.into(),
             debug_syms: true,
+            code_info: false,
+            decl_file: None,
             _non_exhaustive: (),
         }
     }
@@ -87,6 +108,8 @@ impl Debug for Elf {
         let Self {
             path,
             debug_syms: _,
+            code_info: _,
+            decl_file: _,
             _non_exhaustive: (),
         } = self;
 
@@ -95,6 +118,274 @@ impl Debug for Elf {
 }
 
 
+cfg_gsym! {
+/// A Gsym file.
+#[derive(Clone, PartialEq)]
+pub struct Gsym {
+    /// The path to the Gsym file.
+    pub path: PathBuf,
+    /// The struct is non-exhaustive and open to extension.
+    #[doc(hidden)]
+    pub _non_exhaustive: (),
+}
+
+impl Gsym {
+    /// Create a new [`Gsym`] object, referencing the provided path.
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            _non_exhaustive: (),
+        }
+    }
+}
+
+impl From<Gsym> for Source {
+    fn from(gsym: Gsym) -> Self {
+        Source::Gsym(gsym)
+    }
+}
+
+impl Debug for Gsym {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        let Self {
+            path,
+            _non_exhaustive: (),
+        } = self;
+
+        f.debug_tuple(stringify!(Gsym)).field(path).finish()
+    }
+}
+}
+
+
+/// A `kallsyms` file, listing the symbols of a Linux kernel and its
+/// loaded modules.
+#[derive(Clone, PartialEq)]
+pub struct Kallsyms {
+    /// The path to the `kallsyms` file.
+    ///
+    /// A copy of the file may be gzip (requires the `zlib` feature) or
+    /// zstd (requires the `zstd` feature) compressed, in which case it
+    /// is transparently decompressed.
+    pub path: PathBuf,
+    /// The name of the kernel module to restrict the inspection to.
+    ///
+    /// If set, only symbols attributed to the module with the given
+    /// name (e.g., `kvm`) are reported. Otherwise symbols of the core
+    /// kernel and all modules are considered.
+    pub module: Option<String>,
+    /// The struct is non-exhaustive and open to extension.
+    #[doc(hidden)]
+    pub _non_exhaustive: (),
+}
+
+impl Kallsyms {
+    /// Create a new [`Kallsyms`] object, referencing the provided path.
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            module: None,
+            _non_exhaustive: (),
+        }
+    }
+}
+
+impl Default for Kallsyms {
+    /// Create a [`Kallsyms`] object referencing the running kernel's
+    /// `/proc/kallsyms`.
+    fn default() -> Self {
+        Self::new(KALLSYMS)
+    }
+}
+
+impl From<Kallsyms> for Source {
+    fn from(kallsyms: Kallsyms) -> Self {
+        Source::Kallsyms(kallsyms)
+    }
+}
+
+impl Debug for Kallsyms {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        let Self {
+            path,
+            module: _,
+            _non_exhaustive: (),
+        } = self;
+
+        f.debug_tuple(stringify!(Kallsyms)).field(path).finish()
+    }
+}
+
+
+cfg_macho! {
+/// A Mach-O file, as used on macOS.
+#[derive(Clone, PartialEq)]
+pub struct MachO {
+    /// The path to the Mach-O file.
+    pub path: PathBuf,
+    /// The path to the DWARF file of a dSYM bundle to use.
+    ///
+    /// If not set, a bundle named after the Mach-O file with a `.dSYM`
+    /// suffix is searched for next to it.
+    pub dsym: Option<PathBuf>,
+    /// The struct is non-exhaustive and open to extension.
+    #[doc(hidden)]
+    pub _non_exhaustive: (),
+}
+
+impl MachO {
+    /// Create a new [`MachO`] object, referencing the provided path.
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            dsym: None,
+            _non_exhaustive: (),
+        }
+    }
+}
+
+impl From<MachO> for Source {
+    fn from(macho: MachO) -> Self {
+        Source::MachO(macho)
+    }
+}
+
+impl Debug for MachO {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        let Self {
+            path,
+            dsym: _,
+            _non_exhaustive: (),
+        } = self;
+
+        f.debug_tuple(stringify!(MachO)).field(path).finish()
+    }
+}
+}
+
+
+cfg_windows! {
+/// A Windows PE file, accompanied by a PDB file containing its debug
+/// information.
+#[derive(Clone, PartialEq)]
+pub struct Pe {
+    /// The path to the PE file (e.g., an `.exe` or `.dll`).
+    pub path: PathBuf,
+    /// The path to the PDB file to use.
+    ///
+    /// If not set, the PDB file referenced by the PE file is used.
+    pub pdb: Option<PathBuf>,
+    /// The struct is non-exhaustive and open to extension.
+    #[doc(hidden)]
+    pub _non_exhaustive: (),
+}
+
+impl Pe {
+    /// Create a new [`Pe`] object, referencing the provided path.
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            pdb: None,
+            _non_exhaustive: (),
+        }
+    }
+}
+
+impl From<Pe> for Source {
+    fn from(pe: Pe) -> Self {
+        Source::Pe(pe)
+    }
+}
+
+impl Debug for Pe {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        let Self {
+            path,
+            pdb: _,
+            _non_exhaustive: (),
+        } = self;
+
+        f.debug_tuple(stringify!(Pe)).field(path).finish()
+    }
+}
+}
+
+
+/// A process, with symbols looked up in the ELF files mapped into it.
+///
+/// By default, addresses are relocated, i.e., the load bias of the
+/// file a symbol is found in is applied, so that reported addresses
+/// can be used within the process directly.
+#[derive(Clone, PartialEq)]
+pub struct Process {
+    /// The referenced process' ID.
+    pub pid: Pid,
+    /// Whether or not to consult debug symbols to satisfy the request
+    /// (if present).
+    pub debug_syms: bool,
+    /// Whether to report addresses as they appear in the files mapped
+    /// into the process, instead of relocated ones.
+    pub file_relative: bool,
+    /// Whether to work with `/proc/<pid>/map_files/` entries or with
+    /// symbolic paths mentioned in `/proc/<pid>/maps` instead.
+    ///
+    /// See [`symbolize::source::Process::map_files`] for details.
+    ///
+    /// [`symbolize::source::Process::map_files`]: crate::symbolize::source::Process::map_files
+    pub map_files: bool,
+    /// Whether to resolve symbolic paths relative to the process' root
+    /// directory, as accessible via `/proc/<pid>/root/`.
+    ///
+    /// This setting is only relevant when not using
+    /// [`map_files`][Process::map_files].
+    pub proc_root: bool,
+    /// The struct is non-exhaustive and open to extension.
+    #[doc(hidden)]
+    pub _non_exhaustive: (),
+}
+
+impl Process {
+    /// Create a new [`Process`] object using the provided `pid`.
+    ///
+    /// `debug_syms`, `map_files`, and `proc_root` default to `true`
+    /// and `file_relative` to `false` when using this constructor.
+    pub fn new(pid: Pid) -> Self {
+        Self {
+            pid,
+            debug_syms: true,
+            file_relative: false,
+            map_files: true,
+            proc_root: true,
+            _non_exhaustive: (),
+        }
+    }
+}
+
+impl From<Process> for Source {
+    fn from(process: Process) -> Self {
+        Source::Process(process)
+    }
+}
+
+impl Debug for Process {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        let Self {
+            pid,
+            debug_syms: _,
+            file_relative: _,
+            map_files: _,
+            proc_root: _,
+            _non_exhaustive: (),
+        } = self;
+
+        f.debug_tuple(stringify!(Process))
+            // We use the `Display` representation here.
+            .field(&format_args!("{pid}"))
+            .finish()
+    }
+}
+
+
 /// The source to use for the inspection request.
 ///
 /// Objects of this type are used first and foremost with the
@@ -108,6 +399,22 @@ pub enum Source {
     Breakpad(Breakpad),
     /// The source is an ELF file.
     Elf(Elf),
+    /// The source is a Gsym file.
+    #[cfg(feature = "gsym")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "gsym")))]
+    Gsym(Gsym),
+    /// The source is a `kallsyms` file.
+    Kallsyms(Kallsyms),
+    /// The source is a Mach-O file.
+    #[cfg(feature = "macho")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "macho")))]
+    MachO(MachO),
+    /// The source is a Windows PE file with an accompanying PDB file.
+    #[cfg(feature = "windows")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "windows")))]
+    Pe(Pe),
+    /// The source is a process.
+    Process(Process),
 }
 
 impl Source {
@@ -117,6 +424,14 @@ impl Source {
             #[cfg(feature = "breakpad")]
             Self::Breakpad(breakpad) => Some(&breakpad.path),
             Self::Elf(elf) => Some(&elf.path),
+            #[cfg(feature = "gsym")]
+            Self::Gsym(gsym) => Some(&gsym.path),
+            Self::Kallsyms(kallsyms) => Some(&kallsyms.path),
+            #[cfg(feature = "macho")]
+            Self::MachO(macho) => Some(&macho.path),
+            #[cfg(feature = "windows")]
+            Self::Pe(pe) => Some(&pe.path),
+            Self::Process(_process) => None,
         }
     }
 }
@@ -127,6 +442,14 @@ impl Debug for Source {
             #[cfg(feature = "breakpad")]
             Self::Breakpad(breakpad) => Debug::fmt(breakpad, f),
             Self::Elf(elf) => Debug::fmt(elf, f),
+            #[cfg(feature = "gsym")]
+            Self::Gsym(gsym) => Debug::fmt(gsym, f),
+            Self::Kallsyms(kallsyms) => Debug::fmt(kallsyms, f),
+            #[cfg(feature = "macho")]
+            Self::MachO(macho) => Debug::fmt(macho, f),
+            #[cfg(feature = "windows")]
+            Self::Pe(pe) => Debug::fmt(pe, f),
+            Self::Process(process) => Debug::fmt(process, f),
         }
     }
 }
@@ -155,5 +478,33 @@ mod tests {
         assert_eq!(format!("{elf:?}"), "Elf(\"/a-path/with/components.elf\")");
         let src = Source::from(elf);
         assert_eq!(format!("{src:?}"), "Elf(\"/a-path/with/components.elf\")");
+
+        let gsym = Gsym::new("/a-path/with/components.gsym");
+        assert_eq!(
+            format!("{gsym:?}"),
+            "Gsym(\"/a-path/with/components.gsym\")"
+        );
+        let src = Source::from(gsym);
+        assert_eq!(format!("{src:?}"), "Gsym(\"/a-path/with/components.gsym\")");
+
+        let kallsyms = Kallsyms::default();
+        assert_eq!(format!("{kallsyms:?}"), "Kallsyms(\"/proc/kallsyms\")");
+        let src = Source::from(kallsyms);
+        assert_eq!(format!("{src:?}"), "Kallsyms(\"/proc/kallsyms\")");
+
+        let macho = MachO::new("/a-path/with/components");
+        assert_eq!(format!("{macho:?}"), "MachO(\"/a-path/with/components\")");
+        let src = Source::from(macho);
+        assert_eq!(format!("{src:?}"), "MachO(\"/a-path/with/components\")");
+
+        let pe = Pe::new("/a-path/with/components.exe");
+        assert_eq!(format!("{pe:?}"), "Pe(\"/a-path/with/components.exe\")");
+        let src = Source::from(pe);
+        assert_eq!(format!("{src:?}"), "Pe(\"/a-path/with/components.exe\")");
+
+        let process = Process::new(Pid::from(1234));
+        assert_eq!(format!("{process:?}"), "Process(1234)");
+        let src = Source::from(process);
+        assert_eq!(format!("{src:?}"), "Process(1234)");
     }
 }
@@ -0,0 +1,65 @@
+use std::borrow::Cow;
+use std::collections::HashSet;
+use std::sync::Mutex;
+use std::sync::PoisonError;
+
+
+/// The process wide set of interned strings.
+///
+/// Interned strings are never freed.
+static INTERNED: Mutex<Option<HashSet<&'static str>>> = Mutex::new(None);
+
+
+/// Intern `s`, returning a reference to the one shared copy of it.
+pub(crate) fn intern(s: &str) -> &'static str {
+    // Nothing in here can panic while leaving the set in an
+    // inconsistent state, so we can safely ignore poisoning.
+    let mut interned = INTERNED.lock().unwrap_or_else(PoisonError::into_inner);
+    let interned = interned.get_or_insert_with(HashSet::new);
+    if let Some(s) = interned.get(s) {
+        return s
+    }
+
+    let s = Box::leak(Box::<str>::from(s));
+    let _inserted = interned.insert(s);
+    s
+}
+
+/// Intern the string referenced by `s`, if it is owned.
+///
+/// Borrowed strings are expected to refer to data held by caches
+/// already and are returned as-is.
+pub(crate) fn intern_owned(s: Cow<'_, str>) -> Cow<'_, str> {
+    match s {
+        Cow::Borrowed(..) => s,
+        Cow::Owned(s) => Cow::Borrowed(intern(&s)),
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::ptr;
+
+
+    /// Check that equal strings share storage once interned.
+    #[test]
+    fn string_interning() {
+        let s1 = intern("std::vector<int>::push_back");
+        let s2 = intern(&String::from("std::vector<int>::push_back"));
+        assert_eq!(s1, "std::vector<int>::push_back");
+        assert!(ptr::eq(s1, s2));
+
+        let s3 = intern("std::vector<long>::push_back");
+        assert!(!ptr::eq(s1, s3));
+
+        let borrowed = "main";
+        let s4 = intern_owned(Cow::Borrowed(borrowed));
+        assert!(ptr::eq(s4.as_ref(), borrowed));
+
+        let s5 = intern_owned(Cow::Owned(String::from("std::vector<int>::push_back")));
+        assert!(matches!(s5, Cow::Borrowed(s) if ptr::eq(s, s1)));
+    }
+}
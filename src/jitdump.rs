@@ -0,0 +1,421 @@
+//! A module for working with jitdump files.
+//!
+//! See <https://git.kernel.org/pub/scm/linux/kernel/git/torvalds/linux.git/tree/tools/perf/Documentation/jitdump-specification.txt>
+use std::collections::HashMap;
+use std::ffi::OsString;
+use std::fmt::Debug;
+use std::fmt::Formatter;
+use std::fmt::Result as FmtResult;
+use std::fs::File;
+use std::mem::size_of;
+use std::mem::transmute;
+use std::ops::Deref as _;
+use std::path::Path;
+
+use crate::maps;
+use crate::maps::EntryPath;
+use crate::maps::PathName;
+use crate::mmap::Mmap;
+use crate::symbolize::FindSymOpts;
+use crate::symbolize::Reason;
+use crate::symbolize::ResolvedSym;
+use crate::symbolize::SrcLang;
+use crate::symbolize::Symbolize;
+use crate::util::find_match_or_lower_bound_by_key;
+use crate::util::ReadRaw as _;
+use crate::Addr;
+use crate::Error;
+use crate::ErrorExt as _;
+use crate::IntoError as _;
+use crate::Pid;
+use crate::Result;
+
+
+/// The magic number at the start of a jitdump file, as written in
+/// native byte order.
+const JITDUMP_MAGIC: u32 = 0x4a695444;
+
+/// The size of the header preceding each record.
+const RECORD_HEADER_SIZE: usize = 2 * size_of::<u32>() + size_of::<u64>();
+
+/// Record describing newly generated code.
+const JIT_CODE_LOAD: u32 = 0;
+/// Record describing code that got moved to a different address.
+const JIT_CODE_MOVE: u32 = 1;
+/// Record signaling the end of the jit runtime.
+const JIT_CODE_CLOSE: u32 = 3;
+
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+struct Function<'mmap> {
+    /// The name of the function.
+    name: &'mmap str,
+    /// The function's start address.
+    addr: Addr,
+    /// The size of the function.
+    size: u64,
+}
+
+
+/// Parse the records of a jitdump file.
+///
+/// A trailing incomplete record is ignored, as the runtime may be in
+/// the process of writing it.
+fn parse_jitdump(mut data: &[u8]) -> Result<Vec<Function<'_>>> {
+    let full_data = data;
+    let magic = data
+        .read_u32()
+        .ok_or_invalid_data(|| "failed to read jitdump magic")?;
+    if magic != JITDUMP_MAGIC {
+        if magic.swap_bytes() == JITDUMP_MAGIC {
+            return Err(Error::with_unsupported(
+                "jitdump files in non-native byte order are unsupported",
+            ))
+        }
+        return Err(Error::with_invalid_data(format!(
+            "encountered invalid jitdump magic: {magic:#x}"
+        )))
+    }
+
+    let _version = data
+        .read_u32()
+        .ok_or_invalid_data(|| "failed to read jitdump version")?;
+    let header_size = data
+        .read_u32()
+        .ok_or_invalid_data(|| "failed to read jitdump header size")?;
+    data = full_data
+        .get(header_size as usize..)
+        .ok_or_invalid_data(|| format!("jitdump header size ({header_size}) is invalid"))?;
+
+    // Mapping from code index to function name, for resolving names
+    // of moved code.
+    let mut names = HashMap::new();
+    let mut functions = Vec::new();
+
+    while data.len() >= RECORD_HEADER_SIZE {
+        // SANITY: We checked above that enough data are available.
+        let id = data.read_u32().unwrap();
+        let total_size = data.read_u32().unwrap() as usize;
+        let _timestamp = data.read_u64().unwrap();
+
+        let Some(mut record) = total_size
+            .checked_sub(RECORD_HEADER_SIZE)
+            .and_then(|size| data.read_slice(size))
+        else {
+            break
+        };
+
+        match id {
+            JIT_CODE_LOAD => {
+                let (code_addr, code_size, code_index, name) = (|| {
+                    let _pid = record.read_u32()?;
+                    let _tid = record.read_u32()?;
+                    let _vma = record.read_u64()?;
+                    let code_addr = record.read_u64()?;
+                    let code_size = record.read_u64()?;
+                    let code_index = record.read_u64()?;
+                    let name = record.read_cstr()?;
+                    Some((code_addr, code_size, code_index, name))
+                })()
+                .ok_or_invalid_data(|| "failed to read jitdump code load record")?;
+
+                let name = name.to_str().map_err(|err| {
+                    Error::with_invalid_data(format!(
+                        "encountered malformed function name in jitdump code load record: {err}"
+                    ))
+                })?;
+                let _prev = names.insert(code_index, name);
+                let () = functions.push(Function {
+                    name,
+                    addr: code_addr,
+                    size: code_size,
+                });
+            }
+            JIT_CODE_MOVE => {
+                let (new_code_addr, code_size, code_index) = (|| {
+                    let _pid = record.read_u32()?;
+                    let _tid = record.read_u32()?;
+                    let _vma = record.read_u64()?;
+                    let _old_code_addr = record.read_u64()?;
+                    let new_code_addr = record.read_u64()?;
+                    let code_size = record.read_u64()?;
+                    let code_index = record.read_u64()?;
+                    Some((new_code_addr, code_size, code_index))
+                })()
+                .ok_or_invalid_data(|| "failed to read jitdump code move record")?;
+
+                if let Some(name) = names.get(&code_index).copied() {
+                    let () = functions.push(Function {
+                        name,
+                        addr: new_code_addr,
+                        size: code_size,
+                    });
+                }
+            }
+            JIT_CODE_CLOSE => break,
+            // Other records (debug and unwinding information) are of
+            // no interest to us.
+            _ => (),
+        }
+    }
+
+    let () = functions.sort_by_key(|x| (x.addr, x.size));
+    Ok(functions)
+}
+
+
+pub(crate) struct JitDump {
+    /// All functions found in the jitdump file, ordered by start address.
+    // SAFETY: We must not hand out references with a 'static lifetime to
+    //         this member. Rather, they should never outlive `self`.
+    //         Furthermore, this member has to be listed before `_mmap`
+    //         to make sure we never end up with a dangling reference.
+    functions: Vec<Function<'static>>,
+    /// The memory mapped file.
+    _mmap: Mmap,
+}
+
+impl JitDump {
+    /// Find the jitdump file used by the process with the given `pid`,
+    /// if any.
+    ///
+    /// Runtimes are required to map their jitdump file (named
+    /// `jit-<pid>.dump`) into their address space, which is how we
+    /// discover it.
+    pub(crate) fn find(pid: &Pid) -> Result<Option<EntryPath>> {
+        let mut file_name = OsString::from("jit-");
+        let () = file_name.push(pid.resolve().to_string());
+        let () = file_name.push(".dump");
+
+        for result in maps::parse(pid)? {
+            let entry = result?;
+            if let Some(PathName::Path(entry_path)) = entry.path_name {
+                if entry_path.symbolic_path.file_name() == Some(file_name.as_os_str()) {
+                    return Ok(Some(entry_path))
+                }
+            }
+        }
+        Ok(None)
+    }
+
+    /// Load the [`JitDump`] from the given file.
+    pub(crate) fn from_file(path: &Path, file: &File) -> Result<Self> {
+        let mmap = Mmap::map(file)
+            .with_context(|| format!("failed to mmap jitdump file `{}`", path.display()))?;
+        // We transmute the mmap's lifetime to static here as that is a
+        // necessity for self-referentiality.
+        // SAFETY: We never hand out any 'static references later on.
+        let data = unsafe { transmute::<&[u8], &'static [u8]>(mmap.deref()) };
+        let functions = parse_jitdump(data)
+            .with_context(|| format!("failed to parse jitdump file `{}`", path.display()))?;
+
+        let slf = Self {
+            functions,
+            _mmap: mmap,
+        };
+        Ok(slf)
+    }
+}
+
+impl Symbolize for JitDump {
+    fn find_sym(&self, addr: Addr, _opts: &FindSymOpts) -> Result<Result<ResolvedSym<'_>, Reason>> {
+        let result = find_match_or_lower_bound_by_key(&self.functions, addr, |l| l.addr);
+        match result {
+            Some(idx) => {
+                for function in &self.functions[idx..] {
+                    if function.addr > addr {
+                        break
+                    }
+
+                    if (function.addr == addr && function.size == 0)
+                        || (function.addr <= addr && addr < function.addr + function.size)
+                    {
+                        let Function { name, addr, size } = function;
+                        let sym = ResolvedSym {
+                            name,
+                            addr: *addr,
+                            size: usize::try_from(*size).ok(),
+                            lang: SrcLang::Unknown,
+                            code_info: None,
+                            inlined: Box::new([]),
+                            inlined_truncated: false,
+                            section: None,
+                            module: None,
+                        };
+                        return Ok(Ok(sym))
+                    }
+                }
+                Ok(Err(Reason::UnknownAddr))
+            }
+            None => Ok(Err(Reason::UnknownAddr)),
+        }
+    }
+}
+
+impl Debug for JitDump {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        f.debug_struct("JitDump").finish()
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::io::Write as _;
+
+    use tempfile::NamedTempFile;
+
+    use crate::ErrorKind;
+
+
+    /// Create the contents of a jitdump file header.
+    fn header() -> Vec<u8> {
+        let mut data = Vec::new();
+        let () = data.extend_from_slice(&JITDUMP_MAGIC.to_ne_bytes());
+        // version
+        let () = data.extend_from_slice(&1u32.to_ne_bytes());
+        // total_size
+        let () = data.extend_from_slice(&40u32.to_ne_bytes());
+        // elf_mach, pad1, pid
+        let () = data.extend_from_slice(&[0; 3 * size_of::<u32>()]);
+        // timestamp, flags
+        let () = data.extend_from_slice(&[0; 2 * size_of::<u64>()]);
+        data
+    }
+
+    /// Append a record with the given `id` and `body` to `data`.
+    fn record(data: &mut Vec<u8>, id: u32, body: &[u8]) {
+        let total_size = (RECORD_HEADER_SIZE + body.len()) as u32;
+        let () = data.extend_from_slice(&id.to_ne_bytes());
+        let () = data.extend_from_slice(&total_size.to_ne_bytes());
+        let () = data.extend_from_slice(&0u64.to_ne_bytes());
+        let () = data.extend_from_slice(body);
+    }
+
+    /// Append a code load record to `data`.
+    fn code_load(data: &mut Vec<u8>, addr: Addr, size: u64, index: u64, name: &str) {
+        let mut body = Vec::new();
+        let () = body.extend_from_slice(&[0; 2 * size_of::<u32>()]);
+        let () = body.extend_from_slice(&addr.to_ne_bytes());
+        let () = body.extend_from_slice(&addr.to_ne_bytes());
+        let () = body.extend_from_slice(&size.to_ne_bytes());
+        let () = body.extend_from_slice(&index.to_ne_bytes());
+        let () = body.extend_from_slice(name.as_bytes());
+        let () = body.push(0);
+        // The native code.
+        let () = body.extend(vec![0xcc; size as usize]);
+        record(data, JIT_CODE_LOAD, &body)
+    }
+
+    /// Append a code move record to `data`.
+    fn code_move(data: &mut Vec<u8>, old_addr: Addr, new_addr: Addr, size: u64, index: u64) {
+        let mut body = Vec::new();
+        let () = body.extend_from_slice(&[0; 2 * size_of::<u32>()]);
+        let () = body.extend_from_slice(&new_addr.to_ne_bytes());
+        let () = body.extend_from_slice(&old_addr.to_ne_bytes());
+        let () = body.extend_from_slice(&new_addr.to_ne_bytes());
+        let () = body.extend_from_slice(&size.to_ne_bytes());
+        let () = body.extend_from_slice(&index.to_ne_bytes());
+        record(data, JIT_CODE_MOVE, &body)
+    }
+
+    /// Create the contents of a sample jitdump file.
+    fn sample() -> Vec<u8> {
+        let mut data = header();
+        let () = code_load(&mut data, 0x7f00_0000_1000, 0x20, 1, "LazyCompile:~foo");
+        let () = code_load(&mut data, 0x7f00_0000_1040, 0x10, 2, "LazyCompile:~bar");
+        // Debug information record, which should just be skipped.
+        let () = record(&mut data, 2, &[0; 16]);
+        let () = code_move(&mut data, 0x7f00_0000_1000, 0x7f00_0000_2000, 0x20, 1);
+        data
+    }
+
+
+    /// Exercise the `Debug` representation of various types.
+    #[test]
+    fn debug_repr() {
+        let func = Function {
+            name: "foobar",
+            addr: 0x1337,
+            size: 0x42,
+        };
+        assert_ne!(format!("{func:?}"), "");
+
+        let mut file = NamedTempFile::new().unwrap();
+        let () = file.write_all(&sample()).unwrap();
+        let jitdump = JitDump::from_file(file.path(), file.as_file()).unwrap();
+        assert_ne!(format!("{jitdump:?}"), "");
+    }
+
+    /// Make sure that we can parse a valid jitdump file successfully.
+    #[test]
+    fn jitdump_parsing() {
+        let data = sample();
+        let functions = parse_jitdump(&data).unwrap();
+        let expected = vec![
+            Function {
+                name: "LazyCompile:~foo",
+                addr: 0x7f00_0000_1000,
+                size: 0x20,
+            },
+            Function {
+                name: "LazyCompile:~bar",
+                addr: 0x7f00_0000_1040,
+                size: 0x10,
+            },
+            Function {
+                name: "LazyCompile:~foo",
+                addr: 0x7f00_0000_2000,
+                size: 0x20,
+            },
+        ];
+        assert_eq!(functions, expected);
+
+        // A truncated trailing record should just be ignored.
+        let functions = parse_jitdump(&data[..data.len() - 4]).unwrap();
+        assert_eq!(functions.len(), 2);
+    }
+
+    /// Exercise various error paths of the jitdump parsing logic.
+    #[test]
+    fn jitdump_parsing_errors() {
+        let result = parse_jitdump(b"JiT");
+        assert!(result.is_err(), "{result:?}");
+
+        let mut data = sample();
+        data[..4].copy_from_slice(&0xdeadbeefu32.to_ne_bytes());
+        let result = parse_jitdump(&data);
+        assert!(result.is_err(), "{result:?}");
+
+        let mut data = sample();
+        data[..4].copy_from_slice(&JITDUMP_MAGIC.swap_bytes().to_ne_bytes());
+        let err = parse_jitdump(&data).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::Unsupported);
+    }
+
+    /// Check that we can load a jitdump file and use it to symbolize an
+    /// address.
+    #[test]
+    fn jitdump_symbolization() {
+        let mut file = NamedTempFile::new().unwrap();
+        let () = file.write_all(&sample()).unwrap();
+        let jitdump = JitDump::from_file(file.path(), file.as_file()).unwrap();
+
+        for offset in 0..0x20 {
+            let sym = jitdump
+                .find_sym(0x7f00_0000_2000 + offset, &FindSymOpts::Basic)
+                .unwrap()
+                .unwrap();
+            assert_eq!(sym.name, "LazyCompile:~foo");
+            assert_eq!(sym.addr, 0x7f00_0000_2000);
+            assert_eq!(sym.size, Some(0x20));
+        }
+
+        let result = jitdump
+            .find_sym(0x7f00_0000_1050, &FindSymOpts::Basic)
+            .unwrap();
+        assert_eq!(result, Err(Reason::UnknownAddr));
+    }
+}
@@ -11,3 +11,4 @@ pub(super) use prog::BpfInfoCache;
 pub(super) use prog::BpfProg;
 #[cfg(test)]
 pub(super) use prog::BpfTag;
+pub(super) use prog::BPF_MODULE;
@@ -38,6 +38,8 @@ use super::Btf;
 /// BPF kernel programs show up with this prefix followed by a tag and
 /// some other meta-data.
 const BPF_PROG_PREFIX: &str = "bpf_prog_";
+/// The pseudo module that BPF programs are attributed to in `kallsyms`.
+pub(crate) const BPF_MODULE: &str = "bpf";
 
 
 #[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
@@ -378,6 +380,9 @@ impl BpfProg {
             lang: SrcLang::Unknown,
             code_info,
             inlined: Box::new([]),
+            inlined_truncated: false,
+            section: None,
+            module: Some(BPF_MODULE),
         };
         Ok(sym)
     }
@@ -410,6 +415,7 @@ impl<'prog> TryFrom<&'prog BpfProg> for SymInfo<'prog> {
             sym_type: SymType::Function,
             file_offset: None,
             obj_file_name: None,
+            code_info: None,
         };
         Ok(sym)
     }
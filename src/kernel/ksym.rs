@@ -1,30 +1,41 @@
 use std::borrow::Cow;
+use std::cell::RefCell;
 use std::fmt::Debug;
 use std::fmt::Formatter;
 use std::fmt::Result as FmtResult;
-#[cfg(test)]
+use std::fs;
 use std::fs::File;
 use std::io::BufRead as _;
 use std::io::BufReader;
 use std::io::Read;
+use std::io::Seek as _;
+use std::io::SeekFrom;
 use std::ops::ControlFlow;
 use std::ops::Deref as _;
 use std::path::Path;
 use std::path::PathBuf;
+use std::rc::Rc;
 
+use crate::decompress::decompress;
+use crate::decompress::Compression;
+use crate::file_cache::FileCache;
 use crate::inspect::FindAddrOpts;
 use crate::inspect::ForEachFn;
 use crate::inspect::Inspect;
 use crate::inspect::SymInfo;
+use crate::log;
 use crate::once::OnceCell;
 use crate::symbolize::FindSymOpts;
 use crate::symbolize::Reason;
 use crate::symbolize::ResolvedSym;
 use crate::symbolize::SrcLang;
 use crate::symbolize::Symbolize;
+use crate::util::find_lowest_match_by_key;
 use crate::util::find_match_or_lower_bound_by_key;
+use crate::util::Either;
 use crate::Addr;
 use crate::Error;
+use crate::ErrorExt as _;
 use crate::Result;
 use crate::SymType;
 
@@ -32,11 +43,15 @@ use crate::SymType;
 use super::bpf::BpfInfoCache;
 #[cfg(feature = "bpf")]
 use super::bpf::BpfProg;
+#[cfg(feature = "bpf")]
+use super::bpf::BPF_MODULE;
 
 #[cfg(not(feature = "bpf"))]
 type BpfInfoCache = ();
 
 pub const KALLSYMS: &str = "/proc/kallsyms";
+/// The path to the list of loaded kernel modules.
+pub(crate) const MODULES: &str = "/proc/modules";
 const DFL_KSYM_CAP: usize = 200000;
 
 
@@ -49,7 +64,7 @@ enum Ksym {
 }
 
 impl Ksym {
-    fn new(name: &str, addr: Addr) -> Self {
+    fn new(name: &str, addr: Addr, module: Option<Rc<str>>) -> Self {
         #[cfg(feature = "bpf")]
         if let Some(bpf_prog) = BpfProg::parse(name, addr) {
             return Self::BpfProg(Box::new(bpf_prog))
@@ -58,6 +73,7 @@ impl Ksym {
         Self::Kfunc(Kfunc {
             addr,
             name: Box::from(name),
+            module,
         })
     }
 
@@ -90,6 +106,16 @@ impl Ksym {
         }
     }
 
+    /// Retrieve the name of the kernel module the symbol belongs to,
+    /// if any.
+    fn module(&self) -> Option<&str> {
+        match self {
+            Self::Kfunc(kfunc) => kfunc.module.as_deref(),
+            #[cfg(feature = "bpf")]
+            Self::BpfProg(..) => Some(BPF_MODULE),
+        }
+    }
+
     #[cfg(test)]
     fn as_kfunc(&self) -> Option<&Kfunc> {
         match self {
@@ -126,11 +152,13 @@ impl<'ksym> TryFrom<&'ksym Ksym> for SymInfo<'ksym> {
 struct Kfunc {
     addr: Addr,
     name: Box<str>,
+    /// The name of the kernel module the function belongs to, if any.
+    module: Option<Rc<str>>,
 }
 
 impl Kfunc {
     fn resolve(&self, _addr: Addr, _opts: &FindSymOpts) -> Result<ResolvedSym<'_>> {
-        let Kfunc { name, addr } = self;
+        let Kfunc { name, addr, module } = self;
         let sym = ResolvedSym {
             name,
             addr: *addr,
@@ -142,6 +170,9 @@ impl Kfunc {
             // kallsyms doesn't have source code location information.
             code_info: None,
             inlined: Box::new([]),
+            inlined_truncated: false,
+            section: None,
+            module: module.as_deref(),
         };
         Ok(sym)
     }
@@ -151,7 +182,11 @@ impl<'kfunc> TryFrom<&'kfunc Kfunc> for SymInfo<'kfunc> {
     type Error = Error;
 
     fn try_from(other: &'kfunc Kfunc) -> Result<Self, Self::Error> {
-        let Kfunc { name, addr } = other;
+        let Kfunc {
+            name,
+            addr,
+            module: _,
+        } = other;
         let sym = SymInfo {
             name: Cow::Borrowed(name),
             addr: *addr,
@@ -159,20 +194,116 @@ impl<'kfunc> TryFrom<&'kfunc Kfunc> for SymInfo<'kfunc> {
             sym_type: SymType::Function,
             file_offset: None,
             obj_file_name: None,
+            code_info: None,
         };
         Ok(sym)
     }
 }
 
 
+/// Parse kallsyms data from `reader`.
+///
+/// Symbols are split into those of the core kernel and those annotated
+/// as belonging to a module, each sorted by address. If `core` is
+/// `false`, core kernel symbols are skipped.
+fn parse_kallsyms<R>(reader: R, core: bool) -> Result<(Vec<Ksym>, Vec<Ksym>)>
+where
+    R: Read,
+{
+    let mut reader = BufReader::new(reader);
+    let mut line = String::new();
+    let mut core_syms = Vec::with_capacity(if core { DFL_KSYM_CAP } else { 0 });
+    let mut module_syms = Vec::new();
+    // The most recently encountered module. Symbols of a module are
+    // listed consecutively, so this is sufficient for sharing module
+    // names among all their symbols.
+    let mut last_module = None::<Rc<str>>;
+
+    loop {
+        let () = line.clear();
+        let sz = reader.read_line(&mut line)?;
+        if sz == 0 {
+            break
+        }
+
+        let mut tokens = line.split_ascii_whitespace();
+
+        #[rustfmt::skip]
+        let (addr, name) = {
+            let addr = if let Some(token) = tokens.next() { token } else { continue };
+            let _typ = if let Some(token) = tokens.next() { token } else { continue };
+            let name = if let Some(token) = tokens.next() { token } else { continue };
+            (addr, name)
+        };
+        let module = tokens
+            .next()
+            .and_then(|token| token.strip_prefix('['))
+            .and_then(|token| token.strip_suffix(']'));
+
+        if module.is_none() && !core {
+            continue
+        }
+
+        if let Ok(addr) = Addr::from_str_radix(addr, 16) {
+            if addr == 0 {
+                continue
+            }
+
+            if let Some(module) = module {
+                let module = match &last_module {
+                    Some(last) if last.deref() == module => Rc::clone(last),
+                    _ => {
+                        let module = Rc::<str>::from(module);
+                        last_module = Some(Rc::clone(&module));
+                        module
+                    }
+                };
+                let () = module_syms.push(Ksym::new(name, addr, Some(module)));
+            } else {
+                let () = core_syms.push(Ksym::new(name, addr, None));
+            }
+        }
+    }
+
+    let () = core_syms.sort_by_key(|a| a.addr());
+    let () = module_syms.sort_by_key(|a| a.addr());
+    Ok((core_syms, module_syms))
+}
+
+
+/// Read the list of currently loaded kernel modules.
+///
+/// The list is used for detecting module loads and unloads.
+fn read_modules() -> Option<Box<[u8]>> {
+    match fs::read(MODULES) {
+        Ok(modules) => Some(modules.into_boxed_slice()),
+        Err(err) => {
+            log::debug!("failed to read {MODULES}: {err}; not tracking kernel module changes");
+            None
+        }
+    }
+}
+
+
 /// The symbol resolver for /proc/kallsyms.
 ///
 /// The users should provide the path of kallsyms, so you can provide
 /// a copy from other devices.
 pub(crate) struct KsymResolver {
-    /// An index over `syms` that is sorted by name.
+    /// An index over all symbols that is sorted by name.
+    ///
+    /// Indexes refer to `core_syms`, followed by `module_syms`.
     by_name_idx: OnceCell<Box<[usize]>>,
-    syms: Box<[Ksym]>,
+    /// Symbols of the core kernel, sorted by address.
+    ///
+    /// These are shared with resolvers created when refreshing module
+    /// symbols.
+    core_syms: Rc<[Ksym]>,
+    /// Symbols belonging to kernel modules, sorted by address.
+    module_syms: Box<[Ksym]>,
+    /// The list of loaded kernel modules at the time module symbols
+    /// were read, if module changes are being tracked.
+    modules: Option<Box<[u8]>>,
     file_name: PathBuf,
     bpf_info_cache: BpfInfoCache,
 }
@@ -188,43 +319,96 @@ impl KsymResolver {
     where
         R: Read,
     {
-        let mut reader = BufReader::new(reader);
-        let mut line = String::new();
-        let mut syms = Vec::with_capacity(DFL_KSYM_CAP);
-
-        loop {
-            let () = line.clear();
-            let sz = reader.read_line(&mut line)?;
-            if sz == 0 {
-                break
-            }
+        // Module changes can only be tracked for the running kernel's
+        // symbols. Make sure to capture the list of modules before
+        // reading symbols, so that we never miss a change.
+        let modules = if path == Path::new(KALLSYMS) {
+            read_modules()
+        } else {
+            None
+        };
+        let (core_syms, module_syms) = parse_kallsyms(reader, true)?;
 
-            let mut tokens = line.split_ascii_whitespace();
+        let slf = Self {
+            core_syms: Rc::from(core_syms),
+            module_syms: module_syms.into_boxed_slice(),
+            modules,
+            by_name_idx: OnceCell::new(),
+            file_name: path.to_path_buf(),
+            bpf_info_cache: BpfInfoCache::default(),
+        };
+        Ok(slf)
+    }
 
-            #[rustfmt::skip]
-            let (addr, name) = {
-                let addr = if let Some(token) = tokens.next() { token } else { continue };
-                let _typ = if let Some(token) = tokens.next() { token } else { continue };
-                let name = if let Some(token) = tokens.next() { token } else { continue };
-                (addr, name)
-            };
+    /// Load kallsyms data from `file`, transparently decompressing it
+    /// if necessary.
+    fn load_from_file(path: &Path, file: &File) -> Result<Self> {
+        let mut reader = BufReader::new(file);
+        // The running kernel's `kallsyms` file is never compressed, but
+        // snapshots of it may be.
+        let compression = if path != Path::new(KALLSYMS) {
+            let buf = reader
+                .fill_buf()
+                .with_context(|| format!("failed to read `{}`", path.display()))?;
+            Compression::detect(buf)
+        } else {
+            None
+        };
 
-            if let Ok(addr) = Addr::from_str_radix(addr, 16) {
-                if addr == 0 {
-                    continue
-                }
+        if let Some(compression) = compression {
+            let mut data = Vec::new();
+            let _cnt = reader
+                .read_to_end(&mut data)
+                .with_context(|| format!("failed to read `{}`", path.display()))?;
+            let data = decompress(&data, compression)
+                .with_context(|| format!("failed to decompress `{}`", path.display()))?;
+            Self::load_from_reader(data.as_slice(), path)
+        } else {
+            Self::load_from_reader(reader, path)
+        }
+    }
 
-                let ksym = Ksym::new(name, addr);
-                let () = syms.push(ksym);
-            }
+    /// Check whether the set of loaded kernel modules changed since
+    /// module symbols were read and, if so, create a new resolver with
+    /// module symbols re-read from `file`.
+    ///
+    /// Core kernel symbols are shared with the new resolver. If module
+    /// changes are not tracked, `None` is returned.
+    pub(crate) fn refresh(&self, file: &File) -> Result<Option<Self>> {
+        let Some(modules) = &self.modules else {
+            return Ok(None)
+        };
+        let Some(current) = read_modules() else {
+            return Ok(None)
+        };
+        if current == *modules {
+            return Ok(None)
         }
 
-        let () = syms.sort_by_key(|a| a.addr());
+        log::debug!(
+            "kernel modules changed; refreshing module symbols from {}",
+            self.file_name.display()
+        );
+        let mut file = file;
+        let _offset = file.seek(SeekFrom::Start(0))?;
+        let slf = self.refresh_modules(file, current)?;
+        Ok(Some(slf))
+    }
+
+    /// Create a new resolver sharing core kernel symbols with this one,
+    /// but with module symbols read from `reader`.
+    fn refresh_modules<R>(&self, reader: R, modules: Box<[u8]>) -> Result<Self>
+    where
+        R: Read,
+    {
+        let (_core_syms, module_syms) = parse_kallsyms(reader, false)?;
 
         let slf = Self {
-            syms: syms.into_boxed_slice(),
+            core_syms: Rc::clone(&self.core_syms),
+            module_syms: module_syms.into_boxed_slice(),
+            modules: Some(modules),
             by_name_idx: OnceCell::new(),
-            file_name: path.to_path_buf(),
+            file_name: self.file_name.clone(),
             bpf_info_cache: BpfInfoCache::default(),
         };
         Ok(slf)
@@ -235,25 +419,54 @@ impl KsymResolver {
     where
         I: IntoIterator<Item = Kfunc>,
     {
+        let (core_syms, module_syms) = kfuncs
+            .into_iter()
+            .map(Ksym::Kfunc)
+            .partition::<Vec<_>, _>(|ksym| ksym.as_kfunc().unwrap().module.is_none());
+
         Self {
-            syms: kfuncs
-                .into_iter()
-                .map(Ksym::Kfunc)
-                .collect::<Vec<_>>()
-                .into_boxed_slice(),
+            core_syms: Rc::from(core_syms),
+            module_syms: module_syms.into_boxed_slice(),
+            modules: None,
             by_name_idx: OnceCell::new(),
             file_name: PathBuf::new(),
             bpf_info_cache: BpfInfoCache::default(),
         }
     }
 
+    /// Iterate over all symbols, core kernel ones first.
+    fn syms(&self) -> impl Iterator<Item = &Ksym> {
+        self.core_syms.iter().chain(self.module_syms.iter())
+    }
+
+    /// Retrieve the symbol at the given index into the combined list of
+    /// core kernel and module symbols.
+    fn sym(&self, idx: usize) -> &Ksym {
+        if let Some(idx) = idx.checked_sub(self.core_syms.len()) {
+            &self.module_syms[idx]
+        } else {
+            &self.core_syms[idx]
+        }
+    }
+
     fn find_ksym(&self, addr: Addr) -> Result<&Ksym, Reason> {
-        let result = find_match_or_lower_bound_by_key(&self.syms, addr, Ksym::addr)
-            .and_then(|idx| self.syms.get(idx));
+        fn find(syms: &[Ksym], addr: Addr) -> Option<&Ksym> {
+            find_match_or_lower_bound_by_key(syms, addr, Ksym::addr).and_then(|idx| syms.get(idx))
+        }
+
+        // Module symbols may be located below or above the core
+        // kernel's, depending on the architecture. We pick the closest
+        // candidate, giving preference to the core kernel.
+        let result = match (find(&self.core_syms, addr), find(&self.module_syms, addr)) {
+            (Some(core), Some(module)) if module.addr() > core.addr() => Some(module),
+            (Some(core), _) => Some(core),
+            (None, module) => module,
+        };
+
         match result {
             Some(sym) => Ok(sym),
             None => {
-                if self.syms.is_empty() {
+                if self.core_syms.is_empty() && self.module_syms.is_empty() {
                     Err(Reason::MissingSyms)
                 } else {
                     Err(Reason::UnknownAddr)
@@ -262,11 +475,12 @@ impl KsymResolver {
         }
     }
 
-    fn create_by_name_idx(syms: &[Ksym]) -> Box<[usize]> {
-        let mut by_name_idx = (0..syms.len()).collect::<Box<[_]>>();
+    fn create_by_name_idx(&self) -> Box<[usize]> {
+        let mut by_name_idx =
+            (0..self.core_syms.len() + self.module_syms.len()).collect::<Box<[_]>>();
         let () = by_name_idx.sort_by(|idx1, idx2| {
-            let sym1 = &syms[*idx1];
-            let sym2 = &syms[*idx2];
+            let sym1 = self.sym(*idx1);
+            let sym2 = self.sym(*idx2);
             sym1.name()
                 .cmp(sym2.name())
                 .then_with(|| sym1.addr().cmp(&sym2.addr()))
@@ -275,6 +489,79 @@ impl KsymResolver {
         by_name_idx
     }
 
+    /// Look up symbols by name, optionally restricted to those belonging
+    /// to the kernel module `module`.
+    fn find_addr_impl<'slf>(
+        &'slf self,
+        name: &str,
+        opts: &FindAddrOpts,
+        module: Option<&str>,
+    ) -> Result<Vec<SymInfo<'slf>>> {
+        if let SymType::Variable = opts.sym_type {
+            return Ok(Vec::new())
+        }
+
+        let by_name_idx = self.by_name_idx.get_or_init(|| self.create_by_name_idx());
+        let idx = if let Some(idx) =
+            find_lowest_match_by_key(by_name_idx, &name, |idx| self.sym(*idx).name())
+        {
+            idx
+        } else {
+            return Ok(Vec::new())
+        };
+
+        let syms = by_name_idx[idx..]
+            .iter()
+            .map(|idx| self.sym(*idx))
+            .take_while(|sym| sym.name() == name)
+            .filter(|sym| module.is_none() || sym.module() == module)
+            .map(SymInfo::try_from)
+            .collect::<Result<_>>()?;
+        Ok(syms)
+    }
+
+    /// Iterate over all symbols, optionally restricted to those
+    /// belonging to the kernel module `module`.
+    fn for_each_impl(
+        &self,
+        opts: &FindAddrOpts,
+        module: Option<&str>,
+        f: &mut ForEachFn<'_>,
+    ) -> Result<()> {
+        if let SymType::Variable = opts.sym_type {
+            return Ok(())
+        }
+
+        let syms = if module.is_some() {
+            // Core kernel symbols never belong to a module, so there
+            // is no point in looking at them.
+            Either::A(self.module_syms.iter())
+        } else {
+            Either::B(self.syms())
+        };
+
+        for sym in syms {
+            if module.is_some() && sym.module() != module {
+                continue
+            }
+
+            let sym = SymInfo::try_from(sym)?;
+            if let ControlFlow::Break(()) = f(&sym) {
+                return Ok(())
+            }
+        }
+        Ok(())
+    }
+
+    /// Create a view of this resolver that is restricted to the symbols
+    /// of the kernel module `module`.
+    pub(crate) fn for_module<'slf>(&'slf self, module: &'slf str) -> ModuleKsymResolver<'slf> {
+        ModuleKsymResolver {
+            resolver: self,
+            module,
+        }
+    }
+
     /// Retrieve the path to the kallsyms file used by this resolver.
     pub(crate) fn file_name(&self) -> &Path {
         &self.file_name
@@ -295,39 +582,11 @@ impl Symbolize for KsymResolver {
 
 impl Inspect for KsymResolver {
     fn find_addr<'slf>(&'slf self, name: &str, opts: &FindAddrOpts) -> Result<Vec<SymInfo<'slf>>> {
-        if let SymType::Variable = opts.sym_type {
-            return Ok(Vec::new())
-        }
-
-        let by_name_idx = self
-            .by_name_idx
-            .get_or_init(|| Self::create_by_name_idx(&self.syms));
-
-        let result =
-            find_match_or_lower_bound_by_key(by_name_idx, name, |idx| self.syms[*idx].name());
-        let syms = if let Some(idx) = result {
-            by_name_idx[idx..]
-                .iter()
-                .map(|idx| SymInfo::try_from(&self.syms[*idx]))
-                .collect::<Result<_>>()?
-        } else {
-            Vec::new()
-        };
-        Ok(syms)
+        self.find_addr_impl(name, opts, None)
     }
 
     fn for_each(&self, opts: &FindAddrOpts, f: &mut ForEachFn<'_>) -> Result<()> {
-        if let SymType::Variable = opts.sym_type {
-            return Ok(())
-        }
-
-        for sym in self.syms.iter() {
-            let sym = SymInfo::try_from(sym)?;
-            if let ControlFlow::Break(()) = f(&sym) {
-                return Ok(())
-            }
-        }
-        Ok(())
+        self.for_each_impl(opts, None, f)
     }
 }
 
@@ -338,6 +597,54 @@ impl Debug for KsymResolver {
 }
 
 
+/// A view of a [`KsymResolver`] that only covers the symbols of a
+/// single kernel module.
+#[derive(Debug)]
+pub(crate) struct ModuleKsymResolver<'ksym> {
+    resolver: &'ksym KsymResolver,
+    module: &'ksym str,
+}
+
+impl Inspect for ModuleKsymResolver<'_> {
+    fn find_addr<'slf>(&'slf self, name: &str, opts: &FindAddrOpts) -> Result<Vec<SymInfo<'slf>>> {
+        self.resolver.find_addr_impl(name, opts, Some(self.module))
+    }
+
+    fn for_each(&self, opts: &FindAddrOpts, f: &mut ForEachFn<'_>) -> Result<()> {
+        self.resolver.for_each_impl(opts, Some(self.module), f)
+    }
+}
+
+
+impl FileCache<RefCell<Rc<KsymResolver>>> {
+    /// Retrieve a `KsymResolver` for the kallsyms file at `path`.
+    ///
+    /// If auto reloading is enabled, module symbols of the running
+    /// kernel are refreshed when the set of loaded modules changed.
+    /// Resolvers handed out earlier stay valid, as they are reference
+    /// counted.
+    pub(crate) fn ksym_resolver(&self, path: &Path) -> Result<Rc<KsymResolver>> {
+        let (file, cell) = self.entry(path)?;
+        let resolver = cell.get_or_try_init(|| {
+            let resolver = KsymResolver::load_from_file(path, &*file.get()?)?;
+            Result::<_, Error>::Ok(RefCell::new(Rc::new(resolver)))
+        })?;
+
+        if self.auto_reload() {
+            // The kallsyms file itself does not convey changes in its
+            // meta data, so we check for kernel module changes
+            // explicitly.
+            let refreshed = resolver.borrow().refresh(&*file.get()?)?;
+            if let Some(refreshed) = refreshed {
+                *resolver.borrow_mut() = Rc::new(refreshed);
+            }
+        }
+        let resolver = Rc::clone(&resolver.borrow());
+        Ok(resolver)
+    }
+}
+
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -363,7 +670,9 @@ mod tests {
     #[test]
     fn debug_repr() {
         let resolver = KsymResolver {
-            syms: Box::default(),
+            core_syms: Rc::from([]),
+            module_syms: Box::default(),
+            modules: None,
             by_name_idx: OnceCell::new(),
             file_name: PathBuf::new(),
             bpf_info_cache: BpfInfoCache::default(),
@@ -373,6 +682,7 @@ mod tests {
         let kfunc = Kfunc {
             addr: 0x1337,
             name: Box::from("3l33t"),
+            module: None,
         };
         assert_ne!(format!("{kfunc:?}"), "");
     }
@@ -391,33 +701,113 @@ ffffffffc212d000 t ftrace_trampoline    [__builtin__ftrace]
 
         let resolver =
             KsymResolver::load_from_reader(&mut kallsyms.as_slice(), Path::new("<dummy>")).unwrap();
-        assert_eq!(resolver.syms.len(), 5);
+        assert_eq!(resolver.core_syms.len(), 0);
+        assert_eq!(resolver.module_syms.len(), 5);
 
         // Spot-check some of the parsed symbols for sanity.
-        let ksym = resolver.syms[2].as_kfunc().unwrap();
+        let ksym = resolver.module_syms[2].as_kfunc().unwrap();
         assert_eq!(&*ksym.name, "fuse_dev_init");
         assert_eq!(ksym.addr, 0xffffffffc0279010);
+        assert_eq!(ksym.module.as_deref(), Some("fuse"));
 
         #[cfg(feature = "bpf")]
         {
             use crate::kernel::bpf::BpfTag;
 
-            let prog = resolver.syms[1].as_bpf_prog().unwrap();
+            let prog = resolver.module_syms[1].as_bpf_prog().unwrap();
             assert_eq!(prog.addr(), 0xffffffffc003e9c8);
             assert_eq!(prog.name(), "kprobe__cap_capable");
             assert_eq!(
                 prog.tag(),
                 BpfTag::from([0x30, 0x30, 0x4e, 0x82, 0xb4, 0x03, 0x3e, 0xa3])
             );
+
+            let sym = prog
+                .resolve(prog.addr(), &FindSymOpts::Basic, &resolver.bpf_info_cache)
+                .unwrap();
+            assert_eq!(sym.name, "kprobe__cap_capable");
+            assert_eq!(sym.module, Some("bpf"));
         }
         #[cfg(not(feature = "bpf"))]
         {
-            let ksym = resolver.syms[1].as_kfunc().unwrap();
+            let ksym = resolver.module_syms[1].as_kfunc().unwrap();
             assert_eq!(&*ksym.name, "bpf_prog_30304e82b4033ea3_kprobe__cap_capable");
             assert_eq!(ksym.addr, 0xffffffffc003e9c8);
         }
     }
 
+    /// Check that we report the module a symbol belongs to and that
+    /// module symbols can be refreshed.
+    #[tag(miri)]
+    #[test]
+    fn module_symbols() {
+        let kallsyms = br#"ffffffff81000000 T _stext
+ffffffff81000100 T do_one_initcall
+ffffffffc0279010 T fuse_dev_init        [fuse]
+ffffffffc02791d0 T fuse_ctl_init        [fuse]
+"#;
+        let resolver =
+            KsymResolver::load_from_reader(&mut kallsyms.as_slice(), Path::new("<dummy>")).unwrap();
+        assert_eq!(resolver.core_syms.len(), 2);
+        assert_eq!(resolver.module_syms.len(), 2);
+        // We don't track module changes for anything but the running
+        // kernel's symbols.
+        assert_eq!(resolver.modules, None);
+
+        let sym = resolver
+            .find_sym(0xffffffff81000104, &FindSymOpts::Basic)
+            .unwrap()
+            .unwrap();
+        assert_eq!(sym.name, "do_one_initcall");
+        assert_eq!(sym.module, None);
+
+        let sym = resolver
+            .find_sym(0xffffffffc0279020, &FindSymOpts::Basic)
+            .unwrap()
+            .unwrap();
+        assert_eq!(sym.name, "fuse_dev_init");
+        assert_eq!(sym.module, Some("fuse"));
+
+        let opts = FindAddrOpts::default();
+        let syms = resolver.find_addr("fuse_ctl_init", &opts).unwrap();
+        assert!(
+            syms.iter()
+                .any(|sym| sym.name == "fuse_ctl_init" && sym.addr == 0xffffffffc02791d0),
+            "{syms:?}"
+        );
+
+        // Simulate an unload of `fuse` and a load of `ext4`.
+        let kallsyms = br#"ffffffff81000000 T _stext
+ffffffff81000100 T do_one_initcall
+ffffffffc0300000 T ext4_init_fs [ext4]
+"#;
+        let refreshed = resolver
+            .refresh_modules(&mut kallsyms.as_slice(), Box::from(*b"ext4"))
+            .unwrap();
+        assert!(Rc::ptr_eq(&resolver.core_syms, &refreshed.core_syms));
+        assert_eq!(refreshed.module_syms.len(), 1);
+        assert_eq!(refreshed.modules.as_deref(), Some(b"ext4".as_slice()));
+
+        let sym = refreshed
+            .find_sym(0xffffffffc0279020, &FindSymOpts::Basic)
+            .unwrap()
+            .unwrap();
+        assert_eq!(sym.name, "do_one_initcall");
+
+        let sym = refreshed
+            .find_sym(0xffffffffc0300010, &FindSymOpts::Basic)
+            .unwrap()
+            .unwrap();
+        assert_eq!(sym.name, "ext4_init_fs");
+        assert_eq!(sym.module, Some("ext4"));
+
+        let syms = refreshed.find_addr("fuse_ctl_init", &opts).unwrap();
+        assert!(
+            !syms.iter().any(|sym| sym.name == "fuse_ctl_init"),
+            "{syms:?}"
+        );
+    }
+
     /// Check that we can use a `KsymResolver` to find symbols.
     #[test]
     fn ksym_resolver_load_find() {
@@ -429,7 +819,7 @@ ffffffffc212d000 t ftrace_trampoline    [__builtin__ftrace]
         };
 
         assert!(
-            resolver.syms.len() > 10000,
+            resolver.core_syms.len() > 10000,
             "kallsyms seems to be unavailable or with all 0 addresses. (Check {KALLSYMS})"
         );
 
@@ -437,6 +827,8 @@ ffffffffc212d000 t ftrace_trampoline    [__builtin__ftrace]
             let opts = FindAddrOpts {
                 offset_in_file: false,
                 sym_type: SymType::Function,
+                code_info: false,
+                decl_file: None,
             };
             let found = resolver.find_addr(name, &opts).unwrap();
             assert!(
@@ -447,7 +839,7 @@ ffffffffc212d000 t ftrace_trampoline    [__builtin__ftrace]
 
 
         // Find the address of the symbol placed at the middle
-        let sym = &resolver.syms[resolver.syms.len() / 2];
+        let sym = &resolver.core_syms[resolver.core_syms.len() / 2];
         let addr = sym.addr();
         let found = resolver
             .find_sym(addr, &FindSymOpts::Basic)
@@ -460,7 +852,7 @@ ffffffffc212d000 t ftrace_trampoline    [__builtin__ftrace]
         assert!(resolver.find_sym(0, &FindSymOpts::Basic).unwrap().is_err());
 
         // Find the address of the last symbol
-        let sym = &resolver.syms.last().unwrap();
+        let sym = &resolver.core_syms.last().unwrap();
         let addr = sym.addr();
         let found = resolver
             .find_sym(addr, &FindSymOpts::Basic)
@@ -483,18 +875,22 @@ ffffffffc212d000 t ftrace_trampoline    [__builtin__ftrace]
             Kfunc {
                 addr: 0x123,
                 name: Box::from("1"),
+                module: None,
             },
             Kfunc {
                 addr: 0x123,
                 name: Box::from("1.5"),
+                module: None,
             },
             Kfunc {
                 addr: 0x1234,
                 name: Box::from("2"),
+                module: None,
             },
             Kfunc {
                 addr: 0x12345,
                 name: Box::from("3"),
+                module: None,
             },
         ]);
 
@@ -540,23 +936,27 @@ ffffffffc212d000 t ftrace_trampoline    [__builtin__ftrace]
             Kfunc {
                 addr: 0x123,
                 name: Box::from("j"),
+                module: None,
             },
             Kfunc {
                 addr: 0x123,
                 name: Box::from("b"),
+                module: None,
             },
             Kfunc {
                 addr: 0x1234,
                 name: Box::from("a"),
+                module: None,
             },
             Kfunc {
                 addr: 0x12345,
                 name: Box::from("z"),
+                module: None,
             },
         ]);
 
         let opts = FindAddrOpts::default();
-        let mut syms = Vec::with_capacity(resolver.syms.len());
+        let mut syms = Vec::with_capacity(resolver.syms().count());
         let () = resolver
             .for_each(&opts, &mut |sym| {
                 let () = syms.push(sym.name.to_string());
@@ -577,18 +977,22 @@ ffffffffc212d000 t ftrace_trampoline    [__builtin__ftrace]
             Kfunc {
                 addr: 0x123,
                 name: Box::from("j"),
+                module: None,
             },
             Kfunc {
                 addr: 0x123,
                 name: Box::from("b"),
+                module: None,
             },
             Kfunc {
                 addr: 0x1234,
                 name: Box::from("a"),
+                module: None,
             },
             Kfunc {
                 addr: 0x12345,
                 name: Box::from("z"),
+                module: None,
             },
         ]);
 
@@ -604,6 +1008,69 @@ ffffffffc212d000 t ftrace_trampoline    [__builtin__ftrace]
             .unwrap();
     }
 
+    /// Check that symbol look up by name only reports exact matches and
+    /// honors module restrictions.
+    #[tag(miri)]
+    #[test]
+    fn find_addr_by_name() {
+        let module = Rc::<str>::from("mod");
+        let resolver = KsymResolver::from_kfuncs([
+            Kfunc {
+                addr: 0x123,
+                name: Box::from("a"),
+                module: None,
+            },
+            Kfunc {
+                addr: 0x1234,
+                name: Box::from("b"),
+                module: None,
+            },
+            Kfunc {
+                addr: 0x12345,
+                name: Box::from("b"),
+                module: Some(Rc::clone(&module)),
+            },
+            Kfunc {
+                addr: 0x123456,
+                name: Box::from("c"),
+                module: Some(Rc::clone(&module)),
+            },
+        ]);
+
+        let opts = FindAddrOpts::default();
+        let syms = resolver.find_addr("a", &opts).unwrap();
+        assert_eq!(syms.len(), 1);
+        assert_eq!(syms[0].addr, 0x123);
+
+        let syms = resolver.find_addr("b", &opts).unwrap();
+        let addrs = syms.iter().map(|sym| sym.addr).collect::<Vec<_>>();
+        assert_eq!(addrs, vec![0x1234, 0x12345]);
+
+        let syms = resolver.find_addr("bb", &opts).unwrap();
+        assert_eq!(syms, Vec::new());
+
+        let module_resolver = resolver.for_module("mod");
+        let syms = module_resolver.find_addr("a", &opts).unwrap();
+        assert_eq!(syms, Vec::new());
+        let syms = module_resolver.find_addr("b", &opts).unwrap();
+        assert_eq!(syms.len(), 1);
+        assert_eq!(syms[0].addr, 0x12345);
+
+        let mut names = Vec::new();
+        let () = module_resolver
+            .for_each(&opts, &mut |sym| {
+                let () = names.push(sym.name.to_string());
+                ControlFlow::Continue(())
+            })
+            .unwrap();
+        let () = names.sort();
+        assert_eq!(names, vec!["b", "c"]);
+
+        let module_resolver = resolver.for_module("other");
+        let syms = module_resolver.find_addr("b", &opts).unwrap();
+        assert_eq!(syms, Vec::new());
+    }
+
     /// Benchmark the parsing of the kallsyms file.
     #[cfg(feature = "nightly")]
     #[bench]
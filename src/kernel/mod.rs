@@ -5,6 +5,8 @@ mod ksym;
 mod resolver;
 
 // TODO: KsymResolver should ideally be an implementation detail.
+pub(crate) use kaslr::find_kalsr_offset;
 pub(crate) use ksym::KsymResolver;
 pub(crate) use ksym::KALLSYMS;
+pub(crate) use ksym::MODULES;
 pub(crate) use resolver::KernelResolver;
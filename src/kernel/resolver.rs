@@ -4,7 +4,12 @@ use std::fmt::Result as FmtResult;
 use std::path::Path;
 use std::rc::Rc;
 
+use crate::addr_space::AddrSpace;
+use crate::addr_space::Arch;
+use crate::context;
 use crate::elf::ElfResolver;
+use crate::log;
+use crate::symbolize::CodeInfo;
 use crate::symbolize::FindSymOpts;
 use crate::symbolize::Reason;
 use crate::symbolize::ResolvedSym;
@@ -15,7 +20,6 @@ use crate::ErrorExt as _;
 use crate::IntoError as _;
 use crate::Result;
 
-use super::kaslr::find_kalsr_offset;
 use super::ksym::KsymResolver;
 
 
@@ -23,6 +27,13 @@ pub(crate) struct KernelResolver {
     ksym_resolver: Option<Rc<KsymResolver>>,
     elf_resolver: Option<Rc<ElfResolver>>,
     kaslr_offset: u64,
+    /// The architecture to use for filtering out addresses not
+    /// belonging to the kernel address space, if any.
+    ///
+    /// We only filter when symbolizing addresses of the running
+    /// kernel, as we can't make any assumptions about the layout of
+    /// others.
+    arch: Option<Arch>,
 }
 
 impl KernelResolver {
@@ -37,24 +48,37 @@ impl KernelResolver {
             ))
         }
 
-        let kaslr_offset = if let Some(kaslr_offset) = kaslr_offset {
-            kaslr_offset
+        let (kaslr_offset, arch) = if let Some(kaslr_offset) = kaslr_offset {
+            (kaslr_offset, None)
         } else {
-            find_kalsr_offset()
+            let kaslr_offset = context::kaslr_offset()
                 .context("failed to query system KASLR offset")?
-                .unwrap_or_default()
+                .unwrap_or_default();
+            (kaslr_offset, Arch::host())
         };
 
         Ok(KernelResolver {
             ksym_resolver,
             elf_resolver,
             kaslr_offset,
+            arch,
         })
     }
 }
 
 impl Symbolize for KernelResolver {
     fn find_sym(&self, addr: Addr, opts: &FindSymOpts) -> Result<Result<ResolvedSym<'_>, Reason>> {
+        if let Some(arch) = self.arch {
+            // Batches may contain user space addresses mixed in, for
+            // example. Those can't possibly be covered by the kernel
+            // and shouldn't cause the symbolization to fail.
+            let space = AddrSpace::classify(addr, arch);
+            if space != AddrSpace::Kernel {
+                log::debug!("address {addr:#x} is not a kernel address ({space:?})");
+                return Ok(Err(Reason::UnknownAddr))
+            }
+        }
+
         let elf_addr = || {
             addr.checked_sub(self.kaslr_offset).ok_or_invalid_input(|| {
                 format!(
@@ -85,6 +109,25 @@ impl Symbolize for KernelResolver {
             (None, None) => unreachable!(),
         }
     }
+
+    fn find_code_info(&self, addr: Addr, opts: &FindSymOpts) -> Result<Option<CodeInfo<'_>>> {
+        let elf_resolver = if let Some(elf_resolver) = self.elf_resolver.as_ref() {
+            elf_resolver
+        } else {
+            return Ok(None)
+        };
+
+        if let Some(arch) = self.arch {
+            if AddrSpace::classify(addr, arch) != AddrSpace::Kernel {
+                return Ok(None)
+            }
+        }
+
+        match addr.checked_sub(self.kaslr_offset) {
+            Some(elf_addr) => elf_resolver.find_code_info(elf_addr, opts),
+            None => Ok(None),
+        }
+    }
 }
 
 impl Debug for KernelResolver {
@@ -121,4 +164,18 @@ mod tests {
         let kernel = KernelResolver::new(Some(ksym), None, Some(0)).unwrap();
         assert_ne!(format!("{kernel:?}"), "");
     }
+
+    /// Check that addresses outside of the kernel address space are
+    /// reported as unknown when symbolizing the running kernel.
+    #[test]
+    fn non_kernel_addr_filtering() {
+        let ksym = Rc::new(KsymResolver::load_file_name(Path::new(KALLSYMS)).unwrap());
+        let mut kernel = KernelResolver::new(Some(ksym), None, Some(0)).unwrap();
+        kernel.arch = Some(Arch::X86_64);
+
+        for addr in [0x5555_5555_4000, 0xdead_beef_dead_beef] {
+            let result = kernel.find_sym(addr, &FindSymOpts::Basic).unwrap();
+            assert_eq!(result, Err(Reason::UnknownAddr));
+        }
+    }
 }
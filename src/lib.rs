@@ -66,34 +66,64 @@ extern crate test;
 
 #[macro_use]
 mod cfg;
+mod addr_space;
 #[cfg(feature = "breakpad")]
 mod breakpad;
+mod context;
+mod decompress;
 #[cfg(feature = "dwarf")]
 mod dwarf;
 mod elf;
 mod error;
 mod file_cache;
 #[cfg(feature = "gsym")]
-mod gsym;
+#[cfg_attr(docsrs, doc(cfg(feature = "gsym")))]
+pub mod gsym;
+mod index_cache;
 mod insert_map;
 pub mod inspect;
+mod intern;
+mod jitdump;
 mod kernel;
-mod maps;
+#[cfg(feature = "macho")]
+mod macho;
+pub mod maps;
 mod mmap;
 pub mod normalize;
 mod once;
+#[cfg(feature = "windows")]
+mod pe;
+#[cfg(feature = "perf")]
+#[cfg_attr(docsrs, doc(cfg(feature = "perf")))]
+pub mod perf;
 mod perf_map;
 mod pid;
+mod pool;
+mod render;
+#[cfg(feature = "bpf")]
+mod stack_event;
+#[cfg(unix)]
+mod sym_sock;
 pub mod symbolize;
 #[cfg(any(test, feature = "test"))]
 mod test_helper;
+#[cfg(any(test, feature = "test-utils"))]
+#[cfg_attr(docsrs, doc(cfg(feature = "test-utils")))]
+pub mod test_utils;
+#[cfg(feature = "async")]
+mod unblock;
+#[cfg(feature = "dwarf")]
+#[cfg_attr(docsrs, doc(cfg(feature = "dwarf")))]
+pub mod unwind;
 mod util;
+mod vdso;
 #[cfg(feature = "apk")]
 mod zip;
 
 use std::result;
 
 
+pub use crate::context::init;
 pub use crate::error::Error;
 pub use crate::error::ErrorExt;
 pub use crate::error::ErrorKind;
@@ -101,6 +131,8 @@ pub use crate::error::IntoError;
 pub use crate::mmap::Mmap;
 pub use crate::normalize::buildid::BuildId;
 pub use crate::pid::Pid;
+#[cfg(linux)]
+pub use crate::pid::PidFd;
 
 /// A result type using our [`Error`] by default.
 pub type Result<T, E = Error> = result::Result<T, E>;
@@ -112,6 +144,7 @@ pub type Addr = u64;
 
 /// The type of a symbol.
 #[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
 #[non_exhaustive]
 pub enum SymType {
     /// The symbol type is unspecified or unknown.
@@ -151,14 +184,38 @@ impl<T> From<T> for MaybeDefault<T> {
 pub mod helper {
     use super::*;
 
+    pub use crate::addr_space::AddrSpace;
+    pub use crate::addr_space::Arch;
+    pub use crate::normalize::buildid::breakpad_debug_id;
+    pub use crate::normalize::buildid::breakpad_pe_debug_id;
+    pub use crate::normalize::buildid::build_id_from_hex;
+    pub use crate::normalize::buildid::build_id_to_hex;
+    pub use crate::normalize::buildid::read_build_id_from_notes;
     pub use crate::normalize::buildid::read_elf_build_id;
     pub use crate::normalize::buildid::read_elf_build_id_from_mmap;
     pub use crate::normalize::ioctl::is_procmap_query_supported;
+    #[cfg(all(target_os = "linux", target_env = "gnu"))]
+    #[cfg_attr(docsrs, doc(cfg(all(target_os = "linux", target_env = "gnu"))))]
+    pub use crate::render::symbolize_current_backtrace;
+    pub use crate::render::symbolize_pid_addr;
 
     cfg_breakpad! {
+        pub use crate::breakpad::export_breakpad_sym;
         pub use crate::breakpad::BreakpadResolver;
     }
+    #[cfg(feature = "dwarf")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "dwarf")))]
+    pub use crate::dwarf::LineTableRow;
     pub use crate::elf::ElfResolver;
+    cfg_macho! {
+        pub use crate::macho::MachOResolver;
+    }
+    cfg_windows! {
+        pub use crate::pe::PeResolver;
+    }
+    #[cfg(feature = "bpf")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "bpf")))]
+    pub use crate::stack_event::StackEvent;
     cfg_gsym! {
         use std::path::Path;
         use crate::symbolize::Symbolize;
@@ -0,0 +1,5 @@
+mod parser;
+mod resolver;
+
+pub(crate) use resolver::find_dsym;
+pub use resolver::MachOResolver;
@@ -0,0 +1,796 @@
+//! Parsing logic for Mach-O files, as used on macOS.
+//!
+//! Only the bits necessary for symbolization are covered: universal
+//! ("fat") binary headers, segments and sections (used for translating
+//! between file offsets and virtual addresses and for accessing DWARF
+//! data in dSYM files), the `LC_SYMTAB` symbol table, and the
+//! `LC_UUID` identifier used for matching binaries with their dSYMs.
+
+use std::path::Path;
+
+use crate::mmap::Mmap;
+use crate::util::ReadRaw as _;
+use crate::Addr;
+use crate::Error;
+use crate::ErrorExt as _;
+use crate::IntoError as _;
+use crate::Result;
+use crate::SymType;
+
+
+/// The magic number of a 64 bit Mach-O file in host byte order.
+const MH_MAGIC_64: u32 = 0xfeedfacf;
+/// The magic number of a 64 bit Mach-O file in swapped byte order.
+const MH_CIGAM_64: u32 = 0xcffaedfe;
+/// The magic number of a 32 bit Mach-O file in host byte order.
+const MH_MAGIC: u32 = 0xfeedface;
+/// The magic number of a 32 bit Mach-O file in swapped byte order.
+const MH_CIGAM: u32 = 0xcefaedfe;
+/// The magic number of a universal binary, stored big endian.
+const FAT_MAGIC: u32 = 0xcafebabe;
+/// The magic number of a universal binary with 64 bit offsets, stored
+/// big endian.
+const FAT_MAGIC_64: u32 = 0xcafebabf;
+
+/// The size of a 64 bit Mach-O header, in bytes.
+const MACH_HEADER_64_SIZE: usize = 32;
+/// The size of a `fat_arch` entry, in bytes.
+const FAT_ARCH_SIZE: usize = 20;
+/// The size of a `fat_arch_64` entry, in bytes.
+const FAT_ARCH_64_SIZE: usize = 32;
+/// The size of a `section_64` entry, in bytes.
+const SECTION_64_SIZE: usize = 80;
+/// The size of an `nlist_64` entry, in bytes.
+const NLIST_64_SIZE: usize = 16;
+
+/// The load command describing a 64 bit segment.
+const LC_SEGMENT_64: u32 = 0x19;
+/// The load command describing the symbol table.
+const LC_SYMTAB: u32 = 0x2;
+/// The load command containing the image's UUID.
+const LC_UUID: u32 = 0x1b;
+
+/// Mask for the symbolic debugging ("stab") bits of `n_type`.
+const N_STAB: u8 = 0xe0;
+/// Mask for the type bits of `n_type`.
+const N_TYPE: u8 = 0x0e;
+/// The `n_type` value of symbols defined in a section.
+const N_SECT: u8 = 0x0e;
+
+/// Section attribute indicating that a section contains only
+/// instructions.
+const S_ATTR_PURE_INSTRUCTIONS: u32 = 0x8000_0000;
+/// Section attribute indicating that a section contains some
+/// instructions.
+const S_ATTR_SOME_INSTRUCTIONS: u32 = 0x0000_0400;
+
+/// The CPU type of the machine we are running on, if it is one that
+/// Mach-O files commonly target.
+// `CPU_TYPE_X86_64`
+#[cfg(target_arch = "x86_64")]
+const HOST_CPU_TYPE: Option<u32> = Some(0x0100_0007);
+// `CPU_TYPE_ARM64`
+#[cfg(target_arch = "aarch64")]
+const HOST_CPU_TYPE: Option<u32> = Some(0x0100_000c);
+#[cfg(not(any(target_arch = "x86_64", target_arch = "aarch64")))]
+const HOST_CPU_TYPE: Option<u32> = None;
+
+
+/// Read a big endian `u32`, as used in universal binary headers.
+#[inline]
+fn read_be_u32(data: &mut &[u8]) -> Option<u32> {
+    data.read_array().map(u32::from_be_bytes)
+}
+
+/// Read a big endian `u64`, as used in universal binary headers.
+#[inline]
+fn read_be_u64(data: &mut &[u8]) -> Option<u64> {
+    data.read_array().map(u64::from_be_bytes)
+}
+
+
+/// Convert a fixed size, NUL padded name into a byte slice.
+fn fixed_name(name: &[u8; 16]) -> &[u8] {
+    let len = name.iter().position(|b| *b == 0).unwrap_or(name.len());
+    &name[..len]
+}
+
+
+/// A slice of a universal binary.
+#[derive(Clone, Debug, PartialEq)]
+struct FatArch {
+    /// The CPU type the slice is meant for.
+    cpu_type: u32,
+    /// The offset of the slice in the file.
+    offset: u64,
+    /// The size of the slice.
+    size: u64,
+}
+
+
+/// A segment of a Mach-O file.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct Segment {
+    /// The segment's virtual address.
+    pub addr: u64,
+    /// The segment's size in memory.
+    pub size: u64,
+    /// The offset of the segment's data in the file.
+    pub file_off: u64,
+    /// The size of the segment's data in the file.
+    pub file_size: u64,
+}
+
+
+/// A section of a Mach-O file.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct Section {
+    /// The name of the segment containing the section.
+    pub segment: [u8; 16],
+    /// The name of the section.
+    pub name: [u8; 16],
+    /// The section's virtual address.
+    pub addr: u64,
+    /// The section's size.
+    pub size: u64,
+    /// The offset of the section's data in the file, or zero if the
+    /// section has no file backed data.
+    pub file_off: u32,
+    /// The section's type and attributes.
+    pub flags: u32,
+}
+
+impl Section {
+    /// Check whether the section contains code.
+    #[inline]
+    fn is_code(&self) -> bool {
+        self.flags & (S_ATTR_PURE_INSTRUCTIONS | S_ATTR_SOME_INSTRUCTIONS) != 0
+    }
+
+    /// Retrieve the address one past the section's last byte.
+    #[inline]
+    fn end(&self) -> Result<Addr> {
+        self.addr
+            .checked_add(self.size)
+            .ok_or_invalid_data(|| "Mach-O section address range overflows")
+    }
+}
+
+
+/// A symbol from a Mach-O file's symbol table.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct Symbol {
+    /// The name of the symbol, without the leading underscore added
+    /// by the compiler.
+    pub name: Box<str>,
+    /// The symbol's virtual address.
+    pub addr: Addr,
+    /// The size of the symbol.
+    ///
+    /// Mach-O does not record symbol sizes. We infer them from the
+    /// address of the next symbol in the same section.
+    pub size: usize,
+    /// The type of the symbol.
+    pub sym_type: SymType,
+}
+
+
+/// A parsed Mach-O file.
+///
+/// In case of a universal binary this type represents only a single
+/// of its slices.
+#[derive(Debug)]
+pub(crate) struct MachOFile {
+    /// The memory mapped slice of the file.
+    mmap: Mmap,
+    /// The offset of the slice in the file.
+    slice_off: u64,
+    /// The segments of the file.
+    segments: Box<[Segment]>,
+    /// The sections of the file, in load command order.
+    sections: Box<[Section]>,
+    /// The file's symbols, sorted by address.
+    symbols: Box<[Symbol]>,
+    /// The UUID of the file, if any.
+    uuid: Option<[u8; 16]>,
+}
+
+impl MachOFile {
+    /// Parse the Mach-O file at `path`.
+    ///
+    /// If the file is a universal binary and `uuid` is provided, the
+    /// slice with the given UUID is used. Otherwise the slice for the
+    /// host's CPU type is preferred, falling back to the first one.
+    pub(crate) fn open(path: &Path, uuid: Option<&[u8; 16]>) -> Result<Self> {
+        let mmap = Mmap::builder()
+            .open(path)
+            .with_context(|| format!("failed to memory map Mach-O file `{}`", path.display()))?;
+        Self::from_mmap(mmap, uuid)
+            .with_context(|| format!("failed to parse Mach-O file `{}`", path.display()))
+    }
+
+    /// Parse a Mach-O file from the provided memory mapping.
+    pub(crate) fn from_mmap(mmap: Mmap, uuid: Option<&[u8; 16]>) -> Result<Self> {
+        let magic = (&*mmap)
+            .read_array::<4>()
+            .ok_or_invalid_data(|| "failed to read Mach-O magic")?;
+        let slices = match u32::from_be_bytes(magic) {
+            FAT_MAGIC => Self::parse_fat(&mmap, false)?,
+            FAT_MAGIC_64 => Self::parse_fat(&mmap, true)?,
+            _ => return Self::parse(mmap, 0),
+        };
+
+        if let Some(uuid) = uuid {
+            for arch in &slices {
+                let slf = Self::parse_slice(&mmap, arch)?;
+                if slf.uuid.as_ref() == Some(uuid) {
+                    return Ok(slf)
+                }
+            }
+            return Err(Error::with_not_found(
+                "universal binary does not contain slice with matching UUID",
+            ))
+        }
+
+        let arch = slices
+            .iter()
+            .find(|arch| Some(arch.cpu_type) == HOST_CPU_TYPE)
+            .or_else(|| slices.first())
+            .ok_or_invalid_data(|| "universal binary does not contain any slices")?;
+        Self::parse_slice(&mmap, arch)
+    }
+
+    /// Parse the header of a universal binary, returning its slices.
+    fn parse_fat(data: &[u8], is_64: bool) -> Result<Vec<FatArch>> {
+        let mut data = data;
+        let _magic = read_be_u32(&mut data);
+        let count = read_be_u32(&mut data)
+            .ok_or_invalid_data(|| "failed to read universal binary header")?;
+
+        (0..count)
+            .map(|_| {
+                let entry_size = if is_64 {
+                    FAT_ARCH_64_SIZE
+                } else {
+                    FAT_ARCH_SIZE
+                };
+                let mut entry = data
+                    .read_slice(entry_size)
+                    .ok_or_invalid_data(|| "failed to read universal binary slice entry")?;
+                let cpu_type = read_be_u32(&mut entry);
+                let _cpu_subtype = read_be_u32(&mut entry);
+                let (offset, size) = if is_64 {
+                    (read_be_u64(&mut entry), read_be_u64(&mut entry))
+                } else {
+                    (
+                        read_be_u32(&mut entry).map(u64::from),
+                        read_be_u32(&mut entry).map(u64::from),
+                    )
+                };
+                let ((cpu_type, offset), size) = cpu_type
+                    .zip(offset)
+                    .zip(size)
+                    .ok_or_invalid_data(|| "failed to read universal binary slice entry")?;
+                Ok(FatArch {
+                    cpu_type,
+                    offset,
+                    size,
+                })
+            })
+            .collect()
+    }
+
+    /// Parse a single slice of a universal binary.
+    fn parse_slice(mmap: &Mmap, arch: &FatArch) -> Result<Self> {
+        let end = arch
+            .offset
+            .checked_add(arch.size)
+            .ok_or_invalid_data(|| "universal binary slice bounds are invalid")?;
+        let slice = mmap.constrain(arch.offset..end).ok_or_invalid_data(|| {
+            format!(
+                "universal binary slice ({:#x}..{end:#x}) is out of bounds",
+                arch.offset
+            )
+        })?;
+        Self::parse(slice, arch.offset)
+    }
+
+    /// Parse a (thin) Mach-O file, located at offset `slice_off` of
+    /// the file it is contained in.
+    fn parse(mmap: Mmap, slice_off: u64) -> Result<Self> {
+        let mut hdr = &*mmap;
+        let magic = hdr
+            .read_u32()
+            .ok_or_invalid_data(|| "failed to read Mach-O header")?;
+        match magic {
+            MH_MAGIC_64 => (),
+            MH_CIGAM_64 => {
+                return Err(Error::with_unsupported(
+                    "Mach-O file byte order does not match host",
+                ))
+            }
+            MH_MAGIC | MH_CIGAM => {
+                return Err(Error::with_unsupported(
+                    "32 bit Mach-O files are not supported",
+                ))
+            }
+            _ => {
+                return Err(Error::with_invalid_data(format!(
+                    "Mach-O magic ({magic:#x}) is invalid"
+                )))
+            }
+        }
+
+        let _cpu_type = hdr.read_u32();
+        let _cpu_subtype = hdr.read_u32();
+        let _file_type = hdr.read_u32();
+        let cmd_count = hdr.read_u32();
+        let cmds_size = hdr.read_u32();
+        let (cmd_count, cmds_size) = cmd_count
+            .zip(cmds_size)
+            .ok_or_invalid_data(|| "failed to read Mach-O header")?;
+        let mut cmds = mmap
+            .get(MACH_HEADER_64_SIZE..)
+            .and_then(|mut data| data.read_slice(cmds_size as usize))
+            .ok_or_invalid_data(|| "failed to read Mach-O load commands")?;
+
+        let mut segments = Vec::new();
+        let mut sections = Vec::new();
+        let mut symtab = None;
+        let mut uuid = None;
+
+        for _ in 0..cmd_count {
+            let mut cmd_data = cmds;
+            let cmd = cmd_data.read_u32();
+            let cmd_size = cmd_data.read_u32();
+            let (cmd, cmd_size) = cmd
+                .zip(cmd_size)
+                .ok_or_invalid_data(|| "failed to read Mach-O load command")?;
+            let mut cmd_data = cmds
+                .read_slice(cmd_size as usize)
+                .and_then(|data| data.get(8..))
+                .ok_or_invalid_data(|| "Mach-O load command size is invalid")?;
+
+            match cmd {
+                LC_SEGMENT_64 => {
+                    let _name = cmd_data.read_array::<16>();
+                    let addr = cmd_data.read_u64();
+                    let size = cmd_data.read_u64();
+                    let file_off = cmd_data.read_u64();
+                    let file_size = cmd_data.read_u64();
+                    let _max_prot = cmd_data.read_u32();
+                    let _init_prot = cmd_data.read_u32();
+                    let section_count = cmd_data.read_u32();
+                    let _flags = cmd_data.read_u32();
+                    let ((((addr, size), file_off), file_size), section_count) = addr
+                        .zip(size)
+                        .zip(file_off)
+                        .zip(file_size)
+                        .zip(section_count)
+                        .ok_or_invalid_data(|| "failed to read Mach-O segment")?;
+                    let () = segments.push(Segment {
+                        addr,
+                        size,
+                        file_off,
+                        file_size,
+                    });
+
+                    for _ in 0..section_count {
+                        let mut sect = cmd_data
+                            .read_slice(SECTION_64_SIZE)
+                            .ok_or_invalid_data(|| "failed to read Mach-O section")?;
+                        let name = sect.read_array::<16>();
+                        let segment = sect.read_array::<16>();
+                        let addr = sect.read_u64();
+                        let size = sect.read_u64();
+                        let file_off = sect.read_u32();
+                        let _align = sect.read_u32();
+                        let _reloc_off = sect.read_u32();
+                        let _reloc_count = sect.read_u32();
+                        let flags = sect.read_u32();
+                        let (((((name, segment), addr), size), file_off), flags) = name
+                            .zip(segment)
+                            .zip(addr)
+                            .zip(size)
+                            .zip(file_off)
+                            .zip(flags)
+                            .ok_or_invalid_data(|| "failed to read Mach-O section")?;
+                        let () = sections.push(Section {
+                            segment,
+                            name,
+                            addr,
+                            size,
+                            file_off,
+                            flags,
+                        });
+                    }
+                }
+                LC_SYMTAB => {
+                    let sym_off = cmd_data.read_u32();
+                    let sym_count = cmd_data.read_u32();
+                    let str_off = cmd_data.read_u32();
+                    let str_size = cmd_data.read_u32();
+                    symtab = Some(
+                        sym_off
+                            .zip(sym_count)
+                            .zip(str_off.zip(str_size))
+                            .ok_or_invalid_data(|| "failed to read Mach-O symbol table command")?,
+                    );
+                }
+                LC_UUID => {
+                    uuid = Some(
+                        cmd_data
+                            .read_array::<16>()
+                            .ok_or_invalid_data(|| "failed to read Mach-O UUID")?,
+                    );
+                }
+                _ => (),
+            }
+        }
+
+        let symbols = if let Some(((sym_off, sym_count), (str_off, str_size))) = symtab {
+            Self::parse_symtab(&mmap, &sections, sym_off, sym_count, str_off, str_size)?
+        } else {
+            Box::default()
+        };
+
+        let slf = Self {
+            mmap,
+            slice_off,
+            segments: segments.into_boxed_slice(),
+            sections: sections.into_boxed_slice(),
+            symbols,
+            uuid,
+        };
+        Ok(slf)
+    }
+
+    /// Parse the symbol table, retaining all symbols defined in
+    /// sections of the file.
+    fn parse_symtab(
+        data: &[u8],
+        sections: &[Section],
+        sym_off: u32,
+        sym_count: u32,
+        str_off: u32,
+        str_size: u32,
+    ) -> Result<Box<[Symbol]>> {
+        let mut syms = data
+            .get(sym_off as usize..)
+            .and_then(|mut data| data.read_slice(sym_count as usize * NLIST_64_SIZE))
+            .ok_or_invalid_data(|| "failed to read Mach-O symbol table")?;
+        let strs = data
+            .get(str_off as usize..)
+            .and_then(|mut data| data.read_slice(str_size as usize))
+            .ok_or_invalid_data(|| "failed to read Mach-O string table")?;
+
+        // Symbols along with the index of the section they belong to.
+        let mut symbols = Vec::<(Symbol, usize)>::new();
+        while let Some(mut sym) = syms.read_slice(NLIST_64_SIZE) {
+            let name_off = sym.read_u32();
+            let type_ = sym.read_u8();
+            let sect = sym.read_u8();
+            let _desc = sym.read_u16();
+            let value = sym.read_u64();
+            let (((name_off, type_), sect), value) = name_off
+                .zip(type_)
+                .zip(sect)
+                .zip(value)
+                .ok_or_invalid_data(|| "failed to read Mach-O symbol")?;
+
+            if type_ & N_STAB != 0 || type_ & N_TYPE != N_SECT {
+                continue
+            }
+            // Section numbers are one based.
+            let sect_idx = match usize::from(sect).checked_sub(1) {
+                Some(idx) => idx,
+                None => continue,
+            };
+            let section = match sections.get(sect_idx) {
+                Some(section) => section,
+                None => continue,
+            };
+            // Some symbols, such as the one for the Mach-O header
+            // itself, are not actually located inside the section they
+            // reference. They are of no interest to us.
+            if value < section.addr || value >= section.end()? {
+                continue
+            }
+
+            let name = strs
+                .get(name_off as usize..)
+                .and_then(|mut data| data.read_cstr())
+                .ok_or_invalid_data(|| "failed to read Mach-O symbol name")?;
+            let name = name.to_str().map_err(Error::with_invalid_data)?;
+            // The compiler prefixes C-level symbol names with an
+            // underscore.
+            let name = name.strip_prefix('_').unwrap_or(name);
+            if name.is_empty() {
+                continue
+            }
+
+            let sym = Symbol {
+                name: Box::from(name),
+                addr: value as Addr,
+                size: 0,
+                sym_type: if section.is_code() {
+                    SymType::Function
+                } else {
+                    SymType::Variable
+                },
+            };
+            let () = symbols.push((sym, sect_idx));
+        }
+
+        let () = symbols.sort_by_key(|(sym, _sect_idx)| sym.addr);
+
+        // Infer symbol sizes: each symbol extends up to the next one at
+        // a higher address in the same section or the end of said
+        // section. For each section we track the address of the last
+        // symbol seen (walking backwards) along with its end address.
+        let mut next = vec![None::<(Addr, Addr)>; sections.len()];
+        for (sym, sect_idx) in symbols.iter_mut().rev() {
+            // SANITY: We only retained symbols with valid section
+            //         indexes above.
+            let section = &sections[*sect_idx];
+            let end = match next[*sect_idx] {
+                // Aliases share the end address of the symbol they alias.
+                Some((addr, end)) if addr == sym.addr => end,
+                Some((addr, _end)) => addr,
+                None => section.end()?,
+            };
+            sym.size = usize::try_from(end - sym.addr).unwrap_or(usize::MAX);
+            next[*sect_idx] = Some((sym.addr, end));
+        }
+
+        let symbols = symbols
+            .into_iter()
+            .map(|(sym, _sect_idx)| sym)
+            .collect::<Box<[_]>>();
+        Ok(symbols)
+    }
+
+    /// Retrieve the data of the section `name` in segment `segment`.
+    pub(crate) fn section_data(&self, segment: &[u8], name: &[u8]) -> Option<&[u8]> {
+        let section = self.sections.iter().find(|section| {
+            fixed_name(&section.segment) == segment && fixed_name(&section.name) == name
+        })?;
+        if section.file_off == 0 {
+            return None
+        }
+        let start = section.file_off as usize;
+        let end = start.checked_add(usize::try_from(section.size).ok()?)?;
+        self.mmap.get(start..end)
+    }
+
+    /// Find the symbol containing `addr`.
+    pub(crate) fn find_sym(&self, addr: Addr) -> Option<&Symbol> {
+        let idx = self.symbols.partition_point(|sym| sym.addr <= addr);
+        // Walk backwards over aliases starting at the same address,
+        // reporting the first one in symbol table order.
+        let sym = self.symbols.get(idx.checked_sub(1)?)?;
+        let sym = self.symbols[..idx]
+            .iter()
+            .rev()
+            .take_while(|other| other.addr == sym.addr)
+            .last()
+            .unwrap_or(sym);
+        let end = sym.addr.checked_add(sym.size as Addr)?;
+        (addr < end).then_some(sym)
+    }
+
+    /// Convert a virtual address into an offset in the file.
+    pub(crate) fn virt_offset_to_file_offset(&self, addr: Addr) -> Option<u64> {
+        self.segments.iter().find_map(|segment| {
+            let size = segment.size.min(segment.file_size);
+            let offset = addr.checked_sub(segment.addr)?;
+            (offset < size).then(|| self.slice_off + segment.file_off + offset)
+        })
+    }
+
+    /// Convert an offset in the file into a virtual address.
+    pub(crate) fn file_offset_to_virt_offset(&self, file_offset: u64) -> Option<Addr> {
+        let file_offset = file_offset.checked_sub(self.slice_off)?;
+        self.segments.iter().find_map(|segment| {
+            let size = segment.size.min(segment.file_size);
+            let offset = file_offset.checked_sub(segment.file_off)?;
+            // The first segment of executables (`__PAGEZERO`) is not
+            // backed by any file data and should never match.
+            (offset < size).then(|| segment.addr + offset)
+        })
+    }
+
+    /// Retrieve the file's symbols, sorted by address.
+    #[inline]
+    pub(crate) fn symbols(&self) -> &[Symbol] {
+        &self.symbols
+    }
+
+    /// Retrieve the file's UUID, if any.
+    #[inline]
+    pub(crate) fn uuid(&self) -> Option<&[u8; 16]> {
+        self.uuid.as_ref()
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::io::Write as _;
+
+    use tempfile::tempfile;
+    use test_log::test;
+
+    use crate::ErrorKind;
+
+
+    /// Memory map the provided data.
+    fn mmap(data: &[u8]) -> Mmap {
+        let mut file = tempfile().unwrap();
+        let () = file.write_all(data).unwrap();
+        Mmap::map(&file).unwrap()
+    }
+
+
+    /// Check that we properly translate between file offsets and
+    /// virtual addresses.
+    #[test]
+    fn offset_translation() {
+        let macho = MachOFile {
+            mmap: mmap(b""),
+            slice_off: 0x4000,
+            segments: Box::new([
+                // `__PAGEZERO`
+                Segment {
+                    addr: 0,
+                    size: 0x1_0000_0000,
+                    file_off: 0,
+                    file_size: 0,
+                },
+                Segment {
+                    addr: 0x1_0000_0000,
+                    size: 0x4000,
+                    file_off: 0,
+                    file_size: 0x4000,
+                },
+                Segment {
+                    addr: 0x1_0000_4000,
+                    size: 0x8000,
+                    file_off: 0x4000,
+                    file_size: 0x1000,
+                },
+            ]),
+            sections: Box::default(),
+            symbols: Box::default(),
+            uuid: None,
+        };
+
+        assert_eq!(macho.virt_offset_to_file_offset(0x1000), None);
+        assert_eq!(
+            macho.virt_offset_to_file_offset(0x1_0000_0010),
+            Some(0x4010)
+        );
+        assert_eq!(
+            macho.virt_offset_to_file_offset(0x1_0000_4fff),
+            Some(0x8fff)
+        );
+        // Past the segment's file backed data.
+        assert_eq!(macho.virt_offset_to_file_offset(0x1_0000_5000), None);
+
+        assert_eq!(macho.file_offset_to_virt_offset(0x10), None);
+        assert_eq!(
+            macho.file_offset_to_virt_offset(0x4010),
+            Some(0x1_0000_0010)
+        );
+        assert_eq!(
+            macho.file_offset_to_virt_offset(0x8fff),
+            Some(0x1_0000_4fff)
+        );
+        assert_eq!(macho.file_offset_to_virt_offset(0x9000), None);
+    }
+
+    /// Check that we infer symbol sizes and handle aliases correctly.
+    #[test]
+    fn symbol_lookup() {
+        let sym = |name: &str, addr, size| Symbol {
+            name: Box::from(name),
+            addr,
+            size,
+            sym_type: SymType::Function,
+        };
+        let macho = MachOFile {
+            mmap: mmap(b""),
+            slice_off: 0,
+            segments: Box::default(),
+            sections: Box::default(),
+            symbols: Box::new([
+                sym("foo", 0x1000, 0x10),
+                sym("foo_alias", 0x1000, 0x10),
+                sym("bar", 0x1010, 0x20),
+            ]),
+            uuid: None,
+        };
+
+        assert_eq!(macho.find_sym(0xfff), None);
+        assert_eq!(&*macho.find_sym(0x1000).unwrap().name, "foo");
+        assert_eq!(&*macho.find_sym(0x100f).unwrap().name, "foo");
+        assert_eq!(&*macho.find_sym(0x1010).unwrap().name, "bar");
+        assert_eq!(macho.find_sym(0x1030), None);
+    }
+
+    /// Check that sections with overflowing address ranges are
+    /// rejected when parsing the symbol table.
+    #[test]
+    fn overflowing_section() {
+        let section = Section {
+            segment: *b"__TEXT\0\0\0\0\0\0\0\0\0\0",
+            name: *b"__text\0\0\0\0\0\0\0\0\0\0",
+            addr: u64::MAX - 0xf,
+            size: 0x20,
+            file_off: 0,
+            flags: S_ATTR_PURE_INSTRUCTIONS,
+        };
+
+        // A single `nlist_64` entry followed by the string table.
+        let mut data = Vec::new();
+        let () = data.extend_from_slice(&1u32.to_ne_bytes());
+        let () = data.push(N_SECT);
+        let () = data.push(1);
+        let () = data.extend_from_slice(&0u16.to_ne_bytes());
+        let () = data.extend_from_slice(&(u64::MAX - 0x8).to_ne_bytes());
+        let () = data.extend_from_slice(b"\0_foo\0");
+
+        let err = MachOFile::parse_symtab(&data, &[section], 0, 1, 16, 6).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidData);
+    }
+
+    /// Make sure that symbol lookup does not overflow for symbols
+    /// extending to the very end of the address space.
+    #[test]
+    fn overflowing_symbol_lookup() {
+        let macho = MachOFile {
+            mmap: mmap(b""),
+            slice_off: 0,
+            segments: Box::default(),
+            sections: Box::default(),
+            symbols: Box::new([Symbol {
+                name: Box::from("foo"),
+                addr: u64::MAX - 0xf,
+                size: 0x20,
+                sym_type: SymType::Function,
+            }]),
+            uuid: None,
+        };
+
+        assert_eq!(macho.find_sym(u64::MAX - 0x8), None);
+    }
+
+    /// Make sure that we reject data that is not a Mach-O file.
+    #[test]
+    fn invalid_data() {
+        let err = MachOFile::from_mmap(mmap(b"\x7fELF\x02\x01\x01\x00"), None).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidData);
+
+        let err = MachOFile::from_mmap(mmap(&MH_MAGIC.to_ne_bytes()), None).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::Unsupported);
+
+        // A universal binary without any slices.
+        let mut data = FAT_MAGIC.to_be_bytes().to_vec();
+        let () = data.extend_from_slice(&0u32.to_be_bytes());
+        let err = MachOFile::from_mmap(mmap(&data), None).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidData);
+
+        // A universal binary with a slice that is out of bounds.
+        let mut data = FAT_MAGIC.to_be_bytes().to_vec();
+        let () = data.extend_from_slice(&1u32.to_be_bytes());
+        let () = data.extend_from_slice(&0x0100_0007u32.to_be_bytes());
+        let () = data.extend_from_slice(&3u32.to_be_bytes());
+        let () = data.extend_from_slice(&0x1000u32.to_be_bytes());
+        let () = data.extend_from_slice(&0x1000u32.to_be_bytes());
+        let () = data.extend_from_slice(&12u32.to_be_bytes());
+        let err = MachOFile::from_mmap(mmap(&data), None).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidData);
+    }
+}
@@ -0,0 +1,463 @@
+use std::borrow::Cow;
+use std::fmt::Debug;
+use std::fmt::Formatter;
+use std::fmt::Result as FmtResult;
+#[cfg(feature = "dwarf")]
+use std::mem;
+use std::ops::ControlFlow;
+use std::path::Path;
+use std::path::PathBuf;
+
+#[cfg(feature = "dwarf")]
+use crate::dwarf::Units;
+use crate::inspect::FindAddrOpts;
+use crate::inspect::ForEachFn;
+use crate::inspect::Inspect;
+use crate::inspect::SymInfo;
+use crate::symbolize::CodeInfo;
+use crate::symbolize::FindSymOpts;
+use crate::symbolize::Reason;
+use crate::symbolize::ResolvedSym;
+use crate::symbolize::SrcLang;
+use crate::symbolize::Symbolize;
+use crate::symbolize::TranslateFileOffset;
+use crate::Addr;
+#[cfg(feature = "dwarf")]
+use crate::Error;
+use crate::Result;
+use crate::SymType;
+
+use super::parser::MachOFile;
+use super::parser::Symbol;
+
+
+/// The segment containing DWARF sections in dSYM files.
+#[cfg(feature = "dwarf")]
+const DWARF_SEGMENT: &[u8] = b"__DWARF";
+
+
+/// Find the DWARF file inside the dSYM bundle belonging to the Mach-O
+/// file at `path`, if one exists.
+///
+/// dSYM bundles are conventionally located next to the binary, named
+/// after it with a `.dSYM` suffix.
+pub(crate) fn find_dsym(path: &Path) -> Option<PathBuf> {
+    let name = path.file_name()?;
+    let mut bundle = path.as_os_str().to_os_string();
+    let () = bundle.push(".dSYM");
+    let dsym = PathBuf::from(bundle)
+        .join("Contents")
+        .join("Resources")
+        .join("DWARF")
+        .join(name);
+    dsym.is_file().then_some(dsym)
+}
+
+/// Convert a DWARF section name into the name used in Mach-O files
+/// (e.g., `.debug_info` becomes `__debug_info`).
+#[cfg(feature = "dwarf")]
+fn macho_section_name(name: &str) -> Vec<u8> {
+    let name = name.strip_prefix('.').unwrap_or(name);
+    let mut macho_name = b"__".to_vec();
+    let () = macho_name.extend_from_slice(name.as_bytes());
+    // Mach-O section names are limited to 16 bytes.
+    let () = macho_name.truncate(16);
+    macho_name
+}
+
+
+/// A symbol resolver for Mach-O files, as used on macOS.
+///
+/// Symbols are read from the file's `LC_SYMTAB` symbol table. If a
+/// dSYM bundle is available, DWARF debug information contained in it is
+/// used as well (if the `dwarf` feature is enabled). Universal ("fat")
+/// binaries are supported, in which case the slice for the host's
+/// architecture is used, if present, and the first slice otherwise.
+///
+/// All addresses handled by this type are virtual addresses as they
+/// appear in the file, i.e., before any ASLR slide got applied.
+pub struct MachOResolver {
+    /// The parsed DWARF data from the dSYM file.
+    // SAFETY: We must not hand out references with a 'static lifetime to
+    //         this member. Rather, they should never outlive `self`.
+    //         Furthermore, this member has to be listed before `_dsym`
+    //         to make sure we never end up with a dangling reference.
+    #[cfg(feature = "dwarf")]
+    units: Option<Units<'static>>,
+    /// The dSYM file in use, if any.
+    #[cfg(feature = "dwarf")]
+    _dsym: Option<MachOFile>,
+    /// The path of the dSYM file in use, if any.
+    #[cfg(feature = "dwarf")]
+    dsym_path: Option<PathBuf>,
+    /// Whether to report fully qualified function names.
+    #[cfg(feature = "dwarf")]
+    qualified_names: bool,
+    /// The parsed Mach-O file.
+    macho: MachOFile,
+    /// The path of the Mach-O file in use.
+    path: PathBuf,
+}
+
+impl MachOResolver {
+    /// Create a `MachOResolver` that loads data from the provided
+    /// Mach-O file.
+    ///
+    /// A dSYM bundle located next to the file is used for debug
+    /// information, if present.
+    pub fn open<P>(path: P) -> Result<Self>
+    where
+        P: AsRef<Path>,
+    {
+        let path = path.as_ref();
+        Self::open_impl(path, find_dsym(path).as_deref(), false)
+    }
+
+    /// Create a `MachOResolver` that loads data from the provided
+    /// Mach-O file, using debug information from the given dSYM file.
+    ///
+    /// `dsym` should refer to the DWARF file contained in a dSYM bundle
+    /// (i.e., `<bundle>.dSYM/Contents/Resources/DWARF/<name>`).
+    pub fn open_with_dsym<P, Q>(path: P, dsym: Q) -> Result<Self>
+    where
+        P: AsRef<Path>,
+        Q: AsRef<Path>,
+    {
+        Self::open_impl(path.as_ref(), Some(dsym.as_ref()), false)
+    }
+
+    /// Create a `MachOResolver` for the Mach-O file at `path`, using
+    /// debug information from `dsym_path`, if provided.
+    ///
+    /// If `qualified_names` is `true`, names of functions found in
+    /// debug information are reported fully qualified.
+    pub(crate) fn open_impl(
+        path: &Path,
+        dsym_path: Option<&Path>,
+        qualified_names: bool,
+    ) -> Result<Self> {
+        let macho = MachOFile::open(path, None)?;
+
+        #[cfg(feature = "dwarf")]
+        let (dsym, units) = if let Some(dsym_path) = dsym_path {
+            let dsym = MachOFile::open(dsym_path, macho.uuid())?;
+            if dsym.uuid() != macho.uuid() {
+                return Err(Error::with_invalid_input(format!(
+                    "dSYM file `{}` does not match Mach-O file `{}` (UUID differs)",
+                    dsym_path.display(),
+                    path.display()
+                )))
+            }
+
+            // SAFETY: We own the `MachOFile` and make sure that it stays
+            //         around while the `Units` object uses it. Its data
+            //         are memory mapped and do not move along with it.
+            //         As such, it is fine to conjure a 'static lifetime
+            //         here.
+            let static_dsym = unsafe { mem::transmute::<&MachOFile, &'static MachOFile>(&dsym) };
+            let units = Units::load(None, |section| {
+                let name = macho_section_name(section.name());
+                let data = static_dsym
+                    .section_data(DWARF_SEGMENT, &name)
+                    .unwrap_or(&[]);
+                Ok(data)
+            })?;
+            (Some(dsym), Some(units))
+        } else {
+            (None, None)
+        };
+        // Without DWARF support there is nothing we could use a dSYM
+        // file for.
+        #[cfg(not(feature = "dwarf"))]
+        let _dsym_path = dsym_path;
+        #[cfg(not(feature = "dwarf"))]
+        let _qualified_names = qualified_names;
+
+        let slf = Self {
+            #[cfg(feature = "dwarf")]
+            units,
+            #[cfg(feature = "dwarf")]
+            _dsym: dsym,
+            #[cfg(feature = "dwarf")]
+            dsym_path: dsym_path.map(Path::to_path_buf),
+            #[cfg(feature = "dwarf")]
+            qualified_names,
+            macho,
+            path: path.to_path_buf(),
+        };
+        Ok(slf)
+    }
+
+    fn find_symtab_sym(&self, addr: Addr) -> Result<ResolvedSym<'_>, Reason> {
+        let sym = self.macho.find_sym(addr).ok_or_else(|| {
+            if self.macho.symbols().is_empty() {
+                Reason::MissingSyms
+            } else {
+                Reason::UnknownAddr
+            }
+        })?;
+
+        let sym = ResolvedSym {
+            name: &sym.name,
+            addr: sym.addr,
+            size: Some(sym.size),
+            // The symbol table does not carry any source code language
+            // information.
+            lang: SrcLang::Unknown,
+            code_info: None,
+            inlined: Box::new([]),
+            inlined_truncated: false,
+            section: None,
+            module: None,
+        };
+        Ok(sym)
+    }
+
+    fn sym_info<'slf>(&'slf self, sym: &'slf Symbol, opts: &FindAddrOpts) -> SymInfo<'slf> {
+        SymInfo {
+            name: Cow::Borrowed(&sym.name),
+            addr: sym.addr,
+            size: Some(sym.size),
+            sym_type: sym.sym_type,
+            file_offset: opts
+                .offset_in_file
+                .then(|| self.macho.virt_offset_to_file_offset(sym.addr))
+                .flatten(),
+            obj_file_name: Some(Cow::Borrowed(&self.path)),
+            code_info: None,
+        }
+    }
+
+    fn matches(sym: &Symbol, opts: &FindAddrOpts) -> bool {
+        match opts.sym_type {
+            SymType::Undefined => true,
+            sym_type => sym.sym_type == sym_type,
+        }
+    }
+}
+
+impl Symbolize for MachOResolver {
+    #[cfg_attr(feature = "tracing", crate::log::instrument(fields(addr = format_args!("{addr:#x}"))))]
+    fn find_sym(&self, addr: Addr, opts: &FindSymOpts) -> Result<Result<ResolvedSym<'_>, Reason>> {
+        #[cfg(feature = "dwarf")]
+        if let Some(units) = &self.units {
+            return units.find_sym(addr, opts, self.qualified_names, || {
+                Ok(self.find_symtab_sym(addr))
+            })
+        }
+
+        // The symbol table does not carry any source code or inlining
+        // information.
+        let _opts = opts;
+        Ok(self.find_symtab_sym(addr))
+    }
+
+    fn find_code_info(&self, addr: Addr, opts: &FindSymOpts) -> Result<Option<CodeInfo<'_>>> {
+        #[cfg(feature = "dwarf")]
+        if let Some(units) = &self.units {
+            return units.find_code_info(addr, opts)
+        }
+
+        let _addr = addr;
+        let _opts = opts;
+        Ok(None)
+    }
+}
+
+impl TranslateFileOffset for MachOResolver {
+    fn file_offset_to_virt_offset(&self, file_offset: u64) -> Result<Option<Addr>> {
+        Ok(self.macho.file_offset_to_virt_offset(file_offset))
+    }
+}
+
+impl Inspect for MachOResolver {
+    fn find_addr<'slf>(&'slf self, name: &str, opts: &FindAddrOpts) -> Result<Vec<SymInfo<'slf>>> {
+        let syms = self
+            .macho
+            .symbols()
+            .iter()
+            .filter(|sym| &*sym.name == name && Self::matches(sym, opts))
+            .map(|sym| self.sym_info(sym, opts))
+            .collect::<Vec<_>>();
+        Ok(syms)
+    }
+
+    fn for_each(&self, opts: &FindAddrOpts, f: &mut ForEachFn<'_>) -> Result<()> {
+        for sym in self.macho.symbols() {
+            if Self::matches(sym, opts) {
+                let sym = self.sym_info(sym, opts);
+                if let ControlFlow::Break(()) = f(&sym) {
+                    return Ok(())
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Debug for MachOResolver {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        write!(f, "Mach-O {}", self.path.display())?;
+        #[cfg(feature = "dwarf")]
+        if let Some(dsym_path) = &self.dsym_path {
+            write!(f, " ({})", dsym_path.display())?;
+        }
+        Ok(())
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::ffi::OsStr;
+
+    use test_log::test;
+
+    use crate::ErrorKind;
+
+
+    fn test_macho(name: &str) -> PathBuf {
+        Path::new(&env!("CARGO_MANIFEST_DIR"))
+            .join("data")
+            .join(name)
+    }
+
+
+    /// Exercise the `Debug` representation of various types.
+    #[test]
+    fn debug_repr() {
+        let resolver = MachOResolver::open(test_macho("test-macho")).unwrap();
+        let dbg = format!("{resolver:?}");
+        assert!(dbg.starts_with("Mach-O"), "{dbg}");
+        assert!(dbg.contains("test-macho"), "{dbg}");
+    }
+
+    /// Check that we can symbolize addresses using a Mach-O file's
+    /// symbol table only.
+    #[test]
+    fn symbolization_symtab() {
+        let resolver = MachOResolver::open_impl(&test_macho("test-macho"), None, false).unwrap();
+        let syms = resolver.find_addr("add", &FindAddrOpts::default()).unwrap();
+        assert_eq!(syms.len(), 1);
+        let add = syms[0].to_owned();
+        assert_eq!(add.sym_type, SymType::Function);
+
+        let syms = resolver
+            .find_addr("main", &FindAddrOpts::default())
+            .unwrap();
+        let main = syms[0].to_owned();
+        // `add` immediately precedes `main` and its size is inferred
+        // from that.
+        assert!(add.addr < main.addr);
+        assert_eq!(add.size, Some((main.addr - add.addr) as usize));
+
+        let sym = resolver
+            .find_sym(add.addr + 1, &FindSymOpts::CodeInfo)
+            .unwrap()
+            .unwrap();
+        assert_eq!(sym.name, "add");
+        assert_eq!(sym.addr, add.addr);
+        assert_eq!(sym.code_info, None);
+
+        let err = resolver
+            .find_sym(0, &FindSymOpts::Basic)
+            .unwrap()
+            .unwrap_err();
+        assert_eq!(err, Reason::UnknownAddr);
+
+        // Translating the file offset of a function should yield its
+        // address.
+        let opts = FindAddrOpts {
+            offset_in_file: true,
+            ..Default::default()
+        };
+        let syms = resolver.find_addr("main", &opts).unwrap();
+        let offset = syms[0].file_offset.unwrap();
+        assert_eq!(
+            resolver.file_offset_to_virt_offset(offset).unwrap(),
+            Some(main.addr)
+        );
+
+        // There are no variables in the file.
+        let opts = FindAddrOpts {
+            sym_type: SymType::Variable,
+            ..Default::default()
+        };
+        let syms = resolver.find_addr("main", &opts).unwrap();
+        assert!(syms.is_empty());
+    }
+
+    /// Check that we use DWARF information from a dSYM bundle.
+    #[cfg(feature = "dwarf")]
+    #[test]
+    fn symbolization_dsym() {
+        let resolver = MachOResolver::open(test_macho("test-macho")).unwrap();
+        let syms = resolver.find_addr("add", &FindAddrOpts::default()).unwrap();
+        let add = syms[0].to_owned();
+
+        let opts = FindSymOpts::CodeInfo;
+        let sym = resolver.find_sym(add.addr + 2, &opts).unwrap().unwrap();
+        assert_eq!(sym.name, "add");
+        assert_eq!(sym.addr, add.addr);
+        assert_eq!(sym.lang, SrcLang::Unknown);
+        let code_info = sym.code_info.unwrap();
+        assert_eq!(code_info.file, OsStr::new("test-macho.c"));
+        assert_eq!(code_info.line, Some(2));
+
+        let syms = resolver
+            .find_addr("main", &FindAddrOpts::default())
+            .unwrap();
+        let main = &syms[0];
+        let sym = resolver.find_sym(main.addr, &opts).unwrap().unwrap();
+        assert_eq!(sym.name, "main");
+        assert_eq!(sym.code_info.unwrap().line, Some(5));
+        let sym = resolver.find_sym(main.addr + 1, &opts).unwrap().unwrap();
+        assert_eq!(sym.name, "main");
+        assert_eq!(sym.code_info.unwrap().line, Some(6));
+    }
+
+    /// Check that we pick the expected slice of a universal binary.
+    #[cfg(target_arch = "x86_64")]
+    #[test]
+    fn universal_binary() {
+        let thin = MachOResolver::open(test_macho("test-macho")).unwrap();
+        let fat = MachOResolver::open(test_macho("test-macho-fat")).unwrap();
+
+        let opts = FindAddrOpts {
+            offset_in_file: true,
+            ..Default::default()
+        };
+        let thin_main = thin.find_addr("main", &opts).unwrap()[0].to_owned();
+        let fat_main = fat.find_addr("main", &opts).unwrap()[0].to_owned();
+        assert_eq!(thin_main.addr, fat_main.addr);
+        // The x86_64 slice is not the first one in the file, so file
+        // offsets have to differ.
+        assert_ne!(thin_main.file_offset, fat_main.file_offset);
+        assert_eq!(
+            fat.file_offset_to_virt_offset(fat_main.file_offset.unwrap())
+                .unwrap(),
+            Some(fat_main.addr)
+        );
+        assert_eq!(
+            fat.file_offset_to_virt_offset(thin_main.file_offset.unwrap())
+                .unwrap(),
+            None
+        );
+    }
+
+    /// Check that a dSYM file not belonging to the Mach-O file is
+    /// rejected.
+    #[cfg(feature = "dwarf")]
+    #[test]
+    fn mismatching_dsym() {
+        let dsym = test_macho("test-macho.dSYM")
+            .join("Contents")
+            .join("Resources")
+            .join("DWARF")
+            .join("test-macho");
+        let err = MachOResolver::open_with_dsym(test_macho("test-macho-arm64"), dsym).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidInput);
+    }
+}
@@ -1,7 +1,25 @@
+//! Functionality for parsing `/proc/<pid>/maps` files.
+//!
+//! ```no_run
+//! use blazesym::maps;
+//! use blazesym::Pid;
+//!
+//! let entries = maps::parse(&Pid::Slf).unwrap();
+//! for entry in entries {
+//!     let entry = entry.unwrap();
+//!     if maps::is_symbolization_relevant(&entry) {
+//!         println!("{:#x?}: {:?}", entry.range, entry.path_name);
+//!     }
+//! }
+//! ```
+
+use std::borrow::Cow;
+use std::collections::VecDeque;
 use std::fmt::Debug;
 use std::fmt::Display;
 use std::fmt::Formatter;
 use std::fmt::Result as FmtResult;
+use std::fs;
 use std::fs::File;
 use std::io::BufRead;
 use std::io::BufReader;
@@ -13,13 +31,18 @@ use std::ops::BitAndAssign;
 use std::ops::BitOr;
 use std::ops::BitOrAssign;
 use std::ops::Range;
+#[cfg(unix)]
+use std::os::unix::fs::MetadataExt as _;
+use std::path::Path;
 use std::path::PathBuf;
+use std::str;
+use std::str::FromStr as _;
 
 use crate::util;
 use crate::util::bytes_to_path;
 use crate::util::from_radix_16;
 use crate::util::split_bytes;
-use crate::util::trim_ascii;
+use crate::util::trim_ascii_start;
 use crate::util::ReadRaw as _;
 use crate::Addr;
 use crate::BuildId;
@@ -44,11 +67,68 @@ pub struct EntryPath {
     /// parsed. This path has been sanitized and no longer contains any
     /// `(deleted)` suffixes.
     pub symbolic_path: PathBuf,
+    /// Whether the file backing the proc maps entry has been deleted
+    /// (or replaced) since it got mapped, as indicated by a
+    /// `(deleted)` suffix.
+    pub deleted: bool,
     /// The struct is non-exhaustive and open to extension.
     #[doc(hidden)]
     pub _non_exhaustive: (),
 }
 
+impl EntryPath {
+    /// Retrieve the path through which to access the file backing the
+    /// proc maps entry.
+    ///
+    /// The `maps_file` path is used if `map_files` is `true`. It is
+    /// also used if the file has been deleted, because in that case
+    /// `symbolic_path` refers to nothing or to a different file (e.g.,
+    /// an upgraded binary). Otherwise `symbolic_path` is used, resolved
+    /// relative to `root`, if provided.
+    pub(crate) fn access_path(&self, map_files: bool, root: Option<&Path>) -> Cow<'_, Path> {
+        if map_files || self.deleted {
+            Cow::Borrowed(&self.maps_file)
+        } else if let Some(root) = root {
+            // `symbolic_path` is absolute, so we can't just `join` it.
+            let mut path = root.as_os_str().to_os_string();
+            let () = path.push(self.symbolic_path.as_os_str());
+            Cow::Owned(PathBuf::from(path))
+        } else {
+            Cow::Borrowed(&self.symbolic_path)
+        }
+    }
+}
+
+
+/// Retrieve the path to the root directory of the process with the
+/// given `pid`, as seen from within its mount namespace.
+///
+/// [`None`] is returned if the process shares our root directory, in
+/// which case symbolic paths can be used as-is, or if its root
+/// directory is inaccessible (e.g., because the process has exited).
+#[cfg(unix)]
+pub(crate) fn proc_root(pid: &Pid) -> Option<PathBuf> {
+    let root = PathBuf::from(format!("/proc/{pid}/root"));
+    let theirs = fs::metadata(&root).ok()?;
+    let ours = fs::metadata("/").ok()?;
+
+    if (theirs.dev(), theirs.ino()) == (ours.dev(), ours.ino()) {
+        None
+    } else {
+        Some(root)
+    }
+}
+
+/// Retrieve the path to the root directory of the process with the
+/// given `pid`, as seen from within its mount namespace.
+///
+/// Processes cannot have a root directory different from ours on this
+/// platform, so [`None`] is always returned.
+#[cfg(not(unix))]
+pub(crate) fn proc_root(_pid: &Pid) -> Option<PathBuf> {
+    None
+}
+
 
 /// The "pathname" component in a proc maps entry. See `proc(5)` section
 /// `/proc/[pid]/maps`.
@@ -64,7 +144,6 @@ pub enum PathName {
 }
 
 impl PathName {
-    #[cfg(test)]
     pub(crate) fn as_path(&self) -> Option<&EntryPath> {
         match self {
             Self::Path(path) => Some(path),
@@ -83,16 +162,25 @@ impl PathName {
 
 
 /// A type encapsulating the permissions of/for an entity.
-#[derive(Copy, Clone, Debug, Default, PartialEq)]
+///
+/// Permissions can be combined and tested using bitwise operators,
+/// e.g., `perm & Perm::X == Perm::X` checks for executability.
+#[derive(Copy, Clone, Debug, Default, Eq, Hash, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
 #[repr(transparent)]
-pub(crate) struct Perm(u8);
+pub struct Perm(u8);
 
 impl Perm {
+    /// The permission to read.
     pub const R: Perm = Perm(0b100);
+    /// The permission to write.
     pub const W: Perm = Perm(0b010);
+    /// The permission to execute.
     pub const X: Perm = Perm(0b001);
+    /// The permissions to read and write.
     #[cfg(test)]
     pub const RW: Perm = Perm(0b110);
+    /// The permissions to read and execute.
     #[cfg(test)]
     pub const RX: Perm = Perm(0b101);
 }
@@ -143,13 +231,23 @@ impl Display for Perm {
 }
 
 
+/// An entry in a proc maps file, describing a single virtual memory
+/// area (VMA) of a process.
 #[derive(Clone, PartialEq)]
-pub(crate) struct MapsEntry {
+pub struct MapsEntry {
     /// The virtual address range covered by this entry.
     pub range: Range<Addr>,
+    /// The permissions of the memory area.
     pub perm: Perm,
+    /// The offset into the backing file (or other entity) at which the
+    /// memory area starts.
     pub offset: u64,
+    /// The inode of the backing file, or `0` if there is none.
+    pub inode: u64,
+    /// The "pathname" component of the entry, if any.
     pub path_name: Option<PathName>,
+    /// The build ID of the backing file, if it was retrieved alongside
+    /// the entry.
     pub build_id: Option<BuildId<'static>>,
 }
 
@@ -166,6 +264,7 @@ impl Debug for MapsEntry {
             range,
             perm,
             offset,
+            inode,
             path_name,
             build_id: _,
         } = self;
@@ -174,6 +273,7 @@ impl Debug for MapsEntry {
             .field(stringify!(range), &format_args!("{range:#x?}"))
             .field(stringify!(perm), &format_args!("{perm}"))
             .field(stringify!(offset), &format_args!("{offset:#x}"))
+            .field(stringify!(inode), &inode)
             .field(stringify!(path_name), &path_name)
             .finish()
     }
@@ -182,15 +282,18 @@ impl Debug for MapsEntry {
 
 pub(crate) fn parse_path_name(
     path: &[u8],
-    pid: Pid,
+    pid: &Pid,
     vma_start: Addr,
     vma_end: Addr,
 ) -> Result<Option<PathName>> {
     let path_name = match path {
         [] => None,
         [b'/', ..] => {
-            let symbolic_path =
-                bytes_to_path(path.strip_suffix(b" (deleted)").unwrap_or(path))?.to_path_buf();
+            let (path, deleted) = match path.strip_suffix(b" (deleted)") {
+                Some(path) => (path, true),
+                None => (path, false),
+            };
+            let symbolic_path = bytes_to_path(path)?.to_path_buf();
             // Make sure to resolve the potentially symbolic PID so that
             // it have more of a meaning in case of remote symbolization
             // use cases.
@@ -200,6 +303,7 @@ pub(crate) fn parse_path_name(
             Some(PathName::Path(EntryPath {
                 maps_file,
                 symbolic_path,
+                deleted,
                 _non_exhaustive: (),
             }))
         }
@@ -214,6 +318,39 @@ pub(crate) fn parse_path_name(
     Ok(path_name)
 }
 
+/// Undo the escaping the kernel applies to paths reported in proc maps.
+///
+/// The kernel emits the path of a file backed mapping verbatim, with
+/// the exception of newline characters, which get replaced with the
+/// octal escape sequence `\012` (see `show_map_vma` and `mangle_path`
+/// in the kernel sources). Notably, spaces and backslashes are *not*
+/// escaped, meaning that a path literally containing the character
+/// sequence `\012` cannot be distinguished from one containing a
+/// newline. We err on the side of the newline interpretation.
+fn unescape_path(path: &[u8]) -> Cow<'_, [u8]> {
+    const ESCAPED_NEWLINE: &[u8] = b"\\012";
+
+    if !path
+        .windows(ESCAPED_NEWLINE.len())
+        .any(|window| window == ESCAPED_NEWLINE)
+    {
+        return Cow::Borrowed(path)
+    }
+
+    let mut unescaped = Vec::with_capacity(path.len());
+    let mut remainder = path;
+    while !remainder.is_empty() {
+        if remainder.starts_with(ESCAPED_NEWLINE) {
+            let () = unescaped.push(b'\n');
+            remainder = &remainder[ESCAPED_NEWLINE.len()..];
+        } else {
+            let () = unescaped.push(remainder[0]);
+            remainder = &remainder[1..];
+        }
+    }
+    Cow::Owned(unescaped)
+}
+
 fn parse_mode_str(mut mode: &[u8]) -> Option<Perm> {
     let mut perm = Perm::default();
     if mode.read_u8()? == b'r' {
@@ -229,7 +366,7 @@ fn parse_mode_str(mut mode: &[u8]) -> Option<Perm> {
 }
 
 /// Parse a line of a proc maps file.
-fn parse_maps_line<'line>(line: &'line [u8], pid: Pid) -> Result<MapsEntry> {
+fn parse_maps_line<'line>(line: &'line [u8], pid: &Pid) -> Result<MapsEntry> {
     let full_line = line;
 
     let split_once_opt = |line: &'line [u8]| -> Option<(&'line [u8], &'line [u8])> {
@@ -307,15 +444,36 @@ fn parse_maps_line<'line>(line: &'line [u8], pid: Pid) -> Result<MapsEntry> {
     let (_dev, line) = split_once(line, "device component")?;
     // Note that by design, a path may not be present and so we may not be able
     // to successfully split.
-    let path_str = split_once_opt(line)
-        .map(|(_inode, line)| trim_ascii(line))
-        .unwrap_or(b"");
-    let path_name = parse_path_name(path_str, pid, loaded_addr, end_addr)?;
+    let (inode_str, path_str) = split_once_opt(line)
+        .map(|(inode, line)| {
+            // The path is padded with spaces for alignment purposes,
+            // but otherwise emitted as-is. That means that it may
+            // contain (and even end in) spaces, so we only strip the
+            // padding and the line terminator, but nothing else.
+            let line = trim_ascii_start(line);
+            (inode, line.strip_suffix(b"\n").unwrap_or(line))
+        })
+        .unwrap_or((line, b""));
+    let inode = str::from_utf8(inode_str)
+        .ok()
+        .and_then(|inode| u64::from_str(inode).ok())
+        .ok_or_else(|| {
+            Error::new(
+                ErrorKind::InvalidData,
+                format!(
+                    "encountered malformed inode component in proc maps line: {}",
+                    String::from_utf8_lossy(full_line)
+                ),
+            )
+        })?;
+    let path_str = unescape_path(path_str);
+    let path_name = parse_path_name(&path_str, pid, loaded_addr, end_addr)?;
 
     let entry = MapsEntry {
         range: (loaded_addr..end_addr),
         perm,
         offset,
+        inode,
         path_name,
         build_id: None,
     };
@@ -346,7 +504,7 @@ where
                     // There shouldn't be any empty lines, but we'd just ignore them. We
                     // need to trim anyway.
                     if !self.line.is_empty() {
-                        let result = parse_maps_line(&self.line, self.pid);
+                        let result = parse_maps_line(&self.line, &self.pid);
                         break Some(result)
                     }
                 }
@@ -357,7 +515,11 @@ where
 
 
 /// Parse a proc maps file from the provided reader.
-pub(crate) fn parse_file<R>(reader: R, pid: Pid) -> impl Iterator<Item = Result<MapsEntry>>
+///
+/// `pid` should identify the process that the proc maps file belongs
+/// to. It is used for constructing paths to the files backing
+/// individual entries (see [`EntryPath::maps_file`]).
+pub fn parse_file<R>(reader: R, pid: Pid) -> impl Iterator<Item = Result<MapsEntry>>
 where
     R: Read,
 {
@@ -371,36 +533,246 @@ where
 }
 
 /// Parse the maps file for the process with the given PID.
-pub(crate) fn parse(pid: Pid) -> Result<impl Iterator<Item = Result<MapsEntry>>> {
+///
+/// Entries are reported in the order in which they appear in the file,
+/// i.e., sorted by start address.
+pub fn parse(pid: &Pid) -> Result<impl Iterator<Item = Result<MapsEntry>>> {
     let path = format!("/proc/{pid}/maps");
     let file =
         File::open(&path).with_context(|| format!("failed to open proc maps file {path}"))?;
-    let iter = parse_file(file, pid);
+    // Make sure that the file we opened actually belongs to the process
+    // in question.
+    let () = pid.check_alive()?;
+    let iter = parse_file(file, pid.clone());
     Ok(iter)
 }
 
-/// A helper function checking whether a `MapsEntry` has relevance to
-/// symbolization efforts.
-pub(crate) fn filter_relevant(entry: &MapsEntry) -> bool {
+/// Check whether a [`MapsEntry`] has relevance to symbolization
+/// efforts.
+///
+/// Relevant are readable or executable entries. Besides file backed
+/// ones, that includes anonymous entries (which may contain JIT
+/// compiled code) and pseudo-modules such as the vDSO. Other special
+/// mappings (e.g., `[heap]` or `[stack]`) don't contain symbolizable
+/// code, but are kept so that addresses in them can be reported with a
+/// reason more telling than that of an unmapped address.
+pub fn is_symbolization_relevant(entry: &MapsEntry) -> bool {
     // Only readable (r---) or executable (--x-) entries are of relevance.
     // NB: Please keep this logic in sync with flags being used by
     //     `procmap_query`.
-    if (entry.perm & (Perm::R | Perm::X)) == Perm::default() {
-        return false
+    (entry.perm & (Perm::R | Perm::X)) != Perm::default()
+}
+
+/// Check whether the path of a proc maps entry refers to shared memory
+/// or to a hugetlbfs backed mapping, as opposed to a "regular" file.
+///
+/// Such mappings are commonly used by just-in-time compilers and by
+/// DPDK-style applications for code, but they do not have any ELF
+/// structure that we could make sense of. Recognized are:
+/// - POSIX shared memory objects (`/dev/shm/...`)
+/// - System V shared memory segments (`/SYSV<key>`)
+/// - `memfd_create(2)` backed mappings (`/memfd:<name>`)
+/// - anonymous `MAP_HUGETLB` mappings (`/anon_hugepage`)
+/// - files on hugetlbfs mounted at its conventional location
+///   (`/dev/hugepages/...`)
+///
+/// `path` is expected to be the symbolic path, i.e., without any
+/// `(deleted)` suffix.
+pub(crate) fn is_shared_mem(path: &Path) -> bool {
+    const PREFIXES: [&[u8]; 5] = [
+        b"/dev/shm/",
+        b"/dev/hugepages/",
+        b"/SYSV",
+        b"/memfd:",
+        b"/anon_hugepage",
+    ];
+
+    match util::path_to_bytes(path) {
+        Ok(path) => PREFIXES.iter().any(|prefix| path.starts_with(prefix)),
+        Err(_) => false,
+    }
+}
+
+/// Create a copy of `entry` restricted to `range`.
+///
+/// `range` is expected to be a sub-range of the entry's range.
+fn restrict_entry(entry: &MapsEntry, range: Range<Addr>) -> MapsEntry {
+    debug_assert!(entry.range.start <= range.start && range.end <= entry.range.end);
+
+    // Note that any `map_files` path still refers to the original
+    // range. That's fine, as it identifies the same file.
+    MapsEntry {
+        offset: entry.offset + (range.start - entry.range.start),
+        range,
+        ..entry.clone()
+    }
+}
+
+/// Create copies of `entry` covering the parts of its range that are
+/// outside of `range`.
+fn cut_entry(entry: &MapsEntry, range: &Range<Addr>) -> impl Iterator<Item = MapsEntry> {
+    let left = (entry.range.start < range.start)
+        .then(|| restrict_entry(entry, entry.range.start..range.start.min(entry.range.end)));
+    let right = (range.end < entry.range.end)
+        .then(|| restrict_entry(entry, range.end.max(entry.range.start)..entry.range.end));
+    left.into_iter().chain(right)
+}
+
+/// Check whether the (later) entry `new` takes precedence over the
+/// (earlier) entry `old` in the range where both overlap.
+fn takes_precedence(new: &MapsEntry, old: &MapsEntry) -> bool {
+    // Executable entries are the ones we are most likely to be asked
+    // about, so they always win over non-executable ones. Otherwise
+    // the later entry wins, as it likely reflects the more recent
+    // state.
+    new.perm & Perm::X == Perm::X || old.perm & Perm::X != Perm::X
+}
+
+
+/// An iterator adapter resolving overlaps between proc maps entries.
+///
+/// Proc maps entries never overlap when captured atomically, but the
+/// kernel generates the file piece by piece. Concurrent changes to the
+/// address space (e.g., `MAP_FIXED` mappings replacing existing ones
+/// or `mremap` calls) may hence result in overlapping or duplicate
+/// entries. This adapter makes sure that every address is covered by
+/// at most one entry, by trimming or splitting entries as necessary.
+/// For addresses covered by multiple entries:
+/// - an executable entry wins over a non-executable one
+/// - otherwise the entry appearing later wins
+///
+/// Entries are expected to be sorted by start address, as they are in
+/// a proc maps file. Parts of entries starting before an already
+/// reported entry ended are dropped.
+#[derive(Debug)]
+struct NonOverlapping<I> {
+    entries: I,
+    /// Non-overlapping entries sorted by start address that may still
+    /// be affected by subsequent ones.
+    pending: Vec<MapsEntry>,
+    /// Entries (and errors) ready to be reported.
+    ready: VecDeque<Result<MapsEntry>>,
+    /// The end address of the last entry made ready.
+    ready_end: Addr,
+}
+
+impl<I> NonOverlapping<I> {
+    fn new(entries: I) -> Self {
+        Self {
+            entries,
+            pending: Vec::new(),
+            ready: VecDeque::new(),
+            ready_end: 0,
+        }
+    }
+
+    /// Mark all pending entries ending at or before `addr` as ready.
+    fn flush(&mut self, addr: Addr) {
+        let count = self
+            .pending
+            .iter()
+            .take_while(|entry| entry.range.end <= addr)
+            .count();
+
+        for entry in self.pending.drain(..count) {
+            self.ready_end = entry.range.end;
+            let () = self.ready.push_back(Ok(entry));
+        }
+    }
+
+    fn insert(&mut self, entry: MapsEntry) {
+        let () = self.flush(entry.range.start);
+
+        let start = entry.range.start.max(self.ready_end);
+        if start >= entry.range.end {
+            return
+        }
+
+        let mut pending = Vec::with_capacity(self.pending.len() + 1);
+        let mut pieces = vec![restrict_entry(&entry, start..entry.range.end)];
+
+        for old in self.pending.drain(..) {
+            if old.range.end <= start || entry.range.end <= old.range.start {
+                let () = pending.push(old);
+            } else if takes_precedence(&entry, &old) {
+                let () = pending.extend(cut_entry(&old, &(start..entry.range.end)));
+            } else {
+                pieces = pieces
+                    .iter()
+                    .flat_map(|piece| cut_entry(piece, &old.range))
+                    .collect();
+                let () = pending.push(old);
+            }
+        }
+
+        let () = pending.extend(pieces);
+        let () = pending.sort_by_key(|entry| entry.range.start);
+        self.pending = pending;
     }
+}
 
-    match entry.path_name {
-        Some(PathName::Path(..)) => true,
-        Some(PathName::Component(..)) => false,
-        None => true,
+impl<I> Iterator for NonOverlapping<I>
+where
+    I: Iterator<Item = Result<MapsEntry>>,
+{
+    type Item = Result<MapsEntry>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(result) = self.ready.pop_front() {
+                break Some(result)
+            }
+
+            match self.entries.next() {
+                Some(Ok(entry)) => self.insert(entry),
+                Some(Err(err)) => {
+                    // Report all entries preceding the error first.
+                    let () = self.flush(Addr::MAX);
+                    let () = self.ready.push_back(Err(err));
+                }
+                None if self.pending.is_empty() => break None,
+                None => self.flush(Addr::MAX),
+            }
+        }
     }
 }
 
+
 /// Parse the maps file for the process with the given PID and make sure
-/// to filter out unnecessary entries by applying `filter_relevant`.
-pub(crate) fn parse_filtered(pid: Pid) -> Result<impl Iterator<Item = Result<MapsEntry>>> {
-    let entries = parse(pid)?.filter(|result| result.as_ref().map(filter_relevant).unwrap_or(true));
-    Ok(entries)
+/// to filter out unnecessary entries by applying
+/// `is_symbolization_relevant`.
+///
+/// Overlapping entries are resolved as described for
+/// [`NonOverlapping`], meaning that reported entries are sorted and
+/// never overlap.
+pub(crate) fn parse_filtered(pid: &Pid) -> Result<impl Iterator<Item = Result<MapsEntry>>> {
+    let entries = parse(pid)?.filter(|result| {
+        result
+            .as_ref()
+            .map(is_symbolization_relevant)
+            .unwrap_or(true)
+    });
+    Ok(NonOverlapping::new(entries))
+}
+
+
+/// Create a function providing access to already parsed and sorted
+/// proc maps `entries`, for usage with
+/// [`normalize_sorted_user_addrs_with_entries`][crate::normalize::normalize_sorted_user_addrs_with_entries].
+///
+/// Each invocation reports the next entry not ending before the
+/// provided address, skipping over irrelevant ones using binary
+/// search. That way, symbolizing a couple of addresses in a process
+/// with thousands of mappings does not require a linear scan.
+pub(crate) fn entry_lookup<'entries>(
+    mut entries: &'entries [MapsEntry],
+) -> impl FnMut(Addr) -> Option<Result<&'entries MapsEntry>> {
+    move |addr| {
+        let idx = entries.partition_point(|entry| entry.range.end <= addr);
+        let (entry, rest) = entries.get(idx..)?.split_first()?;
+        entries = rest;
+        Some(Ok(entry))
+    }
 }
 
 
@@ -408,15 +780,18 @@ pub(crate) fn parse_filtered(pid: Pid) -> Result<impl Iterator<Item = Result<Map
 mod tests {
     use super::*;
 
-    #[cfg(feature = "nightly")]
     use std::fs::read;
+    use std::fs::File;
     #[cfg(feature = "nightly")]
     use std::hint::black_box;
-    use std::path::Path;
+    use std::io::Write as _;
 
+    use tempfile::tempdir;
     use test_log::test;
     use test_tag::tag;
 
+    use crate::mmap::Mmap;
+
     #[cfg(feature = "nightly")]
     use test::Bencher;
 
@@ -424,7 +799,7 @@ mod tests {
     /// Exercise the `Debug` representation of various types.
     #[test]
     fn debug_repr() {
-        let mut maps = parse(Pid::Slf).unwrap();
+        let mut maps = parse(&Pid::Slf).unwrap();
         assert_ne!(format!("{:?}", maps.next().unwrap()), "");
     }
 
@@ -432,7 +807,7 @@ mod tests {
     #[allow(clippy::suspicious_map)]
     #[test]
     fn self_map_parsing() {
-        let maps = parse(Pid::Slf).unwrap();
+        let maps = parse(&Pid::Slf).unwrap();
         assert_ne!(maps.map(|entry| entry.unwrap()).count(), 0);
     }
 
@@ -490,15 +865,16 @@ ffffffffff600000-ffffffffff601000 --xp 00000000 00:00 0                  [vsysca
 "#;
 
         let pid = Pid::Slf.resolve().into();
-        let entries = parse_file(lines.as_bytes(), pid);
+        let entries = parse_file(lines.as_bytes(), Pid::clone(&pid));
         let () = entries.for_each(|entry| {
             let _entry = entry.unwrap();
         });
 
         // Parse the first (actual) line.
-        let entry = parse_maps_line(lines.lines().next().unwrap().as_bytes(), pid).unwrap();
+        let entry = parse_maps_line(lines.lines().next().unwrap().as_bytes(), &pid).unwrap();
         assert_eq!(entry.range.start, 0x400000);
         assert_eq!(entry.range.end, 0x401000);
+        assert_eq!(entry.inode, 47459);
         assert_eq!(
             entry
                 .path_name
@@ -510,7 +886,7 @@ ffffffffff600000-ffffffffff601000 --xp 00000000 00:00 0                  [vsysca
             Path::new(&format!("/proc/{pid}/map_files/400000-401000"))
         );
 
-        let entry = parse_maps_line(lines.lines().nth(6).unwrap().as_bytes(), pid).unwrap();
+        let entry = parse_maps_line(lines.lines().nth(6).unwrap().as_bytes(), &pid).unwrap();
         assert_eq!(entry.range.start, 0x55f4a95cb000);
         assert_eq!(entry.range.end, 0x55f4a95cf000);
         assert_eq!(entry.perm, Perm::RX);
@@ -526,17 +902,18 @@ ffffffffff600000-ffffffffff601000 --xp 00000000 00:00 0                  [vsysca
         );
         assert_eq!(entry.path_name.as_ref().unwrap().as_component(), None);
 
-        let entry = parse_maps_line(lines.lines().nth(10).unwrap().as_bytes(), pid).unwrap();
+        let entry = parse_maps_line(lines.lines().nth(10).unwrap().as_bytes(), &pid).unwrap();
         assert_eq!(entry.range.start, 0x55f4aa379000);
         assert_eq!(entry.range.end, 0x55f4aa39a000);
         assert_eq!(entry.perm, Perm::RW);
+        assert_eq!(entry.inode, 0);
         assert_eq!(
             entry.path_name.as_ref().unwrap().as_component().unwrap(),
             "[heap]",
         );
         assert_eq!(entry.path_name.as_ref().unwrap().as_path(), None);
 
-        let entry = parse_maps_line(lines.lines().nth(12).unwrap().as_bytes(), pid).unwrap();
+        let entry = parse_maps_line(lines.lines().nth(12).unwrap().as_bytes(), &pid).unwrap();
         assert_eq!(entry.perm, Perm::R);
         assert_eq!(
             entry
@@ -549,12 +926,271 @@ ffffffffff600000-ffffffffff601000 --xp 00000000 00:00 0                  [vsysca
             Path::new(&format!("/proc/{pid}/map_files/7f2321e00000-7f2321e37000"))
         );
 
-        let entry = parse_maps_line(lines.lines().nth(23).unwrap().as_bytes(), pid).unwrap();
+        let entry = parse_maps_line(lines.lines().nth(23).unwrap().as_bytes(), &pid).unwrap();
         assert_eq!(entry.range.start, 0x7fa7bb5fa000);
         assert_eq!(entry.range.end, 0x7fa7bb602000);
         assert_eq!(entry.path_name, None);
     }
 
+    /// Check that paths containing spaces and escaped newlines are
+    /// parsed correctly.
+    #[tag(miri)]
+    #[test]
+    fn map_line_parsing_special_paths() {
+        let pid = Pid::Slf;
+
+        let line = b"7fa7bb75a000-7fa7bb75c000 r--p 00000000 00:20 12023220                   /tmp/a dir/with spaces.so\n";
+        let entry = parse_maps_line(line, &pid).unwrap();
+        assert_eq!(
+            entry.path_name.unwrap().as_path().unwrap().symbolic_path,
+            Path::new("/tmp/a dir/with spaces.so")
+        );
+
+        // Trailing spaces are part of the path.
+        let line = b"7fa7bb75a000-7fa7bb75c000 r--p 00000000 00:20 12023220                   /tmp/trailing  \n";
+        let entry = parse_maps_line(line, &pid).unwrap();
+        let path_name = entry.path_name.unwrap();
+        let entry_path = path_name.as_path().unwrap();
+        assert_eq!(entry_path.symbolic_path, Path::new("/tmp/trailing  "));
+        assert!(!entry_path.deleted);
+        assert_eq!(
+            entry_path.access_path(false, None),
+            entry_path.symbolic_path
+        );
+        assert_eq!(
+            entry_path.access_path(false, Some(Path::new("/proc/42/root"))),
+            Path::new("/proc/42/root/tmp/trailing  ")
+        );
+        assert_eq!(entry_path.access_path(true, None), entry_path.maps_file);
+
+        let line = b"7fa7bb75a000-7fa7bb75c000 r--p 00000000 00:20 12023220                   /tmp/new\\012line (deleted)\n";
+        let entry = parse_maps_line(line, &pid).unwrap();
+        let path_name = entry.path_name.unwrap();
+        let entry_path = path_name.as_path().unwrap();
+        assert_eq!(entry_path.symbolic_path, Path::new("/tmp/new\nline"));
+        assert!(entry_path.deleted);
+        // Deleted files should always be accessed through
+        // `map_files`.
+        assert_eq!(entry_path.access_path(false, None), entry_path.maps_file);
+        assert_eq!(
+            entry_path.access_path(false, Some(Path::new("/proc/42/root"))),
+            entry_path.maps_file
+        );
+
+        // An anonymous mapping, potentially with trailing whitespace.
+        let line = b"7fa7bb5fa000-7fa7bb602000 rw-p 00000000 00:00 0 \n";
+        let entry = parse_maps_line(line, &pid).unwrap();
+        assert_eq!(entry.path_name, None);
+    }
+
+    /// Check that we correctly identify shared memory and hugetlbfs
+    /// backed mappings.
+    #[test]
+    fn shared_mem_detection() {
+        let pid = Pid::Slf;
+        let lines = [
+            &b"7f2d3c600000-7f2d3c800000 r-xs 00000000 00:01 32769                      /SYSV00001234 (deleted)\n"[..],
+            b"7f2d3c800000-7f2d3ca00000 r-xs 00000000 00:01 1034                       /memfd:jit-code (deleted)\n",
+            b"7f2d3ca00000-7f2d3cc00000 r-xp 00000000 00:0f 2217                       /anon_hugepage (deleted)\n",
+            b"7f2d3cc00000-7f2d3ce00000 r-xs 00200000 00:19 6                          /dev/shm/jit-1234\n",
+            b"7f2d3ce00000-7f2d3d000000 r-xs 00000000 00:30 5                          /dev/hugepages/rtemap_0\n",
+        ];
+
+        for line in lines {
+            let entry = parse_maps_line(line, &pid).unwrap();
+            let path = &entry
+                .path_name
+                .as_ref()
+                .unwrap()
+                .as_path()
+                .unwrap()
+                .symbolic_path;
+            assert!(is_shared_mem(path), "{path:?}");
+        }
+
+        assert!(!is_shared_mem(Path::new("/usr/lib64/libc.so.6")));
+        assert!(!is_shared_mem(Path::new("/dev/shmem.so")));
+        assert!(!is_shared_mem(Path::new("/tmp/dev/shm/libfoo.so")));
+    }
+
+    /// Check that we correctly identify entries relevant to
+    /// symbolization.
+    #[tag(miri)]
+    #[test]
+    fn symbolization_relevance() {
+        let pid = Pid::Slf;
+        let relevant = [
+            &b"55f4a95cb000-55f4a95cf000 r-xp 00002000 00:20 41445                      /usr/bin/cat\n"[..],
+            b"55f4a95cf000-55f4a95d1000 r--p 00006000 00:20 41445                      /usr/bin/cat\n",
+            b"7fa7bb5fa000-7fa7bb602000 rw-p 00000000 00:00 0\n",
+            b"7ffd033ab000-7ffd033ad000 r-xp 00000000 00:00 0                          [vdso]\n",
+            b"55f4aa379000-55f4aa39a000 rw-p 00000000 00:00 0                          [heap]\n",
+            b"7ffd03212000-7ffd03234000 rw-p 00000000 00:00 0                          [stack]\n",
+        ];
+        let irrelevant = [
+            &b"7fa7bb5f4000-7fa7bb5f8000 ---p 001f3000 00:20 12023223                   /usr/lib64/libc.so.6\n"[..],
+            b"ffffffffff600000-ffffffffff601000 ---p 00000000 00:00 0                  [vsyscall]\n",
+        ];
+
+        for line in relevant {
+            let entry = parse_maps_line(line, &pid).unwrap();
+            assert!(is_symbolization_relevant(&entry), "{entry:?}");
+        }
+
+        for line in irrelevant {
+            let entry = parse_maps_line(line, &pid).unwrap();
+            assert!(!is_symbolization_relevant(&entry), "{entry:?}");
+        }
+    }
+
+    /// Check that overlapping proc maps entries are resolved as
+    /// expected.
+    #[tag(miri)]
+    #[test]
+    fn overlapping_entry_resolution() {
+        fn resolve(lines: &[&str]) -> Vec<(Range<Addr>, u64, u64)> {
+            let pid = Pid::Slf;
+            let entries = lines
+                .iter()
+                .map(|line| parse_maps_line(line.as_bytes(), &pid));
+            NonOverlapping::new(entries)
+                .map(|entry| {
+                    let entry = entry.unwrap();
+                    (entry.range, entry.offset, entry.inode)
+                })
+                .collect()
+        }
+
+        // Non-overlapping entries are reported as-is.
+        let lines = [
+            "1000-2000 r--p 00000000 00:20 1 /a",
+            "2000-3000 r-xp 00001000 00:20 1 /a",
+        ];
+        assert_eq!(
+            resolve(&lines),
+            vec![(0x1000..0x2000, 0, 1), (0x2000..0x3000, 0x1000, 1)]
+        );
+
+        // Duplicate entries are reported only once.
+        let lines = [
+            "1000-2000 r-xp 00000000 00:20 1 /a",
+            "1000-2000 r-xp 00000000 00:20 1 /a",
+        ];
+        assert_eq!(resolve(&lines), vec![(0x1000..0x2000, 0, 1)]);
+
+        // A later entry wins over an earlier one...
+        let lines = [
+            "1000-3000 r--p 00000000 00:20 1 /a",
+            "2000-4000 r--p 00000000 00:20 2 /b",
+        ];
+        assert_eq!(
+            resolve(&lines),
+            vec![(0x1000..0x2000, 0, 1), (0x2000..0x4000, 0, 2)]
+        );
+
+        // ... unless the earlier one is executable and the later one
+        // is not.
+        let lines = [
+            "1000-3000 r-xp 00000000 00:20 1 /a",
+            "2000-4000 r--p 00000000 00:20 2 /b",
+        ];
+        assert_eq!(
+            resolve(&lines),
+            vec![(0x1000..0x3000, 0, 1), (0x3000..0x4000, 0x1000, 2)]
+        );
+
+        // An entry contained in another one splits it...
+        let lines = [
+            "1000-4000 r--p 00000000 00:20 1 /a",
+            "2000-3000 r-xp 00000000 00:20 2 /b",
+        ];
+        assert_eq!(
+            resolve(&lines),
+            vec![
+                (0x1000..0x2000, 0, 1),
+                (0x2000..0x3000, 0, 2),
+                (0x3000..0x4000, 0x2000, 1)
+            ]
+        );
+
+        // ... or vanishes, if it loses.
+        let lines = [
+            "1000-4000 r-xp 00000000 00:20 1 /a",
+            "2000-3000 r--p 00000000 00:20 2 /b",
+        ];
+        assert_eq!(resolve(&lines), vec![(0x1000..0x4000, 0, 1)]);
+
+        // Parts of entries reaching into already reported ones are
+        // dropped.
+        let lines = [
+            "1000-2000 r--p 00000000 00:20 1 /a",
+            "3000-4000 r--p 00000000 00:20 2 /b",
+            "1800-3800 r--p 00000000 00:20 3 /c",
+        ];
+        assert_eq!(
+            resolve(&lines),
+            vec![
+                (0x1000..0x2000, 0, 1),
+                (0x2000..0x3800, 0x800, 3),
+                (0x3800..0x4000, 0x800, 2)
+            ]
+        );
+    }
+
+    /// Make sure that errors are reported in order when resolving
+    /// overlapping entries.
+    #[tag(miri)]
+    #[test]
+    fn overlapping_entry_resolution_errors() {
+        let pid = Pid::Slf;
+        let lines = [
+            "1000-2000 r--p 00000000 00:20 1 /a",
+            "2000-3000 r--p 00000000",
+            "3000-4000 r--p 00000000 00:20 2 /b",
+        ];
+        let entries = lines
+            .iter()
+            .map(|line| parse_maps_line(line.as_bytes(), &pid));
+        let mut iter = NonOverlapping::new(entries);
+        assert_eq!(iter.next().unwrap().unwrap().inode, 1);
+        let _err = iter.next().unwrap().unwrap_err();
+        assert_eq!(iter.next().unwrap().unwrap().inode, 2);
+        assert!(iter.next().is_none());
+    }
+
+    /// Check that we unescape paths as expected.
+    #[tag(miri)]
+    #[test]
+    fn path_unescaping() {
+        let path = unescape_path(b"/usr/lib/libc.so");
+        assert!(matches!(path, Cow::Borrowed(..)), "{path:?}");
+        assert_eq!(path.as_ref(), b"/usr/lib/libc.so");
+        assert_eq!(unescape_path(b"\\012").as_ref(), b"\n");
+        assert_eq!(unescape_path(b"/a\\012b\\012").as_ref(), b"/a\nb\n");
+        assert_eq!(unescape_path(b"/a\\01").as_ref(), b"/a\\01");
+    }
+
+    /// Check that we report the correct path for a file mapping whose
+    /// path contains spaces and newlines.
+    #[test]
+    fn self_map_parsing_special_paths() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("a file\nwith newline and spaces ");
+        let mut file = File::create(&path).unwrap();
+        let () = file.write_all(&[0xff; 4096]).unwrap();
+        let file = File::open(&path).unwrap();
+        let mmap = Mmap::map(&file).unwrap();
+        let addr = mmap.as_ptr() as Addr;
+
+        let entry = parse(&Pid::Slf)
+            .unwrap()
+            .map(|entry| entry.unwrap())
+            .find(|entry| entry.range.contains(&addr))
+            .unwrap();
+        let path_name = entry.path_name.unwrap();
+        assert_eq!(path_name.as_path().unwrap().symbolic_path, path);
+    }
+
     /// Check that we error out as expected on malformed proc maps lines.
     #[tag(miri)]
     #[test]
@@ -573,13 +1209,54 @@ ffffffffff600000-ffffffffff601000 --xp 00000000 00:00 0                  [vsysca
             b"7fa7bb75a000-7fa7bb75c000 r--p".as_slice(),
             b"7fa7bb75a000-7fa7bb75c000 r--p 00000000".as_slice(),
             b"7fa7bb75a000-7fa7bb75c000 r--p 000zz000 00:20".as_slice(),
+            b"7fa7bb75a000-7fa7bb75c000 r--p 00000000 00:20 12x3 /tmp/foo".as_slice(),
         ];
 
         let () = lines.iter().for_each(|line| {
-            let _err = parse_maps_line(line, Pid::Slf).unwrap_err();
+            let _err = parse_maps_line(line, &Pid::Slf).unwrap_err();
         });
     }
 
+    /// Check that we only report a process' root directory if it
+    /// differs from ours.
+    #[test]
+    fn proc_root_lookup() {
+        assert_eq!(proc_root(&Pid::Slf), None);
+        // A PID that should not be in use.
+        assert_eq!(proc_root(&Pid::from(u32::MAX)), None);
+    }
+
+    /// Check that `entry_lookup` reports the expected entries.
+    #[test]
+    fn entry_lookup_skipping() {
+        let maps = Path::new(&env!("CARGO_MANIFEST_DIR"))
+            .join("data")
+            .join("proc-maps-large");
+        let lines = read(&maps).unwrap();
+        let entries = parse_file(lines.as_slice(), Pid::Slf)
+            .collect::<Result<Vec<_>>>()
+            .unwrap();
+        assert!(entries.len() > 4);
+
+        let mid = entries.len() / 2;
+        let mut lookup = entry_lookup(&entries);
+        let entry = lookup(entries[mid].range.start).unwrap().unwrap();
+        assert_eq!(entry, &entries[mid]);
+        // Asking again for an address covered by an entry we already
+        // reported moves on to the next one.
+        let entry = lookup(entries[mid].range.start).unwrap().unwrap();
+        assert_eq!(entry, &entries[mid + 1]);
+        let last = entries.last().unwrap();
+        let entry = lookup(last.range.end - 1).unwrap().unwrap();
+        assert_eq!(entry, last);
+        assert!(lookup(last.range.end).is_none());
+        assert!(lookup(0).is_none());
+
+        let mut lookup = entry_lookup(&entries);
+        let entry = lookup(last.range.end).map(Result::unwrap);
+        assert_eq!(entry, None);
+    }
+
     /// Benchmark the parsing of a large /proc/[pid]/maps file.
     #[cfg(feature = "nightly")]
     #[bench]
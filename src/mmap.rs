@@ -2,7 +2,7 @@ use std::fs::File;
 use std::ops::Deref;
 use std::ops::Range;
 use std::path::Path;
-use std::rc::Rc;
+use std::sync::Arc;
 
 use memmap2::Mmap as Mapping;
 use memmap2::MmapOptions;
@@ -63,7 +63,7 @@ impl Builder {
             }?;
 
             Mmap {
-                mapping: Some(Rc::new(mapping)),
+                mapping: Some(Arc::new(mapping)),
                 view: 0..len as u64,
             }
         };
@@ -76,7 +76,7 @@ impl Builder {
 #[derive(Clone, Debug)]
 pub struct Mmap {
     /// The actual memory mapping.
-    mapping: Option<Rc<Mapping>>,
+    mapping: Option<Arc<Mapping>>,
     /// The view on the memory mapping that this object represents.
     view: Range<u64>,
 }
@@ -93,6 +93,26 @@ impl Mmap {
         Self::builder().map(file)
     }
 
+    /// Create an anonymous memory mapping holding a copy of `data`.
+    pub(crate) fn from_bytes(data: &[u8]) -> Result<Self> {
+        if data.is_empty() {
+            return Ok(Mmap {
+                mapping: None,
+                view: 0..1,
+            })
+        }
+
+        let mut mapping = MmapOptions::new().len(data.len()).map_anon()?;
+        let () = mapping.copy_from_slice(data);
+        let mapping = mapping.make_read_only()?;
+
+        let mmap = Mmap {
+            mapping: Some(Arc::new(mapping)),
+            view: 0..data.len() as u64,
+        };
+        Ok(mmap)
+    }
+
     /// Create a new `Mmap` object (sharing the same underlying memory mapping
     /// as the current one) that restricts its view to the provided `range`.
     /// Adjustment happens relative to the current view.
@@ -170,6 +190,18 @@ mod tests {
         );
     }
 
+    /// Check that we can create an anonymous mapping from a byte slice.
+    #[test]
+    fn mmap_from_bytes() {
+        let mmap = Mmap::from_bytes(b"").unwrap();
+        assert_eq!(mmap.deref(), &[] as &[u8]);
+
+        let mmap = Mmap::from_bytes(b"abcdefghijklmnopqrstuvwxyz").unwrap();
+        assert_eq!(mmap.deref(), b"abcdefghijklmnopqrstuvwxyz");
+        let mmap = mmap.constrain(1..4).unwrap();
+        assert_eq!(mmap.deref(), b"bcd");
+    }
+
     /// Check that we can properly restrict the view of a `Mmap`.
     #[test]
     fn view_constraining() {
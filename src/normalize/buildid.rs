@@ -1,14 +1,23 @@
 use std::borrow::Cow;
+use std::collections::HashMap;
+use std::fmt::Write as _;
 use std::mem::size_of;
 use std::path::Path;
+use std::path::PathBuf;
+use std::str;
+use std::sync::Mutex;
+use std::sync::PoisonError;
 
-use crate::elf;
 use crate::elf::types::ElfN_Nhdr;
+use crate::elf::types::NT_GNU_BUILD_ID;
 use crate::elf::ElfParser;
 use crate::file_cache::FileCache;
+use crate::file_cache::FileMeta;
 use crate::util::align_up_u32;
+use crate::util::stat;
 use crate::util::ReadRaw as _;
 use crate::Error;
+use crate::ErrorExt as _;
 use crate::IntoError as _;
 use crate::Mmap;
 use crate::Result;
@@ -17,42 +26,31 @@ use crate::Result;
 /// A GNU build ID, as raw bytes.
 pub type BuildId<'src> = Cow<'src, [u8]>;
 
+/// The size of the GUID part of a Breakpad debug ID, in bytes.
+const DEBUG_ID_GUID_LEN: usize = 16;
+/// The owner name of GNU ELF notes.
+const GNU_NOTE_NAME: &[u8] = b"GNU\0";
 
-/// Iterate over all note sections to find one of type
-/// [`NT_GNU_BUILD_ID`][elf::types::NT_GNU_BUILD_ID].
+/// The maximum number of files the process wide build ID cache keeps
+/// track of before it is reset.
+const MAX_CACHED_BUILD_IDS: usize = 4096;
+
+/// The process wide cache of build IDs, keyed by the device and inode
+/// number of the file they were read from.
+///
+/// Alongside each build ID we store the full meta data of the file at
+/// the time it was read, so that we can detect in-place modifications.
+static BUILD_IDS: Mutex<Option<HashMap<(libc::dev_t, libc::ino_t), CachedBuildId>>> =
+    Mutex::new(None);
+
+type CachedBuildId = (FileMeta, Option<BuildId<'static>>);
+
+
+/// Read the build ID of the ELF file represented by `parser`, if any.
+#[inline]
 fn read_build_id(parser: &ElfParser) -> Result<Option<BuildId<'_>>> {
-    let shdrs = parser.section_headers()?;
-    for (idx, shdr) in shdrs.iter(0).enumerate() {
-        if shdr.type_() == elf::types::SHT_NOTE {
-            // SANITY: We just found the index so the section data should always
-            //         be found.
-            let mut bytes = parser.section_data(idx).unwrap();
-
-            while bytes.len() >= size_of::<ElfN_Nhdr>() {
-                let nhdr = bytes
-                    .read_pod_ref::<ElfN_Nhdr>()
-                    .ok_or_invalid_data(|| "failed to read build ID section header")?;
-
-                // Type check is assumed to suffice, but we still need
-                // to skip the name bytes.
-                let () = bytes
-                    .advance(align_up_u32(nhdr.n_namesz, 4) as _)
-                    .ok_or_invalid_data(|| "failed to skip over ELF note name")?;
-
-                if nhdr.n_type == elf::types::NT_GNU_BUILD_ID {
-                    let build_id = bytes
-                        .read_slice(nhdr.n_descsz as _)
-                        .ok_or_invalid_data(|| "failed to read build ID section contents")?;
-                    return Ok(Some(Cow::Borrowed(build_id)))
-                } else {
-                    let () = bytes
-                        .advance(align_up_u32(nhdr.n_descsz, 4) as _)
-                        .ok_or_invalid_data(|| "failed to skip over ELF note descriptor")?;
-                }
-            }
-        }
-    }
-    Ok(None)
+    let build_id = parser.build_id()?.map(Cow::Borrowed);
+    Ok(build_id)
 }
 
 
@@ -81,9 +79,7 @@ pub(super) struct DefaultBuildIdReader;
 impl BuildIdReader<'_> for DefaultBuildIdReader {
     /// Attempt to read an ELF binary's build ID from a file.
     fn read_build_id_fallible(&self, path: &Path) -> Result<Option<BuildId<'static>>> {
-        let parser = ElfParser::open(path)?;
-        let buildid = read_build_id(&parser)?.map(|buildid| Cow::Owned(buildid.to_vec()));
-        Ok(buildid)
+        read_elf_build_id_cached(path)
     }
 }
 
@@ -106,7 +102,7 @@ impl<'src> BuildIdReader<'src> for CachingBuildIdReader<'src> {
         let (file, cell) = self.cache.entry(path)?;
         let build_id = cell
             .get_or_try_init(|| {
-                let parser = ElfParser::open_file(file, path)?;
+                let parser = ElfParser::open_file(&*file.get()?, path)?;
                 let buildid = read_build_id(&parser)?.map(|buildid| Cow::Owned(buildid.to_vec()));
                 Result::<_, Error>::Ok(buildid)
             })?
@@ -132,7 +128,7 @@ impl BuildIdReader<'_> for NoBuildIdReader {
 /// Build IDs can have variable length, depending on which flavor is used (e.g.,
 /// 20 bytes for `sha1` flavor). They are reported as "raw" bytes. If you need a
 /// hexadecimal representation as reported by tools such as `readelf(1)`, a post
-/// processing step is necessary.
+/// processing step is necessary, e.g., using [`build_id_to_hex`].
 ///
 /// Returns [`None`] if the file does not contain a build ID.
 ///
@@ -153,12 +149,7 @@ impl BuildIdReader<'_> for NoBuildIdReader {
 /// let build_id = blazesym::helper::read_elf_build_id(&path).unwrap();
 /// match build_id {
 ///     Some(bytes) => {
-///        let build_id = bytes
-///            .iter()
-///            .fold(String::with_capacity(bytes.len() * 2), |mut s, b| {
-///                let () = s.push_str(&format!("{b:02x}"));
-///                s
-///            });
+///        let build_id = blazesym::helper::build_id_to_hex(&bytes);
 ///        println!("{path} has build ID {build_id}");
 ///     },
 ///     None => println!("{path} has no build ID"),
@@ -188,19 +179,313 @@ pub fn read_elf_build_id_from_mmap(mmap: &Mmap) -> Result<Option<BuildId<'static
 }
 
 
+/// Format a build ID as lower case hexadecimal string.
+///
+/// This is the representation used by tools such as `readelf(1)` and
+/// `file(1)` as well as by `debuginfod` servers.
+pub fn build_id_to_hex(build_id: &[u8]) -> String {
+    build_id
+        .iter()
+        .fold(String::with_capacity(build_id.len() * 2), |mut s, b| {
+            let _result = write!(s, "{b:02x}");
+            s
+        })
+}
+
+/// Parse a build ID from its hexadecimal representation.
+///
+/// Both lower and upper case digits are accepted.
+pub fn build_id_from_hex(hex: &str) -> Result<BuildId<'static>> {
+    if hex.len() % 2 == 1 {
+        return Err(Error::with_invalid_input(format!(
+            "build ID `{hex}` has odd number of hexadecimal digits"
+        )))
+    }
+    if !hex.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return Err(Error::with_invalid_input(format!(
+            "build ID `{hex}` contains non-hexadecimal characters"
+        )))
+    }
+
+    let build_id = hex
+        .as_bytes()
+        .chunks_exact(2)
+        .map(|digits| {
+            // SANITY: We checked that all characters are ASCII
+            //         hexadecimal digits above.
+            let digits = str::from_utf8(digits).unwrap();
+            u8::from_str_radix(digits, 16).unwrap()
+        })
+        .collect::<Vec<_>>();
+    Ok(Cow::Owned(build_id))
+}
+
+/// Create a Breakpad debug ID from a GUID and an age.
+fn debug_id(id: &[u8], age: u32) -> String {
+    let mut guid = [0u8; DEBUG_ID_GUID_LEN];
+    let len = id.len().min(DEBUG_ID_GUID_LEN);
+    let () = guid[..len].copy_from_slice(&id[..len]);
+    // The GUID is stored with its first three fields in little endian
+    // byte order, but formatted in big endian one.
+    let () = guid[0..4].reverse();
+    let () = guid[4..6].reverse();
+    let () = guid[6..8].reverse();
+
+    let mut debug_id = guid.iter().fold(
+        String::with_capacity(DEBUG_ID_GUID_LEN * 2 + 8),
+        |mut s, b| {
+            let _result = write!(s, "{b:02X}");
+            s
+        },
+    );
+    let _result = write!(debug_id, "{age:X}");
+    debug_id
+}
+
+/// Create the Breakpad debug ID (also referred to as module ID) of an
+/// ELF file from its build ID.
+///
+/// Breakpad interprets the first 16 bytes of the build ID as a GUID,
+/// padding shorter ones with zeroes, and appends an "age" that is
+/// always zero for ELF files. The result is what is used to organize
+/// symbol files on Breakpad symbol servers, e.g.,
+/// `<module>/<debug-id>/<module>.sym`.
+pub fn breakpad_debug_id(build_id: &[u8]) -> String {
+    debug_id(build_id, 0)
+}
+
+/// Create the Breakpad debug ID of a PE file from the GUID and age of
+/// the PDB file it references.
+///
+/// `guid` is expected in its on-disk byte order, as stored in the
+/// CodeView debug directory entry of the PE file.
+pub fn breakpad_pe_debug_id(guid: &[u8; 16], age: u32) -> String {
+    debug_id(guid, age)
+}
+
+/// Find the descriptor of the GNU build ID note in `notes`, the
+/// contents of an ELF note section or segment.
+pub(crate) fn find_build_id_note(mut notes: &[u8]) -> Result<Option<&[u8]>> {
+    while notes.len() >= size_of::<ElfN_Nhdr>() {
+        let nhdr = notes
+            .read_pod::<ElfN_Nhdr>()
+            .ok_or_invalid_data(|| "failed to read ELF note header")?;
+        let name = notes
+            .read_slice(nhdr.n_namesz as _)
+            .ok_or_invalid_data(|| "failed to read ELF note name")?;
+        let () = notes
+            .advance((align_up_u32(nhdr.n_namesz, 4) - nhdr.n_namesz) as _)
+            .ok_or_invalid_data(|| "failed to skip over ELF note name padding")?;
+
+        if nhdr.n_type == NT_GNU_BUILD_ID && name == GNU_NOTE_NAME {
+            let build_id = notes
+                .read_slice(nhdr.n_descsz as _)
+                .ok_or_invalid_data(|| "failed to read build ID note contents")?;
+            return Ok(Some(build_id))
+        } else {
+            let () = notes
+                .advance(align_up_u32(nhdr.n_descsz, 4) as _)
+                .ok_or_invalid_data(|| "failed to skip over ELF note descriptor")?;
+        }
+    }
+    Ok(None)
+}
+
+/// Extract the GNU build ID from raw ELF note data.
+///
+/// `notes` is expected to contain the contents of an ELF note section
+/// (e.g., `.note.gnu.build-id`) or `PT_NOTE` segment, i.e., a sequence
+/// of note headers with their names and descriptors.
+///
+/// Returns [`None`] if the data do not contain a build ID note.
+pub fn read_build_id_from_notes(notes: &[u8]) -> Result<Option<BuildId<'_>>> {
+    let build_id = find_build_id_note(notes)?.map(Cow::Borrowed);
+    Ok(build_id)
+}
+
+
+/// Read the build ID of the ELF file at `path`, consulting the process
+/// wide build ID cache first.
+///
+/// The same file is commonly mapped into many processes and may be
+/// looked at by many normalizers and symbolizers. The cache allows us
+/// to get by with a single `stat` for all but the first of them.
+pub(crate) fn read_elf_build_id_cached(path: &Path) -> Result<Option<BuildId<'static>>> {
+    let stat = stat(path).with_context(|| format!("failed to stat {}", path.display()))?;
+    let meta = FileMeta::from(&stat);
+    let id = meta.file_id();
+
+    {
+        // Nothing in here can panic while leaving the map in an
+        // inconsistent state, so we can safely ignore poisoning.
+        let cache = BUILD_IDS.lock().unwrap_or_else(PoisonError::into_inner);
+        if let Some((cached_meta, build_id)) = cache.as_ref().and_then(|cache| cache.get(&id)) {
+            if *cached_meta == meta {
+                return Ok(build_id.clone())
+            }
+        }
+    }
+
+    // We deliberately do not hold the lock while parsing, so that
+    // concurrent readers of unrelated files are not serialized.
+    let build_id = read_elf_build_id(path)?;
+
+    let mut cache = BUILD_IDS.lock().unwrap_or_else(PoisonError::into_inner);
+    let cache = cache.get_or_insert_with(HashMap::new);
+    if cache.len() >= MAX_CACHED_BUILD_IDS {
+        let () = cache.clear();
+    }
+    let _prev = cache.insert(id, (meta, build_id.clone()));
+    Ok(build_id)
+}
+
+
+/// Construct the path of the debug file for a binary with the given
+/// build ID below `debug_dir`.
+///
+/// Following convention, such files are located at
+/// `<debug_dir>/.build-id/<xx>/<yyyy>.debug`, where `<xx>` is the
+/// hexadecimal representation of the first byte of the build ID and
+/// `<yyyy>` that of the remaining ones. [`None`] is returned if the
+/// build ID is too short to be represented this way.
+pub(crate) fn build_id_debug_path(debug_dir: &Path, build_id: &[u8]) -> Option<PathBuf> {
+    let [first, rest @ ..] = build_id else {
+        return None
+    };
+    if rest.is_empty() {
+        return None
+    }
+
+    let file = build_id_to_hex(rest);
+    let path = debug_dir
+        .join(".build-id")
+        .join(format!("{first:02x}"))
+        .join(file + ".debug");
+    Some(path)
+}
+
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
     use std::fs::File;
+    use std::io::Seek as _;
+    use std::io::Write as _;
 
+    use tempfile::NamedTempFile;
     use test_log::test;
     use test_tag::tag;
 
+    use crate::test_utils::ElfBuilder;
     use crate::ErrorKind;
     use crate::Result;
 
 
+    /// Check that we construct build ID based debug file paths as
+    /// expected.
+    #[tag(miri)]
+    #[test]
+    fn build_id_debug_path_construction() {
+        let dir = Path::new("/usr/lib/debug");
+        let path = build_id_debug_path(dir, &[0x0a, 0xbc, 0xde, 0xf0]).unwrap();
+        assert_eq!(path, Path::new("/usr/lib/debug/.build-id/0a/bcdef0.debug"));
+
+        assert_eq!(build_id_debug_path(dir, &[]), None);
+        assert_eq!(build_id_debug_path(dir, &[0x0a]), None);
+    }
+
+    /// Check that we can convert build IDs to and from their
+    /// hexadecimal representation.
+    #[tag(miri)]
+    #[test]
+    fn build_id_hex_conversion() {
+        let build_id = [0x0a, 0xbc, 0xde, 0xf0, 0x12];
+        let hex = build_id_to_hex(&build_id);
+        assert_eq!(hex, "0abcdef012");
+        assert_eq!(build_id_from_hex(&hex).unwrap(), &build_id[..]);
+        assert_eq!(build_id_from_hex("0ABCDEF012").unwrap(), &build_id[..]);
+
+        assert_eq!(build_id_to_hex(&[]), "");
+        assert!(build_id_from_hex("").unwrap().is_empty());
+
+        let err = build_id_from_hex("abc").unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidInput);
+        let err = build_id_from_hex("xy").unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidInput);
+        let err = build_id_from_hex("+f").unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidInput);
+        let err = build_id_from_hex("\u{e9}").unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidInput);
+    }
+
+    /// Check that we derive Breakpad debug IDs the way Breakpad does.
+    #[tag(miri)]
+    #[test]
+    fn breakpad_debug_id_creation() {
+        let build_id = [
+            0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0x09, 0x0a, 0x0b, 0x0c, 0x0d, 0x0e,
+            0x0f, 0x10, 0x11, 0x12,
+        ];
+        assert_eq!(
+            breakpad_debug_id(&build_id),
+            "0403020106050807090A0B0C0D0E0F100"
+        );
+        assert_eq!(
+            breakpad_debug_id(&[0xab]),
+            "000000AB0000000000000000000000000"
+        );
+
+        let guid = [
+            0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0x09, 0x0a, 0x0b, 0x0c, 0x0d, 0x0e,
+            0x0f, 0x10,
+        ];
+        assert_eq!(
+            breakpad_pe_debug_id(&guid, 1),
+            "0403020106050807090A0B0C0D0E0F101"
+        );
+        assert_eq!(
+            breakpad_pe_debug_id(&guid, 0x2a),
+            "0403020106050807090A0B0C0D0E0F102A"
+        );
+    }
+
+    /// Check that we can extract a build ID from raw ELF note data.
+    #[tag(miri)]
+    #[test]
+    fn build_id_note_reading() {
+        fn note(name: &[u8], type_: u32, desc: &[u8]) -> Vec<u8> {
+            let mut note = Vec::new();
+            let () = note.extend_from_slice(&(name.len() as u32).to_ne_bytes());
+            let () = note.extend_from_slice(&(desc.len() as u32).to_ne_bytes());
+            let () = note.extend_from_slice(&type_.to_ne_bytes());
+            let () = note.extend_from_slice(name);
+            let () = note.resize(align_up_u32(note.len() as u32, 4) as usize, 0);
+            let () = note.extend_from_slice(desc);
+            let () = note.resize(align_up_u32(note.len() as u32, 4) as usize, 0);
+            note
+        }
+
+        let mut notes = note(b"GNU\0", 1, &[0x01, 0x02, 0x03, 0x04]);
+        // A note of the right type but from a different owner.
+        let () = notes.extend(note(b"Go\0", NT_GNU_BUILD_ID, &[0xff; 3]));
+        let () = notes.extend(note(b"GNU\0", NT_GNU_BUILD_ID, &[0xab, 0xcd, 0xef]));
+
+        let build_id = read_build_id_from_notes(&notes).unwrap().unwrap();
+        assert_eq!(build_id, &[0xab, 0xcd, 0xef][..]);
+
+        // The data do not have to be aligned.
+        let mut unaligned = vec![0];
+        let () = unaligned.extend_from_slice(&notes);
+        let build_id = read_build_id_from_notes(&unaligned[1..]).unwrap().unwrap();
+        assert_eq!(build_id, &[0xab, 0xcd, 0xef][..]);
+
+        let build_id = read_build_id_from_notes(&notes[..20]).unwrap();
+        assert_eq!(build_id, None);
+        let err = read_build_id_from_notes(&notes[..notes.len() - 4]).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidData);
+    }
+
     /// Check that we can read a binary's build ID based on the ELF section name
     /// as well as ELF section type.
     #[tag(other_os)]
@@ -257,4 +542,47 @@ mod tests {
         let err = read_elf_build_id(&elf).unwrap_err();
         assert_eq!(err.kind(), ErrorKind::NotFound);
     }
+
+    /// Check that build IDs are served from the process wide cache and
+    /// that modifications of a file are picked up.
+    #[test]
+    fn cached_build_id_reading() {
+        fn elf_with_build_id(build_id: &[u8]) -> Vec<u8> {
+            let mut builder = ElfBuilder::new();
+            let _text = builder.add_section(".text", 0x1000, vec![0xcc; 0x10]);
+            let () = builder.add_note("GNU", 3, build_id.to_vec());
+            builder.build()
+        }
+
+        let mut file = NamedTempFile::new().unwrap();
+        let () = file.write_all(&elf_with_build_id(&[0xaa; 20])).unwrap();
+        let () = file.flush().unwrap();
+
+        let build_id = read_elf_build_id_cached(file.path()).unwrap();
+        assert_eq!(build_id.as_deref(), Some([0xaa; 20].as_slice()));
+
+        let meta = FileMeta::from(&stat(file.path()).unwrap());
+        let cached = BUILD_IDS
+            .lock()
+            .unwrap()
+            .as_ref()
+            .and_then(|cache| cache.get(&meta.file_id()).cloned());
+        assert_eq!(cached, Some((meta, build_id.clone())));
+
+        let build_id = read_elf_build_id_cached(file.path()).unwrap();
+        assert_eq!(build_id.as_deref(), Some([0xaa; 20].as_slice()));
+
+        // Rewrite the file in place with a build ID of different
+        // length. The inode stays the same, but the size changes.
+        let () = file.as_file().set_len(0).unwrap();
+        let () = file.rewind().unwrap();
+        let () = file.write_all(&elf_with_build_id(&[0xbb; 16])).unwrap();
+        let () = file.flush().unwrap();
+
+        let build_id = read_elf_build_id_cached(file.path()).unwrap();
+        assert_eq!(build_id.as_deref(), Some([0xbb; 16].as_slice()));
+
+        let err = read_elf_build_id_cached(Path::new("/does/not/exist")).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::NotFound);
+    }
 }
@@ -9,6 +9,7 @@ use std::mem::MaybeUninit;
 use libc::ENOENT;
 use libc::ENOTTY;
 
+use crate::context::procmap_query_supported;
 use crate::maps::MapsEntry;
 use crate::maps::Perm;
 use crate::Addr;
@@ -142,7 +143,7 @@ fn vma_flags_to_perm(vma_flags: u64) -> Perm {
 #[cfg(linux)]
 pub(crate) fn query_procmap(
     file: &File,
-    pid: Pid,
+    pid: &Pid,
     addr: Addr,
     build_id: bool,
 ) -> Result<Option<MapsEntry>> {
@@ -158,11 +159,11 @@ pub(crate) fn query_procmap(
         size: size_of::<procmap_query>() as _,
         query_flags: (PROCMAP_QUERY_COVERING_OR_NEXT_VMA
             // NB: Keep filter flags roughly in sync with
-            //     `filter_relevant` function. Note that because the
-            //     ioctl ANDs the conditions, we can't mirror exactly
-            //     what `filter_relevant` does (r OR x). So we just
-            //     filter for readable, pretty much all things
-            //     executable will be readable anyway.
+            //     `is_symbolization_relevant` function. Note that
+            //     because the ioctl ANDs the conditions, we can't
+            //     mirror exactly what `is_symbolization_relevant` does
+            //     (r OR x). So we just filter for readable, pretty much
+            //     all things executable will be readable anyway.
             | PROCMAP_QUERY_VMA_READABLE) as _,
         query_addr: addr,
         vma_name_addr: path_buf.as_mut_ptr() as _,
@@ -210,6 +211,7 @@ pub(crate) fn query_procmap(
         range: query.vma_start..query.vma_end,
         perm: vma_flags_to_perm(query.vma_flags),
         offset: query.vma_offset,
+        inode: query.inode,
         path_name,
         build_id: None,
     };
@@ -227,7 +229,7 @@ pub(crate) fn query_procmap(
 #[cfg(not(linux))]
 pub(crate) fn query_procmap(
     _file: &File,
-    _pid: Pid,
+    _pid: &Pid,
     _addr: Addr,
     _build_id: bool,
 ) -> Result<Option<MapsEntry>> {
@@ -235,15 +237,15 @@ pub(crate) fn query_procmap(
 }
 
 
-/// Check whether the `PROCMAP_QUERY` ioctl is supported by the system.
-pub fn is_procmap_query_supported() -> Result<bool> {
+/// Probe for support of the `PROCMAP_QUERY` ioctl.
+pub(crate) fn probe_procmap_query() -> Result<bool> {
     let pid = Pid::Slf;
     let path = format!("/proc/{pid}/maps");
     let file = File::open(&path).with_context(|| format!("failed to open `{path}` for reading"))?;
     let addr = 0;
     let build_ids = false;
 
-    let result = query_procmap(&file, pid, addr, build_ids);
+    let result = query_procmap(&file, &pid, addr, build_ids);
     match result {
         Ok(..) => Ok(true),
         Err(err) if err.kind() == ErrorKind::Unsupported => Ok(false),
@@ -251,6 +253,14 @@ pub fn is_procmap_query_supported() -> Result<bool> {
     }
 }
 
+/// Check whether the `PROCMAP_QUERY` ioctl is supported by the system.
+///
+/// The result is determined once and cached for the remainder of the
+/// process' life time.
+pub fn is_procmap_query_supported() -> Result<bool> {
+    procmap_query_supported()
+}
+
 
 #[cfg(test)]
 mod tests {
@@ -301,7 +311,7 @@ mod tests {
         let path = format!("/proc/{pid}/maps");
         let file = File::open(path).unwrap();
         let addr = 0xfffffffff000;
-        let result = query_procmap(&file, pid, addr, false).unwrap();
+        let result = query_procmap(&file, &pid, addr, false).unwrap();
         assert_eq!(result, None);
     }
 
@@ -315,7 +325,9 @@ mod tests {
             let path = format!("/proc/{pid}/maps");
             let file = File::open(path).unwrap();
             let addr = valid_vma_querying_ioctl as Addr;
-            let entry = query_procmap(&file, pid, addr, build_ids).unwrap().unwrap();
+            let entry = query_procmap(&file, &pid, addr, build_ids)
+                .unwrap()
+                .unwrap();
             assert!(
                 entry.range.contains(&addr),
                 "{:#x?} : {addr:#x}",
@@ -353,7 +365,7 @@ mod tests {
     #[test]
     #[ignore = "test requires PROCMAP_QUERY ioctl kernel support"]
     fn vma_comparison() {
-        fn parse_maps(pid: Pid, from_text: &mut Vec<MapsEntry>) {
+        fn parse_maps(pid: &Pid, from_text: &mut Vec<MapsEntry>) {
             let () = from_text.clear();
 
             let it = maps::parse_filtered(pid).unwrap();
@@ -363,7 +375,7 @@ mod tests {
             }
         }
 
-        fn parse_ioctl(pid: Pid, from_ioctl: &mut Vec<MapsEntry>) {
+        fn parse_ioctl(pid: &Pid, from_ioctl: &mut Vec<MapsEntry>) {
             let () = from_ioctl.clear();
 
             let path = format!("/proc/{pid}/maps");
@@ -372,7 +384,7 @@ mod tests {
             let mut next_addr = 0;
             while let Some(entry) = query_procmap(&file, pid, next_addr, build_ids).unwrap() {
                 next_addr = entry.range.end;
-                if maps::filter_relevant(&entry) {
+                if maps::is_symbolization_relevant(&entry) {
                     let () = from_ioctl.push(entry);
                 }
             }
@@ -386,8 +398,8 @@ mod tests {
         // if the heap has to grow), meaning that we could see
         // mismatches for good reason. So we give it a few attempts.
         for _ in 0..5 {
-            let () = parse_maps(pid, &mut from_text);
-            let () = parse_ioctl(pid, &mut from_ioctl);
+            let () = parse_maps(&pid, &mut from_text);
+            let () = parse_ioctl(&pid, &mut from_ioctl);
 
             if from_text == from_ioctl {
                 break
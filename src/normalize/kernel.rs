@@ -0,0 +1,395 @@
+use std::fs::File;
+use std::io::BufRead as _;
+use std::io::BufReader;
+use std::io::Read;
+use std::ops::Range;
+
+use crate::context;
+use crate::kernel::KALLSYMS;
+use crate::kernel::MODULES;
+use crate::log;
+use crate::util::find_match_or_lower_bound_by_key;
+use crate::Addr;
+use crate::ErrorExt as _;
+use crate::Result;
+
+use super::meta::BpfProg;
+use super::meta::Kernel;
+use super::meta::KernelMeta;
+use super::meta::KernelModule;
+use super::meta::Unknown;
+use super::normalizer::Output;
+use super::Reason;
+
+
+/// The prefix of `kallsyms` names of BPF programs.
+const BPF_PROG_PREFIX: &str = "bpf_prog_";
+
+
+/// A type representing the output of kernel address normalization.
+pub type KernelOutput = Output<KernelMeta>;
+
+
+/// Parse the name of a BPF program as it appears in `kallsyms`, i.e.,
+/// `bpf_prog_<tag>[_<name>]`.
+fn parse_bpf_prog(sym: &str, addr: Addr) -> Option<BpfProg> {
+    let sym = sym.strip_prefix(BPF_PROG_PREFIX)?;
+    let (tag, name) = sym.split_at(sym.find('_').unwrap_or(sym.len()));
+    let name = name.strip_prefix('_').unwrap_or(name);
+    if tag.len() != 16 {
+        return None
+    }
+    let tag = u64::from_str_radix(tag, 16).ok()?;
+
+    let prog = BpfProg {
+        name: name.to_string(),
+        tag: tag.to_be_bytes(),
+        addr,
+        _non_exhaustive: (),
+    };
+    Some(prog)
+}
+
+
+/// The layout of the running kernel, as relevant for the normalization
+/// of addresses.
+#[derive(Debug, Default)]
+struct KernelLayout {
+    /// The address range of the core kernel's text, if known.
+    text: Option<Range<Addr>>,
+    /// Loaded kernel modules along with their address ranges, sorted by
+    /// start address.
+    modules: Vec<(Range<Addr>, KernelModule)>,
+    /// BPF programs along with their (estimated) address ranges, sorted
+    /// by start address.
+    bpf_progs: Vec<(Range<Addr>, BpfProg)>,
+}
+
+impl KernelLayout {
+    /// Parse `kallsyms` data from `reader`, retrieving the range of the
+    /// core kernel's text as well as BPF programs.
+    fn parse_kallsyms<R>(&mut self, reader: R) -> Result<()>
+    where
+        R: Read,
+    {
+        let mut reader = BufReader::new(reader);
+        let mut line = String::new();
+        let mut addrs = Vec::new();
+        let mut stext = None;
+        let mut etext = None;
+        let mut bpf_progs = Vec::new();
+
+        loop {
+            let () = line.clear();
+            let sz = reader.read_line(&mut line)?;
+            if sz == 0 {
+                break
+            }
+
+            let mut tokens = line.split_ascii_whitespace();
+            #[rustfmt::skip]
+            let (addr, name) = {
+                let addr = if let Some(token) = tokens.next() { token } else { continue };
+                let _typ = if let Some(token) = tokens.next() { token } else { continue };
+                let name = if let Some(token) = tokens.next() { token } else { continue };
+                (addr, name)
+            };
+            let module = tokens.next();
+
+            let addr = match Addr::from_str_radix(addr, 16) {
+                // Addresses are reported as zero if the reader lacks
+                // the necessary privileges.
+                Ok(addr) if addr != 0 => addr,
+                _ => continue,
+            };
+            let () = addrs.push(addr);
+
+            match (name, module) {
+                ("_stext", None) => stext = Some(addr),
+                ("_etext", None) => etext = Some(addr),
+                (name, Some("[bpf]")) => {
+                    if let Some(prog) = parse_bpf_prog(name, addr) {
+                        let () = bpf_progs.push(prog);
+                    }
+                }
+                _ => (),
+            }
+        }
+
+        self.text = match (stext, etext) {
+            (Some(stext), Some(etext)) if stext < etext => Some(stext..etext),
+            _ => None,
+        };
+
+        // kallsyms does not contain size information. We assume that a
+        // BPF program extends up to the next symbol.
+        let () = addrs.sort_unstable();
+        let () = bpf_progs.sort_by_key(|prog| prog.addr);
+        self.bpf_progs = bpf_progs
+            .into_iter()
+            .map(|prog| {
+                let idx = addrs.partition_point(|addr| *addr <= prog.addr);
+                let end = addrs.get(idx).copied().unwrap_or(Addr::MAX);
+                (prog.addr..end, prog)
+            })
+            .collect();
+        Ok(())
+    }
+
+    /// Parse the list of loaded kernel modules, as present in
+    /// `/proc/modules`, from `reader`.
+    ///
+    /// Each line has the form `<name> <size> <refcnt> <deps> <state>
+    /// <addr> [<taints>]`.
+    fn parse_modules<R>(&mut self, reader: R) -> Result<()>
+    where
+        R: Read,
+    {
+        let mut modules = Vec::new();
+        for line in BufReader::new(reader).lines() {
+            let line = line?;
+            let mut tokens = line.split_ascii_whitespace();
+            let name = tokens.next();
+            let size = tokens.next();
+            let addr = tokens.nth(3);
+
+            let (name, size, addr) = match (name, size, addr) {
+                (Some(name), Some(size), Some(addr)) => (name, size, addr),
+                _ => continue,
+            };
+            let size = match size.parse::<u64>() {
+                Ok(size) => size,
+                Err(..) => continue,
+            };
+            let addr = addr.strip_prefix("0x").unwrap_or(addr);
+            let addr = match Addr::from_str_radix(addr, 16) {
+                Ok(addr) if addr != 0 => addr,
+                _ => continue,
+            };
+
+            let module = KernelModule {
+                name: name.to_string(),
+                addr,
+                _non_exhaustive: (),
+            };
+            let () = modules.push((addr..addr.saturating_add(size), module));
+        }
+
+        let () = modules.sort_by_key(|(range, _module)| range.start);
+        self.modules = modules;
+        Ok(())
+    }
+
+    /// Load the layout of the running kernel.
+    fn load() -> Result<Self> {
+        let mut slf = Self::default();
+        let kallsyms = File::open(KALLSYMS)
+            .with_context(|| format!("failed to open `{KALLSYMS}` for reading"))?;
+        let () = slf.parse_kallsyms(kallsyms)?;
+
+        match File::open(MODULES) {
+            Ok(modules) => slf.parse_modules(modules)?,
+            Err(err) => {
+                log::debug!("failed to open {MODULES}: {err}; not normalizing module addresses");
+            }
+        }
+        Ok(slf)
+    }
+}
+
+
+/// Find the entry in `entries` whose range contains `addr`.
+fn find_entry<T>(entries: &[(Range<Addr>, T)], addr: Addr) -> Option<usize> {
+    let idx = find_match_or_lower_bound_by_key(entries, addr, |(range, _)| range.start)?;
+    let (range, _) = entries.get(idx)?;
+    range.contains(&addr).then_some(idx)
+}
+
+
+/// Normalize `addrs` given the provided kernel layout.
+fn normalize_kernel_addrs_with_layout(
+    addrs: &[Addr],
+    layout: &KernelLayout,
+    kaslr_offset: u64,
+) -> KernelOutput {
+    fn meta_idx(
+        meta: &mut Vec<KernelMeta>,
+        idx: &mut Option<usize>,
+        create: impl FnOnce() -> KernelMeta,
+    ) -> usize {
+        *idx.get_or_insert_with(|| {
+            let () = meta.push(create());
+            meta.len() - 1
+        })
+    }
+
+    let mut meta = Vec::new();
+    let mut kernel_idx = None;
+    let mut unknown_idx = None;
+    let mut module_idxs = vec![None; layout.modules.len()];
+    let mut bpf_prog_idxs = vec![None; layout.bpf_progs.len()];
+
+    let outputs = addrs
+        .iter()
+        .map(|addr| {
+            let addr = *addr;
+            if layout
+                .text
+                .as_ref()
+                .map(|text| text.contains(&addr))
+                .unwrap_or(false)
+            {
+                let idx = meta_idx(&mut meta, &mut kernel_idx, || {
+                    KernelMeta::Kernel(Kernel {
+                        kaslr_offset,
+                        _non_exhaustive: (),
+                    })
+                });
+                (addr.wrapping_sub(kaslr_offset), idx)
+            } else if let Some(idx) = find_entry(&layout.modules, addr) {
+                let (range, module) = &layout.modules[idx];
+                let idx = meta_idx(&mut meta, &mut module_idxs[idx], || {
+                    KernelMeta::Module(module.clone())
+                });
+                (addr - range.start, idx)
+            } else if let Some(idx) = find_entry(&layout.bpf_progs, addr) {
+                let (range, prog) = &layout.bpf_progs[idx];
+                let idx = meta_idx(&mut meta, &mut bpf_prog_idxs[idx], || {
+                    KernelMeta::BpfProg(prog.clone())
+                });
+                (addr - range.start, idx)
+            } else {
+                let idx = meta_idx(&mut meta, &mut unknown_idx, || {
+                    KernelMeta::Unknown(Unknown::new(Reason::Unmapped))
+                });
+                (addr, idx)
+            }
+        })
+        .collect();
+
+    KernelOutput { outputs, meta }
+}
+
+
+/// Normalize kernel addresses of the running system.
+pub(super) fn normalize_kernel_addrs(addrs: &[Addr]) -> Result<KernelOutput> {
+    let layout = KernelLayout::load()?;
+    let kaslr_offset = context::kaslr_offset()
+        .context("failed to query system KASLR offset")?
+        .unwrap_or_default();
+    Ok(normalize_kernel_addrs_with_layout(
+        addrs,
+        &layout,
+        kaslr_offset,
+    ))
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use test_log::test;
+
+
+    const KALLSYMS_DATA: &str = "\
+ffffffff81000000 T _stext
+ffffffff81000100 T do_one_initcall
+ffffffff82000000 T _etext
+ffffffffa0001000 t bpf_prog_6deef7357e7b4530_sd_devices\t[bpf]
+ffffffffa0001800 t bpf_prog_ee0e253c78993a24\t[bpf]
+ffffffffa0002000 t bpf_trampoline_6442453466\t[bpf]
+ffffffffc0a00000 t nft_do_chain\t[nf_tables]
+";
+    const MODULES_DATA: &str = "\
+nf_tables 372736 0 - Live 0xffffffffc0a00000
+hidden 4096 0 - Live 0x0000000000000000
+";
+
+
+    /// Check that we can parse BPF program names as they appear in
+    /// `kallsyms`.
+    #[test]
+    fn bpf_prog_parsing() {
+        let prog = parse_bpf_prog("bpf_prog_6deef7357e7b4530_sd_devices", 0x1337).unwrap();
+        assert_eq!(prog.name, "sd_devices");
+        assert_eq!(prog.tag, [0x6d, 0xee, 0xf7, 0x35, 0x7e, 0x7b, 0x45, 0x30]);
+        assert_eq!(prog.addr, 0x1337);
+
+        let prog = parse_bpf_prog("bpf_prog_ee0e253c78993a24", 0x1337).unwrap();
+        assert_eq!(prog.name, "");
+
+        assert_eq!(parse_bpf_prog("bpf_prog_xyz_foo", 0x1337), None);
+        assert_eq!(parse_bpf_prog("bpf_trampoline_6442453466", 0x1337), None);
+    }
+
+    /// Check that kernel addresses are classified and normalized as
+    /// expected.
+    #[test]
+    fn kernel_addr_normalization() {
+        let mut layout = KernelLayout::default();
+        let () = layout.parse_kallsyms(KALLSYMS_DATA.as_bytes()).unwrap();
+        let () = layout.parse_modules(MODULES_DATA.as_bytes()).unwrap();
+        assert_eq!(layout.text, Some(0xffffffff81000000..0xffffffff82000000));
+        assert_eq!(layout.modules.len(), 1);
+        assert_eq!(layout.bpf_progs.len(), 2);
+
+        let kaslr_offset = 0x1000000;
+        let addrs = [
+            0xffffffff81000142,
+            0xffffffffc0a00010,
+            0xffffffffa0001010,
+            0xffffffffa0001810,
+            0x7f0000001000,
+            0xffffffff81000200,
+            0xffffffffa0002010,
+        ];
+        let normalized = normalize_kernel_addrs_with_layout(&addrs, &layout, kaslr_offset);
+        assert_eq!(normalized.outputs.len(), addrs.len());
+
+        let (offset, idx) = normalized.outputs[0];
+        assert_eq!(offset, 0xffffffff80000142);
+        let kernel = normalized.meta[idx].as_kernel().unwrap();
+        assert_eq!(kernel.kaslr_offset, kaslr_offset);
+
+        let (offset, idx) = normalized.outputs[1];
+        assert_eq!(offset, 0x10);
+        let module = normalized.meta[idx].as_module().unwrap();
+        assert_eq!(module.name, "nf_tables");
+        assert_eq!(module.addr, 0xffffffffc0a00000);
+
+        let (offset, idx) = normalized.outputs[2];
+        assert_eq!(offset, 0x10);
+        let prog = normalized.meta[idx].as_bpf_prog().unwrap();
+        assert_eq!(prog.name, "sd_devices");
+
+        let (offset, idx) = normalized.outputs[3];
+        assert_eq!(offset, 0x10);
+        let prog = normalized.meta[idx].as_bpf_prog().unwrap();
+        assert_eq!(prog.name, "");
+
+        let (offset, idx) = normalized.outputs[4];
+        assert_eq!(offset, 0x7f0000001000);
+        let unknown = normalized.meta[idx].as_unknown().unwrap();
+        assert_eq!(unknown.reason, Reason::Unmapped);
+
+        // Meta data should be shared between addresses with the same
+        // origin.
+        assert_eq!(normalized.outputs[5].1, normalized.outputs[0].1);
+        // Addresses in BPF trampolines are not attributed to programs.
+        assert_eq!(normalized.outputs[6].1, normalized.outputs[4].1);
+        assert_eq!(normalized.meta.len(), 5);
+    }
+
+    /// Check that we can normalize addresses of the running kernel.
+    #[test]
+    fn running_kernel_addr_normalization() {
+        let addrs = [0x1337, 0xffffffff81000000];
+        let normalized = normalize_kernel_addrs(&addrs).unwrap();
+        assert_eq!(normalized.outputs.len(), addrs.len());
+        assert_eq!(normalized.outputs[0].0, 0x1337);
+        assert!(normalized.meta[normalized.outputs[0].1]
+            .as_unknown()
+            .is_some());
+    }
+}
@@ -1,5 +1,7 @@
 use std::path::PathBuf;
 
+use crate::Addr;
+
 use super::buildid::BuildId;
 use super::Reason;
 
@@ -38,11 +40,14 @@ use super::Reason;
 ///   .unwrap();
 /// ```
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
 pub struct Apk {
     /// The canonical absolute path to the APK, including its name.
+    #[cfg_attr(feature = "serde", serde(with = "crate::util::serde_path"))]
     pub path: PathBuf,
     /// The struct is non-exhaustive and open to extension.
     #[doc(hidden)]
+    #[cfg_attr(feature = "serde", serde(skip))]
     pub _non_exhaustive: (),
 }
 
@@ -51,13 +56,51 @@ pub struct Apk {
 ///
 /// This type is used in the [`UserMeta::Elf`] variant.
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
 pub struct Elf<'src> {
     /// The canonical absolute path to the ELF file, including its name.
+    #[cfg_attr(feature = "serde", serde(with = "crate::util::serde_path"))]
     pub path: PathBuf,
     /// The ELF file's build ID, if available and readable.
     pub build_id: Option<BuildId<'src>>,
     /// The struct is non-exhaustive and open to extension.
     #[doc(hidden)]
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub _non_exhaustive: (),
+}
+
+
+/// Meta information about a shared memory or hugetlbfs backed mapping.
+///
+/// This type is used in the [`UserMeta::SharedMem`] variant.
+///
+/// Such mappings are commonly used for code by just-in-time compilers
+/// or DPDK-style applications. The corresponding output is the offset
+/// into the shared memory object. Because the object does not carry
+/// any symbol information itself, symbolization requires support by
+/// the application that created it, e.g., via a
+/// [`SharedMemDispatch`][crate::symbolize::SharedMemDispatch]
+/// callback.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub struct SharedMem {
+    /// The name of the mapping, as reported in `/proc/<pid>/maps`
+    /// (e.g., `/dev/shm/jit-1234`, `/SYSV00001234`, `/memfd:jit`, or
+    /// `/anon_hugepage`).
+    ///
+    /// Note that names are not necessarily unique.
+    #[cfg_attr(feature = "serde", serde(with = "crate::util::serde_path"))]
+    pub path: PathBuf,
+    /// The virtual address at which the mapping starts.
+    pub addr: Addr,
+    /// The offset of the mapping into the shared memory object.
+    ///
+    /// Together with `addr` this offset can be used to convert the
+    /// normalized output back into a virtual address, if necessary.
+    pub offset: u64,
+    /// The struct is non-exhaustive and open to extension.
+    #[doc(hidden)]
+    #[cfg_attr(feature = "serde", serde(skip))]
     pub _non_exhaustive: (),
 }
 
@@ -70,6 +113,7 @@ pub struct Elf<'src> {
 /// An unknown address will be reported in non-normalized form (i.e., as
 /// provided as input by the user).
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
 pub struct Unknown {
     /// The reason why normalization failed.
     ///
@@ -78,6 +122,7 @@ pub struct Unknown {
     pub reason: Reason,
     /// The struct is non-exhaustive and open to extension.
     #[doc(hidden)]
+    #[cfg_attr(feature = "serde", serde(skip))]
     pub _non_exhaustive: (),
 }
 
@@ -100,12 +145,16 @@ impl From<Unknown> for UserMeta<'_> {
 
 /// Meta information for an address.
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
 #[non_exhaustive]
 pub enum UserMeta<'src> {
     /// The address belongs to an APK file.
     Apk(Apk),
     /// The address belongs to an ELF file.
     Elf(Elf<'src>),
+    /// The address belongs to a shared memory or hugetlbfs backed
+    /// mapping.
+    SharedMem(SharedMem),
     /// The address' origin is unknown.
     Unknown(Unknown),
 }
@@ -129,6 +178,131 @@ impl<'src> UserMeta<'src> {
         }
     }
 
+    /// Retrieve the [`SharedMem`] of this enum, if this variant is
+    /// active.
+    #[inline]
+    pub fn as_shared_mem(&self) -> Option<&SharedMem> {
+        match self {
+            Self::SharedMem(shared_mem) => Some(shared_mem),
+            _ => None,
+        }
+    }
+
+    /// Retrieve the [`Unknown`] of this enum, if this variant is active.
+    #[inline]
+    pub fn as_unknown(&self) -> Option<&Unknown> {
+        match self {
+            Self::Unknown(unknown) => Some(unknown),
+            _ => None,
+        }
+    }
+}
+
+
+/// Meta information about the core kernel image.
+///
+/// This type is used in the [`KernelMeta::Kernel`] variant.
+///
+/// The corresponding output is the address as it appears in the kernel
+/// image (e.g., `vmlinux`), i.e., with the KASLR offset removed.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub struct Kernel {
+    /// The KASLR offset that was applied to the kernel image.
+    pub kaslr_offset: u64,
+    /// The struct is non-exhaustive and open to extension.
+    #[doc(hidden)]
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub _non_exhaustive: (),
+}
+
+
+/// Meta information about a loaded kernel module.
+///
+/// This type is used in the [`KernelMeta::Module`] variant.
+///
+/// The corresponding output is the offset of the address from the
+/// module's load address.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub struct KernelModule {
+    /// The name of the module.
+    pub name: String,
+    /// The address at which the module is loaded.
+    pub addr: Addr,
+    /// The struct is non-exhaustive and open to extension.
+    #[doc(hidden)]
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub _non_exhaustive: (),
+}
+
+
+/// Meta information about a BPF program.
+///
+/// This type is used in the [`KernelMeta::BpfProg`] variant.
+///
+/// The corresponding output is the offset of the address from the start
+/// of the program.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub struct BpfProg {
+    /// The name of the program, which may be empty.
+    pub name: String,
+    /// The program's tag, which is a hash over its instructions.
+    pub tag: [u8; 8],
+    /// The address at which the program is located.
+    pub addr: Addr,
+    /// The struct is non-exhaustive and open to extension.
+    #[doc(hidden)]
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub _non_exhaustive: (),
+}
+
+
+/// Meta information for a kernel address.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[non_exhaustive]
+pub enum KernelMeta {
+    /// The address belongs to the text of the core kernel image.
+    Kernel(Kernel),
+    /// The address belongs to a kernel module.
+    Module(KernelModule),
+    /// The address belongs to a BPF program.
+    BpfProg(BpfProg),
+    /// The address' origin is unknown.
+    Unknown(Unknown),
+}
+
+impl KernelMeta {
+    /// Retrieve the [`Kernel`] of this enum, if this variant is active.
+    #[inline]
+    pub fn as_kernel(&self) -> Option<&Kernel> {
+        match self {
+            Self::Kernel(kernel) => Some(kernel),
+            _ => None,
+        }
+    }
+
+    /// Retrieve the [`KernelModule`] of this enum, if this variant is
+    /// active.
+    #[inline]
+    pub fn as_module(&self) -> Option<&KernelModule> {
+        match self {
+            Self::Module(module) => Some(module),
+            _ => None,
+        }
+    }
+
+    /// Retrieve the [`BpfProg`] of this enum, if this variant is active.
+    #[inline]
+    pub fn as_bpf_prog(&self) -> Option<&BpfProg> {
+        match self {
+            Self::BpfProg(prog) => Some(prog),
+            _ => None,
+        }
+    }
+
     /// Retrieve the [`Unknown`] of this enum, if this variant is active.
     #[inline]
     pub fn as_unknown(&self) -> Option<&Unknown> {
@@ -139,6 +313,12 @@ impl<'src> UserMeta<'src> {
     }
 }
 
+impl From<Unknown> for KernelMeta {
+    fn from(unknown: Unknown) -> Self {
+        Self::Unknown(unknown)
+    }
+}
+
 
 #[cfg(test)]
 mod tests {
@@ -155,6 +335,7 @@ mod tests {
         });
         assert!(meta.as_apk().is_some());
         assert!(meta.as_elf().is_none());
+        assert!(meta.as_shared_mem().is_none());
         assert!(meta.as_unknown().is_none());
 
         let meta = UserMeta::Elf(Elf {
@@ -164,6 +345,18 @@ mod tests {
         });
         assert!(meta.as_apk().is_none());
         assert!(meta.as_elf().is_some());
+        assert!(meta.as_shared_mem().is_none());
+        assert!(meta.as_unknown().is_none());
+
+        let meta = UserMeta::SharedMem(SharedMem {
+            path: PathBuf::from("/dev/shm/jit"),
+            addr: 0x7f2d3cc00000,
+            offset: 0x200000,
+            _non_exhaustive: (),
+        });
+        assert!(meta.as_apk().is_none());
+        assert!(meta.as_elf().is_none());
+        assert!(meta.as_shared_mem().is_some());
         assert!(meta.as_unknown().is_none());
 
         let meta = UserMeta::Unknown(Unknown {
@@ -172,6 +365,38 @@ mod tests {
         });
         assert!(meta.as_apk().is_none());
         assert!(meta.as_elf().is_none());
+        assert!(meta.as_shared_mem().is_none());
         assert!(meta.as_unknown().is_some());
     }
+
+    /// Check that normalization output survives a round trip through
+    /// `serde`.
+    #[cfg(feature = "serde")]
+    #[test]
+    fn user_output_serde_roundtrip() {
+        use std::borrow::Cow;
+
+        use crate::normalize::UserOutput;
+
+        let output = UserOutput {
+            outputs: vec![(0x1337, 0), (0x42, 1), (0xdead, 2)],
+            meta: vec![
+                UserMeta::Elf(Elf {
+                    path: PathBuf::from("/tmp/executable.bin"),
+                    build_id: Some(Cow::Borrowed(&[0xde, 0xad, 0xbe, 0xef])),
+                    _non_exhaustive: (),
+                }),
+                UserMeta::Apk(Apk {
+                    path: PathBuf::from("/tmp/archive.apk"),
+                    _non_exhaustive: (),
+                }),
+                UserMeta::Unknown(Unknown::new(Reason::Unmapped)),
+            ],
+        };
+
+        let json = serde_json::to_string(&output).unwrap();
+        let deserialized = serde_json::from_str::<UserOutput>(&json).unwrap();
+        assert_eq!(deserialized.outputs, output.outputs);
+        assert_eq!(deserialized.meta, output.meta);
+    }
 }
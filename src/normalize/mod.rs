@@ -44,17 +44,25 @@
 
 pub(crate) mod buildid;
 pub(crate) mod ioctl;
+mod kernel;
 mod meta;
 mod normalizer;
 mod user;
+mod wire;
 
 use std::fmt::Display;
 use std::fmt::Formatter;
 use std::fmt::Result as FmtResult;
 use std::str;
 
+pub use kernel::KernelOutput;
 pub use meta::Apk;
+pub use meta::BpfProg;
 pub use meta::Elf;
+pub use meta::Kernel;
+pub use meta::KernelMeta;
+pub use meta::KernelModule;
+pub use meta::SharedMem;
 pub use meta::Unknown;
 pub use meta::UserMeta;
 pub use normalizer::Builder;
@@ -64,6 +72,8 @@ pub use normalizer::Normalizer;
 #[doc(hidden)]
 pub use normalizer::Output;
 pub use user::UserOutput;
+pub use wire::decode;
+pub use wire::encode;
 
 pub(crate) use user::normalize_sorted_user_addrs_with_entries;
 pub(crate) use user::Handler;
@@ -100,6 +110,7 @@ pub struct NormalizeOpts {
 /// over time and, hence, should not be relied upon for the correctness of the
 /// application.
 #[derive(Copy, Clone, Debug, Eq, Hash, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
 #[non_exhaustive]
 pub enum Reason {
     /// The absolute address was not found in the corresponding process' virtual
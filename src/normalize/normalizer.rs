@@ -17,6 +17,8 @@ use super::buildid::BuildId;
 use super::buildid::DefaultBuildIdReader;
 use super::buildid::NoBuildIdReader;
 use super::ioctl::query_procmap;
+use super::kernel;
+use super::kernel::KernelOutput;
 use super::user;
 use super::user::normalize_sorted_user_addrs_with_entries;
 use super::user::UserOutput;
@@ -29,6 +31,7 @@ use super::NormalizeOpts;
 /// input necessary for addresses to be symbolized on a system other than where
 /// they were recorded.
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
 pub struct Output<M> {
     /// Outputs along with an index into `meta` for retrieval of the
     /// corresponding meta information.
@@ -239,7 +242,7 @@ impl Normalizer {
     fn normalize_user_addrs_iter<A>(
         &self,
         addrs: A,
-        pid: Pid,
+        pid: &Pid,
         map_files: bool,
     ) -> Result<UserOutput>
     where
@@ -249,26 +252,28 @@ impl Normalizer {
             let path = format!("/proc/{pid}/maps");
             let file = File::open(&path)
                 .with_context(|| format!("failed to open `{path}` for reading"))?;
+            // Make sure that the file we opened actually belongs to the
+            // process in question.
+            let () = pid.check_alive()?;
 
             if !self.cache_vmas {
                 let entries =
                     move |addr| query_procmap(&file, pid, addr, self.build_ids).transpose();
                 self.normalize_user_addrs_impl(addrs, entries, map_files)
             } else {
-                let entries = self.cached_entries.get_or_try_insert(pid, || {
+                let entries = self.cached_entries.get_or_try_insert(pid.clone(), || {
                     let mut entries = Vec::new();
                     let mut next_addr = 0;
                     while let Some(entry) = query_procmap(&file, pid, next_addr, self.build_ids)? {
                         next_addr = entry.range.end;
-                        if maps::filter_relevant(&entry) {
+                        if maps::is_symbolization_relevant(&entry) {
                             let () = entries.push(entry);
                         }
                     }
                     Ok(entries.into_boxed_slice())
                 })?;
 
-                let mut entry_iter = entries.iter().map(Ok);
-                let entries = |_addr| entry_iter.next();
+                let entries = maps::entry_lookup(entries);
                 self.normalize_user_addrs_impl(addrs, entries, map_files)
             }
         } else {
@@ -277,7 +282,7 @@ impl Normalizer {
                 let entries = |_addr| entry_iter.next();
                 self.normalize_user_addrs_impl(addrs, entries, map_files)
             } else {
-                let parsed = self.cached_entries.get_or_try_insert(pid, || {
+                let parsed = self.cached_entries.get_or_try_insert(pid.clone(), || {
                     // If we use the cached maps entries but don't have anything
                     // cached yet, then just parse the file eagerly and take it from
                     // there.
@@ -285,8 +290,7 @@ impl Normalizer {
                     Ok(parsed)
                 })?;
 
-                let mut entry_iter = parsed.iter().map(Ok);
-                let entries = |_addr| entry_iter.next();
+                let entries = maps::entry_lookup(parsed);
                 self.normalize_user_addrs_impl(addrs, entries, map_files)
             }
         }
@@ -323,22 +327,80 @@ impl Normalizer {
             _non_exhaustive: (),
         } = *opts;
 
-        if sorted_addrs {
-            self.normalize_user_addrs_iter(addrs.iter().copied(), pid, map_files)
+        let output = if sorted_addrs {
+            self.normalize_user_addrs_iter(addrs.iter().copied(), &pid, map_files)
         } else {
             util::with_ordered_elems(
                 addrs,
                 |normalized: &mut UserOutput| normalized.outputs.as_mut_slice(),
-                |sorted_addrs| self.normalize_user_addrs_iter(sorted_addrs, pid, map_files),
+                |sorted_addrs| self.normalize_user_addrs_iter(sorted_addrs, &pid, map_files),
             )
-        }
+        }?;
+
+        // Build IDs may have been read from files opened via
+        // `/proc/<pid>/map_files/`. Make sure that they actually belong
+        // to the process in question.
+        let () = pid.check_alive()?;
+        Ok(output)
     }
 
     /// Normalize addresses belonging to a process.
     ///
     /// A convenience wrapper around [`Normalizer::normalize_user_addrs_opts`][]
     /// that uses the default normalization options.
+    ///
+    /// `addrs` may be provided in arbitrary order. They are sorted
+    /// internally and outputs are reported in the original order. If
+    /// you know addresses to be sorted already, consider using
+    /// [`Normalizer::normalize_user_addrs_sorted`][] instead.
     pub fn normalize_user_addrs(&self, pid: Pid, addrs: &[Addr]) -> Result<UserOutput> {
         self.normalize_user_addrs_opts(pid, addrs, &NormalizeOpts::default())
     }
+
+    /// Normalize sorted addresses belonging to a process.
+    ///
+    /// A convenience wrapper around [`Normalizer::normalize_user_addrs_opts`][]
+    /// that uses the default normalization options, except for
+    /// [`NormalizeOpts::sorted_addrs`][], which is set.
+    ///
+    /// `addrs` have to be sorted in ascending order, or an error will
+    /// be returned. In return, no sorting and restoring of the
+    /// original order has to happen, making this method faster than
+    /// [`Normalizer::normalize_user_addrs`][].
+    pub fn normalize_user_addrs_sorted(&self, pid: Pid, addrs: &[Addr]) -> Result<UserOutput<'_>> {
+        let opts = NormalizeOpts {
+            sorted_addrs: true,
+            ..Default::default()
+        };
+        self.normalize_user_addrs_opts(pid, addrs, &opts)
+    }
+
+    /// Normalize addresses belonging to the running kernel.
+    ///
+    /// Each of the provided `addrs` is classified as belonging to the
+    /// text of the core kernel image, a loaded kernel module, or a BPF
+    /// program, and normalized accordingly (refer to
+    /// [`Kernel`][crate::normalize::Kernel],
+    /// [`KernelModule`][crate::normalize::KernelModule], and
+    /// [`BpfProg`][crate::normalize::BpfProg] for details).
+    ///
+    /// Addresses that can't be attributed to any of these, including
+    /// user space addresses that may be present in a mixed stack trace,
+    /// are not normalized and reported as
+    /// [`Unknown`][crate::normalize::Unknown] meta entries. As a result,
+    /// a mixed stack can be normalized by passing it to both this method
+    /// and [`Normalizer::normalize_user_addrs`][] and picking, for each
+    /// address, the output that is not unknown.
+    ///
+    /// Information about the kernel's layout is retrieved from
+    /// `/proc/kallsyms` and `/proc/modules`. Note that the kernel may
+    /// hide addresses from these files for insufficiently privileged
+    /// users, in which case all addresses will be reported as unknown.
+    ///
+    /// Normalized outputs are reported in the exact same order (and in
+    /// equal amount) in which the non-normalized ones were provided.
+    #[cfg_attr(feature = "tracing", crate::log::instrument(skip_all, fields(addrs = ?Hexify(addrs)), err))]
+    pub fn normalize_kernel_addrs(&self, addrs: &[Addr]) -> Result<KernelOutput> {
+        kernel::normalize_kernel_addrs(addrs)
+    }
 }
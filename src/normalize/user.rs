@@ -6,6 +6,7 @@ use std::path::Path;
 use std::path::PathBuf;
 
 use crate::maps;
+use crate::maps::is_shared_mem;
 use crate::maps::MapsEntry;
 use crate::maps::PathName;
 use crate::Addr;
@@ -16,6 +17,7 @@ use crate::Result;
 use super::buildid::BuildIdReader;
 use super::meta::Apk;
 use super::meta::Elf;
+use super::meta::SharedMem;
 use super::meta::Unknown;
 use super::meta::UserMeta;
 use super::normalizer::Output;
@@ -46,6 +48,19 @@ fn make_apk_meta(path: &Path) -> UserMeta<'static> {
 }
 
 
+/// Make a [`UserMeta::SharedMem`] variant.
+fn make_shared_mem_meta(path: &Path, entry: &MapsEntry) -> UserMeta<'static> {
+    let shared_mem = SharedMem {
+        path: path.to_path_buf(),
+        addr: entry.range.start,
+        offset: entry.offset,
+        _non_exhaustive: (),
+    };
+    let meta = UserMeta::SharedMem(shared_mem);
+    meta
+}
+
+
 /// A type representing the output of user addresses normalization.
 pub type UserOutput<'src> = Output<UserMeta<'src>>;
 
@@ -103,6 +118,30 @@ impl<'src> UserOutput<'src> {
         let () = self.outputs.push((file_offset, meta_idx));
         Ok(())
     }
+
+    /// Add an offset into a shared memory object to this object.
+    ///
+    /// Names of shared memory mappings are not necessarily unique,
+    /// which is why meta data are tracked on a per-mapping basis, as
+    /// identified by their start address.
+    fn add_shared_mem_offset(
+        &mut self,
+        offset: u64,
+        entry: &MapsEntry,
+        path: &Path,
+        shared_mem_lookup: &mut HashMap<Addr, usize>,
+    ) {
+        let meta_idx = match shared_mem_lookup.entry(entry.range.start) {
+            Entry::Occupied(occupied) => *occupied.get(),
+            Entry::Vacant(vacancy) => {
+                let meta_idx = self.meta.len();
+                let () = self.meta.push(make_shared_mem_meta(path, entry));
+                *vacancy.insert(meta_idx)
+            }
+        };
+
+        let () = self.outputs.push((offset, meta_idx));
+    }
 }
 
 
@@ -126,6 +165,9 @@ pub(super) struct NormalizationHandler<'reader, 'src> {
     /// A mapping from [`Reason`] to the index of the `Unknown` entry with this
     /// very reason in `meta_lookup`, if any.
     unknown_cache: HashMap<Reason, usize>,
+    /// Lookup table from the start address of a shared memory mapping
+    /// to the index of its meta data in `output.meta`.
+    shared_mem_lookup: HashMap<Addr, usize>,
     /// Report `map_files` entries instead of symbolic paths.
     map_files: bool,
 }
@@ -145,6 +187,7 @@ impl<'reader, 'src> NormalizationHandler<'reader, 'src> {
             build_id_reader: reader,
             meta_lookup: HashMap::new(),
             unknown_cache: HashMap::new(),
+            shared_mem_lookup: HashMap::new(),
             map_files,
         }
     }
@@ -167,6 +210,16 @@ impl Handler<Reason> for NormalizationHandler<'_, '_> {
                     &entry_path.symbolic_path
                 };
                 let file_off = addr - entry.range.start + entry.offset;
+                if is_shared_mem(&entry_path.symbolic_path) {
+                    let () = self.normalized.add_shared_mem_offset(
+                        file_off,
+                        entry,
+                        &entry_path.symbolic_path,
+                        &mut self.shared_mem_lookup,
+                    );
+                    return Ok(())
+                }
+
                 let ext = entry_path
                     .symbolic_path
                     .extension()
@@ -322,8 +375,12 @@ mod tests {
             let addrs = [unknown_addr as Addr];
             let map_files = false;
 
-            let mut entry_iter = maps::parse_file(maps.as_bytes(), pid)
-                .filter(|result| result.as_ref().map(maps::filter_relevant).unwrap_or(true));
+            let mut entry_iter = maps::parse_file(maps.as_bytes(), pid).filter(|result| {
+                result
+                    .as_ref()
+                    .map(maps::is_symbolization_relevant)
+                    .unwrap_or(true)
+            });
             let entries = |_addr| entry_iter.next();
 
             let reader = NoBuildIdReader;
@@ -365,6 +422,7 @@ mod tests {
                 range: 0x10000..0x20000,
                 perm: Perm::default(),
                 offset: 0,
+                inode: 0,
                 path_name: Some(PathName::Component(
                     "doesntreallymatternowdoesit".to_string(),
                 )),
@@ -374,6 +432,7 @@ mod tests {
                 range: 0x30000..0x40000,
                 perm: Perm::default(),
                 offset: 0,
+                inode: 0,
                 path_name: None,
                 build_id: None,
             }),
@@ -400,6 +459,54 @@ mod tests {
         );
     }
 
+    /// Check that we normalize addresses in shared memory and
+    /// hugetlbfs backed mappings.
+    #[test]
+    fn normalize_shared_mem() {
+        let maps = r#"7f2d3c600000-7f2d3c800000 r-xs 00000000 00:01 32769                      /SYSV00000000 (deleted)
+7f2d3c800000-7f2d3ca00000 r-xs 00000000 00:01 32770                      /SYSV00000000 (deleted)
+7f2d3cc00000-7f2d3ce00000 r-xs 00200000 00:19 6                          /dev/shm/jit-1234
+"#;
+        let addrs = [
+            0x7f2d3c600010,
+            0x7f2d3c600020,
+            0x7f2d3c800030,
+            0x7f2d3cc00040,
+        ];
+        let map_files = false;
+
+        let mut entry_iter = maps::parse_file(maps.as_bytes(), Pid::Slf);
+        let entries = |_addr| entry_iter.next();
+
+        let reader = NoBuildIdReader;
+        let mut handler = NormalizationHandler::new(&reader, addrs.len(), map_files);
+        let () = normalize_sorted_user_addrs_with_entries(
+            addrs.as_slice().iter().copied(),
+            entries,
+            &mut handler,
+        )
+        .unwrap();
+
+        let normalized = handler.normalized;
+        assert_eq!(
+            normalized.outputs,
+            vec![(0x10, 0), (0x20, 0), (0x30, 1), (0x200040, 2)]
+        );
+        // The two System V segments have the same name, but they are
+        // different objects.
+        assert_eq!(normalized.meta.len(), 3);
+
+        let shared_mem = normalized.meta[1].as_shared_mem().unwrap();
+        assert_eq!(shared_mem.path, Path::new("/SYSV00000000"));
+        assert_eq!(shared_mem.addr, 0x7f2d3c800000);
+        assert_eq!(shared_mem.offset, 0);
+
+        let shared_mem = normalized.meta[2].as_shared_mem().unwrap();
+        assert_eq!(shared_mem.path, Path::new("/dev/shm/jit-1234"));
+        assert_eq!(shared_mem.addr, 0x7f2d3cc00000);
+        assert_eq!(shared_mem.offset, 0x200000);
+    }
+
     struct FailingBuildIdReader;
 
     impl BuildIdReader<'_> for FailingBuildIdReader {
@@ -415,7 +522,7 @@ mod tests {
         let addrs = [build_id_read_failures as Addr];
         let map_files = false;
 
-        let mut entry_iter = maps::parse_filtered(Pid::Slf).unwrap();
+        let mut entry_iter = maps::parse_filtered(&Pid::Slf).unwrap();
         let entries = |_addr| entry_iter.next();
 
         let reader = FailingBuildIdReader;
@@ -0,0 +1,454 @@
+use std::borrow::Cow;
+#[cfg(unix)]
+use std::os::unix::ffi::OsStrExt as _;
+use std::path::Path;
+use std::path::PathBuf;
+
+use crate::util::bytes_to_path;
+use crate::util::ReadRaw as _;
+use crate::Error;
+use crate::IntoError as _;
+use crate::Result;
+
+use super::meta::Apk;
+use super::meta::Elf;
+use super::meta::SharedMem;
+use super::meta::Unknown;
+use super::meta::UserMeta;
+use super::Reason;
+use super::UserOutput;
+
+
+/// The magic bytes every encoded object starts with.
+const MAGIC: [u8; 4] = *b"BZNA";
+/// The version of the wire format produced by [`encode`].
+const VERSION: u16 = 1;
+
+const TAG_APK: u8 = 1;
+const TAG_ELF: u8 = 2;
+const TAG_SHARED_MEM: u8 = 3;
+const TAG_UNKNOWN: u8 = 4;
+
+const REASON_UNMAPPED: u8 = 0;
+const REASON_MISSING_COMPONENT: u8 = 1;
+const REASON_UNSUPPORTED: u8 = 2;
+
+/// Flag bit indicating that an ELF meta record carries a build ID.
+const ELF_FLAG_BUILD_ID: u8 = 1 << 0;
+
+
+fn write_uleb128(buf: &mut Vec<u8>, mut value: u64) {
+    loop {
+        let byte = (value & 0x7f) as u8;
+        value >>= 7;
+        if value == 0 {
+            let () = buf.push(byte);
+            break
+        }
+        let () = buf.push(byte | 0x80);
+    }
+}
+
+fn write_bytes(buf: &mut Vec<u8>, bytes: &[u8]) {
+    let () = write_uleb128(buf, bytes.len() as u64);
+    let () = buf.extend_from_slice(bytes);
+}
+
+/// Retrieve the encoded representation of `path`.
+///
+/// On Unix systems paths are encoded as the raw bytes they consist of.
+#[cfg(unix)]
+fn path_bytes(path: &Path) -> Cow<'_, [u8]> {
+    Cow::Borrowed(path.as_os_str().as_bytes())
+}
+
+/// Retrieve the encoded representation of `path`.
+///
+/// Elsewhere paths are encoded as UTF-8, with invalid sequences
+/// replaced by `U+FFFD REPLACEMENT CHARACTER`.
+#[cfg(not(unix))]
+fn path_bytes(path: &Path) -> Cow<'_, [u8]> {
+    match path.to_string_lossy() {
+        Cow::Borrowed(path) => Cow::Borrowed(path.as_bytes()),
+        Cow::Owned(path) => Cow::Owned(path.into_bytes()),
+    }
+}
+
+fn write_path(buf: &mut Vec<u8>, path: &Path) {
+    write_bytes(buf, &path_bytes(path))
+}
+
+fn encode_meta(buf: &mut Vec<u8>, meta: &UserMeta<'_>) {
+    let mut payload = Vec::new();
+    let tag = match meta {
+        UserMeta::Apk(Apk {
+            path,
+            _non_exhaustive: (),
+        }) => {
+            let () = write_path(&mut payload, path);
+            TAG_APK
+        }
+        UserMeta::Elf(Elf {
+            path,
+            build_id,
+            _non_exhaustive: (),
+        }) => {
+            let () = write_path(&mut payload, path);
+            if let Some(build_id) = build_id {
+                let () = payload.push(ELF_FLAG_BUILD_ID);
+                let () = write_bytes(&mut payload, build_id);
+            } else {
+                let () = payload.push(0);
+            }
+            TAG_ELF
+        }
+        UserMeta::SharedMem(SharedMem {
+            path,
+            addr,
+            offset,
+            _non_exhaustive: (),
+        }) => {
+            let () = write_path(&mut payload, path);
+            let () = write_uleb128(&mut payload, *addr);
+            let () = write_uleb128(&mut payload, *offset);
+            TAG_SHARED_MEM
+        }
+        UserMeta::Unknown(Unknown {
+            reason,
+            _non_exhaustive: (),
+        }) => {
+            let reason = match reason {
+                Reason::Unmapped => REASON_UNMAPPED,
+                Reason::MissingComponent => REASON_MISSING_COMPONENT,
+                Reason::Unsupported => REASON_UNSUPPORTED,
+            };
+            let () = payload.push(reason);
+            TAG_UNKNOWN
+        }
+    };
+
+    let () = buf.push(tag);
+    let () = write_bytes(buf, &payload);
+}
+
+/// Encode the output of user address normalization in a compact and
+/// stable binary format.
+///
+/// The resulting bytes can be embedded into capture files or otherwise
+/// transferred to a different system, where [`decode`] can be used to
+/// recreate the [`UserOutput`] object.
+///
+/// # Format
+/// All multi-byte integers are encoded as unsigned little endian base
+/// 128 (ULEB128) values, with the exception of the version, which is a
+/// fixed size little endian `u16`. Byte strings (paths and build IDs)
+/// are encoded as a ULEB128 length followed by the raw bytes. The
+/// layout is:
+///
+/// - magic (4 bytes): `BZNA`
+/// - version (`u16`): currently `1`
+/// - meta count (ULEB128), followed by that many meta records, each consisting
+///   of:
+///   - tag (`u8`): `1` (APK), `2` (ELF), `3` (shared memory), or `4` (unknown)
+///   - payload (byte string):
+///     - APK: path
+///     - ELF: path, flags (`u8`; bit 0 indicates a build ID is present),
+///       optional build ID
+///     - shared memory: path, address (ULEB128), offset (ULEB128)
+///     - unknown: reason (`u8`): `0` (unmapped), `1` (missing component), or
+///       `2` (unsupported)
+/// - output count (ULEB128), followed by that many output records, each
+///   consisting of a file offset (ULEB128) and a meta index (ULEB128)
+///
+/// # Compatibility
+/// The version is only bumped for incompatible changes and [`decode`]
+/// rejects versions it does not know about. Within a version, the
+/// format may be extended in the following ways, all of which older
+/// decoders tolerate:
+/// - new fields may be appended to a meta record's payload; trailing payload
+///   bytes are ignored
+/// - new meta tags may be introduced; records with an unknown tag decode as
+///   [`UserMeta::Unknown`] with [`Reason::Unsupported`], preserving indices
+/// - new reasons may be introduced; unknown ones decode as
+///   [`Reason::Unsupported`]
+/// - unknown flag bits are ignored
+/// - data may be appended after the output records; it is ignored
+pub fn encode(output: &UserOutput<'_>) -> Vec<u8> {
+    let UserOutput { outputs, meta } = output;
+
+    let mut buf = Vec::new();
+    let () = buf.extend_from_slice(&MAGIC);
+    let () = buf.extend_from_slice(&VERSION.to_le_bytes());
+
+    let () = write_uleb128(&mut buf, meta.len() as u64);
+    let () = meta.iter().for_each(|meta| encode_meta(&mut buf, meta));
+
+    let () = write_uleb128(&mut buf, outputs.len() as u64);
+    let () = outputs.iter().for_each(|(offset, meta_idx)| {
+        let () = write_uleb128(&mut buf, *offset);
+        let () = write_uleb128(&mut buf, *meta_idx as u64);
+    });
+    buf
+}
+
+
+fn read_count(data: &mut &[u8], what: &str) -> Result<usize> {
+    let count = data
+        .read_u64_leb128()
+        .ok_or_invalid_data(|| format!("failed to read {what} count"))?;
+    // Each record occupies at least one byte. Bail out early on bogus
+    // counts instead of allocating excessive amounts of memory.
+    usize::try_from(count)
+        .ok()
+        .filter(|count| *count <= data.len())
+        .ok_or_invalid_data(|| format!("encountered invalid {what} count: {count}"))
+}
+
+fn read_bytes<'data>(data: &mut &'data [u8], what: &str) -> Result<&'data [u8]> {
+    data.read_u64_leb128()
+        .and_then(|len| usize::try_from(len).ok())
+        .and_then(|len| data.read_slice(len))
+        .ok_or_invalid_data(|| format!("failed to read {what}"))
+}
+
+fn read_path(data: &mut &[u8]) -> Result<PathBuf> {
+    let path = read_bytes(data, "path")?;
+    let path = bytes_to_path(path)?;
+    Ok(path.to_path_buf())
+}
+
+fn read_u64(data: &mut &[u8], what: &str) -> Result<u64> {
+    data.read_u64_leb128()
+        .ok_or_invalid_data(|| format!("failed to read {what}"))
+}
+
+fn read_u8(data: &mut &[u8], what: &str) -> Result<u8> {
+    data.read_u8()
+        .ok_or_invalid_data(|| format!("failed to read {what}"))
+}
+
+fn decode_meta<'data>(data: &mut &'data [u8]) -> Result<UserMeta<'data>> {
+    let tag = read_u8(data, "meta tag")?;
+    let mut payload = read_bytes(data, "meta payload")?;
+    let payload = &mut payload;
+
+    let meta = match tag {
+        TAG_APK => UserMeta::Apk(Apk {
+            path: read_path(payload)?,
+            _non_exhaustive: (),
+        }),
+        TAG_ELF => {
+            let path = read_path(payload)?;
+            let flags = read_u8(payload, "ELF flags")?;
+            let build_id = if flags & ELF_FLAG_BUILD_ID != 0 {
+                Some(Cow::Borrowed(read_bytes(payload, "build ID")?))
+            } else {
+                None
+            };
+            UserMeta::Elf(Elf {
+                path,
+                build_id,
+                _non_exhaustive: (),
+            })
+        }
+        TAG_SHARED_MEM => UserMeta::SharedMem(SharedMem {
+            path: read_path(payload)?,
+            addr: read_u64(payload, "shared memory address")?,
+            offset: read_u64(payload, "shared memory offset")?,
+            _non_exhaustive: (),
+        }),
+        TAG_UNKNOWN => {
+            let reason = match read_u8(payload, "reason")? {
+                REASON_UNMAPPED => Reason::Unmapped,
+                REASON_MISSING_COMPONENT => Reason::MissingComponent,
+                _ => Reason::Unsupported,
+            };
+            UserMeta::Unknown(Unknown::new(reason))
+        }
+        _ => UserMeta::Unknown(Unknown::new(Reason::Unsupported)),
+    };
+    Ok(meta)
+}
+
+/// Decode the output of user address normalization from data produced
+/// by [`encode`].
+///
+/// Build IDs are borrowed from `data`. Please refer to [`encode`] for a
+/// description of the format and the compatibility guarantees provided.
+pub fn decode(mut data: &[u8]) -> Result<UserOutput<'_>> {
+    let data = &mut data;
+    let magic = data
+        .read_array::<4>()
+        .ok_or_invalid_data(|| "failed to read magic")?;
+    if magic != MAGIC {
+        return Err(Error::with_invalid_data(format!(
+            "encountered invalid magic: {magic:x?}"
+        )))
+    }
+
+    let version = data
+        .read_array::<2>()
+        .map(u16::from_le_bytes)
+        .ok_or_invalid_data(|| "failed to read version")?;
+    if version != VERSION {
+        return Err(Error::with_unsupported(format!(
+            "encountered unsupported normalized output version: {version}"
+        )))
+    }
+
+    let count = read_count(data, "meta")?;
+    let meta = (0..count)
+        .map(|_| decode_meta(data))
+        .collect::<Result<Vec<_>>>()?;
+
+    let count = read_count(data, "output")?;
+    let outputs = (0..count)
+        .map(|_| {
+            let offset = read_u64(data, "file offset")?;
+            let meta_idx = read_u64(data, "meta index")?;
+            let meta_idx = usize::try_from(meta_idx)
+                .ok()
+                .filter(|idx| *idx < meta.len())
+                .ok_or_invalid_data(|| format!("encountered invalid meta index: {meta_idx}"))?;
+            Ok((offset, meta_idx))
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    Ok(UserOutput { outputs, meta })
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::ErrorKind;
+
+
+    fn output() -> UserOutput<'static> {
+        UserOutput {
+            outputs: vec![(0x1337, 1), (0xdeadbeef, 3), (0x42, 0), (0x0, 2)],
+            meta: vec![
+                UserMeta::Apk(Apk {
+                    path: PathBuf::from("/data/app/test.apk"),
+                    _non_exhaustive: (),
+                }),
+                UserMeta::Elf(Elf {
+                    path: PathBuf::from("/usr/lib64/libc.so.6"),
+                    build_id: Some(Cow::Borrowed(&[0xde, 0xad, 0xbe, 0xef])),
+                    _non_exhaustive: (),
+                }),
+                UserMeta::SharedMem(SharedMem {
+                    path: PathBuf::from("/dev/shm/foo"),
+                    addr: 0x7f0000000000,
+                    offset: 0x2000,
+                    _non_exhaustive: (),
+                }),
+                UserMeta::Unknown(Unknown::new(Reason::MissingComponent)),
+            ],
+        }
+    }
+
+
+    /// Check that we can round trip normalized output through the wire
+    /// format.
+    #[test]
+    fn wire_roundtrip() {
+        let output = output();
+        let data = encode(&output);
+        assert_eq!(&data[..6], b"BZNA\x01\x00");
+
+        let decoded = decode(&data).unwrap();
+        assert_eq!(decoded.outputs, output.outputs);
+        assert_eq!(decoded.meta, output.meta);
+
+        let output = UserOutput {
+            outputs: Vec::new(),
+            meta: vec![UserMeta::Elf(Elf {
+                path: PathBuf::from("/bin/true"),
+                build_id: None,
+                _non_exhaustive: (),
+            })],
+        };
+        let data = encode(&output);
+        let decoded = decode(&data).unwrap();
+        assert_eq!(decoded.outputs, output.outputs);
+        assert_eq!(decoded.meta, output.meta);
+    }
+
+    /// Make sure that decoding tolerates data produced by a newer
+    /// encoder in the ways documented.
+    #[test]
+    fn wire_forward_compat() {
+        let mut data = Vec::new();
+        let () = data.extend_from_slice(&MAGIC);
+        let () = data.extend_from_slice(&VERSION.to_le_bytes());
+        let () = write_uleb128(&mut data, 3);
+        // An APK record with an additional trailing field.
+        let mut payload = Vec::new();
+        let () = write_path(&mut payload, Path::new("/foo.apk"));
+        let () = payload.extend_from_slice(&[0xff, 0xff]);
+        let () = data.push(TAG_APK);
+        let () = write_bytes(&mut data, &payload);
+        // A record with a tag we don't know about.
+        let () = data.push(0x7f);
+        let () = write_bytes(&mut data, b"whatever");
+        // An unknown record with a reason we don't know about.
+        let () = data.push(TAG_UNKNOWN);
+        let () = write_bytes(&mut data, &[0x7f]);
+        let () = write_uleb128(&mut data, 1);
+        let () = write_uleb128(&mut data, 0x1000);
+        let () = write_uleb128(&mut data, 1);
+        // Some trailing data.
+        let () = data.extend_from_slice(b"trailing");
+
+        let decoded = decode(&data).unwrap();
+        assert_eq!(decoded.outputs, vec![(0x1000, 1)]);
+        assert_eq!(
+            decoded.meta[0].as_apk().unwrap().path,
+            Path::new("/foo.apk")
+        );
+        assert_eq!(
+            decoded.meta[1].as_unknown().unwrap().reason,
+            Reason::Unsupported
+        );
+        assert_eq!(
+            decoded.meta[2].as_unknown().unwrap().reason,
+            Reason::Unsupported
+        );
+    }
+
+    /// Check that we fail decoding of invalid data gracefully.
+    #[test]
+    fn wire_invalid() {
+        let data = encode(&output());
+
+        let err = decode(b"").unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidData);
+
+        let mut invalid = data.clone();
+        invalid[0] = b'X';
+        let err = decode(&invalid).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidData);
+
+        let mut invalid = data.clone();
+        invalid[4] = 2;
+        let err = decode(&invalid).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::Unsupported);
+
+        // Truncated data should never decode successfully.
+        for len in 0..data.len() {
+            let err = decode(&data[..len]).unwrap_err();
+            assert_eq!(err.kind(), ErrorKind::InvalidData, "{len}");
+        }
+
+        // A meta index out of bounds is invalid.
+        let mut invalid = encode(&UserOutput {
+            outputs: vec![(0x1000, 0)],
+            meta: vec![UserMeta::Unknown(Unknown::new(Reason::Unmapped))],
+        });
+        *invalid.last_mut().unwrap() = 1;
+        let err = decode(&invalid).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidData);
+    }
+}
@@ -0,0 +1,340 @@
+//! Loading of symbol and line information from PDB files.
+
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::ffi::OsString;
+use std::fs::File;
+use std::path::Path;
+use std::path::PathBuf;
+
+use pdb::FallibleIterator as _;
+use pdb::FileIndex;
+use pdb::LineProgram;
+use pdb::PdbInternalSectionOffset;
+use pdb::StringTable;
+use pdb::SymbolData;
+use pdb::PDB;
+
+use crate::Addr;
+use crate::Error;
+use crate::ErrorExt as _;
+use crate::Result;
+
+use super::parser::PeFile;
+
+
+/// Line numbers used by the Microsoft toolchain to mark code that
+/// should not be attributed to any source line.
+const HIDDEN_LINES: [u32; 2] = [0xf00f00, 0xfeefee];
+
+
+/// A function, as described by a PDB file.
+#[derive(Debug)]
+pub(crate) struct Function {
+    /// The name of the function.
+    pub name: String,
+    /// The relative virtual address of the function's first byte.
+    pub addr: Addr,
+    /// The size of the function, if known.
+    ///
+    /// Functions that we only know about by virtue of public symbols
+    /// lack size information.
+    pub size: Option<usize>,
+}
+
+
+/// A source file referenced by line information.
+#[derive(Debug)]
+pub(crate) struct SrcFile {
+    /// The directory containing the file, if known.
+    pub dir: Option<PathBuf>,
+    /// The name of the file.
+    pub file: OsString,
+}
+
+impl SrcFile {
+    fn new(path: &str) -> Self {
+        // Paths recorded in PDB files are usually Windows ones and we
+        // may not be running on Windows.
+        match path.rfind(['/', '\\']) {
+            Some(idx) => Self {
+                dir: Some(PathBuf::from(&path[..idx])),
+                file: OsString::from(&path[idx + 1..]),
+            },
+            None => Self {
+                dir: None,
+                file: OsString::from(path),
+            },
+        }
+    }
+}
+
+
+/// A single row of the line table.
+#[derive(Debug)]
+pub(crate) struct LineRow {
+    /// The relative virtual address of the first instruction covered
+    /// by the row.
+    pub addr: Addr,
+    /// The index of the source file in [`DebugInfo::files`].
+    pub file: usize,
+    /// The line number.
+    pub line: u32,
+    /// The column number, if known.
+    pub column: Option<u16>,
+}
+
+
+/// Symbol and line information loaded from a PDB file.
+#[derive(Debug, Default)]
+pub(crate) struct DebugInfo {
+    /// All known functions, sorted by address.
+    pub functions: Vec<Function>,
+    /// All line table rows, sorted by address.
+    pub lines: Vec<LineRow>,
+    /// The source files referenced by `lines`.
+    pub files: Vec<SrcFile>,
+}
+
+impl DebugInfo {
+    /// Load debug information from the PDB file at `path`, which is
+    /// expected to belong to the provided PE file.
+    pub(crate) fn open(path: &Path, pe: &PeFile) -> Result<Self> {
+        let file = File::open(path)
+            .with_context(|| format!("failed to open PDB file `{}`", path.display()))?;
+        let mut pdb = PDB::open(file)
+            .with_context(|| format!("failed to open PDB file `{}`", path.display()))?;
+
+        if let Some(pdb_ref) = pe.pdb() {
+            let info = pdb.pdb_information()?;
+            if info.guid.to_bytes_le() != pdb_ref.guid {
+                return Err(Error::with_invalid_input(format!(
+                    "PDB file `{}` does not match PE file (GUID {} differs)",
+                    path.display(),
+                    info.guid
+                )))
+            }
+        }
+
+        Self::load(&mut pdb)
+            .with_context(|| format!("failed to load debug information from `{}`", path.display()))
+    }
+
+    fn load(pdb: &mut PDB<'_, File>) -> Result<Self> {
+        let address_map = pdb.address_map()?;
+        // Not all PDB files have a string table, but without it we
+        // cannot make sense of line information.
+        let strings = pdb.string_table().ok();
+        let to_rva = |offset: PdbInternalSectionOffset| {
+            offset.to_rva(&address_map).map(|rva| Addr::from(rva.0))
+        };
+
+        let mut slf = Self::default();
+        let mut file_idxs = HashMap::<String, usize>::new();
+
+        let dbi = pdb.debug_information()?;
+        let mut modules = dbi.modules()?;
+        while let Some(module) = modules.next()? {
+            let info = match pdb.module_info(&module)? {
+                Some(info) => info,
+                None => continue,
+            };
+            let program = info.line_program()?;
+            let mut module_files = HashMap::<FileIndex, usize>::new();
+
+            let mut symbols = info.symbols()?;
+            while let Some(symbol) = symbols.next()? {
+                let proc = match symbol.parse() {
+                    Ok(SymbolData::Procedure(proc)) => proc,
+                    _ => continue,
+                };
+                let addr = match to_rva(proc.offset) {
+                    Some(addr) => addr,
+                    None => continue,
+                };
+
+                let func = Function {
+                    name: proc.name.to_string().into_owned(),
+                    addr,
+                    size: Some(proc.len as usize),
+                };
+                let () = slf.functions.push(func);
+
+                if let Some(strings) = &strings {
+                    let mut lines = program.lines_for_symbol(proc.offset);
+                    while let Some(line) = lines.next()? {
+                        if HIDDEN_LINES.contains(&line.line_start) {
+                            continue
+                        }
+                        let addr = match to_rva(line.offset) {
+                            Some(addr) => addr,
+                            None => continue,
+                        };
+                        let file = match module_files.get(&line.file_index) {
+                            Some(file) => *file,
+                            None => {
+                                let file = slf.file_idx(
+                                    &mut file_idxs,
+                                    &program,
+                                    strings,
+                                    line.file_index,
+                                )?;
+                                let _prev = module_files.insert(line.file_index, file);
+                                file
+                            }
+                        };
+
+                        let row = LineRow {
+                            addr,
+                            file,
+                            line: line.line_start,
+                            column: line.column_start.and_then(|col| u16::try_from(col).ok()),
+                        };
+                        let () = slf.lines.push(row);
+                    }
+                }
+            }
+        }
+
+        // Public symbols cover functions for which we may not have
+        // private symbols, e.g., because they are part of a library
+        // that was built without debug information.
+        let known = slf
+            .functions
+            .iter()
+            .map(|func| func.addr)
+            .collect::<HashSet<_>>();
+        let globals = pdb.global_symbols()?;
+        let mut symbols = globals.iter();
+        while let Some(symbol) = symbols.next()? {
+            let public = match symbol.parse() {
+                Ok(SymbolData::Public(public)) if public.function => public,
+                _ => continue,
+            };
+            match to_rva(public.offset) {
+                Some(addr) if !known.contains(&addr) => {
+                    let func = Function {
+                        name: public.name.to_string().into_owned(),
+                        addr,
+                        size: None,
+                    };
+                    let () = slf.functions.push(func);
+                }
+                _ => (),
+            }
+        }
+
+        let () = slf.functions.sort_by_key(|func| func.addr);
+        let () = slf.lines.sort_by_key(|row| row.addr);
+        Ok(slf)
+    }
+
+    /// Retrieve the index of the file referenced by `file_index` in
+    /// `self.files`, adding it if not yet present.
+    fn file_idx(
+        &mut self,
+        file_idxs: &mut HashMap<String, usize>,
+        program: &LineProgram<'_>,
+        strings: &StringTable<'_>,
+        file_index: FileIndex,
+    ) -> Result<usize> {
+        let info = program.get_file_info(file_index)?;
+        let name = info.name.to_string_lossy(strings)?;
+        if let Some(idx) = file_idxs.get(name.as_ref()) {
+            return Ok(*idx)
+        }
+
+        let idx = self.files.len();
+        let () = self.files.push(SrcFile::new(&name));
+        let _prev = file_idxs.insert(name.into_owned(), idx);
+        Ok(idx)
+    }
+
+    /// Find the function containing `addr`.
+    pub(crate) fn find_function(&self, addr: Addr) -> Option<&Function> {
+        let idx = self.functions.partition_point(|func| func.addr <= addr);
+        let func = self.functions.get(idx.checked_sub(1)?)?;
+        match func.size {
+            Some(size) => (addr < func.addr + size as Addr).then_some(func),
+            // Without size information the best we can do is to
+            // attribute the address to the closest preceding function.
+            None => Some(func),
+        }
+    }
+
+    /// Find the line table row covering `addr`, which is part of
+    /// `func`.
+    pub(crate) fn find_line(&self, addr: Addr, func: &Function) -> Option<&LineRow> {
+        let idx = self.lines.partition_point(|row| row.addr <= addr);
+        let row = self.lines.get(idx.checked_sub(1)?)?;
+        (row.addr >= func.addr).then_some(row)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+
+    /// Check that we split source file paths correctly.
+    #[test]
+    fn src_file_splitting() {
+        let file = SrcFile::new(r"C:\src\project\main.c");
+        assert_eq!(file.dir, Some(PathBuf::from(r"C:\src\project")));
+        assert_eq!(file.file, OsString::from("main.c"));
+
+        let file = SrcFile::new("/blazesym/data/test-pe.c");
+        assert_eq!(file.dir, Some(PathBuf::from("/blazesym/data")));
+        assert_eq!(file.file, OsString::from("test-pe.c"));
+
+        let file = SrcFile::new("main.c");
+        assert_eq!(file.dir, None);
+        assert_eq!(file.file, OsString::from("main.c"));
+    }
+
+    /// Check that we can find functions and line information by
+    /// address.
+    #[test]
+    fn function_lookup() {
+        let info = DebugInfo {
+            functions: vec![
+                Function {
+                    name: "foo".to_string(),
+                    addr: 0x1000,
+                    size: Some(0x10),
+                },
+                Function {
+                    name: "bar".to_string(),
+                    addr: 0x1020,
+                    size: None,
+                },
+            ],
+            lines: vec![
+                LineRow {
+                    addr: 0x1000,
+                    file: 0,
+                    line: 1,
+                    column: None,
+                },
+                LineRow {
+                    addr: 0x1008,
+                    file: 0,
+                    line: 2,
+                    column: Some(3),
+                },
+            ],
+            files: vec![SrcFile::new("foo.c")],
+        };
+
+        assert!(info.find_function(0xfff).is_none());
+        let func = info.find_function(0x100a).unwrap();
+        assert_eq!(func.name, "foo");
+        assert_eq!(info.find_line(0x100a, func).unwrap().line, 2);
+        assert!(info.find_function(0x1010).is_none());
+
+        let func = info.find_function(0x1030).unwrap();
+        assert_eq!(func.name, "bar");
+        assert!(info.find_line(0x1030, func).is_none());
+    }
+}
@@ -0,0 +1,5 @@
+mod debug_info;
+mod parser;
+mod resolver;
+
+pub use resolver::PeResolver;
@@ -0,0 +1,403 @@
+//! Parsing logic for the headers of PE/COFF files, as used on Windows.
+//!
+//! Only the bits necessary for symbolization are covered: the section
+//! table (used for translating between file offsets and relative
+//! virtual addresses) and the CodeView debug directory entry
+//! referencing the PDB file containing debug information.
+
+use std::fs::File;
+use std::path::Path;
+use std::path::PathBuf;
+
+use crate::mmap::Mmap;
+use crate::util::bytes_to_path;
+use crate::util::ReadRaw as _;
+use crate::Addr;
+use crate::Error;
+use crate::ErrorExt as _;
+use crate::IntoError as _;
+use crate::Result;
+
+
+/// The signature at the start of every PE file ("MZ").
+const DOS_MAGIC: [u8; 2] = *b"MZ";
+/// The offset of the `e_lfanew` field in the DOS header.
+const DOS_LFANEW_OFFSET: usize = 0x3c;
+/// The signature at the start of the PE header ("PE\0\0").
+const PE_MAGIC: [u8; 4] = *b"PE\0\0";
+/// The optional header magic of a PE32 file.
+const OPT_HDR_MAGIC_PE32: u16 = 0x10b;
+/// The optional header magic of a PE32+ file.
+const OPT_HDR_MAGIC_PE32_PLUS: u16 = 0x20b;
+/// The offset of the data directory count in a PE32 optional header.
+const OPT_HDR_DIR_COUNT_OFFSET_PE32: usize = 92;
+/// The offset of the data directory count in a PE32+ optional header.
+const OPT_HDR_DIR_COUNT_OFFSET_PE32_PLUS: usize = 108;
+/// The index of the debug directory in the optional header's data
+/// directory table.
+const DIRECTORY_ENTRY_DEBUG: u32 = 6;
+/// The size of a single section header, in bytes.
+const SECTION_HDR_SIZE: usize = 40;
+/// The size of a single debug directory entry, in bytes.
+const DEBUG_DIR_SIZE: usize = 28;
+/// The debug directory entry type of CodeView information.
+const DEBUG_TYPE_CODEVIEW: u32 = 2;
+/// The signature of a PDB 7.0 CodeView record ("RSDS").
+const CV_SIGNATURE_RSDS: [u8; 4] = *b"RSDS";
+
+
+/// A trait for reading little endian integers, as used by PE files,
+/// irrespective of the host's byte order.
+trait ReadLe<'data> {
+    fn read_le_u16(&mut self) -> Option<u16>;
+    fn read_le_u32(&mut self) -> Option<u32>;
+}
+
+impl<'data> ReadLe<'data> for &'data [u8] {
+    #[inline]
+    fn read_le_u16(&mut self) -> Option<u16> {
+        self.read_array().map(u16::from_le_bytes)
+    }
+
+    #[inline]
+    fn read_le_u32(&mut self) -> Option<u32> {
+        self.read_array().map(u32::from_le_bytes)
+    }
+}
+
+
+/// A section of a PE file.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct Section {
+    /// The section's relative virtual address.
+    pub rva: u32,
+    /// The section's size in memory.
+    pub virt_size: u32,
+    /// The offset of the section's data in the file.
+    pub file_off: u32,
+    /// The size of the section's data in the file.
+    pub file_size: u32,
+}
+
+
+/// The PDB reference stored in a PE file's CodeView debug directory
+/// entry.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct PdbRef {
+    /// The GUID of the PDB file, in its on-disk byte order.
+    pub guid: [u8; 16],
+    /// The age of the PDB file.
+    pub age: u32,
+    /// The path of the PDB file, as recorded by the linker.
+    pub path: PathBuf,
+}
+
+
+/// The parsed headers of a PE file.
+#[derive(Clone, Debug)]
+pub(crate) struct PeFile {
+    /// The image's sections.
+    sections: Box<[Section]>,
+    /// The PDB file referenced by the image, if any.
+    pdb: Option<PdbRef>,
+}
+
+impl PeFile {
+    /// Parse the headers of the PE file at `path`.
+    pub(crate) fn open(path: &Path) -> Result<Self> {
+        let file = File::open(path)
+            .with_context(|| format!("failed to open PE file `{}`", path.display()))?;
+        let mmap = Mmap::map(&file)
+            .with_context(|| format!("failed to memory map PE file `{}`", path.display()))?;
+        Self::parse(&mmap).with_context(|| format!("failed to parse PE file `{}`", path.display()))
+    }
+
+    /// Parse PE file headers from the provided data.
+    pub(crate) fn parse(data: &[u8]) -> Result<Self> {
+        let mut dos = data;
+        if dos.read_array::<2>() != Some(DOS_MAGIC) {
+            return Err(Error::with_invalid_data(
+                "file does not start with DOS header",
+            ))
+        }
+
+        let pe_off = data
+            .get(DOS_LFANEW_OFFSET..)
+            .and_then(|mut bytes| bytes.read_le_u32())
+            .ok_or_invalid_data(|| "failed to read PE header offset")?;
+        let mut hdr = data
+            .get(pe_off as usize..)
+            .ok_or_invalid_data(|| format!("PE header offset ({pe_off:#x}) is invalid"))?;
+        if hdr.read_array::<4>() != Some(PE_MAGIC) {
+            return Err(Error::with_invalid_data("PE header signature not found"))
+        }
+
+        // The COFF file header.
+        let _machine = hdr.read_le_u16();
+        let section_count = hdr.read_le_u16();
+        let _timestamp = hdr.read_le_u32();
+        let _symtab_off = hdr.read_le_u32();
+        let _symbol_count = hdr.read_le_u32();
+        let opt_hdr_size = hdr.read_le_u16();
+        let _characteristics = hdr.read_le_u16();
+        let (section_count, opt_hdr_size) = section_count
+            .zip(opt_hdr_size)
+            .ok_or_invalid_data(|| "failed to read COFF file header")?;
+
+        let opt_hdr = hdr
+            .read_slice(opt_hdr_size.into())
+            .ok_or_invalid_data(|| "failed to read PE optional header")?;
+        let debug_dir = Self::parse_opt_hdr(opt_hdr)?;
+
+        let sections = (0..section_count)
+            .map(|_| {
+                let mut shdr = hdr
+                    .read_slice(SECTION_HDR_SIZE)
+                    .ok_or_invalid_data(|| "failed to read PE section header")?;
+                let _name = shdr.read_array::<8>();
+                let section = Section {
+                    virt_size: shdr.read_le_u32().unwrap_or_default(),
+                    rva: shdr.read_le_u32().unwrap_or_default(),
+                    file_size: shdr.read_le_u32().unwrap_or_default(),
+                    file_off: shdr.read_le_u32().unwrap_or_default(),
+                };
+                Ok(section)
+            })
+            .collect::<Result<Box<[_]>>>()?;
+
+        let mut slf = Self {
+            sections,
+            pdb: None,
+        };
+
+        if let Some((rva, size)) = debug_dir {
+            slf.pdb = slf.parse_debug_dir(data, rva, size)?;
+        }
+        Ok(slf)
+    }
+
+    /// Parse the optional header, returning the location of the debug
+    /// directory, if present.
+    fn parse_opt_hdr(opt_hdr: &[u8]) -> Result<Option<(u32, u32)>> {
+        let mut bytes = opt_hdr;
+        let magic = bytes
+            .read_le_u16()
+            .ok_or_invalid_data(|| "failed to read PE optional header magic")?;
+        let dir_count_off = match magic {
+            OPT_HDR_MAGIC_PE32 => OPT_HDR_DIR_COUNT_OFFSET_PE32,
+            OPT_HDR_MAGIC_PE32_PLUS => OPT_HDR_DIR_COUNT_OFFSET_PE32_PLUS,
+            _ => {
+                return Err(Error::with_unsupported(format!(
+                    "PE optional header magic ({magic:#x}) is unsupported"
+                )))
+            }
+        };
+
+        let mut dirs = opt_hdr
+            .get(dir_count_off..)
+            .ok_or_invalid_data(|| "failed to read PE data directory count")?;
+        let dir_count = dirs
+            .read_le_u32()
+            .ok_or_invalid_data(|| "failed to read PE data directory count")?;
+        if dir_count <= DIRECTORY_ENTRY_DEBUG {
+            return Ok(None)
+        }
+
+        let () = dirs
+            .advance(DIRECTORY_ENTRY_DEBUG as usize * 8)
+            .ok_or_invalid_data(|| "failed to read PE debug data directory")?;
+        let rva = dirs.read_le_u32();
+        let size = dirs.read_le_u32();
+        let debug_dir = rva
+            .zip(size)
+            .ok_or_invalid_data(|| "failed to read PE debug data directory")?;
+        let debug_dir = if debug_dir.1 == 0 {
+            None
+        } else {
+            Some(debug_dir)
+        };
+        Ok(debug_dir)
+    }
+
+    /// Parse the debug directory, looking for a CodeView entry
+    /// referencing a PDB file.
+    fn parse_debug_dir(&self, data: &[u8], rva: u32, size: u32) -> Result<Option<PdbRef>> {
+        let off = self
+            .rva_to_file_offset(rva.into())
+            .ok_or_invalid_data(|| format!("PE debug directory RVA ({rva:#x}) is invalid"))?;
+        let mut dir = data
+            .get(off as usize..)
+            .and_then(|mut bytes| bytes.read_slice(size as usize))
+            .ok_or_invalid_data(|| "failed to read PE debug directory")?;
+
+        while let Some(mut entry) = dir.read_slice(DEBUG_DIR_SIZE) {
+            let _characteristics = entry.read_le_u32();
+            let _timestamp = entry.read_le_u32();
+            let _major = entry.read_le_u16();
+            let _minor = entry.read_le_u16();
+            let type_ = entry.read_le_u32();
+            let data_size = entry.read_le_u32();
+            let _data_rva = entry.read_le_u32();
+            let data_off = entry.read_le_u32();
+            if type_ != Some(DEBUG_TYPE_CODEVIEW) {
+                continue
+            }
+
+            let (data_size, data_off) = data_size
+                .zip(data_off)
+                .ok_or_invalid_data(|| "failed to read PE debug directory entry")?;
+            let mut cv = data
+                .get(data_off as usize..)
+                .and_then(|mut bytes| bytes.read_slice(data_size as usize))
+                .ok_or_invalid_data(|| "failed to read CodeView record")?;
+            if cv.read_array::<4>() != Some(CV_SIGNATURE_RSDS) {
+                // Older CodeView formats (e.g., "NB10") reference PDB
+                // versions that we do not support.
+                continue
+            }
+
+            let guid = cv.read_array::<16>();
+            let age = cv.read_le_u32();
+            let path = cv.read_cstr();
+            let ((guid, age), path) = guid
+                .zip(age)
+                .zip(path)
+                .ok_or_invalid_data(|| "failed to read CodeView PDB reference")?;
+            let pdb = PdbRef {
+                guid,
+                age,
+                path: bytes_to_path(path.to_bytes())?.to_path_buf(),
+            };
+            return Ok(Some(pdb))
+        }
+        Ok(None)
+    }
+
+    /// Convert a relative virtual address into a file offset.
+    pub(crate) fn rva_to_file_offset(&self, rva: Addr) -> Option<u64> {
+        self.sections.iter().find_map(|section| {
+            let start = Addr::from(section.rva);
+            let size = section.virt_size.min(section.file_size);
+            let offset = rva.checked_sub(start)?;
+            (offset < Addr::from(size)).then(|| u64::from(section.file_off) + offset)
+        })
+    }
+
+    /// Convert a file offset into a relative virtual address.
+    pub(crate) fn file_offset_to_rva(&self, file_offset: u64) -> Option<Addr> {
+        self.sections.iter().find_map(|section| {
+            let start = u64::from(section.file_off);
+            let size = section.virt_size.min(section.file_size);
+            let offset = file_offset.checked_sub(start)?;
+            (offset < u64::from(size)).then(|| Addr::from(section.rva) + offset)
+        })
+    }
+
+    /// Locate the PDB file referenced by this PE file, which is
+    /// located at `path`.
+    ///
+    /// The path recorded by the linker is checked first. Because it
+    /// frequently refers to a location on the build host, we fall back
+    /// to looking for a file of the same name next to the PE file.
+    pub(crate) fn find_pdb(&self, path: &Path) -> Result<PathBuf> {
+        let pdb = self.pdb.as_ref().ok_or_invalid_input(|| {
+            format!("PE file `{}` does not reference a PDB file", path.display())
+        })?;
+
+        if pdb.path.is_file() {
+            return Ok(pdb.path.clone())
+        }
+
+        // The recorded path may be a Windows one, using backslashes as
+        // separators.
+        let name = pdb
+            .path
+            .as_os_str()
+            .to_string_lossy()
+            .rsplit(['/', '\\'])
+            .next()
+            .map(str::to_string)
+            .unwrap_or_default();
+        if !name.is_empty() {
+            let candidate = path.with_file_name(name);
+            if candidate.is_file() {
+                return Ok(candidate)
+            }
+        }
+
+        Err(Error::with_not_found(format!(
+            "failed to find PDB file `{}` referenced by `{}`",
+            pdb.path.display(),
+            path.display()
+        )))
+    }
+
+    /// Retrieve the PDB reference of the image, if any.
+    #[inline]
+    pub(crate) fn pdb(&self) -> Option<&PdbRef> {
+        self.pdb.as_ref()
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use test_log::test;
+
+    use crate::ErrorKind;
+
+
+    /// Check that we properly translate between file offsets and
+    /// relative virtual addresses.
+    #[test]
+    fn offset_translation() {
+        let pe = PeFile {
+            sections: Box::new([
+                Section {
+                    rva: 0x1000,
+                    virt_size: 0x80,
+                    file_off: 0x400,
+                    file_size: 0x200,
+                },
+                Section {
+                    rva: 0x2000,
+                    virt_size: 0x300,
+                    file_off: 0x600,
+                    file_size: 0x200,
+                },
+            ]),
+            pdb: None,
+        };
+
+        assert_eq!(pe.rva_to_file_offset(0xfff), None);
+        assert_eq!(pe.rva_to_file_offset(0x1000), Some(0x400));
+        assert_eq!(pe.rva_to_file_offset(0x107f), Some(0x47f));
+        assert_eq!(pe.rva_to_file_offset(0x1080), None);
+        assert_eq!(pe.rva_to_file_offset(0x21ff), Some(0x7ff));
+        // Past the section's file backed data.
+        assert_eq!(pe.rva_to_file_offset(0x2200), None);
+
+        assert_eq!(pe.file_offset_to_rva(0x3ff), None);
+        assert_eq!(pe.file_offset_to_rva(0x410), Some(0x1010));
+        assert_eq!(pe.file_offset_to_rva(0x480), None);
+        assert_eq!(pe.file_offset_to_rva(0x600), Some(0x2000));
+    }
+
+    /// Make sure that we reject data that is not a PE file.
+    #[test]
+    fn invalid_data() {
+        let err = PeFile::parse(b"").unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidData);
+
+        let err = PeFile::parse(b"\x7fELF\x02\x01\x01").unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidData);
+
+        let mut data = vec![0; 0x80];
+        data[..2].copy_from_slice(&DOS_MAGIC);
+        data[DOS_LFANEW_OFFSET..DOS_LFANEW_OFFSET + 4].copy_from_slice(&0x1000u32.to_le_bytes());
+        let err = PeFile::parse(&data).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidData);
+    }
+}
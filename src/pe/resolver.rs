@@ -0,0 +1,297 @@
+use std::borrow::Cow;
+use std::fmt::Debug;
+use std::fmt::Formatter;
+use std::fmt::Result as FmtResult;
+use std::ops::ControlFlow;
+use std::path::Path;
+use std::path::PathBuf;
+
+use crate::inspect::FindAddrOpts;
+use crate::inspect::ForEachFn;
+use crate::inspect::Inspect;
+use crate::inspect::SymInfo;
+use crate::symbolize::CodeInfo;
+use crate::symbolize::FindSymOpts;
+use crate::symbolize::Reason;
+use crate::symbolize::ResolvedSym;
+use crate::symbolize::SrcLang;
+use crate::symbolize::Symbolize;
+use crate::symbolize::TranslateFileOffset;
+use crate::Addr;
+use crate::Error;
+use crate::Result;
+use crate::SymType;
+
+use super::debug_info::DebugInfo;
+use super::debug_info::Function;
+use super::parser::PeFile;
+
+
+/// A symbol resolver for a PE file, using debug information from its
+/// accompanying PDB file.
+///
+/// All addresses handled by this type are relative virtual addresses
+/// (RVAs), i.e., offsets from the image's load address.
+pub struct PeResolver {
+    /// The parsed PE file headers.
+    pe: PeFile,
+    /// Symbol and line information from the PDB file.
+    debug_info: DebugInfo,
+    /// The path of the PE file in use.
+    path: PathBuf,
+    /// The path of the PDB file in use.
+    pdb_path: PathBuf,
+}
+
+impl PeResolver {
+    /// Create a `PeResolver` that loads data from the provided PE file.
+    ///
+    /// The PDB file is located based on the reference stored in the PE
+    /// file: first at the recorded path, then next to the PE file.
+    pub fn open<P>(path: P) -> Result<Self>
+    where
+        P: AsRef<Path>,
+    {
+        Self::open_impl(path.as_ref(), None)
+    }
+
+    /// Create a `PeResolver` that loads data from the provided PE file,
+    /// using debug information from the given PDB file.
+    pub fn open_with_pdb<P, Q>(path: P, pdb: Q) -> Result<Self>
+    where
+        P: AsRef<Path>,
+        Q: AsRef<Path>,
+    {
+        Self::open_impl(path.as_ref(), Some(pdb.as_ref()))
+    }
+
+    pub(crate) fn open_impl(path: &Path, pdb: Option<&Path>) -> Result<Self> {
+        let pe = PeFile::open(path)?;
+        let pdb_path = match pdb {
+            Some(pdb) => pdb.to_path_buf(),
+            None => pe.find_pdb(path)?,
+        };
+        let debug_info = DebugInfo::open(&pdb_path, &pe)?;
+
+        let slf = Self {
+            pe,
+            debug_info,
+            path: path.to_path_buf(),
+            pdb_path,
+        };
+        Ok(slf)
+    }
+
+    fn sym_info<'slf>(&'slf self, func: &'slf Function, opts: &FindAddrOpts) -> SymInfo<'slf> {
+        SymInfo {
+            name: Cow::Borrowed(&func.name),
+            addr: func.addr,
+            size: func.size,
+            sym_type: SymType::Function,
+            file_offset: opts
+                .offset_in_file
+                .then(|| self.pe.rva_to_file_offset(func.addr))
+                .flatten(),
+            obj_file_name: None,
+            code_info: None,
+        }
+    }
+}
+
+impl Symbolize for PeResolver {
+    #[cfg_attr(feature = "tracing", crate::log::instrument(fields(addr = format_args!("{addr:#x}"))))]
+    fn find_sym(&self, addr: Addr, opts: &FindSymOpts) -> Result<Result<ResolvedSym<'_>, Reason>> {
+        let func = if let Some(func) = self.debug_info.find_function(addr) {
+            func
+        } else {
+            let reason = if self.debug_info.functions.is_empty() {
+                Reason::MissingSyms
+            } else {
+                Reason::UnknownAddr
+            };
+            return Ok(Err(reason))
+        };
+
+        let code_info = if opts.code_info() {
+            self.debug_info.find_line(addr, func).and_then(|row| {
+                let file = self.debug_info.files.get(row.file)?;
+                Some(CodeInfo {
+                    dir: file.dir.as_deref().map(Cow::Borrowed),
+                    file: Cow::Borrowed(&file.file),
+                    line: Some(row.line),
+                    column: row.column,
+                    _non_exhaustive: (),
+                })
+            })
+        } else {
+            None
+        };
+
+        let sym = ResolvedSym {
+            name: &func.name,
+            addr: func.addr,
+            size: func.size,
+            lang: SrcLang::Unknown,
+            code_info,
+            inlined: Box::new([]),
+            inlined_truncated: false,
+            section: None,
+            module: None,
+        };
+        Ok(Ok(sym))
+    }
+}
+
+impl TranslateFileOffset for PeResolver {
+    fn file_offset_to_virt_offset(&self, file_offset: u64) -> Result<Option<Addr>> {
+        Ok(self.pe.file_offset_to_rva(file_offset))
+    }
+}
+
+impl Inspect for PeResolver {
+    fn find_addr<'slf>(&'slf self, name: &str, opts: &FindAddrOpts) -> Result<Vec<SymInfo<'slf>>> {
+        if let SymType::Variable = opts.sym_type {
+            return Err(Error::with_unsupported(
+                "PE logic does not currently support variable lookup",
+            ))
+        }
+
+        let syms = self
+            .debug_info
+            .functions
+            .iter()
+            .filter(|func| func.name == name)
+            .map(|func| self.sym_info(func, opts))
+            .collect::<Vec<_>>();
+        Ok(syms)
+    }
+
+    fn for_each(&self, opts: &FindAddrOpts, f: &mut ForEachFn<'_>) -> Result<()> {
+        if let SymType::Variable = opts.sym_type {
+            return Err(Error::with_unsupported(
+                "PE logic does not currently support variable iteration",
+            ))
+        }
+
+        for func in &self.debug_info.functions {
+            let sym = self.sym_info(func, opts);
+            if let ControlFlow::Break(()) = f(&sym) {
+                return Ok(())
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Debug for PeResolver {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        write!(
+            f,
+            "PE {} ({})",
+            self.path.display(),
+            self.pdb_path.display()
+        )
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::ffi::OsStr;
+
+    use test_log::test;
+
+    use crate::ErrorKind;
+
+
+    fn test_pe() -> PathBuf {
+        Path::new(&env!("CARGO_MANIFEST_DIR"))
+            .join("data")
+            .join("test-pe.exe")
+    }
+
+
+    /// Exercise the `Debug` representation of various types.
+    #[test]
+    fn debug_repr() {
+        let resolver = PeResolver::open(test_pe()).unwrap();
+        let dbg = format!("{resolver:?}");
+        assert!(dbg.starts_with("PE"), "{dbg}");
+        assert!(dbg.ends_with("test-pe.pdb)"), "{dbg}");
+    }
+
+    /// Check that we can symbolize addresses in a PE file.
+    #[test]
+    fn symbolization() {
+        let resolver = PeResolver::open(test_pe()).unwrap();
+        let syms = resolver.find_addr("add", &FindAddrOpts::default()).unwrap();
+        assert_eq!(syms.len(), 1);
+        let add = syms[0].to_owned();
+
+        let opts = FindSymOpts::CodeInfo;
+        let sym = resolver.find_sym(add.addr + 1, &opts).unwrap().unwrap();
+        assert_eq!(sym.name, "add");
+        assert_eq!(sym.addr, add.addr);
+        assert_eq!(sym.size, add.size);
+        let code_info = sym.code_info.unwrap();
+        assert_eq!(code_info.file, OsStr::new("test-pe.c"));
+        assert_eq!(code_info.line, Some(2));
+
+        let syms = resolver
+            .find_addr("main", &FindAddrOpts::default())
+            .unwrap();
+        let main = &syms[0];
+        let sym = resolver.find_sym(main.addr, &opts).unwrap().unwrap();
+        assert_eq!(sym.name, "main");
+        assert_eq!(sym.code_info.unwrap().line, Some(5));
+        let sym = resolver.find_sym(main.addr + 4, &opts).unwrap().unwrap();
+        assert_eq!(sym.name, "main");
+        assert_eq!(sym.code_info.unwrap().line, Some(6));
+
+        let err = resolver.find_sym(0, &opts).unwrap().unwrap_err();
+        assert_eq!(err, Reason::UnknownAddr);
+
+        // Translating the file offset of a function should yield its
+        // address.
+        let opts = FindAddrOpts {
+            offset_in_file: true,
+            ..Default::default()
+        };
+        let syms = resolver.find_addr("main", &opts).unwrap();
+        let offset = syms[0].file_offset.unwrap();
+        assert_eq!(
+            resolver.file_offset_to_virt_offset(offset).unwrap(),
+            Some(main.addr)
+        );
+    }
+
+    /// Check that a PDB file not belonging to the PE file is rejected.
+    #[test]
+    fn mismatching_pdb() {
+        let pdb = Path::new(&env!("CARGO_MANIFEST_DIR"))
+            .join("data")
+            .join("test-pe-other.pdb");
+        let err = PeResolver::open_with_pdb(test_pe(), pdb).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidInput);
+    }
+
+    /// Check that [`PeResolver::find_addr`] and [`PeResolver::for_each`]
+    /// behave as expected for unsupported requests.
+    #[test]
+    fn unsupported_ops() {
+        let resolver = PeResolver::open(test_pe()).unwrap();
+        let opts = FindAddrOpts {
+            sym_type: SymType::Variable,
+            ..Default::default()
+        };
+        let err = resolver.find_addr("a_variable", &opts).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::Unsupported);
+
+        let err = resolver
+            .for_each(&opts, &mut |_| ControlFlow::Continue(()))
+            .unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::Unsupported);
+    }
+}
@@ -0,0 +1,988 @@
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::ffi::OsStr;
+use std::fs::File;
+use std::mem::size_of;
+use std::ops::Range;
+use std::path::Path;
+
+use crate::mmap::Mmap;
+use crate::normalize::buildid::BuildId;
+use crate::util::bytes_to_os_str;
+use crate::util::bytes_to_path;
+use crate::util::ReadRaw as _;
+use crate::Addr;
+use crate::Error;
+use crate::ErrorExt as _;
+use crate::IntoError as _;
+use crate::Result;
+
+
+/// The magic number at the start of a `perf.data` file (`PERFILE2`),
+/// as written in native byte order.
+const PERF_MAGIC: u64 = 0x32454c4946524550;
+/// The size of the file header.
+const HEADER_SIZE: usize = 13 * size_of::<u64>();
+/// The size of the header preceding each record.
+const RECORD_HEADER_SIZE: usize = size_of::<u32>() + 2 * size_of::<u16>();
+/// The size of a section descriptor, consisting of offset and size.
+const SECTION_SIZE: usize = 2 * size_of::<u64>();
+
+/// The feature bit indicating the presence of a build ID table.
+const HEADER_BUILD_ID: u32 = 2;
+
+const PERF_RECORD_MMAP: u32 = 1;
+const PERF_RECORD_COMM: u32 = 3;
+const PERF_RECORD_FORK: u32 = 7;
+const PERF_RECORD_SAMPLE: u32 = 9;
+const PERF_RECORD_MMAP2: u32 = 10;
+
+const PERF_RECORD_MISC_CPUMODE_MASK: u16 = 0x7;
+const PERF_RECORD_MISC_KERNEL: u16 = 1;
+const PERF_RECORD_MISC_USER: u16 = 2;
+const PERF_RECORD_MISC_COMM_EXEC: u16 = 1 << 13;
+const PERF_RECORD_MISC_MMAP_BUILD_ID: u16 = 1 << 14;
+const PERF_RECORD_MISC_BUILD_ID_SIZE: u16 = 1 << 15;
+
+const PERF_SAMPLE_IP: u64 = 1 << 0;
+const PERF_SAMPLE_TID: u64 = 1 << 1;
+const PERF_SAMPLE_TIME: u64 = 1 << 2;
+const PERF_SAMPLE_ADDR: u64 = 1 << 3;
+const PERF_SAMPLE_READ: u64 = 1 << 4;
+const PERF_SAMPLE_CALLCHAIN: u64 = 1 << 5;
+const PERF_SAMPLE_ID: u64 = 1 << 6;
+const PERF_SAMPLE_CPU: u64 = 1 << 7;
+const PERF_SAMPLE_PERIOD: u64 = 1 << 8;
+const PERF_SAMPLE_STREAM_ID: u64 = 1 << 9;
+const PERF_SAMPLE_IDENTIFIER: u64 = 1 << 16;
+
+const PERF_FORMAT_TOTAL_TIME_ENABLED: u64 = 1 << 0;
+const PERF_FORMAT_TOTAL_TIME_RUNNING: u64 = 1 << 1;
+const PERF_FORMAT_ID: u64 = 1 << 2;
+const PERF_FORMAT_GROUP: u64 = 1 << 3;
+const PERF_FORMAT_LOST: u64 = 1 << 4;
+
+/// The bit of `perf_event_attr` flags indicating that non-sample
+/// records carry sample ID information as well.
+const ATTR_FLAG_SAMPLE_ID_ALL: u64 = 1 << 18;
+
+const PERF_CONTEXT_KERNEL: u64 = -128i64 as u64;
+const PERF_CONTEXT_USER: u64 = -512i64 as u64;
+const PERF_CONTEXT_MAX: u64 = -4095i64 as u64;
+
+
+/// Read a section descriptor from `data`, returning the byte range it
+/// covers.
+fn read_section(data: &mut &[u8]) -> Option<Range<usize>> {
+    let offset = usize::try_from(data.read_u64()?).ok()?;
+    let size = usize::try_from(data.read_u64()?).ok()?;
+    Some(offset..offset.checked_add(size)?)
+}
+
+/// Retrieve the data covered by `range`, making sure it is valid.
+fn section_data<'data>(data: &'data [u8], range: &Range<usize>, name: &str) -> Result<&'data [u8]> {
+    data.get(range.clone())
+        .ok_or_invalid_data(|| format!("perf.data {name} section ({range:?}) is out of bounds"))
+}
+
+/// Read a file path from `data`.
+fn read_path<'data>(data: &mut &'data [u8]) -> Option<&'data Path> {
+    let path = data.read_cstr()?;
+    bytes_to_path(path.to_bytes()).ok()
+}
+
+
+/// The execution context of an address.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum Context {
+    Kernel,
+    User,
+    Other,
+}
+
+impl Context {
+    fn from_misc(misc: u16) -> Self {
+        match misc & PERF_RECORD_MISC_CPUMODE_MASK {
+            PERF_RECORD_MISC_KERNEL => Self::Kernel,
+            PERF_RECORD_MISC_USER => Self::User,
+            _ => Self::Other,
+        }
+    }
+}
+
+
+/// The format of samples, as described by the event attributes.
+#[derive(Clone, Copy, Debug)]
+struct SampleFormat {
+    /// The `PERF_SAMPLE_*` bits describing the contents of samples.
+    sample_type: u64,
+    /// The `PERF_FORMAT_*` bits describing the format of read values.
+    read_format: u64,
+    /// Whether non-sample records carry sample ID information.
+    sample_id_all: bool,
+}
+
+impl SampleFormat {
+    /// Retrieve the time stamp of a non-sample record from its sample
+    /// ID information, if present.
+    fn record_time(&self, record: &[u8]) -> Option<u64> {
+        if !self.sample_id_all || self.sample_type & PERF_SAMPLE_TIME == 0 {
+            return None
+        }
+
+        // All sample ID members are eight bytes in size and the time
+        // stamp is only preceded by the process and thread IDs.
+        let size = [
+            PERF_SAMPLE_TID,
+            PERF_SAMPLE_TIME,
+            PERF_SAMPLE_ID,
+            PERF_SAMPLE_STREAM_ID,
+            PERF_SAMPLE_CPU,
+            PERF_SAMPLE_IDENTIFIER,
+        ]
+        .into_iter()
+        .filter(|bit| self.sample_type & bit != 0)
+        .count()
+            * size_of::<u64>();
+        let offset = record.len().checked_sub(size)?
+            + if self.sample_type & PERF_SAMPLE_TID != 0 {
+                size_of::<u64>()
+            } else {
+                0
+            };
+        record.get(offset..)?.read_u64()
+    }
+
+    /// Skip over the read values contained in a sample.
+    fn skip_read_values(&self, data: &mut &[u8]) -> Option<()> {
+        let optional = [
+            PERF_FORMAT_TOTAL_TIME_ENABLED,
+            PERF_FORMAT_TOTAL_TIME_RUNNING,
+        ]
+        .into_iter()
+        .filter(|bit| self.read_format & bit != 0)
+        .count();
+        let per_value = 1 + [PERF_FORMAT_ID, PERF_FORMAT_LOST]
+            .into_iter()
+            .filter(|bit| self.read_format & bit != 0)
+            .count();
+
+        let cnt = if self.read_format & PERF_FORMAT_GROUP != 0 {
+            let nr = usize::try_from(data.read_u64()?).ok()?;
+            optional.checked_add(nr.checked_mul(per_value)?)?
+        } else {
+            optional + per_value
+        };
+        data.advance(cnt.checked_mul(size_of::<u64>())?)
+    }
+
+    /// Parse the body of a `PERF_RECORD_SAMPLE` record.
+    fn parse_sample(&self, mut data: &[u8], misc: u16) -> Option<SampleRecord> {
+        let sample_type = self.sample_type;
+
+        if sample_type & PERF_SAMPLE_IDENTIFIER != 0 {
+            let _id = data.read_u64()?;
+        }
+        // Presence of the instruction pointer and thread IDs is
+        // checked when opening the file.
+        let ip = data.read_u64()?;
+        let pid = data.read_u32()?;
+        let tid = data.read_u32()?;
+        let time = if sample_type & PERF_SAMPLE_TIME != 0 {
+            Some(data.read_u64()?)
+        } else {
+            None
+        };
+
+        for bit in [
+            PERF_SAMPLE_ADDR,
+            PERF_SAMPLE_ID,
+            PERF_SAMPLE_STREAM_ID,
+            PERF_SAMPLE_CPU,
+            PERF_SAMPLE_PERIOD,
+        ] {
+            if sample_type & bit != 0 {
+                let () = data.advance(size_of::<u64>())?;
+            }
+        }
+
+        if sample_type & PERF_SAMPLE_READ != 0 {
+            let () = self.skip_read_values(&mut data)?;
+        }
+
+        let mut sample = SampleRecord {
+            pid,
+            tid,
+            time,
+            kstack: Vec::new(),
+            ustack: Vec::new(),
+            _non_exhaustive: (),
+        };
+        let mut context = Context::from_misc(misc);
+
+        if sample_type & PERF_SAMPLE_CALLCHAIN != 0 {
+            let nr = usize::try_from(data.read_u64()?).ok()?;
+            let ips = data.read_slice(nr.checked_mul(size_of::<u64>())?)?;
+            for ip in ips.chunks_exact(size_of::<u64>()) {
+                // SANITY: `chunks_exact` guarantees the chunk size.
+                let ip = u64::from_ne_bytes(ip.try_into().unwrap());
+                if ip >= PERF_CONTEXT_MAX {
+                    context = match ip {
+                        PERF_CONTEXT_KERNEL => Context::Kernel,
+                        PERF_CONTEXT_USER => Context::User,
+                        _ => Context::Other,
+                    };
+                    continue
+                }
+                let () = sample.push(context, ip);
+            }
+        } else {
+            let () = sample.push(context, ip);
+        }
+        Some(sample)
+    }
+}
+
+
+/// A record describing a memory mapping, as reported by
+/// `PERF_RECORD_MMAP` and `PERF_RECORD_MMAP2`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct MmapRecord<'data> {
+    /// The ID of the process the mapping was created in.
+    ///
+    /// Mappings of the kernel and its modules have a value of
+    /// `u32::MAX`.
+    pub pid: u32,
+    /// The ID of the thread the mapping was created by.
+    pub tid: u32,
+    /// The virtual address at which the mapping starts.
+    pub addr: Addr,
+    /// The length of the mapping.
+    pub len: u64,
+    /// The offset of the mapping into the mapped file.
+    pub pgoff: u64,
+    /// The path of the mapped file, or a pseudo path such as `//anon`
+    /// or `[vdso]`.
+    pub path: &'data Path,
+    /// The build ID of the mapped file, if it was recorded as part of
+    /// the record.
+    pub build_id: Option<BuildId<'data>>,
+    /// The time stamp of the record, if available.
+    pub time: Option<u64>,
+    /// The struct is non-exhaustive and open to extension.
+    #[doc(hidden)]
+    pub _non_exhaustive: (),
+}
+
+
+/// A record describing the creation of a process or thread, as reported
+/// by `PERF_RECORD_FORK`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ForkRecord {
+    /// The ID of the newly created process.
+    pub pid: u32,
+    /// The ID of the parent process.
+    pub ppid: u32,
+    /// The ID of the newly created thread.
+    pub tid: u32,
+    /// The ID of the parent thread.
+    pub ptid: u32,
+    /// The time stamp of the record.
+    pub time: u64,
+    /// The struct is non-exhaustive and open to extension.
+    #[doc(hidden)]
+    pub _non_exhaustive: (),
+}
+
+
+/// A record describing a change of a thread's name, as reported by
+/// `PERF_RECORD_COMM`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CommRecord<'data> {
+    /// The ID of the process.
+    pub pid: u32,
+    /// The ID of the thread.
+    pub tid: u32,
+    /// The new name of the thread.
+    pub comm: &'data OsStr,
+    /// Whether the name change was caused by an `exec`.
+    pub exec: bool,
+    /// The time stamp of the record, if available.
+    pub time: Option<u64>,
+    /// The struct is non-exhaustive and open to extension.
+    #[doc(hidden)]
+    pub _non_exhaustive: (),
+}
+
+
+/// A sample, as reported by `PERF_RECORD_SAMPLE`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SampleRecord {
+    /// The ID of the process the sample was taken in.
+    pub pid: u32,
+    /// The ID of the thread the sample was taken in.
+    pub tid: u32,
+    /// The time stamp of the sample, if available.
+    pub time: Option<u64>,
+    /// The kernel stack, innermost frame first.
+    ///
+    /// If no call chain was captured, this stack contains only the
+    /// sampled instruction pointer, if it belongs to the kernel.
+    pub kstack: Vec<Addr>,
+    /// The user space stack, innermost frame first.
+    ///
+    /// If no call chain was captured, this stack contains only the
+    /// sampled instruction pointer, if it belongs to user space.
+    pub ustack: Vec<Addr>,
+    /// The struct is non-exhaustive and open to extension.
+    #[doc(hidden)]
+    pub _non_exhaustive: (),
+}
+
+impl SampleRecord {
+    fn push(&mut self, context: Context, addr: Addr) {
+        match context {
+            Context::Kernel => self.kstack.push(addr),
+            Context::User => self.ustack.push(addr),
+            // Hypervisor and guest addresses are not supported.
+            Context::Other => (),
+        }
+    }
+}
+
+
+/// A record contained in a `perf.data` file.
+#[derive(Clone, Debug, PartialEq)]
+#[non_exhaustive]
+pub enum Record<'data> {
+    /// A memory mapping got created.
+    Mmap(MmapRecord<'data>),
+    /// A process or thread got created.
+    Fork(ForkRecord),
+    /// A thread changed its name.
+    Comm(CommRecord<'data>),
+    /// A sample got taken.
+    Sample(SampleRecord),
+}
+
+impl Record<'_> {
+    /// Retrieve the time stamp of the record, if available.
+    pub fn time(&self) -> Option<u64> {
+        match self {
+            Self::Mmap(mmap) => mmap.time,
+            Self::Fork(fork) => Some(fork.time),
+            Self::Comm(comm) => comm.time,
+            Self::Sample(sample) => sample.time,
+        }
+    }
+}
+
+
+/// An iterator over the records of a `perf.data` file.
+///
+/// Records of types not represented by [`Record`] are skipped.
+#[derive(Debug)]
+pub struct Records<'data> {
+    /// The remaining data of the data section.
+    data: &'data [u8],
+    /// The format of samples.
+    format: SampleFormat,
+}
+
+impl<'data> Records<'data> {
+    /// Parse the next record of interest, if any.
+    fn parse_next(&mut self) -> Result<Option<Record<'data>>> {
+        let (type_, misc, size) = (|| {
+            let type_ = self.data.read_u32()?;
+            let misc = self.data.read_u16()?;
+            let size = self.data.read_u16()?;
+            Some((type_, misc, size))
+        })()
+        .ok_or_invalid_data(|| "failed to read perf record header")?;
+
+        let data = usize::from(size)
+            .checked_sub(RECORD_HEADER_SIZE)
+            .and_then(|size| self.data.read_slice(size))
+            .ok_or_invalid_data(|| format!("perf record size ({size}) is invalid"))?;
+        let mut record = data;
+
+        let record = match type_ {
+            PERF_RECORD_MMAP | PERF_RECORD_MMAP2 => {
+                let mmap = (|| {
+                    let pid = record.read_u32()?;
+                    let tid = record.read_u32()?;
+                    let addr = record.read_u64()?;
+                    let len = record.read_u64()?;
+                    let pgoff = record.read_u64()?;
+                    let mut build_id = None;
+                    if type_ == PERF_RECORD_MMAP2 {
+                        let id = record.read_slice(24)?;
+                        let _prot = record.read_u32()?;
+                        let _flags = record.read_u32()?;
+                        if misc & PERF_RECORD_MISC_MMAP_BUILD_ID != 0 {
+                            let len = usize::from(id[0]);
+                            build_id = Some(Cow::Borrowed(id.get(4..4 + len)?));
+                        }
+                    }
+                    let path = read_path(&mut record)?;
+                    Some(MmapRecord {
+                        pid,
+                        tid,
+                        addr,
+                        len,
+                        pgoff,
+                        path,
+                        build_id,
+                        time: self.format.record_time(data),
+                        _non_exhaustive: (),
+                    })
+                })()
+                .ok_or_invalid_data(|| "failed to read perf mmap record")?;
+                Record::Mmap(mmap)
+            }
+            PERF_RECORD_FORK => {
+                let fork = (|| {
+                    let pid = record.read_u32()?;
+                    let ppid = record.read_u32()?;
+                    let tid = record.read_u32()?;
+                    let ptid = record.read_u32()?;
+                    let time = record.read_u64()?;
+                    Some(ForkRecord {
+                        pid,
+                        ppid,
+                        tid,
+                        ptid,
+                        time,
+                        _non_exhaustive: (),
+                    })
+                })()
+                .ok_or_invalid_data(|| "failed to read perf fork record")?;
+                Record::Fork(fork)
+            }
+            PERF_RECORD_COMM => {
+                let comm = (|| {
+                    let pid = record.read_u32()?;
+                    let tid = record.read_u32()?;
+                    let comm = bytes_to_os_str(record.read_cstr()?.to_bytes()).ok()?;
+                    Some(CommRecord {
+                        pid,
+                        tid,
+                        comm,
+                        exec: misc & PERF_RECORD_MISC_COMM_EXEC != 0,
+                        time: self.format.record_time(data),
+                        _non_exhaustive: (),
+                    })
+                })()
+                .ok_or_invalid_data(|| "failed to read perf comm record")?;
+                Record::Comm(comm)
+            }
+            PERF_RECORD_SAMPLE => {
+                let sample = self
+                    .format
+                    .parse_sample(record, misc)
+                    .ok_or_invalid_data(|| "failed to read perf sample record")?;
+                Record::Sample(sample)
+            }
+            _ => return Ok(None),
+        };
+        Ok(Some(record))
+    }
+}
+
+impl<'data> Iterator for Records<'data> {
+    type Item = Result<Record<'data>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while !self.data.is_empty() {
+            match self.parse_next() {
+                Ok(Some(record)) => return Some(Ok(record)),
+                Ok(None) => continue,
+                Err(err) => {
+                    // We can't make sense of any subsequent data.
+                    self.data = &[];
+                    return Some(Err(err))
+                }
+            }
+        }
+        None
+    }
+}
+
+
+/// A `perf.data` file, as written by `perf record`.
+///
+/// Files written in pipe mode or in non-native byte order are not
+/// supported. Samples are required to contain the instruction pointer
+/// and the process and thread IDs (as is the case by default).
+#[derive(Debug)]
+pub struct PerfData {
+    /// The memory mapped file.
+    mmap: Mmap,
+    /// The format of samples.
+    format: SampleFormat,
+    /// The byte range of the data section.
+    data: Range<usize>,
+    /// The byte range of the build ID table, if present.
+    build_ids: Option<Range<usize>>,
+}
+
+impl PerfData {
+    /// Open the `perf.data` file at `path`.
+    pub fn open<P>(path: P) -> Result<Self>
+    where
+        P: AsRef<Path>,
+    {
+        let path = path.as_ref();
+        let file =
+            File::open(path).with_context(|| format!("failed to open `{}`", path.display()))?;
+        let mmap = Mmap::map(&file)
+            .with_context(|| format!("failed to memory map `{}`", path.display()))?;
+        Self::from_mmap(mmap)
+    }
+
+    /// Parse the file header of the `perf.data` file contained in
+    /// `mmap`.
+    pub(crate) fn from_mmap(mmap: Mmap) -> Result<Self> {
+        let mut data = &*mmap;
+        let magic = data
+            .read_u64()
+            .ok_or_invalid_data(|| "failed to read perf.data magic")?;
+        if magic != PERF_MAGIC {
+            if magic.swap_bytes() == PERF_MAGIC {
+                return Err(Error::with_unsupported(
+                    "perf.data files in non-native byte order are unsupported",
+                ))
+            }
+            return Err(Error::with_invalid_data(format!(
+                "encountered invalid perf.data magic: {magic:#x}"
+            )))
+        }
+
+        let (size, attr_size, attrs, events, features) = (|| {
+            let size = data.read_u64()?;
+            let attr_size = usize::try_from(data.read_u64()?).ok()?;
+            let attrs = read_section(&mut data)?;
+            let events = read_section(&mut data)?;
+            let _event_types = read_section(&mut data)?;
+            let features = data.read_u64()?;
+            Some((size, attr_size, attrs, events, features))
+        })()
+        .ok_or_invalid_data(|| "failed to read perf.data header")?;
+
+        if size != HEADER_SIZE as u64 {
+            return Err(Error::with_unsupported(format!(
+                "perf.data header size ({size}) is unsupported; pipe mode is not supported"
+            )))
+        }
+
+        let format = Self::parse_attrs(section_data(&mmap, &attrs, "attribute")?, attr_size)?;
+        let _data = section_data(&mmap, &events, "data")?;
+
+        let build_ids = if features & (1 << HEADER_BUILD_ID) != 0 {
+            // Feature sections are stored after the data section, with
+            // one section descriptor for each feature present, in
+            // order.
+            let idx = (features & ((1 << HEADER_BUILD_ID) - 1)).count_ones() as usize;
+            let range = mmap
+                .get(events.end + idx * SECTION_SIZE..)
+                .and_then(|mut data| read_section(&mut data))
+                .ok_or_invalid_data(|| "failed to read perf.data build ID section")?;
+            let _data = section_data(&mmap, &range, "build ID")?;
+            Some(range)
+        } else {
+            None
+        };
+
+        let slf = Self {
+            mmap,
+            format,
+            data: events,
+            build_ids,
+        };
+        Ok(slf)
+    }
+
+    /// Parse the event attributes, determining the sample format used.
+    fn parse_attrs(mut data: &[u8], attr_size: usize) -> Result<SampleFormat> {
+        if attr_size <= SECTION_SIZE || data.len() % attr_size != 0 {
+            return Err(Error::with_invalid_data(format!(
+                "perf.data attribute size ({attr_size}) is invalid"
+            )))
+        }
+
+        let mut format = None::<SampleFormat>;
+        while let Some(mut attr) = data.read_slice(attr_size) {
+            let (sample_type, read_format, flags) = (|| {
+                let () = attr.advance(3 * size_of::<u64>())?;
+                let sample_type = attr.read_u64()?;
+                let read_format = attr.read_u64()?;
+                let flags = attr.read_u64()?;
+                Some((sample_type, read_format, flags))
+            })()
+            .ok_or_invalid_data(|| "failed to read perf.data event attribute")?;
+
+            let attr_format = SampleFormat {
+                sample_type,
+                read_format,
+                sample_id_all: flags & ATTR_FLAG_SAMPLE_ID_ALL != 0,
+            };
+
+            match format {
+                None => format = Some(attr_format),
+                Some(format)
+                    if format.sample_type == attr_format.sample_type
+                        && format.read_format == attr_format.read_format
+                        && format.sample_id_all == attr_format.sample_id_all => {}
+                Some(..) => {
+                    return Err(Error::with_unsupported(
+                        "perf.data files with differing sample formats are unsupported",
+                    ))
+                }
+            }
+        }
+
+        let format = format.ok_or_invalid_data(|| "perf.data file contains no event attributes")?;
+        let required = PERF_SAMPLE_IP | PERF_SAMPLE_TID;
+        if format.sample_type & required != required {
+            return Err(Error::with_unsupported(
+                "perf.data samples lacking instruction pointer or thread IDs are unsupported",
+            ))
+        }
+        Ok(format)
+    }
+
+    /// Create an iterator over the records of the file, in the order
+    /// in which they are stored.
+    ///
+    /// Note that records are not necessarily ordered by time.
+    pub fn records(&self) -> Records<'_> {
+        Records {
+            // SANITY: The range was validated when the file was opened.
+            data: &self.mmap[self.data.clone()],
+            format: self.format,
+        }
+    }
+
+    /// Retrieve the build IDs of the files referenced by samples, as
+    /// recorded in the build ID table.
+    pub(super) fn build_ids(&self) -> Result<HashMap<&Path, BuildId<'_>>> {
+        let mut build_ids = HashMap::new();
+        let Some(range) = &self.build_ids else {
+            return Ok(build_ids)
+        };
+
+        // SANITY: The range was validated when the file was opened.
+        let mut data = &self.mmap[range.clone()];
+        while !data.is_empty() {
+            let (path, build_id) = (|| {
+                let _type = data.read_u32()?;
+                let misc = data.read_u16()?;
+                let size = data.read_u16()?;
+                let mut record =
+                    data.read_slice(usize::from(size).checked_sub(RECORD_HEADER_SIZE)?)?;
+                let _pid = record.read_u32()?;
+                let id = record.read_slice(24)?;
+                let len = if misc & PERF_RECORD_MISC_BUILD_ID_SIZE != 0 {
+                    usize::from(id[20]).min(20)
+                } else {
+                    20
+                };
+                let path = read_path(&mut record)?;
+                Some((path, &id[..len]))
+            })()
+            .ok_or_invalid_data(|| "failed to read perf.data build ID record")?;
+            let _prev = build_ids.insert(path, Cow::Borrowed(build_id));
+        }
+        Ok(build_ids)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use test_log::test;
+
+    use crate::ErrorKind;
+
+
+    const SAMPLE_TYPE: u64 = PERF_SAMPLE_IDENTIFIER
+        | PERF_SAMPLE_IP
+        | PERF_SAMPLE_TID
+        | PERF_SAMPLE_TIME
+        | PERF_SAMPLE_CPU
+        | PERF_SAMPLE_CALLCHAIN;
+
+
+    /// Serialize a record with the given type, misc bits, and body.
+    fn record(type_: u32, misc: u16, body: &[u8]) -> Vec<u8> {
+        let mut data = Vec::new();
+        let () = data.extend_from_slice(&type_.to_ne_bytes());
+        let () = data.extend_from_slice(&misc.to_ne_bytes());
+        let size = (RECORD_HEADER_SIZE + body.len()) as u16;
+        let () = data.extend_from_slice(&size.to_ne_bytes());
+        let () = data.extend_from_slice(body);
+        data
+    }
+
+    /// Serialize a NUL terminated string, padded to eight bytes.
+    fn cstr(s: &str) -> Vec<u8> {
+        let mut data = s.as_bytes().to_vec();
+        let () = data.resize((s.len() + 8) & !7, 0);
+        data
+    }
+
+    /// Serialize the sample ID trailer of a non-sample record.
+    fn sample_id(pid: u32, time: u64) -> Vec<u8> {
+        [
+            &pid.to_ne_bytes()[..],
+            &pid.to_ne_bytes(),
+            &time.to_ne_bytes(),
+            &0u64.to_ne_bytes(),
+            &0u64.to_ne_bytes(),
+        ]
+        .concat()
+    }
+
+    /// Serialize a `perf.data` file containing the provided records
+    /// and build ID table entries.
+    fn perf_data(records: &[Vec<u8>], build_ids: &[Vec<u8>]) -> Vec<u8> {
+        let mut attr = vec![0; 128];
+        let () = attr[4..8].copy_from_slice(&128u32.to_ne_bytes());
+        let () = attr[24..32].copy_from_slice(&SAMPLE_TYPE.to_ne_bytes());
+        let () = attr[40..48].copy_from_slice(&ATTR_FLAG_SAMPLE_ID_ALL.to_ne_bytes());
+        // The section describing the IDs of the event.
+        let () = attr.extend_from_slice(&[0; SECTION_SIZE]);
+
+        let records = records.concat();
+        let build_ids = build_ids.concat();
+        let attrs_offset = HEADER_SIZE;
+        let data_offset = attrs_offset + attr.len();
+        let features_offset = data_offset + records.len();
+        let build_ids_offset = features_offset + SECTION_SIZE;
+
+        let mut data = Vec::new();
+        for value in [
+            PERF_MAGIC,
+            HEADER_SIZE as u64,
+            attr.len() as u64,
+            attrs_offset as u64,
+            attr.len() as u64,
+            data_offset as u64,
+            records.len() as u64,
+            0,
+            0,
+            1 << HEADER_BUILD_ID,
+            0,
+            0,
+            0,
+        ] {
+            let () = data.extend_from_slice(&value.to_ne_bytes());
+        }
+        let () = data.extend_from_slice(&attr);
+        let () = data.extend_from_slice(&records);
+        let () = data.extend_from_slice(&(build_ids_offset as u64).to_ne_bytes());
+        let () = data.extend_from_slice(&(build_ids.len() as u64).to_ne_bytes());
+        let () = data.extend_from_slice(&build_ids);
+        data
+    }
+
+
+    /// Check that we can parse the records of a `perf.data` file.
+    #[test]
+    fn record_parsing() {
+        let mmap2 = [
+            &42u32.to_ne_bytes()[..],
+            &43u32.to_ne_bytes(),
+            &0x1000u64.to_ne_bytes(),
+            &0x2000u64.to_ne_bytes(),
+            &0x3000u64.to_ne_bytes(),
+            // Build ID size, reserved bytes, and build ID.
+            &[4, 0, 0, 0],
+            &[0xde, 0xad, 0xbe, 0xef],
+            &[0; 16],
+            &5u32.to_ne_bytes(),
+            &2u32.to_ne_bytes(),
+            &cstr("/usr/bin/test"),
+            &sample_id(42, 1000),
+        ]
+        .concat();
+        let comm = [
+            &42u32.to_ne_bytes()[..],
+            &42u32.to_ne_bytes(),
+            &cstr("test"),
+            &sample_id(42, 900),
+        ]
+        .concat();
+        let fork = [
+            &44u32.to_ne_bytes()[..],
+            &42u32.to_ne_bytes(),
+            &44u32.to_ne_bytes(),
+            &42u32.to_ne_bytes(),
+            &1100u64.to_ne_bytes(),
+            &sample_id(44, 1100),
+        ]
+        .concat();
+        let sample = [
+            &1u64.to_ne_bytes()[..],
+            &0xffffffff81000010u64.to_ne_bytes(),
+            &42u32.to_ne_bytes(),
+            &43u32.to_ne_bytes(),
+            &1200u64.to_ne_bytes(),
+            &0u64.to_ne_bytes(),
+            &6u64.to_ne_bytes(),
+            &PERF_CONTEXT_KERNEL.to_ne_bytes(),
+            &0xffffffff81000010u64.to_ne_bytes(),
+            &0xffffffff81000020u64.to_ne_bytes(),
+            &PERF_CONTEXT_USER.to_ne_bytes(),
+            &0x1010u64.to_ne_bytes(),
+            &0x1020u64.to_ne_bytes(),
+        ]
+        .concat();
+        let build_id = [&record(0, PERF_RECORD_MISC_BUILD_ID_SIZE, &{
+            let mut id = [0; 24];
+            let () = id[..3].copy_from_slice(&[1, 2, 3]);
+            id[20] = 3;
+            [&42u32.to_ne_bytes()[..], &id, &cstr("/usr/lib/libtest.so")].concat()
+        })[..]]
+        .concat();
+
+        let data = perf_data(
+            &[
+                record(PERF_RECORD_MMAP2, PERF_RECORD_MISC_MMAP_BUILD_ID, &mmap2),
+                record(PERF_RECORD_COMM, PERF_RECORD_MISC_COMM_EXEC, &comm),
+                // A record type we don't care about.
+                record(PERF_RECORD_MMAP2 + 1, 0, &[0; 8]),
+                record(PERF_RECORD_FORK, 0, &fork),
+                record(PERF_RECORD_SAMPLE, PERF_RECORD_MISC_KERNEL, &sample),
+            ],
+            &[build_id],
+        );
+        let perf_data = PerfData::from_mmap(Mmap::from_bytes(&data).unwrap()).unwrap();
+        let records = perf_data.records().collect::<Result<Vec<_>>>().unwrap();
+        assert_eq!(records.len(), 4);
+
+        let Record::Mmap(mmap) = &records[0] else {
+            panic!("unexpected record: {:?}", records[0])
+        };
+        assert_eq!(mmap.pid, 42);
+        assert_eq!(mmap.tid, 43);
+        assert_eq!(mmap.addr, 0x1000);
+        assert_eq!(mmap.len, 0x2000);
+        assert_eq!(mmap.pgoff, 0x3000);
+        assert_eq!(mmap.path, Path::new("/usr/bin/test"));
+        assert_eq!(
+            mmap.build_id.as_deref(),
+            Some([0xde, 0xad, 0xbe, 0xef].as_slice())
+        );
+        assert_eq!(records[0].time(), Some(1000));
+
+        let Record::Comm(comm) = &records[1] else {
+            panic!("unexpected record: {:?}", records[1])
+        };
+        assert_eq!(comm.comm, OsStr::new("test"));
+        assert!(comm.exec);
+        assert_eq!(comm.time, Some(900));
+
+        let Record::Fork(fork) = &records[2] else {
+            panic!("unexpected record: {:?}", records[2])
+        };
+        assert_eq!((fork.pid, fork.ppid), (44, 42));
+        assert_eq!(fork.time, 1100);
+
+        let Record::Sample(sample) = &records[3] else {
+            panic!("unexpected record: {:?}", records[3])
+        };
+        assert_eq!((sample.pid, sample.tid), (42, 43));
+        assert_eq!(sample.time, Some(1200));
+        assert_eq!(sample.kstack, vec![0xffffffff81000010, 0xffffffff81000020]);
+        assert_eq!(sample.ustack, vec![0x1010, 0x1020]);
+
+        let build_ids = perf_data.build_ids().unwrap();
+        assert_eq!(
+            build_ids
+                .get(Path::new("/usr/lib/libtest.so"))
+                .map(|id| &**id),
+            Some([1, 2, 3].as_slice())
+        );
+    }
+
+    /// Check that samples without a call chain report the sampled
+    /// instruction pointer as the only frame.
+    #[test]
+    fn sample_without_callchain() {
+        let format = SampleFormat {
+            sample_type: PERF_SAMPLE_IP | PERF_SAMPLE_TID,
+            read_format: 0,
+            sample_id_all: false,
+        };
+        let data = [
+            &0x1337u64.to_ne_bytes()[..],
+            &1u32.to_ne_bytes(),
+            &2u32.to_ne_bytes(),
+        ]
+        .concat();
+        let sample = format.parse_sample(&data, PERF_RECORD_MISC_USER).unwrap();
+        assert_eq!(sample.kstack, Vec::<Addr>::new());
+        assert_eq!(sample.ustack, vec![0x1337]);
+        assert_eq!(sample.time, None);
+    }
+
+    /// Check that we can skip over read values of different formats.
+    #[test]
+    fn read_value_skipping() {
+        let format = SampleFormat {
+            sample_type: 0,
+            read_format: PERF_FORMAT_GROUP | PERF_FORMAT_TOTAL_TIME_ENABLED | PERF_FORMAT_ID,
+            sample_id_all: false,
+        };
+        // `nr`, time enabled, and two pairs of value and ID.
+        let data = [2u64, 0, 0, 0, 0, 0, 0x1337]
+            .iter()
+            .flat_map(|value| value.to_ne_bytes())
+            .collect::<Vec<_>>();
+        let mut slice = data.as_slice();
+        let () = format.skip_read_values(&mut slice).unwrap();
+        assert_eq!(slice.read_u64(), Some(0x1337));
+
+        let format = SampleFormat {
+            read_format: PERF_FORMAT_LOST,
+            ..format
+        };
+        let mut slice = data.as_slice();
+        let () = format.skip_read_values(&mut slice).unwrap();
+        assert_eq!(slice.read_u64(), Some(0));
+    }
+
+    /// Check that we reject invalid or unsupported files.
+    #[test]
+    fn invalid_file_parsing() {
+        let err = PerfData::from_mmap(Mmap::from_bytes(b"PERFILE").unwrap()).unwrap_err();
+        assert!(err.to_string().contains("magic"), "{err}");
+
+        let data = PERF_MAGIC.swap_bytes().to_ne_bytes();
+        let err = PerfData::from_mmap(Mmap::from_bytes(&data).unwrap()).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::Unsupported);
+
+        // Pipe mode files have a header consisting of only magic and
+        // size.
+        let mut data = perf_data(&[], &[]);
+        let () = data[8..16].copy_from_slice(&16u64.to_ne_bytes());
+        let err = PerfData::from_mmap(Mmap::from_bytes(&data).unwrap()).unwrap_err();
+        assert!(err.to_string().contains("pipe mode"), "{err}");
+
+        let mut data = perf_data(&[record(PERF_RECORD_SAMPLE, 0, &[0; 4])], &[]);
+        let perf_data = PerfData::from_mmap(Mmap::from_bytes(&data).unwrap()).unwrap();
+        let mut records = perf_data.records();
+        let err = records.next().unwrap().unwrap_err();
+        assert!(err.to_string().contains("sample record"), "{err}");
+        assert!(records.next().is_none());
+
+        // Samples lacking thread IDs are unsupported.
+        let () =
+            data[HEADER_SIZE + 24..HEADER_SIZE + 32].copy_from_slice(&PERF_SAMPLE_IP.to_ne_bytes());
+        let err = PerfData::from_mmap(Mmap::from_bytes(&data).unwrap()).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::Unsupported);
+    }
+}
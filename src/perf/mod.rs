@@ -0,0 +1,43 @@
+//! Functionality for post-processing `perf.data` files.
+//!
+//! A `perf.data` file, as written by `perf record`, contains the memory
+//! mappings of all processes of interest along with the samples taken.
+//! [`PerfData`] provides access to these records and can symbolize the
+//! stacks of all samples in bulk, without requiring the sampled
+//! processes to still be around.
+//!
+//! ```no_run
+//! use blazesym::perf::PerfData;
+//! use blazesym::symbolize::source::Kernel;
+//! use blazesym::symbolize::SymbolStore;
+//! use blazesym::symbolize::Symbolizer;
+//!
+//! let perf_data = PerfData::open("perf.data").unwrap();
+//! let symbolizer = Symbolizer::new();
+//! let stacks = perf_data
+//!     .symbolize_stacks(&symbolizer, &SymbolStore::default(), Some(&Kernel::default()))
+//!     .unwrap();
+//!
+//! for stack in stacks {
+//!     println!("{}/{}:", stack.pid, stack.tid);
+//!     for (addr, sym) in stack.kstack.iter().chain(stack.ustack.iter()) {
+//!         match sym.as_sym() {
+//!             Some(sym) => println!("  {addr:#x}: {}", sym.name),
+//!             None => println!("  {addr:#x}: <no-symbol>"),
+//!         }
+//!     }
+//! }
+//! ```
+
+mod data;
+mod stacks;
+
+pub use data::CommRecord;
+pub use data::ForkRecord;
+pub use data::MmapRecord;
+pub use data::PerfData;
+pub use data::Record;
+pub use data::Records;
+pub use data::SampleRecord;
+pub use stacks::SymbolizedStack;
+pub use stacks::SymbolizedStacks;
@@ -0,0 +1,628 @@
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
+use std::path::Path;
+use std::vec;
+
+use crate::normalize;
+use crate::normalize::buildid::BuildId;
+use crate::normalize::Elf;
+use crate::normalize::Unknown;
+use crate::normalize::UserMeta;
+use crate::normalize::UserOutput;
+use crate::symbolize::source::Kernel;
+use crate::symbolize::source::Source;
+use crate::symbolize::AddrKind;
+use crate::symbolize::Input;
+use crate::symbolize::Reason;
+use crate::symbolize::SymbolStore;
+use crate::symbolize::Symbolized;
+use crate::symbolize::Symbolizer;
+use crate::Addr;
+use crate::Result;
+
+use super::data::ForkRecord;
+use super::data::MmapRecord;
+use super::data::Record;
+use super::data::SampleRecord;
+use super::PerfData;
+
+
+/// A memory mapping of a process.
+#[derive(Clone, Debug)]
+struct Mapping {
+    /// The range of virtual addresses covered by the mapping.
+    addr: Addr,
+    end: Addr,
+    /// The offset of the mapping into the mapped file.
+    pgoff: u64,
+    /// The index of the meta data describing the mapped entity.
+    meta_idx: usize,
+}
+
+
+/// A sample whose frames got resolved to indices into the list of
+/// addresses to symbolize.
+#[derive(Debug)]
+struct Sample {
+    pid: u32,
+    tid: u32,
+    time: Option<u64>,
+    /// Kernel frames, as pairs of address and index into the list of
+    /// kernel addresses.
+    kframes: Vec<(Addr, usize)>,
+    /// User space frames, as pairs of address and index into the
+    /// normalized outputs.
+    uframes: Vec<(Addr, usize)>,
+}
+
+
+/// Retrieve the kind of the `idx`th address of a stack.
+///
+/// All but the innermost frame are return addresses.
+#[inline]
+fn addr_kind(idx: usize) -> AddrKind {
+    if idx == 0 {
+        AddrKind::Ip
+    } else {
+        AddrKind::ReturnAddr
+    }
+}
+
+
+/// State for replaying the memory mappings of all processes and
+/// normalizing sampled user space addresses accordingly.
+#[derive(Debug)]
+struct Stacks<'data> {
+    /// Build IDs of files, as recorded in the build ID table.
+    build_ids: HashMap<&'data Path, BuildId<'data>>,
+    /// The current memory mappings of each process, in the order in
+    /// which they got created.
+    maps: HashMap<u32, Vec<Mapping>>,
+    /// The normalized user space addresses to symbolize.
+    output: UserOutput<'data>,
+    /// The kinds of the addresses in `output`.
+    kinds: Vec<AddrKind>,
+    /// Mapping from normalized address, meta data index, and address
+    /// kind to the index of the corresponding output.
+    output_idxs: HashMap<(u64, usize, AddrKind), usize>,
+    /// Mapping from ELF file to the index of its meta data.
+    elf_idxs: HashMap<(&'data Path, Option<BuildId<'data>>), usize>,
+    /// Mapping from reason to the index of the corresponding
+    /// [`Unknown`] meta data.
+    unknown_idxs: HashMap<normalize::Reason, usize>,
+    /// The kernel addresses to symbolize.
+    kaddrs: Vec<(Addr, AddrKind)>,
+    /// Mapping from kernel address and kind to its index in `kaddrs`.
+    kaddr_idxs: HashMap<(Addr, AddrKind), usize>,
+    /// All samples processed so far.
+    samples: Vec<Sample>,
+}
+
+impl<'data> Stacks<'data> {
+    fn new(build_ids: HashMap<&'data Path, BuildId<'data>>) -> Self {
+        Self {
+            build_ids,
+            maps: HashMap::new(),
+            output: UserOutput {
+                outputs: Vec::new(),
+                meta: Vec::new(),
+            },
+            kinds: Vec::new(),
+            output_idxs: HashMap::new(),
+            elf_idxs: HashMap::new(),
+            unknown_idxs: HashMap::new(),
+            kaddrs: Vec::new(),
+            kaddr_idxs: HashMap::new(),
+            samples: Vec::new(),
+        }
+    }
+
+    /// Retrieve the index of the [`Unknown`] meta data for `reason`,
+    /// creating it if necessary.
+    fn unknown_idx(&mut self, reason: normalize::Reason) -> usize {
+        let meta = &mut self.output.meta;
+        *self.unknown_idxs.entry(reason).or_insert_with(|| {
+            let () = meta.push(UserMeta::Unknown(Unknown::new(reason)));
+            meta.len() - 1
+        })
+    }
+
+    fn handle_record(&mut self, record: Record<'data>) {
+        match record {
+            Record::Mmap(mmap) => self.handle_mmap(mmap),
+            Record::Fork(fork) => self.handle_fork(fork),
+            Record::Comm(comm) => {
+                if comm.exec {
+                    let _maps = self.maps.remove(&comm.pid);
+                }
+            }
+            Record::Sample(sample) => self.handle_sample(sample),
+        }
+    }
+
+    fn handle_mmap(&mut self, mmap: MmapRecord<'data>) {
+        let MmapRecord {
+            pid,
+            addr,
+            len,
+            pgoff,
+            path,
+            build_id,
+            ..
+        } = mmap;
+
+        // Kernel and kernel module mappings are of no interest, as
+        // kernel addresses are symbolized as-is.
+        if pid == u32::MAX {
+            return
+        }
+
+        // Note that `Path::starts_with` compares entire components and
+        // so can't be used for checking the prefix.
+        let pseudo = path.to_string_lossy().starts_with("//");
+        let meta_idx = if pseudo || !path.is_absolute() {
+            // Anonymous mappings (`//anon`) and special ones (such as
+            // `[vdso]`) are not backed by a file we could symbolize
+            // with.
+            let reason = if path == Path::new("//anon") {
+                normalize::Reason::MissingComponent
+            } else {
+                normalize::Reason::Unsupported
+            };
+            self.unknown_idx(reason)
+        } else {
+            let build_id = build_id.or_else(|| self.build_ids.get(path).cloned());
+            let meta = &mut self.output.meta;
+            *self
+                .elf_idxs
+                .entry((path, build_id.clone()))
+                .or_insert_with(|| {
+                    let elf = Elf {
+                        path: path.to_path_buf(),
+                        build_id,
+                        _non_exhaustive: (),
+                    };
+                    let () = meta.push(UserMeta::Elf(elf));
+                    meta.len() - 1
+                })
+        };
+
+        let mapping = Mapping {
+            addr,
+            end: addr.saturating_add(len),
+            pgoff,
+            meta_idx,
+        };
+        let () = self.maps.entry(pid).or_default().push(mapping);
+    }
+
+    fn handle_fork(&mut self, fork: ForkRecord) {
+        // Threads share the address space of their process; new
+        // processes start out with a copy of their parent's.
+        if fork.pid != fork.ppid {
+            let maps = self.maps.get(&fork.ppid).cloned().unwrap_or_default();
+            let _maps = self.maps.insert(fork.pid, maps);
+        }
+    }
+
+    /// Normalize the user space address `addr` of process `pid`,
+    /// returning the index of the corresponding output.
+    fn normalize_addr(&mut self, pid: u32, addr: Addr, kind: AddrKind) -> usize {
+        let adjusted = addr - kind.adjustment(addr) as Addr;
+        // More recently created mappings take precedence over older
+        // ones they overlap with.
+        let mapping = self.maps.get(&pid).and_then(|maps| {
+            maps.iter()
+                .rev()
+                .find(|mapping| (mapping.addr..mapping.end).contains(&adjusted))
+        });
+
+        let (output, meta_idx) = match mapping {
+            Some(mapping) if matches!(self.output.meta[mapping.meta_idx], UserMeta::Elf(..)) => {
+                (adjusted - mapping.addr + mapping.pgoff, mapping.meta_idx)
+            }
+            Some(mapping) => (adjusted, mapping.meta_idx),
+            None => (adjusted, self.unknown_idx(normalize::Reason::Unmapped)),
+        };
+
+        match self.output_idxs.entry((output, meta_idx, kind)) {
+            Entry::Occupied(occupied) => *occupied.get(),
+            Entry::Vacant(vacancy) => {
+                let () = self.output.outputs.push((output, meta_idx));
+                let () = self.kinds.push(kind);
+                *vacancy.insert(self.output.outputs.len() - 1)
+            }
+        }
+    }
+
+    fn handle_sample(&mut self, sample: SampleRecord) {
+        let SampleRecord {
+            pid,
+            tid,
+            time,
+            kstack,
+            ustack,
+            ..
+        } = sample;
+
+        let kframes = kstack
+            .into_iter()
+            .enumerate()
+            .map(|(idx, addr)| {
+                let key = (addr, addr_kind(idx));
+                let idx = *self.kaddr_idxs.entry(key).or_insert_with(|| {
+                    let () = self.kaddrs.push(key);
+                    self.kaddrs.len() - 1
+                });
+                (addr, idx)
+            })
+            .collect();
+        let uframes = ustack
+            .into_iter()
+            .enumerate()
+            .map(|(idx, addr)| (addr, self.normalize_addr(pid, addr, addr_kind(idx))))
+            .collect();
+
+        let sample = Sample {
+            pid,
+            tid,
+            time,
+            kframes,
+            uframes,
+        };
+        let () = self.samples.push(sample);
+    }
+
+    /// Symbolize all addresses gathered.
+    fn symbolize<'sym>(
+        self,
+        symbolizer: &'sym Symbolizer,
+        store: &SymbolStore,
+        kernel: Option<&Kernel>,
+    ) -> Result<SymbolizedStacks<'sym>> {
+        let mut usyms = symbolizer.symbolize_normalized(&self.output, store)?;
+        let () = usyms
+            .iter_mut()
+            .zip(self.output.outputs.iter().zip(&self.kinds))
+            .for_each(|(symbolized, ((_output, meta_idx), kind))| {
+                if let (Symbolized::Sym(sym), UserMeta::Elf(..)) =
+                    (symbolized, &self.output.meta[*meta_idx])
+                {
+                    // The output was normalized from the adjusted
+                    // address.
+                    sym.addr_adjust = usize::from(*kind == AddrKind::ReturnAddr);
+                }
+            });
+
+        let ksyms = match kernel {
+            Some(kernel) if !self.kaddrs.is_empty() => {
+                let src = Source::Kernel(kernel.clone());
+                match symbolizer.symbolize_tagged(&src, Input::AbsAddr(&self.kaddrs)) {
+                    Ok(ksyms) => ksyms,
+                    Err(err) => match Reason::from_file_error(&err) {
+                        Some(reason) => vec![Symbolized::Unknown(reason); self.kaddrs.len()],
+                        None => return Err(err),
+                    },
+                }
+            }
+            _ => vec![Symbolized::Unknown(Reason::Unsupported); self.kaddrs.len()],
+        };
+
+        let stacks = SymbolizedStacks {
+            samples: self.samples.into_iter(),
+            ksyms,
+            usyms,
+        };
+        Ok(stacks)
+    }
+}
+
+
+/// A sampled stack with all its frames symbolized.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SymbolizedStack<'sym> {
+    /// The ID of the process the sample was taken in.
+    pub pid: u32,
+    /// The ID of the thread the sample was taken in.
+    pub tid: u32,
+    /// The time stamp of the sample, if available.
+    pub time: Option<u64>,
+    /// The kernel stack, innermost frame first, as pairs of address
+    /// and symbolization result.
+    pub kstack: Vec<(Addr, Symbolized<'sym>)>,
+    /// The user space stack, innermost frame first, as pairs of
+    /// address and symbolization result.
+    pub ustack: Vec<(Addr, Symbolized<'sym>)>,
+    /// The struct is non-exhaustive and open to extension.
+    #[doc(hidden)]
+    pub _non_exhaustive: (),
+}
+
+
+/// An iterator over the symbolized stacks of a `perf.data` file, as
+/// returned by [`PerfData::symbolize_stacks`].
+#[derive(Debug)]
+pub struct SymbolizedStacks<'sym> {
+    /// The samples to report.
+    samples: vec::IntoIter<Sample>,
+    /// Symbolization results for kernel addresses.
+    ksyms: Vec<Symbolized<'sym>>,
+    /// Symbolization results for user space addresses.
+    usyms: Vec<Symbolized<'sym>>,
+}
+
+impl<'sym> Iterator for SymbolizedStacks<'sym> {
+    type Item = SymbolizedStack<'sym>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let Sample {
+            pid,
+            tid,
+            time,
+            kframes,
+            uframes,
+        } = self.samples.next()?;
+
+        let stack = SymbolizedStack {
+            pid,
+            tid,
+            time,
+            kstack: kframes
+                .into_iter()
+                .map(|(addr, idx)| (addr, self.ksyms[idx].clone()))
+                .collect(),
+            ustack: uframes
+                .into_iter()
+                .map(|(addr, idx)| (addr, self.usyms[idx].clone()))
+                .collect(),
+            _non_exhaustive: (),
+        };
+        Some(stack)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.samples.size_hint()
+    }
+}
+
+impl ExactSizeIterator for SymbolizedStacks<'_> {}
+
+
+/// Replay the memory mappings described by `records` and gather the
+/// addresses of all samples.
+fn process_records<'data>(
+    records: Vec<Record<'data>>,
+    build_ids: HashMap<&'data Path, BuildId<'data>>,
+) -> Stacks<'data> {
+    let mut stacks = Stacks::new(build_ids);
+    let () = records
+        .into_iter()
+        .for_each(|record| stacks.handle_record(record));
+    stacks
+}
+
+
+impl PerfData {
+    /// Symbolize the stacks of all samples contained in the file.
+    ///
+    /// User space addresses are normalized based on the memory
+    /// mappings recorded for the sampled process and then symbolized
+    /// in bulk using the files described by `store` (refer to
+    /// [`Symbolizer::symbolize_normalized`]). Build IDs are taken from
+    /// the recorded mappings or the file's build ID table, if
+    /// available. Kernel addresses are symbolized using `kernel`. If
+    /// it is `None`, they are reported as [`Reason::Unsupported`].
+    ///
+    /// All but the innermost frame of a stack are treated as return
+    /// addresses and adjusted accordingly (see [`AddrKind`]).
+    ///
+    /// Records are processed ordered by time, if time stamps are
+    /// available for all of them, and in file order otherwise.
+    /// Stacks are reported in the same order.
+    pub fn symbolize_stacks<'sym>(
+        &self,
+        symbolizer: &'sym Symbolizer,
+        store: &SymbolStore,
+        kernel: Option<&Kernel>,
+    ) -> Result<SymbolizedStacks<'sym>> {
+        let build_ids = self.build_ids()?;
+        let mut records = self.records().collect::<Result<Vec<_>>>()?;
+        if records.iter().all(|record| record.time().is_some()) {
+            // Records are written per CPU and are only loosely ordered
+            // in the file. Note that sorting is stable, preserving the
+            // order of records with identical time stamps.
+            let () = records.sort_by_key(Record::time);
+        }
+
+        let stacks = process_records(records, build_ids);
+        stacks.symbolize(symbolizer, store, kernel)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::env;
+
+    use test_log::test;
+
+    use crate::elf::ElfParser;
+
+
+    fn mmap(pid: u32, addr: Addr, len: u64, pgoff: u64, path: &Path) -> Record<'_> {
+        Record::Mmap(MmapRecord {
+            pid,
+            tid: pid,
+            addr,
+            len,
+            pgoff,
+            path,
+            build_id: None,
+            time: None,
+            _non_exhaustive: (),
+        })
+    }
+
+    fn sample(pid: u32, kstack: &[Addr], ustack: &[Addr]) -> Record<'static> {
+        Record::Sample(SampleRecord {
+            pid,
+            tid: pid,
+            time: None,
+            kstack: kstack.to_vec(),
+            ustack: ustack.to_vec(),
+            _non_exhaustive: (),
+        })
+    }
+
+
+    /// Check that we replay memory mappings of processes correctly
+    /// when normalizing sampled addresses.
+    #[test]
+    fn mapping_replay() {
+        let path = Path::new("/usr/bin/test");
+        let other = Path::new("/usr/bin/other");
+        let records = vec![
+            mmap(1, 0x1000, 0x1000, 0x0, path),
+            mmap(
+                u32::MAX,
+                0xffffffff81000000,
+                0x1000000,
+                0x0,
+                Path::new("[kernel.kallsyms]"),
+            ),
+            Record::Fork(ForkRecord {
+                pid: 2,
+                ppid: 1,
+                tid: 2,
+                ptid: 1,
+                time: 0,
+                _non_exhaustive: (),
+            }),
+            mmap(2, 0x1800, 0x1000, 0x3000, other),
+            mmap(2, 0x4000, 0x1000, 0x0, Path::new("//anon")),
+            sample(1, &[0xffffffff81000010], &[0x1010, 0x1810]),
+            sample(2, &[], &[0x1010, 0x1811, 0x4010, 0x9000]),
+            sample(1, &[0xffffffff81000010], &[0x1010]),
+        ];
+
+        let stacks = process_records(records, HashMap::new());
+        assert_eq!(stacks.samples.len(), 3);
+        assert_eq!(stacks.kaddrs, vec![(0xffffffff81000010, AddrKind::Ip)]);
+
+        let outputs = |sample: &Sample| {
+            sample
+                .uframes
+                .iter()
+                .map(|(_addr, idx)| {
+                    let (output, meta_idx) = stacks.output.outputs[*idx];
+                    (output, &stacks.output.meta[meta_idx])
+                })
+                .collect::<Vec<_>>()
+        };
+
+        let elf = |path: &Path| {
+            UserMeta::Elf(Elf {
+                path: path.to_path_buf(),
+                build_id: None,
+                _non_exhaustive: (),
+            })
+        };
+        let unknown = |reason| UserMeta::Unknown(Unknown::new(reason));
+
+        // The return address gets adjusted before normalization.
+        assert_eq!(
+            outputs(&stacks.samples[0]),
+            vec![(0x10, &elf(path)), (0x80f, &elf(path))]
+        );
+        assert_eq!(
+            outputs(&stacks.samples[1]),
+            vec![
+                (0x10, &elf(path)),
+                (0x3010, &elf(other)),
+                (0x400f, &unknown(normalize::Reason::MissingComponent)),
+                (0x8fff, &unknown(normalize::Reason::Unmapped)),
+            ]
+        );
+        // Identical addresses should be normalized only once.
+        assert_eq!(
+            stacks.samples[2].uframes[0].1,
+            stacks.samples[0].uframes[0].1
+        );
+        assert_eq!(stacks.samples[2].kframes, stacks.samples[0].kframes);
+    }
+
+    /// Check that an `exec` discards the mappings of a process.
+    #[test]
+    fn exec_handling() {
+        let path = Path::new("/usr/bin/test");
+        let records = vec![
+            mmap(1, 0x1000, 0x1000, 0x0, path),
+            Record::Comm(super::super::CommRecord {
+                pid: 1,
+                tid: 1,
+                comm: "test".as_ref(),
+                exec: true,
+                time: None,
+                _non_exhaustive: (),
+            }),
+            sample(1, &[], &[0x1010]),
+        ];
+
+        let stacks = process_records(records, HashMap::new());
+        let (_output, meta_idx) = stacks.output.outputs[stacks.samples[0].uframes[0].1];
+        assert_eq!(
+            stacks.output.meta[meta_idx],
+            UserMeta::Unknown(Unknown::new(normalize::Reason::Unmapped))
+        );
+    }
+
+    /// Check that we can symbolize sampled stacks end-to-end.
+    #[test]
+    fn stack_symbolization() {
+        let path = Path::new(&env!("CARGO_MANIFEST_DIR"))
+            .join("data")
+            .join("test-stable-addrs.bin");
+        let parser = ElfParser::open(&path).unwrap();
+        let offset = parser.find_file_offset(0x2000200).unwrap().unwrap();
+
+        // Map the file such that `factorial` ends up at 0x10000200.
+        let base = 0x10000200 - offset;
+        let records = vec![
+            mmap(1, base, 0x10000, 0, &path),
+            sample(1, &[0xffffffff81000000], &[0x10000201, 0x10000201]),
+        ];
+
+        let stacks = process_records(records, HashMap::new());
+        let symbolizer = Symbolizer::new();
+        let mut symbolized = stacks
+            .symbolize(&symbolizer, &SymbolStore::default(), None)
+            .unwrap();
+        assert_eq!(symbolized.len(), 1);
+
+        let stack = symbolized.next().unwrap();
+        assert_eq!(stack.pid, 1);
+        assert_eq!(
+            stack.kstack,
+            vec![(0xffffffff81000000, Symbolized::Unknown(Reason::Unsupported))]
+        );
+
+        let (addr, sym) = &stack.ustack[0];
+        assert_eq!(*addr, 0x10000201);
+        let sym = sym.as_sym().unwrap();
+        assert_eq!(sym.name, "factorial");
+        assert_eq!(sym.offset, 1);
+        assert_eq!(sym.addr_adjust, 0);
+
+        let (addr, sym) = &stack.ustack[1];
+        assert_eq!(*addr, 0x10000201);
+        let sym = sym.as_sym().unwrap();
+        assert_eq!(sym.name, "factorial");
+        assert_eq!(sym.offset, 0);
+        assert_eq!(sym.addr_adjust, 1);
+
+        assert_eq!(symbolized.next(), None);
+    }
+}
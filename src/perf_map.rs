@@ -127,7 +127,7 @@ pub(crate) struct PerfMap {
 impl PerfMap {
     /// Retrieve the path to a perf map file representing the process with the
     /// given `pid`.
-    pub(crate) fn path(pid: Pid) -> PathBuf {
+    pub(crate) fn path(pid: &Pid) -> PathBuf {
         let pid = pid.resolve();
         // The documentation mentions /tmp by name specifically, ignoring
         // `TMPDIR` et al, so that is what we work with as well.
@@ -175,6 +175,9 @@ impl Symbolize for PerfMap {
                             lang: SrcLang::Unknown,
                             code_info: None,
                             inlined: Box::new([]),
+                            inlined_truncated: false,
+                            section: None,
+                            module: None,
                         };
                         return Ok(Ok(sym))
                     }
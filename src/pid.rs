@@ -1,26 +1,255 @@
+#[cfg(linux)]
+use std::fmt::Debug;
 use std::fmt::Display;
 use std::fmt::Formatter;
 use std::fmt::Result as FmtResult;
+#[cfg(linux)]
+use std::fs::read_to_string;
+#[cfg(linux)]
+use std::hash::Hash;
+#[cfg(linux)]
+use std::hash::Hasher;
+#[cfg(linux)]
+use std::io;
 use std::num::NonZeroU32;
+#[cfg(linux)]
+use std::os::fd::AsFd;
+#[cfg(linux)]
+use std::os::fd::AsRawFd as _;
+#[cfg(linux)]
+use std::os::fd::BorrowedFd;
+#[cfg(linux)]
+use std::os::fd::OwnedFd;
 use std::process;
+#[cfg(linux)]
+use std::ptr;
+#[cfg(linux)]
+use std::sync::Arc;
+
+#[cfg(linux)]
+use crate::Error;
+#[cfg(linux)]
+use crate::ErrorExt as _;
+#[cfg(linux)]
+use crate::IntoError as _;
+use crate::Result;
+
+
+/// Retrieve the start time of the process with the given `pid`, in
+/// clock ticks after system boot.
+#[cfg(linux)]
+fn read_start_time(pid: NonZeroU32) -> Result<u64> {
+    let path = format!("/proc/{pid}/stat");
+    let stat = read_to_string(&path).with_context(|| format!("failed to read `{path}`"))?;
+    // The second field is the process' name, enclosed in parentheses,
+    // which itself may contain spaces and parentheses. So we only start
+    // splitting after the last closing parenthesis, with the first
+    // resulting field being the third one. The start time is the 22nd.
+    stat.rsplit_once(')')
+        .and_then(|(_, fields)| fields.split_whitespace().nth(22 - 3))
+        .and_then(|start_time| start_time.parse::<u64>().ok())
+        .ok_or_invalid_data(|| format!("failed to find process start time in `{path}`"))
+}
+
+
+/// A process file descriptor ("pidfd") referencing a process.
+///
+/// A pidfd refers to a specific process and, unlike a numeric process
+/// ID, can't end up referring to a different process once the original
+/// one exited and its ID got reused. Symbolization and normalization
+/// requests referencing a process via a pidfd verify that the process
+/// is still alive after having opened its `/proc/<pid>/` files, which
+/// eliminates races with process ID reuse.
+///
+/// Two [`PidFd`] objects compare equal if they reference the same
+/// process, irrespective of whether they use the same file descriptor.
+/// The process is identified by its ID along with its start time, so
+/// that a process that got assigned the ID of one that already exited
+/// is considered distinct.
+///
+/// Objects of this type are cheap to clone and share the underlying
+/// file descriptor.
+#[cfg(linux)]
+#[cfg_attr(docsrs, doc(cfg(target_os = "linux")))]
+#[derive(Clone)]
+pub struct PidFd {
+    /// The ID of the process, as it was valid when the pidfd was
+    /// wrapped.
+    pid: NonZeroU32,
+    /// The start time of the process, in clock ticks after system
+    /// boot.
+    start_time: u64,
+    /// The pidfd itself.
+    fd: Arc<OwnedFd>,
+}
+
+#[cfg(linux)]
+impl PidFd {
+    /// Wrap the provided pidfd (as returned by `pidfd_open(2)` or
+    /// `clone3(2)` with `CLONE_PIDFD`, for example).
+    ///
+    /// The ID of the referenced process is retrieved eagerly and
+    /// construction fails if the process has already exited or if it
+    /// lives in a PID namespace not visible to us.
+    pub fn new(fd: OwnedFd) -> Result<Self> {
+        let path = format!("/proc/self/fdinfo/{}", fd.as_raw_fd());
+        let fdinfo = read_to_string(&path)
+            .with_context(|| format!("failed to read pidfd information from `{path}`"))?;
+        let pid = fdinfo
+            .lines()
+            .find_map(|line| line.strip_prefix("Pid:"))
+            .ok_or_invalid_input(|| format!("file descriptor {} is not a pidfd", fd.as_raw_fd()))?
+            .trim();
+        // The kernel reports -1 for processes that already exited and
+        // 0 for those not residing in our PID namespace (or a
+        // descendant thereof).
+        let pid = pid
+            .parse::<u32>()
+            .ok()
+            .and_then(NonZeroU32::new)
+            .ok_or_invalid_input(|| {
+                format!("pidfd refers to process not accessible to us (PID: {pid})")
+            })?;
+
+        let start_time = read_start_time(pid)?;
+
+        let slf = Self {
+            pid,
+            start_time,
+            fd: Arc::new(fd),
+        };
+        // Make sure that the start time we read belongs to the process
+        // referenced by the pidfd and not to one that got assigned its
+        // ID after it exited.
+        let () = slf.check_alive()?;
+        Ok(slf)
+    }
+
+    /// Retrieve the ID of the referenced process.
+    #[inline]
+    pub fn pid(&self) -> NonZeroU32 {
+        self.pid
+    }
+
+    /// Check that the referenced process is still alive.
+    ///
+    /// If it is, any `/proc/<pid>/` file opened prior to this check is
+    /// guaranteed to belong to the referenced process.
+    pub(crate) fn check_alive(&self) -> Result<()> {
+        // SAFETY: The file descriptor is valid for the lifetime of
+        //         `self` and a signal of zero merely performs error
+        //         checking.
+        let rc = unsafe {
+            libc::syscall(
+                libc::SYS_pidfd_send_signal,
+                self.fd.as_raw_fd(),
+                0,
+                ptr::null::<libc::siginfo_t>(),
+                0,
+            )
+        };
+        if rc != 0 {
+            let err = io::Error::last_os_error();
+            return Err(Error::from(err))
+                .with_context(|| format!("process {} is no longer alive", self.pid))
+        }
+        Ok(())
+    }
+}
+
+#[cfg(linux)]
+impl AsFd for PidFd {
+    #[inline]
+    fn as_fd(&self) -> BorrowedFd<'_> {
+        self.fd.as_fd()
+    }
+}
+
+#[cfg(linux)]
+impl Debug for PidFd {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        let Self {
+            pid,
+            start_time,
+            fd,
+        } = self;
+
+        f.debug_struct(stringify!(PidFd))
+            .field(stringify!(pid), pid)
+            .field(stringify!(start_time), start_time)
+            .field(stringify!(fd), &fd.as_raw_fd())
+            .finish()
+    }
+}
+
+#[cfg(linux)]
+impl PartialEq for PidFd {
+    fn eq(&self, other: &Self) -> bool {
+        self.pid == other.pid && self.start_time == other.start_time
+    }
+}
+
+#[cfg(linux)]
+impl Eq for PidFd {}
+
+#[cfg(linux)]
+impl Hash for PidFd {
+    fn hash<H>(&self, state: &mut H)
+    where
+        H: Hasher,
+    {
+        let () = self.pid.hash(state);
+        let () = self.start_time.hash(state);
+    }
+}
 
 
 /// An enumeration identifying a process.
-#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
 pub enum Pid {
     /// The current process.
     Slf,
     /// The process identified by the provided ID.
     Pid(NonZeroU32),
+    /// The process referenced by the provided pidfd.
+    #[cfg(linux)]
+    #[cfg_attr(docsrs, doc(cfg(target_os = "linux")))]
+    PidFd(PidFd),
 }
 
 impl Pid {
+    /// Create a [`Pid`] referencing the process identified by the
+    /// provided pidfd.
+    #[cfg(linux)]
+    #[cfg_attr(docsrs, doc(cfg(target_os = "linux")))]
+    #[inline]
+    pub fn from_pidfd(fd: OwnedFd) -> Result<Self> {
+        PidFd::new(fd).map(Self::PidFd)
+    }
+
     /// Resolve this [`Pid`] into an actual number, if it is the symbolic
     /// [`Pid::Slf`] variant.
     pub(crate) fn resolve(&self) -> u32 {
         match self {
             Self::Slf => process::id(),
             Self::Pid(pid) => pid.get(),
+            #[cfg(linux)]
+            Self::PidFd(pidfd) => pidfd.pid().get(),
+        }
+    }
+
+    /// Check that the process is still alive, if it is referenced by
+    /// a pidfd.
+    ///
+    /// This check is meant to be performed after opening a file below
+    /// `/proc/<pid>/`, to ensure that the file belongs to the process
+    /// in question and not one that got assigned the same ID after the
+    /// original exited.
+    pub(crate) fn check_alive(&self) -> Result<()> {
+        match self {
+            Self::Slf | Self::Pid(..) => Ok(()),
+            #[cfg(linux)]
+            Self::PidFd(pidfd) => pidfd.check_alive(),
         }
     }
 }
@@ -30,10 +259,19 @@ impl Display for Pid {
         match self {
             Self::Slf => write!(f, "self"),
             Self::Pid(pid) => write!(f, "{pid}"),
+            #[cfg(linux)]
+            Self::PidFd(pidfd) => write!(f, "{}", pidfd.pid()),
         }
     }
 }
 
+#[cfg(linux)]
+impl From<PidFd> for Pid {
+    fn from(pidfd: PidFd) -> Self {
+        Self::PidFd(pidfd)
+    }
+}
+
 impl From<u32> for Pid {
     fn from(pid: u32) -> Self {
         NonZeroU32::new(pid).map(Pid::Pid).unwrap_or(Pid::Slf)
@@ -45,7 +283,13 @@ impl From<u32> for Pid {
 mod tests {
     use super::*;
 
+    #[cfg(linux)]
+    use std::collections::hash_map::DefaultHasher;
     use std::fs::read_link;
+    #[cfg(linux)]
+    use std::fs::File;
+    #[cfg(linux)]
+    use std::os::fd::FromRawFd as _;
 
 
     fn resolve_pid() -> u32 {
@@ -70,4 +314,68 @@ mod tests {
         let pid = Pid::Slf.resolve();
         assert_eq!(pid, resolve_pid());
     }
+
+    /// Check that we can wrap a pidfd and resolve it to the process'
+    /// ID.
+    #[cfg(linux)]
+    #[test]
+    fn pidfd_resolution() {
+        let fd = unsafe { libc::syscall(libc::SYS_pidfd_open, process::id(), 0) };
+        if fd < 0 {
+            // pidfds may not be supported by the kernel we are running on.
+            return
+        }
+        let fd = unsafe { OwnedFd::from_raw_fd(fd as _) };
+        let pid = Pid::from_pidfd(fd).unwrap();
+        assert_eq!(pid.resolve(), resolve_pid());
+        assert_eq!(pid.to_string(), resolve_pid().to_string());
+        let () = pid.check_alive().unwrap();
+        assert_ne!(format!("{pid:?}"), "");
+    }
+
+    /// Check that pidfds compare equal only if they reference the
+    /// same process.
+    #[cfg(linux)]
+    #[test]
+    fn pidfd_identity() {
+        let open = || {
+            let fd = unsafe { libc::syscall(libc::SYS_pidfd_open, process::id(), 0) };
+            (fd >= 0).then(|| unsafe { OwnedFd::from_raw_fd(fd as _) })
+        };
+
+        let (fd1, fd2) = match (open(), open()) {
+            (Some(fd1), Some(fd2)) => (fd1, fd2),
+            // pidfds may not be supported by the kernel we are running on.
+            _ => return,
+        };
+        let pidfd1 = PidFd::new(fd1).unwrap();
+        let pidfd2 = PidFd::new(fd2).unwrap();
+        assert_ne!(pidfd1.as_fd().as_raw_fd(), pidfd2.as_fd().as_raw_fd());
+        assert_eq!(pidfd1, pidfd2);
+
+        let hash = |pidfd: &PidFd| {
+            let mut hasher = DefaultHasher::new();
+            let () = pidfd.hash(&mut hasher);
+            hasher.finish()
+        };
+        assert_eq!(hash(&pidfd1), hash(&pidfd2));
+
+        // A process with the same ID but a different start time, as
+        // would be the case after ID reuse, is a different one.
+        let reused = PidFd {
+            start_time: pidfd1.start_time + 1,
+            ..pidfd1.clone()
+        };
+        assert_eq!(reused.pid(), pidfd1.pid());
+        assert_ne!(reused, pidfd1);
+    }
+
+    /// Make sure that we fail to wrap a file descriptor that is not a
+    /// pidfd.
+    #[cfg(linux)]
+    #[test]
+    fn non_pidfd_wrapping() {
+        let file = File::open("/proc/self/maps").unwrap();
+        let _err = PidFd::new(OwnedFd::from(file)).unwrap_err();
+    }
 }
@@ -0,0 +1,168 @@
+use std::fmt::Debug;
+use std::fmt::Formatter;
+use std::fmt::Result as FmtResult;
+use std::io;
+use std::sync::mpsc::channel;
+use std::sync::mpsc::Sender;
+use std::sync::Arc;
+use std::thread::Builder;
+use std::thread::JoinHandle;
+
+use crate::Error;
+use crate::Result;
+
+
+/// A job to execute on a worker thread, provided with the worker's
+/// state.
+pub(crate) type Job<S> = Box<dyn FnOnce(&S) + Send>;
+
+
+struct Worker<S> {
+    /// The channel over which jobs are sent to the worker.
+    sender: Option<Sender<Job<S>>>,
+    /// The handle of the worker thread.
+    handle: Option<JoinHandle<()>>,
+}
+
+
+/// A fixed size pool of worker threads.
+///
+/// Each worker thread owns some state, constructed on the thread
+/// itself and, hence, not required to be [`Send`]. Jobs are explicitly
+/// assigned to workers, allowing for affinity between jobs and the
+/// state they work on.
+pub(crate) struct WorkerPool<S> {
+    workers: Box<[Worker<S>]>,
+}
+
+impl<S> WorkerPool<S>
+where
+    S: 'static,
+{
+    /// Create a new pool of `count` worker threads, each constructing
+    /// its state using `init`.
+    pub(crate) fn new<F>(count: usize, init: F) -> Result<Self>
+    where
+        F: Fn() -> S + Send + Sync + 'static,
+    {
+        let init = Arc::new(init);
+        let workers = (0..count)
+            .map(|idx| {
+                let (sender, receiver) = channel::<Job<S>>();
+                let init = Arc::clone(&init);
+                let handle = Builder::new()
+                    .name(format!("blazesym-worker-{idx}"))
+                    .spawn(move || {
+                        let state = init();
+                        // The loop terminates once the pool is dropped
+                        // and, with it, the sending end of the channel.
+                        let () = receiver.into_iter().for_each(|job| job(&state));
+                    })?;
+
+                Ok(Worker {
+                    sender: Some(sender),
+                    handle: Some(handle),
+                })
+            })
+            .collect::<Result<Box<[_]>>>()?;
+
+        Ok(Self { workers })
+    }
+
+    /// Retrieve the number of workers in the pool.
+    #[inline]
+    pub(crate) fn len(&self) -> usize {
+        self.workers.len()
+    }
+
+    /// Execute `job` on the worker with index `idx`.
+    pub(crate) fn execute(&self, idx: usize, job: Job<S>) -> Result<()> {
+        // SANITY: The sender is only ever removed on drop.
+        let sender = self.workers[idx].sender.as_ref().unwrap();
+        sender.send(job).map_err(|_err| {
+            Error::from(io::Error::new(
+                io::ErrorKind::BrokenPipe,
+                format!("worker thread {idx} terminated unexpectedly"),
+            ))
+        })
+    }
+}
+
+impl<S> Debug for WorkerPool<S> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        f.debug_struct("WorkerPool")
+            .field("workers", &self.workers.len())
+            .finish()
+    }
+}
+
+impl<S> Drop for WorkerPool<S> {
+    fn drop(&mut self) {
+        let () = self.workers.iter_mut().for_each(|worker| {
+            let _sender = worker.sender.take();
+        });
+        let () = self.workers.iter_mut().for_each(|worker| {
+            if let Some(handle) = worker.handle.take() {
+                // A worker panic has already been reported to the job
+                // submitter by means of a terminated channel.
+                let _result = handle.join();
+            }
+        });
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::cell::Cell;
+
+
+    /// Check that jobs are executed on the designated workers, with
+    /// access to their state.
+    #[test]
+    fn job_execution() {
+        let pool = WorkerPool::new(2, || Cell::new(0usize)).unwrap();
+        assert_eq!(pool.len(), 2);
+        assert_ne!(format!("{pool:?}"), "");
+
+        let (sender, receiver) = channel();
+        for idx in [0, 1, 1, 0, 1] {
+            let sender = sender.clone();
+            let () = pool
+                .execute(
+                    idx,
+                    Box::new(move |count: &Cell<usize>| {
+                        let () = count.set(count.get() + 1);
+                        let () = sender.send((idx, count.get())).unwrap();
+                    }),
+                )
+                .unwrap();
+        }
+        let () = drop(sender);
+
+        let mut results = receiver.into_iter().collect::<Vec<_>>();
+        let () = results.sort();
+        assert_eq!(results, vec![(0, 1), (0, 2), (1, 1), (1, 2), (1, 3)]);
+    }
+
+    /// Check that we report an error when submitting a job to a worker
+    /// that terminated.
+    #[test]
+    fn terminated_worker() {
+        let pool = WorkerPool::new(1, || ()).unwrap();
+        let () = pool
+            .execute(0, Box::new(|()| panic!("induced panic")))
+            .unwrap();
+
+        // Submission may still succeed while the worker is winding
+        // down, but eventually it has to fail.
+        let err = loop {
+            if let Err(err) = pool.execute(0, Box::new(|()| ())) {
+                break err
+            }
+        };
+        assert!(format!("{err}").contains("terminated"), "{err}");
+    }
+}
@@ -0,0 +1,107 @@
+use crate::symbolize::fmt;
+use crate::symbolize::source::Process;
+use crate::symbolize::source::Source;
+use crate::symbolize::AddrKind;
+use crate::symbolize::Input;
+use crate::symbolize::Symbolizer;
+use crate::Addr;
+use crate::Pid;
+use crate::Result;
+
+
+/// Capture and symbolize the call stack of the calling thread.
+///
+/// One rendered string is returned per captured stack frame, with the
+/// most recent frame first. Inlined functions are reported on
+/// additional lines of the string belonging to the frame they are
+/// contained in. Up to 64 frames are captured.
+///
+/// This function is meant for quick diagnostics. Users requiring more
+/// control should use a [`Symbolizer`] directly.
+#[cfg(all(target_os = "linux", target_env = "gnu"))]
+#[cfg_attr(docsrs, doc(cfg(all(target_os = "linux", target_env = "gnu"))))]
+pub fn symbolize_current_backtrace() -> Result<Vec<String>> {
+    use std::mem::size_of;
+    use std::ptr;
+
+    const MAX_CNT: usize = 64;
+
+    let () = assert_eq!(size_of::<*mut libc::c_void>(), size_of::<Addr>());
+
+    let mut bt_buf = [ptr::null_mut::<libc::c_void>(); MAX_CNT];
+    // SAFETY: `bt_buf` is a valid buffer of `MAX_CNT` elements.
+    let bt_cnt = unsafe { libc::backtrace(bt_buf.as_mut_ptr(), MAX_CNT as _) };
+    let bt_cnt = usize::try_from(bt_cnt).unwrap_or(0).min(MAX_CNT);
+
+    // All but the top most address are return addresses, which we
+    // need to adjust to point into the call instruction.
+    let addrs = bt_buf[..bt_cnt]
+        .iter()
+        .enumerate()
+        .map(|(idx, addr)| {
+            let kind = if idx == 0 {
+                AddrKind::Ip
+            } else {
+                AddrKind::ReturnAddr
+            };
+            (*addr as Addr, kind)
+        })
+        .collect::<Vec<_>>();
+
+    let src = Source::Process(Process::new(Pid::Slf));
+    let symbolizer = Symbolizer::new();
+    let syms = symbolizer.symbolize_tagged(&src, Input::AbsAddr(&addrs))?;
+    let rendered = addrs
+        .iter()
+        .zip(syms.iter())
+        .map(|((addr, _kind), sym)| fmt::frames(*addr, sym).to_string())
+        .collect();
+    Ok(rendered)
+}
+
+/// Symbolize an absolute address in the process with the given PID.
+///
+/// The result is rendered as a human readable string, with inlined
+/// functions reported on additional lines.
+///
+/// This function is meant for quick diagnostics. Users requiring more
+/// control should use a [`Symbolizer`] directly.
+pub fn symbolize_pid_addr(pid: Pid, addr: Addr) -> Result<String> {
+    let src = Source::Process(Process::new(pid));
+    let symbolizer = Symbolizer::new();
+    let sym = symbolizer.symbolize_single(&src, Input::AbsAddr(addr))?;
+    Ok(fmt::frames(addr, &sym).to_string())
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use test_log::test;
+
+
+    /// Check that we can symbolize an address in our own process.
+    #[test]
+    fn pid_addr_symbolization() {
+        let addr = symbolize_pid_addr as *const () as Addr;
+        let rendered = symbolize_pid_addr(Pid::Slf, addr).unwrap();
+        assert!(rendered.contains("symbolize_pid_addr"), "{rendered}");
+    }
+
+    /// Check that we can symbolize the current backtrace.
+    #[cfg(all(target_os = "linux", target_env = "gnu"))]
+    #[test]
+    fn current_backtrace_symbolization() {
+        #[inline(never)]
+        fn capture() -> Vec<String> {
+            symbolize_current_backtrace().unwrap()
+        }
+
+        let frames = capture();
+        assert!(
+            frames.iter().any(|frame| frame.contains("capture")),
+            "{frames:#?}"
+        );
+    }
+}
@@ -0,0 +1,202 @@
+use std::mem::size_of;
+
+use crate::symbolize::source::Kernel;
+use crate::symbolize::source::Process;
+use crate::symbolize::source::Source;
+use crate::util::ReadRaw as _;
+use crate::Addr;
+use crate::Error;
+use crate::IntoError as _;
+use crate::Pid;
+use crate::Result;
+
+
+/// The size of the fixed-size event header, preceding the stacks.
+const HEADER_SIZE: usize = 4 * size_of::<u32>();
+
+
+/// Read a stack of `depth` addresses from `data`, only retaining
+/// the first `size` bytes worth of addresses.
+fn read_stack(data: &mut &[u8], depth: usize, size: i32, name: &str) -> Result<Vec<Addr>> {
+    let stack = data
+        .read_slice(depth * size_of::<Addr>())
+        .ok_or_invalid_data(|| format!("failed to read {name} stack"))?;
+
+    // A negative size signals an error reported by `bpf_get_stack`;
+    // treat it as an empty stack.
+    let size = usize::try_from(size).unwrap_or(0);
+    if size > stack.len() || size % size_of::<Addr>() != 0 {
+        return Err(Error::with_invalid_data(format!(
+            "{name} stack size ({size}) is invalid for stack depth {depth}"
+        )))
+    }
+
+    let mut stack = &stack[..size];
+    let addrs = (0..size / size_of::<Addr>())
+        // SANITY: We checked above that enough data are available.
+        .map(|_| stack.read_u64().unwrap())
+        .collect();
+    Ok(addrs)
+}
+
+
+/// A stack trace event as commonly emitted by BPF programs.
+///
+/// Many BPF programs capture kernel and user space stacks using
+/// `bpf_get_stack` and send them to user space via a ring or perf
+/// buffer. This type decodes the following event layout (in native
+/// byte order):
+/// ```c
+/// struct stack_event {
+///     __u32 pid;
+///     __u32 tgid;
+///     __s32 kstack_sz;
+///     __s32 ustack_sz;
+///     __u64 kstack[MAX_STACK_DEPTH];
+///     __u64 ustack[MAX_STACK_DEPTH];
+/// };
+/// ```
+///
+/// `kstack_sz` and `ustack_sz` are the values returned by the
+/// corresponding `bpf_get_stack` invocations, i.e., the number of bytes
+/// written or a negative error. `MAX_STACK_DEPTH` is inferred from the
+/// size of the event.
+#[derive(Clone, Debug, PartialEq)]
+pub struct StackEvent {
+    /// The ID of the thread that the stacks were captured for.
+    pub pid: u32,
+    /// The ID of the process that the stacks were captured for.
+    pub tgid: u32,
+    /// The kernel stack.
+    pub kstack: Vec<Addr>,
+    /// The user space stack.
+    pub ustack: Vec<Addr>,
+    /// The struct is non-exhaustive and open to extension.
+    #[doc(hidden)]
+    pub _non_exhaustive: (),
+}
+
+impl StackEvent {
+    /// Decode a [`StackEvent`] from raw event data, as received from a
+    /// BPF ring or perf buffer.
+    pub fn from_bytes(mut data: &[u8]) -> Result<Self> {
+        let stacks_size = data.len().checked_sub(HEADER_SIZE).ok_or_invalid_data(|| {
+            format!(
+                "stack event is too small ({} bytes); expected at least {HEADER_SIZE}",
+                data.len()
+            )
+        })?;
+
+        if stacks_size % (2 * size_of::<Addr>()) != 0 {
+            return Err(Error::with_invalid_data(format!(
+                "stack event size ({}) does not correspond to two equally sized stacks",
+                data.len()
+            )))
+        }
+        let depth = stacks_size / (2 * size_of::<Addr>());
+
+        // SANITY: We checked above that the header is present.
+        let pid = data.read_u32().unwrap();
+        let tgid = data.read_u32().unwrap();
+        let kstack_sz = data.read_pod::<i32>().unwrap();
+        let ustack_sz = data.read_pod::<i32>().unwrap();
+
+        let kstack = read_stack(&mut data, depth, kstack_sz, "kernel")?;
+        let ustack = read_stack(&mut data, depth, ustack_sz, "user")?;
+
+        let event = Self {
+            pid,
+            tgid,
+            kstack,
+            ustack,
+            _non_exhaustive: (),
+        };
+        Ok(event)
+    }
+
+    /// Create a symbolization source for addresses of the kernel
+    /// stack.
+    #[inline]
+    pub fn kernel_src(&self) -> Source<'static> {
+        Source::Kernel(Kernel::default())
+    }
+
+    /// Create a symbolization source for addresses of the user space
+    /// stack.
+    #[inline]
+    pub fn user_src(&self) -> Source<'static> {
+        Source::Process(Process::new(Pid::from(self.tgid)))
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::mem::size_of_val;
+
+
+    /// Serialize a stack event into its raw representation.
+    fn serialize(pid: u32, tgid: u32, kstack: &[Addr], ustack: &[Addr], depth: usize) -> Vec<u8> {
+        let mut data = Vec::new();
+        let () = data.extend_from_slice(&pid.to_ne_bytes());
+        let () = data.extend_from_slice(&tgid.to_ne_bytes());
+        let kstack_sz = size_of_val(kstack) as i32;
+        let () = data.extend_from_slice(&kstack_sz.to_ne_bytes());
+        let ustack_sz = size_of_val(ustack) as i32;
+        let () = data.extend_from_slice(&ustack_sz.to_ne_bytes());
+
+        for stack in [kstack, ustack] {
+            for i in 0..depth {
+                let addr = stack.get(i).copied().unwrap_or(0);
+                let () = data.extend_from_slice(&addr.to_ne_bytes());
+            }
+        }
+        data
+    }
+
+
+    /// Check that we can decode a valid stack event.
+    #[test]
+    fn event_decoding() {
+        let data = serialize(42, 43, &[0xffffffff81000000, 0x1337], &[0x1000], 4);
+        let event = StackEvent::from_bytes(&data).unwrap();
+        assert_eq!(event.pid, 42);
+        assert_eq!(event.tgid, 43);
+        assert_eq!(event.kstack, vec![0xffffffff81000000, 0x1337]);
+        assert_eq!(event.ustack, vec![0x1000]);
+
+        assert!(matches!(event.kernel_src(), Source::Kernel(..)));
+        let Source::Process(process) = event.user_src() else {
+            panic!("unexpected source")
+        };
+        assert_eq!(process.pid, Pid::from(43));
+    }
+
+    /// Check that a negative stack size is treated as an empty stack.
+    #[test]
+    fn event_decoding_stack_error() {
+        let mut data = serialize(1, 1, &[], &[0x1000], 2);
+        let () = data[8..12].copy_from_slice(&(-14i32).to_ne_bytes());
+        let event = StackEvent::from_bytes(&data).unwrap();
+        assert_eq!(event.kstack, Vec::<Addr>::new());
+        assert_eq!(event.ustack, vec![0x1000]);
+    }
+
+    /// Check that we reject malformed stack events.
+    #[test]
+    fn invalid_event_decoding() {
+        let err = StackEvent::from_bytes(&[0; 15]).unwrap_err();
+        assert!(err.to_string().contains("too small"), "{err}");
+
+        let data = serialize(1, 1, &[], &[], 2);
+        let err = StackEvent::from_bytes(&data[..data.len() - 1]).unwrap_err();
+        assert!(err.to_string().contains("equally sized"), "{err}");
+
+        let mut data = serialize(1, 1, &[0x1], &[], 2);
+        let () = data[8..12].copy_from_slice(&24i32.to_ne_bytes());
+        let err = StackEvent::from_bytes(&data).unwrap_err();
+        assert!(err.to_string().contains("stack size"), "{err}");
+    }
+}
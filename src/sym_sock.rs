@@ -0,0 +1,362 @@
+//! A client for the symbol socket protocol, which allows language VMs
+//! to serve symbol queries for their JIT regions over a Unix domain
+//! socket.
+//!
+//! The protocol is line based. For each address to symbolize, the
+//! client sends a request line containing the address as a hex number
+//! without `0x` prefix:
+//! ```text
+//! ADDR
+//! ```
+//!
+//! The server responds with exactly one line. If the address is not
+//! known to it, this line is empty. Otherwise it has the format:
+//! ```text
+//! START<TAB>SIZE<TAB>NAME[<TAB>FILE[<TAB>LINE]]
+//! ```
+//! START and SIZE are hex numbers without `0x` (as they are in perf
+//! maps), NAME is the name of the function containing the address,
+//! and FILE and LINE optionally provide the source code location of
+//! the address, with LINE being a decimal number.
+
+use std::ffi::OsStr;
+use std::io::BufRead as _;
+use std::io::BufReader;
+use std::io::Read as _;
+use std::io::Write as _;
+use std::os::unix::net::UnixStream;
+use std::path::Path;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use crate::symbolize::CodeInfo;
+use crate::symbolize::FindSymOpts;
+use crate::symbolize::Reason;
+use crate::symbolize::ResolvedSym;
+use crate::symbolize::SrcLang;
+use crate::symbolize::Symbolize;
+use crate::Addr;
+use crate::Error;
+use crate::ErrorExt as _;
+use crate::IntoError as _;
+use crate::Result;
+
+
+/// The time we wait for a server to accept a request or respond to
+/// it, before giving up.
+const TIMEOUT: Duration = Duration::from_secs(1);
+/// The maximum length of a response line we accept, in bytes,
+/// including the terminating newline.
+const MAX_LINE: u64 = 64 * 1024;
+
+
+/// A symbol, as reported by a symbol socket server.
+#[derive(Debug, PartialEq)]
+pub(crate) struct SockSym {
+    /// The name of the function.
+    name: String,
+    /// The function's start address.
+    addr: Addr,
+    /// The size of the function.
+    size: usize,
+    /// The source file containing the address, if reported.
+    file: Option<PathBuf>,
+    /// The line number of the address, if reported.
+    line: Option<u32>,
+}
+
+impl Symbolize for SockSym {
+    fn find_sym(&self, addr: Addr, opts: &FindSymOpts) -> Result<Result<ResolvedSym<'_>, Reason>> {
+        let Self {
+            name,
+            addr: sym_addr,
+            size,
+            file,
+            line,
+        } = self;
+
+        // The symbol's range is reported by the server and so we can't
+        // trust it to not overflow.
+        let contained = sym_addr
+            .checked_add(*size as Addr)
+            .map(|end| (*sym_addr..end).contains(&addr))
+            .unwrap_or(false);
+        if !contained {
+            return Ok(Err(Reason::UnknownAddr))
+        }
+
+        let code_info = if opts.code_info() {
+            file.as_ref().map(|file| CodeInfo {
+                dir: None,
+                file: file.as_os_str().into(),
+                line: *line,
+                column: None,
+                _non_exhaustive: (),
+            })
+        } else {
+            None
+        };
+
+        let sym = ResolvedSym {
+            name,
+            addr: *sym_addr,
+            size: Some(*size),
+            lang: SrcLang::Unknown,
+            code_info,
+            inlined: Box::new([]),
+            inlined_truncated: false,
+            section: None,
+            module: None,
+        };
+        Ok(Ok(sym))
+    }
+}
+
+
+/// Parse a response line of a symbol socket server.
+fn parse_response(response: &str) -> Result<Option<SockSym>> {
+    let response = response.trim_end_matches(['\r', '\n']);
+    if response.is_empty() {
+        return Ok(None)
+    }
+
+    let mut parts = response.split('\t');
+    let addr = parts
+        .next()
+        .and_then(|addr| Addr::from_str_radix(addr, 16).ok())
+        .ok_or_invalid_data(|| {
+            format!("encountered malformed start address in symbol socket response: {response}")
+        })?;
+    let size = parts
+        .next()
+        .and_then(|size| usize::from_str_radix(size, 16).ok())
+        .ok_or_invalid_data(|| {
+            format!("encountered malformed size in symbol socket response: {response}")
+        })?;
+    let name = parts
+        .next()
+        .filter(|name| !name.is_empty())
+        .ok_or_invalid_data(|| {
+            format!("encountered malformed name in symbol socket response: {response}")
+        })?;
+    let file = parts
+        .next()
+        .filter(|file| !file.is_empty())
+        .map(|file| PathBuf::from(OsStr::new(file)));
+    let line = parts
+        .next()
+        .map(|line| line.parse::<u32>())
+        .transpose()
+        .map_err(|err| {
+            Error::with_invalid_data(format!(
+                "encountered malformed line number in symbol socket response: {response}: {err}"
+            ))
+        })?;
+
+    let sym = SockSym {
+        name: name.to_string(),
+        addr,
+        size,
+        file,
+        line,
+    };
+    Ok(Some(sym))
+}
+
+
+/// A connection to a symbol socket server.
+#[derive(Debug)]
+pub(crate) struct SymSock {
+    /// The path to the socket.
+    path: PathBuf,
+    /// The connected stream, wrapped for line based reading.
+    stream: BufReader<UnixStream>,
+}
+
+impl SymSock {
+    /// Connect to the symbol socket server listening at `path`.
+    pub(crate) fn connect(path: &Path) -> Result<Self> {
+        let stream = UnixStream::connect(path)
+            .with_context(|| format!("failed to connect to symbol socket `{}`", path.display()))?;
+        let () = stream.set_read_timeout(Some(TIMEOUT))?;
+        let () = stream.set_write_timeout(Some(TIMEOUT))?;
+
+        let slf = Self {
+            path: path.to_path_buf(),
+            stream: BufReader::new(stream),
+        };
+        Ok(slf)
+    }
+
+    /// Ask the server for the symbol containing `addr`.
+    pub(crate) fn query(&mut self, addr: Addr) -> Result<Option<SockSym>> {
+        let () = self
+            .stream
+            .get_ref()
+            .write_all(format!("{addr:x}\n").as_bytes())
+            .with_context(|| {
+                format!(
+                    "failed to send request to symbol socket `{}`",
+                    self.path.display()
+                )
+            })?;
+
+        // We bound the amount of data read, so that a misbehaving
+        // server can't make us allocate arbitrary amounts of memory by
+        // never sending a newline.
+        let mut line = String::new();
+        let count = (&mut self.stream)
+            .take(MAX_LINE)
+            .read_line(&mut line)
+            .with_context(|| {
+                format!(
+                    "failed to read response from symbol socket `{}`",
+                    self.path.display()
+                )
+            })?;
+        if count == 0 {
+            return Err(Error::with_invalid_data(format!(
+                "symbol socket `{}` closed the connection unexpectedly",
+                self.path.display()
+            )))
+        }
+        if !line.ends_with('\n') && count as u64 >= MAX_LINE {
+            return Err(Error::with_invalid_data(format!(
+                "response from symbol socket `{}` exceeds maximum length of {MAX_LINE} bytes",
+                self.path.display()
+            )))
+        }
+
+        parse_response(&line)
+            .with_context(|| format!("failed to parse response from `{}`", self.path.display()))
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::os::unix::net::UnixListener;
+    use std::thread;
+
+    use tempfile::tempdir;
+
+    use crate::ErrorKind;
+
+
+    /// Check that we can parse symbol socket responses.
+    #[test]
+    fn response_parsing() {
+        assert_eq!(parse_response("\n").unwrap(), None);
+
+        let sym = parse_response("1000\t20\tjit::foo bar\n").unwrap().unwrap();
+        assert_eq!(sym.name, "jit::foo bar");
+        assert_eq!(sym.addr, 0x1000);
+        assert_eq!(sym.size, 0x20);
+        assert_eq!(sym.file, None);
+        assert_eq!(sym.line, None);
+
+        let sym = parse_response("1000\t20\tfoo\tsrc/foo.js\t42")
+            .unwrap()
+            .unwrap();
+        assert_eq!(sym.file, Some(PathBuf::from("src/foo.js")));
+        assert_eq!(sym.line, Some(42));
+
+        let result = parse_response("xxxx\t20\tfoo");
+        assert!(result.is_err(), "{result:?}");
+        let result = parse_response("1000\t20");
+        assert!(result.is_err(), "{result:?}");
+        let result = parse_response("1000\t20\tfoo\tfoo.js\tline");
+        assert!(result.is_err(), "{result:?}");
+    }
+
+    /// Make sure that we do not choke on symbols with a range
+    /// overflowing the address space.
+    #[test]
+    fn overflowing_sym_range() {
+        let sym = parse_response("ffffffffffffff00\t1000\tfoo\n")
+            .unwrap()
+            .unwrap();
+        let result = sym
+            .find_sym(0xffffffffffffff10, &FindSymOpts::Basic)
+            .unwrap();
+        assert_eq!(result.unwrap_err(), Reason::UnknownAddr);
+    }
+
+    /// Check that we can query a symbol socket server.
+    #[test]
+    fn socket_query() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("sym.sock");
+        let listener = UnixListener::bind(&path).unwrap();
+
+        let server = thread::spawn(move || {
+            let (stream, _addr) = listener.accept().unwrap();
+            let mut reader = BufReader::new(&stream);
+            let mut line = String::new();
+            while reader.read_line(&mut line).unwrap() != 0 {
+                let addr = Addr::from_str_radix(line.trim_end(), 16).unwrap();
+                let response = if (0x1000..0x1020).contains(&addr) {
+                    "1000\t20\tjit_fn\tjit.js\t7\n"
+                } else {
+                    "\n"
+                };
+                let () = (&stream).write_all(response.as_bytes()).unwrap();
+                let () = line.clear();
+            }
+        });
+
+        let mut sock = SymSock::connect(&path).unwrap();
+        let sym = sock.query(0x1010).unwrap().unwrap();
+        assert_eq!(sym.name, "jit_fn");
+
+        let opts = FindSymOpts::CodeInfo;
+        let resolved = sym.find_sym(0x1010, &opts).unwrap().unwrap();
+        assert_eq!(resolved.addr, 0x1000);
+        assert_eq!(resolved.size, Some(0x20));
+        assert_eq!(resolved.code_info.unwrap().line, Some(7));
+        assert_eq!(
+            sym.find_sym(0x1020, &opts).unwrap().unwrap_err(),
+            Reason::UnknownAddr
+        );
+
+        assert_eq!(sock.query(0x2000).unwrap(), None);
+
+        let () = drop(sock);
+        let () = server.join().unwrap();
+    }
+
+    /// Check that we bail out on responses exceeding the maximum line
+    /// length.
+    #[test]
+    fn socket_query_overlong_response() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("sym.sock");
+        let listener = UnixListener::bind(&path).unwrap();
+
+        let server = thread::spawn(move || {
+            let (stream, _addr) = listener.accept().unwrap();
+            let mut reader = BufReader::new(&stream);
+            let mut line = String::new();
+            let _count = reader.read_line(&mut line).unwrap();
+            // Send more than the maximum line length without ever
+            // terminating the line. The client may hang up on us at
+            // any point, so ignore errors.
+            let data = vec![b'a'; 4096];
+            for _ in 0..=MAX_LINE / 4096 {
+                if (&stream).write_all(&data).is_err() {
+                    break
+                }
+            }
+        });
+
+        let mut sock = SymSock::connect(&path).unwrap();
+        let err = sock.query(0x1010).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidData);
+        assert!(format!("{err:#}").contains("maximum length"), "{err:#}");
+
+        let () = drop(sock);
+        let () = server.join().unwrap();
+    }
+}
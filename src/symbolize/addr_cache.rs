@@ -0,0 +1,160 @@
+//! Memoization of per-address symbolization results.
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+use std::rc::Weak;
+
+use crate::elf::ElfResolver;
+use crate::Addr;
+
+use super::Symbolized;
+
+
+/// The memoized symbolization results of a single resolver.
+#[derive(Debug)]
+struct Entry {
+    /// The resolver that the results belong to.
+    ///
+    /// We only hold on to a weak reference, so as to not keep the
+    /// resolver alive beyond its removal from the file cache. Its
+    /// allocation sticks around as long as we do, though, which means
+    /// that its address can't be reused for a different resolver.
+    resolver: Weak<ElfResolver>,
+    /// The memoized results, keyed by address and whether only the
+    /// symbol name was looked up.
+    syms: HashMap<(Addr, bool), Symbolized<'static>>,
+}
+
+
+/// A cache of per-address symbolization results, kept on a
+/// per-resolver basis.
+///
+/// Results are invalidated together with the resolver they were
+/// produced by, i.e., once the file cache drops it (because the file
+/// changed or was pruned).
+#[derive(Debug)]
+pub(crate) struct AddrCache {
+    /// The maximum number of results to keep per resolver.
+    capacity: usize,
+    /// The memoized results, keyed by resolver address.
+    entries: RefCell<HashMap<*const ElfResolver, Entry>>,
+}
+
+impl AddrCache {
+    /// Create a new `AddrCache` keeping up to `capacity` results per
+    /// resolver.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            entries: RefCell::new(HashMap::new()),
+        }
+    }
+
+    /// Look up the memoized result of symbolizing `addr` using
+    /// `resolver`.
+    pub fn get(
+        &self,
+        resolver: &Rc<ElfResolver>,
+        addr: Addr,
+        names_only: bool,
+    ) -> Option<Symbolized<'static>> {
+        let entries = self.entries.borrow();
+        let entry = entries.get(&Rc::as_ptr(resolver))?;
+        entry.syms.get(&(addr, names_only)).cloned()
+    }
+
+    /// Memoize the result of symbolizing `addr` using `resolver`.
+    pub fn insert(
+        &self,
+        resolver: &Rc<ElfResolver>,
+        addr: Addr,
+        names_only: bool,
+        symbolized: &Symbolized<'_>,
+    ) {
+        let mut entries = self.entries.borrow_mut();
+        let key = Rc::as_ptr(resolver);
+        if !entries.contains_key(&key) {
+            // A new resolver is a good indication that others may have
+            // been dropped, so take the chance to clean up.
+            let () = entries.retain(|_key, entry| entry.resolver.strong_count() > 0);
+        }
+
+        let entry = entries.entry(key).or_insert_with(|| Entry {
+            resolver: Rc::downgrade(resolver),
+            syms: HashMap::new(),
+        });
+
+        if entry.syms.len() >= self.capacity {
+            // We don't track access order, so just evict an arbitrary
+            // result. Hot addresses will quickly make it back in.
+            if let Some(evict) = entry.syms.keys().next().copied() {
+                let _symbolized = entry.syms.remove(&evict);
+            }
+        }
+        let _prev = entry
+            .syms
+            .insert((addr, names_only), symbolized.clone().into_owned());
+    }
+
+    /// Remove all results belonging to resolvers that have since been
+    /// dropped.
+    pub fn purge(&self) {
+        let () = self
+            .entries
+            .borrow_mut()
+            .retain(|_key, entry| entry.resolver.strong_count() > 0);
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::path::Path;
+
+    use test_log::test;
+
+    use crate::elf::ElfParser;
+    use crate::symbolize::Reason;
+
+
+    /// Check that results are memoized per resolver, subject to the
+    /// configured capacity, and dropped along with their resolver.
+    #[test]
+    fn memoization() {
+        let path = Path::new(&env!("CARGO_MANIFEST_DIR"))
+            .join("data")
+            .join("test-stable-addrs.bin");
+        let resolver = |path: &Path| {
+            let parser = Rc::new(ElfParser::open(path).unwrap());
+            Rc::new(ElfResolver::from_parser(parser, None, false, false).unwrap())
+        };
+        let resolver1 = resolver(&path);
+        let resolver2 = resolver(&path);
+
+        let cache = AddrCache::new(2);
+        let unknown = Symbolized::Unknown(Reason::UnknownAddr);
+        let missing = Symbolized::Unknown(Reason::MissingSyms);
+        let () = cache.insert(&resolver1, 0x1000, false, &unknown);
+        let () = cache.insert(&resolver2, 0x1000, false, &missing);
+        assert_eq!(cache.get(&resolver1, 0x1000, false), Some(unknown.clone()));
+        assert_eq!(cache.get(&resolver1, 0x1000, true), None);
+        assert_eq!(cache.get(&resolver2, 0x1000, false), Some(missing));
+
+        // Inserting beyond the capacity evicts an existing result.
+        let () = cache.insert(&resolver1, 0x2000, false, &unknown);
+        let () = cache.insert(&resolver1, 0x3000, false, &unknown);
+        let count = [0x1000, 0x2000, 0x3000]
+            .into_iter()
+            .filter(|addr| cache.get(&resolver1, *addr, false).is_some())
+            .count();
+        assert_eq!(count, 2);
+        assert!(cache.get(&resolver1, 0x3000, false).is_some());
+
+        let () = drop(resolver2);
+        let () = cache.purge();
+        assert_eq!(cache.entries.borrow().len(), 1);
+    }
+}
@@ -0,0 +1,464 @@
+//! Rendering of symbolization results in a set of canonical formats.
+//!
+//! The types in this module wrap a [`Symbolized`] object and implement
+//! [`Display`], producing one of the following representations:
+//! - [`Frames`]: the multi-line frame format used by **blazesym**'s own
+//!   tooling, with inlined functions on separate lines
+//! - [`Addr2Line`]: output compatible with that of `addr2line --functions
+//!   --inlines`
+//! - [`Json`]: a single line JSON object, suitable for emitting JSON lines
+//!
+//! None of the representations contain a trailing newline.
+//!
+//! ```no_run
+//! use blazesym::symbolize::fmt;
+//! use blazesym::symbolize::source::Elf;
+//! use blazesym::symbolize::source::Source;
+//! use blazesym::symbolize::Input;
+//! use blazesym::symbolize::Symbolizer;
+//!
+//! let src = Source::Elf(Elf::new("/usr/bin/sleep"));
+//! let symbolizer = Symbolizer::new();
+//! let addrs = [0x2000];
+//! let syms = symbolizer.symbolize(&src, Input::VirtOffset(&addrs)).unwrap();
+//! for (addr, sym) in addrs.into_iter().zip(syms.iter()) {
+//!     println!("{}", fmt::frames(addr, sym));
+//! }
+//! ```
+
+use std::fmt::Display;
+use std::fmt::Formatter;
+use std::fmt::Result as FmtResult;
+
+use crate::Addr;
+
+use super::CodeInfo;
+use super::InlinedFn;
+use super::Sym;
+use super::Symbolized;
+
+
+/// The width used for rendering addresses.
+const ADDR_WIDTH: usize = 16;
+
+
+/// Create a [`Frames`] object rendering the symbolization result of
+/// `input_addr`.
+#[inline]
+pub fn frames<'sym>(input_addr: Addr, symbolized: &'sym Symbolized<'sym>) -> Frames<'sym> {
+    Frames {
+        input_addr,
+        symbolized,
+    }
+}
+
+/// Create an [`Addr2Line`] object rendering the provided symbolization
+/// result.
+#[inline]
+pub fn addr2line<'sym>(symbolized: &'sym Symbolized<'sym>) -> Addr2Line<'sym> {
+    Addr2Line { symbolized }
+}
+
+/// Create a [`Json`] object rendering the symbolization result of
+/// `input_addr`.
+#[inline]
+pub fn json<'sym>(input_addr: Addr, symbolized: &'sym Symbolized<'sym>) -> Json<'sym> {
+    Json {
+        input_addr,
+        symbolized,
+    }
+}
+
+
+/// Write the source code location described by `code_info` in the form
+/// `<path>[:<line>[:<column>]]`.
+fn write_location(f: &mut Formatter<'_>, code_info: &CodeInfo<'_>) -> FmtResult {
+    let path = code_info.to_path();
+    let path = path.display();
+
+    match (code_info.line, code_info.column) {
+        (Some(line), Some(col)) => write!(f, "{path}:{line}:{col}"),
+        (Some(line), None) => write!(f, "{path}:{line}"),
+        (None, _) => write!(f, "{path}"),
+    }
+}
+
+
+/// A symbolization result rendered as a set of frames.
+///
+/// The first line contains the input address, the symbol name, its
+/// address and the offset of the input address from it, followed by
+/// source code location information, if available. Each inlined
+/// function is reported on an additional line, in the order in which
+/// their calls are nested:
+/// ```text
+/// 0x00000000201234: outer @ 0x201200+0x34 src/lib.c:10:5
+///                   inner src/lib.c:42 [inlined]
+/// ```
+#[derive(Clone, Copy, Debug)]
+pub struct Frames<'sym> {
+    input_addr: Addr,
+    symbolized: &'sym Symbolized<'sym>,
+}
+
+impl Display for Frames<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        let input_addr = self.input_addr;
+
+        match self.symbolized {
+            Symbolized::Sym(sym) => {
+                let () = write!(
+                    f,
+                    "{input_addr:#0width$x}: {name} @ {addr:#x}+{offset:#x}",
+                    name = sym.name,
+                    addr = sym.addr,
+                    offset = sym.offset,
+                    width = ADDR_WIDTH,
+                )?;
+                if let Some(code_info) = &sym.code_info {
+                    let () = f.write_str(" ")?;
+                    let () = write_location(f, code_info)?;
+                }
+
+                for inlined in sym.inlined.iter() {
+                    let () = write!(f, "\n{:width$}  {}", " ", inlined.name, width = ADDR_WIDTH)?;
+                    if let Some(code_info) = &inlined.code_info {
+                        let () = f.write_str(" ")?;
+                        let () = write_location(f, code_info)?;
+                    }
+                    let () = f.write_str(" [inlined]")?;
+                }
+                Ok(())
+            }
+            Symbolized::Code(code_info) => {
+                let () = write!(
+                    f,
+                    "{input_addr:#0width$x}: <no-symbol> ",
+                    width = ADDR_WIDTH
+                )?;
+                write_location(f, code_info)
+            }
+            Symbolized::Unknown(reason) => {
+                write!(
+                    f,
+                    "{input_addr:#0width$x}: <no-symbol> ({reason})",
+                    width = ADDR_WIDTH
+                )
+            }
+        }
+    }
+}
+
+
+/// A symbolization result rendered the way `addr2line --functions
+/// --inlines` would.
+///
+/// Each frame is reported as two lines: the function name followed by
+/// `<path>:<line>`. Frames are reported innermost first, meaning that
+/// the functions inlined at the address precede the function they got
+/// inlined into. Unknown information is represented by `??` (names and
+/// paths) and `?` (line numbers), respectively.
+#[derive(Clone, Copy, Debug)]
+pub struct Addr2Line<'sym> {
+    symbolized: &'sym Symbolized<'sym>,
+}
+
+impl Addr2Line<'_> {
+    fn write_frame(
+        f: &mut Formatter<'_>,
+        name: Option<&str>,
+        code_info: Option<&CodeInfo<'_>>,
+    ) -> FmtResult {
+        let () = writeln!(f, "{}", name.unwrap_or("??"))?;
+        match code_info {
+            Some(code_info) => {
+                let path = code_info.to_path();
+                let path = path.display();
+                match code_info.line {
+                    Some(line) => write!(f, "{path}:{line}"),
+                    None => write!(f, "{path}:?"),
+                }
+            }
+            None => f.write_str("??:0"),
+        }
+    }
+}
+
+impl Display for Addr2Line<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        match self.symbolized {
+            Symbolized::Sym(Sym {
+                name,
+                code_info,
+                inlined,
+                ..
+            }) => {
+                for InlinedFn {
+                    name, code_info, ..
+                } in inlined.iter().rev()
+                {
+                    let () = Self::write_frame(f, Some(name), code_info.as_ref())?;
+                    let () = f.write_str("\n")?;
+                }
+                Self::write_frame(f, Some(name), code_info.as_ref())
+            }
+            Symbolized::Code(code_info) => Self::write_frame(f, None, Some(code_info)),
+            Symbolized::Unknown(..) => Self::write_frame(f, None, None),
+        }
+    }
+}
+
+
+/// Write `s` as a JSON string, including the enclosing quotes.
+fn write_json_str(f: &mut Formatter<'_>, s: &str) -> FmtResult {
+    let () = f.write_str("\"")?;
+    for c in s.chars() {
+        let () = match c {
+            '"' => f.write_str("\\\"")?,
+            '\\' => f.write_str("\\\\")?,
+            '\n' => f.write_str("\\n")?,
+            '\r' => f.write_str("\\r")?,
+            '\t' => f.write_str("\\t")?,
+            c if c.is_control() => write!(f, "\\u{:04x}", u32::from(c))?,
+            c => write!(f, "{c}")?,
+        };
+    }
+    f.write_str("\"")
+}
+
+/// Write `value` as a JSON value, or `null` if it is `None`.
+fn write_json_opt<T>(f: &mut Formatter<'_>, value: Option<T>) -> FmtResult
+where
+    T: Display,
+{
+    match value {
+        Some(value) => write!(f, "{value}"),
+        None => f.write_str("null"),
+    }
+}
+
+/// Write `code_info` as a JSON object.
+fn write_json_code_info(f: &mut Formatter<'_>, code_info: &CodeInfo<'_>) -> FmtResult {
+    let () = f.write_str("{\"dir\":")?;
+    match &code_info.dir {
+        Some(dir) => write_json_str(f, &dir.to_string_lossy())?,
+        None => f.write_str("null")?,
+    }
+    let () = f.write_str(",\"file\":")?;
+    let () = write_json_str(f, &code_info.file.to_string_lossy())?;
+    let () = f.write_str(",\"line\":")?;
+    let () = write_json_opt(f, code_info.line)?;
+    let () = f.write_str(",\"column\":")?;
+    let () = write_json_opt(f, code_info.column)?;
+    f.write_str("}")
+}
+
+/// Write `code_info` as a JSON object, or `null` if it is `None`.
+fn write_json_opt_code_info(f: &mut Formatter<'_>, code_info: Option<&CodeInfo<'_>>) -> FmtResult {
+    match code_info {
+        Some(code_info) => write_json_code_info(f, code_info),
+        None => f.write_str("null"),
+    }
+}
+
+
+/// A symbolization result rendered as a single line JSON object.
+///
+/// The object contains the input address as `addr`, along with one of
+/// the following members, depending on the result:
+/// - `sym`: the symbol the address got symbolized as, with the members `name`,
+///   `addr`, `offset`, `size`, `module`, `code_info`, and `inlined` (itself a
+///   list of objects with members `name` and `code_info`)
+/// - `code_info`: source code location information for an address not
+///   attributed to a symbol
+/// - `reason`: a human readable description of why the address could not be
+///   symbolized
+///
+/// Source code location information is represented as an object with
+/// members `dir`, `file`, `line`, and `column`. Member names are
+/// consistent with those used by the `serde` representation of the
+/// respective types. Paths that are not valid Unicode are converted
+/// lossily.
+#[derive(Clone, Copy, Debug)]
+pub struct Json<'sym> {
+    input_addr: Addr,
+    symbolized: &'sym Symbolized<'sym>,
+}
+
+impl Display for Json<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        let () = write!(f, "{{\"addr\":{}", self.input_addr)?;
+
+        match self.symbolized {
+            Symbolized::Sym(sym) => {
+                let () = f.write_str(",\"sym\":{\"name\":")?;
+                let () = write_json_str(f, &sym.name)?;
+                let () = write!(f, ",\"addr\":{},\"offset\":{}", sym.addr, sym.offset)?;
+                let () = f.write_str(",\"size\":")?;
+                let () = write_json_opt(f, sym.size)?;
+                let () = f.write_str(",\"module\":")?;
+                match &sym.module {
+                    Some(module) => write_json_str(f, &module.to_string_lossy())?,
+                    None => f.write_str("null")?,
+                }
+                let () = f.write_str(",\"code_info\":")?;
+                let () = write_json_opt_code_info(f, sym.code_info.as_ref())?;
+                let () = f.write_str(",\"inlined\":[")?;
+                for (idx, inlined) in sym.inlined.iter().enumerate() {
+                    if idx > 0 {
+                        let () = f.write_str(",")?;
+                    }
+                    let () = f.write_str("{\"name\":")?;
+                    let () = write_json_str(f, &inlined.name)?;
+                    let () = f.write_str(",\"code_info\":")?;
+                    let () = write_json_opt_code_info(f, inlined.code_info.as_ref())?;
+                    let () = f.write_str("}")?;
+                }
+                let () = f.write_str("]}")?;
+            }
+            Symbolized::Code(code_info) => {
+                let () = f.write_str(",\"code_info\":")?;
+                let () = write_json_code_info(f, code_info)?;
+            }
+            Symbolized::Unknown(reason) => {
+                let () = f.write_str(",\"reason\":")?;
+                let () = write_json_str(f, &reason.to_string())?;
+            }
+        }
+        f.write_str("}")
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::borrow::Cow;
+    use std::ffi::OsStr;
+    use std::path::Path;
+
+    use test_log::test;
+
+    use crate::symbolize::Reason;
+
+
+    /// Create a `Sym` with an inlined function chain for testing
+    /// purposes.
+    fn test_sym() -> Symbolized<'static> {
+        let code_info = |file, line| CodeInfo {
+            dir: Some(Cow::Borrowed(Path::new("/src"))),
+            file: Cow::Borrowed(OsStr::new(file)),
+            line: Some(line),
+            column: None,
+            _non_exhaustive: (),
+        };
+
+        Symbolized::Sym(Sym {
+            name: Cow::Borrowed("outer"),
+            addr: 0x1000,
+            offset: 0x37,
+            size: Some(0x100),
+            code_info: Some(code_info("outer.c", 10)),
+            inlined: Box::new([
+                InlinedFn {
+                    name: Cow::Borrowed("middle"),
+                    code_info: Some(code_info("middle.h", 20)),
+                    _non_exhaustive: (),
+                },
+                InlinedFn {
+                    name: Cow::Borrowed("inner \"fn\""),
+                    code_info: None,
+                    _non_exhaustive: (),
+                },
+            ]),
+            inlined_truncated: false,
+            section: None,
+            perm: None,
+            addr_adjust: 0,
+            module: None,
+            module_base: None,
+            file_offset: None,
+            _non_exhaustive: (),
+        })
+    }
+
+    /// Check that we render unknown symbols as expected.
+    #[test]
+    fn unknown_rendering() {
+        let symbolized = Symbolized::Unknown(Reason::UnknownAddr);
+        assert_eq!(
+            frames(0x1337, &symbolized).to_string(),
+            "0x00000000001337: <no-symbol> (address not found in symbolization source)"
+        );
+        assert_eq!(addr2line(&symbolized).to_string(), "??\n??:0");
+        assert_eq!(
+            json(0x1337, &symbolized).to_string(),
+            r#"{"addr":4919,"reason":"address not found in symbolization source"}"#
+        );
+    }
+
+    /// Check that we render source code information of addresses not
+    /// attributed to a symbol as expected.
+    #[test]
+    fn code_rendering() {
+        let code_info = CodeInfo {
+            dir: None,
+            file: Cow::Borrowed(OsStr::new("test.c")),
+            line: Some(42),
+            column: None,
+            _non_exhaustive: (),
+        };
+        let symbolized = Symbolized::Code(code_info);
+        assert_eq!(
+            frames(0x1337, &symbolized).to_string(),
+            "0x00000000001337: <no-symbol> test.c:42"
+        );
+        assert_eq!(addr2line(&symbolized).to_string(), "??\ntest.c:42");
+        assert_eq!(
+            json(0x1337, &symbolized).to_string(),
+            r#"{"addr":4919,"code_info":{"dir":null,"file":"test.c","line":42,"column":null}}"#
+        );
+    }
+
+    /// Check that we render symbols with inlined functions as
+    /// expected in all formats.
+    #[test]
+    fn sym_rendering() {
+        let symbolized = test_sym();
+        assert_eq!(
+            frames(0x1037, &symbolized).to_string(),
+            "\
+0x00000000001037: outer @ 0x1000+0x37 /src/outer.c:10
+                  middle /src/middle.h:20 [inlined]
+                  inner \"fn\" [inlined]"
+        );
+        assert_eq!(
+            addr2line(&symbolized).to_string(),
+            "inner \"fn\"\n??:0\nmiddle\n/src/middle.h:20\nouter\n/src/outer.c:10"
+        );
+
+        let json = json(0x1037, &symbolized).to_string();
+        assert_eq!(
+            json,
+            concat!(
+                r#"{"addr":4151,"sym":{"name":"outer","addr":4096,"offset":55,"size":256,"#,
+                r#""module":null,"code_info":{"dir":"/src","file":"outer.c","line":10,"column":null},"#,
+                r#""inlined":[{"name":"middle","code_info":{"dir":"/src","file":"middle.h","#,
+                r#""line":20,"column":null}},{"name":"inner \"fn\"","code_info":null}]}}"#
+            )
+        );
+        assert!(!json.contains('\n'));
+    }
+
+    /// Check that we properly escape strings in JSON output.
+    #[test]
+    fn json_escaping() {
+        let mut symbolized = test_sym();
+        if let Symbolized::Sym(sym) = &mut symbolized {
+            sym.name = Cow::Borrowed("a\\b\n\u{1}");
+        }
+        let json = json(0, &symbolized).to_string();
+        assert!(json.contains(r#""name":"a\\b\n\u0001""#), "{json}");
+    }
+}
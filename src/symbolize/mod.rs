@@ -90,37 +90,68 @@
 //! [`ApkMemberInfo`]). Please refer to the
 //! [`gsym-in-apk`](https://github.com/libbpf/blazesym/blob/main/examples/gsym-in-apk)
 //! example, which illustrates the basic workflow.
+//!
+//! ### Shared Memory
+//! Code mapped from shared memory or hugetlbfs (as used by some just-in-time
+//! compilers or DPDK-style applications) typically carries no symbol
+//! information that **blazesym** could interpret. Addresses in such mappings
+//! are reported as unsupported, unless a shared memory dispatcher is installed
+//! (refer to [`SharedMemInfo`] and
+//! [`Builder::set_shared_mem_dispatcher`]), which can provide a resolver
+//! backed by user-provided symbol data.
+//!
+//! ### Output
+//! Symbolization results can be rendered in a set of canonical formats,
+//! including `addr2line` compatible text and JSON lines, by means of
+//! the types in the [`fmt`] module.
 
+mod addr_cache;
 pub mod cache;
+pub mod fmt;
 pub mod source;
 mod symbolizer;
 
 use std::borrow::Cow;
+use std::collections::HashMap;
 use std::ffi::OsStr;
 use std::fmt::Debug;
 use std::fmt::Display;
 use std::fmt::Formatter;
 use std::fmt::Result as FmtResult;
+use std::ops::Range;
 use std::path::Path;
+use std::path::PathBuf;
 use std::str;
+use std::time::Duration;
 
 cfg_apk! {
     pub use symbolizer::ApkDispatch;
     pub use symbolizer::ApkMemberInfo;
 }
+pub(crate) use symbolizer::maybe_demangle;
 pub use symbolizer::Builder;
 pub use symbolizer::ProcessDispatch;
 pub use symbolizer::ProcessMemberInfo;
+pub use symbolizer::SharedMemDispatch;
+pub use symbolizer::SharedMemInfo;
 pub use symbolizer::Symbolizer;
+pub use symbolizer::UnknownAddrInfo;
+pub use symbolizer::UnknownAddrResolver;
 
 // Strictly speaking these types are applicable to the entire crate, but right
 // now they are only used as part of the symbolization APIs, so we re-export
 // them through this module only.
+pub use crate::file_cache::CacheKey;
 pub use crate::maps::EntryPath as ProcessMemberPath;
 pub use crate::maps::PathName as ProcessMemberType;
+pub use crate::maps::Perm;
 
+use crate::elf::DEFAULT_DEBUG_DIRS;
 use crate::normalize;
+use crate::util;
 use crate::Addr;
+use crate::Error;
+use crate::ErrorKind;
 use crate::Result;
 
 
@@ -173,7 +204,9 @@ pub enum Input<T> {
     VirtOffset(T),
     /// A file offset.
     ///
-    /// A file offset is the linear offset of a symbol in a file.
+    /// A file offset is the linear offset of a symbol in a file. It can,
+    /// for example, be calculated from an absolute address and the
+    /// memory mapping covering it, as reported in perf `mmap` records.
     FileOffset(T),
 }
 
@@ -214,6 +247,78 @@ impl<T> Input<T> {
     }
 }
 
+/// The kind of an address to symbolize.
+///
+/// Addresses gathered from a stack walk are return addresses: they
+/// point to the instruction following a call. Attributing such an
+/// address to the instruction it references may yield the wrong line
+/// number or, if the call was the last instruction of a function, even
+/// the wrong function. Tagging addresses with their kind allows for
+/// adjusting return addresses to point into the call instruction
+/// instead.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub enum AddrKind {
+    /// The address is an instruction pointer, e.g., as captured as part
+    /// of a sample. It is symbolized as-is.
+    #[default]
+    Ip,
+    /// The address is a return address, e.g., as retrieved from a stack
+    /// walk. It is adjusted to point into the call instruction prior to
+    /// symbolization.
+    ReturnAddr,
+}
+
+impl AddrKind {
+    /// Retrieve the adjustment to subtract from an address of this kind
+    /// before symbolizing it.
+    #[inline]
+    pub(crate) fn adjustment(&self, addr: Addr) -> usize {
+        match self {
+            Self::Ip => 0,
+            // A return address of zero is bogus, but we should not
+            // wrap around.
+            Self::ReturnAddr => usize::from(addr > 0),
+        }
+    }
+}
+
+
+/// The policy determining which symbol information is consulted when
+/// symbolizing addresses in ELF files.
+///
+/// The sources of symbol information are considered in the following
+/// order, with later ones only being used if earlier ones are disabled
+/// or do not cover an address:
+/// 1. DWARF debug information, contained either in the ELF file itself or in a
+///    separate debug file referenced via its build ID or `.gnu_debuglink`
+///    section
+/// 2. the ELF symbol table (`.symtab`)
+/// 3. the dynamic symbol table (`.dynsym`)
+///
+/// Note that DWARF debug information is only ever consulted if the
+/// `dwarf` feature is enabled and the symbolization source asks for
+/// debug symbols (e.g., [`Elf::debug_syms`][source::Elf::debug_syms]).
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+#[non_exhaustive]
+pub enum ElfSymPolicy {
+    /// Prefer DWARF debug information, if available, and fall back to
+    /// ELF symbols otherwise.
+    #[default]
+    PreferDebugInfo,
+    /// Only consult ELF symbols, ignoring any debug information.
+    ///
+    /// This is the fastest option, but no source code location or
+    /// inlined function information will be reported.
+    SymtabOnly,
+    /// Require DWARF debug information.
+    ///
+    /// Addresses in ELF files without debug information are reported
+    /// as [`Reason::MissingSyms`], instead of being symbolized using
+    /// ELF symbols.
+    RequireDebugInfo,
+}
+
+
 #[cfg(test)]
 impl<T> Input<&[T]>
 where
@@ -231,11 +336,23 @@ where
 
 
 /// Source code location information for a symbol or inlined function.
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
 pub struct CodeInfo<'src> {
     /// The directory in which the source file resides.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, with = "crate::util::serde_path::opt")
+    )]
     pub dir: Option<Cow<'src, Path>>,
     /// The file that defines the symbol.
+    #[cfg_attr(
+        feature = "serde",
+        serde(
+            serialize_with = "crate::util::serde_path::serialize",
+            deserialize_with = "crate::util::serde_path::deserialize_os_str"
+        )
+    )]
     pub file: Cow<'src, OsStr>,
     /// The line number of the symbolized instruction in the source
     /// code.
@@ -249,6 +366,7 @@ pub struct CodeInfo<'src> {
     pub column: Option<u16>,
     /// The struct is non-exhaustive and open to extension.
     #[doc(hidden)]
+    #[cfg_attr(feature = "serde", serde(skip))]
     pub _non_exhaustive: (),
 }
 
@@ -277,11 +395,21 @@ impl CodeInfo<'_> {
             _non_exhaustive: (),
         }
     }
+
+    /// Convert all paths of this object into valid UTF-8.
+    fn into_lossy_paths(self) -> Self {
+        Self {
+            dir: self.dir.map(util::lossy_path),
+            file: util::lossy_os_str(self.file),
+            ..self
+        }
+    }
 }
 
 
 /// A type representing an inlined function.
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
 pub struct InlinedFn<'src> {
     /// The symbol name of the inlined function.
     pub name: Cow<'src, str>,
@@ -289,9 +417,30 @@ pub struct InlinedFn<'src> {
     pub code_info: Option<CodeInfo<'src>>,
     /// The struct is non-exhaustive and open to extension.
     #[doc(hidden)]
+    #[cfg_attr(feature = "serde", serde(skip))]
     pub _non_exhaustive: (),
 }
 
+impl InlinedFn<'_> {
+    /// Convert this object into one with all references converted into
+    /// guaranteed owned (i.e., heap allocated) members.
+    fn into_owned(self) -> InlinedFn<'static> {
+        InlinedFn {
+            name: Cow::Owned(self.name.into_owned()),
+            code_info: self.code_info.as_ref().map(CodeInfo::to_owned),
+            _non_exhaustive: (),
+        }
+    }
+
+    /// Convert all paths of this object into valid UTF-8.
+    fn into_lossy_paths(self) -> Self {
+        Self {
+            code_info: self.code_info.map(CodeInfo::into_lossy_paths),
+            ..self
+        }
+    }
+}
+
 
 /// The source code language from which a symbol originates.
 #[derive(Clone, Copy, Default, Debug, PartialEq)]
@@ -322,11 +471,24 @@ pub struct ResolvedSym<'src> {
     pub code_info: Option<CodeInfo<'src>>,
     /// Inlined function information.
     pub inlined: Box<[InlinedFn<'src>]>,
+    /// Whether `inlined` was truncated because the chain of inlined
+    /// functions exceeded a depth limit.
+    pub inlined_truncated: bool,
+    /// The name of the section the address resolved into, if known.
+    pub section: Option<&'src str>,
+    /// The name of the module the symbol belongs to, if reported by
+    /// the resolver.
+    ///
+    /// Resolvers for file based sources generally don't report it, as
+    /// the module is implied by the source. For kallsyms, it is the
+    /// name of the kernel module containing the symbol.
+    pub module: Option<&'src str>,
 }
 
 
 /// The result of address symbolization by [`Symbolizer`].
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
 pub struct Sym<'src> {
     /// The symbol name that an address belongs to.
     pub name: Cow<'src, str>,
@@ -362,11 +524,378 @@ pub struct Sym<'src> {
     /// contains an inlined call to `h`, the symbols will be reported in the
     /// order `f`, `g`, `h`.
     pub inlined: Box<[InlinedFn<'src>]>,
+    /// Whether `inlined` was truncated because the chain of inlined
+    /// functions covering the address exceeded a depth limit.
+    ///
+    /// In that case, only the outermost inlined functions are reported.
+    /// See [`Builder::set_max_inlined_depth`].
+    pub inlined_truncated: bool,
+    /// The name of the section (e.g., `.text` or `.plt`) that the
+    /// address falls into, if known.
+    ///
+    /// This information is currently only available for addresses
+    /// resolved by means of ELF files.
+    pub section: Option<Cow<'src, str>>,
+    /// The permissions of the memory mapping that the address belongs
+    /// to.
+    ///
+    /// This information is only available when symbolizing addresses
+    /// in the context of a live process.
+    pub perm: Option<Perm>,
+    /// The adjustment that was subtracted from the input address
+    /// before symbolizing it.
+    ///
+    /// This value is only ever non-zero for addresses tagged as
+    /// [`AddrKind::ReturnAddr`] and symbolized by means of
+    /// [`Symbolizer::symbolize_tagged`]. Both `offset` and `code_info`
+    /// refer to the adjusted address.
+    pub addr_adjust: usize,
+    /// The path to the module (i.e., binary or shared object) that the
+    /// address was resolved in, if known.
+    ///
+    /// This information is currently only available for addresses
+    /// resolved by means of ELF files, including in the context of a
+    /// process or core dump. For processes, the path is the one found
+    /// in its memory mappings. For kernel addresses resolved by means
+    /// of kallsyms and belonging to a kernel module, it is the name of
+    /// said module (e.g., `ext4`).
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, with = "crate::util::serde_path::opt")
+    )]
+    pub module: Option<Cow<'src, Path>>,
+    /// The address that the beginning of the module's file is mapped
+    /// at, if known.
+    ///
+    /// This information is available when symbolizing addresses of a
+    /// process or core dump, or when symbolizing absolute addresses in
+    /// an ELF file with a base address provided.
+    pub module_base: Option<Addr>,
+    /// The offset of the symbolized address in the module's file, if
+    /// known.
+    ///
+    /// Combined with `module`, this allows for cross-referencing with
+    /// the output of tools such as `objdump(1)`.
+    pub file_offset: Option<u64>,
     /// The struct is non-exhaustive and open to extension.
     #[doc(hidden)]
+    #[cfg_attr(feature = "serde", serde(skip))]
     pub _non_exhaustive: (),
 }
 
+impl Sym<'_> {
+    /// Convert this object into one with all references converted into
+    /// guaranteed owned (i.e., heap allocated) members.
+    fn into_owned(self) -> Sym<'static> {
+        Sym {
+            name: Cow::Owned(self.name.into_owned()),
+            addr: self.addr,
+            offset: self.offset,
+            size: self.size,
+            code_info: self.code_info.as_ref().map(CodeInfo::to_owned),
+            inlined: self
+                .inlined
+                .into_vec()
+                .into_iter()
+                .map(InlinedFn::into_owned)
+                .collect(),
+            inlined_truncated: self.inlined_truncated,
+            section: self.section.map(|section| Cow::Owned(section.into_owned())),
+            perm: self.perm,
+            addr_adjust: self.addr_adjust,
+            module: self.module.map(|module| Cow::Owned(module.into_owned())),
+            module_base: self.module_base,
+            file_offset: self.file_offset,
+            _non_exhaustive: (),
+        }
+    }
+
+    /// Convert all paths of this object into valid UTF-8.
+    fn into_lossy_paths(self) -> Self {
+        Self {
+            code_info: self.code_info.map(CodeInfo::into_lossy_paths),
+            inlined: self
+                .inlined
+                .into_vec()
+                .into_iter()
+                .map(InlinedFn::into_lossy_paths)
+                .collect(),
+            module: self.module.map(util::lossy_path),
+            ..self
+        }
+    }
+}
+
+
+/// The classification of an address as reported by
+/// [`Symbolizer::classify_addrs`].
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum AddrClass {
+    /// The address belongs to a file backed module and is likely to be
+    /// symbolizable.
+    Symbolizable,
+    /// The address is known to not be symbolizable, for the provided
+    /// reason.
+    Unknown(Reason),
+}
+
+
+/// Per-module statistics of a symbolization batch.
+///
+/// This type is used as part of [`Stats`].
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ModuleStats {
+    /// The number of addresses in the module that were successfully
+    /// symbolized.
+    pub resolved: usize,
+    /// The number of addresses in the module that could not be
+    /// symbolized.
+    pub unknown: usize,
+    /// The struct is non-exhaustive and open to extension.
+    #[doc(hidden)]
+    pub _non_exhaustive: (),
+}
+
+
+/// Statistics about a batch of addresses symbolized by means of
+/// [`Symbolizer::symbolize_with_stats`].
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Stats {
+    /// The number of addresses that were successfully symbolized.
+    pub resolved: usize,
+    /// The number of addresses that could not be symbolized, keyed by
+    /// the reason for the failure.
+    pub unknown: HashMap<Reason, usize>,
+    /// Tallies for each of the modules (e.g., ELF files) that input
+    /// addresses mapped to, keyed by the module's path.
+    ///
+    /// Addresses that could not be attributed to a file backed module
+    /// (e.g., because they are unmapped or belong to the kernel) are
+    /// not accounted for here.
+    pub modules: HashMap<PathBuf, ModuleStats>,
+    /// The time it took to symbolize the batch.
+    pub duration: Duration,
+    /// The struct is non-exhaustive and open to extension.
+    #[doc(hidden)]
+    pub _non_exhaustive: (),
+}
+
+impl Stats {
+    /// Account for a single symbolization result.
+    fn record(&mut self, symbolized: &Symbolized<'_>) {
+        match symbolized {
+            Symbolized::Sym(..) | Symbolized::Code(..) => self.resolved += 1,
+            Symbolized::Unknown(reason) => *self.unknown.entry(*reason).or_default() += 1,
+        }
+    }
+}
+
+impl ModuleStats {
+    /// Account for a single symbolization result.
+    fn record(&mut self, symbolized: &Symbolized<'_>) {
+        match symbolized {
+            Symbolized::Sym(..) | Symbolized::Code(..) => self.resolved += 1,
+            Symbolized::Unknown(..) => self.unknown += 1,
+        }
+    }
+}
+
+
+/// Information about what a symbolization source can provide, as
+/// reported by [`Symbolizer::probe`].
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SourceInfo {
+    /// Whether the source contains any function symbols.
+    ///
+    /// A source without symbols (e.g., a stripped binary without
+    /// accompanying debug information) can't be used for symbolizing
+    /// addresses.
+    pub syms: bool,
+    /// Whether the source contains source code location information
+    /// (e.g., DWARF line tables).
+    pub code_info: bool,
+    /// Whether the source contains information about inlined
+    /// functions.
+    pub inlined_fns: bool,
+    /// The build ID of the source, if any.
+    pub build_id: Option<Vec<u8>>,
+    /// The range of addresses covered by function symbols, if any.
+    pub addr_range: Option<Range<Addr>>,
+    /// The struct is non-exhaustive and open to extension.
+    #[doc(hidden)]
+    pub _non_exhaustive: (),
+}
+
+
+/// The kind of resolver used for symbolizing addresses in a process
+/// memory mapping, as reported by [`Symbolizer::process_mappings`].
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[non_exhaustive]
+pub enum ResolverKind {
+    /// A user provided resolver, as created by a process or shared
+    /// memory dispatcher.
+    ///
+    /// See [`Builder::set_process_dispatcher`] and
+    /// [`Builder::set_shared_mem_dispatcher`].
+    Custom,
+    /// A resolver consulting only ELF symbols.
+    Elf,
+    /// A resolver consulting DWARF debug information, in addition to
+    /// ELF symbols.
+    Dwarf,
+    /// The process' perf map.
+    PerfMap,
+    /// A resolver for the kernel provided vDSO or vsyscall pages.
+    Vdso,
+    /// Resolvers for the individual members of an APK.
+    ///
+    /// These are created lazily, based on the member an address
+    /// falls into.
+    #[cfg(feature = "apk")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "apk")))]
+    Apk,
+}
+
+
+/// Information about a memory mapping of a process and how addresses
+/// in it are being symbolized, as reported by
+/// [`Symbolizer::process_mappings`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct ProcessMapping {
+    /// The virtual address range covered by the mapping.
+    pub range: Range<Addr>,
+    /// The offset of the mapping in the file backing it.
+    pub offset: u64,
+    /// The path of the file backing the mapping or the name of the
+    /// kernel provided component (e.g., `[vdso]`), if any.
+    pub path: Option<PathBuf>,
+    /// The kind of resolver used for addresses in the mapping or, if
+    /// none is available, the reason why.
+    pub resolver: Result<ResolverKind, Reason>,
+    /// The struct is non-exhaustive and open to extension.
+    #[doc(hidden)]
+    pub _non_exhaustive: (),
+}
+
+
+/// The budget available to [`Symbolizer::warm_up`].
+///
+/// Modules are never warmed up partially. Hence, the budget is only
+/// checked before starting work on a module and it may be overrun by
+/// what is required for a single one.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct WarmUpBudget {
+    /// The maximum amount of time to spend.
+    pub time: Option<Duration>,
+    /// The maximum combined size of the files to load, in bytes.
+    ///
+    /// The size of a file serves as a rough approximation of the
+    /// memory required for the symbolization data derived from it.
+    /// Modules not backed by a single file (such as the kernel) are not
+    /// accounted for.
+    pub file_size: Option<u64>,
+    /// The struct is non-exhaustive and open to extension.
+    #[doc(hidden)]
+    pub _non_exhaustive: (),
+}
+
+
+/// The progress of a [`Symbolizer::warm_up`] operation, as reported
+/// after each module.
+#[derive(Debug)]
+pub struct WarmUpProgress<'p> {
+    /// The path of the module that was just processed, if it is backed
+    /// by a single file.
+    pub module: Option<&'p Path>,
+    /// The error encountered while warming up the module, if any.
+    pub error: Option<&'p Error>,
+    /// The number of modules processed so far, including this one.
+    pub done: usize,
+    /// The total number of modules to warm up.
+    pub total: usize,
+    /// The struct is non-exhaustive and open to extension.
+    #[doc(hidden)]
+    pub _non_exhaustive: (),
+}
+
+
+/// Information about the files kept open by a [`Symbolizer`], as
+/// reported by [`Symbolizer::fd_usage`].
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct FdUsage {
+    /// The number of files currently kept open.
+    pub open: usize,
+    /// The maximum number of files to keep open, if any, as set via
+    /// [`Builder::set_max_open_files`].
+    pub max_open: Option<usize>,
+    /// The number of times a file got closed to stay within budget.
+    pub closed: usize,
+    /// The number of times a previously closed file got reopened.
+    pub reopened: usize,
+    /// The struct is non-exhaustive and open to extension.
+    #[doc(hidden)]
+    pub _non_exhaustive: (),
+}
+
+
+/// Information about the files a [`Symbolizer`] caches data for, as
+/// reported by [`Symbolizer::cache_usage`].
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct CacheUsage {
+    /// The number of files data are currently cached for.
+    pub files: usize,
+    /// The maximum number of files to cache data for, if any, as set
+    /// via [`Builder::set_max_cached_files`].
+    pub max_files: Option<usize>,
+    /// The struct is non-exhaustive and open to extension.
+    #[doc(hidden)]
+    pub _non_exhaustive: (),
+}
+
+
+/// A description of where to find the files needed for symbolizing
+/// the output of a [`Normalizer`][crate::normalize::Normalizer], as
+/// used by [`Symbolizer::symbolize_normalized`].
+///
+/// Files are looked up by the build ID recorded during normalization
+/// first, falling back to the recorded path, if enabled.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SymbolStore {
+    /// Explicit mapping of build IDs to the files to use for them.
+    pub build_ids: HashMap<Vec<u8>, PathBuf>,
+    /// Directories containing files organized by build ID.
+    ///
+    /// Following convention, for a build ID `xxyyyy...` the file
+    /// `<dir>/.build-id/xx/yyyy....debug` is used, if present. By
+    /// default, the system's debug directories are searched.
+    pub build_id_dirs: Vec<PathBuf>,
+    /// Whether to fall back to the path recorded during normalization
+    /// if no file could be found by build ID.
+    ///
+    /// If a build ID was recorded, the file at the path is only used
+    /// if its build ID matches. This fallback is mostly useful when
+    /// symbolizing on the system the addresses were normalized on.
+    pub recorded_paths: bool,
+    /// Whether or not to consult debug symbols to satisfy the request
+    /// (if present).
+    pub debug_syms: bool,
+    /// The struct is non-exhaustive and open to extension.
+    #[doc(hidden)]
+    pub _non_exhaustive: (),
+}
+
+impl Default for SymbolStore {
+    fn default() -> Self {
+        Self {
+            build_ids: HashMap::new(),
+            build_id_dirs: DEFAULT_DEBUG_DIRS.iter().map(PathBuf::from).collect(),
+            recorded_paths: true,
+            debug_syms: true,
+            _non_exhaustive: (),
+        }
+    }
+}
+
 
 /// The reason why symbolization failed.
 ///
@@ -393,6 +922,19 @@ pub enum Reason {
     Unsupported,
     /// The address could not be found in the symbolization source.
     UnknownAddr,
+    /// The file containing symbolization data for the address could
+    /// not be found.
+    FileNotFound,
+    /// Permission to access the file containing symbolization data for
+    /// the address was denied.
+    PermissionDenied,
+    /// The address belongs to a thread's stack (`[stack]`).
+    Stack,
+    /// The address belongs to the process' heap (`[heap]`).
+    Heap,
+    /// The address belongs to a special mapping set up by the kernel
+    /// (e.g., `[vvar]`) that does not contain symbolizable code.
+    SpecialMapping,
 }
 
 impl Reason {
@@ -406,6 +948,38 @@ impl Reason {
             Self::MissingSyms => b"symbolization source has no or no relevant symbols\0",
             Self::Unsupported => b"address belongs to unsupported entity\0",
             Self::UnknownAddr => b"address not found in symbolization source\0",
+            Self::FileNotFound => b"symbolization source file not found\0",
+            Self::PermissionDenied => b"permission denied accessing symbolization source file\0",
+            Self::Stack => b"address belongs to the stack\0",
+            Self::Heap => b"address belongs to the heap\0",
+            Self::SpecialMapping => b"address belongs to a special mapping\0",
+        }
+    }
+
+    /// Determine the reason corresponding to an error encountered while
+    /// accessing the file backing an address, if any.
+    ///
+    /// Errors not captured by any reason should be reported to the
+    /// caller instead.
+    pub(crate) fn from_file_error(err: &Error) -> Option<Self> {
+        match err.kind() {
+            ErrorKind::NotFound => Some(Self::FileNotFound),
+            ErrorKind::PermissionDenied => Some(Self::PermissionDenied),
+            _ => None,
+        }
+    }
+
+    /// Determine the reason corresponding to an address in a proc maps
+    /// entry with the given component (e.g., `[stack]`) that we can't
+    /// symbolize.
+    pub(crate) fn from_component(component: &str) -> Self {
+        match component {
+            "[heap]" => Self::Heap,
+            // Kernels prior to 4.5 report thread stacks as
+            // `[stack:<tid>]`.
+            "[stack]" => Self::Stack,
+            _ if component.starts_with("[stack:") => Self::Stack,
+            _ => Self::SpecialMapping,
         }
     }
 }
@@ -435,10 +1009,19 @@ impl From<normalize::Reason> for Reason {
 /// An enumeration used as reporting vehicle for address symbolization.
 // We keep this enum as exhaustive because additions to it, should they occur,
 // are expected to be backwards-compatibility breaking.
+// The vast majority of symbolization results are expected to be
+// `Sym` objects, so boxing them would merely add an allocation.
+#[allow(clippy::large_enum_variant)]
 #[derive(Clone, Debug, PartialEq)]
 pub enum Symbolized<'src> {
     /// The input address was symbolized as the provided symbol.
     Sym(Sym<'src>),
+    /// The input address could not be attributed to a symbol, but
+    /// source code location information for it is available.
+    ///
+    /// This variant is only reported if source code information was
+    /// requested (see [`Builder::enable_code_info`]).
+    Code(CodeInfo<'src>),
     /// The input address was not found and could not be symbolized.
     ///
     /// The provided reason is a best guess, hinting at what ultimately
@@ -453,7 +1036,7 @@ impl<'src> Symbolized<'src> {
     pub fn as_sym(&self) -> Option<&Sym<'src>> {
         match self {
             Self::Sym(sym) => Some(sym),
-            Self::Unknown(..) => None,
+            Self::Code(..) | Self::Unknown(..) => None,
         }
     }
 
@@ -463,9 +1046,79 @@ impl<'src> Symbolized<'src> {
     pub fn into_sym(self) -> Option<Sym<'src>> {
         match self {
             Self::Sym(sym) => Some(sym),
+            Self::Code(..) | Self::Unknown(..) => None,
+        }
+    }
+
+    /// Retrieve the source code location information for the input
+    /// address, if any.
+    ///
+    /// Code information is available independent of whether the address
+    /// could be attributed to a symbol. For the [`Sym`][Self::Sym]
+    /// variant, this is the symbol's [`code_info`][Sym::code_info].
+    #[inline]
+    pub fn code_info(&self) -> Option<&CodeInfo<'src>> {
+        match self {
+            Self::Sym(sym) => sym.code_info.as_ref(),
+            Self::Code(code_info) => Some(code_info),
             Self::Unknown(..) => None,
         }
     }
+
+    /// Convert this object into one with all references converted into
+    /// guaranteed owned (i.e., heap allocated) members.
+    pub(crate) fn into_owned(self) -> Symbolized<'static> {
+        match self {
+            Self::Sym(sym) => Symbolized::Sym(sym.into_owned()),
+            Self::Code(code_info) => Symbolized::Code(code_info.to_owned()),
+            Self::Unknown(reason) => Symbolized::Unknown(reason),
+        }
+    }
+
+    /// Convert all paths of this object into valid UTF-8.
+    pub(crate) fn into_lossy_paths(self) -> Self {
+        match self {
+            Self::Sym(sym) => Self::Sym(sym.into_lossy_paths()),
+            Self::Code(code_info) => Self::Code(code_info.into_lossy_paths()),
+            Self::Unknown(reason) => Self::Unknown(reason),
+        }
+    }
+}
+
+
+/// A uniform record describing the symbolization of a single input
+/// address, as produced by [`Symbolizer::symbolize_frames`].
+///
+/// Contrary to [`Symbolized`], a `Frame` always retains the raw input
+/// as well as whatever could be learned about the module the address
+/// belongs to, even if symbolization ultimately failed. That makes it
+/// suitable for being stored or forwarded for later (re-)processing.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Frame<'src> {
+    /// The input address, as provided by the user.
+    pub addr: Addr,
+    /// The normalized address, i.e., the offset of the input address
+    /// in the file of `module`, if known.
+    pub norm_addr: Option<u64>,
+    /// The path to the module (e.g., binary or shared object) that the
+    /// address belongs to, if known.
+    pub module: Option<Cow<'src, Path>>,
+    /// The symbol the address was symbolized as, if symbolization was
+    /// successful.
+    pub sym: Option<Sym<'src>>,
+    /// Source code location information for the address, if it could
+    /// not be attributed to a symbol but such information is available.
+    ///
+    /// If `sym` is set, code information is reported as part of it.
+    pub code_info: Option<CodeInfo<'src>>,
+    /// The reason why symbolization failed, if it did.
+    ///
+    /// This member is set if and only if both `sym` and `code_info` are
+    /// `None`.
+    pub reason: Option<Reason>,
+    /// The struct is non-exhaustive and open to extension.
+    #[doc(hidden)]
+    pub _non_exhaustive: (),
 }
 
 
@@ -484,6 +1137,18 @@ where
 {
     /// Find the symbol corresponding to the given address.
     fn find_sym(&self, addr: Addr, opts: &FindSymOpts) -> Result<Result<ResolvedSym<'_>, Reason>>;
+
+    /// Find source code location information for the given address,
+    /// independent of any symbol covering it.
+    ///
+    /// This method is consulted for addresses for which
+    /// [`find_sym`][Symbolize::find_sym] did not find a symbol. The
+    /// default implementation reports no information.
+    #[inline]
+    fn find_code_info(&self, addr: Addr, opts: &FindSymOpts) -> Result<Option<CodeInfo<'_>>> {
+        let _ = (addr, opts);
+        Ok(None)
+    }
 }
 
 impl<S> AsSymbolize for S
@@ -524,6 +1189,9 @@ where
 mod tests {
     use super::*;
 
+    #[cfg(all(unix, feature = "serde"))]
+    use std::os::unix::ffi::OsStrExt as _;
+
 
     /// Exercise the `Debug` representation of various types.
     #[test]
@@ -553,6 +1221,13 @@ mod tests {
                 code_info: Some(code_info.clone()),
                 _non_exhaustive: (),
             }]),
+            inlined_truncated: false,
+            section: Some(Cow::Borrowed(".text")),
+            perm: Some(Perm::R | Perm::X),
+            addr_adjust: 0,
+            module: Some(Cow::Borrowed(Path::new("/usr/lib/libc.so.6"))),
+            module_base: Some(0x7f0000000000),
+            file_offset: Some(0x1000),
             _non_exhaustive: (),
         };
         assert_ne!(format!("{sym:?}"), "");
@@ -561,6 +1236,65 @@ mod tests {
         assert_ne!(format!("{symbolized:?}"), "");
     }
 
+    /// Check that a [`Sym`] survives a round trip through `serde`.
+    #[cfg(feature = "serde")]
+    #[test]
+    fn sym_serde_roundtrip() {
+        let code_info = CodeInfo {
+            dir: Some(Cow::Borrowed(Path::new("/tmp/some-dir"))),
+            file: Cow::Borrowed(OsStr::new("test.c")),
+            line: Some(1337),
+            column: Some(42),
+            _non_exhaustive: (),
+        };
+        let sym = Sym {
+            name: Cow::Borrowed("test"),
+            addr: 1337,
+            offset: 42,
+            size: Some(128),
+            code_info: Some(code_info.clone()),
+            inlined: Box::new([InlinedFn {
+                name: Cow::Borrowed("inlined_test"),
+                code_info: Some(code_info),
+                _non_exhaustive: (),
+            }]),
+            inlined_truncated: false,
+            section: Some(Cow::Borrowed(".text")),
+            perm: Some(Perm::R | Perm::X),
+            addr_adjust: 1,
+            module: Some(Cow::Borrowed(Path::new("/usr/lib/libc.so.6"))),
+            module_base: Some(0x7f0000000000),
+            file_offset: Some(0x1000),
+            _non_exhaustive: (),
+        };
+
+        let json = serde_json::to_string(&sym).unwrap();
+        let deserialized = serde_json::from_str::<Sym>(&json).unwrap();
+        assert_eq!(deserialized, sym);
+    }
+
+    /// Check that paths that are not valid UTF-8 are serialized in
+    /// both lossy and lossless form and survive a round trip.
+    #[cfg(all(unix, feature = "serde"))]
+    #[test]
+    fn code_info_serde_non_utf8() {
+        let code_info = CodeInfo {
+            dir: Some(Cow::Borrowed(Path::new(OsStr::from_bytes(b"/tmp/\xffdir")))),
+            file: Cow::Borrowed(OsStr::from_bytes(b"test.c")),
+            line: None,
+            column: None,
+            _non_exhaustive: (),
+        };
+
+        let json = serde_json::to_value(&code_info).unwrap();
+        assert_eq!(json["dir"]["lossy"], "/tmp/\u{fffd}dir");
+        assert_eq!(json["dir"]["bytes"], serde_json::json!(b"/tmp/\xffdir"));
+        assert_eq!(json["file"], "test.c");
+
+        let deserialized = serde_json::from_value::<CodeInfo>(json).unwrap();
+        assert_eq!(deserialized, code_info);
+    }
+
     /// Exercise the `Display` representation of various types.
     #[test]
     fn display_repr() {
@@ -609,6 +1343,23 @@ mod tests {
     fn symbolized_unknown_conversions() {
         let symbolized = Symbolized::Unknown(Reason::UnknownAddr);
         assert_eq!(symbolized.as_sym(), None);
+        assert_eq!(symbolized.code_info(), None);
+        assert_eq!(symbolized.into_sym(), None);
+    }
+
+    /// Test the `Symbolized` conversion methods for the `Code` variant.
+    #[test]
+    fn symbolized_code_conversions() {
+        let code_info = CodeInfo {
+            dir: None,
+            file: Cow::Borrowed(OsStr::new("test.c")),
+            line: Some(42),
+            column: None,
+            _non_exhaustive: (),
+        };
+        let symbolized = Symbolized::Code(code_info.clone());
+        assert_eq!(symbolized.as_sym(), None);
+        assert_eq!(symbolized.code_info(), Some(&code_info));
         assert_eq!(symbolized.into_sym(), None);
     }
 }
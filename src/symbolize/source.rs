@@ -1,13 +1,31 @@
 //! Definitions of supported symbolization sources.
 
 use std::cmp::min;
+use std::collections::HashMap;
+use std::ffi::OsStr;
+use std::ffi::OsString;
 use std::fmt::Debug;
 use std::fmt::Formatter;
 use std::fmt::Result as FmtResult;
+use std::fs;
+#[cfg(linux)]
+use std::os::fd::OwnedFd;
+use std::path::Path;
 use std::path::PathBuf;
+use std::sync::Arc;
 
+use crate::log;
+use crate::maps;
+use crate::maps::is_shared_mem;
+use crate::maps::MapsEntry;
+use crate::maps::PathName;
+use crate::normalize::buildid::read_elf_build_id_cached;
+use crate::vdso;
+use crate::Addr;
+use crate::ErrorExt as _;
 use crate::MaybeDefault;
 use crate::Pid;
+use crate::Result;
 
 #[cfg(doc)]
 use super::Symbolizer;
@@ -112,6 +130,118 @@ impl Debug for Breakpad {
 }
 
 
+/// The base address at which an ELF file is loaded.
+///
+/// This type is used in conjunction with the [`Elf::base_addr`] member.
+#[derive(Clone, Debug, PartialEq)]
+#[non_exhaustive]
+pub enum BaseAddr {
+    /// The base address (i.e., the load bias) of the ELF file.
+    ///
+    /// This is the difference between an absolute address and the
+    /// corresponding virtual offset in the file. For position
+    /// independent binaries (`ET_DYN`) it is typically the start
+    /// address of the file's first mapping. Non-relocatable binaries
+    /// (`ET_EXEC`) are always loaded at a bias of `0`.
+    Addr(Addr),
+    /// Derive the base address from a mapping of the ELF file, as
+    /// reported in `/proc/<pid>/maps`, for example.
+    Mapping {
+        /// The start address of the mapping.
+        addr: Addr,
+        /// The file offset that is mapped at `addr`.
+        file_offset: u64,
+    },
+    /// Derive the base address by looking up where the ELF file is
+    /// mapped in the process with the given PID.
+    ///
+    /// The process' memory mappings are searched for the file (as
+    /// identified by its device and inode number) and the lowest
+    /// mapping is used as the anchor, as with
+    /// [`BaseAddr::Mapping`].
+    Process(Pid),
+    /// Derive the base address from the runtime address of a symbol
+    /// contained in the ELF file.
+    ///
+    /// The symbol has to be unambiguous, i.e., all symbols with the
+    /// given name have to reside at the same address.
+    Symbol {
+        /// The name of the symbol.
+        name: String,
+        /// The address at which the symbol resides at runtime.
+        addr: Addr,
+    },
+}
+
+
+/// A core dump of a process.
+///
+/// This type is used in the [`Source::CoreDump`] variant.
+///
+/// The memory map of the process at the time of the dump is
+/// reconstructed from the `NT_FILE` note contained in the core file.
+/// Only absolute addresses are supported as input. Files mapped into
+/// the process are looked up at the paths recorded in the dump, with
+/// the exception of the main executable, for which [`exe`][Self::exe]
+/// is used.
+#[derive(Clone)]
+pub struct CoreDump {
+    /// The path to the core dump file.
+    pub path: PathBuf,
+    /// The path to the executable that the dumped process was running.
+    pub exe: PathBuf,
+    /// Whether or not to consult debug symbols to satisfy the request
+    /// (if present).
+    ///
+    /// On top of this runtime configuration, the crate needs to be
+    /// built with the `dwarf` feature to actually consult debug
+    /// symbols. If neither is satisfied, ELF symbols will be used.
+    pub debug_syms: bool,
+    /// The struct is non-exhaustive and open to extension.
+    #[doc(hidden)]
+    pub _non_exhaustive: (),
+}
+
+impl CoreDump {
+    /// Create a new [`CoreDump`] object, referencing the provided core
+    /// file and executable.
+    ///
+    /// `debug_syms` defaults to `true` when using this constructor.
+    #[inline]
+    pub fn new(path: impl Into<PathBuf>, exe: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            exe: exe.into(),
+            debug_syms: true,
+            _non_exhaustive: (),
+        }
+    }
+}
+
+impl From<CoreDump> for Source<'static> {
+    #[inline]
+    fn from(core: CoreDump) -> Self {
+        Self::CoreDump(core)
+    }
+}
+
+impl Debug for CoreDump {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        let Self {
+            path,
+            exe,
+            debug_syms: _,
+            _non_exhaustive: (),
+        } = self;
+
+        f.debug_tuple(stringify!(CoreDump))
+            .field(path)
+            .field(exe)
+            .finish()
+    }
+}
+
+
 /// A single ELF file.
 ///
 /// This type is used in the [`Source::Elf`] variant.
@@ -125,7 +255,33 @@ pub struct Elf {
     /// On top of this runtime configuration, the crate needs to be
     /// built with the `dwarf` feature to actually consult debug
     /// symbols. If neither is satisfied, ELF symbols will be used.
+    ///
+    /// If the file contains a `.gnu_debuglink` section, the referenced
+    /// debug file is searched for in the configured debug directories
+    /// and its CRC validated. If the debug file can't be found, the
+    /// file's own symbols are used instead.
     pub debug_syms: bool,
+    /// The base address at which the ELF file is loaded.
+    ///
+    /// If set, [`Input::AbsAddr`][crate::symbolize::Input::AbsAddr]
+    /// inputs are supported and translated into virtual offsets using
+    /// this base address. The base address is validated against the
+    /// ELF file's type and segment layout and an error is reported if
+    /// it can't be correct. Rather than providing it directly, the
+    /// base address can also be derived from a running process'
+    /// mappings ([`BaseAddr::Process`]) or from the runtime address of
+    /// a known symbol ([`BaseAddr::Symbol`]).
+    pub base_addr: Option<BaseAddr>,
+    /// The path to the ELF file that `path` was produced from by the
+    /// BOLT post-link optimizer.
+    ///
+    /// If set, `path` has to contain BOLT address translation data (as
+    /// emitted when running BOLT with `--enable-bat`). Addresses are
+    /// translated into their counterparts in the original file and
+    /// symbolized using it, instead of `path`. That allows for
+    /// attributing samples collected on an optimized binary to the
+    /// original symbols and source code lines.
+    pub bolt_original: Option<PathBuf>,
     /// The struct is non-exhaustive and open to extension.
     #[doc(hidden)]
     pub _non_exhaustive: (),
@@ -140,6 +296,8 @@ impl Elf {
         Self {
             path: path.into(),
             debug_syms: true,
+            base_addr: None,
+            bolt_original: None,
             _non_exhaustive: (),
         }
     }
@@ -157,6 +315,8 @@ impl Debug for Elf {
         let Self {
             path,
             debug_syms: _,
+            base_addr: _,
+            bolt_original: _,
             _non_exhaustive: (),
         } = self;
 
@@ -168,6 +328,13 @@ impl Debug for Elf {
 /// Configuration for kernel address symbolization.
 ///
 /// This type is used in the [`Source::Kernel`] variant.
+///
+/// With the `bpf` feature enabled, addresses in JIT-ed BPF programs
+/// (as listed in `kallsyms` as `bpf_prog_<tag>_<name>`) are resolved
+/// to the BPF program's name. Source code location information is
+/// retrieved from the program's BTF line information, if present and
+/// requested. Such symbols are reported as belonging to the `bpf`
+/// module.
 #[derive(Clone, Debug, PartialEq)]
 pub struct Kernel {
     /// The path of a `kallsyms` file to use.
@@ -179,6 +346,10 @@ pub struct Kernel {
     /// If both a `vmlinux` as well as a `kallsyms` file are found,
     /// `vmlinux` will generally be given preference and `kallsyms` acts
     /// as a fallback.
+    ///
+    /// A user provided `kallsyms` copy may be gzip (requires the `zlib`
+    /// feature) or zstd (requires the `zstd` feature) compressed, in
+    /// which case it is transparently decompressed.
     pub kallsyms: MaybeDefault<PathBuf>,
     /// The path of the `vmlinux` file to use.
     ///
@@ -195,6 +366,11 @@ pub struct Kernel {
     /// If both a `vmlinux` as well as a `kallsyms` file are found,
     /// `vmlinux` will generally be given preference and `kallsyms` acts
     /// as a fallback.
+    ///
+    /// Similar to `kallsyms`, a user provided `vmlinux` file may be gzip
+    /// or zstd compressed (e.g., `vmlinux.gz`). Its contents are then
+    /// decompressed into memory, up to a maximum size of 4 GiB. xz
+    /// compressed files are not supported.
     pub vmlinux: MaybeDefault<PathBuf>,
     /// The KASLR offset to use.
     ///
@@ -267,7 +443,11 @@ pub struct Process {
     /// where they provide an interface for the runtime to expose addresses of
     /// dynamic symbols to profiling tools.
     ///
+    /// If enabled, a [jitdump][] file mapped into the process is
+    /// consulted as well, for addresses not covered by the perf map.
+    ///
     /// [perf map]: https://git.kernel.org/pub/scm/linux/kernel/git/torvalds/linux.git/tree/tools/perf/Documentation/jit-interface.txt
+    /// [jitdump]: https://git.kernel.org/pub/scm/linux/kernel/git/torvalds/linux.git/tree/tools/perf/Documentation/jitdump-specification.txt
     pub perf_map: bool,
     /// Whether to work with `/proc/<pid>/map_files/` entries or with
     /// symbolic paths mentioned in `/proc/<pid>/maps` instead.
@@ -277,7 +457,22 @@ pub struct Process {
     /// contexts or when files have been deleted from the file system.
     /// However, by using symbolic paths the need for requiring the
     /// `SYS_ADMIN` capability is eliminated.
+    ///
+    /// Files that have been deleted or replaced since they got mapped
+    /// (as indicated by a `(deleted)` suffix in `/proc/<pid>/maps`)
+    /// are always accessed through `/proc/<pid>/map_files/`, because
+    /// their symbolic paths no longer refer to the mapped file.
     pub map_files: bool,
+    /// Whether to resolve symbolic paths relative to the process' root
+    /// directory, as accessible via `/proc/<pid>/root/`.
+    ///
+    /// For processes running in a different mount namespace (e.g., in
+    /// a container), this makes sure that the files as seen by the
+    /// process are used instead of whatever may be present at the same
+    /// path on the host. Users that have already mapped paths themselves
+    /// may want to disable this behavior. It is only relevant when not
+    /// using [`map_files`][Process::map_files].
+    pub proc_root: bool,
     /// The struct is non-exhaustive and open to extension.
     #[doc(hidden)]
     pub _non_exhaustive: (),
@@ -295,9 +490,28 @@ impl Process {
             debug_syms: true,
             perf_map: true,
             map_files: true,
+            proc_root: true,
             _non_exhaustive: (),
         }
     }
+
+    /// Create a new [`Process`] object referencing the process
+    /// identified by the provided pidfd.
+    ///
+    /// Referencing a process via a pidfd pins it, meaning that
+    /// symbolization will fail instead of silently using data of an
+    /// unrelated process, should the original one have exited and its
+    /// ID been reused. See [`PidFd`][crate::PidFd] for details.
+    ///
+    /// `debug_syms` and `perf_map` default to `true` when using this
+    /// constructor.
+    #[cfg(linux)]
+    #[cfg_attr(docsrs, doc(cfg(target_os = "linux")))]
+    #[inline]
+    pub fn from_pidfd(fd: OwnedFd) -> Result<Self> {
+        let pid = Pid::from_pidfd(fd)?;
+        Ok(Self::new(pid))
+    }
 }
 
 impl Debug for Process {
@@ -307,6 +521,7 @@ impl Debug for Process {
             debug_syms: _,
             perf_map: _,
             map_files: _,
+            proc_root: _,
             _non_exhaustive: (),
         } = self;
 
@@ -325,6 +540,211 @@ impl From<Process> for Source<'static> {
 }
 
 
+/// Options influencing the capture of a [`ProcessSnapshot`].
+#[derive(Clone, Debug)]
+pub struct SnapshotOpts {
+    /// Whether to read and record the build IDs of the ELF files
+    /// mapped into the process.
+    ///
+    /// Build IDs are made available through
+    /// [`MapsEntry::build_id`][crate::maps::MapsEntry::build_id] and
+    /// allow for checking whether the files present at symbolization
+    /// time are still the ones that were mapped. Failure to read a
+    /// build ID is not considered an error.
+    pub build_ids: bool,
+    /// A directory into which to copy files that are mapped into the
+    /// process, but have been deleted (or replaced) on the file system.
+    ///
+    /// Such files would otherwise only be accessible for as long as the
+    /// process is alive. Each file is copied once, even if mapped
+    /// multiple times, and the copy is then used for symbolization.
+    /// The directory has to exist and is not cleaned up by the library.
+    pub copy_deleted: Option<PathBuf>,
+    /// The struct is non-exhaustive and open to extension.
+    #[doc(hidden)]
+    pub _non_exhaustive: (),
+}
+
+impl Default for SnapshotOpts {
+    fn default() -> Self {
+        Self {
+            build_ids: true,
+            copy_deleted: None,
+            _non_exhaustive: (),
+        }
+    }
+}
+
+
+/// A snapshot of a process' memory mappings, for symbolizing addresses
+/// after the fact.
+///
+/// This type is used in the [`Source::ProcessSnapshot`] variant.
+///
+/// [`Source::Process`] inspects the process at the time of
+/// symbolization, which requires it to still be alive. A snapshot, on
+/// the other hand, captures the relevant state at sample time and can
+/// be used for symbolization later on, even once the process has
+/// exited.
+///
+/// Mapped files are looked up at their symbolic paths (as opposed to
+/// through `/proc/<pid>/map_files/`) during symbolization, unless they
+/// had been deleted and got copied as per
+/// [`SnapshotOpts::copy_deleted`]. The vDSO is copied as part of the
+/// capture. Perf maps and jitdump files are not consulted. Only
+/// absolute addresses are supported as input.
+#[derive(Clone)]
+pub struct ProcessSnapshot {
+    /// The ID of the captured process.
+    pub(crate) pid: Pid,
+    /// The process' (symbolization relevant) memory mappings.
+    pub(crate) entries: Arc<[MapsEntry]>,
+    /// The contents of the process' vDSO, if any.
+    pub(crate) vdso: Option<Arc<[u8]>>,
+    /// Whether or not to consult debug symbols to satisfy the request
+    /// (if present).
+    ///
+    /// On top of this runtime configuration, the crate needs to be
+    /// built with the `dwarf` feature to actually consult debug
+    /// symbols. If neither is satisfied, ELF symbols will be used.
+    pub debug_syms: bool,
+}
+
+impl ProcessSnapshot {
+    /// Capture a snapshot of the process identified by `pid`, using
+    /// default options.
+    ///
+    /// `debug_syms` defaults to `true`.
+    #[inline]
+    pub fn capture(pid: Pid) -> Result<Self> {
+        Self::capture_opts(pid, &SnapshotOpts::default())
+    }
+
+    /// Capture a snapshot of the process identified by `pid`, using
+    /// the provided options.
+    ///
+    /// `debug_syms` defaults to `true`.
+    pub fn capture_opts(pid: Pid, opts: &SnapshotOpts) -> Result<Self> {
+        let SnapshotOpts {
+            build_ids,
+            copy_deleted,
+            _non_exhaustive: (),
+        } = opts;
+
+        let mut entries = maps::parse_filtered(&pid)?.collect::<Result<Vec<_>>>()?;
+        let mut vdso = None;
+        let mut vdso_failed = false;
+        let mut copies = HashMap::<(u64, PathBuf), PathBuf>::new();
+
+        for entry in entries.iter_mut() {
+            match &mut entry.path_name {
+                Some(PathName::Component(component))
+                    if component == vdso::VDSO && vdso.is_none() =>
+                {
+                    match vdso::read_vdso(&pid, entry.range.clone()) {
+                        Ok(data) => vdso = Some(Arc::<[u8]>::from(data)),
+                        Err(err) => {
+                            log::debug!("failed to capture vDSO of process {pid}: {err}");
+                            vdso_failed = true;
+                        }
+                    }
+                }
+                Some(PathName::Path(path)) if !is_shared_mem(&path.symbolic_path) => {
+                    if let (Some(dir), true) = (copy_deleted, path.deleted) {
+                        let key = (entry.inode, path.symbolic_path.clone());
+                        let copy = if let Some(copy) = copies.get(&key) {
+                            copy.clone()
+                        } else {
+                            let mut name = OsString::from(format!("{pid}-{}-", entry.inode));
+                            let () = name.push(
+                                path.symbolic_path
+                                    .file_name()
+                                    .unwrap_or_else(|| OsStr::new("deleted")),
+                            );
+                            let copy = dir.join(name);
+                            let _size = fs::copy(&path.maps_file, &copy).with_context(|| {
+                                format!(
+                                    "failed to copy `{}` to `{}`",
+                                    path.maps_file.display(),
+                                    copy.display()
+                                )
+                            })?;
+                            copies.entry(key).or_insert(copy).clone()
+                        };
+                        path.maps_file = copy;
+                    }
+
+                    if *build_ids {
+                        entry.build_id =
+                            read_elf_build_id_cached(&path.maps_file).unwrap_or_else(|err| {
+                                log::debug!(
+                                    "failed to read build ID of `{}`: {err}",
+                                    path.symbolic_path.display()
+                                );
+                                None
+                            });
+                    }
+                }
+                _ => (),
+            }
+        }
+
+        if vdso_failed {
+            // Without its contents there is no way for us to symbolize
+            // addresses in the vDSO later on. Treat them as unmapped
+            // instead of attempting to read the memory of whatever
+            // process may then be using the ID.
+            let () = entries.retain(|entry| {
+                !matches!(&entry.path_name, Some(PathName::Component(component)) if component == vdso::VDSO)
+            });
+        }
+
+        let slf = Self {
+            pid,
+            entries: Arc::from(entries),
+            vdso,
+            debug_syms: true,
+        };
+        Ok(slf)
+    }
+
+    /// Retrieve the ID of the captured process.
+    #[inline]
+    pub fn pid(&self) -> &Pid {
+        &self.pid
+    }
+
+    /// Retrieve the captured memory mappings, sorted by address.
+    #[inline]
+    pub fn entries(&self) -> &[MapsEntry] {
+        &self.entries
+    }
+}
+
+impl Debug for ProcessSnapshot {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        let Self {
+            pid,
+            entries: _,
+            vdso: _,
+            debug_syms: _,
+        } = self;
+
+        f.debug_tuple(stringify!(ProcessSnapshot))
+            // We use the `Display` representation here.
+            .field(&format_args!("{pid}"))
+            .finish()
+    }
+}
+
+impl From<ProcessSnapshot> for Source<'static> {
+    #[inline]
+    fn from(snapshot: ProcessSnapshot) -> Self {
+        Self::ProcessSnapshot(snapshot)
+    }
+}
+
+
 cfg_gsym! {
 /// Enumeration of supported Gsym sources.
 ///
@@ -397,6 +817,11 @@ impl<'dat> From<GsymData<'dat>> for Source<'dat> {
 
 
 /// A Gsym file.
+///
+/// The file may be zstd compressed (commonly using a `.gsym.zst`
+/// extension), in which case it is transparently decompressed into
+/// memory when opened. Support for compressed files requires the
+/// `zstd` feature.
 #[derive(Clone)]
 pub struct GsymFile {
     /// The path to the Gsym file.
@@ -437,6 +862,128 @@ impl From<GsymFile> for Source<'static> {
 }
 
 
+cfg_macho! {
+/// A Mach-O file, as used on macOS.
+///
+/// This type is used in the [`Source::MachO`] variant.
+#[derive(Clone)]
+pub struct MachO {
+    /// The path to the Mach-O file.
+    ///
+    /// In case of a universal ("fat") binary, the slice for the host's
+    /// architecture is used, if present, and the first slice otherwise.
+    pub path: PathBuf,
+    /// The path to the DWARF file of a dSYM bundle to use for debug
+    /// information.
+    ///
+    /// If not set, a bundle named after the Mach-O file with a `.dSYM`
+    /// suffix is searched for next to it (i.e.,
+    /// `<path>.dSYM/Contents/Resources/DWARF/<name>`). The dSYM file's
+    /// UUID has to match that of the Mach-O file.
+    ///
+    /// On top of this runtime configuration, the crate needs to be
+    /// built with the `dwarf` feature to actually consult debug
+    /// information. If it is unavailable, the file's symbol table
+    /// will be used.
+    pub dsym: Option<PathBuf>,
+    /// The struct is non-exhaustive and open to extension.
+    #[doc(hidden)]
+    pub _non_exhaustive: (),
+}
+
+impl MachO {
+    /// Create a new [`MachO`] object, referencing the provided path.
+    ///
+    /// `dsym` defaults to `None` when using this constructor.
+    #[inline]
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            dsym: None,
+            _non_exhaustive: (),
+        }
+    }
+}
+
+impl From<MachO> for Source<'static> {
+    #[inline]
+    fn from(macho: MachO) -> Self {
+        Self::MachO(macho)
+    }
+}
+
+impl Debug for MachO {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        let Self {
+            path,
+            dsym: _,
+            _non_exhaustive: (),
+        } = self;
+
+        f.debug_tuple(stringify!(MachO)).field(path).finish()
+    }
+}
+}
+
+
+cfg_windows! {
+/// A Windows PE file, accompanied by a PDB file containing its debug
+/// information.
+///
+/// This type is used in the [`Source::Pe`] variant.
+#[derive(Clone)]
+pub struct Pe {
+    /// The path to the PE file (e.g., an `.exe` or `.dll`).
+    pub path: PathBuf,
+    /// The path to the PDB file to use.
+    ///
+    /// If not set, the PDB file referenced by the PE file is used. It
+    /// is searched for at the recorded path as well as in the directory
+    /// containing the PE file.
+    ///
+    /// Note that the PDB file is loaded when the PE file is first
+    /// symbolized and cached alongside it from there on.
+    pub pdb: Option<PathBuf>,
+    /// The struct is non-exhaustive and open to extension.
+    #[doc(hidden)]
+    pub _non_exhaustive: (),
+}
+
+impl Pe {
+    /// Create a new [`Pe`] object, referencing the provided path.
+    ///
+    /// `pdb` defaults to `None` when using this constructor.
+    #[inline]
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            pdb: None,
+            _non_exhaustive: (),
+        }
+    }
+}
+
+impl From<Pe> for Source<'static> {
+    #[inline]
+    fn from(pe: Pe) -> Self {
+        Self::Pe(pe)
+    }
+}
+
+impl Debug for Pe {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        let Self {
+            path,
+            pdb: _,
+            _non_exhaustive: (),
+        } = self;
+
+        f.debug_tuple(stringify!(Pe)).field(path).finish()
+    }
+}
+}
+
+
 /// The description of a source of symbols and debug information that the
 /// library will consult to satisfy an address symbolization request.
 ///
@@ -453,12 +1000,24 @@ pub enum Source<'dat> {
     #[cfg(feature = "breakpad")]
     #[cfg_attr(docsrs, doc(cfg(feature = "breakpad")))]
     Breakpad(Breakpad),
+    /// A core dump of a process.
+    CoreDump(CoreDump),
     /// A single ELF file.
     Elf(Elf),
     /// Information about the Linux kernel.
     Kernel(Kernel),
+    /// A Mach-O file, as used on macOS.
+    #[cfg(feature = "macho")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "macho")))]
+    MachO(MachO),
+    /// A Windows PE file with an accompanying PDB file.
+    #[cfg(feature = "windows")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "windows")))]
+    Pe(Pe),
     /// Information about a process.
     Process(Process),
+    /// A snapshot of a process' memory mappings.
+    ProcessSnapshot(ProcessSnapshot),
     /// A Gsym file.
     #[cfg(feature = "gsym")]
     #[cfg_attr(docsrs, doc(cfg(feature = "gsym")))]
@@ -467,6 +1026,33 @@ pub enum Source<'dat> {
     Phantom(&'dat ()),
 }
 
+impl Source<'_> {
+    /// Retrieve the path to the one module (file) that the source
+    /// references, if any.
+    pub(crate) fn module_path(&self) -> Option<&Path> {
+        match self {
+            #[cfg(feature = "apk")]
+            Self::Apk(apk) => Some(&apk.path),
+            #[cfg(feature = "breakpad")]
+            Self::Breakpad(breakpad) => Some(&breakpad.path),
+            Self::Elf(elf) => Some(&elf.path),
+            #[cfg(feature = "macho")]
+            Self::MachO(macho) => Some(&macho.path),
+            #[cfg(feature = "windows")]
+            Self::Pe(pe) => Some(&pe.path),
+            Self::CoreDump(..)
+            | Self::Kernel(..)
+            | Self::Process(..)
+            | Self::ProcessSnapshot(..) => None,
+            #[cfg(feature = "gsym")]
+            Self::Gsym(Gsym::File(file)) => Some(&file.path),
+            #[cfg(feature = "gsym")]
+            Self::Gsym(Gsym::Data(..)) => None,
+            Self::Phantom(()) => unreachable!(),
+        }
+    }
+}
+
 impl Debug for Source<'_> {
     fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
         match self {
@@ -474,9 +1060,15 @@ impl Debug for Source<'_> {
             Self::Apk(apk) => Debug::fmt(apk, f),
             #[cfg(feature = "breakpad")]
             Self::Breakpad(breakpad) => Debug::fmt(breakpad, f),
+            Self::CoreDump(core) => Debug::fmt(core, f),
             Self::Elf(elf) => Debug::fmt(elf, f),
             Self::Kernel(kernel) => Debug::fmt(kernel, f),
+            #[cfg(feature = "macho")]
+            Self::MachO(macho) => Debug::fmt(macho, f),
+            #[cfg(feature = "windows")]
+            Self::Pe(pe) => Debug::fmt(pe, f),
             Self::Process(process) => Debug::fmt(process, f),
+            Self::ProcessSnapshot(snapshot) => Debug::fmt(snapshot, f),
             #[cfg(feature = "gsym")]
             Self::Gsym(gsym) => Debug::fmt(gsym, f),
             Self::Phantom(()) => unreachable!(),
@@ -509,11 +1101,32 @@ mod tests {
             "Breakpad(\"/a-path/with/components.sym\")"
         );
 
+        let core = CoreDump::new("/a-path/core", "/a-path/exe");
+        assert_eq!(
+            format!("{core:?}"),
+            "CoreDump(\"/a-path/core\", \"/a-path/exe\")"
+        );
+        let src = Source::from(core);
+        assert_eq!(
+            format!("{src:?}"),
+            "CoreDump(\"/a-path/core\", \"/a-path/exe\")"
+        );
+
         let elf = Elf::new("/a-path/with/components.elf");
         assert_eq!(format!("{elf:?}"), "Elf(\"/a-path/with/components.elf\")");
         let src = Source::from(elf);
         assert_eq!(format!("{src:?}"), "Elf(\"/a-path/with/components.elf\")");
 
+        let macho = MachO::new("/a-path/with/components");
+        assert_eq!(format!("{macho:?}"), "MachO(\"/a-path/with/components\")");
+        let src = Source::from(macho);
+        assert_eq!(format!("{src:?}"), "MachO(\"/a-path/with/components\")");
+
+        let pe = Pe::new("/a-path/with/components.exe");
+        assert_eq!(format!("{pe:?}"), "Pe(\"/a-path/with/components.exe\")");
+        let src = Source::from(pe);
+        assert_eq!(format!("{src:?}"), "Pe(\"/a-path/with/components.exe\")");
+
         let gsym_data = GsymData::new(b"12345");
         assert_eq!(format!("{gsym_data:?}"), "GsymData([49, 50, 51, 52, 53])");
         let gsym = Gsym::Data(gsym_data.clone());
@@ -539,5 +1152,15 @@ mod tests {
         assert_eq!(format!("{process:?}"), "Process(1234)");
         let src = Source::from(process);
         assert_eq!(format!("{src:?}"), "Process(1234)");
+
+        let snapshot = ProcessSnapshot {
+            pid: Pid::from(1234),
+            entries: Arc::from([]),
+            vdso: None,
+            debug_syms: true,
+        };
+        assert_eq!(format!("{snapshot:?}"), "ProcessSnapshot(1234)");
+        let src = Source::from(snapshot);
+        assert_eq!(format!("{src:?}"), "ProcessSnapshot(1234)");
     }
 }
@@ -1,51 +1,95 @@
 use std::borrow::Cow;
+use std::cell::Cell;
 use std::cell::RefCell;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::BTreeSet;
 use std::collections::HashMap;
+use std::collections::HashSet;
 use std::ffi::OsStr;
 use std::fmt::Debug;
 use std::fmt::Formatter;
 use std::fmt::Result as FmtResult;
 use std::fs::File;
+use std::hash::Hash as _;
+use std::hash::Hasher as _;
+use std::io;
 use std::mem::take;
 use std::ops::Deref as _;
 use std::ops::Range;
 use std::path::Path;
 use std::path::PathBuf;
 use std::rc::Rc;
+use std::sync::mpsc::channel;
+use std::sync::Arc;
+use std::time::Instant;
 
+use crate::addr_space::AddrSpace;
+use crate::addr_space::Arch;
 #[cfg(feature = "breakpad")]
 use crate::breakpad::BreakpadResolver;
+use crate::elf::CoreMaps;
 use crate::elf::ElfParser;
 use crate::elf::ElfResolver;
 use crate::elf::ElfResolverData;
 #[cfg(feature = "dwarf")]
 use crate::elf::DEFAULT_DEBUG_DIRS;
+#[cfg(feature = "async")]
+use crate::file_cache::open_file;
+use crate::file_cache::EntryBudget;
+use crate::file_cache::FdBudget;
 use crate::file_cache::FileCache;
 #[cfg(feature = "gsym")]
 use crate::gsym::GsymResolver;
+use crate::index_cache::IndexCache;
 use crate::insert_map::InsertMap;
+use crate::intern::intern_owned;
+use crate::jitdump::JitDump;
 use crate::kernel::KernelResolver;
 use crate::kernel::KsymResolver;
 use crate::kernel::KALLSYMS;
 use crate::log;
+#[cfg(feature = "macho")]
+use crate::macho::find_dsym;
+#[cfg(feature = "macho")]
+use crate::macho::MachOResolver;
 use crate::maps;
+use crate::maps::is_shared_mem;
 use crate::maps::EntryPath;
 use crate::maps::MapsEntry;
 use crate::maps::PathName;
+use crate::maps::Perm;
 use crate::mmap::Mmap;
 use crate::normalize;
+use crate::normalize::buildid::build_id_debug_path;
+use crate::normalize::buildid::read_elf_build_id_cached;
 use crate::normalize::normalize_sorted_user_addrs_with_entries;
 use crate::normalize::Handler as _;
+use crate::normalize::UserMeta;
+use crate::normalize::UserOutput;
+use crate::once::OnceCell;
+#[cfg(feature = "windows")]
+use crate::pe::PeResolver;
 use crate::perf_map::PerfMap;
+use crate::pool::WorkerPool;
+#[cfg(unix)]
+use crate::sym_sock::SockSym;
+#[cfg(unix)]
+use crate::sym_sock::SymSock;
+use crate::symbolize::CacheKey;
+use crate::symbolize::CodeInfo;
 use crate::symbolize::InlinedFn;
 use crate::symbolize::Resolve;
 use crate::symbolize::TranslateFileOffset;
+#[cfg(feature = "async")]
+use crate::unblock::unblock;
 use crate::util;
 #[cfg(linux)]
 use crate::util::uname_release;
 use crate::util::Dbg;
 #[cfg(feature = "tracing")]
 use crate::util::Hexify;
+use crate::vdso;
+use crate::vdso::Vsyscall;
 #[cfg(feature = "apk")]
 use crate::zip;
 use crate::Addr;
@@ -56,12 +100,15 @@ use crate::IntoError as _;
 use crate::Pid;
 use crate::Result;
 
+use super::addr_cache::AddrCache;
 use super::cache;
 use super::cache::Cache;
 #[cfg(feature = "apk")]
 use super::source::Apk;
+use super::source::BaseAddr;
 #[cfg(feature = "breakpad")]
 use super::source::Breakpad;
+use super::source::CoreDump;
 use super::source::Elf;
 #[cfg(feature = "gsym")]
 use super::source::Gsym;
@@ -70,16 +117,110 @@ use super::source::GsymData;
 #[cfg(feature = "gsym")]
 use super::source::GsymFile;
 use super::source::Kernel;
+#[cfg(feature = "macho")]
+use super::source::MachO;
+#[cfg(feature = "windows")]
+use super::source::Pe;
 use super::source::Process;
 use super::source::Source;
+use super::AddrClass;
+use super::AddrKind;
+use super::CacheUsage;
+use super::ElfSymPolicy;
+use super::FdUsage;
 use super::FindSymOpts;
+use super::Frame;
 use super::Input;
+use super::ModuleStats;
+use super::ProcessMapping;
 use super::Reason;
 use super::ResolvedSym;
+use super::ResolverKind;
+use super::SourceInfo;
 use super::SrcLang;
+use super::Stats;
 use super::Sym;
+use super::SymbolStore;
 use super::Symbolize;
 use super::Symbolized;
+use super::WarmUpBudget;
+use super::WarmUpProgress;
+
+
+/// Retrieve the path of the file backing a process memory mapping, if
+/// it is subject to preloading.
+fn preloadable_entry_path(entry: &MapsEntry) -> Option<&EntryPath> {
+    let entry_path = match &entry.path_name {
+        Some(PathName::Path(entry_path)) if !is_shared_mem(&entry_path.symbolic_path) => entry_path,
+        _ => return None,
+    };
+
+    // APK resolvers are created on a per-member basis, which we can't
+    // infer from the mapping alone.
+    let ext = entry_path.symbolic_path.extension();
+    if cfg!(feature = "apk") && matches!(ext.and_then(OsStr::to_str), Some("apk") | Some("zip")) {
+        return None
+    }
+    Some(entry_path)
+}
+
+
+/// A module to warm up as part of [`Symbolizer::warm_up`].
+enum WarmUpItem<'src> {
+    /// An ELF file.
+    Elf {
+        path: &'src Path,
+        debug_syms: bool,
+        bolt_original: Option<&'src Path>,
+    },
+    /// A file mapped into a process.
+    Entry {
+        entry: MapsEntry,
+        debug_syms: bool,
+        map_files: bool,
+        root: Option<PathBuf>,
+    },
+    /// Any other source, which is preloaded as a whole.
+    Other(&'src Source<'src>),
+}
+
+impl WarmUpItem<'_> {
+    /// Retrieve the path of the module, for reporting purposes.
+    fn module(&self) -> Option<&Path> {
+        match self {
+            Self::Elf { path, .. } => Some(path),
+            Self::Entry { entry, .. } => {
+                preloadable_entry_path(entry).map(|entry_path| entry_path.symbolic_path.as_path())
+            }
+            Self::Other(src) => match src {
+                #[cfg(feature = "breakpad")]
+                Source::Breakpad(breakpad) => Some(&breakpad.path),
+                #[cfg(feature = "gsym")]
+                Source::Gsym(Gsym::File(gsym)) => Some(&gsym.path),
+                #[cfg(feature = "macho")]
+                Source::MachO(macho) => Some(&macho.path),
+                #[cfg(feature = "windows")]
+                Source::Pe(pe) => Some(&pe.path),
+                _ => None,
+            },
+        }
+    }
+
+    /// Retrieve the path of the file backing the module, if any, for
+    /// budgeting purposes.
+    fn file(&self) -> Option<Cow<'_, Path>> {
+        match self {
+            Self::Entry {
+                entry,
+                map_files,
+                root,
+                ..
+            } => preloadable_entry_path(entry)
+                .map(|entry_path| entry_path.access_path(*map_files, root.as_deref())),
+            _ => self.module().map(Cow::Borrowed),
+        }
+    }
+}
 
 
 /// A type for displaying debug information for a [`MapsEntry`].
@@ -114,6 +255,35 @@ impl Debug for DebugMapsEntry<'_> {
 }
 
 
+/// Collect the paths of the files backing the VMAs that any of the
+/// provided addresses fall into.
+#[cfg(feature = "async")]
+fn vma_paths(
+    entries: &[MapsEntry],
+    addrs: &[Addr],
+    map_files: bool,
+    root: Option<&Path>,
+) -> Vec<PathBuf> {
+    let mut addrs = addrs.to_vec();
+    let () = addrs.sort_unstable();
+
+    let mut paths = entries
+        .iter()
+        .filter_map(|entry| match &entry.path_name {
+            Some(PathName::Path(path)) if !is_shared_mem(&path.symbolic_path) => {
+                let idx = addrs.partition_point(|addr| *addr < entry.range.start);
+                let covered = matches!(addrs.get(idx), Some(addr) if entry.range.contains(addr));
+                covered.then(|| path.access_path(map_files, root).into_owned())
+            }
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    let () = paths.sort();
+    let () = paths.dedup();
+    paths
+}
+
+
 #[cfg(feature = "apk")]
 fn create_apk_elf_path(apk: &Path, elf: &Path) -> Result<PathBuf> {
     let mut extension = apk
@@ -139,7 +309,7 @@ fn create_apk_elf_path(apk: &Path, elf: &Path) -> Result<PathBuf> {
 
 /// Demangle a symbol name using the demangling scheme for the given language.
 #[cfg(feature = "demangle")]
-fn maybe_demangle(name: Cow<'_, str>, language: SrcLang) -> Cow<'_, str> {
+pub(crate) fn maybe_demangle(name: Cow<'_, str>, language: SrcLang) -> Cow<'_, str> {
     match language {
         SrcLang::Rust => rustc_demangle::try_demangle(name.as_ref())
             .ok()
@@ -161,7 +331,7 @@ fn maybe_demangle(name: Cow<'_, str>, language: SrcLang) -> Cow<'_, str> {
 }
 
 #[cfg(not(feature = "demangle"))]
-fn maybe_demangle(name: Cow<'_, str>, _language: SrcLang) -> Cow<'_, str> {
+pub(crate) fn maybe_demangle(name: Cow<'_, str>, _language: SrcLang) -> Cow<'_, str> {
     // Demangling is disabled.
     name
 }
@@ -219,17 +389,96 @@ impl<F> ProcessDispatch for F where F: Fn(ProcessMemberInfo<'_>) -> Result<Optio
 fn default_apk_dispatcher(
     info: ApkMemberInfo<'_>,
     debug_dirs: Option<&[PathBuf]>,
+    qualified_names: bool,
+    strict: bool,
 ) -> Result<Box<dyn Resolve>> {
     // Create an Android-style binary-in-APK path for
     // reporting purposes.
     let apk_elf_path = create_apk_elf_path(info.apk_path, info.member_path)?;
     let parser = Rc::new(ElfParser::from_mmap(info.member_mmap, Some(apk_elf_path)));
-    let resolver = ElfResolver::from_parser(parser, debug_dirs)?;
+    let resolver = ElfResolver::from_parser(parser, debug_dirs, qualified_names, strict)?;
     let resolver = Box::new(resolver);
     Ok(resolver)
 }
 
 
+/// Information about a shared memory or hugetlbfs backed mapping of a
+/// process.
+///
+/// This type is used in conjunction with the shared memory
+/// "dispatcher" infrastructure; see
+/// [`Builder::set_shared_mem_dispatcher`].
+#[derive(Clone, Debug)]
+pub struct SharedMemInfo<'dat> {
+    /// The virtual address range covered by the mapping.
+    pub range: Range<Addr>,
+    /// The offset of the mapping into the shared memory object.
+    pub offset: u64,
+    /// Path information about the mapping, as found in
+    /// `/proc/<pid>/maps`.
+    pub path: &'dat EntryPath,
+    /// The struct is non-exhaustive and open to extension.
+    #[doc(hidden)]
+    pub _non_exhaustive: (),
+}
+
+
+/// The signature of a dispatcher function for symbolization of
+/// addresses in shared memory or hugetlbfs backed mappings.
+///
+/// This type is used in conjunction with the shared memory
+/// "dispatcher" infrastructure; see
+/// [`Builder::set_shared_mem_dispatcher`].
+///
+/// If this function returns `Some` resolver, this resolver will be used
+/// for addresses belonging to the represented mapping. If `None` is
+/// returned, addresses in the mapping are reported as unsupported.
+pub trait SharedMemDispatch: Fn(SharedMemInfo<'_>) -> Result<Option<Box<dyn Resolve>>> {}
+
+impl<F> SharedMemDispatch for F where F: Fn(SharedMemInfo<'_>) -> Result<Option<Box<dyn Resolve>>> {}
+
+
+/// Information about an address in an anonymous executable mapping of
+/// a process.
+///
+/// This type is used in conjunction with the unknown address resolver
+/// infrastructure; see [`Builder::set_unknown_addr_resolver`].
+#[derive(Clone, Debug)]
+pub struct UnknownAddrInfo<'dat> {
+    /// The PID of the process the address belongs to.
+    pub pid: &'dat Pid,
+    /// The address to symbolize.
+    pub addr: Addr,
+    /// The virtual address range covered by the mapping containing
+    /// `addr`.
+    pub range: Range<Addr>,
+    /// The struct is non-exhaustive and open to extension.
+    #[doc(hidden)]
+    pub _non_exhaustive: (),
+}
+
+
+/// The signature of a function resolving addresses in anonymous
+/// executable mappings of a process.
+///
+/// This type is used in conjunction with the unknown address resolver
+/// infrastructure; see [`Builder::set_unknown_addr_resolver`].
+///
+/// If this function returns `Some` symbolizer, it will be used for
+/// symbolizing the address in question. If `None` is returned or the
+/// symbolizer does not know the address, symbolization proceeds as if
+/// no function was set.
+pub trait UnknownAddrResolver:
+    Fn(UnknownAddrInfo<'_>) -> Result<Option<Box<dyn Symbolize>>>
+{
+}
+
+impl<F> UnknownAddrResolver for F where
+    F: Fn(UnknownAddrInfo<'_>) -> Result<Option<Box<dyn Symbolize>>>
+{
+}
+
+
 /// Information about an address space member of a process.
 #[derive(Clone, Debug)]
 pub struct ProcessMemberInfo<'dat> {
@@ -253,20 +502,50 @@ pub struct Builder {
     /// symbolization sources that were updated since the last
     /// symbolization operation.
     auto_reload: bool,
+    /// Whether to apply additional checks when opening files referenced
+    /// by symbolization sources.
+    strict_file_access: bool,
+    /// The keys to use for identifying cached files below certain path
+    /// prefixes.
+    cache_keys: Vec<(PathBuf, CacheKey)>,
     /// Whether to attempt to gather source code location information.
     code_info: bool,
     /// Whether to report inlined functions as part of symbolization.
     inlined_fns: bool,
+    /// The maximum number of inlined functions to report per symbol,
+    /// if any.
+    max_inlined_depth: Option<usize>,
     /// Whether or not to transparently demangle symbols.
     ///
     /// Demangling happens on a best-effort basis. Currently supported
     /// languages are Rust and C++ and the flag will have no effect if
     /// the underlying language does not mangle symbols (such as C).
     demangle: bool,
+    /// Whether to intern owned symbol names in a process wide
+    /// interner.
+    intern_names: bool,
+    /// Whether to report fully qualified function names derived from
+    /// debug information.
+    qualified_names: bool,
+    /// The policy determining which symbol information is consulted
+    /// for ELF files.
+    elf_sym_policy: ElfSymPolicy,
+    /// The source root relative to which source code paths are
+    /// reported, if any.
+    source_root: Option<PathBuf>,
+    /// Whether to report paths lossily converted to valid UTF-8.
+    lossy_paths: bool,
+    /// The directory in which to persist parsed symbol indexes, if
+    /// any.
+    cache_dir: Option<PathBuf>,
     /// List of additional directories in which split debug information
     /// is looked for.
     #[cfg(feature = "dwarf")]
     debug_dirs: Vec<PathBuf>,
+    /// Whether to accept Gsym data of versions newer than the ones
+    /// known to the library.
+    #[cfg(feature = "gsym")]
+    gsym_forward_compat: bool,
     /// The "dispatch" function to use when symbolizing addresses
     /// mapping to members of an APK.
     #[cfg(feature = "apk")]
@@ -274,16 +553,97 @@ pub struct Builder {
     /// The "dispatch" function to use when symbolizing addresses
     /// mapping to members of a process.
     process_dispatch: Option<Dbg<Box<dyn ProcessDispatch>>>,
+    /// The "dispatch" function to use when symbolizing addresses
+    /// mapping to shared memory or hugetlbfs backed mappings of a
+    /// process.
+    shared_mem_dispatch: Option<Dbg<Box<dyn SharedMemDispatch>>>,
+    /// The function to use for symbolizing addresses in anonymous
+    /// executable mappings of a process.
+    unknown_addr_resolver: Option<Dbg<Box<dyn UnknownAddrResolver>>>,
+    /// The number of worker threads to use for symbolizing large
+    /// address batches.
+    parallelism: usize,
+    /// The maximum number of files to keep open, if any.
+    max_open_files: Option<usize>,
+    /// The maximum number of files to cache data for, if any.
+    max_cached_files: Option<usize>,
+    /// The maximum number of symbolization results to cache per file,
+    /// if any.
+    addr_cache_capacity: Option<usize>,
 }
 
 impl Builder {
     /// Enable/disable auto reloading of symbolization sources in the
     /// presence of updates.
+    ///
+    /// For the running kernel's `/proc/kallsyms`, the loading and
+    /// unloading of kernel modules is detected by means of
+    /// `/proc/modules`, and module symbols are refreshed accordingly.
     pub fn enable_auto_reload(mut self, enable: bool) -> Self {
         self.auto_reload = enable;
         self
     }
 
+    /// Enable/disable additional checks when opening files referenced
+    /// by symbolization sources.
+    ///
+    /// This option is meant for hardening privileged agents that
+    /// symbolize addresses on behalf of untrusted processes and,
+    /// hence, work with untrusted file paths (e.g., as reported in
+    /// `/proc/<pid>/maps`). When enabled:
+    /// - symbolic links as the final path component are not followed
+    ///   (`O_NOFOLLOW`), with the exception of kernel provided
+    ///   `/proc/<pid>/map_files/` entries
+    /// - only regular files are accepted; files are opened non-blocking, so
+    ///   that FIFOs and devices are rejected without waiting on them
+    /// - only files owned by the super user or the effective user of the
+    ///   current process are accepted
+    ///
+    /// These checks also apply to separate debug information files
+    /// found in debug directories or via debug links as well as to
+    /// split DWARF files. Files are always opened with `O_CLOEXEC`,
+    /// irrespective of this setting.
+    ///
+    /// Because parsed symbol data are kept in heap memory as well as
+    /// in file backed memory mappings, locking them into memory is best
+    /// achieved process wide, using `mlockall(MCL_CURRENT | MCL_FUTURE)`.
+    ///
+    /// Strict file access is only supported on Unix systems. Elsewhere
+    /// opening any file fails with
+    /// [`ErrorKind::Unsupported`][crate::ErrorKind::Unsupported] when it is
+    /// enabled.
+    ///
+    /// This setting is disabled by default.
+    pub fn enable_strict_file_access(mut self, enable: bool) -> Self {
+        self.strict_file_access = enable;
+        self
+    }
+
+    /// Set the key used for identifying cached files below the given
+    /// path prefix.
+    ///
+    /// With auto reloading enabled (see
+    /// [`Builder::enable_auto_reload`]), a file is re-parsed once its
+    /// key changes. By default files are identified by their device,
+    /// inode number, size, and modification time. On some file systems
+    /// modification time jitter causes spurious invalidation of
+    /// cached data. For read-only system paths it may be preferable to
+    /// ignore the modification time or to identify files by their build
+    /// ID only.
+    ///
+    /// `prefix` is matched component wise against the paths used for
+    /// opening files. Note that when symbolizing processes with
+    /// [`Process::map_files`] enabled, these are
+    /// `/proc/<pid>/map_files/` paths. If multiple prefixes match a
+    /// path, the longest one wins. Setting a key for a prefix that has
+    /// been configured already replaces the previous setting.
+    pub fn set_file_cache_key(mut self, prefix: impl Into<PathBuf>, key: CacheKey) -> Self {
+        let prefix = prefix.into();
+        let () = self.cache_keys.retain(|(other, _key)| *other != prefix);
+        let () = self.cache_keys.push((prefix, key));
+        self
+    }
+
     /// Enable/disable source code location information (line numbers,
     /// file names etc.).
     pub fn enable_code_info(mut self, enable: bool) -> Self {
@@ -293,26 +653,173 @@ impl Builder {
 
     /// Enable/disable inlined function reporting.
     ///
+    /// When enabled, the chain of inlined calls covering an address is
+    /// reported via [`Sym::inlined`], based on
+    /// `DW_TAG_inlined_subroutine` entries in DWARF, inline info in
+    /// Gsym, and `INLINE` records in Breakpad files. Other sources do
+    /// not carry inline information.
+    ///
     /// This option only has an effect if `code_info` is `true`.
     pub fn enable_inlined_fns(mut self, enable: bool) -> Self {
         self.inlined_fns = enable;
         self
     }
 
+    /// Set the maximum depth of inlined function chains to report.
+    ///
+    /// Pathological or adversarial debug information may contain
+    /// extremely deep chains of inlined calls. If set, at most `depth`
+    /// of the outermost inlined functions covering an address are
+    /// reported and [`Sym::inlined_truncated`] indicates whether any
+    /// were cut off. Irrespective of this setting, DWARF inline
+    /// information is only parsed up to an internal depth limit.
+    ///
+    /// A value of `None`, the default, reports inlined functions up to
+    /// said internal limit.
+    pub fn set_max_inlined_depth(mut self, depth: Option<usize>) -> Self {
+        self.max_inlined_depth = depth;
+        self
+    }
+
     /// Enable/disable transparent demangling of symbol names.
     ///
     /// Demangling happens on a best-effort basis. Currently supported languages
-    /// are Rust and C++ and the flag will have no effect if the underlying
-    /// language does not mangle symbols (such as C).
+    /// are Rust (legacy and v0 mangling) and C++ (Itanium ABI) and the
+    /// flag will have no effect if the underlying language does not
+    /// mangle symbols (such as C). Names from all sources are subject to
+    /// demangling; if the source language is unknown, Rust demangling
+    /// is attempted first, followed by C++.
+    ///
+    /// Demangling is enabled by default, but requires the `demangle`
+    /// feature to be active. Without it, this flag has no effect.
     pub fn enable_demangling(mut self, enable: bool) -> Self {
         self.demangle = enable;
         self
     }
 
+    /// Enable/disable interning of symbol names.
+    ///
+    /// Symbol names that are not borrowed from cached symbolization
+    /// sources (e.g., because they were demangled) are otherwise
+    /// allocated anew for each symbolization result. When interning
+    /// is enabled, such names are stored in a process wide interner
+    /// instead and shared among all results and [`Symbolizer`]
+    /// instances that have the setting enabled. That can considerably
+    /// reduce memory usage when holding on to many results, e.g., for
+    /// aggregation purposes, as names commonly repeat across modules
+    /// (think template instantiations or C runtime symbols).
+    ///
+    /// Interned names are never freed, so this setting should only be
+    /// enabled if the set of symbols encountered is bounded.
+    ///
+    /// This setting is disabled by default.
+    pub fn enable_name_interning(mut self, enable: bool) -> Self {
+        self.intern_names = enable;
+        self
+    }
+
+    /// Enable/disable reporting of fully qualified function names.
+    ///
+    /// When enabled and DWARF debug information is available, the
+    /// names of functions are reconstructed from the namespaces,
+    /// classes, structs, and unions they are declared in (e.g.,
+    /// `ns::Class::method`), instead of reporting the linkage name.
+    /// Such names are human readable irrespective of demangling
+    /// support, but they do not contain parameter types. Functions in
+    /// anonymous namespaces are reported as part of `(anonymous
+    /// namespace)`. Names of inlined functions as well as names
+    /// retrieved from other sources are not affected.
+    ///
+    /// This setting is disabled by default.
+    pub fn enable_qualified_names(mut self, enable: bool) -> Self {
+        self.qualified_names = enable;
+        self
+    }
+
+    /// Set the policy determining which symbol information is
+    /// consulted when symbolizing addresses in ELF files.
+    ///
+    /// This setting allows for trading off speed for the quality of
+    /// symbolization results, for example by ignoring debug
+    /// information altogether ([`ElfSymPolicy::SymtabOnly`]) or by
+    /// insisting on it ([`ElfSymPolicy::RequireDebugInfo`]). Which
+    /// kind of resolver ends up being used for a given mapping of a
+    /// process can be inspected using
+    /// [`Symbolizer::process_mappings`].
+    ///
+    /// By default, [`ElfSymPolicy::PreferDebugInfo`] is used.
+    pub fn set_elf_sym_policy(mut self, policy: ElfSymPolicy) -> Self {
+        self.elf_sym_policy = policy;
+        self
+    }
+
+    /// Set the source root directory relative to which source code
+    /// paths are reported.
+    ///
+    /// When set, source code location information (see
+    /// [`Sym::code_info`] and [`InlinedFn::code_info`]) referring to
+    /// an absolute path within `root` is reported relative to it. That
+    /// is, [`CodeInfo::to_path`] will produce a path relative to
+    /// `root`. Paths outside of `root` are reported unchanged. Matching
+    /// happens component wise and purely lexically, without accessing
+    /// the file system.
+    ///
+    /// A value of `None`, the default, reports paths as they are found
+    /// in the symbolization source.
+    pub fn set_source_root<P>(mut self, root: Option<P>) -> Self
+    where
+        P: AsRef<Path>,
+    {
+        self.source_root = root.map(|root| root.as_ref().to_path_buf());
+        self
+    }
+
+    /// Enable/disable lossy reporting of paths.
+    ///
+    /// Paths found in symbolization sources, such as source file paths
+    /// in DWARF or module paths in proc maps, are not guaranteed to be
+    /// valid UTF-8. By default they are reported losslessly, i.e., as
+    /// the raw bytes found. When this setting is enabled, paths in
+    /// [`Sym::code_info`], [`InlinedFn::code_info`], [`Sym::module`],
+    /// and [`Frame::module`] are converted to valid UTF-8 instead, with
+    /// invalid sequences being replaced by `U+FFFD REPLACEMENT
+    /// CHARACTER`. That may be preferable when paths are mostly used
+    /// for display purposes or passed on to consumers expecting UTF-8.
+    ///
+    /// This setting is disabled by default.
+    pub fn enable_lossy_paths(mut self, enable: bool) -> Self {
+        self.lossy_paths = enable;
+        self
+    }
+
+    /// Set the directory in which to persist parsed symbol indexes.
+    ///
+    /// Making sense of the symbol table of a large binary (think
+    /// `vmlinux`) can take a significant amount of time, which is paid
+    /// afresh on every run. If a cache directory is set, indexes
+    /// created for ELF files carrying a build ID are written to it and
+    /// memory mapped instead of recreated on subsequent runs, by this
+    /// or other processes.
+    ///
+    /// The directory is created if it does not exist yet. Failure to
+    /// read from or write to it is not fatal and merely causes indexes
+    /// to be created in memory.
+    ///
+    /// A value of `None`, the default, disables index persistence.
+    pub fn set_cache_dir<P>(mut self, dir: Option<P>) -> Self
+    where
+        P: AsRef<Path>,
+    {
+        self.cache_dir = dir.map(|dir| dir.as_ref().to_path_buf());
+        self
+    }
+
     /// Set debug directories to search for split debug information.
     ///
     /// These directories will be consulted (in given order) when resolving
-    /// debug links in binaries. By default `/usr/lib/debug` and `/lib/debug/`
+    /// debug links in binaries. Prior to that, debug files are looked up
+    /// by the binary's build ID, at `.build-id/<xx>/<yyyy>.debug` below
+    /// each of the directories. By default `/usr/lib/debug` and `/lib/debug/`
     /// will be searched. Setting a list here will overwrite these defaults, so
     /// make sure to include these directories as desired.
     ///
@@ -341,6 +848,20 @@ impl Builder {
         self
     }
 
+    /// Enable/disable forward compatible parsing of Gsym data.
+    ///
+    /// By default, only Gsym data of versions known to the library are
+    /// accepted. When forward compatibility is enabled, data of newer
+    /// versions is accepted as well, on the assumption that such
+    /// versions only extend the format. Known tables are parsed as
+    /// usual, while address data of unknown info types is skipped.
+    #[cfg(feature = "gsym")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "gsym")))]
+    pub fn enable_gsym_forward_compat(mut self, enable: bool) -> Self {
+        self.gsym_forward_compat = enable;
+        self
+    }
+
     /// Set the "dispatch" function to use when symbolizing addresses
     /// mapping to members of an APK.
     #[cfg(feature = "apk")]
@@ -363,20 +884,201 @@ impl Builder {
         self
     }
 
+    /// Set the "dispatch" function to use when symbolizing addresses
+    /// mapping to shared memory or hugetlbfs backed mappings of a
+    /// process.
+    ///
+    /// Code mapped from shared memory (`/dev/shm/...`, System V shared
+    /// memory segments, or `memfd_create(2)` backed files) or from
+    /// hugetlbfs is commonly generated at run time, e.g., by
+    /// just-in-time compilers, and lacks any symbol information that
+    /// the library could make sense of on its own. The dispatcher is
+    /// invoked once per such mapping and the resolver it returns is
+    /// used for all addresses inside of it. Addresses are provided to
+    /// the resolver as offsets into the shared memory object, passed
+    /// through [`TranslateFileOffset::file_offset_to_virt_offset`]
+    /// first.
+    ///
+    /// Without a dispatcher, or if it does not return a resolver,
+    /// addresses in such mappings are reported as
+    /// [`Reason::Unsupported`]. Note that a dispatcher set via
+    /// [`Builder::set_process_dispatcher`] takes precedence.
+    pub fn set_shared_mem_dispatcher<D>(mut self, shared_mem_dispatch: D) -> Self
+    where
+        D: SharedMemDispatch + 'static,
+    {
+        self.shared_mem_dispatch = Some(Dbg(Box::new(shared_mem_dispatch)));
+        self
+    }
+
+    /// Set a function for resolving addresses in anonymous executable
+    /// mappings of a process.
+    ///
+    /// Such mappings are commonly used by just-in-time compilers for
+    /// generated code, which **blazesym** does not have any symbol
+    /// information for. The provided function is invoked with the
+    /// PID and address for each such address (see
+    /// [`UnknownAddrInfo`]) and can return a symbolizer backed by the
+    /// runtime's own symbol tables. The result of it is reported just
+    /// like any other symbolization result.
+    ///
+    /// The function is consulted after registered symbol sockets (see
+    /// [`Symbolizer::register_sym_socket`]), but before the process'
+    /// perf map.
+    pub fn set_unknown_addr_resolver<R>(mut self, unknown_addr_resolver: R) -> Self
+    where
+        R: UnknownAddrResolver + 'static,
+    {
+        self.unknown_addr_resolver = Some(Dbg(Box::new(unknown_addr_resolver)));
+        self
+    }
+
+    /// Set the number of worker threads to use for symbolizing large
+    /// batches of process addresses.
+    ///
+    /// When set to a value greater than one, [`Symbolizer::symbolize`]
+    /// distributes large batches of addresses of a [`Process`] source
+    /// among a pool of worker threads, grouped by the module (e.g.,
+    /// shared object) they belong to. Each module is always handled by
+    /// the same worker thread, so that parsed symbolization data
+    /// are reused across calls. The order of results is unaffected.
+    ///
+    /// Worker threads are spawned lazily, on first use. Addresses in
+    /// anonymous mappings (which may be covered by perf maps or symbol
+    /// sockets) are always symbolized on the calling thread. Because
+    /// dispatch functions are not required to be thread-safe,
+    /// parallel symbolization is unavailable when any dispatcher is
+    /// set (see, e.g., [`Builder::set_process_dispatcher`]).
+    ///
+    /// A value of zero or one disables parallel symbolization, which
+    /// is the default.
+    pub fn set_parallelism(mut self, threads: usize) -> Self {
+        self.parallelism = threads;
+        self
+    }
+
+    /// Set the maximum number of files the [`Symbolizer`] keeps open.
+    ///
+    /// Cached symbolization data generally keep the file they were
+    /// created from open. When symbolizing addresses in many
+    /// processes, that may exhaust the process' file descriptor limit.
+    /// If set, the least recently used files are closed once more
+    /// than `max` files are open. Data already parsed stay cached and
+    /// files are transparently reopened by path when needed again, after
+    /// verifying that they are unchanged (by means of their build ID,
+    /// if present, or their meta data otherwise). The kernel's
+    /// `kallsyms` file is not subject to this limit.
+    ///
+    /// With parallel symbolization enabled (see
+    /// [`Builder::set_parallelism`]), files opened by worker threads
+    /// count against the same limit.
+    ///
+    /// Current usage can be inquired via [`Symbolizer::fd_usage`].
+    /// A value of `None`, the default, imposes no limit.
+    pub fn set_max_open_files(mut self, max: Option<usize>) -> Self {
+        self.max_open_files = max;
+        self
+    }
+
+    /// Set the maximum number of files the [`Symbolizer`] caches data
+    /// for.
+    ///
+    /// Symbolization results borrow from cached data, which hence
+    /// cannot be evicted behind the user's back. Instead, once data
+    /// for `max` files are cached, symbolizing addresses in yet
+    /// another file fails with an error of kind
+    /// [`ErrorKind::OutOfMemory`][crate::ErrorKind::OutOfMemory]. Room
+    /// can be made using [`Symbolizer::prune_cache`] or
+    /// [`Symbolizer::clear_cache`]. In conjunction with
+    /// [`Builder::set_max_open_files`] and one [`Symbolizer`] instance
+    /// per party, this setting allows for bounding the resources used
+    /// when symbolizing on behalf of multiple parties, without them
+    /// affecting each other.
+    ///
+    /// Stale data for files that changed since they were cached count
+    /// against the limit until pruned. With parallel symbolization
+    /// enabled (see [`Builder::set_parallelism`]), data cached by
+    /// worker threads count against the same limit.
+    ///
+    /// Current usage can be inquired via [`Symbolizer::cache_usage`].
+    /// A value of `None`, the default, imposes no limit.
+    pub fn set_max_cached_files(mut self, max: Option<usize>) -> Self {
+        self.max_cached_files = max;
+        self
+    }
+
+    /// Set the maximum number of symbolization results to cache for
+    /// each ELF file.
+    ///
+    /// Workloads such as sampling profilers tend to symbolize the same
+    /// (hot) addresses over and over again. With this setting enabled,
+    /// results are memoized per address, sparing repeated symbol
+    /// lookups and walks of DWARF line programs, at the cost of
+    /// additional memory. Once `capacity` results are cached for a
+    /// file, an arbitrary one is evicted to make room for the next.
+    /// Cached results are invalidated together with the remaining data
+    /// cached for the file, e.g., when it is reloaded because it
+    /// changed or when it is pruned via [`Symbolizer::prune_cache`].
+    ///
+    /// With parallel symbolization enabled (see
+    /// [`Builder::set_parallelism`]), each worker thread maintains a
+    /// cache of its own.
+    ///
+    /// A value of `None`, the default, disables caching.
+    pub fn set_addr_cache_capacity(mut self, capacity: Option<usize>) -> Self {
+        self.addr_cache_capacity = capacity;
+        self
+    }
+
     /// Create the [`Symbolizer`] object.
     pub fn build(self) -> Symbolizer {
+        self.build_impl(None, None)
+    }
+
+    /// Create the [`Symbolizer`] object, with files opened by it
+    /// counting against `fd_budget` and files cached by it counting
+    /// against `entry_budget`, if provided.
+    fn build_impl(
+        self,
+        fd_budget: Option<Arc<FdBudget>>,
+        entry_budget: Option<Arc<EntryBudget>>,
+    ) -> Symbolizer {
         let Self {
             auto_reload,
+            strict_file_access,
+            cache_keys,
             code_info,
             inlined_fns,
+            max_inlined_depth,
             demangle,
+            intern_names,
+            qualified_names,
+            elf_sym_policy,
+            source_root,
+            lossy_paths,
+            cache_dir,
             #[cfg(feature = "dwarf")]
             debug_dirs,
+            #[cfg(feature = "gsym")]
+            gsym_forward_compat,
             #[cfg(feature = "apk")]
             apk_dispatch,
             process_dispatch,
+            shared_mem_dispatch,
+            unknown_addr_resolver,
+            parallelism,
+            max_open_files,
+            max_cached_files,
+            addr_cache_capacity,
         } = self;
 
+        // All files opened or cached on behalf of this instance,
+        // including those of worker threads, count against a single
+        // budget each.
+        let fd_budget = fd_budget.unwrap_or_else(|| Arc::new(FdBudget::new(max_open_files)));
+        let entry_budget =
+            entry_budget.unwrap_or_else(|| Arc::new(EntryBudget::new(max_cached_files)));
+
         let find_sym_opts = match (code_info, inlined_fns) {
             (false, inlined_fns) => {
                 if inlined_fns {
@@ -390,25 +1092,146 @@ impl Builder {
             (true, true) => FindSymOpts::CodeInfoAndInlined,
         };
 
+        #[cfg(feature = "apk")]
+        let have_dispatch = apk_dispatch.is_some();
+        #[cfg(not(feature = "apk"))]
+        let have_dispatch = false;
+        let have_dispatch =
+            have_dispatch || process_dispatch.is_some() || shared_mem_dispatch.is_some();
+
+        let worker_config = if parallelism > 1 {
+            if have_dispatch {
+                log::warn!(
+                    "parallel symbolization asked for but a dispatch function is set; setting is being ignored"
+                );
+                None
+            } else {
+                Some(WorkerConfig {
+                    threads: parallelism,
+                    auto_reload,
+                    strict_file_access,
+                    cache_keys: cache_keys.clone(),
+                    code_info,
+                    inlined_fns,
+                    max_inlined_depth,
+                    demangle,
+                    intern_names,
+                    qualified_names,
+                    elf_sym_policy,
+                    source_root: source_root.clone(),
+                    cache_dir: cache_dir.clone(),
+                    #[cfg(feature = "dwarf")]
+                    debug_dirs: debug_dirs.clone(),
+                    #[cfg(feature = "gsym")]
+                    gsym_forward_compat,
+                    fd_budget: Arc::clone(&fd_budget),
+                    entry_budget: Arc::clone(&entry_budget),
+                    addr_cache_capacity,
+                })
+            }
+        } else {
+            None
+        };
+
         Symbolizer {
             #[cfg(feature = "apk")]
-            apk_cache: FileCache::builder().enable_auto_reload(auto_reload).build(),
+            apk_cache: FileCache::builder()
+                .enable_auto_reload(auto_reload)
+                .enable_strict(strict_file_access)
+                .set_keys(cache_keys.clone())
+                .set_fd_budget(Some(Arc::clone(&fd_budget)))
+                .set_entry_budget(Some(Arc::clone(&entry_budget)))
+                .build(),
             #[cfg(feature = "breakpad")]
-            breakpad_cache: FileCache::builder().enable_auto_reload(auto_reload).build(),
-            elf_cache: FileCache::builder().enable_auto_reload(auto_reload).build(),
+            breakpad_cache: FileCache::builder()
+                .enable_auto_reload(auto_reload)
+                .enable_strict(strict_file_access)
+                .set_keys(cache_keys.clone())
+                .set_fd_budget(Some(Arc::clone(&fd_budget)))
+                .set_entry_budget(Some(Arc::clone(&entry_budget)))
+                .build(),
+            elf_cache: FileCache::builder()
+                .enable_auto_reload(auto_reload)
+                .enable_strict(strict_file_access)
+                .set_keys(cache_keys.clone())
+                .set_fd_budget(Some(Arc::clone(&fd_budget)))
+                .set_entry_budget(Some(Arc::clone(&entry_budget)))
+                .build(),
             #[cfg(feature = "gsym")]
-            gsym_cache: FileCache::builder().enable_auto_reload(auto_reload).build(),
-            ksym_cache: FileCache::builder().enable_auto_reload(auto_reload).build(),
-            perf_map_cache: FileCache::builder().enable_auto_reload(auto_reload).build(),
+            gsym_cache: FileCache::builder()
+                .enable_auto_reload(auto_reload)
+                .enable_strict(strict_file_access)
+                .set_keys(cache_keys.clone())
+                .set_fd_budget(Some(Arc::clone(&fd_budget)))
+                .set_entry_budget(Some(Arc::clone(&entry_budget)))
+                .build(),
+            ksym_cache: FileCache::builder()
+                .enable_auto_reload(auto_reload)
+                .enable_strict(strict_file_access)
+                .set_keys(cache_keys.clone())
+                .set_entry_budget(Some(Arc::clone(&entry_budget)))
+                .build(),
+            #[cfg(feature = "macho")]
+            macho_cache: FileCache::builder()
+                .enable_auto_reload(auto_reload)
+                .enable_strict(strict_file_access)
+                .set_keys(cache_keys.clone())
+                .set_fd_budget(Some(Arc::clone(&fd_budget)))
+                .set_entry_budget(Some(Arc::clone(&entry_budget)))
+                .build(),
+            #[cfg(feature = "windows")]
+            pe_cache: FileCache::builder()
+                .enable_auto_reload(auto_reload)
+                .enable_strict(strict_file_access)
+                .set_keys(cache_keys.clone())
+                .set_fd_budget(Some(Arc::clone(&fd_budget)))
+                .set_entry_budget(Some(Arc::clone(&entry_budget)))
+                .build(),
+            jitdump_cache: FileCache::builder()
+                .enable_auto_reload(auto_reload)
+                .enable_strict(strict_file_access)
+                .set_keys(cache_keys.clone())
+                .set_fd_budget(Some(Arc::clone(&fd_budget)))
+                .set_entry_budget(Some(Arc::clone(&entry_budget)))
+                .build(),
+            perf_map_cache: FileCache::builder()
+                .enable_auto_reload(auto_reload)
+                .enable_strict(strict_file_access)
+                .set_keys(cache_keys.clone())
+                .set_fd_budget(Some(Arc::clone(&fd_budget)))
+                .set_entry_budget(Some(Arc::clone(&entry_budget)))
+                .build(),
             process_vma_cache: RefCell::new(HashMap::new()),
             process_cache: InsertMap::new(),
+            shared_mem_cache: InsertMap::new(),
+            #[cfg(unix)]
+            sym_socks: RefCell::new(HashMap::new()),
             find_sym_opts,
+            names_only: Cell::new(false),
+            max_inlined_depth,
             demangle,
+            intern_names,
+            qualified_names,
+            elf_sym_policy,
+            source_root,
+            lossy_paths,
+            index_cache: cache_dir.map(IndexCache::new),
             #[cfg(feature = "dwarf")]
             debug_dirs,
+            #[cfg(feature = "gsym")]
+            gsym_forward_compat,
             #[cfg(feature = "apk")]
             apk_dispatch,
             process_dispatch,
+            shared_mem_dispatch,
+            unknown_addr_resolver,
+            worker_config,
+            worker_pool: OnceCell::new(),
+            fd_budget,
+            entry_budget,
+            addr_cache: addr_cache_capacity
+                .filter(|capacity| *capacity > 0)
+                .map(AddrCache::new),
         }
     }
 }
@@ -417,19 +1240,160 @@ impl Default for Builder {
     fn default() -> Self {
         Self {
             auto_reload: true,
+            strict_file_access: false,
+            cache_keys: Vec::new(),
             code_info: true,
             inlined_fns: true,
+            max_inlined_depth: None,
             demangle: true,
+            intern_names: false,
+            qualified_names: false,
+            elf_sym_policy: ElfSymPolicy::default(),
+            source_root: None,
+            lossy_paths: false,
+            cache_dir: None,
             #[cfg(feature = "dwarf")]
             debug_dirs: DEFAULT_DEBUG_DIRS
                 .iter()
                 .map(PathBuf::from)
                 .collect::<Vec<_>>(),
+            #[cfg(feature = "gsym")]
+            gsym_forward_compat: false,
+            #[cfg(feature = "apk")]
+            apk_dispatch: None,
+            process_dispatch: None,
+            shared_mem_dispatch: None,
+            unknown_addr_resolver: None,
+            parallelism: 1,
+            max_open_files: None,
+            max_cached_files: None,
+            addr_cache_capacity: None,
+        }
+    }
+}
+
+
+/// The configuration of the [`Symbolizer`] instances used by worker
+/// threads for parallel symbolization.
+#[derive(Clone, Debug)]
+struct WorkerConfig {
+    /// The number of worker threads.
+    threads: usize,
+    auto_reload: bool,
+    strict_file_access: bool,
+    cache_keys: Vec<(PathBuf, CacheKey)>,
+    code_info: bool,
+    inlined_fns: bool,
+    max_inlined_depth: Option<usize>,
+    demangle: bool,
+    intern_names: bool,
+    qualified_names: bool,
+    elf_sym_policy: ElfSymPolicy,
+    source_root: Option<PathBuf>,
+    cache_dir: Option<PathBuf>,
+    #[cfg(feature = "dwarf")]
+    debug_dirs: Vec<PathBuf>,
+    #[cfg(feature = "gsym")]
+    gsym_forward_compat: bool,
+    /// The budget for open files, shared with the main instance.
+    fd_budget: Arc<FdBudget>,
+    /// The budget for cached files, shared with the main instance.
+    entry_budget: Arc<EntryBudget>,
+    addr_cache_capacity: Option<usize>,
+}
+
+impl WorkerConfig {
+    /// Create a [`Symbolizer`] for use by a worker thread.
+    fn build(&self) -> Symbolizer {
+        let Self {
+            threads: _,
+            auto_reload,
+            strict_file_access,
+            cache_keys,
+            code_info,
+            inlined_fns,
+            max_inlined_depth,
+            demangle,
+            intern_names,
+            qualified_names,
+            elf_sym_policy,
+            source_root,
+            cache_dir,
+            #[cfg(feature = "dwarf")]
+            debug_dirs,
+            #[cfg(feature = "gsym")]
+            gsym_forward_compat,
+            fd_budget,
+            entry_budget,
+            addr_cache_capacity,
+        } = self;
+
+        Builder {
+            auto_reload: *auto_reload,
+            strict_file_access: *strict_file_access,
+            cache_keys: cache_keys.clone(),
+            code_info: *code_info,
+            inlined_fns: *inlined_fns,
+            max_inlined_depth: *max_inlined_depth,
+            demangle: *demangle,
+            intern_names: *intern_names,
+            qualified_names: *qualified_names,
+            elf_sym_policy: *elf_sym_policy,
+            source_root: source_root.clone(),
+            // Paths are converted as necessary on the calling thread.
+            lossy_paths: false,
+            cache_dir: cache_dir.clone(),
+            #[cfg(feature = "dwarf")]
+            debug_dirs: debug_dirs.clone(),
+            #[cfg(feature = "gsym")]
+            gsym_forward_compat: *gsym_forward_compat,
             #[cfg(feature = "apk")]
             apk_dispatch: None,
             process_dispatch: None,
+            shared_mem_dispatch: None,
+            unknown_addr_resolver: None,
+            parallelism: 1,
+            max_open_files: fd_budget.max_open(),
+            max_cached_files: entry_budget.max_entries(),
+            addr_cache_capacity: *addr_cache_capacity,
+        }
+        .build_impl(Some(Arc::clone(fd_budget)), Some(Arc::clone(entry_budget)))
+    }
+}
+
+
+/// Find the file to use for symbolizing addresses normalized to the
+/// ELF file described by `elf`, as configured by `store`.
+fn find_normalized_elf(elf: &normalize::Elf<'_>, store: &SymbolStore) -> Result<Option<PathBuf>> {
+    if let Some(build_id) = &elf.build_id {
+        if let Some(path) = store.build_ids.get(&**build_id) {
+            return Ok(Some(path.clone()))
         }
+
+        for dir in &store.build_id_dirs {
+            if let Some(path) = build_id_debug_path(dir, build_id) {
+                if path.exists() {
+                    return Ok(Some(path))
+                }
+            }
+        }
+    }
+
+    if store.recorded_paths && elf.path.exists() {
+        if let Some(build_id) = &elf.build_id {
+            // The file may have been replaced in the mean time and we
+            // must not report bogus symbols for it.
+            if read_elf_build_id_cached(&elf.path)?.as_deref() != Some(&**build_id) {
+                log::debug!(
+                    "build ID of `{}` does not match recorded one",
+                    elf.path.display()
+                );
+                return Ok(None)
+            }
+        }
+        return Ok(Some(elf.path.clone()))
     }
+    Ok(None)
 }
 
 
@@ -441,28 +1405,33 @@ struct SymbolizeHandler<'sym> {
     /// Whether or not to consult debug symbols to satisfy the request
     /// (if present).
     debug_syms: bool,
-    /// Whether or not to consult the process' perf map (if any) to
-    /// satisfy the request.
+    /// Whether or not to consult the process' perf map and jitdump
+    /// file (if any) to satisfy the request.
     perf_map: bool,
+    /// The process' jitdump file, once looked up.
+    jitdump: Option<Option<EntryPath>>,
+    /// A resolver for the process' vDSO, once created.
+    vdso: Option<ElfResolver>,
     /// Whether to work with `/proc/<pid>/map_files/` entries or with
     /// symbolic paths mentioned in `/proc/<pid>/maps` instead.
     map_files: bool,
+    /// The process' root directory, relative to which to resolve
+    /// symbolic paths, if any.
+    proc_root: Option<PathBuf>,
     /// Symbols representing the symbolized addresses.
     all_symbols: Vec<Symbolized<'sym>>,
+    /// Per-module statistics, if requested.
+    modules: Option<HashMap<PathBuf, ModuleStats>>,
 }
 
 impl SymbolizeHandler<'_> {
     #[cfg(feature = "apk")]
     fn handle_apk_addr(&mut self, addr: Addr, file_off: u64, entry_path: &EntryPath) -> Result<()> {
-        let apk_path = if self.map_files {
-            &entry_path.maps_file
-        } else {
-            &entry_path.symbolic_path
-        };
+        let apk_path = entry_path.access_path(self.map_files, self.proc_root.as_deref());
 
         match self
             .symbolizer
-            .apk_resolver(apk_path, file_off, self.debug_syms)?
+            .apk_resolver(&apk_path, file_off, self.debug_syms)?
         {
             Some((elf_resolver, elf_addr)) => {
                 let symbol = self.symbolizer.symbolize_with_resolver(
@@ -477,22 +1446,25 @@ impl SymbolizeHandler<'_> {
     }
 
     fn handle_elf_addr(&mut self, addr: Addr, file_off: u64, entry_path: &EntryPath) -> Result<()> {
-        let path = if self.map_files {
-            &entry_path.maps_file
-        } else {
-            &entry_path.symbolic_path
-        };
+        let path = entry_path.access_path(self.map_files, self.proc_root.as_deref());
 
-        let resolver = self
-            .symbolizer
-            .elf_cache
-            .elf_resolver(path, self.symbolizer.maybe_debug_dirs(self.debug_syms))?;
+        let resolver = self.symbolizer.elf_cache.elf_resolver(
+            &path,
+            self.symbolizer.maybe_debug_dirs(self.debug_syms),
+            self.symbolizer.qualified_names,
+            self.symbolizer.index_cache.as_ref(),
+        )?;
+
+        if let Some(reason) = self.symbolizer.check_elf_sym_policy(resolver)? {
+            let () = self.handle_unknown_addr(addr, reason);
+            return Ok(())
+        }
 
         match resolver.file_offset_to_virt_offset(file_off)? {
             Some(addr) => {
                 let symbol = self
                     .symbolizer
-                    .symbolize_with_resolver(addr, &Resolver::Cached(resolver.deref()))?;
+                    .symbolize_with_elf_resolver(addr, resolver)?;
                 let () = self.all_symbols.push(symbol);
             }
             None => self.handle_unknown_addr(addr, Reason::InvalidFileOffset),
@@ -500,34 +1472,172 @@ impl SymbolizeHandler<'_> {
         Ok(())
     }
 
-    fn handle_perf_map_addr(&mut self, addr: Addr) -> Result<()> {
-        if let Some(perf_map) = self.symbolizer.perf_map(self.pid)? {
-            let symbolized = self
-                .symbolizer
-                .symbolize_with_resolver(addr, &Resolver::Cached(perf_map))?;
-            let () = self.all_symbols.push(symbolized);
-        } else {
-            let () = self.handle_unknown_addr(addr, Reason::UnknownAddr);
+    fn handle_shared_mem_addr(
+        &mut self,
+        addr: Addr,
+        file_off: u64,
+        entry: &MapsEntry,
+        entry_path: &EntryPath,
+    ) -> Result<()> {
+        let resolver = self
+            .symbolizer
+            .shared_mem_dispatch_resolver(entry, entry_path)?;
+        match resolver {
+            Some(resolver) => match resolver.file_offset_to_virt_offset(file_off)? {
+                Some(addr) => {
+                    let symbol = self.symbolizer.symbolize_with_resolver(
+                        addr,
+                        &Resolver::Cached(resolver.as_symbolize()),
+                    )?;
+                    let () = self.all_symbols.push(symbol);
+                }
+                None => self.handle_unknown_addr(addr, Reason::InvalidFileOffset),
+            },
+            None => self.handle_unknown_addr(addr, Reason::Unsupported),
         }
         Ok(())
     }
-}
 
-impl normalize::Handler<Reason> for SymbolizeHandler<'_> {
-    #[cfg_attr(feature = "tracing", crate::log::instrument(skip_all, fields(addr = format_args!("{_addr:#x}"), ?reason)))]
-    fn handle_unknown_addr(&mut self, _addr: Addr, reason: Reason) {
-        let () = self.all_symbols.push(Symbolized::Unknown(reason));
+    /// Account for the most recently symbolized address as belonging
+    /// to the module at `path`.
+    fn record_module(&mut self, path: &Path) {
+        if let Some(modules) = &mut self.modules {
+            // SANITY: This method is only ever called after an address
+            //         got symbolized.
+            let symbolized = self.all_symbols.last().unwrap();
+            if let Some(stats) = modules.get_mut(path) {
+                let () = stats.record(symbolized);
+            } else {
+                let mut stats = ModuleStats::default();
+                let () = stats.record(symbolized);
+                let _prev = modules.insert(path.to_path_buf(), stats);
+            }
+        }
     }
 
-    #[cfg_attr(feature = "tracing", crate::log::instrument(skip_all, fields(addr = format_args!("{addr:#x}"), entry = ?DebugMapsEntry(entry))))]
-    fn handle_entry_addr(&mut self, addr: Addr, entry: &MapsEntry) -> Result<()> {
-        if let Some(path_name) = &entry.path_name {
+    fn handle_anon_addr(&mut self, addr: Addr, entry: &MapsEntry) -> Result<()> {
+        if (entry.perm & Perm::X) != Perm::default() {
+            #[cfg(unix)]
+            match self.symbolizer.query_sym_sock(&self.pid, addr) {
+                Ok(Some(sym)) => {
+                    let symbolized = self
+                        .symbolizer
+                        .symbolize_with_resolver(addr, &Resolver::Uncached(&sym))?;
+                    let () = self.all_symbols.push(symbolized);
+                    return Ok(())
+                }
+                Ok(None) => (),
+                Err(()) => {
+                    let () = self
+                        .all_symbols
+                        .push(Symbolized::Unknown(Reason::UnknownAddr));
+                    return Ok(())
+                }
+            }
+
             if let Some(resolver) = self
                 .symbolizer
-                .process_dispatch_resolver(entry.range.clone(), path_name)?
+                .unknown_addr_resolver(&self.pid, addr, entry)?
             {
-                let file_off = addr - entry.range.start + entry.offset;
-                let () = match resolver.file_offset_to_virt_offset(file_off)? {
+                let symbolized = self
+                    .symbolizer
+                    .symbolize_with_resolver(addr, &Resolver::Uncached(resolver.as_ref()))?;
+                if let Symbolized::Sym(..) = symbolized {
+                    let () = self.all_symbols.push(symbolized);
+                    return Ok(())
+                }
+            }
+        }
+
+        if self.perf_map {
+            self.handle_perf_map_addr(addr)
+        } else {
+            let () = self.handle_unknown_addr(addr, Reason::UnknownAddr);
+            Ok(())
+        }
+    }
+
+    fn handle_perf_map_addr(&mut self, addr: Addr) -> Result<()> {
+        if let Some(perf_map) = self.symbolizer.perf_map(&self.pid)? {
+            let symbolized = self
+                .symbolizer
+                .symbolize_with_resolver(addr, &Resolver::Cached(perf_map))?;
+            if let Symbolized::Sym(..) = symbolized {
+                let () = self.all_symbols.push(symbolized);
+                return Ok(())
+            }
+        }
+        self.handle_jitdump_addr(addr)
+    }
+
+    fn handle_jitdump_addr(&mut self, addr: Addr) -> Result<()> {
+        let entry_path = match &self.jitdump {
+            Some(entry_path) => entry_path,
+            None => self.jitdump.insert(JitDump::find(&self.pid)?),
+        };
+
+        if let Some(entry_path) = entry_path {
+            let path = entry_path.access_path(self.map_files, self.proc_root.as_deref());
+
+            let jitdump = self.symbolizer.jitdump(&path)?;
+            // Make sure that the file actually belongs to the process
+            // in question.
+            let () = self.pid.check_alive()?;
+            if let Some(jitdump) = jitdump {
+                let symbolized = self
+                    .symbolizer
+                    .symbolize_with_resolver(addr, &Resolver::Cached(jitdump))?;
+                let () = self.all_symbols.push(symbolized);
+                return Ok(())
+            }
+        }
+
+        let () = self.handle_unknown_addr(addr, Reason::UnknownAddr);
+        Ok(())
+    }
+
+    /// Symbolize `addr`, which belongs to the kernel provided
+    /// pseudo-module mapped by `entry`.
+    fn handle_pseudo_module_addr(
+        &mut self,
+        addr: Addr,
+        entry: &MapsEntry,
+        component: &str,
+    ) -> Result<()> {
+        let file_off = addr - entry.range.start + entry.offset;
+        let symbolized = match component {
+            vdso::VDSO => {
+                let resolver = match &self.vdso {
+                    Some(resolver) => resolver,
+                    None => self
+                        .vdso
+                        .insert(vdso::vdso_resolver(&self.pid, entry.range.clone())?),
+                };
+                match resolver.file_offset_to_virt_offset(file_off)? {
+                    Some(addr) => self
+                        .symbolizer
+                        .symbolize_with_resolver(addr, &Resolver::Uncached(resolver))?,
+                    None => Symbolized::Unknown(Reason::InvalidFileOffset),
+                }
+            }
+            vdso::VSYSCALL => self
+                .symbolizer
+                .symbolize_with_resolver(file_off, &Resolver::Uncached(&Vsyscall))?,
+            _ => Symbolized::Unknown(Reason::from_component(component)),
+        };
+        let () = self.all_symbols.push(symbolized);
+        Ok(())
+    }
+
+    /// Symbolize `addr`, which belongs to the memory mapping `entry`.
+    fn handle_entry_addr_impl(&mut self, addr: Addr, entry: &MapsEntry) -> Result<()> {
+        if let Some(path_name) = &entry.path_name {
+            if let Some(resolver) = self
+                .symbolizer
+                .process_dispatch_resolver(entry.range.clone(), path_name)?
+            {
+                let file_off = addr - entry.range.start + entry.offset;
+                let () = match resolver.file_offset_to_virt_offset(file_off)? {
                     Some(addr) => {
                         let symbol = self.symbolizer.symbolize_with_resolver(
                             addr,
@@ -537,6 +1647,9 @@ impl normalize::Handler<Reason> for SymbolizeHandler<'_> {
                     }
                     None => self.handle_unknown_addr(addr, Reason::InvalidFileOffset),
                 };
+                if let PathName::Path(entry_path) = path_name {
+                    let () = self.record_module(&entry_path.symbolic_path);
+                }
                 return Ok(())
             }
 
@@ -552,28 +1665,80 @@ impl normalize::Handler<Reason> for SymbolizeHandler<'_> {
                     .symbolic_path
                     .extension()
                     .unwrap_or_else(|| OsStr::new(""));
-                match ext.to_str() {
+                let () = match ext.to_str() {
+                    _ if is_shared_mem(&entry_path.symbolic_path) => {
+                        self.handle_shared_mem_addr(addr, file_off, entry, entry_path)
+                    }
                     #[cfg(feature = "apk")]
                     Some("apk") | Some("zip") => self.handle_apk_addr(addr, file_off, entry_path),
                     _ => self.handle_elf_addr(addr, file_off, entry_path),
-                }
+                }?;
+                // The file may have been opened via
+                // `/proc/<pid>/map_files/` or `/proc/<pid>/root/`. Make
+                // sure that it actually belongs to the process in
+                // question.
+                let () = self.pid.check_alive()?;
+                let () = self.record_module(&entry_path.symbolic_path);
+                Ok(())
             }
-            Some(PathName::Component(..)) => {
-                let () = self.handle_unknown_addr(addr, Reason::Unsupported);
+            Some(PathName::Component(component)) => {
+                let () = self.handle_pseudo_module_addr(addr, entry, component)?;
+                let () = self.record_module(Path::new(component));
                 Ok(())
             }
             // If there is no path associated with this entry, we don't
             // really have any idea what the address may belong to. But
-            // there is a chance that the address is part of the perf
-            // map, so check that.
+            // there is a chance that the address is covered by a
+            // registered symbol socket or part of the perf map, so
+            // check that.
             // TODO: It's not entirely clear if a perf map could also
             //       cover addresses belonging to entries with a path.
-            None if self.perf_map => self.handle_perf_map_addr(addr),
-            None => {
-                let () = self.handle_unknown_addr(addr, Reason::UnknownAddr);
-                Ok(())
+            None => self.handle_anon_addr(addr, entry),
+        }
+    }
+}
+
+impl normalize::Handler<Reason> for SymbolizeHandler<'_> {
+    #[cfg_attr(feature = "tracing", crate::log::instrument(skip_all, fields(addr = format_args!("{_addr:#x}"), ?reason)))]
+    fn handle_unknown_addr(&mut self, _addr: Addr, reason: Reason) {
+        let () = self.all_symbols.push(Symbolized::Unknown(reason));
+    }
+
+    #[cfg_attr(feature = "tracing", crate::log::instrument(skip_all, fields(addr = format_args!("{addr:#x}"), entry = ?DebugMapsEntry(entry))))]
+    fn handle_entry_addr(&mut self, addr: Addr, entry: &MapsEntry) -> Result<()> {
+        if let Err(err) = self.handle_entry_addr_impl(addr, entry) {
+            // Failure to access the file backing a single entry should
+            // not prevent symbolization of addresses in other entries.
+            match Reason::from_file_error(&err) {
+                Some(reason) => {
+                    log::debug!("failed to symbolize address {addr:#x}: {err:?}");
+                    let () = self.handle_unknown_addr(addr, reason);
+                    return Ok(())
+                }
+                None => return Err(err),
+            }
+        }
+        // SANITY: Each handled address results in exactly one
+        //         symbolization result.
+        if let Symbolized::Sym(sym) = self.all_symbols.last_mut().unwrap() {
+            sym.perm = Some(entry.perm);
+            // Anonymous mappings and the like don't have a file backing
+            // them and so there is no meaningful module to report.
+            // Components we were able to symbolize in are pseudo-modules
+            // such as the vDSO, which we report by name.
+            let module = match &entry.path_name {
+                Some(PathName::Path(entry_path)) => Some(entry_path.symbolic_path.clone()),
+                Some(PathName::Component(component)) => Some(PathBuf::from(component)),
+                None => None,
+            };
+            if let Some(module) = module {
+                let file_off = addr - entry.range.start + entry.offset;
+                sym.module = Some(Cow::Owned(module));
+                sym.module_base = addr.checked_sub(file_off);
+                sym.file_offset = Some(file_off);
             }
         }
+        Ok(())
     }
 }
 
@@ -619,6 +1784,27 @@ impl<'tmp, 'slf: 'tmp> Resolver<'tmp, 'slf> {
 /// symbolization source, you may want to consider creating a new `Symbolizer`
 /// instance regularly.
 ///
+/// Parsed symbolization sources, process memory maps, and memoized
+/// symbolization results are cached on a per-instance basis. Services
+/// symbolizing on behalf of multiple parties (e.g., customers) that
+/// require these caches to be partitioned should use one instance per
+/// party. Cached data of an instance can be dropped selectively using
+/// [`Symbolizer::prune_cache`], or altogether by dropping the instance,
+/// without affecting other instances.
+///
+/// Some data are shared across instances, however:
+/// - build IDs read as part of process symbolization are kept in a process wide
+///   cache, keyed by device and inode number of the file they were read from
+/// - with [`Builder::enable_name_interning`], symbol names are stored in a
+///   process wide interner that is never flushed
+/// - with [`Builder::set_cache_dir`], symbol indexes are persisted to the
+///   provided directory and reused by all instances (and processes) using the
+///   same one
+///
+/// None of these contain data derived from anything but the files
+/// being symbolized, but instances that should not share them have to
+/// use different cache directories and not enable name interning.
+///
 /// # Notes
 /// Please note that demangling results are not cached.
 #[derive(Debug)]
@@ -630,7 +1816,17 @@ pub struct Symbolizer {
     elf_cache: FileCache<ElfResolverData>,
     #[cfg(feature = "gsym")]
     gsym_cache: FileCache<GsymResolver<'static>>,
-    ksym_cache: FileCache<Rc<KsymResolver>>,
+    /// Cache of kallsyms resolvers.
+    ///
+    /// Resolvers for the running kernel's symbols are replaced once
+    /// kernel modules are loaded or unloaded, if auto reloading is
+    /// enabled.
+    ksym_cache: FileCache<RefCell<Rc<KsymResolver>>>,
+    #[cfg(feature = "macho")]
+    macho_cache: FileCache<MachOResolver>,
+    #[cfg(feature = "windows")]
+    pe_cache: FileCache<PeResolver>,
+    jitdump_cache: FileCache<JitDump>,
     perf_map_cache: FileCache<PerfMap>,
     /// Cache of VMA data on per-process basis.
     ///
@@ -638,13 +1834,44 @@ pub struct Symbolizer {
     /// data by the user.
     process_vma_cache: RefCell<HashMap<Pid, Box<[maps::MapsEntry]>>>,
     process_cache: InsertMap<PathName, Option<Box<dyn Resolve>>>,
+    /// Resolvers for shared memory mappings, keyed by their
+    /// `map_files` path (which is unique to each mapping).
+    shared_mem_cache: InsertMap<PathBuf, Option<Box<dyn Resolve>>>,
+    /// Symbol sockets registered on a per-process basis.
+    #[cfg(unix)]
+    sym_socks: RefCell<HashMap<Pid, SymSock>>,
     find_sym_opts: FindSymOpts,
+    /// Whether only symbol names are to be looked up, overriding
+    /// `find_sym_opts` for the duration of a call.
+    names_only: Cell<bool>,
+    max_inlined_depth: Option<usize>,
     demangle: bool,
+    intern_names: bool,
+    qualified_names: bool,
+    elf_sym_policy: ElfSymPolicy,
+    source_root: Option<PathBuf>,
+    lossy_paths: bool,
+    index_cache: Option<IndexCache>,
     #[cfg(feature = "dwarf")]
     debug_dirs: Vec<PathBuf>,
+    #[cfg(feature = "gsym")]
+    gsym_forward_compat: bool,
     #[cfg(feature = "apk")]
     apk_dispatch: Option<Dbg<Box<dyn ApkDispatch>>>,
     process_dispatch: Option<Dbg<Box<dyn ProcessDispatch>>>,
+    shared_mem_dispatch: Option<Dbg<Box<dyn SharedMemDispatch>>>,
+    unknown_addr_resolver: Option<Dbg<Box<dyn UnknownAddrResolver>>>,
+    /// The configuration for parallel symbolization, if enabled.
+    worker_config: Option<WorkerConfig>,
+    /// The pool of worker threads used for parallel symbolization,
+    /// created lazily.
+    worker_pool: OnceCell<WorkerPool<Symbolizer>>,
+    /// The budget for files kept open by our caches.
+    fd_budget: Arc<FdBudget>,
+    /// The budget for files our caches hold data for.
+    entry_budget: Arc<EntryBudget>,
+    /// The cache of per-address symbolization results, if enabled.
+    addr_cache: Option<AddrCache>,
 }
 
 impl Symbolizer {
@@ -668,6 +1895,44 @@ impl Symbolizer {
         }
     }
 
+    /// Convert paths in `symbolized` to valid UTF-8, if asked for.
+    fn maybe_lossy_paths<'sym>(&self, symbolized: Symbolized<'sym>) -> Symbolized<'sym> {
+        if self.lossy_paths {
+            symbolized.into_lossy_paths()
+        } else {
+            symbolized
+        }
+    }
+
+    /// Report the provided source code location information relative
+    /// to the configured source root, if any and if applicable.
+    fn localize_code_info<'src>(&self, code_info: CodeInfo<'src>) -> CodeInfo<'src> {
+        let root = match &self.source_root {
+            Some(root) => root,
+            None => return code_info,
+        };
+
+        let path = code_info.to_path();
+        let relative = match path.strip_prefix(root) {
+            Ok(relative) if path.is_absolute() => relative,
+            _ => return code_info,
+        };
+        let file = match relative.file_name() {
+            Some(file) => Cow::Owned(file.to_os_string()),
+            None => return code_info,
+        };
+        let dir = relative
+            .parent()
+            .filter(|dir| !dir.as_os_str().is_empty())
+            .map(|dir| Cow::Owned(dir.to_path_buf()));
+
+        CodeInfo {
+            dir,
+            file,
+            ..code_info
+        }
+    }
+
     /// Symbolize an address using the provided [`SymResolver`].
     #[cfg_attr(feature = "tracing", crate::log::instrument(skip_all, fields(addr = format_args!("{addr:#x}"), resolver = ?resolver.inner())))]
     fn symbolize_with_resolver<'slf>(
@@ -675,8 +1940,17 @@ impl Symbolizer {
         addr: Addr,
         resolver: &Resolver<'_, 'slf>,
     ) -> Result<Symbolized<'slf>> {
-        let (sym_name, sym_addr, sym_size, code_info, inlined) = match resolver {
-            Resolver::Uncached(resolver) => match resolver.find_sym(addr, &self.find_sym_opts)? {
+        let (
+            sym_name,
+            sym_addr,
+            sym_size,
+            code_info,
+            mut inlined,
+            mut inlined_truncated,
+            section,
+            module,
+        ) = match resolver {
+            Resolver::Uncached(resolver) => match resolver.find_sym(addr, self.find_sym_opts())? {
                 Ok(sym) => {
                     let ResolvedSym {
                         name,
@@ -685,6 +1959,9 @@ impl Symbolizer {
                         lang,
                         code_info,
                         inlined,
+                        inlined_truncated,
+                        section,
+                        module,
                     } = sym;
 
                     let name =
@@ -706,12 +1983,31 @@ impl Symbolizer {
                         })
                         .collect::<Vec<_>>()
                         .into_boxed_slice();
+                    let section = section.map(|section| Cow::Owned(section.to_string()));
+                    let module = module.map(|module| Cow::Owned(PathBuf::from(module)));
 
-                    (name, addr, size, code_info, inlined)
+                    (
+                        name,
+                        addr,
+                        size,
+                        code_info,
+                        inlined,
+                        inlined_truncated,
+                        section,
+                        module,
+                    )
+                }
+                Err(reason) => {
+                    let symbolized = match resolver.find_code_info(addr, self.find_sym_opts())? {
+                        Some(code_info) => {
+                            Symbolized::Code(self.localize_code_info(code_info.to_owned()))
+                        }
+                        None => Symbolized::Unknown(reason),
+                    };
+                    return Ok(symbolized)
                 }
-                Err(reason) => return Ok(Symbolized::Unknown(reason)),
             },
-            Resolver::Cached(resolver) => match resolver.find_sym(addr, &self.find_sym_opts)? {
+            Resolver::Cached(resolver) => match resolver.find_sym(addr, self.find_sym_opts())? {
                 Ok(sym) => {
                     let ResolvedSym {
                         name,
@@ -720,6 +2016,9 @@ impl Symbolizer {
                         lang,
                         code_info,
                         mut inlined,
+                        inlined_truncated,
+                        section,
+                        module,
                     } = sym;
 
                     let name = self.maybe_demangle(Cow::Borrowed(name), lang);
@@ -727,12 +2026,53 @@ impl Symbolizer {
                         let name = take(&mut inlined_fn.name);
                         inlined_fn.name = self.maybe_demangle(name, lang);
                     });
-                    (name, addr, size, code_info, inlined)
+                    (
+                        name,
+                        addr,
+                        size,
+                        code_info,
+                        inlined,
+                        inlined_truncated,
+                        section.map(Cow::Borrowed),
+                        module.map(|module| Cow::Borrowed(Path::new(module))),
+                    )
+                }
+                Err(reason) => {
+                    let symbolized = match resolver.find_code_info(addr, self.find_sym_opts())? {
+                        Some(code_info) => Symbolized::Code(self.localize_code_info(code_info)),
+                        None => Symbolized::Unknown(reason),
+                    };
+                    return Ok(symbolized)
                 }
-                Err(reason) => return Ok(Symbolized::Unknown(reason)),
             },
         };
 
+        if let Some(max_depth) = self.max_inlined_depth {
+            if inlined.len() > max_depth {
+                let mut truncated = Vec::from(inlined);
+                let () = truncated.truncate(max_depth);
+                inlined = truncated.into_boxed_slice();
+                inlined_truncated = true;
+            }
+        }
+
+        let code_info = code_info.map(|info| self.localize_code_info(info));
+        let () = inlined.iter_mut().for_each(|inlined_fn| {
+            inlined_fn.code_info = inlined_fn
+                .code_info
+                .take()
+                .map(|info| self.localize_code_info(info));
+        });
+
+        let sym_name = if self.intern_names {
+            let () = inlined.iter_mut().for_each(|inlined_fn| {
+                inlined_fn.name = intern_owned(take(&mut inlined_fn.name));
+            });
+            intern_owned(sym_name)
+        } else {
+            sym_name
+        };
+
         let sym = Sym {
             name: sym_name,
             addr: sym_addr,
@@ -740,6 +2080,13 @@ impl Symbolizer {
             size: sym_size,
             code_info,
             inlined,
+            inlined_truncated,
+            section,
+            perm: None,
+            addr_adjust: 0,
+            module,
+            module_base: None,
+            file_offset: None,
             _non_exhaustive: (),
         };
         Ok(Symbolized::Sym(sym))
@@ -757,16 +2104,99 @@ impl Symbolizer {
             .collect()
     }
 
+    /// Retrieve the resolver for the original file of a BOLT optimized
+    /// one, if any.
+    fn bolt_original_resolver(
+        &self,
+        original: Option<&Path>,
+        debug_syms: bool,
+    ) -> Result<Option<&ElfResolver>> {
+        original
+            .map(|original| {
+                self.elf_cache
+                    .elf_resolver(
+                        original,
+                        self.maybe_debug_dirs(debug_syms),
+                        self.qualified_names,
+                        self.index_cache.as_ref(),
+                    )
+                    .map(|resolver| resolver.deref())
+            })
+            .transpose()
+    }
+
+    /// Symbolize `addr`, a virtual offset in the ELF file represented
+    /// by `resolver`, consulting the per-address result cache, if
+    /// enabled.
+    fn symbolize_with_elf_resolver<'slf>(
+        &'slf self,
+        addr: Addr,
+        resolver: &'slf Rc<ElfResolver>,
+    ) -> Result<Symbolized<'slf>> {
+        let addr_cache = match &self.addr_cache {
+            Some(addr_cache) => addr_cache,
+            None => return self.symbolize_with_resolver(addr, &Resolver::Cached(resolver.deref())),
+        };
+
+        let names_only = self.names_only.get();
+        if let Some(symbolized) = addr_cache.get(resolver, addr, names_only) {
+            return Ok(symbolized)
+        }
+
+        let symbolized = self.symbolize_with_resolver(addr, &Resolver::Cached(resolver.deref()))?;
+        let () = addr_cache.insert(resolver, addr, names_only, &symbolized);
+        Ok(symbolized)
+    }
+
+    /// Symbolize `addr`, a virtual offset in the ELF file represented
+    /// by `resolver`.
+    ///
+    /// If `original` is present, `resolver` refers to a BOLT optimized
+    /// file and the address is translated and symbolized using the
+    /// original file instead.
+    ///
+    /// `bias`, if present, is the load bias of the file and used for
+    /// reporting the module's base address.
+    fn symbolize_elf_addr<'slf>(
+        &'slf self,
+        addr: Addr,
+        resolver: &'slf Rc<ElfResolver>,
+        original: Option<&'slf ElfResolver>,
+        bias: Option<Addr>,
+    ) -> Result<Symbolized<'slf>> {
+        if let Some(reason) = self.check_elf_sym_policy(original.unwrap_or(resolver.deref()))? {
+            return Ok(Symbolized::Unknown(reason))
+        }
+
+        let mut symbolized = match original {
+            Some(original) => match resolver.translate_bolt_addr(addr, original)? {
+                Some(addr) => self.symbolize_with_resolver(addr, &Resolver::Cached(original))?,
+                None => Symbolized::Unknown(Reason::UnknownAddr),
+            },
+            None => self.symbolize_with_elf_resolver(addr, resolver)?,
+        };
+
+        if let Symbolized::Sym(sym) = &mut symbolized {
+            let file_off = resolver.virt_offset_to_file_offset(addr)?;
+            sym.module = resolver.path().map(Cow::Borrowed);
+            sym.module_base = bias
+                .zip(file_off)
+                .and_then(|(bias, file_off)| (bias + addr).checked_sub(file_off));
+            sym.file_offset = file_off;
+        }
+        Ok(symbolized)
+    }
+
     #[cfg(feature = "gsym")]
     fn create_gsym_resolver(&self, path: &Path, file: &File) -> Result<GsymResolver<'static>> {
-        let resolver = GsymResolver::from_file(path.to_path_buf(), file)?;
+        let resolver = GsymResolver::from_file(path.to_path_buf(), file, self.gsym_forward_compat)?;
         Ok(resolver)
     }
 
     #[cfg(feature = "gsym")]
     fn gsym_resolver<'slf>(&'slf self, path: &Path) -> Result<&'slf GsymResolver<'static>> {
         let (file, cell) = self.gsym_cache.entry(path)?;
-        let resolver = cell.get_or_try_init(|| self.create_gsym_resolver(path, file))?;
+        let resolver = cell.get_or_try_init(|| self.create_gsym_resolver(path, &*file.get()?))?;
         Ok(resolver)
     }
 
@@ -806,10 +2236,20 @@ impl Symbolizer {
                         if let Some(resolver) = (apk_dispatch)(info.clone())? {
                             resolver
                         } else {
-                            default_apk_dispatcher(info, debug_dirs)?
+                            default_apk_dispatcher(
+                                info,
+                                debug_dirs,
+                                self.qualified_names,
+                                self.apk_cache.strict(),
+                            )?
                         }
                     } else {
-                        default_apk_dispatcher(info, debug_dirs)?
+                        default_apk_dispatcher(
+                            info,
+                            debug_dirs,
+                            self.qualified_names,
+                            self.apk_cache.strict(),
+                        )?
                     };
 
                     Ok(resolver)
@@ -835,7 +2275,7 @@ impl Symbolizer {
     ) -> Result<Option<(&'slf dyn Resolve, Addr)>> {
         let (file, cell) = self.apk_cache.entry(path)?;
         let (apk, resolvers) = cell.get_or_try_init(|| {
-            let apk = zip::Archive::with_mmap(Mmap::builder().map(file)?)?;
+            let apk = zip::Archive::with_mmap(Mmap::builder().map(&*file.get()?)?)?;
             let resolvers = InsertMap::new();
             Result::<_, Error>::Ok((apk, resolvers))
         })?;
@@ -854,21 +2294,95 @@ impl Symbolizer {
     #[cfg(feature = "breakpad")]
     fn breakpad_resolver<'slf>(&'slf self, path: &Path) -> Result<&'slf BreakpadResolver> {
         let (file, cell) = self.breakpad_cache.entry(path)?;
-        let resolver = cell.get_or_try_init(|| self.create_breakpad_resolver(path, file))?;
+        let resolver =
+            cell.get_or_try_init(|| self.create_breakpad_resolver(path, &*file.get()?))?;
+        Ok(resolver)
+    }
+
+    #[cfg(feature = "macho")]
+    fn macho_resolver<'slf>(
+        &'slf self,
+        path: &Path,
+        dsym: Option<&Path>,
+    ) -> Result<&'slf MachOResolver> {
+        let (_file, cell) = self.macho_cache.entry(path)?;
+        let resolver = cell.get_or_try_init(|| {
+            let dsym = dsym.map(Path::to_path_buf).or_else(|| find_dsym(path));
+            MachOResolver::open_impl(path, dsym.as_deref(), self.qualified_names)
+        })?;
         Ok(resolver)
     }
 
+    #[cfg(feature = "windows")]
+    fn pe_resolver<'slf>(&'slf self, path: &Path, pdb: Option<&Path>) -> Result<&'slf PeResolver> {
+        let (_file, cell) = self.pe_cache.entry(path)?;
+        let resolver = cell.get_or_try_init(|| PeResolver::open_impl(path, pdb))?;
+        Ok(resolver)
+    }
+
+    /// Query the symbol socket registered for the process with the
+    /// given `pid`, if any, for the symbol containing `addr`.
+    ///
+    /// A socket that fails to serve the query (e.g., because the server
+    /// did not respond in time or closed the connection) is
+    /// unregistered, as the request-response stream may be out of
+    /// sync. The failure is reported as `Err(())`.
+    #[cfg(unix)]
+    fn query_sym_sock(&self, pid: &Pid, addr: Addr) -> Result<Option<SockSym>, ()> {
+        let mut sym_socks = self.sym_socks.borrow_mut();
+        let result = match sym_socks.get_mut(pid) {
+            Some(sock) => sock.query(addr),
+            None => return Ok(None),
+        };
+
+        result.map_err(|err| {
+            log::warn!("dropping symbol socket for process {pid}: {err:#}");
+            let _sock = sym_socks.remove(pid);
+        })
+    }
+
+    fn unknown_addr_resolver(
+        &self,
+        pid: &Pid,
+        addr: Addr,
+        entry: &MapsEntry,
+    ) -> Result<Option<Box<dyn Symbolize>>> {
+        if let Some(Dbg(unknown_addr_resolver)) = &self.unknown_addr_resolver {
+            let info = UnknownAddrInfo {
+                pid,
+                addr,
+                range: entry.range.clone(),
+                _non_exhaustive: (),
+            };
+            (unknown_addr_resolver)(info)
+        } else {
+            Ok(None)
+        }
+    }
+
     fn create_perf_map(&self, path: &Path, file: &File) -> Result<PerfMap> {
         let perf_map = PerfMap::from_file(path, file)?;
         Ok(perf_map)
     }
 
-    fn perf_map(&self, pid: Pid) -> Result<Option<&PerfMap>> {
+    fn jitdump(&self, path: &Path) -> Result<Option<&JitDump>> {
+        match self.jitdump_cache.entry(path) {
+            Ok((file, cell)) => {
+                let jitdump = cell.get_or_try_init(|| JitDump::from_file(path, &*file.get()?))?;
+                Ok(Some(jitdump))
+            }
+            Err(err) if err.kind() == ErrorKind::NotFound => Ok(None),
+            Err(err) => Err(err).with_context(|| format!("failed to open jitdump file `{path:?}`")),
+        }
+    }
+
+    fn perf_map(&self, pid: &Pid) -> Result<Option<&PerfMap>> {
         let path = PerfMap::path(pid);
 
         match self.perf_map_cache.entry(&path) {
             Ok((file, cell)) => {
-                let perf_map = cell.get_or_try_init(|| self.create_perf_map(&path, file))?;
+                let perf_map =
+                    cell.get_or_try_init(|| self.create_perf_map(&path, &*file.get()?))?;
                 Ok(Some(perf_map))
             }
             Err(err) if err.kind() == ErrorKind::NotFound => Ok(None),
@@ -898,32 +2412,208 @@ impl Symbolizer {
         }
     }
 
+    fn shared_mem_dispatch_resolver<'slf>(
+        &'slf self,
+        entry: &MapsEntry,
+        entry_path: &EntryPath,
+    ) -> Result<Option<&'slf dyn Resolve>> {
+        if let Some(Dbg(shared_mem_dispatch)) = &self.shared_mem_dispatch {
+            let resolver =
+                self.shared_mem_cache
+                    .get_or_try_insert(entry_path.maps_file.clone(), || {
+                        let info = SharedMemInfo {
+                            range: entry.range.clone(),
+                            offset: entry.offset,
+                            path: entry_path,
+                            _non_exhaustive: (),
+                        };
+                        (shared_mem_dispatch)(info)
+                    })?;
+            Ok(resolver.as_deref())
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Symbolize the given list of user space addresses in the provided
+    /// process, distributing work among worker threads.
+    ///
+    /// `None` is returned if parallel symbolization is disabled or the
+    /// batch of addresses is too small to warrant it.
+    #[allow(clippy::too_many_arguments)]
+    fn symbolize_user_addrs_parallel(
+        &self,
+        addrs: &[Addr],
+        pid: &Pid,
+        debug_syms: bool,
+        perf_map: bool,
+        map_files: bool,
+        proc_root: bool,
+        vmas: Option<&[MapsEntry]>,
+        vdso: Option<&[u8]>,
+    ) -> Result<Option<Vec<Symbolized<'_>>>> {
+        /// The minimum number of addresses in a batch for us to bother
+        /// with parallel symbolization.
+        const MIN_PARALLEL_ADDRS: usize = 1024;
+
+        let config = match &self.worker_config {
+            Some(config) if addrs.len() >= MIN_PARALLEL_ADDRS => config,
+            _ => return Ok(None),
+        };
+        let pool = self.worker_pool.get_or_try_init(|| {
+            let config = config.clone();
+            WorkerPool::new(config.threads, move || config.build())
+        })?;
+
+        let vmas = if let Some(vmas) = vmas {
+            Arc::<[MapsEntry]>::from(vmas)
+        } else if let Some(cached) = self.process_vma_cache.borrow().get(pid) {
+            Arc::<[MapsEntry]>::from(&**cached)
+        } else {
+            maps::parse_filtered(pid)?.collect::<Result<Arc<[_]>>>()?
+        };
+
+        // Indices of addresses to symbolize on the calling thread and
+        // on each of the workers, respectively.
+        let mut local = Vec::new();
+        let mut remote = vec![Vec::new(); pool.len()];
+        let () = addrs.iter().enumerate().for_each(|(idx, addr)| {
+            let entry = util::find_range_by_key(&vmas, *addr, |entry| &entry.range)
+                .map(|entry_idx| &vmas[entry_idx]);
+            match entry {
+                Some(MapsEntry {
+                    path_name: Some(PathName::Path(path)),
+                    ..
+                }) if !is_shared_mem(&path.symbolic_path) => {
+                    let mut hasher = DefaultHasher::new();
+                    let () = path.symbolic_path.hash(&mut hasher);
+                    let worker = (hasher.finish() % pool.len() as u64) as usize;
+                    let () = remote[worker].push(idx);
+                }
+                _ => local.push(idx),
+            }
+        });
+
+        let (sender, receiver) = channel();
+        let mut pending = 0;
+        for (worker, idxs) in remote.into_iter().enumerate() {
+            if idxs.is_empty() {
+                continue
+            }
+
+            let worker_addrs = idxs.iter().map(|idx| addrs[*idx]).collect::<Vec<_>>();
+            let pid = pid.clone();
+            let vmas = Arc::clone(&vmas);
+            let sender = sender.clone();
+            let names_only = self.names_only.get();
+            let job = move |symbolizer: &Symbolizer| {
+                let result = symbolizer
+                    .with_names_only(names_only, || {
+                        symbolizer.symbolize_user_addrs(
+                            &worker_addrs,
+                            &pid,
+                            debug_syms,
+                            perf_map,
+                            map_files,
+                            proc_root,
+                            Some(&vmas),
+                            None,
+                            None,
+                        )
+                    })
+                    .map(|symbolized| {
+                        symbolized
+                            .into_iter()
+                            .map(Symbolized::into_owned)
+                            .collect::<Vec<_>>()
+                    });
+                // The receiver may be gone if symbolization on the
+                // calling thread failed in the meantime.
+                let _result = sender.send((idxs, result));
+            };
+            let () = pool.execute(worker, Box::new(job))?;
+            pending += 1;
+        }
+        let () = drop(sender);
+
+        let mut results = (0..addrs.len()).map(|_| None).collect::<Vec<_>>();
+        if !local.is_empty() {
+            let local_addrs = local.iter().map(|idx| addrs[*idx]).collect::<Vec<_>>();
+            let symbolized = self.symbolize_user_addrs(
+                &local_addrs,
+                pid,
+                debug_syms,
+                perf_map,
+                map_files,
+                proc_root,
+                Some(&vmas),
+                vdso,
+                None,
+            )?;
+            let () = local
+                .into_iter()
+                .zip(symbolized)
+                .for_each(|(idx, symbolized)| results[idx] = Some(symbolized));
+        }
+
+        for _ in 0..pending {
+            let (idxs, symbolized) = receiver.recv().map_err(|_err| {
+                Error::from(io::Error::new(
+                    io::ErrorKind::BrokenPipe,
+                    "symbolization worker terminated unexpectedly",
+                ))
+            })?;
+            let () = idxs
+                .into_iter()
+                .zip(symbolized?)
+                .for_each(|(idx, symbolized)| results[idx] = Some(symbolized));
+        }
+
+        // SANITY: Each address was assigned to exactly one of the
+        //         local or remote batches, all of which have been
+        //         symbolized at this point.
+        let results = results
+            .into_iter()
+            .map(|symbolized| symbolized.unwrap())
+            .collect();
+        Ok(Some(results))
+    }
+
     /// Symbolize the given list of user space addresses in the provided
     /// process.
+    #[allow(clippy::too_many_arguments)]
     fn symbolize_user_addrs(
         &self,
         addrs: &[Addr],
-        pid: Pid,
+        pid: &Pid,
         debug_syms: bool,
         perf_map: bool,
         map_files: bool,
-    ) -> Result<Vec<Symbolized>> {
+        proc_root: bool,
+        vmas: Option<&[MapsEntry]>,
+        vdso: Option<&[u8]>,
+        modules: Option<&mut HashMap<PathBuf, ModuleStats>>,
+    ) -> Result<Vec<Symbolized<'_>>> {
         let mut handler = SymbolizeHandler {
             symbolizer: self,
-            pid,
+            pid: pid.clone(),
             debug_syms,
             perf_map,
+            jitdump: None,
+            vdso: vdso.map(vdso::vdso_resolver_from_data).transpose()?,
             map_files,
+            proc_root: proc_root.then(|| maps::proc_root(pid)).flatten(),
             all_symbols: Vec::with_capacity(addrs.len()),
+            modules: modules.as_ref().map(|_| HashMap::new()),
         };
 
         let handler = util::with_ordered_elems(
             addrs,
             |handler: &mut SymbolizeHandler<'_>| handler.all_symbols.as_mut_slice(),
             |sorted_addrs| -> Result<SymbolizeHandler<'_>> {
-                if let Some(cached) = self.process_vma_cache.borrow().get(&pid) {
-                    let mut entry_iter = cached.iter().map(Ok);
-                    let entries = |_addr| entry_iter.next();
+                let cache = self.process_vma_cache.borrow();
+                if let Some(cached) = vmas.or_else(|| cache.get(pid).map(|entries| &**entries)) {
+                    let entries = maps::entry_lookup(cached);
 
                     let () = normalize_sorted_user_addrs_with_entries(
                         sorted_addrs,
@@ -944,19 +2634,96 @@ impl Symbolizer {
                 }
             },
         )?;
+
+        if let Some(modules) = modules {
+            // SANITY: We populated the handler's module statistics
+            //         if and only if they were requested.
+            *modules = handler.modules.unwrap();
+        }
         Ok(handler.all_symbols)
     }
 
-    fn create_ksym_resolver(&self, path: &Path, file: &File) -> Result<Rc<KsymResolver>> {
-        let resolver = KsymResolver::load_from_reader(file, path)?;
-        let resolver = Rc::new(resolver);
-        Ok(resolver)
-    }
+    /// Symbolize a list of absolute addresses captured in a core dump.
+    fn symbolize_core_dump_addrs(
+        &self,
+        addrs: &[Addr],
+        core: &CoreDump,
+        mut modules: Option<&mut HashMap<PathBuf, ModuleStats>>,
+    ) -> Result<Vec<Symbolized<'_>>> {
+        let CoreDump {
+            path,
+            exe,
+            debug_syms,
+            _non_exhaustive: (),
+        } = core;
 
-    fn ksym_resolver<'slf>(&'slf self, path: &Path) -> Result<&'slf Rc<KsymResolver>> {
-        let (file, cell) = self.ksym_cache.entry(path)?;
-        let resolver = cell.get_or_try_init(|| self.create_ksym_resolver(path, file))?;
-        Ok(resolver)
+        let parser = ElfParser::open(path)?;
+        let maps = CoreMaps::parse(&parser)?;
+        let exe_path = maps.exe_path();
+
+        addrs
+            .iter()
+            .map(|addr| {
+                let map = match maps.find(*addr) {
+                    Some(map) => map,
+                    None => return Ok(Symbolized::Unknown(Reason::Unmapped)),
+                };
+                // The main executable is looked up at the user provided
+                // path, everything else where it was at the time of the
+                // dump.
+                let path = if exe_path == Some(map.path.as_path()) {
+                    exe
+                } else {
+                    &map.path
+                };
+
+                let result = self
+                    .elf_cache
+                    .elf_resolver(
+                        path,
+                        self.maybe_debug_dirs(*debug_syms),
+                        self.qualified_names,
+                        self.index_cache.as_ref(),
+                    )
+                    .and_then(|resolver| {
+                        if let Some(reason) = self.check_elf_sym_policy(resolver)? {
+                            return Ok(Symbolized::Unknown(reason))
+                        }
+
+                        let file_off = addr - map.range.start + map.file_off;
+                        let mut symbolized = match resolver.file_offset_to_virt_offset(file_off)? {
+                            Some(addr) => self.symbolize_with_elf_resolver(addr, resolver)?,
+                            None => Symbolized::Unknown(Reason::InvalidFileOffset),
+                        };
+                        if let Symbolized::Sym(sym) = &mut symbolized {
+                            sym.module = Some(Cow::Owned(path.to_path_buf()));
+                            sym.module_base = addr.checked_sub(file_off);
+                            sym.file_offset = Some(file_off);
+                        }
+                        Ok(symbolized)
+                    });
+                // Failure to access a single module should not prevent
+                // symbolization of addresses in other ones.
+                let symbolized = match result {
+                    Ok(symbolized) => symbolized,
+                    Err(err) => match Reason::from_file_error(&err) {
+                        Some(reason) => {
+                            log::debug!("failed to symbolize address {addr:#x}: {err:?}");
+                            Symbolized::Unknown(reason)
+                        }
+                        None => return Err(err),
+                    },
+                };
+
+                if let Some(modules) = &mut modules {
+                    let () = modules
+                        .entry(path.to_path_buf())
+                        .or_default()
+                        .record(&symbolized);
+                }
+                Ok(symbolized)
+            })
+            .collect()
     }
 
     #[cfg(linux)]
@@ -974,12 +2741,12 @@ impl Symbolizer {
 
         let ksym_resolver = match kallsyms {
             MaybeDefault::Some(kallsyms) => {
-                let ksym_resolver = self.ksym_resolver(kallsyms)?;
+                let ksym_resolver = self.ksym_cache.ksym_resolver(kallsyms)?;
                 Some(ksym_resolver)
             }
             MaybeDefault::Default => {
                 let kallsyms = Path::new(KALLSYMS);
-                let result = self.ksym_resolver(kallsyms);
+                let result = self.ksym_cache.ksym_resolver(kallsyms);
                 match result {
                     Ok(resolver) => Some(resolver),
                     Err(err) => {
@@ -996,9 +2763,12 @@ impl Symbolizer {
 
         let elf_resolver = match vmlinux {
             MaybeDefault::Some(vmlinux) => {
-                let resolver = self
-                    .elf_cache
-                    .elf_resolver(vmlinux, self.maybe_debug_dirs(*debug_syms))?;
+                let resolver = self.elf_cache.compressed_elf_resolver(
+                    vmlinux,
+                    self.maybe_debug_dirs(*debug_syms),
+                    self.qualified_names,
+                    self.index_cache.as_ref(),
+                )?;
                 Some(resolver)
             }
             MaybeDefault::Default => {
@@ -1014,9 +2784,12 @@ impl Symbolizer {
                 });
 
                 if let Some(vmlinux) = vmlinux {
-                    let result = self
-                        .elf_cache
-                        .elf_resolver(&vmlinux, self.maybe_debug_dirs(*debug_syms));
+                    let result = self.elf_cache.elf_resolver(
+                        &vmlinux,
+                        self.maybe_debug_dirs(*debug_syms),
+                        self.qualified_names,
+                        self.index_cache.as_ref(),
+                    );
                     match result {
                         Ok(resolver) => {
                             log::debug!("found suitable vmlinux file `{}`", vmlinux.display());
@@ -1037,7 +2810,7 @@ impl Symbolizer {
             MaybeDefault::None => None,
         };
 
-        KernelResolver::new(ksym_resolver.cloned(), elf_resolver.cloned(), *kaslr_offset)
+        KernelResolver::new(ksym_resolver, elf_resolver.cloned(), *kaslr_offset)
     }
 
     #[cfg(not(linux))]
@@ -1066,8 +2839,11 @@ impl Symbolizer {
                 _non_exhaustive: (),
             }) => {
                 if *cache_vmas {
-                    let parsed = maps::parse_filtered(*pid)?.collect::<Result<Box<_>>>()?;
-                    let _prev = self.process_vma_cache.borrow_mut().insert(*pid, parsed);
+                    let parsed = maps::parse_filtered(pid)?.collect::<Result<Box<_>>>()?;
+                    let _prev = self
+                        .process_vma_cache
+                        .borrow_mut()
+                        .insert(pid.clone(), parsed);
                 }
             }
             Cache::Phantom(()) => unreachable!(),
@@ -1075,23 +2851,779 @@ impl Symbolizer {
         Ok(())
     }
 
-    /// Symbolize a list of addresses.
-    ///
-    /// Symbolize a list of addresses using the provided symbolization
-    /// [`Source`][Source].
-    ///
-    /// This function returns exactly one [`Symbolized`] object for each input
-    /// address, in the order of input addresses.
+    /// Eagerly load the symbolization data associated with `src`.
     ///
-    /// The following table lists which features the various formats
-    /// (represented by the [`Source`][Source] argument) support. If a feature
-    /// is not supported, the corresponding data in the [`Sym`] result will not
-    /// be populated.
+    /// Files are opened and parsed and the resulting resolvers are
+    /// cached, just as would happen on first symbolization of an
+    /// address belonging to the source. Calling this method ahead of a
+    /// profiling session hence removes this work from the symbolization
+    /// path. For [`Source::Process`] and [`Source::ProcessSnapshot`],
+    /// all binaries mapped into the process are loaded. Failure to load any one
+    /// of them is logged, but otherwise ignored, as it may just have been
+    /// unmapped in the meantime.
     ///
-    /// | Format      | Feature                          | Supported by format? | Supported by blazesym? |
-    /// |-------------|----------------------------------|:--------------------:|:----------------------:|
-    /// | Breakpad    | symbol size                      | yes                  | yes                    |
-    /// |             | source code location information | yes                  | yes                    |
+    /// Sources whose data are not cached are rejected with an error of
+    /// kind [`ErrorKind::Unsupported`].
+    #[cfg_attr(feature = "tracing", crate::log::instrument(skip_all, err))]
+    pub fn preload(&self, src: &Source) -> Result<()> {
+        match src {
+            #[cfg(feature = "breakpad")]
+            Source::Breakpad(Breakpad {
+                path,
+                _non_exhaustive: (),
+            }) => {
+                let _resolver = self.breakpad_resolver(path)?;
+            }
+            Source::Elf(Elf {
+                path,
+                debug_syms,
+                base_addr: _,
+                bolt_original,
+                _non_exhaustive: (),
+            }) => {
+                let () = self.preload_elf(path, *debug_syms, false)?;
+                if let Some(original) = bolt_original {
+                    let () = self.preload_elf(original, *debug_syms, false)?;
+                }
+            }
+            Source::Kernel(kernel) => {
+                // The kernel resolver itself is cheap to create, but
+                // it caches the expensive to load kallsyms and vmlinux
+                // data as part of its creation.
+                let _resolver = self.create_kernel_resolver(kernel)?;
+            }
+            #[cfg(feature = "macho")]
+            Source::MachO(MachO {
+                path,
+                dsym,
+                _non_exhaustive: (),
+            }) => {
+                let _resolver = self.macho_resolver(path, dsym.as_deref())?;
+            }
+            #[cfg(feature = "windows")]
+            Source::Pe(Pe {
+                path,
+                pdb,
+                _non_exhaustive: (),
+            }) => {
+                let _resolver = self.pe_resolver(path, pdb.as_deref())?;
+            }
+            Source::Process(Process {
+                pid,
+                debug_syms,
+                perf_map: _,
+                map_files,
+                proc_root,
+                _non_exhaustive: (),
+            }) => {
+                let () = self.preload_process(pid, *debug_syms, *map_files, *proc_root)?;
+            }
+            #[cfg(feature = "gsym")]
+            Source::Gsym(Gsym::File(GsymFile {
+                path,
+                _non_exhaustive: (),
+            })) => {
+                let _resolver = self.gsym_resolver(path)?;
+            }
+            #[cfg(feature = "apk")]
+            Source::Apk(..) => {
+                return Err(Error::with_unsupported(
+                    "APK sources do not support preloading",
+                ))
+            }
+            Source::ProcessSnapshot(snapshot) => {
+                let () = self.preload_process_entries(
+                    &snapshot.entries,
+                    snapshot.debug_syms,
+                    false,
+                    None,
+                );
+            }
+            Source::CoreDump(..) => {
+                return Err(Error::with_unsupported(
+                    "core dump sources do not support preloading",
+                ))
+            }
+            #[cfg(feature = "gsym")]
+            Source::Gsym(Gsym::Data(..)) => {
+                return Err(Error::with_unsupported(
+                    "in-memory Gsym sources do not support preloading",
+                ))
+            }
+            Source::Phantom(()) => unreachable!(),
+        }
+        Ok(())
+    }
+
+    /// Report what information the symbolization source `src` is
+    /// able to provide, without symbolizing any addresses.
+    ///
+    /// This functionality can be used for detecting and reporting
+    /// deficiencies (e.g., a stripped binary without accompanying
+    /// debug information) upfront, before attempting symbolization.
+    /// Data loaded in the process are cached, just as they would be
+    /// as part of symbolization.
+    ///
+    /// Currently only [`Source::Elf`] is supported. Other sources are
+    /// rejected with an error of kind [`ErrorKind::Unsupported`].
+    #[cfg_attr(feature = "tracing", crate::log::instrument(skip_all, err))]
+    pub fn probe(&self, src: &Source) -> Result<SourceInfo> {
+        match src {
+            Source::Elf(Elf {
+                path,
+                debug_syms,
+                base_addr: _,
+                bolt_original: _,
+                _non_exhaustive: (),
+            }) => {
+                let resolver = self.elf_cache.elf_resolver(
+                    path,
+                    self.maybe_debug_dirs(*debug_syms),
+                    self.qualified_names,
+                    self.index_cache.as_ref(),
+                )?;
+                resolver.probe()
+            }
+            Source::Phantom(()) => unreachable!(),
+            _ => Err(Error::with_unsupported(
+                "probing is only supported for ELF sources",
+            )),
+        }
+    }
+
+    /// Report the memory mappings of a process, along with the kind of
+    /// resolver used for symbolizing addresses in each of them.
+    ///
+    /// This functionality is meant for introspection, e.g., to find out
+    /// why addresses in a certain mapping can't be symbolized. If no
+    /// resolver is available for a mapping, the reason is reported
+    /// instead. Resolvers are created as necessary, just as would
+    /// happen during symbolization (or as part of
+    /// [`Symbolizer::preload`]), and cached. Mappings are reported in
+    /// ascending address order.
+    ///
+    /// Only [`Source::Process`] and [`Source::ProcessSnapshot`] are
+    /// supported. Other sources are rejected with an error of kind
+    /// [`ErrorKind::Unsupported`].
+    #[cfg_attr(feature = "tracing", crate::log::instrument(skip_all, err))]
+    pub fn process_mappings(&self, src: &Source) -> Result<Vec<ProcessMapping>> {
+        match src {
+            Source::Process(Process {
+                pid,
+                debug_syms,
+                perf_map,
+                map_files,
+                proc_root,
+                _non_exhaustive: (),
+            }) => {
+                let root = proc_root.then(|| maps::proc_root(pid)).flatten();
+                let vmas = self.process_vmas(pid)?;
+                let perf_map = *perf_map && self.perf_map(pid)?.is_some();
+                vmas.iter()
+                    .map(|entry| {
+                        self.process_mapping(
+                            entry,
+                            *debug_syms,
+                            perf_map,
+                            true,
+                            *map_files,
+                            root.as_deref(),
+                        )
+                    })
+                    .collect()
+            }
+            Source::ProcessSnapshot(snapshot) => snapshot
+                .entries
+                .iter()
+                .map(|entry| {
+                    self.process_mapping(
+                        entry,
+                        snapshot.debug_syms,
+                        false,
+                        snapshot.vdso.is_some(),
+                        false,
+                        None,
+                    )
+                })
+                .collect(),
+            Source::Phantom(()) => unreachable!(),
+            _ => Err(Error::with_unsupported(
+                "mapping introspection is only supported for process sources",
+            )),
+        }
+    }
+
+    /// Determine how addresses in the process memory mapping `entry`
+    /// are being symbolized.
+    fn process_mapping(
+        &self,
+        entry: &MapsEntry,
+        debug_syms: bool,
+        perf_map: bool,
+        vdso: bool,
+        map_files: bool,
+        root: Option<&Path>,
+    ) -> Result<ProcessMapping> {
+        let resolver = if let Some(path_name) = &entry.path_name {
+            self.process_dispatch_resolver(entry.range.clone(), path_name)?
+        } else {
+            None
+        };
+
+        let resolver = match (resolver, &entry.path_name) {
+            (Some(_resolver), _) => Ok(ResolverKind::Custom),
+            (None, Some(PathName::Path(entry_path))) => {
+                let ext = entry_path
+                    .symbolic_path
+                    .extension()
+                    .unwrap_or_else(|| OsStr::new(""));
+                match ext.to_str() {
+                    _ if is_shared_mem(&entry_path.symbolic_path) => {
+                        match self.shared_mem_dispatch_resolver(entry, entry_path)? {
+                            Some(_resolver) => Ok(ResolverKind::Custom),
+                            None => Err(Reason::Unsupported),
+                        }
+                    }
+                    #[cfg(feature = "apk")]
+                    Some("apk") | Some("zip") => Ok(ResolverKind::Apk),
+                    _ => {
+                        let path = entry_path.access_path(map_files, root);
+                        let result = self.elf_cache.elf_resolver(
+                            &path,
+                            self.maybe_debug_dirs(debug_syms),
+                            self.qualified_names,
+                            self.index_cache.as_ref(),
+                        );
+                        match result {
+                            Ok(resolver) if resolver.uses_debug_info()? => Ok(ResolverKind::Dwarf),
+                            Ok(resolver) => match self.check_elf_sym_policy(resolver)? {
+                                Some(reason) => Err(reason),
+                                None => Ok(ResolverKind::Elf),
+                            },
+                            Err(err) => match Reason::from_file_error(&err) {
+                                Some(reason) => Err(reason),
+                                None => return Err(err),
+                            },
+                        }
+                    }
+                }
+            }
+            (None, Some(PathName::Component(component))) => match component.as_str() {
+                vdso::VDSO if vdso => Ok(ResolverKind::Vdso),
+                vdso::VSYSCALL => Ok(ResolverKind::Vdso),
+                _ => Err(Reason::from_component(component)),
+            },
+            (None, None) if perf_map => Ok(ResolverKind::PerfMap),
+            (None, None) => Err(Reason::MissingComponent),
+        };
+
+        let path = match &entry.path_name {
+            Some(PathName::Path(entry_path)) => Some(entry_path.symbolic_path.clone()),
+            Some(PathName::Component(component)) => Some(PathBuf::from(component)),
+            None => None,
+        };
+
+        let mapping = ProcessMapping {
+            range: entry.range.clone(),
+            offset: entry.offset,
+            path,
+            resolver,
+            _non_exhaustive: (),
+        };
+        Ok(mapping)
+    }
+
+    /// Preload the ELF file at `path`, optionally also indexing its
+    /// DWARF debug information (`debug_info`), if in use.
+    fn preload_elf(&self, path: &Path, debug_syms: bool, debug_info: bool) -> Result<()> {
+        let resolver = self.elf_cache.elf_resolver(
+            path,
+            self.maybe_debug_dirs(debug_syms),
+            self.qualified_names,
+            self.index_cache.as_ref(),
+        )?;
+        let () = resolver.preload()?;
+        if debug_info {
+            let () = resolver.preload_debug_info()?;
+        }
+        Ok(())
+    }
+
+    fn preload_process(
+        &self,
+        pid: &Pid,
+        debug_syms: bool,
+        map_files: bool,
+        proc_root: bool,
+    ) -> Result<()> {
+        let root = proc_root.then(|| maps::proc_root(pid)).flatten();
+        let vmas = self.process_vmas(pid)?;
+        let () = self.preload_process_entries(&vmas, debug_syms, map_files, root.as_deref());
+        Ok(())
+    }
+
+    /// Retrieve the (symbolization relevant) memory mappings of the
+    /// process `pid`, preferring cached ones.
+    fn process_vmas(&self, pid: &Pid) -> Result<Box<[MapsEntry]>> {
+        if let Some(cached) = self.process_vma_cache.borrow().get(pid) {
+            Ok(cached.clone())
+        } else {
+            maps::parse_filtered(pid)?.collect::<Result<Box<_>>>()
+        }
+    }
+
+    /// Preload the binaries backing the provided process `entries`,
+    /// logging and otherwise ignoring failures.
+    fn preload_process_entries(
+        &self,
+        entries: &[MapsEntry],
+        debug_syms: bool,
+        map_files: bool,
+        root: Option<&Path>,
+    ) {
+        for entry in entries.iter() {
+            let entry_path = match preloadable_entry_path(entry) {
+                Some(entry_path) => entry_path,
+                None => continue,
+            };
+
+            let result =
+                self.preload_process_entry(entry, entry_path, debug_syms, map_files, root, false);
+            if let Err(err) = result {
+                log::warn!(
+                    "failed to preload `{}`: {err}; ignoring...",
+                    entry_path.symbolic_path.display()
+                );
+            }
+        }
+    }
+
+    /// Preload the binary at `entry_path`, backing the process memory
+    /// mapping `entry`.
+    fn preload_process_entry(
+        &self,
+        entry: &MapsEntry,
+        entry_path: &EntryPath,
+        debug_syms: bool,
+        map_files: bool,
+        root: Option<&Path>,
+        debug_info: bool,
+    ) -> Result<()> {
+        // SANITY: The caller has to ensure that the entry has a path.
+        let path_name = entry.path_name.as_ref().unwrap();
+        let resolver = self.process_dispatch_resolver(entry.range.clone(), path_name)?;
+        if resolver.is_some() {
+            return Ok(())
+        }
+
+        let path = entry_path.access_path(map_files, root);
+        self.preload_elf(&path, debug_syms, debug_info)
+    }
+
+    /// Warm up the symbolizer for the given sources, within the limits
+    /// of `budget`.
+    ///
+    /// Warming up is similar to [`Symbolizer::preload`], but it also
+    /// eagerly builds indexes over DWARF debug information, which is
+    /// otherwise done lazily as addresses get symbolized. For large
+    /// debug files that can take considerable time, which this method
+    /// allows to be spent upfront. Work happens one module (e.g., an
+    /// ELF file or, for [`Source::Process`] and
+    /// [`Source::ProcessSnapshot`], each of the binaries mapped into
+    /// the process) at a time, with `progress` being invoked after each
+    /// one. A module is ready for symbolization once it was reported.
+    ///
+    /// Failure to warm up a module is reported to `progress`, but
+    /// otherwise ignored. Once the budget is exhausted, no further
+    /// modules are warmed up. The method returns `true` if all modules
+    /// were processed and `false` if work stopped early.
+    #[cfg_attr(feature = "tracing", crate::log::instrument(skip_all, fields(budget = ?budget), err))]
+    pub fn warm_up<F>(
+        &self,
+        srcs: &[Source],
+        budget: &WarmUpBudget,
+        mut progress: F,
+    ) -> Result<bool>
+    where
+        F: FnMut(&WarmUpProgress<'_>),
+    {
+        let start = Instant::now();
+        let items = self.warm_up_items(srcs)?;
+        let total = items.len();
+        let mut file_size = 0;
+
+        for (idx, item) in items.iter().enumerate() {
+            if let Some(time) = budget.time {
+                if start.elapsed() >= time {
+                    return Ok(false)
+                }
+            }
+
+            if let Some(max_file_size) = budget.file_size {
+                let size = item
+                    .file()
+                    .and_then(|path| path.metadata().ok())
+                    .map(|metadata| metadata.len())
+                    .unwrap_or(0);
+                if file_size + size > max_file_size {
+                    return Ok(false)
+                }
+                file_size += size;
+            }
+
+            let result = match item {
+                WarmUpItem::Elf {
+                    path,
+                    debug_syms,
+                    bolt_original,
+                } => self.preload_elf(path, *debug_syms, true).and_then(|()| {
+                    if let Some(original) = bolt_original {
+                        self.preload_elf(original, *debug_syms, true)
+                    } else {
+                        Ok(())
+                    }
+                }),
+                WarmUpItem::Entry {
+                    entry,
+                    debug_syms,
+                    map_files,
+                    root,
+                } => {
+                    // SANITY: Only entries with a path are part of the
+                    //         work list.
+                    let entry_path = preloadable_entry_path(entry).unwrap();
+                    self.preload_process_entry(
+                        entry,
+                        entry_path,
+                        *debug_syms,
+                        *map_files,
+                        root.as_deref(),
+                        true,
+                    )
+                }
+                WarmUpItem::Other(src) => self.preload(src),
+            };
+
+            let report = WarmUpProgress {
+                module: item.module(),
+                error: result.as_ref().err(),
+                done: idx + 1,
+                total,
+                _non_exhaustive: (),
+            };
+            let () = progress(&report);
+        }
+        Ok(true)
+    }
+
+    /// Determine the list of modules to warm up for the provided
+    /// sources.
+    fn warm_up_items<'src>(&self, srcs: &'src [Source]) -> Result<Vec<WarmUpItem<'src>>> {
+        let mut items = Vec::new();
+        // The same file may be mapped multiple times, but we only want
+        // to report it once.
+        let mut files = HashSet::new();
+        let mut add_entries = |items: &mut Vec<_>,
+                               entries: &[MapsEntry],
+                               debug_syms: bool,
+                               map_files: bool,
+                               root: Option<PathBuf>| {
+            for entry in entries {
+                if let Some(entry_path) = preloadable_entry_path(entry) {
+                    if files.insert(entry_path.symbolic_path.clone()) {
+                        let () = items.push(WarmUpItem::Entry {
+                            entry: entry.clone(),
+                            debug_syms,
+                            map_files,
+                            root: root.clone(),
+                        });
+                    }
+                }
+            }
+        };
+
+        for src in srcs {
+            match src {
+                Source::Elf(Elf {
+                    path,
+                    debug_syms,
+                    base_addr: _,
+                    bolt_original,
+                    _non_exhaustive: (),
+                }) => {
+                    let () = items.push(WarmUpItem::Elf {
+                        path,
+                        debug_syms: *debug_syms,
+                        bolt_original: bolt_original.as_deref(),
+                    });
+                }
+                Source::Process(Process {
+                    pid,
+                    debug_syms,
+                    perf_map: _,
+                    map_files,
+                    proc_root,
+                    _non_exhaustive: (),
+                }) => {
+                    let root = proc_root.then(|| maps::proc_root(pid)).flatten();
+                    let vmas = self.process_vmas(pid)?;
+                    let () = add_entries(&mut items, &vmas, *debug_syms, *map_files, root);
+                }
+                Source::ProcessSnapshot(snapshot) => {
+                    let () = add_entries(
+                        &mut items,
+                        &snapshot.entries,
+                        snapshot.debug_syms,
+                        false,
+                        None,
+                    );
+                }
+                src => {
+                    let () = items.push(WarmUpItem::Other(src));
+                }
+            }
+        }
+        Ok(items)
+    }
+
+    /// Retrieve information about the files currently kept open.
+    ///
+    /// See [`Builder::set_max_open_files`] for how to limit the number
+    /// of open files. Files kept open by worker threads used for
+    /// parallel symbolization are accounted for as well.
+    pub fn fd_usage(&self) -> FdUsage {
+        FdUsage {
+            open: self.fd_budget.open(),
+            max_open: self.fd_budget.max_open(),
+            closed: self.fd_budget.closed(),
+            reopened: self.fd_budget.reopened(),
+            _non_exhaustive: (),
+        }
+    }
+
+    /// Retrieve information about the files data are currently cached
+    /// for.
+    ///
+    /// See [`Builder::set_max_cached_files`] for how to limit the
+    /// number of cached files.
+    pub fn cache_usage(&self) -> CacheUsage {
+        CacheUsage {
+            files: self.entry_budget.entries(),
+            max_files: self.entry_budget.max_entries(),
+            _non_exhaustive: (),
+        }
+    }
+
+    /// Remove all cached data associated with the file at `path`.
+    ///
+    /// Cached data are usually kept around for the life time of the
+    /// [`Symbolizer`]. For long running programs symbolizing addresses
+    /// in binaries that are eventually deleted (e.g., because they got
+    /// upgraded), this can amount to a considerable amount of memory
+    /// as well as disk space being held on to. This method drops all
+    /// resolvers for the file, along with any open file descriptors.
+    ///
+    /// `path` has to be the path through which the file was accessed.
+    /// For process symbolization with [`Process::map_files`] enabled,
+    /// that is the `/proc/<pid>/map_files/` path.
+    ///
+    /// This method returns `true` if any cached data were removed.
+    pub fn prune_cache(&mut self, path: &Path) -> bool {
+        let mut pruned = false;
+        #[cfg(feature = "apk")]
+        {
+            pruned |= self.apk_cache.remove(path);
+        }
+        #[cfg(feature = "breakpad")]
+        {
+            pruned |= self.breakpad_cache.remove(path);
+        }
+        pruned |= self.elf_cache.remove(path);
+        #[cfg(feature = "gsym")]
+        {
+            pruned |= self.gsym_cache.remove(path);
+        }
+        pruned |= self.ksym_cache.remove(path);
+        #[cfg(feature = "macho")]
+        {
+            pruned |= self.macho_cache.remove(path);
+        }
+        #[cfg(feature = "windows")]
+        {
+            pruned |= self.pe_cache.remove(path);
+        }
+        pruned |= self.jitdump_cache.remove(path);
+        pruned |= self.perf_map_cache.remove(path);
+
+        let () = self.process_cache.retain(|path_name, _resolver| {
+            let keep = match path_name {
+                PathName::Path(entry_path) => {
+                    entry_path.maps_file != path && entry_path.symbolic_path != path
+                }
+                PathName::Component(..) => true,
+            };
+            pruned |= !keep;
+            keep
+        });
+        let () = self.shared_mem_cache.retain(|maps_file, _resolver| {
+            let keep = maps_file != path;
+            pruned |= !keep;
+            keep
+        });
+
+        if let Some(addr_cache) = &self.addr_cache {
+            let () = addr_cache.purge();
+        }
+
+        // Worker threads maintain caches of their own, which we can't
+        // reach into. Just tear them down; they will be recreated on
+        // demand.
+        if self.worker_pool.get().is_some() {
+            self.worker_pool = OnceCell::new();
+        }
+        pruned
+    }
+
+    /// Remove all cached data.
+    ///
+    /// This method drops all resolvers and cached process meta data
+    /// (see [`Symbolizer::cache`]), along with any open file
+    /// descriptors, resetting the [`Symbolizer`] to the state it was
+    /// in after construction. Registered symbol sockets are retained.
+    pub fn clear_cache(&mut self) {
+        #[cfg(feature = "apk")]
+        let () = self.apk_cache.clear();
+        #[cfg(feature = "breakpad")]
+        let () = self.breakpad_cache.clear();
+        let () = self.elf_cache.clear();
+        #[cfg(feature = "gsym")]
+        let () = self.gsym_cache.clear();
+        let () = self.ksym_cache.clear();
+        #[cfg(feature = "macho")]
+        let () = self.macho_cache.clear();
+        #[cfg(feature = "windows")]
+        let () = self.pe_cache.clear();
+        let () = self.jitdump_cache.clear();
+        let () = self.perf_map_cache.clear();
+        let () = self.process_vma_cache.get_mut().clear();
+        let () = self.process_cache.retain(|_path_name, _resolver| false);
+        let () = self.shared_mem_cache.retain(|_maps_file, _resolver| false);
+
+        if let Some(addr_cache) = &self.addr_cache {
+            let () = addr_cache.purge();
+        }
+        self.worker_pool = OnceCell::new();
+    }
+
+    /// Register a symbol socket for the process with the given `pid`.
+    ///
+    /// Language runtimes with just-in-time compilers (e.g., virtual
+    /// machines) can serve symbol queries for their JIT regions over a
+    /// Unix domain socket, as an alternative to constantly rewritten
+    /// perf maps. Once registered, the socket listening at `path` is
+    /// consulted when symbolizing addresses that fall into anonymous
+    /// executable mappings of the process, before any perf map.
+    ///
+    /// The protocol is line based. For each address the symbolizer
+    /// sends a line containing the address as hex number without `0x`
+    /// prefix. The server responds with an empty line if the address
+    /// is unknown to it, and with a line of the format
+    /// `START<TAB>SIZE<TAB>NAME[<TAB>FILE[<TAB>LINE]]` otherwise.
+    /// `START` and `SIZE` are hex numbers without `0x` prefix (as they
+    /// are in perf maps) and `LINE` is a decimal number.
+    ///
+    /// A connection to the socket is established as part of this
+    /// call. A previously registered socket for the same process is
+    /// replaced.
+    #[cfg(unix)]
+    #[cfg_attr(docsrs, doc(cfg(unix)))]
+    pub fn register_sym_socket(&self, pid: Pid, path: &Path) -> Result<()> {
+        let sock = SymSock::connect(path)?;
+        let _prev = self.sym_socks.borrow_mut().insert(pid, sock);
+        Ok(())
+    }
+
+    /// Unregister the symbol socket registered for the process with
+    /// the given `pid`, if any.
+    ///
+    /// This function returns `true` if a socket was registered.
+    #[cfg(unix)]
+    #[cfg_attr(docsrs, doc(cfg(unix)))]
+    pub fn unregister_sym_socket(&self, pid: &Pid) -> bool {
+        self.sym_socks.borrow_mut().remove(pid).is_some()
+    }
+
+    /// Classify addresses of a process as being likely symbolizable or
+    /// not.
+    ///
+    /// This method is meant to serve as a cheap pre-filter, e.g., for
+    /// use cases with very high sample rates, allowing for dropping or
+    /// deferring addresses that can't be symbolized. Classification is
+    /// based solely on the process' VMAs, which need to have been
+    /// cached earlier by means of [`Symbolizer::cache`]. No I/O is
+    /// performed and no symbolization sources are consulted.
+    ///
+    /// This function returns exactly one [`AddrClass`] object for each
+    /// input address, in the order of input addresses. Addresses
+    /// reported as [`AddrClass::Unknown`] would be reported as
+    /// [`Symbolized::Unknown`] by [`symbolize`][Self::symbolize] for a
+    /// [`Process`] source, unless a perf map, symbol socket, or
+    /// unknown address resolver is consulted (which may cover
+    /// addresses in anonymous mappings).
+    pub fn classify_addrs(&self, pid: &Pid, addrs: &[Addr]) -> Result<Vec<AddrClass>> {
+        let cache = self.process_vma_cache.borrow();
+        let entries = cache.get(pid).ok_or_else(|| {
+            Error::with_not_found(format!("no VMAs are cached for process {pid}"))
+        })?;
+
+        let classes = addrs
+            .iter()
+            .map(|addr| {
+                let idx = entries.partition_point(|entry| entry.range.end <= *addr);
+                match entries.get(idx) {
+                    Some(entry) if entry.range.contains(addr) => match &entry.path_name {
+                        Some(PathName::Path(entry_path))
+                            if is_shared_mem(&entry_path.symbolic_path)
+                                && self.shared_mem_dispatch.is_none()
+                                && self.process_dispatch.is_none() =>
+                        {
+                            AddrClass::Unknown(Reason::Unsupported)
+                        }
+                        Some(PathName::Path(..)) => AddrClass::Symbolizable,
+                        Some(PathName::Component(component))
+                            if vdso::is_pseudo_module(component) =>
+                        {
+                            AddrClass::Symbolizable
+                        }
+                        Some(PathName::Component(component)) => {
+                            AddrClass::Unknown(Reason::from_component(component))
+                        }
+                        None => AddrClass::Unknown(Reason::UnknownAddr),
+                    },
+                    _ => AddrClass::Unknown(Reason::Unmapped),
+                }
+            })
+            .collect();
+        Ok(classes)
+    }
+
+    /// Symbolize a list of addresses.
+    ///
+    /// Symbolize a list of addresses using the provided symbolization
+    /// [`Source`][Source].
+    ///
+    /// This function returns exactly one [`Symbolized`] object for each input
+    /// address, in the order of input addresses.
+    ///
+    /// The following table lists which features the various formats
+    /// (represented by the [`Source`][Source] argument) support. If a feature
+    /// is not supported, the corresponding data in the [`Sym`] result will not
+    /// be populated.
+    ///
+    /// | Format      | Feature                          | Supported by format? | Supported by blazesym? |
+    /// |-------------|----------------------------------|:--------------------:|:----------------------:|
+    /// | Breakpad    | symbol size                      | yes                  | yes                    |
+    /// |             | source code location information | yes                  | yes                    |
     /// |             | inlined function information     | yes                  | yes                    |
     /// | ELF         | symbol size                      | yes                  | yes                    |
     /// |             | source code location information | no                   | N/A                    |
@@ -1105,14 +3637,224 @@ impl Symbolizer {
     /// | Ksym        | symbol size                      | no                   | N/A                    |
     /// |             | source code location information | no                   | N/A                    |
     /// |             | inlined function information     | no                   | N/A                    |
+    /// | Mach-O      | symbol size                      | no                   | yes (inferred)         |
+    /// |             | source code location information | no                   | N/A                    |
+    /// |             | inlined function information     | no                   | N/A                    |
+    /// | dSYM        | symbol size                      | yes                  | yes                    |
+    /// |             | source code location information | yes                  | yes                    |
+    /// |             | inlined function information     | yes                  | yes                    |
+    /// | PE/PDB      | symbol size                      | yes                  | yes                    |
+    /// |             | source code location information | yes                  | yes                    |
+    /// |             | inlined function information     | yes                  | no                     |
     /// | BPF program | symbol size                      | no (?)               | no                     |
     /// |             | source code location information | yes                  | yes                    |
     /// |             | inlined function information     | no                   | no                     |
+    ///
+    /// Not all sources support all kinds of [`Input`]. The following
+    /// table lists which ones are accepted by which source. Passing an
+    /// unsupported combination results in an error of kind
+    /// [`ErrorKind::Unsupported`].
+    ///
+    /// | Source    | [`Input::AbsAddr`]     | [`Input::VirtOffset`] | [`Input::FileOffset`] |
+    /// |-----------|:----------------------:|:---------------------:|:---------------------:|
+    /// | APK       | no                     | no                    | yes                   |
+    /// | Breakpad  | no                     | no                    | yes                   |
+    /// | Core dump | yes                    | no                    | no                    |
+    /// | ELF       | yes (with `base_addr`) | yes                   | yes                   |
+    /// | Gsym      | no                     | yes                   | no                    |
+    /// | Kernel    | yes                    | no                    | no                    |
+    /// | Mach-O    | no                     | yes                   | yes                   |
+    /// | PE        | no                     | yes                   | yes                   |
+    /// | Process   | yes                    | no                    | no                    |
+    ///
+    /// File offsets are what, for example, perf reports as part of
+    /// `PERF_RECORD_MMAP2` records (in conjunction with the sample's
+    /// address). Hence, if a component other than blazesym already
+    /// took care of mapping process addresses to files, symbolization
+    /// can happen without the need to convert inputs to absolute
+    /// addresses first.
     #[cfg_attr(feature = "tracing", crate::log::instrument(skip_all, fields(src = ?src, addrs = ?input.map(Hexify)), err))]
     pub fn symbolize<'slf>(
         &'slf self,
         src: &Source,
         input: Input<&[u64]>,
+    ) -> Result<Vec<Symbolized<'slf>>> {
+        self.symbolize_impl(src, input, None, None)
+    }
+
+    /// Symbolize a list of addresses, reporting symbol names only.
+    ///
+    /// This method works like [`symbolize`][Self::symbolize], but
+    /// irrespective of the [`Symbolizer`]'s configuration it neither
+    /// reports source code information nor inlined functions. As no
+    /// line tables need to be parsed to this end, it can be
+    /// considerably faster, especially when symbolizing addresses in
+    /// large binaries with DWARF debug information that have not been
+    /// used for symbolization before.
+    #[cfg_attr(feature = "tracing", crate::log::instrument(skip_all, fields(src = ?src, addrs = ?input.map(Hexify)), err))]
+    pub fn symbolize_names_only<'slf>(
+        &'slf self,
+        src: &Source,
+        input: Input<&[u64]>,
+    ) -> Result<Vec<Symbolized<'slf>>> {
+        self.with_names_only(true, || self.symbolize_impl(src, input, None, None))
+    }
+
+    /// Symbolize a list of addresses and gather statistics about the
+    /// batch.
+    ///
+    /// This method works like [`symbolize`][Self::symbolize], but it
+    /// additionally reports a [`Stats`] object summarizing the outcome
+    /// of the symbolization.
+    #[cfg_attr(feature = "tracing", crate::log::instrument(skip_all, fields(src = ?src, addrs = ?input.map(Hexify)), err))]
+    pub fn symbolize_with_stats<'slf>(
+        &'slf self,
+        src: &Source,
+        input: Input<&[u64]>,
+    ) -> Result<(Vec<Symbolized<'slf>>, Stats)> {
+        let start = Instant::now();
+        let mut stats = Stats::default();
+        let symbolized = self.symbolize_impl(src, input, None, Some(&mut stats.modules))?;
+
+        let () = symbolized
+            .iter()
+            .for_each(|symbolized| stats.record(symbolized));
+        // For sources other than processes, all addresses are
+        // attributed to the one module they reference, if any.
+        if let Some(path) = src.module_path() {
+            let mut module = ModuleStats::default();
+            let () = symbolized
+                .iter()
+                .for_each(|symbolized| module.record(symbolized));
+            let _prev = stats.modules.insert(path.to_path_buf(), module);
+        }
+        stats.duration = start.elapsed();
+        Ok((symbolized, stats))
+    }
+
+    /// Symbolize a list of addresses without blocking on file I/O.
+    ///
+    /// This method works like [`symbolize`][Self::symbolize], but
+    /// before symbolizing it performs the bulk of the necessary file
+    /// I/O on a separate thread: for process sources the process'
+    /// VMAs are parsed and, for all sources, the files containing
+    /// the symbolization data of the input addresses are read into
+    /// the page cache. Only then are the addresses symbolized on the
+    /// calling thread, which then mostly amounts to CPU bound work.
+    ///
+    /// The returned future is executor agnostic. Note that, as is the
+    /// case for [`Symbolizer`] itself, it is not [`Send`]. Separate
+    /// debug information files and auxiliary data such as perf maps
+    /// are not prefetched. Offloaded work is performed on a small pool
+    /// of lazily spawned helper threads shared by all [`Symbolizer`]
+    /// instances, so that a slow file operation does not hold up
+    /// unrelated requests.
+    ///
+    /// # Limitations
+    /// Only file I/O is moved off the calling thread. Parsing of
+    /// symbolization sources, e.g., of ELF symbol tables and DWARF
+    /// debug information, still happens as part of polling the
+    /// returned future and may take considerable time for large
+    /// binaries that have not been used for symbolization before.
+    /// Users sensitive to such delays should consider running
+    /// symbolization requests on a dedicated blocking thread pool
+    /// provided by their executor instead.
+    #[cfg(feature = "async")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "async")))]
+    pub async fn symbolize_async<'slf>(
+        &'slf self,
+        src: &Source<'_>,
+        input: Input<&[u64]>,
+    ) -> Result<Vec<Symbolized<'slf>>> {
+        let (vmas, paths) = match (src, input) {
+            (
+                Source::Process(Process {
+                    pid,
+                    debug_syms: _,
+                    perf_map: _,
+                    map_files,
+                    proc_root,
+                    _non_exhaustive: (),
+                }),
+                Input::AbsAddr(addrs),
+            ) => {
+                let vmas = if self.process_vma_cache.borrow().contains_key(pid) {
+                    None
+                } else {
+                    let pid = pid.clone();
+                    let vmas = unblock(move || -> Result<Box<[MapsEntry]>> {
+                        maps::parse_filtered(&pid)?.collect()
+                    })?
+                    .await?;
+                    Some(vmas)
+                };
+
+                let cache = self.process_vma_cache.borrow();
+                // SANITY: We either retrieved VMAs above or they are
+                //         cached.
+                let entries = vmas
+                    .as_deref()
+                    .or_else(|| cache.get(pid).map(|entries| &**entries))
+                    .unwrap();
+                let root = proc_root.then(|| maps::proc_root(pid)).flatten();
+                let paths = vma_paths(entries, addrs, *map_files, root.as_deref());
+                (vmas, paths)
+            }
+            (Source::ProcessSnapshot(snapshot), Input::AbsAddr(addrs)) => {
+                let paths = vma_paths(&snapshot.entries, addrs, false, None);
+                (None, paths)
+            }
+            (Source::CoreDump(core), _) => (None, vec![core.path.clone()]),
+            _ => (
+                None,
+                src.module_path()
+                    .map(Path::to_path_buf)
+                    .into_iter()
+                    .collect(),
+            ),
+        };
+
+        // Files are opened subject to the same checks as during
+        // symbolization proper, so that strict file access cannot be
+        // bypassed by prefetching.
+        let strict = self.elf_cache.strict();
+        let () = unblock(move || {
+            paths.iter().for_each(|path| {
+                // Prefetching is best effort only. Errors will
+                // surface as part of the actual symbolization.
+                let _result = open_file(path, strict)
+                    .and_then(|file| util::prefetch_file(&file).map_err(Error::from));
+            })
+        })?
+        .await;
+
+        self.symbolize_impl(src, input, vmas.as_deref(), None)
+    }
+
+    fn symbolize_impl<'slf>(
+        &'slf self,
+        src: &Source,
+        input: Input<&[u64]>,
+        vmas: Option<&[MapsEntry]>,
+        modules: Option<&mut HashMap<PathBuf, ModuleStats>>,
+    ) -> Result<Vec<Symbolized<'slf>>> {
+        let symbolized = self.symbolize_src(src, input, vmas, modules)?;
+        if self.lossy_paths {
+            Ok(symbolized
+                .into_iter()
+                .map(Symbolized::into_lossy_paths)
+                .collect())
+        } else {
+            Ok(symbolized)
+        }
+    }
+
+    fn symbolize_src<'slf>(
+        &'slf self,
+        src: &Source,
+        input: Input<&[u64]>,
+        vmas: Option<&[MapsEntry]>,
+        modules: Option<&mut HashMap<PathBuf, ModuleStats>>,
     ) -> Result<Vec<Symbolized<'slf>>> {
         match src {
             #[cfg(feature = "apk")]
@@ -1121,16 +3863,12 @@ impl Symbolizer {
                 debug_syms,
                 _non_exhaustive: (),
             }) => match input {
-                Input::VirtOffset(..) => {
-                    return Err(Error::with_unsupported(
-                        "APK symbolization does not support virtual offset inputs",
-                    ))
-                }
-                Input::AbsAddr(..) => {
-                    return Err(Error::with_unsupported(
-                        "APK symbolization does not support absolute address inputs",
-                    ))
-                }
+                Input::VirtOffset(..) => Err(Error::with_unsupported(
+                    "APK symbolization does not support virtual offset inputs",
+                )),
+                Input::AbsAddr(..) => Err(Error::with_unsupported(
+                    "APK symbolization does not support absolute address inputs",
+                )),
                 Input::FileOffset(offsets) => offsets
                     .iter()
                     .map(
@@ -1167,34 +3905,62 @@ impl Symbolizer {
                 let symbols = self.symbolize_addrs(addrs, &Resolver::Cached(resolver))?;
                 Ok(symbols)
             }
+            Source::CoreDump(core) => {
+                let addrs = match input {
+                    Input::AbsAddr(addrs) => addrs,
+                    Input::VirtOffset(..) => {
+                        return Err(Error::with_unsupported(
+                            "core dump symbolization does not support virtual offset inputs",
+                        ))
+                    }
+                    Input::FileOffset(..) => {
+                        return Err(Error::with_unsupported(
+                            "core dump symbolization does not support file offset inputs",
+                        ))
+                    }
+                };
+
+                self.symbolize_core_dump_addrs(addrs, core, modules)
+            }
             Source::Elf(Elf {
                 path,
                 debug_syms,
+                base_addr,
+                bolt_original,
                 _non_exhaustive: (),
             }) => {
-                let resolver = self
-                    .elf_cache
-                    .elf_resolver(path, self.maybe_debug_dirs(*debug_syms))?;
+                let resolver = self.elf_cache.elf_resolver(
+                    path,
+                    self.maybe_debug_dirs(*debug_syms),
+                    self.qualified_names,
+                    self.index_cache.as_ref(),
+                )?;
+                let original =
+                    self.bolt_original_resolver(bolt_original.as_deref(), *debug_syms)?;
                 match input {
                     Input::VirtOffset(addrs) => addrs
                         .iter()
-                        .map(|addr| {
-                            self.symbolize_with_resolver(*addr, &Resolver::Cached(resolver.deref()))
-                        })
+                        .map(|addr| self.symbolize_elf_addr(*addr, resolver, original, None))
                         .collect(),
-                    Input::AbsAddr(..) => {
-                        return Err(Error::with_unsupported(
-                            "ELF symbolization does not support absolute address inputs",
-                        ))
+                    Input::AbsAddr(addrs) => {
+                        let bias = self.elf_load_bias(resolver, base_addr.as_ref())?;
+                        addrs
+                            .iter()
+                            .map(|addr| match addr.checked_sub(bias) {
+                                Some(addr) => {
+                                    self.symbolize_elf_addr(addr, resolver, original, Some(bias))
+                                }
+                                None => Ok(Symbolized::Unknown(Reason::UnknownAddr)),
+                            })
+                            .collect()
                     }
                     Input::FileOffset(offsets) => offsets
                         .iter()
                         .map(
                             |offset| match resolver.file_offset_to_virt_offset(*offset)? {
-                                Some(addr) => self.symbolize_with_resolver(
-                                    addr,
-                                    &Resolver::Cached(resolver.deref()),
-                                ),
+                                Some(addr) => {
+                                    self.symbolize_elf_addr(addr, resolver, original, None)
+                                }
                                 None => Ok(Symbolized::Unknown(Reason::InvalidFileOffset)),
                             },
                         )
@@ -1220,11 +3986,66 @@ impl Symbolizer {
                 let symbols = self.symbolize_addrs(addrs, &Resolver::Uncached(resolver.deref()))?;
                 Ok(symbols)
             }
+            #[cfg(feature = "macho")]
+            Source::MachO(MachO {
+                path,
+                dsym,
+                _non_exhaustive: (),
+            }) => {
+                let resolver = self.macho_resolver(path, dsym.as_deref())?;
+                match input {
+                    Input::VirtOffset(addrs) => {
+                        self.symbolize_addrs(addrs, &Resolver::Cached(resolver))
+                    }
+                    Input::AbsAddr(..) => Err(Error::with_unsupported(
+                        "Mach-O symbolization does not support absolute address inputs",
+                    )),
+                    Input::FileOffset(offsets) => offsets
+                        .iter()
+                        .map(
+                            |offset| match resolver.file_offset_to_virt_offset(*offset)? {
+                                Some(addr) => {
+                                    self.symbolize_with_resolver(addr, &Resolver::Cached(resolver))
+                                }
+                                None => Ok(Symbolized::Unknown(Reason::InvalidFileOffset)),
+                            },
+                        )
+                        .collect(),
+                }
+            }
+            #[cfg(feature = "windows")]
+            Source::Pe(Pe {
+                path,
+                pdb,
+                _non_exhaustive: (),
+            }) => {
+                let resolver = self.pe_resolver(path, pdb.as_deref())?;
+                match input {
+                    Input::VirtOffset(addrs) => {
+                        self.symbolize_addrs(addrs, &Resolver::Cached(resolver))
+                    }
+                    Input::AbsAddr(..) => Err(Error::with_unsupported(
+                        "PE symbolization does not support absolute address inputs",
+                    )),
+                    Input::FileOffset(offsets) => offsets
+                        .iter()
+                        .map(
+                            |offset| match resolver.file_offset_to_virt_offset(*offset)? {
+                                Some(addr) => {
+                                    self.symbolize_with_resolver(addr, &Resolver::Cached(resolver))
+                                }
+                                None => Ok(Symbolized::Unknown(Reason::InvalidFileOffset)),
+                            },
+                        )
+                        .collect(),
+                }
+            }
             Source::Process(Process {
                 pid,
                 debug_syms,
                 perf_map,
                 map_files,
+                proc_root,
                 _non_exhaustive: (),
             }) => {
                 let addrs = match input {
@@ -1241,7 +4062,73 @@ impl Symbolizer {
                     }
                 };
 
-                self.symbolize_user_addrs(addrs, *pid, *debug_syms, *perf_map, *map_files)
+                if modules.is_none() {
+                    if let Some(symbolized) = self.symbolize_user_addrs_parallel(
+                        addrs,
+                        pid,
+                        *debug_syms,
+                        *perf_map,
+                        *map_files,
+                        *proc_root,
+                        vmas,
+                        None,
+                    )? {
+                        return Ok(symbolized)
+                    }
+                }
+
+                self.symbolize_user_addrs(
+                    addrs,
+                    pid,
+                    *debug_syms,
+                    *perf_map,
+                    *map_files,
+                    *proc_root,
+                    vmas,
+                    None,
+                    modules,
+                )
+            }
+            Source::ProcessSnapshot(snapshot) => {
+                let addrs =
+                    match input {
+                        Input::AbsAddr(addrs) => addrs,
+                        Input::VirtOffset(..) => return Err(Error::with_unsupported(
+                            "process snapshot symbolization does not support virtual offset inputs",
+                        )),
+                        Input::FileOffset(..) => return Err(Error::with_unsupported(
+                            "process snapshot symbolization does not support file offset inputs",
+                        )),
+                    };
+
+                // The process may no longer exist, so we only work
+                // with what got captured in the snapshot.
+                if modules.is_none() {
+                    if let Some(symbolized) = self.symbolize_user_addrs_parallel(
+                        addrs,
+                        &snapshot.pid,
+                        snapshot.debug_syms,
+                        false,
+                        false,
+                        false,
+                        Some(&snapshot.entries),
+                        snapshot.vdso.as_deref(),
+                    )? {
+                        return Ok(symbolized)
+                    }
+                }
+
+                self.symbolize_user_addrs(
+                    addrs,
+                    &snapshot.pid,
+                    snapshot.debug_syms,
+                    false,
+                    false,
+                    false,
+                    Some(&snapshot.entries),
+                    snapshot.vdso.as_deref(),
+                    modules,
+                )
             }
             #[cfg(feature = "gsym")]
             Source::Gsym(Gsym::Data(GsymData {
@@ -1262,7 +4149,7 @@ impl Symbolizer {
                     }
                 };
 
-                let resolver = Rc::new(GsymResolver::with_data(data)?);
+                let resolver = Rc::new(GsymResolver::with_data(data, self.gsym_forward_compat)?);
                 let symbols = self.symbolize_addrs(addrs, &Resolver::Uncached(resolver.deref()))?;
                 Ok(symbols)
             }
@@ -1283,14 +4170,277 @@ impl Symbolizer {
                             "Gsym symbolization does not support file offset inputs",
                         ))
                     }
-                };
+                };
+
+                let resolver = self.gsym_resolver(path)?;
+                let symbols = self.symbolize_addrs(addrs, &Resolver::Cached(resolver))?;
+                Ok(symbols)
+            }
+            Source::Phantom(()) => unreachable!(),
+        }
+    }
+
+    /// Symbolize a list of addresses tagged with their kind.
+    ///
+    /// This method works like [`symbolize`][Self::symbolize], but it
+    /// allows for mixing instruction pointers and return addresses (as
+    /// is common in call chains) in a single batch. Addresses tagged as
+    /// [`AddrKind::ReturnAddr`] are adjusted to point into the call
+    /// instruction before being symbolized. The adjustment applied is
+    /// reported as part of [`Sym::addr_adjust`].
+    pub fn symbolize_tagged<'slf>(
+        &'slf self,
+        src: &Source,
+        input: Input<&[(Addr, AddrKind)]>,
+    ) -> Result<Vec<Symbolized<'slf>>> {
+        let tagged = input.into_inner();
+        let addrs = tagged
+            .iter()
+            .map(|(addr, kind)| addr - kind.adjustment(*addr) as Addr)
+            .collect::<Vec<_>>();
+        let mut symbolized = self.symbolize(src, input.map(|_| addrs.as_slice()))?;
+        debug_assert_eq!(symbolized.len(), tagged.len());
+
+        let () = symbolized
+            .iter_mut()
+            .zip(tagged)
+            .for_each(|(symbolized, (addr, kind))| {
+                if let Symbolized::Sym(sym) = symbolized {
+                    sym.addr_adjust = kind.adjustment(*addr);
+                }
+            });
+        Ok(symbolized)
+    }
+
+    /// Symbolize a list of addresses, reporting a [`Frame`] for each.
+    ///
+    /// This method works like [`symbolize`][Self::symbolize], but
+    /// instead of a [`Symbolized`] object it reports a [`Frame`] for
+    /// each input address. Frames always retain the input address as
+    /// well as the module and normalized address, to the degree known,
+    /// irrespective of whether symbolization succeeded.
+    pub fn symbolize_frames<'slf>(
+        &'slf self,
+        src: &Source,
+        input: Input<&[u64]>,
+    ) -> Result<Vec<Frame<'slf>>> {
+        let addrs = input.into_inner();
+        let symbolized = self.symbolize(src, input)?;
+        debug_assert_eq!(symbolized.len(), addrs.len());
+
+        let frames = symbolized
+            .into_iter()
+            .zip(addrs)
+            .map(|(symbolized, addr)| {
+                let (module, norm_addr, sym, code_info, reason) = match symbolized {
+                    Symbolized::Sym(sym) => {
+                        (sym.module.clone(), sym.file_offset, Some(sym), None, None)
+                    }
+                    Symbolized::Code(code_info) => (None, None, None, Some(code_info), None),
+                    Symbolized::Unknown(reason) => (None, None, None, None, Some(reason)),
+                };
+                // For sources referencing a single file, we always
+                // know the module and file offset inputs are already
+                // normalized.
+                let module = module.or_else(|| {
+                    let path = Cow::Owned(src.module_path()?.to_path_buf());
+                    if self.lossy_paths {
+                        Some(util::lossy_path(path))
+                    } else {
+                        Some(path)
+                    }
+                });
+                let norm_addr = norm_addr.or(match input {
+                    Input::FileOffset(..) => Some(*addr),
+                    Input::AbsAddr(..) | Input::VirtOffset(..) => None,
+                });
+
+                Frame {
+                    addr: *addr,
+                    norm_addr,
+                    module,
+                    sym,
+                    code_info,
+                    reason,
+                    _non_exhaustive: (),
+                }
+            })
+            .collect();
+        Ok(frames)
+    }
+
+    /// Symbolize the output of a
+    /// [`Normalizer`][crate::normalize::Normalizer].
+    ///
+    /// The files to symbolize with are picked based on the build IDs
+    /// and paths recorded during normalization, as configured by
+    /// `store`. One result is reported for each of
+    /// `normalized.outputs`, in the same order. Outputs for which no
+    /// file could be found are reported as [`Reason::FileNotFound`].
+    pub fn symbolize_normalized<'slf>(
+        &'slf self,
+        normalized: &UserOutput<'_>,
+        store: &SymbolStore,
+    ) -> Result<Vec<Symbolized<'slf>>> {
+        let UserOutput { outputs, meta } = normalized;
+
+        // Indices of outputs, grouped by the meta data they reference.
+        let mut groups = vec![Vec::new(); meta.len()];
+        for (idx, (_output, meta_idx)) in outputs.iter().enumerate() {
+            let group = groups.get_mut(*meta_idx).ok_or_invalid_input(|| {
+                format!("output {idx} references invalid meta data index {meta_idx}")
+            })?;
+            let () = group.push(idx);
+        }
+
+        let mut symbolized = outputs.iter().map(|_| None).collect::<Vec<_>>();
+        for (meta, group) in meta.iter().zip(groups) {
+            if group.is_empty() {
+                continue
+            }
+
+            let src = match meta {
+                #[cfg(feature = "apk")]
+                UserMeta::Apk(apk) if store.recorded_paths && apk.path.exists() => {
+                    let mut apk = Apk::new(&apk.path);
+                    apk.debug_syms = store.debug_syms;
+                    Ok(Source::Apk(apk))
+                }
+                UserMeta::Apk(..) => Err(Reason::FileNotFound),
+                UserMeta::Elf(elf) => match find_normalized_elf(elf, store)? {
+                    Some(path) => {
+                        let mut elf = Elf::new(path);
+                        elf.debug_syms = store.debug_syms;
+                        Ok(Source::Elf(elf))
+                    }
+                    None => Err(Reason::FileNotFound),
+                },
+                UserMeta::SharedMem(..) => Err(Reason::Unsupported),
+                UserMeta::Unknown(unknown) => Err(Reason::from(unknown.reason)),
+            };
+
+            let results = match src {
+                Ok(src) => {
+                    let offsets = group.iter().map(|idx| outputs[*idx].0).collect::<Vec<_>>();
+                    match self.symbolize(&src, Input::FileOffset(&offsets)) {
+                        Ok(results) => results,
+                        Err(err) => match Reason::from_file_error(&err) {
+                            Some(reason) => vec![Symbolized::Unknown(reason); group.len()],
+                            None => return Err(err),
+                        },
+                    }
+                }
+                Err(reason) => vec![Symbolized::Unknown(reason); group.len()],
+            };
+            debug_assert_eq!(results.len(), group.len());
 
-                let resolver = self.gsym_resolver(path)?;
-                let symbols = self.symbolize_addrs(addrs, &Resolver::Cached(resolver))?;
-                Ok(symbols)
+            let () = group
+                .into_iter()
+                .zip(results)
+                .for_each(|(idx, result)| symbolized[idx] = Some(result));
+        }
+
+        // SANITY: Each output belongs to exactly one group and so got
+        //         symbolized.
+        let symbolized = symbolized.into_iter().map(Option::unwrap).collect();
+        Ok(symbolized)
+    }
+
+    /// Symbolize a list of absolute addresses using multiple sources.
+    ///
+    /// Mixed stacks, for example those containing both kernel and user
+    /// space addresses, may not be attributable to a single
+    /// [`Source`]. This method determines the source that each address
+    /// belongs to and symbolizes it accordingly:
+    /// - [`Kernel`] sources cover kernel space addresses
+    /// - [`Process`], [`ProcessSnapshot`], and [`CoreDump`] sources cover user
+    ///   space addresses
+    /// - [`Elf`] sources, which need to have a [`base_addr`][Elf::base_addr]
+    ///   set, cover the address range their loadable segments got mapped to
+    ///
+    /// If multiple sources cover an address, ELF sources take precedence
+    /// over all others, with the one with the smallest covered range being
+    /// picked. Otherwise the source appearing first in `srcs` is used.
+    /// Addresses not covered by any source are reported as
+    /// [`Reason::Unmapped`]. One result is reported for each of `addrs`,
+    /// in the same order.
+    ///
+    /// Other source types do not support absolute address inputs and
+    /// cause an error to be reported.
+    pub fn symbolize_multi<'slf>(
+        &'slf self,
+        srcs: &[Source],
+        addrs: &[Addr],
+    ) -> Result<Vec<Symbolized<'slf>>> {
+        let coverages = srcs
+            .iter()
+            .map(|src| self.source_coverage(src))
+            .collect::<Result<Vec<_>>>()?;
+        let dispatch = SourceDispatch::new(&coverages);
+
+        // Indices of addresses, grouped by the source they belong to.
+        let mut groups = vec![Vec::new(); srcs.len()];
+        let mut symbolized = addrs.iter().map(|_| None).collect::<Vec<_>>();
+        for (idx, addr) in addrs.iter().enumerate() {
+            match dispatch.find(*addr) {
+                Some(src_idx) => groups[src_idx].push(idx),
+                None => symbolized[idx] = Some(Symbolized::Unknown(Reason::Unmapped)),
             }
-            Source::Phantom(()) => unreachable!(),
         }
+
+        for (src, group) in srcs.iter().zip(groups) {
+            if group.is_empty() {
+                continue
+            }
+
+            let group_addrs = group.iter().map(|idx| addrs[*idx]).collect::<Vec<_>>();
+            let results = self.symbolize(src, Input::AbsAddr(&group_addrs))?;
+            debug_assert_eq!(results.len(), group.len());
+
+            let () = group
+                .into_iter()
+                .zip(results)
+                .for_each(|(idx, result)| symbolized[idx] = Some(result));
+        }
+
+        // SANITY: Each address either belongs to exactly one group and
+        //         so got symbolized or was reported as unmapped.
+        let symbolized = symbolized.into_iter().map(Option::unwrap).collect();
+        Ok(symbolized)
+    }
+
+    /// Determine the absolute addresses covered by a source.
+    fn source_coverage(&self, src: &Source) -> Result<Coverage> {
+        let coverage = match src {
+            Source::Kernel(..) => Coverage::Space(AddrSpace::Kernel),
+            Source::Process(..) | Source::ProcessSnapshot(..) | Source::CoreDump(..) => {
+                Coverage::Space(AddrSpace::User)
+            }
+            Source::Elf(Elf {
+                path,
+                debug_syms,
+                base_addr,
+                ..
+            }) => {
+                let resolver = self.elf_cache.elf_resolver(
+                    path,
+                    self.maybe_debug_dirs(*debug_syms),
+                    self.qualified_names,
+                    self.index_cache.as_ref(),
+                )?;
+                let bias = self.elf_load_bias(resolver, base_addr.as_ref())?;
+                let range = resolver.load_range()?.unwrap_or(0..0);
+                let start = range.start.saturating_add(bias);
+                let end = range.end.saturating_add(bias);
+                Coverage::Range(start..end)
+            }
+            src => {
+                return Err(Error::with_unsupported(format!(
+                    "{src:?} does not support absolute address inputs"
+                )))
+            }
+        };
+        Ok(coverage)
     }
 
     /// Symbolize a single input address/offset.
@@ -1303,6 +4453,15 @@ impl Symbolizer {
         &'slf self,
         src: &Source,
         input: Input<u64>,
+    ) -> Result<Symbolized<'slf>> {
+        let symbolized = self.symbolize_single_src(src, input)?;
+        Ok(self.maybe_lossy_paths(symbolized))
+    }
+
+    fn symbolize_single_src<'slf>(
+        &'slf self,
+        src: &Source,
+        input: Input<u64>,
     ) -> Result<Symbolized<'slf>> {
         match src {
             #[cfg(feature = "apk")]
@@ -1311,22 +4470,18 @@ impl Symbolizer {
                 debug_syms,
                 _non_exhaustive: (),
             }) => match input {
-                Input::VirtOffset(..) => {
-                    return Err(Error::with_unsupported(
-                        "APK symbolization does not support virtual offset inputs",
-                    ))
-                }
-                Input::AbsAddr(..) => {
-                    return Err(Error::with_unsupported(
-                        "APK symbolization does not support absolute address inputs",
-                    ))
-                }
+                Input::VirtOffset(..) => Err(Error::with_unsupported(
+                    "APK symbolization does not support virtual offset inputs",
+                )),
+                Input::AbsAddr(..) => Err(Error::with_unsupported(
+                    "APK symbolization does not support absolute address inputs",
+                )),
                 Input::FileOffset(offset) => match self.apk_resolver(path, offset, *debug_syms)? {
                     Some((elf_resolver, elf_addr)) => self.symbolize_with_resolver(
                         elf_addr,
                         &Resolver::Cached(elf_resolver.as_symbolize()),
                     ),
-                    None => return Ok(Symbolized::Unknown(Reason::InvalidFileOffset)),
+                    None => Ok(Symbolized::Unknown(Reason::InvalidFileOffset)),
                 },
             },
             #[cfg(feature = "breakpad")]
@@ -1351,30 +4506,60 @@ impl Symbolizer {
                 let resolver = self.breakpad_resolver(path)?;
                 self.symbolize_with_resolver(addr, &Resolver::Cached(resolver))
             }
+            Source::CoreDump(core) => {
+                let addr = match input {
+                    Input::AbsAddr(addr) => addr,
+                    Input::VirtOffset(..) => {
+                        return Err(Error::with_unsupported(
+                            "core dump symbolization does not support virtual offset inputs",
+                        ))
+                    }
+                    Input::FileOffset(..) => {
+                        return Err(Error::with_unsupported(
+                            "core dump symbolization does not support file offset inputs",
+                        ))
+                    }
+                };
+
+                let mut symbols = self.symbolize_core_dump_addrs(&[addr], core, None)?;
+                debug_assert!(symbols.len() == 1, "{symbols:#?}");
+                // SANITY: `symbolize_core_dump_addrs` always returns
+                //         one result for one input.
+                Ok(symbols.pop().unwrap())
+            }
             Source::Elf(Elf {
                 path,
                 debug_syms,
+                base_addr,
+                bolt_original,
                 _non_exhaustive: (),
             }) => {
-                let resolver = self
-                    .elf_cache
-                    .elf_resolver(path, self.maybe_debug_dirs(*debug_syms))?;
-                let addr = match input {
-                    Input::VirtOffset(addr) => addr,
-                    Input::AbsAddr(..) => {
-                        return Err(Error::with_unsupported(
-                            "ELF symbolization does not support absolute address inputs",
-                        ))
+                let resolver = self.elf_cache.elf_resolver(
+                    path,
+                    self.maybe_debug_dirs(*debug_syms),
+                    self.qualified_names,
+                    self.index_cache.as_ref(),
+                )?;
+                let original =
+                    self.bolt_original_resolver(bolt_original.as_deref(), *debug_syms)?;
+                let (addr, bias) = match input {
+                    Input::VirtOffset(addr) => (addr, None),
+                    Input::AbsAddr(addr) => {
+                        let bias = self.elf_load_bias(resolver, base_addr.as_ref())?;
+                        match addr.checked_sub(bias) {
+                            Some(addr) => (addr, Some(bias)),
+                            None => return Ok(Symbolized::Unknown(Reason::UnknownAddr)),
+                        }
                     }
                     Input::FileOffset(offset) => {
                         match resolver.file_offset_to_virt_offset(offset)? {
-                            Some(addr) => addr,
+                            Some(addr) => (addr, None),
                             None => return Ok(Symbolized::Unknown(Reason::InvalidFileOffset)),
                         }
                     }
                 };
 
-                self.symbolize_with_resolver(addr, &Resolver::Cached(resolver.deref()))
+                self.symbolize_elf_addr(addr, resolver, original, bias)
             }
             Source::Kernel(kernel) => {
                 let addr = match input {
@@ -1394,11 +4579,60 @@ impl Symbolizer {
                 let resolver = Rc::new(self.create_kernel_resolver(kernel)?);
                 self.symbolize_with_resolver(addr, &Resolver::Uncached(resolver.deref()))
             }
+            #[cfg(feature = "macho")]
+            Source::MachO(MachO {
+                path,
+                dsym,
+                _non_exhaustive: (),
+            }) => {
+                let resolver = self.macho_resolver(path, dsym.as_deref())?;
+                let addr = match input {
+                    Input::VirtOffset(addr) => addr,
+                    Input::AbsAddr(..) => {
+                        return Err(Error::with_unsupported(
+                            "Mach-O symbolization does not support absolute address inputs",
+                        ))
+                    }
+                    Input::FileOffset(offset) => {
+                        match resolver.file_offset_to_virt_offset(offset)? {
+                            Some(addr) => addr,
+                            None => return Ok(Symbolized::Unknown(Reason::InvalidFileOffset)),
+                        }
+                    }
+                };
+
+                self.symbolize_with_resolver(addr, &Resolver::Cached(resolver))
+            }
+            #[cfg(feature = "windows")]
+            Source::Pe(Pe {
+                path,
+                pdb,
+                _non_exhaustive: (),
+            }) => {
+                let resolver = self.pe_resolver(path, pdb.as_deref())?;
+                let addr = match input {
+                    Input::VirtOffset(addr) => addr,
+                    Input::AbsAddr(..) => {
+                        return Err(Error::with_unsupported(
+                            "PE symbolization does not support absolute address inputs",
+                        ))
+                    }
+                    Input::FileOffset(offset) => {
+                        match resolver.file_offset_to_virt_offset(offset)? {
+                            Some(addr) => addr,
+                            None => return Ok(Symbolized::Unknown(Reason::InvalidFileOffset)),
+                        }
+                    }
+                };
+
+                self.symbolize_with_resolver(addr, &Resolver::Cached(resolver))
+            }
             Source::Process(Process {
                 pid,
                 debug_syms,
                 perf_map,
                 map_files,
+                proc_root,
                 _non_exhaustive: (),
             }) => {
                 let addr = match input {
@@ -1415,8 +4649,46 @@ impl Symbolizer {
                     }
                 };
 
-                let mut symbols =
-                    self.symbolize_user_addrs(&[addr], *pid, *debug_syms, *perf_map, *map_files)?;
+                let mut symbols = self.symbolize_user_addrs(
+                    &[addr],
+                    pid,
+                    *debug_syms,
+                    *perf_map,
+                    *map_files,
+                    *proc_root,
+                    None,
+                    None,
+                    None,
+                )?;
+                debug_assert!(symbols.len() == 1, "{symbols:#?}");
+                // SANITY: `symbolize_user_addrs` should *always* return
+                //         one result for one input (except on error
+                //         paths, of course).
+                Ok(symbols.pop().unwrap())
+            }
+            Source::ProcessSnapshot(snapshot) => {
+                let addr =
+                    match input {
+                        Input::AbsAddr(addr) => addr,
+                        Input::VirtOffset(..) => return Err(Error::with_unsupported(
+                            "process snapshot symbolization does not support virtual offset inputs",
+                        )),
+                        Input::FileOffset(..) => return Err(Error::with_unsupported(
+                            "process snapshot symbolization does not support file offset inputs",
+                        )),
+                    };
+
+                let mut symbols = self.symbolize_user_addrs(
+                    &[addr],
+                    &snapshot.pid,
+                    snapshot.debug_syms,
+                    false,
+                    false,
+                    false,
+                    Some(&snapshot.entries),
+                    snapshot.vdso.as_deref(),
+                    None,
+                )?;
                 debug_assert!(symbols.len() == 1, "{symbols:#?}");
                 // SANITY: `symbolize_user_addrs` should *always* return
                 //         one result for one input (except on error
@@ -1442,7 +4714,7 @@ impl Symbolizer {
                     }
                 };
 
-                let resolver = Rc::new(GsymResolver::with_data(data)?);
+                let resolver = Rc::new(GsymResolver::with_data(data, self.gsym_forward_compat)?);
                 self.symbolize_with_resolver(addr, &Resolver::Uncached(resolver.deref()))
             }
             #[cfg(feature = "gsym")]
@@ -1471,13 +4743,58 @@ impl Symbolizer {
         }
     }
 
+    /// Calculate the load bias of an ELF file for the purpose of
+    /// symbolizing absolute addresses.
+    fn elf_load_bias(&self, resolver: &ElfResolver, base_addr: Option<&BaseAddr>) -> Result<Addr> {
+        let base_addr = base_addr.ok_or_else(|| {
+            Error::with_unsupported(
+                "ELF symbolization does not support absolute address inputs without a base address",
+            )
+        })?;
+        resolver.load_bias(base_addr)
+    }
+
+    /// Retrieve the options to use for looking up symbols.
+    fn find_sym_opts(&self) -> &FindSymOpts {
+        if self.names_only.get() {
+            &FindSymOpts::Basic
+        } else {
+            &self.find_sym_opts
+        }
+    }
+
+    /// Invoke `f` with symbol lookup restricted to symbol names, if
+    /// `names_only` is `true`.
+    fn with_names_only<T, F>(&self, names_only: bool, f: F) -> T
+    where
+        F: FnOnce() -> T,
+    {
+        let prev = self.names_only.replace(names_only);
+        let result = f();
+        let () = self.names_only.set(prev);
+        result
+    }
+
     fn maybe_debug_dirs(&self, debug_syms: bool) -> Option<&[PathBuf]> {
         #[cfg(feature = "dwarf")]
         let debug_dirs = &self.debug_dirs;
         #[cfg(not(feature = "dwarf"))]
         let debug_dirs = &[];
+        let debug_syms = debug_syms && self.elf_sym_policy != ElfSymPolicy::SymtabOnly;
         debug_syms.then_some(debug_dirs)
     }
+
+    /// Check whether `resolver` satisfies the configured
+    /// [`ElfSymPolicy`], returning the reason to report for addresses
+    /// in the file if it does not.
+    fn check_elf_sym_policy(&self, resolver: &ElfResolver) -> Result<Option<Reason>> {
+        match self.elf_sym_policy {
+            ElfSymPolicy::RequireDebugInfo if !resolver.uses_debug_info()? => {
+                Ok(Some(Reason::MissingSyms))
+            }
+            _ => Ok(None),
+        }
+    }
 }
 
 impl Default for Symbolizer {
@@ -1487,15 +4804,120 @@ impl Default for Symbolizer {
 }
 
 
+/// The absolute addresses covered by a source, as used by
+/// [`Symbolizer::symbolize_multi`].
+#[derive(Debug)]
+enum Coverage {
+    /// All addresses of the given address space.
+    Space(AddrSpace),
+    /// The addresses in the given range.
+    Range(Range<Addr>),
+}
+
+/// A structure for efficiently looking up the source covering an
+/// address, as used by [`Symbolizer::symbolize_multi`].
+#[derive(Debug)]
+struct SourceDispatch {
+    /// Sorted and non-overlapping address ranges, along with the index
+    /// of the source covering each.
+    ranges: Vec<(Range<Addr>, usize)>,
+    /// Address spaces, along with the index of the first source
+    /// covering each.
+    spaces: Vec<(AddrSpace, usize)>,
+}
+
+impl SourceDispatch {
+    fn new(coverages: &[Coverage]) -> Self {
+        let mut spaces = Vec::new();
+        let mut events = Vec::new();
+        for (idx, coverage) in coverages.iter().enumerate() {
+            match coverage {
+                Coverage::Space(space) => {
+                    if !spaces.iter().any(|(other, _idx)| other == space) {
+                        let () = spaces.push((*space, idx));
+                    }
+                }
+                Coverage::Range(range) if !range.is_empty() => {
+                    // Smaller ranges take precedence over larger ones,
+                    // with ties being broken by source order.
+                    let key = (range.end - range.start, idx);
+                    let () = events.push((range.start, true, key));
+                    let () = events.push((range.end, false, key));
+                }
+                Coverage::Range(..) => (),
+            }
+        }
+        let () = events.sort_unstable_by_key(|(addr, _start, _key)| *addr);
+
+        // Sweep over all range boundaries, keeping track of the ranges
+        // covering the current address and emitting a new range
+        // whenever the source taking precedence changes.
+        let mut ranges = Vec::new();
+        let mut active = BTreeSet::new();
+        let mut current = None::<(Addr, usize)>;
+        let mut events = events.into_iter().peekable();
+        while let Some((addr, start, key)) = events.next() {
+            if start {
+                let _inserted = active.insert(key);
+            } else {
+                let _removed = active.remove(&key);
+            }
+
+            if matches!(events.peek(), Some((next, _start, _key)) if *next == addr) {
+                continue
+            }
+
+            let winner = active.first().map(|(_len, idx)| *idx);
+            if current.map(|(_start, idx)| idx) != winner {
+                if let Some((start, idx)) = current.take() {
+                    let () = ranges.push((start..addr, idx));
+                }
+                current = winner.map(|idx| (addr, idx));
+            }
+        }
+        debug_assert_eq!(current, None);
+
+        Self { ranges, spaces }
+    }
+
+    /// Find the index of the source covering `addr`, if any.
+    fn find(&self, addr: Addr) -> Option<usize> {
+        if let Some(idx) = util::find_range_by_key(&self.ranges, addr, |(range, _idx)| range) {
+            return Some(self.ranges[idx].1)
+        }
+
+        let space = match Arch::host() {
+            Some(arch) => AddrSpace::classify(addr, arch),
+            // Without knowledge of the architecture's address space
+            // layout we fall back to splitting the address space in
+            // halves.
+            None if addr >> (Addr::BITS - 1) == 0 => AddrSpace::User,
+            None => AddrSpace::Kernel,
+        };
+        self.spaces
+            .iter()
+            .find_map(|(other, idx)| (*other == space).then_some(*idx))
+    }
+}
+
 #[cfg(test)]
 #[allow(clippy::missing_transmute_annotations)]
 mod tests {
     use super::*;
 
+    use std::fs::read_link;
+    use std::fs::rename;
+    use std::fs::write;
+    #[cfg(unix)]
+    use std::os::unix::ffi::OsStrExt as _;
+    use std::time::Duration;
+
+    use tempfile::tempdir;
     use test_log::test;
 
     use crate::maps::Perm;
-    use crate::symbolize::CodeInfo;
+    use crate::test_utils::ElfBuilder;
+    use crate::SymType;
 
 
     /// Exercise the `Debug` representation of various types.
@@ -1511,12 +4933,12 @@ mod tests {
             .join("data")
             .join("test-stable-addrs.bin");
         let parser = Rc::new(ElfParser::open(&test_elf).unwrap());
-        let resolver = ElfResolver::from_parser(parser, None).unwrap();
+        let resolver = ElfResolver::from_parser(parser, None, false, false).unwrap();
         let resolver = Resolver::Cached(&resolver);
         assert_ne!(format!("{resolver:?}"), "");
         assert_ne!(format!("{:?}", resolver.inner()), "");
 
-        let entries = maps::parse(Pid::Slf).unwrap();
+        let entries = maps::parse(&Pid::Slf).unwrap();
         let () = entries.for_each(|entry| {
             assert_ne!(format!("{:?}", DebugMapsEntry(&entry.unwrap())), "");
         });
@@ -1565,6 +4987,163 @@ mod tests {
         );
     }
 
+    /// Check that source code paths are reported relative to the
+    /// configured source root, where applicable.
+    #[test]
+    fn source_root_localization() {
+        let info = |dir: Option<&'static str>, file: &'static str| CodeInfo {
+            dir: dir.map(|dir| Cow::Borrowed(Path::new(dir))),
+            file: Cow::Borrowed(OsStr::new(file)),
+            line: Some(42),
+            column: Some(2),
+            _non_exhaustive: (),
+        };
+
+        let symbolizer = Symbolizer::new();
+        let localized = symbolizer.localize_code_info(info(Some("/src/proj/lib"), "a.c"));
+        assert_eq!(localized.to_path(), Path::new("/src/proj/lib/a.c"));
+
+        let symbolizer = Symbolizer::builder()
+            .set_source_root(Some("/src/proj"))
+            .build();
+        let localized = symbolizer.localize_code_info(info(Some("/src/proj/lib"), "a.c"));
+        assert_eq!(localized.dir.as_deref(), Some(Path::new("lib")));
+        assert_eq!(localized.file, OsStr::new("a.c"));
+        assert_eq!(localized.line, Some(42));
+        assert_eq!(localized.column, Some(2));
+
+        let localized = symbolizer.localize_code_info(info(Some("/src/proj"), "lib/b.c"));
+        assert_eq!(localized.to_path(), Path::new("lib/b.c"));
+
+        let localized = symbolizer.localize_code_info(info(Some("/src/proj"), "c.c"));
+        assert_eq!(localized.dir, None);
+        assert_eq!(localized.to_path(), Path::new("c.c"));
+
+        // Only whole components are matched.
+        let localized = symbolizer.localize_code_info(info(Some("/src/project"), "d.c"));
+        assert_eq!(localized.to_path(), Path::new("/src/project/d.c"));
+
+        // Relative paths are left alone.
+        let localized = symbolizer.localize_code_info(info(None, "e.c"));
+        assert_eq!(localized.to_path(), Path::new("e.c"));
+    }
+
+    /// Check that paths are converted to valid UTF-8 if asked for.
+    #[cfg(unix)]
+    #[test]
+    fn lossy_path_reporting() {
+        let mut builder = ElfBuilder::new();
+        let text = builder.add_section(".text", 0x4000, vec![0xcc; 0x100]);
+        let () = builder.add_symbol("first", text, 0x4000, 0x20, SymType::Function);
+        let dir = tempdir().unwrap();
+        let path = dir.path().join(OsStr::from_bytes(b"test-\xff.bin"));
+        let () = write(&path, builder.build()).unwrap();
+        let lossy = PathBuf::from(path.to_string_lossy().into_owned());
+
+        let src = Source::Elf(Elf::new(&path));
+        let symbolizer = Symbolizer::new();
+        let frames = symbolizer
+            .symbolize_frames(&src, Input::VirtOffset(&[0x4010]))
+            .unwrap();
+        assert_eq!(frames.len(), 1);
+        assert_eq!(frames[0].module.as_deref(), Some(path.as_path()));
+        assert_eq!(frames[0].sym.as_ref().unwrap().name, "first");
+
+        let code_info = CodeInfo {
+            dir: Some(Cow::Borrowed(Path::new(OsStr::from_bytes(b"/src/\xfe")))),
+            file: Cow::Borrowed(OsStr::from_bytes(b"a\xff.c")),
+            line: Some(42),
+            column: None,
+            _non_exhaustive: (),
+        };
+        let symbolized = symbolizer.maybe_lossy_paths(Symbolized::Code(code_info.clone()));
+        assert_eq!(symbolized.code_info(), Some(&code_info));
+
+        let symbolizer = Symbolizer::builder().enable_lossy_paths(true).build();
+        let frames = symbolizer
+            .symbolize_frames(&src, Input::VirtOffset(&[0x4010]))
+            .unwrap();
+        assert_eq!(frames[0].module.as_deref(), Some(lossy.as_path()));
+        assert_eq!(frames[0].sym.as_ref().unwrap().name, "first");
+
+        let symbolized = symbolizer.maybe_lossy_paths(Symbolized::Code(code_info));
+        let code_info = symbolized.code_info().unwrap();
+        assert_eq!(code_info.dir.as_deref(), Some(Path::new("/src/\u{fffd}")));
+        assert_eq!(code_info.file, OsStr::new("a\u{fffd}.c"));
+        assert_eq!(code_info.line, Some(42));
+    }
+
+    /// Check that we can symbolize addresses using multiple sources at
+    /// once.
+    #[test]
+    fn multi_source_symbolization() {
+        let mut builder = ElfBuilder::new();
+        let text = builder.add_section(".text", 0x4000, vec![0xcc; 0x100]);
+        let () = builder.add_symbol("first", text, 0x4000, 0x20, SymType::Function);
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("test.bin");
+        let () = write(&path, builder.build()).unwrap();
+
+        let mut elf = Elf::new(&path);
+        elf.base_addr = Some(BaseAddr::Addr(0x10000));
+        let srcs = [Source::Process(Process::new(Pid::Slf)), Source::Elf(elf)];
+        let kernel_addr = if Arch::host().is_some() {
+            0xffff_ffff_8100_0000
+        } else {
+            Addr::MAX
+        };
+        let addrs = [0x14010, 0x10, kernel_addr, 0x14000];
+        let symbolizer = Symbolizer::new();
+        let symbolized = symbolizer.symbolize_multi(&srcs, &addrs).unwrap();
+        assert_eq!(symbolized.len(), addrs.len());
+        let sym = symbolized[0].as_sym().unwrap();
+        assert_eq!(sym.name, "first");
+        assert_eq!(sym.addr, 0x4000);
+        assert_eq!(sym.offset, 0x10);
+        assert_eq!(symbolized[1], Symbolized::Unknown(Reason::Unmapped));
+        assert_eq!(symbolized[2], Symbolized::Unknown(Reason::Unmapped));
+        assert_eq!(symbolized[3].as_sym().unwrap().name, "first");
+
+        // ELF sources without a base address cannot be used.
+        let srcs = [Source::Elf(Elf::new(&path))];
+        let err = symbolizer.symbolize_multi(&srcs, &addrs).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::Unsupported);
+    }
+
+    /// Check that we pick the expected source when multiple ones
+    /// cover an address.
+    #[test]
+    fn source_dispatch() {
+        let coverages = [
+            Coverage::Space(AddrSpace::User),
+            Coverage::Range(0x1000..0x5000),
+            Coverage::Range(0x2000..0x3000),
+            Coverage::Range(0x2000..0x3000),
+            Coverage::Range(0x4800..0x9000),
+            Coverage::Range(0xa000..0xa000),
+            Coverage::Space(AddrSpace::User),
+        ];
+        let dispatch = SourceDispatch::new(&coverages);
+        assert_eq!(
+            dispatch.ranges,
+            vec![
+                (0x1000..0x2000, 1),
+                (0x2000..0x3000, 2),
+                (0x3000..0x5000, 1),
+                (0x5000..0x9000, 4),
+            ]
+        );
+        assert_eq!(dispatch.find(0xfff), Some(0));
+        assert_eq!(dispatch.find(0x1000), Some(1));
+        assert_eq!(dispatch.find(0x2fff), Some(2));
+        assert_eq!(dispatch.find(0x3000), Some(1));
+        assert_eq!(dispatch.find(0x4800), Some(1));
+        assert_eq!(dispatch.find(0x5000), Some(4));
+        assert_eq!(dispatch.find(0x9000), Some(0));
+        assert_eq!(dispatch.find(0xa000), Some(0));
+        assert_eq!(dispatch.find(Addr::MAX), None);
+    }
+
     /// Make sure that we error out as expected on certain input
     /// variants.
     #[test]
@@ -1648,6 +5227,7 @@ mod tests {
                 range: 0x10000..0x20000,
                 perm: Perm::default(),
                 offset: 0,
+                inode: 0,
                 path_name: Some(PathName::Component("a-component".to_string())),
                 build_id: None,
             }),
@@ -1655,6 +5235,7 @@ mod tests {
                 range: 0x30000..0x40000,
                 perm: Perm::default(),
                 offset: 0,
+                inode: 0,
                 path_name: None,
                 build_id: None,
             }),
@@ -1668,8 +5249,12 @@ mod tests {
             pid: Pid::Slf,
             debug_syms: false,
             perf_map: false,
+            jitdump: None,
+            vdso: None,
             map_files: false,
+            proc_root: None,
             all_symbols: Vec::new(),
+            modules: None,
         };
         let () = normalize_sorted_user_addrs_with_entries(
             addrs.as_slice().iter().copied(),
@@ -1681,9 +5266,374 @@ mod tests {
         let syms = handler.all_symbols;
         assert_eq!(syms.len(), 2);
         assert!(
-            matches!(syms[0], Symbolized::Unknown(Reason::Unsupported)),
+            matches!(syms[0], Symbolized::Unknown(Reason::SpecialMapping)),
             "{:?}",
             syms[0]
         );
     }
+
+    /// Check that we can introspect the memory mappings of the current
+    /// process.
+    #[test]
+    fn process_mapping_introspection() {
+        let symbolizer = Symbolizer::new();
+        let src = Source::Process(Process::new(Pid::Slf));
+        let mappings = symbolizer.process_mappings(&src).unwrap();
+        assert!(!mappings.is_empty());
+        assert!(mappings
+            .windows(2)
+            .all(|pair| pair[0].range.start < pair[1].range.start));
+
+        let exe = read_link("/proc/self/exe").unwrap();
+        let mapping = mappings
+            .iter()
+            .find(|mapping| mapping.path.as_deref() == Some(exe.as_path()))
+            .unwrap();
+        assert!(
+            matches!(
+                mapping.resolver,
+                Ok(ResolverKind::Elf) | Ok(ResolverKind::Dwarf)
+            ),
+            "{mapping:?}"
+        );
+
+        let src = Source::Kernel(Kernel::default());
+        let err = symbolizer.process_mappings(&src).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::Unsupported);
+    }
+
+    /// Check that addresses in special mappings such as `[stack]` are
+    /// reported with a corresponding reason.
+    #[test]
+    fn special_mapping_reasons() {
+        let entries = maps::parse(&Pid::Slf)
+            .unwrap()
+            .collect::<Result<Vec<_>>>()
+            .unwrap();
+        let components = entries
+            .iter()
+            .filter(|entry| maps::is_symbolization_relevant(entry))
+            .filter_map(|entry| match &entry.path_name {
+                Some(PathName::Component(component)) if !vdso::is_pseudo_module(component) => {
+                    Some((entry.range.start, component.as_str()))
+                }
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        // The main thread's stack should always be present.
+        assert!(
+            components
+                .iter()
+                .any(|(_addr, component)| *component == "[stack]"),
+            "{components:?}"
+        );
+
+        let symbolizer = Symbolizer::new();
+        let src = Source::Process(Process::new(Pid::Slf));
+        for (addr, component) in components {
+            let expected = match component {
+                "[stack]" => Reason::Stack,
+                "[heap]" => Reason::Heap,
+                _ => Reason::SpecialMapping,
+            };
+            let result = symbolizer
+                .symbolize_single(&src, Input::AbsAddr(addr))
+                .unwrap();
+            assert_eq!(result, Symbolized::Unknown(expected), "{component}");
+        }
+
+        assert_eq!(Reason::from_component("[stack:1337]"), Reason::Stack);
+        assert_eq!(Reason::from_component("[uprobes]"), Reason::SpecialMapping);
+    }
+
+    /// Check that the number of files cached by a [`Symbolizer`] is
+    /// limited as configured and that clearing the cache makes room.
+    #[test]
+    fn max_cached_files() {
+        let dir = tempdir().unwrap();
+        let mut builder = ElfBuilder::new();
+        let text = builder.add_section(".text", 0x4000, vec![0xcc; 0x100]);
+        let () = builder.add_symbol("func", text, 0x4000, 0x20, SymType::Function);
+        let elf = builder.build();
+        let path1 = dir.path().join("file1.bin");
+        let () = write(&path1, &elf).unwrap();
+        let path2 = dir.path().join("file2.bin");
+        let () = write(&path2, &elf).unwrap();
+
+        let mut symbolizer = Symbolizer::builder().set_max_cached_files(Some(1)).build();
+        let src1 = Source::Elf(Elf::new(&path1));
+        let src2 = Source::Elf(Elf::new(&path2));
+
+        let sym = symbolizer
+            .symbolize_single(&src1, Input::VirtOffset(0x4010))
+            .unwrap()
+            .into_sym()
+            .unwrap();
+        assert_eq!(sym.name, "func");
+        let usage = symbolizer.cache_usage();
+        assert_eq!(usage.files, 1);
+        assert_eq!(usage.max_files, Some(1));
+
+        let err = symbolizer
+            .symbolize_single(&src2, Input::VirtOffset(0x4010))
+            .unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::OutOfMemory);
+
+        let () = symbolizer.clear_cache();
+        assert_eq!(symbolizer.cache_usage().files, 0);
+
+        let sym = symbolizer
+            .symbolize_single(&src2, Input::VirtOffset(0x4010))
+            .unwrap()
+            .into_sym()
+            .unwrap();
+        assert_eq!(sym.name, "func");
+        assert_eq!(symbolizer.cache_usage().files, 1);
+
+        // Other instances are unaffected by the limit.
+        let symbolizer = Symbolizer::new();
+        let _sym = symbolizer
+            .symbolize_single(&src1, Input::VirtOffset(0x4010))
+            .unwrap();
+        let _sym = symbolizer
+            .symbolize_single(&src2, Input::VirtOffset(0x4010))
+            .unwrap();
+        let usage = symbolizer.cache_usage();
+        assert_eq!(usage.files, 2);
+        assert_eq!(usage.max_files, None);
+    }
+
+    /// Check that the configured [`ElfSymPolicy`] is honored when
+    /// symbolizing addresses in ELF files.
+    #[test]
+    fn elf_sym_policy() {
+        let dir = tempdir().unwrap();
+        let mut builder = ElfBuilder::new();
+        let text = builder.add_section(".text", 0x4000, vec![0xcc; 0x100]);
+        let () = builder.add_symbol("func", text, 0x4000, 0x20, SymType::Function);
+        let no_dwarf = dir.path().join("no-dwarf.bin");
+        let () = write(&no_dwarf, builder.build()).unwrap();
+
+        let dwarf = Path::new(&env!("CARGO_MANIFEST_DIR"))
+            .join("data")
+            .join("test-stable-addrs.bin");
+
+        let symbolize = |policy, path: &Path, addr| {
+            let symbolizer = Symbolizer::builder().set_elf_sym_policy(policy).build();
+            let src = Source::Elf(Elf::new(path));
+            symbolizer
+                .symbolize_single(&src, Input::VirtOffset(addr))
+                .unwrap()
+                .into_owned()
+        };
+
+        for policy in [ElfSymPolicy::PreferDebugInfo, ElfSymPolicy::SymtabOnly] {
+            let sym = symbolize(policy, &no_dwarf, 0x4010).into_sym().unwrap();
+            assert_eq!(sym.name, "func");
+            assert_eq!(sym.code_info, None);
+        }
+
+        let result = symbolize(ElfSymPolicy::RequireDebugInfo, &no_dwarf, 0x4010);
+        assert_eq!(result, Symbolized::Unknown(Reason::MissingSyms));
+
+        let sym = symbolize(ElfSymPolicy::PreferDebugInfo, &dwarf, 0x2000200)
+            .into_sym()
+            .unwrap();
+        assert_eq!(sym.name, "factorial");
+        assert!(sym.code_info.is_some());
+
+        let sym = symbolize(ElfSymPolicy::SymtabOnly, &dwarf, 0x2000200)
+            .into_sym()
+            .unwrap();
+        assert_eq!(sym.name, "factorial");
+        assert_eq!(sym.code_info, None);
+
+        let sym = symbolize(ElfSymPolicy::RequireDebugInfo, &dwarf, 0x2000200)
+            .into_sym()
+            .unwrap();
+        assert_eq!(sym.name, "factorial");
+        assert!(sym.code_info.is_some());
+    }
+
+    /// Check that per-address symbolization results are cached and
+    /// invalidated when the underlying file changes.
+    #[test]
+    fn addr_cache_invalidation() {
+        fn build_elf(path: &Path, name: &str) {
+            let mut builder = ElfBuilder::new();
+            let text = builder.add_section(".text", 0x4000, vec![0xcc; 0x100]);
+            let () = builder.add_symbol(name, text, 0x4000, 0x20, SymType::Function);
+            let tmp = path.with_extension("tmp");
+            let () = write(&tmp, builder.build()).unwrap();
+            // Replace the file atomically, making sure that it is
+            // recognized as changed.
+            let () = rename(&tmp, path).unwrap();
+        }
+
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("test.bin");
+        let () = build_elf(&path, "first");
+
+        let symbolizer = Symbolizer::builder()
+            .set_addr_cache_capacity(Some(1))
+            .build();
+        let src = Source::Elf(Elf::new(&path));
+        let symbolize = |addr| {
+            symbolizer
+                .symbolize_single(&src, Input::VirtOffset(addr))
+                .unwrap()
+                .into_sym()
+                .unwrap()
+                .into_owned()
+        };
+
+        let sym = symbolize(0x4010);
+        assert_eq!(sym.name, "first");
+        assert_eq!(sym.offset, 0x10);
+        assert_eq!(symbolize(0x4010), sym);
+        // Eviction must not affect results.
+        assert_eq!(symbolize(0x4008).offset, 0x8);
+        assert_eq!(symbolize(0x4010), sym);
+
+        let () = build_elf(&path, "second");
+        assert_eq!(symbolize(0x4010).name, "second");
+    }
+
+    /// Check that warming up a symbolizer adheres to the provided
+    /// budget and reports progress as expected.
+    #[test]
+    fn warm_up_budget() {
+        let dir = tempdir().unwrap();
+        let paths = ["first.bin", "second.bin"].map(|name| {
+            let mut builder = ElfBuilder::new();
+            let text = builder.add_section(".text", 0x4000, vec![0xcc; 0x100]);
+            let () = builder.add_symbol("func", text, 0x4000, 0x20, SymType::Function);
+            let path = dir.path().join(name);
+            let () = write(&path, builder.build()).unwrap();
+            path
+        });
+        let srcs = paths
+            .iter()
+            .map(|path| Source::Elf(Elf::new(path)))
+            .collect::<Vec<_>>();
+
+        let symbolizer = Symbolizer::new();
+        let mut reports = Vec::new();
+        let complete = symbolizer
+            .warm_up(&srcs, &WarmUpBudget::default(), |progress| {
+                assert_eq!(progress.error.map(ToString::to_string), None);
+                let () = reports.push((
+                    progress.module.map(Path::to_path_buf),
+                    progress.done,
+                    progress.total,
+                ));
+            })
+            .unwrap();
+        assert!(complete);
+        assert_eq!(
+            reports,
+            vec![
+                (Some(paths[0].clone()), 1, 2),
+                (Some(paths[1].clone()), 2, 2)
+            ]
+        );
+
+        let budget = WarmUpBudget {
+            time: Some(Duration::ZERO),
+            ..Default::default()
+        };
+        let complete = Symbolizer::new()
+            .warm_up(&srcs, &budget, |_progress| panic!("no progress expected"))
+            .unwrap();
+        assert!(!complete);
+
+        let budget = WarmUpBudget {
+            file_size: Some(paths[0].metadata().unwrap().len()),
+            ..Default::default()
+        };
+        let mut done = 0;
+        let complete = Symbolizer::new()
+            .warm_up(&srcs, &budget, |progress| done = progress.done)
+            .unwrap();
+        assert!(!complete);
+        assert_eq!(done, 1);
+
+        // Failure to warm up a module should be reported, but not be
+        // fatal.
+        let srcs = [Source::Elf(Elf::new(dir.path().join("does-not-exist")))];
+        let mut errors = 0;
+        let complete = Symbolizer::new()
+            .warm_up(&srcs, &WarmUpBudget::default(), |progress| {
+                errors += usize::from(progress.error.is_some())
+            })
+            .unwrap();
+        assert!(complete);
+        assert_eq!(errors, 1);
+    }
+
+    /// Check that we can warm up a symbolizer for the current process.
+    #[test]
+    fn warm_up_process() {
+        // Indexing the debug information of the test binary itself
+        // is rather time consuming, so skip it.
+        let mut process = Process::new(Pid::Slf);
+        process.debug_syms = false;
+        let srcs = [Source::Process(process)];
+        let mut total = 0;
+        let complete = Symbolizer::new()
+            .warm_up(&srcs, &WarmUpBudget::default(), |progress| {
+                total = progress.total
+            })
+            .unwrap();
+        assert!(complete);
+        assert_ne!(total, 0);
+    }
+
+    /// Check that files opened by worker threads count against the
+    /// budget of the symbolizer they are working on behalf of.
+    #[test]
+    fn parallel_fd_budget() {
+        let mut process = Process::new(Pid::Slf);
+        process.debug_syms = false;
+        let src = Source::Process(process);
+        let addrs = [
+            parallel_fd_budget as *const () as Addr,
+            libc::getpid as *const () as Addr,
+        ]
+        .into_iter()
+        .cycle()
+        .take(2 * 1024)
+        .collect::<Vec<_>>();
+
+        let symbolizer = Symbolizer::builder()
+            .set_parallelism(3)
+            .set_max_open_files(Some(1))
+            .build();
+        let results = symbolizer.symbolize(&src, Input::AbsAddr(&addrs)).unwrap();
+        assert_eq!(results.len(), addrs.len());
+
+        let usage = symbolizer.fd_usage();
+        assert_eq!(usage.max_open, Some(1));
+        assert_eq!(usage.open, 1);
+    }
+
+    /// Check that asynchronous symbolization does not wait on a FIFO
+    /// when strict file access is enabled.
+    #[cfg(all(feature = "async", unix))]
+    #[test]
+    fn symbolize_async_strict_fifo() {
+        use crate::util::mkfifo;
+
+        let tmpdir = tempdir().unwrap();
+        let fifo = tmpdir.path().join("fifo");
+        let () = mkfifo(&fifo).unwrap();
+
+        let symbolizer = Symbolizer::builder()
+            .enable_strict_file_access(true)
+            .build();
+        let src = Source::Elf(Elf::new(&fifo));
+        let future = symbolizer.symbolize_async(&src, Input::VirtOffset(&[0x1000]));
+        let err = pollster::block_on(future).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidInput);
+    }
 }
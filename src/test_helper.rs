@@ -23,6 +23,8 @@ pub fn find_the_answer_fn(mmap: &Mmap) -> (inspect::SymInfo<'static>, Addr) {
     let opts = inspect::FindAddrOpts {
         offset_in_file: true,
         sym_type: SymType::Function,
+        code_info: false,
+        decl_file: None,
     };
     let syms = elf_parser.find_addr("the_answer", &opts).unwrap();
     // There is only one symbol with this address in there.
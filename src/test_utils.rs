@@ -0,0 +1,620 @@
+//! Utilities for testing code working with **blazesym**.
+//!
+//! Currently this module provides [`ElfBuilder`], a generator for
+//! synthetic ELF files. Such files can be used for exercising
+//! symbolization and inspection logic without the need for committing
+//! binary fixtures.
+
+use crate::util::align_up_usize;
+use crate::SymType;
+
+
+const SHN_LORESERVE: usize = 0xff00;
+const SHN_XINDEX: u16 = 0xffff;
+
+const SHT_PROGBITS: u32 = 1;
+const SHT_SYMTAB: u32 = 2;
+const SHT_STRTAB: u32 = 3;
+const SHT_NOTE: u32 = 7;
+
+const SHF_ALLOC: u64 = 0x2;
+const SHF_EXECINSTR: u64 = 0x4;
+
+const PT_LOAD: u32 = 1;
+const PF_X: u32 = 0x1;
+const PF_R: u32 = 0x4;
+
+const STB_GLOBAL: u8 = 1;
+const STT_OBJECT: u8 = 1;
+const STT_FUNC: u8 = 2;
+
+/// The alignment we use for all data in the generated file.
+const ALIGN: usize = 16;
+
+
+/// The class of an ELF file.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum Class {
+    /// A 32 bit ELF file.
+    Elf32,
+    /// A 64 bit ELF file.
+    #[default]
+    Elf64,
+}
+
+
+/// The byte order used for encoding an ELF file.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Endianness {
+    /// Little endian byte order.
+    Little,
+    /// Big endian byte order.
+    Big,
+}
+
+impl Endianness {
+    /// Retrieve the byte order of the host system.
+    #[inline]
+    pub fn native() -> Self {
+        if cfg!(target_endian = "little") {
+            Self::Little
+        } else {
+            Self::Big
+        }
+    }
+}
+
+impl Default for Endianness {
+    #[inline]
+    fn default() -> Self {
+        Self::native()
+    }
+}
+
+
+#[derive(Debug)]
+struct Section {
+    name: String,
+    addr: u64,
+    data: Vec<u8>,
+}
+
+#[derive(Debug)]
+struct Symbol {
+    name: String,
+    /// The index of the user section the symbol belongs to.
+    section: usize,
+    addr: u64,
+    size: u64,
+    sym_type: SymType,
+}
+
+#[derive(Debug)]
+struct Note {
+    name: String,
+    type_: u32,
+    desc: Vec<u8>,
+}
+
+
+/// A writer producing data in the format of a specific ELF class and
+/// byte order.
+struct Writer {
+    class: Class,
+    endianness: Endianness,
+    data: Vec<u8>,
+}
+
+impl Writer {
+    fn u8(&mut self, value: u8) {
+        let () = self.data.push(value);
+    }
+
+    fn u16(&mut self, value: u16) {
+        let bytes = match self.endianness {
+            Endianness::Little => value.to_le_bytes(),
+            Endianness::Big => value.to_be_bytes(),
+        };
+        let () = self.data.extend_from_slice(&bytes);
+    }
+
+    fn u32(&mut self, value: u32) {
+        let bytes = match self.endianness {
+            Endianness::Little => value.to_le_bytes(),
+            Endianness::Big => value.to_be_bytes(),
+        };
+        let () = self.data.extend_from_slice(&bytes);
+    }
+
+    fn u64(&mut self, value: u64) {
+        let bytes = match self.endianness {
+            Endianness::Little => value.to_le_bytes(),
+            Endianness::Big => value.to_be_bytes(),
+        };
+        let () = self.data.extend_from_slice(&bytes);
+    }
+
+    /// Write a class dependent "word" (i.e., an address, offset, or
+    /// size). For 32 bit files the value is truncated.
+    fn word(&mut self, value: u64) {
+        match self.class {
+            Class::Elf32 => self.u32(value as u32),
+            Class::Elf64 => self.u64(value),
+        }
+    }
+
+    fn bytes(&mut self, bytes: &[u8]) {
+        let () = self.data.extend_from_slice(bytes);
+    }
+
+    fn align(&mut self, align: usize) {
+        let len = align_up_usize(self.data.len(), align);
+        let () = self.data.resize(len, 0);
+    }
+
+    fn offset(&self) -> u64 {
+        self.data.len() as u64
+    }
+}
+
+
+/// A string table under construction.
+struct StrTab {
+    data: Vec<u8>,
+}
+
+impl StrTab {
+    fn new() -> Self {
+        Self { data: vec![0] }
+    }
+
+    fn add(&mut self, s: &str) -> u32 {
+        let idx = self.data.len() as u32;
+        let () = self.data.extend_from_slice(s.as_bytes());
+        let () = self.data.push(0);
+        idx
+    }
+}
+
+
+/// A section header under construction.
+struct Shdr {
+    name: u32,
+    type_: u32,
+    flags: u64,
+    addr: u64,
+    offset: u64,
+    size: u64,
+    link: u32,
+    info: u32,
+    entsize: u64,
+}
+
+
+/// A builder for synthetic ELF files.
+///
+/// The generated file contains one section per call to
+/// [`add_section`][Self::add_section], each covered by a `PT_LOAD`
+/// program header, as well as `.symtab` and `.strtab` sections with
+/// all added symbols. Notes end up in a `.note` section.
+///
+/// ```
+/// use blazesym::test_utils::ElfBuilder;
+/// use blazesym::SymType;
+///
+/// let mut builder = ElfBuilder::new();
+/// let text = builder.add_section(".text", 0x1000, vec![0xcc; 0x100]);
+/// let () = builder.add_symbol("main", text, 0x1000, 0x20, SymType::Function);
+/// let elf = builder.build();
+/// assert_eq!(&elf[0..4], b"\x7fELF");
+/// ```
+#[derive(Debug, Default)]
+pub struct ElfBuilder {
+    class: Class,
+    endianness: Endianness,
+    file_type: u16,
+    sections: Vec<Section>,
+    symbols: Vec<Symbol>,
+    notes: Vec<Note>,
+    padding_sections: usize,
+    symtab_names: Option<(String, String)>,
+    symtab_entsize: Option<u64>,
+    symtab_link: Option<u32>,
+    omit_shstrtab: bool,
+}
+
+impl ElfBuilder {
+    /// Create a new [`ElfBuilder`] for a 64 bit shared object in the
+    /// byte order of the host.
+    pub fn new() -> Self {
+        Self {
+            file_type: 3,
+            ..Default::default()
+        }
+    }
+
+    /// Set the class of the ELF file to generate.
+    pub fn set_class(&mut self, class: Class) -> &mut Self {
+        self.class = class;
+        self
+    }
+
+    /// Set the byte order to use for the ELF file to generate.
+    pub fn set_endianness(&mut self, endianness: Endianness) -> &mut Self {
+        self.endianness = endianness;
+        self
+    }
+
+    /// Set the ELF file type (`e_type`), e.g., `2` for an executable
+    /// or `3` for a shared object.
+    pub fn set_file_type(&mut self, file_type: u16) -> &mut Self {
+        self.file_type = file_type;
+        self
+    }
+
+    /// Add an allocated, executable section with the given contents,
+    /// to be loaded at `addr`.
+    ///
+    /// This method returns an index that can be used for referencing
+    /// the section when adding symbols.
+    pub fn add_section(&mut self, name: &str, addr: u64, data: Vec<u8>) -> usize {
+        let () = self.sections.push(Section {
+            name: name.to_string(),
+            addr,
+            data,
+        });
+        self.sections.len() - 1
+    }
+
+    /// Add a global symbol to the symbol table.
+    ///
+    /// `section` is an index as returned by
+    /// [`add_section`][Self::add_section].
+    ///
+    /// # Panics
+    /// This method panics if `section` does not refer to a previously
+    /// added section.
+    pub fn add_symbol(
+        &mut self,
+        name: &str,
+        section: usize,
+        addr: u64,
+        size: u64,
+        sym_type: SymType,
+    ) {
+        assert!(
+            section < self.sections.len(),
+            "section index {section} out of bounds"
+        );
+        let () = self.symbols.push(Symbol {
+            name: name.to_string(),
+            section,
+            addr,
+            size,
+            sym_type,
+        });
+    }
+
+    /// Add a note with the given owner name, type, and descriptor.
+    pub fn add_note(&mut self, name: &str, type_: u32, desc: Vec<u8>) {
+        let () = self.notes.push(Note {
+            name: name.to_string(),
+            type_,
+            desc,
+        });
+    }
+
+    /// Add `count` empty sections, e.g., for the purpose of exceeding
+    /// the section count representable in the ELF header.
+    pub fn add_padding_sections(&mut self, count: usize) -> &mut Self {
+        self.padding_sections += count;
+        self
+    }
+
+    /// Use the provided names for the symbol table and its string
+    /// table instead of `.symtab` and `.strtab`.
+    pub fn set_symtab_names(&mut self, symtab: &str, strtab: &str) -> &mut Self {
+        self.symtab_names = Some((symtab.to_string(), strtab.to_string()));
+        self
+    }
+
+    /// Override the `sh_entsize` value of the symbol table section.
+    pub fn set_symtab_entsize(&mut self, entsize: u64) -> &mut Self {
+        self.symtab_entsize = Some(entsize);
+        self
+    }
+
+    /// Override the `sh_link` value of the symbol table section, which
+    /// otherwise references the string table.
+    pub fn set_symtab_link(&mut self, link: u32) -> &mut Self {
+        self.symtab_link = Some(link);
+        self
+    }
+
+    /// Do not emit a section name string table, leaving all sections
+    /// unnamed.
+    pub fn omit_shstrtab(&mut self) -> &mut Self {
+        self.omit_shstrtab = true;
+        self
+    }
+
+    fn write_sym(w: &mut Writer, name: u32, value: u64, size: u64, info: u8, shndx: u16) {
+        match w.class {
+            Class::Elf32 => {
+                let () = w.u32(name);
+                let () = w.u32(value as u32);
+                let () = w.u32(size as u32);
+                let () = w.u8(info);
+                let () = w.u8(0);
+                let () = w.u16(shndx);
+            }
+            Class::Elf64 => {
+                let () = w.u32(name);
+                let () = w.u8(info);
+                let () = w.u8(0);
+                let () = w.u16(shndx);
+                let () = w.u64(value);
+                let () = w.u64(size);
+            }
+        }
+    }
+
+    fn write_shdr(w: &mut Writer, shdr: &Shdr) {
+        let () = w.u32(shdr.name);
+        let () = w.u32(shdr.type_);
+        let () = w.word(shdr.flags);
+        let () = w.word(shdr.addr);
+        let () = w.word(shdr.offset);
+        let () = w.word(shdr.size);
+        let () = w.u32(shdr.link);
+        let () = w.u32(shdr.info);
+        let () = w.word(ALIGN as u64);
+        let () = w.word(shdr.entsize);
+    }
+
+    /// Generate the ELF file.
+    pub fn build(&self) -> Vec<u8> {
+        let (ehdr_size, phdr_size, shdr_size, sym_size, machine) = match self.class {
+            Class::Elf32 => (52u16, 32u16, 40u16, 16u64, 3u16),
+            Class::Elf64 => (64u16, 56u16, 64u16, 24u64, 62u16),
+        };
+
+        let mut w = Writer {
+            class: self.class,
+            endianness: self.endianness,
+            data: Vec::new(),
+        };
+        let mut shstrtab = StrTab::new();
+        let mut shdrs = vec![Shdr {
+            name: 0,
+            type_: 0,
+            flags: 0,
+            addr: 0,
+            offset: 0,
+            size: 0,
+            link: 0,
+            info: 0,
+            entsize: 0,
+        }];
+
+        // Leave room for the ELF header and program headers, which we
+        // fill in at the end.
+        let phoff = align_up_usize(usize::from(ehdr_size), ALIGN) as u64;
+        let phnum = self.sections.len();
+        let () = w.bytes(&vec![0; phoff as usize + phnum * usize::from(phdr_size)]);
+
+        let mut phdrs = Vec::with_capacity(phnum);
+        for section in &self.sections {
+            let () = w.align(ALIGN);
+            let offset = w.offset();
+            let () = w.bytes(&section.data);
+            let () = shdrs.push(Shdr {
+                name: shstrtab.add(&section.name),
+                type_: SHT_PROGBITS,
+                flags: SHF_ALLOC | SHF_EXECINSTR,
+                addr: section.addr,
+                offset,
+                size: section.data.len() as u64,
+                link: 0,
+                info: 0,
+                entsize: 0,
+            });
+            let () = phdrs.push((offset, section.addr, section.data.len() as u64));
+        }
+
+        for _ in 0..self.padding_sections {
+            let () = shdrs.push(Shdr {
+                name: 0,
+                type_: SHT_PROGBITS,
+                flags: 0,
+                addr: 0,
+                offset: w.offset(),
+                size: 0,
+                link: 0,
+                info: 0,
+                entsize: 0,
+            });
+        }
+
+        if !self.notes.is_empty() {
+            let () = w.align(ALIGN);
+            let offset = w.offset();
+            for note in &self.notes {
+                let () = w.u32(note.name.len() as u32 + 1);
+                let () = w.u32(note.desc.len() as u32);
+                let () = w.u32(note.type_);
+                let () = w.bytes(note.name.as_bytes());
+                let () = w.u8(0);
+                let () = w.align(4);
+                let () = w.bytes(&note.desc);
+                let () = w.align(4);
+            }
+            let () = shdrs.push(Shdr {
+                name: shstrtab.add(".note"),
+                type_: SHT_NOTE,
+                flags: 0,
+                addr: 0,
+                offset,
+                size: w.offset() - offset,
+                link: 0,
+                info: 0,
+                entsize: 0,
+            });
+        }
+
+        let mut strtab = StrTab::new();
+        let () = w.align(ALIGN);
+        let symtab_offset = w.offset();
+        let () = Self::write_sym(&mut w, 0, 0, 0, 0, 0);
+        for symbol in &self.symbols {
+            let name = strtab.add(&symbol.name);
+            let type_ = match symbol.sym_type {
+                SymType::Variable => STT_OBJECT,
+                _ => STT_FUNC,
+            };
+            let info = (STB_GLOBAL << 4) | type_;
+            // User sections start at index one.
+            let shndx = u16::try_from(symbol.section + 1).unwrap_or(SHN_XINDEX);
+            let () = Self::write_sym(&mut w, name, symbol.addr, symbol.size, info, shndx);
+        }
+        let symtab_size = w.offset() - symtab_offset;
+        let symtab_idx = shdrs.len();
+        let (symtab_name, strtab_name) = self
+            .symtab_names
+            .as_ref()
+            .map(|(symtab, strtab)| (symtab.as_str(), strtab.as_str()))
+            .unwrap_or((".symtab", ".strtab"));
+        let () = shdrs.push(Shdr {
+            name: shstrtab.add(symtab_name),
+            type_: SHT_SYMTAB,
+            flags: 0,
+            addr: 0,
+            offset: symtab_offset,
+            size: symtab_size,
+            link: self.symtab_link.unwrap_or((symtab_idx + 1) as u32),
+            // All symbols are global.
+            info: 1,
+            entsize: self.symtab_entsize.unwrap_or(sym_size),
+        });
+
+        let () = w.align(ALIGN);
+        let strtab_offset = w.offset();
+        let () = w.bytes(&strtab.data);
+        let () = shdrs.push(Shdr {
+            name: shstrtab.add(strtab_name),
+            type_: SHT_STRTAB,
+            flags: 0,
+            addr: 0,
+            offset: strtab_offset,
+            size: strtab.data.len() as u64,
+            link: 0,
+            info: 0,
+            entsize: 0,
+        });
+
+        let shstrndx = if self.omit_shstrtab {
+            0
+        } else {
+            let shstrndx = shdrs.len();
+            let shstrtab_name = shstrtab.add(".shstrtab");
+            let () = w.align(ALIGN);
+            let shstrtab_offset = w.offset();
+            let () = w.bytes(&shstrtab.data);
+            let () = shdrs.push(Shdr {
+                name: shstrtab_name,
+                type_: SHT_STRTAB,
+                flags: 0,
+                addr: 0,
+                offset: shstrtab_offset,
+                size: shstrtab.data.len() as u64,
+                link: 0,
+                info: 0,
+                entsize: 0,
+            });
+            shstrndx
+        };
+
+        // Section counts and string table indices that don't fit into
+        // the ELF header are stored in the initial section header.
+        let shnum = shdrs.len();
+        if shnum >= SHN_LORESERVE {
+            shdrs[0].size = shnum as u64;
+        }
+        if shstrndx >= SHN_LORESERVE {
+            shdrs[0].link = shstrndx as u32;
+        }
+
+        let () = w.align(ALIGN);
+        let shoff = w.offset();
+        let () = shdrs.iter().for_each(|shdr| Self::write_shdr(&mut w, shdr));
+
+        // Now that the layout is known, fill in the headers.
+        let mut hdr = Writer {
+            class: self.class,
+            endianness: self.endianness,
+            data: Vec::new(),
+        };
+        let () = hdr.bytes(b"\x7fELF");
+        let () = hdr.u8(match self.class {
+            Class::Elf32 => 1,
+            Class::Elf64 => 2,
+        });
+        let () = hdr.u8(match self.endianness {
+            Endianness::Little => 1,
+            Endianness::Big => 2,
+        });
+        let () = hdr.u8(1);
+        let () = hdr.bytes(&[0; 9]);
+        let () = hdr.u16(self.file_type);
+        let () = hdr.u16(machine);
+        let () = hdr.u32(1);
+        let () = hdr.word(0);
+        let () = hdr.word(phoff);
+        let () = hdr.word(shoff);
+        let () = hdr.u32(0);
+        let () = hdr.u16(ehdr_size);
+        let () = hdr.u16(phdr_size);
+        let () = hdr.u16(phnum as u16);
+        let () = hdr.u16(shdr_size);
+        let () = hdr.u16(if shnum >= SHN_LORESERVE {
+            0
+        } else {
+            shnum as u16
+        });
+        let () = hdr.u16(if shstrndx >= SHN_LORESERVE {
+            SHN_XINDEX
+        } else {
+            shstrndx as u16
+        });
+        let () = hdr.align(ALIGN);
+
+        for (offset, addr, size) in phdrs {
+            match self.class {
+                Class::Elf32 => {
+                    let () = hdr.u32(PT_LOAD);
+                    let () = hdr.u32(offset as u32);
+                    let () = hdr.u32(addr as u32);
+                    let () = hdr.u32(addr as u32);
+                    let () = hdr.u32(size as u32);
+                    let () = hdr.u32(size as u32);
+                    let () = hdr.u32(PF_R | PF_X);
+                    let () = hdr.u32(ALIGN as u32);
+                }
+                Class::Elf64 => {
+                    let () = hdr.u32(PT_LOAD);
+                    let () = hdr.u32(PF_R | PF_X);
+                    let () = hdr.u64(offset);
+                    let () = hdr.u64(addr);
+                    let () = hdr.u64(addr);
+                    let () = hdr.u64(size);
+                    let () = hdr.u64(size);
+                    let () = hdr.u64(ALIGN as u64);
+                }
+            }
+        }
+
+        let mut data = w.data;
+        let () = data[..hdr.data.len()].copy_from_slice(&hdr.data);
+        data
+    }
+}
@@ -0,0 +1,244 @@
+use std::collections::VecDeque;
+use std::future::Future;
+use std::panic::catch_unwind;
+use std::panic::resume_unwind;
+use std::panic::AssertUnwindSafe;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::sync::Condvar;
+use std::sync::Mutex;
+use std::task::Context;
+use std::task::Poll;
+use std::task::Waker;
+use std::thread;
+use std::thread::Builder;
+use std::time::Duration;
+
+use crate::Result;
+
+
+/// The maximum number of helper threads we run concurrently.
+const MAX_HELPERS: usize = 8;
+/// The time after which an idle helper thread exits.
+const IDLE_TIMEOUT: Duration = Duration::from_secs(10);
+
+
+/// A unit of work to run on a helper thread.
+type Job = Box<dyn FnOnce() + Send>;
+
+
+/// The state of the process wide pool of helper threads.
+struct Pool {
+    /// Work items not yet picked up by any helper thread.
+    queue: VecDeque<Job>,
+    /// The number of helper threads currently alive.
+    threads: usize,
+    /// The number of helper threads currently waiting for work.
+    idle: usize,
+}
+
+static POOL: Mutex<Pool> = Mutex::new(Pool {
+    queue: VecDeque::new(),
+    threads: 0,
+    idle: 0,
+});
+/// The condition variable used for signaling helper threads that work
+/// is available.
+static WORK: Condvar = Condvar::new();
+
+
+/// The main loop of a helper thread.
+fn helper() {
+    // SANITY: The lock is never held while running user provided
+    //         code and so it can't be poisoned.
+    let mut pool = POOL.lock().unwrap();
+    loop {
+        if let Some(job) = pool.queue.pop_front() {
+            let () = drop(pool);
+            let () = job();
+            pool = POOL.lock().unwrap();
+            continue
+        }
+
+        pool.idle += 1;
+        let (guard, result) = WORK.wait_timeout(pool, IDLE_TIMEOUT).unwrap();
+        pool = guard;
+        pool.idle -= 1;
+
+        if result.timed_out() && pool.queue.is_empty() {
+            pool.threads -= 1;
+            break
+        }
+    }
+}
+
+
+/// Run `job` on one of the process wide helper threads, spawning a new
+/// one if none is available and the pool is not yet exhausted.
+///
+/// If all [`MAX_HELPERS`] threads are busy, the job is queued and run
+/// once one of them becomes available.
+fn submit(job: Job) -> Result<()> {
+    // SANITY: The lock is never held while running user provided
+    //         code and so it can't be poisoned.
+    let mut pool = POOL.lock().unwrap();
+    let () = pool.queue.push_back(job);
+
+    // Every idle helper is going to pick up one queued job.
+    if pool.idle >= pool.queue.len() || pool.threads >= MAX_HELPERS {
+        let () = WORK.notify_one();
+        return Ok(())
+    }
+
+    // We spawn while holding the lock. The new thread will just wait
+    // for it before picking up the job.
+    match Builder::new()
+        .name("blazesym-unblock".to_string())
+        .spawn(helper)
+    {
+        Ok(_handle) => {
+            pool.threads += 1;
+            Ok(())
+        }
+        Err(err) => {
+            // No other thread could have taken the job, as we held
+            // the lock all along.
+            let _job = pool.queue.pop_back();
+            Err(err.into())
+        }
+    }
+}
+
+
+#[derive(Debug)]
+struct State<T> {
+    /// The result of the offloaded work, once available.
+    result: Option<thread::Result<T>>,
+    /// The waker of the task most recently polling for the result.
+    waker: Option<Waker>,
+}
+
+
+/// A future resolving to the result of some blocking work performed on
+/// a separate thread.
+#[derive(Debug)]
+pub(crate) struct Unblock<T> {
+    state: Arc<Mutex<State<T>>>,
+}
+
+impl<T> Future for Unblock<T> {
+    type Output = T;
+
+    fn poll(self: Pin<&mut Self>, ctx: &mut Context<'_>) -> Poll<Self::Output> {
+        // SANITY: The lock is never held while running user provided
+        //         code and so it can't be poisoned.
+        let mut state = self.state.lock().unwrap();
+        match state.result.take() {
+            Some(Ok(value)) => Poll::Ready(value),
+            Some(Err(payload)) => resume_unwind(payload),
+            None => {
+                state.waker = Some(ctx.waker().clone());
+                Poll::Pending
+            }
+        }
+    }
+}
+
+
+/// Run `f` on a helper thread, returning a future resolving to its
+/// result.
+///
+/// Helper threads are shared by all callers. They are spawned lazily,
+/// up to a small maximum, and exit after being idle for a while. A
+/// blocking work item hence only holds up others once all helpers are
+/// busy.
+///
+/// This function is executor agnostic: the returned future does not
+/// depend on any particular runtime for being driven to completion.
+/// Panics inside `f` are propagated to the awaiting task.
+pub(crate) fn unblock<F, T>(f: F) -> Result<Unblock<T>>
+where
+    F: FnOnce() -> T + Send + 'static,
+    T: Send + 'static,
+{
+    let state = Arc::new(Mutex::new(State {
+        result: None,
+        waker: None,
+    }));
+    let shared = Arc::clone(&state);
+
+    let () = submit(Box::new(move || {
+        let result = catch_unwind(AssertUnwindSafe(f));
+        let waker = {
+            // SANITY: The lock is never held while running user
+            //         provided code and so it can't be poisoned.
+            let mut state = shared.lock().unwrap();
+            state.result = Some(result);
+            state.waker.take()
+        };
+        if let Some(waker) = waker {
+            let () = waker.wake();
+        }
+    }))?;
+
+    Ok(Unblock { state })
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::sync::mpsc::channel;
+
+
+    /// Check that we can retrieve the result of offloaded work.
+    #[test]
+    fn unblock_result() {
+        let future = unblock(|| 42).unwrap();
+        let value = pollster::block_on(future);
+        assert_eq!(value, 42);
+    }
+
+    /// Check that a panic in offloaded work is propagated to the
+    /// awaiting task.
+    #[test]
+    fn unblock_panic() {
+        let future = unblock(|| panic!("induced panic")).unwrap();
+        let result = catch_unwind(AssertUnwindSafe(|| pollster::block_on(future)));
+        assert!(result.is_err());
+    }
+
+    /// Check that a blocking work item does not hold up others.
+    #[test]
+    fn unblock_concurrent() {
+        let (sender, receiver) = channel::<()>();
+        let blocked = unblock(move || receiver.recv().unwrap()).unwrap();
+
+        let id = pollster::block_on(unblock(|| thread::current().id()).unwrap());
+        assert_ne!(id, thread::current().id());
+
+        let () = sender.send(()).unwrap();
+        let () = pollster::block_on(blocked);
+    }
+
+    /// Check that the number of helper threads is bounded and that
+    /// excess work gets queued and run eventually.
+    #[test]
+    fn unblock_bounded() {
+        let (senders, futures) = (0..2 * MAX_HELPERS)
+            .map(|_| {
+                let (sender, receiver) = channel::<()>();
+                let future = unblock(move || receiver.recv().unwrap()).unwrap();
+                (sender, future)
+            })
+            .unzip::<_, _, Vec<_>, Vec<_>>();
+
+        assert!(POOL.lock().unwrap().threads <= MAX_HELPERS);
+
+        let () = senders
+            .into_iter()
+            .for_each(|sender| sender.send(()).unwrap());
+        let () = futures.into_iter().for_each(pollster::block_on);
+    }
+}
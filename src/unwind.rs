@@ -0,0 +1,1021 @@
+//! Functionality for unwinding the stack of a process.
+//!
+//! Unwinding is based on the call frame information (CFI) contained in
+//! the `.eh_frame` and `.debug_frame` sections of the ELF binaries
+//! mapped into the process. It produces the list of addresses making
+//! up the call stack, which can subsequently be symbolized using
+//! [`Symbolizer::symbolize`][crate::symbolize::Symbolizer::symbolize]
+//! with a [`Process`][crate::symbolize::source::Process] source and
+//! [`Input::AbsAddr`][crate::symbolize::Input::AbsAddr].
+//!
+//! ```no_run
+//! use blazesym::symbolize::source::Process;
+//! use blazesym::symbolize::source::Source;
+//! use blazesym::symbolize::Input;
+//! use blazesym::symbolize::Symbolizer;
+//! use blazesym::unwind::Memory;
+//! use blazesym::unwind::Registers;
+//! use blazesym::unwind::Unwinder;
+//! use blazesym::Pid;
+//!
+//! # let (ip, sp, fp) = (0, 0, 0);
+//! let pid = Pid::from(1234);
+//! let regs = Registers {
+//!     ip,
+//!     sp,
+//!     fp,
+//!     ..Default::default()
+//! };
+//! let unwinder = Unwinder::new();
+//! let addrs = unwinder.unwind(pid, &regs, &Memory::Process).unwrap();
+//!
+//! let src = Source::Process(Process::new(pid));
+//! let symbolizer = Symbolizer::new();
+//! let syms = symbolizer.symbolize(&src, Input::AbsAddr(&addrs)).unwrap();
+//! ```
+
+use std::fmt::Debug;
+use std::fmt::Formatter;
+use std::fmt::Result as FmtResult;
+use std::fs::File;
+#[cfg(unix)]
+use std::os::unix::fs::FileExt as _;
+use std::path::Path;
+
+use gimli::BaseAddresses;
+use gimli::CfaRule;
+use gimli::DebugFrame;
+use gimli::EhFrame;
+use gimli::EhFrameHdr;
+use gimli::EndianSlice;
+use gimli::NativeEndian;
+use gimli::Register;
+use gimli::RegisterRule;
+use gimli::UnwindContext;
+use gimli::UnwindSection as _;
+
+use crate::elf::types::EM_AARCH64;
+use crate::elf::types::EM_X86_64;
+use crate::elf::ElfParser;
+use crate::file_cache::FileCache;
+use crate::log::debug;
+use crate::maps;
+use crate::maps::MapsEntry;
+use crate::maps::PathName;
+use crate::maps::Perm;
+use crate::once::OnceCell;
+use crate::Addr;
+use crate::Error;
+use crate::ErrorExt as _;
+use crate::Pid;
+use crate::Result;
+
+
+type R<'dat> = EndianSlice<'dat, NativeEndian>;
+
+
+/// The CPU registers of a thread at the point at which it was stopped,
+/// as required for unwinding its stack.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Registers {
+    /// The instruction pointer (`rip` on x86_64, `pc` on AArch64).
+    pub ip: Addr,
+    /// The stack pointer (`rsp` on x86_64, `sp` on AArch64).
+    pub sp: Addr,
+    /// The frame pointer (`rbp` on x86_64, `x29` on AArch64).
+    pub fp: Addr,
+    /// The link register (`x30`) holding the return address of leaf
+    /// functions on AArch64. Unused on x86_64.
+    pub lr: Option<Addr>,
+    /// The struct is non-exhaustive and open to extension.
+    #[doc(hidden)]
+    pub _non_exhaustive: (),
+}
+
+
+/// The memory of a process, as used for reading the saved registers
+/// and return addresses of the frames on its stack.
+pub enum Memory<'mem> {
+    /// Read memory directly from the process being unwound, using
+    /// `/proc/<pid>/mem`.
+    ///
+    /// This typically requires the process to be stopped (e.g., by
+    /// virtue of being traced) in order to produce meaningful results.
+    Process,
+    /// A copy of the stack, as captured at the time the process was
+    /// stopped.
+    Stack {
+        /// The address of the first byte of `data`, usually the stack
+        /// pointer at the time the copy was taken.
+        base: Addr,
+        /// The stack data.
+        data: &'mem [u8],
+    },
+    /// A callback reading the memory at the provided address into the
+    /// provided buffer, reporting whether it succeeded in doing so.
+    Callback(&'mem dyn Fn(Addr, &mut [u8]) -> bool),
+}
+
+impl Debug for Memory<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        match self {
+            Self::Process => f.write_str("Process"),
+            Self::Stack { base, data } => f
+                .debug_struct("Stack")
+                .field("base", &format_args!("{base:#x}"))
+                .field("len", &data.len())
+                .finish(),
+            Self::Callback(..) => f.write_str("Callback"),
+        }
+    }
+}
+
+
+/// Read `buf.len()` bytes at offset `addr` from `file`, reporting
+/// whether the read succeeded.
+#[cfg(unix)]
+fn read_exact_at(file: &File, buf: &mut [u8], addr: Addr) -> bool {
+    file.read_exact_at(buf, addr).is_ok()
+}
+
+/// Read `buf.len()` bytes at offset `addr` from `file`, reporting
+/// whether the read succeeded.
+///
+/// Positional reads are only supported on Unix systems.
+#[cfg(not(unix))]
+fn read_exact_at(_file: &File, _buf: &mut [u8], _addr: Addr) -> bool {
+    false
+}
+
+
+/// Reader for the memory of a process.
+struct MemReader<'mem> {
+    memory: &'mem Memory<'mem>,
+    pid: &'mem Pid,
+    /// The lazily opened `/proc/<pid>/mem` file, if applicable.
+    file: OnceCell<Option<File>>,
+}
+
+impl<'mem> MemReader<'mem> {
+    fn new(memory: &'mem Memory<'mem>, pid: &'mem Pid) -> Self {
+        Self {
+            memory,
+            pid,
+            file: OnceCell::new(),
+        }
+    }
+
+    /// Read a 64 bit value at `addr`.
+    fn read_u64(&self, addr: Addr) -> Option<u64> {
+        let mut buf = [0; 8];
+        let success = match self.memory {
+            Memory::Process => {
+                let file = self.file.get_or_init(|| {
+                    let path = format!("/proc/{}/mem", self.pid);
+                    let file = File::open(&path)
+                        .map_err(|err| debug!("failed to open `{path}`: {err}"))
+                        .ok()?;
+                    // Make sure that the file we opened actually belongs
+                    // to the process in question.
+                    let () = self
+                        .pid
+                        .check_alive()
+                        .map_err(|err| debug!("failed to verify `{path}`: {err:#}"))
+                        .ok()?;
+                    Some(file)
+                });
+                file.as_ref()
+                    .map(|file| read_exact_at(file, &mut buf, addr))
+                    .unwrap_or(false)
+            }
+            Memory::Stack { base, data } => {
+                let offset = addr
+                    .checked_sub(*base)
+                    .and_then(|o| usize::try_from(o).ok());
+                if let Some(src) = offset.and_then(|o| data.get(o..o.checked_add(buf.len())?)) {
+                    let () = buf.copy_from_slice(src);
+                    true
+                } else {
+                    false
+                }
+            }
+            Memory::Callback(f) => f(addr, &mut buf),
+        };
+        success.then(|| u64::from_ne_bytes(buf))
+    }
+}
+
+
+/// The DWARF register numbers of relevance to unwinding on a given
+/// architecture.
+#[derive(Clone, Copy, Debug)]
+struct ArchRegs {
+    sp: Register,
+    fp: Register,
+    /// The register holding the return address.
+    ra: Register,
+    /// Whether the return address is held in a link register (as
+    /// opposed to being stored on the stack by the call instruction).
+    link_reg: bool,
+}
+
+impl ArchRegs {
+    fn from_machine(machine: u16) -> Option<Self> {
+        match machine {
+            EM_X86_64 => Some(Self {
+                sp: gimli::X86_64::RSP,
+                fp: gimli::X86_64::RBP,
+                ra: gimli::X86_64::RA,
+                link_reg: false,
+            }),
+            EM_AARCH64 => Some(Self {
+                sp: gimli::AArch64::SP,
+                fp: gimli::AArch64::X29,
+                ra: gimli::AArch64::X30,
+                link_reg: true,
+            }),
+            _ => None,
+        }
+    }
+
+    fn host() -> Option<Self> {
+        if cfg!(target_arch = "x86_64") {
+            Self::from_machine(EM_X86_64)
+        } else if cfg!(target_arch = "aarch64") {
+            Self::from_machine(EM_AARCH64)
+        } else {
+            None
+        }
+    }
+}
+
+
+/// The register state of a single frame.
+#[derive(Clone, Debug)]
+struct Frame {
+    /// The instruction pointer or return address.
+    ip: Addr,
+    sp: Addr,
+    fp: Option<Addr>,
+    /// The value of the link register, if known.
+    lr: Option<Addr>,
+}
+
+impl Frame {
+    fn get(&self, reg: Register, arch: &ArchRegs) -> Option<Addr> {
+        if reg == arch.sp {
+            Some(self.sp)
+        } else if reg == arch.fp {
+            self.fp
+        } else if arch.link_reg && reg == arch.ra {
+            self.lr
+        } else {
+            None
+        }
+    }
+}
+
+
+/// The section data of an ELF file relevant to unwinding.
+struct CfiSections<'dat> {
+    bases: BaseAddresses,
+    eh_frame: Option<EhFrame<R<'dat>>>,
+    eh_frame_hdr: Option<EhFrameHdr<R<'dat>>>,
+    debug_frame: Option<DebugFrame<R<'dat>>>,
+}
+
+impl<'dat> CfiSections<'dat> {
+    fn load(parser: &'dat ElfParser) -> Result<Self> {
+        let mut bases = BaseAddresses::default();
+        let section = |name| -> Result<Option<(Addr, &'dat [u8])>> {
+            if let Some(idx) = parser.find_section(name)? {
+                // SANITY: The index was just reported by the parser.
+                let addr = parser.section_addr(idx)?.unwrap();
+                let data = parser.section_data(idx)?;
+                Ok(Some((addr, data)))
+            } else {
+                Ok(None)
+            }
+        };
+
+        if let Some((addr, _data)) = section(".text")? {
+            bases = bases.set_text(addr);
+        }
+        if let Some((addr, _data)) = section(".got")? {
+            bases = bases.set_got(addr);
+        }
+        let eh_frame = if let Some((addr, data)) = section(".eh_frame")? {
+            bases = bases.set_eh_frame(addr);
+            let mut eh_frame = EhFrame::new(data, NativeEndian);
+            let () = eh_frame.set_address_size(8);
+            Some(eh_frame)
+        } else {
+            None
+        };
+        let eh_frame_hdr = if let Some((addr, data)) = section(".eh_frame_hdr")? {
+            bases = bases.set_eh_frame_hdr(addr);
+            Some(EhFrameHdr::new(data, NativeEndian))
+        } else {
+            None
+        };
+        let debug_frame = section(".debug_frame")?.map(|(_addr, data)| {
+            let mut debug_frame = DebugFrame::new(data, NativeEndian);
+            let () = debug_frame.set_address_size(8);
+            debug_frame
+        });
+
+        let slf = Self {
+            bases,
+            eh_frame,
+            eh_frame_hdr,
+            debug_frame,
+        };
+        Ok(slf)
+    }
+
+    /// Find the CFA and the rules for recovering the frame pointer and
+    /// return address for the instruction at `addr`.
+    fn find_rules(
+        &self,
+        ctx: &mut UnwindContext<usize>,
+        addr: Addr,
+        arch: &ArchRegs,
+    ) -> Option<(CfaRule<usize>, RegisterRule<usize>, RegisterRule<usize>)> {
+        let rules = |row: &gimli::UnwindTableRow<usize>| {
+            (
+                row.cfa().clone(),
+                row.register(arch.fp),
+                row.register(arch.ra),
+            )
+        };
+
+        if let Some(eh_frame) = &self.eh_frame {
+            let table = self
+                .eh_frame_hdr
+                .as_ref()
+                .and_then(|hdr| hdr.parse(&self.bases, 8).ok());
+            let result = if let Some(table) = table.as_ref().and_then(|hdr| hdr.table()) {
+                table
+                    .unwind_info_for_address(
+                        eh_frame,
+                        &self.bases,
+                        ctx,
+                        addr,
+                        EhFrame::cie_from_offset,
+                    )
+                    .map(rules)
+            } else {
+                eh_frame
+                    .unwind_info_for_address(&self.bases, ctx, addr, EhFrame::cie_from_offset)
+                    .map(rules)
+            };
+            if let Ok(rules) = result {
+                return Some(rules)
+            }
+        }
+
+        if let Some(debug_frame) = &self.debug_frame {
+            if let Ok(rules) = debug_frame
+                .unwind_info_for_address(&self.bases, ctx, addr, DebugFrame::cie_from_offset)
+                .map(rules)
+            {
+                return Some(rules)
+            }
+        }
+        None
+    }
+}
+
+
+/// A builder for configurable construction of [`Unwinder`] objects.
+///
+/// By default at most 128 frames are unwound, frame pointer based
+/// unwinding is used for code without call frame information, and
+/// binaries are accessed via `/proc/<pid>/map_files/` entries.
+#[derive(Clone, Debug)]
+pub struct Builder {
+    /// See [`Builder::set_max_frames`].
+    max_frames: usize,
+    /// See [`Builder::enable_fp_fallback`].
+    fp_fallback: bool,
+    /// See [`Builder::enable_map_files`].
+    map_files: bool,
+}
+
+impl Builder {
+    /// Set the maximum number of frames to report.
+    pub fn set_max_frames(mut self, max_frames: usize) -> Self {
+        self.max_frames = max_frames;
+        self
+    }
+
+    /// Enable/disable unwinding based on frame pointers for code that
+    /// is not covered by call frame information (e.g., just-in-time
+    /// compiled code).
+    pub fn enable_fp_fallback(mut self, enable: bool) -> Self {
+        self.fp_fallback = enable;
+        self
+    }
+
+    /// Enable/disable the usage of `/proc/<pid>/map_files/` entries for
+    /// accessing the binaries mapped into the process, as opposed to
+    /// their symbolic paths.
+    ///
+    /// Refer to [`Process::map_files`][crate::symbolize::source::Process::map_files]
+    /// for details.
+    pub fn enable_map_files(mut self, enable: bool) -> Self {
+        self.map_files = enable;
+        self
+    }
+
+    /// Create the [`Unwinder`] object.
+    pub fn build(self) -> Unwinder {
+        let Builder {
+            max_frames,
+            fp_fallback,
+            map_files,
+        } = self;
+
+        Unwinder {
+            max_frames,
+            fp_fallback,
+            map_files,
+            elf_cache: FileCache::default(),
+        }
+    }
+}
+
+impl Default for Builder {
+    fn default() -> Self {
+        Self {
+            max_frames: 128,
+            fp_fallback: true,
+            map_files: true,
+        }
+    }
+}
+
+
+/// An unwinder for the stacks of processes.
+///
+/// Parsed ELF files are cached inside of an `Unwinder` object and
+/// reused for subsequent unwinding operations.
+#[derive(Debug)]
+pub struct Unwinder {
+    /// See [`Builder::set_max_frames`].
+    max_frames: usize,
+    /// See [`Builder::enable_fp_fallback`].
+    fp_fallback: bool,
+    /// See [`Builder::enable_map_files`].
+    map_files: bool,
+    /// A cache of parsed ELF files.
+    elf_cache: FileCache<ElfParser>,
+}
+
+impl Unwinder {
+    /// Create a new [`Unwinder`].
+    ///
+    /// This method is just a short hand for instantiating an `Unwinder`
+    /// from the default [`Builder`].
+    #[inline]
+    pub fn new() -> Self {
+        Builder::default().build()
+    }
+
+    /// Retrieve a [`Builder`] object for configurable construction of
+    /// an [`Unwinder`].
+    #[inline]
+    pub fn builder() -> Builder {
+        Builder::default()
+    }
+
+    /// Retrieve the (cached) ELF parser for the file at `path`.
+    fn elf_parser(&self, path: &Path) -> Result<&ElfParser> {
+        let (file, cell) = self.elf_cache.entry(path)?;
+        let parser = cell.get_or_try_init(|| ElfParser::open_file(&*file.get()?, path))?;
+        Ok(parser)
+    }
+
+    /// Find the ELF file mapped at `addr`, returning its parser as well
+    /// as the address translated into the file's virtual address space.
+    fn find_module(
+        &self,
+        entries: &[MapsEntry],
+        root: Option<&Path>,
+        addr: Addr,
+    ) -> Option<(&ElfParser, Addr)> {
+        let idx = entries
+            .partition_point(|entry| entry.range.end <= addr)
+            .min(entries.len());
+        let entry = entries.get(idx)?;
+        if !entry.range.contains(&addr) || (entry.perm & Perm::X) == Perm::default() {
+            return None
+        }
+
+        let path = if let Some(PathName::Path(path)) = &entry.path_name {
+            path.access_path(self.map_files, root)
+        } else {
+            return None
+        };
+
+        let parser = self
+            .elf_parser(&path)
+            .map_err(|err| debug!("failed to open `{}`: {err}", path.display()))
+            .ok()?;
+        let file_offset = addr - entry.range.start + entry.offset;
+        let virt_addr = parser.file_offset_to_virt_offset(file_offset).ok()??;
+        Some((parser, virt_addr))
+    }
+
+    /// Unwind a single frame using call frame information, if
+    /// available.
+    ///
+    /// The outer `Option` is `None` if no call frame information covers
+    /// the frame's address, while the inner one is `None` if unwinding
+    /// should stop.
+    fn unwind_cfi(
+        &self,
+        ctx: &mut UnwindContext<usize>,
+        parser: &ElfParser,
+        addr: Addr,
+        frame: &Frame,
+        arch: &ArchRegs,
+        memory: &MemReader<'_>,
+    ) -> Option<Option<Frame>> {
+        let sections = CfiSections::load(parser)
+            .map_err(|err| debug!("failed to load call frame information: {err}"))
+            .ok()?;
+        let (cfa, fp_rule, ra_rule) = sections.find_rules(ctx, addr, arch)?;
+
+        let cfa = match cfa {
+            CfaRule::RegisterAndOffset { register, offset } => {
+                let value = frame.get(register, arch);
+                value.map(|value| value.wrapping_add_signed(offset))
+            }
+            CfaRule::Expression(..) => {
+                debug!("CFA expressions are not supported; stopping unwinding");
+                None
+            }
+        };
+        let cfa = if let Some(cfa) = cfa {
+            cfa
+        } else {
+            return Some(None)
+        };
+
+        let recover = |rule, reg| match rule {
+            RegisterRule::SameValue => frame.get(reg, arch),
+            RegisterRule::Offset(offset) => memory.read_u64(cfa.wrapping_add_signed(offset)),
+            RegisterRule::ValOffset(offset) => Some(cfa.wrapping_add_signed(offset)),
+            RegisterRule::Register(other) => frame.get(other, arch),
+            _ => None,
+        };
+
+        // An undefined frame pointer rule means that the register
+        // was not saved, i.e., it retains its value.
+        let fp = match fp_rule {
+            RegisterRule::Undefined => frame.fp,
+            rule => recover(rule, arch.fp),
+        };
+        let ra = match ra_rule {
+            // An undefined return address marks the outermost frame,
+            // unless it is held in the link register, in which case
+            // the function may just not have saved it.
+            RegisterRule::Undefined if arch.link_reg => frame.lr,
+            RegisterRule::Undefined => None,
+            rule => recover(rule, arch.ra),
+        };
+
+        let frame = ra.map(|ra| Frame {
+            ip: ra,
+            sp: cfa,
+            fp,
+            // The caller's link register value is unknown.
+            lr: None,
+        });
+        Some(frame)
+    }
+
+    /// Unwind a single frame using frame pointers.
+    fn unwind_fp(&self, frame: &Frame, memory: &MemReader<'_>) -> Option<Frame> {
+        // On both x86_64 and AArch64 the frame pointer points to the
+        // saved frame pointer of the caller, followed by the return
+        // address.
+        let fp = frame.fp?;
+        let ra = memory.read_u64(fp.checked_add(8)?)?;
+        let caller_fp = memory.read_u64(fp)?;
+
+        let frame = Frame {
+            ip: ra,
+            sp: fp.checked_add(16)?,
+            fp: Some(caller_fp),
+            lr: None,
+        };
+        Some(frame)
+    }
+
+    /// Unwind the stack of the process with the given `pid`, given the
+    /// register state of the thread of interest in `regs`.
+    ///
+    /// Reported are the instruction pointer, followed by the return
+    /// addresses of all frames found on the stack, innermost first.
+    /// Addresses are absolute and can be symbolized as part of a
+    /// [`Process`][crate::symbolize::source::Process] source.
+    ///
+    /// Note that return addresses reference the instruction following
+    /// the call. As such, they may be attributed to a source code line
+    /// other than that of the call itself.
+    ///
+    /// Unwinding stops once the outermost frame has been reached, once
+    /// the configured maximum number of frames has been reported, or
+    /// when a frame cannot be unwound (e.g., because it is not covered
+    /// by call frame information or memory could not be read). An
+    /// error is reported only if the process' memory mappings could not
+    /// be retrieved or if a process referenced by a pidfd exited while
+    /// it was being unwound.
+    pub fn unwind(&self, pid: Pid, regs: &Registers, memory: &Memory<'_>) -> Result<Vec<Addr>> {
+        let entries = maps::parse_filtered(&pid)?
+            .collect::<Result<Vec<_>>>()
+            .with_context(|| format!("failed to read memory mappings of process {pid}"))?;
+        let root = maps::proc_root(&pid);
+        let root = root.as_deref();
+        let memory = MemReader::new(memory, &pid);
+        let mut ctx = UnwindContext::new();
+
+        let arch = self
+            .find_module(&entries, root, regs.ip)
+            .and_then(|(parser, _addr)| parser.machine().ok())
+            .and_then(ArchRegs::from_machine)
+            .or_else(ArchRegs::host)
+            .ok_or_else(|| {
+                Error::with_unsupported("unwinding is unsupported on this architecture")
+            })?;
+
+        let mut frame = Frame {
+            ip: regs.ip,
+            sp: regs.sp,
+            fp: Some(regs.fp),
+            lr: regs.lr,
+        };
+        let mut addrs = Vec::new();
+
+        while addrs.len() < self.max_frames {
+            let () = addrs.push(frame.ip);
+
+            // Return addresses point to the instruction following the
+            // call, which may belong to a different function or CFI
+            // row altogether, so we look up the call instead.
+            let lookup = if addrs.len() > 1 {
+                frame.ip.saturating_sub(1)
+            } else {
+                frame.ip
+            };
+
+            let result = self
+                .find_module(&entries, root, lookup)
+                .and_then(|(parser, addr)| {
+                    self.unwind_cfi(&mut ctx, parser, addr, &frame, &arch, &memory)
+                });
+            let next = match result {
+                Some(next) => next,
+                None if self.fp_fallback => self.unwind_fp(&frame, &memory),
+                None => None,
+            };
+
+            match next {
+                // The stack grows downwards, so the caller's frame has
+                // to reside at a higher address. Anything else hints at
+                // corrupted state and may cause us to loop.
+                Some(next) if next.ip != 0 && next.sp > frame.sp => frame = next,
+                _ => break,
+            }
+        }
+
+        // Binaries may have been opened via `/proc/<pid>/map_files/`
+        // or `/proc/<pid>/root/`. Make sure that they actually belong
+        // to the process in question.
+        let () = pid.check_alive()?;
+        Ok(addrs)
+    }
+}
+
+impl Default for Unwinder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(target_arch = "x86_64")]
+    use std::arch::asm;
+    #[cfg(target_arch = "x86_64")]
+    use std::hint::black_box;
+
+    #[cfg(all(linux, target_arch = "x86_64"))]
+    use crate::inspect;
+    #[cfg(all(linux, target_arch = "x86_64"))]
+    use crate::inspect::Inspector;
+    #[cfg(target_arch = "x86_64")]
+    use crate::symbolize::source::Process;
+    #[cfg(target_arch = "x86_64")]
+    use crate::symbolize::source::Source;
+    #[cfg(target_arch = "x86_64")]
+    use crate::symbolize::Input;
+    #[cfg(all(linux, target_arch = "x86_64"))]
+    use crate::symbolize::Reason;
+    #[cfg(all(linux, target_arch = "x86_64"))]
+    use crate::symbolize::Symbolized;
+    #[cfg(target_arch = "x86_64")]
+    use crate::symbolize::Symbolizer;
+
+
+    /// Exercise the `Debug` representation of various types.
+    #[test]
+    fn debug_repr() {
+        let unwinder = Unwinder::new();
+        assert_ne!(format!("{unwinder:?}"), "");
+
+        let memory = Memory::Stack {
+            base: 0x1000,
+            data: &[0; 16],
+        };
+        assert_eq!(format!("{memory:?}"), "Stack { base: 0x1000, len: 16 }");
+        let f = |_addr, _buf: &mut [u8]| false;
+        let memory = Memory::Callback(&f);
+        assert_eq!(format!("{memory:?}"), "Callback");
+    }
+
+    /// Check that we can read values from a stack copy.
+    #[test]
+    fn stack_memory_reading() {
+        let data = [1u64, 2, 3]
+            .into_iter()
+            .flat_map(u64::to_ne_bytes)
+            .collect::<Vec<_>>();
+        let memory = Memory::Stack {
+            base: 0x1000,
+            data: &data,
+        };
+        let reader = MemReader::new(&memory, &Pid::Slf);
+        assert_eq!(reader.read_u64(0x1000), Some(1));
+        assert_eq!(reader.read_u64(0x1010), Some(3));
+        assert_eq!(
+            reader.read_u64(0x1004),
+            Some(u64::from_ne_bytes([0, 0, 0, 0, 2, 0, 0, 0]))
+        );
+        assert_eq!(reader.read_u64(0x1011), None);
+        assert_eq!(reader.read_u64(0xfff), None);
+        assert_eq!(reader.read_u64(u64::MAX), None);
+    }
+
+    /// Capture the registers of the calling function.
+    #[cfg(target_arch = "x86_64")]
+    #[inline(always)]
+    fn capture_regs() -> Registers {
+        let ip: u64;
+        let sp: u64;
+        let fp: u64;
+        // SAFETY: The instructions only read register values.
+        unsafe {
+            asm!(
+                "lea {ip}, [rip]",
+                "mov {sp}, rsp",
+                "mov {fp}, rbp",
+                ip = out(reg) ip,
+                sp = out(reg) sp,
+                fp = out(reg) fp,
+                options(nomem, nostack, preserves_flags),
+            )
+        };
+
+        Registers {
+            ip,
+            sp,
+            fp,
+            ..Default::default()
+        }
+    }
+
+    #[cfg(target_arch = "x86_64")]
+    #[inline(never)]
+    fn unwind_inner(f: &dyn Fn(&Registers) -> Vec<Addr>) -> Vec<Addr> {
+        let regs = capture_regs();
+        f(&regs)
+    }
+
+    #[cfg(target_arch = "x86_64")]
+    #[inline(never)]
+    fn unwind_outer(f: &dyn Fn(&Registers) -> Vec<Addr>) -> Vec<Addr> {
+        let addrs = unwind_inner(f);
+        black_box(addrs)
+    }
+
+    /// Check that the provided addresses symbolize to our test
+    /// functions.
+    #[cfg(target_arch = "x86_64")]
+    fn check_frames(addrs: &[Addr]) {
+        assert!(addrs.len() >= 3, "{addrs:#x?}");
+
+        let src = Source::Process(Process::new(Pid::Slf));
+        let symbolizer = Symbolizer::new();
+        let syms = symbolizer
+            .symbolize(&src, Input::AbsAddr(&addrs[0..3]))
+            .unwrap();
+        let names = syms
+            .iter()
+            .map(|sym| {
+                sym.as_sym()
+                    .map(|sym| sym.name.to_string())
+                    .unwrap_or_default()
+            })
+            .collect::<Vec<_>>();
+        assert!(names[0].ends_with("unwind_inner"), "{names:?}");
+        assert!(names[1].ends_with("unwind_outer"), "{names:?}");
+    }
+
+    /// Check that we can unwind our own stack, reading memory directly
+    /// from the process.
+    #[cfg(target_arch = "x86_64")]
+    #[test]
+    fn unwind_self() {
+        let unwinder = Unwinder::new();
+        let addrs =
+            unwind_outer(&|regs| unwinder.unwind(Pid::Slf, regs, &Memory::Process).unwrap());
+        let () = check_frames(&addrs);
+
+        let unwinder = Unwinder::builder().set_max_frames(2).build();
+        let addrs =
+            unwind_outer(&|regs| unwinder.unwind(Pid::Slf, regs, &Memory::Process).unwrap());
+        assert_eq!(addrs.len(), 2);
+    }
+
+    /// Check that we can unwind our own stack using a copy of it as
+    /// well as using a memory reading callback.
+    #[cfg(target_arch = "x86_64")]
+    #[test]
+    fn unwind_self_stack_copy() {
+        let mem = File::open("/proc/self/mem").unwrap();
+        let read = |addr, buf: &mut [u8]| read_exact_at(&mem, buf, addr);
+
+        let unwinder = Unwinder::new();
+        let addrs = unwind_outer(&|regs| {
+            // Copy the stack up to its end or 64 KiB, whatever comes
+            // first.
+            let mut data = Vec::new();
+            let mut buf = [0; 8];
+            while data.len() < 64 * 1024 && read(regs.sp + data.len() as u64, &mut buf) {
+                let () = data.extend_from_slice(&buf);
+            }
+
+            let memory = Memory::Stack {
+                base: regs.sp,
+                data: &data,
+            };
+            unwinder.unwind(Pid::Slf, regs, &memory).unwrap()
+        });
+        let () = check_frames(&addrs);
+
+        let addrs = unwind_outer(&|regs| {
+            let memory = Memory::Callback(&read);
+            unwinder.unwind(Pid::Slf, regs, &memory).unwrap()
+        });
+        let () = check_frames(&addrs);
+    }
+
+    /// Check that we stop unwinding gracefully when memory can't be
+    /// read.
+    #[cfg(target_arch = "x86_64")]
+    #[test]
+    fn unwind_unreadable_memory() {
+        let read = |_addr, _buf: &mut [u8]| false;
+        let memory = Memory::Callback(&read);
+        let unwinder = Unwinder::new();
+        let addrs = unwind_outer(&|regs| unwinder.unwind(Pid::Slf, regs, &memory).unwrap());
+        assert_eq!(addrs.len(), 1);
+    }
+
+    /// Check that we can unwind and symbolize the stack of a process
+    /// stopped early during startup, while it is still executing code
+    /// of the dynamic linker.
+    #[cfg(all(linux, target_arch = "x86_64"))]
+    #[test]
+    fn unwind_process_startup() {
+        use std::env::current_exe;
+        use std::ffi::CString;
+        use std::mem::MaybeUninit;
+        use std::os::unix::ffi::OsStrExt as _;
+        use std::ptr;
+
+        // We execute the test binary itself, with a filter not
+        // matching any test, should the child ever get to run.
+        let exe = current_exe().unwrap();
+        let path = CString::new(exe.as_os_str().as_bytes()).unwrap();
+        let exact = CString::new("--exact").unwrap();
+        let filter = CString::new("<none>").unwrap();
+        let argv = [path.as_ptr(), exact.as_ptr(), filter.as_ptr(), ptr::null()];
+
+        // SAFETY: The child only uses async-signal-safe functions
+        //         before replacing its image.
+        let pid = unsafe { libc::fork() };
+        assert!(pid >= 0);
+        if pid == 0 {
+            // SAFETY: All pointers passed in are valid.
+            unsafe {
+                let _rc = libc::ptrace(libc::PTRACE_TRACEME, 0, ptr::null_mut::<libc::c_void>(), 0);
+                let _rc = libc::execv(path.as_ptr(), argv.as_ptr());
+                libc::_exit(1)
+            }
+        }
+
+        // The child stops with a `SIGTRAP` once it successfully
+        // executed the program, before running any user space code
+        // besides the dynamic linker's entry point.
+        let mut status = 0;
+        // SAFETY: `status` is a valid pointer.
+        let rc = unsafe { libc::waitpid(pid, &mut status, 0) };
+        assert_eq!(rc, pid);
+        assert!(libc::WIFSTOPPED(status));
+        assert_eq!(libc::WSTOPSIG(status), libc::SIGTRAP);
+
+        let mut regs = MaybeUninit::<libc::user_regs_struct>::uninit();
+        // SAFETY: `regs` is large enough to hold the child's
+        //         registers.
+        let rc = unsafe {
+            libc::ptrace(
+                libc::PTRACE_GETREGS,
+                pid,
+                ptr::null_mut::<libc::c_void>(),
+                regs.as_mut_ptr(),
+            )
+        };
+        assert_eq!(rc, 0);
+        // SAFETY: `PTRACE_GETREGS` succeeded and initialized `regs`.
+        let regs = unsafe { regs.assume_init() };
+        let regs = Registers {
+            ip: regs.rip,
+            sp: regs.rsp,
+            fp: regs.rbp,
+            ..Default::default()
+        };
+
+        let child = Pid::from(u32::try_from(pid).unwrap());
+        let linker = maps::parse(&child)
+            .unwrap()
+            .map(Result::unwrap)
+            .find(|entry| entry.range.contains(&regs.ip))
+            .and_then(|entry| match entry.path_name {
+                Some(PathName::Path(path)) => Some(path.symbolic_path),
+                _ => None,
+            });
+        let addrs = Unwinder::new().unwind(child.clone(), &regs, &Memory::Process);
+        // `__tls_get_addr` is provided by the dynamic linker itself.
+        let inspector = Inspector::new();
+        let src = inspect::source::Source::Process(inspect::source::Process::new(child.clone()));
+        let tls_get_addr = inspector.lookup(&src, &["__tls_get_addr"]);
+
+        let src = Source::Process(Process::new(child));
+        let symbolizer = Symbolizer::new();
+        let entry = symbolizer.symbolize_single(&src, Input::AbsAddr(regs.ip));
+        let tls_get_addr = tls_get_addr.map(|syms| {
+            let addrs = syms[0].iter().map(|sym| sym.addr).collect::<Vec<_>>();
+            symbolizer.symbolize(&src, Input::AbsAddr(&addrs))
+        });
+
+        // SAFETY: `kill` and `waitpid` are safe to call with any
+        //         arguments.
+        unsafe {
+            let _rc = libc::kill(pid, libc::SIGKILL);
+            let _rc = libc::waitpid(pid, &mut status, 0);
+        }
+
+        let linker = linker.unwrap();
+        let file_name = linker.file_name().unwrap().to_str().unwrap();
+        assert!(file_name.starts_with("ld-"), "{linker:?}");
+
+        let addrs = addrs.unwrap();
+        assert_eq!(addrs[0], regs.ip);
+
+        // The dynamic linker's entry point is not necessarily covered
+        // by a symbol, but the address has to be attributed to it.
+        let entry = entry.unwrap();
+        assert!(
+            matches!(
+                entry,
+                Symbolized::Sym(..) | Symbolized::Unknown(Reason::MissingSyms)
+            ),
+            "{entry:?}"
+        );
+
+        let syms = tls_get_addr.unwrap().unwrap();
+        assert!(!syms.is_empty());
+        for sym in syms {
+            let sym = sym.as_sym().unwrap();
+            assert!(sym.name.ends_with("__tls_get_addr"), "{sym:?}");
+            assert_eq!(sym.offset, 0);
+        }
+    }
+}
@@ -1,18 +1,26 @@
+use std::borrow::Cow;
 use std::cmp::Ordering;
 use std::ffi::CStr;
 use std::ffi::CString;
 use std::ffi::OsStr;
+use std::ffi::OsString;
 use std::fmt::Debug;
 use std::fmt::Formatter;
 use std::fmt::Result as FmtResult;
+#[cfg(feature = "async")]
+use std::fs::File;
 use std::io;
 use std::iter;
 use std::mem::align_of;
 use std::mem::size_of;
 use std::mem::MaybeUninit;
+use std::ops::Range;
+#[cfg(all(target_os = "linux", feature = "async"))]
+use std::os::fd::AsRawFd as _;
 #[cfg(unix)]
 use std::os::unix::ffi::OsStrExt as _;
 use std::path::Path;
+use std::path::PathBuf;
 use std::slice;
 #[cfg(not(unix))]
 use std::str::from_utf8;
@@ -130,27 +138,6 @@ pub(crate) fn trim_ascii_start(mut bytes: &[u8]) -> &[u8] {
     bytes
 }
 
-// TODO: This is a copy of unstable `trim_ascii_end` from std. Once stabilized,
-//       we should remove this functionality in favor of the std version.
-#[inline]
-pub(crate) fn trim_ascii_end(mut bytes: &[u8]) -> &[u8] {
-    while let [rest @ .., last] = bytes {
-        if last.is_ascii_whitespace() {
-            bytes = rest;
-        } else {
-            break;
-        }
-    }
-    bytes
-}
-
-// TODO: This is a copy of unstable `trim_ascii` from std. Once stabilized,
-//       we should remove this functionality in favor of the std version.
-#[inline]
-pub(crate) fn trim_ascii(bytes: &[u8]) -> &[u8] {
-    trim_ascii_end(trim_ascii_start(bytes))
-}
-
 /// Splits the slice on the first element that matches the specified predicate.
 // TODO: This is a copy of unstable `<[u8]>::split_once` from std. Once
 //       stabilized, we should remove this functionality in favor of the std
@@ -243,6 +230,147 @@ pub(crate) fn path_to_bytes(path: &Path) -> io::Result<&[u8]> {
     Ok(bytes)
 }
 
+/// Convert `path` into a path that is valid UTF-8, replacing invalid
+/// sequences with `U+FFFD REPLACEMENT CHARACTER`.
+pub(crate) fn lossy_path(path: Cow<'_, Path>) -> Cow<'_, Path> {
+    if path.to_str().is_some() {
+        path
+    } else {
+        Cow::Owned(PathBuf::from(path.to_string_lossy().into_owned()))
+    }
+}
+
+/// Convert `s` into an [`OsStr`] that is valid UTF-8, replacing invalid
+/// sequences with `U+FFFD REPLACEMENT CHARACTER`.
+pub(crate) fn lossy_os_str(s: Cow<'_, OsStr>) -> Cow<'_, OsStr> {
+    if s.to_str().is_some() {
+        s
+    } else {
+        Cow::Owned(OsString::from(s.to_string_lossy().into_owned()))
+    }
+}
+
+
+/// `serde` support for paths that may not be valid UTF-8.
+///
+/// Paths that are valid UTF-8 are represented as plain strings. All
+/// others are represented as an object containing both a lossy string
+/// form (`lossy`) and the raw bytes of the path (`bytes`), the latter
+/// of which is used when deserializing.
+#[cfg(feature = "serde")]
+pub(crate) mod serde_path {
+    use std::borrow::Cow;
+    use std::ffi::OsStr;
+    use std::path::Path;
+    use std::path::PathBuf;
+
+    use serde::de::Error as _;
+    use serde::ser::Error as _;
+    use serde::Deserialize as _;
+    use serde::Deserializer;
+    use serde::Serialize;
+    use serde::Serializer;
+
+    use super::bytes_to_path;
+    use super::path_to_bytes;
+
+
+    #[derive(serde::Deserialize, serde::Serialize)]
+    #[serde(untagged)]
+    enum Repr<'path> {
+        Str(Cow<'path, str>),
+        Raw {
+            lossy: Cow<'path, str>,
+            bytes: Cow<'path, [u8]>,
+        },
+    }
+
+    impl Repr<'_> {
+        fn into_path_buf(self) -> Result<PathBuf, String> {
+            match self {
+                Self::Str(s) => Ok(PathBuf::from(s.into_owned())),
+                Self::Raw { lossy: _, bytes } => bytes_to_path(&bytes)
+                    .map(Path::to_path_buf)
+                    .map_err(|err| err.to_string()),
+            }
+        }
+    }
+
+    struct Ser<'path>(&'path Path);
+
+    impl Serialize for Ser<'_> {
+        fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+        where
+            S: Serializer,
+        {
+            let repr = match self.0.to_str() {
+                Some(s) => Repr::Str(Cow::Borrowed(s)),
+                None => Repr::Raw {
+                    lossy: self.0.to_string_lossy(),
+                    bytes: Cow::Borrowed(path_to_bytes(self.0).map_err(S::Error::custom)?),
+                },
+            };
+            repr.serialize(serializer)
+        }
+    }
+
+    pub(crate) fn serialize<S, P>(path: &P, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+        P: AsRef<Path>,
+    {
+        Ser(path.as_ref()).serialize(serializer)
+    }
+
+    pub(crate) fn deserialize<'de, D, P>(deserializer: D) -> Result<P, D::Error>
+    where
+        D: Deserializer<'de>,
+        P: From<PathBuf>,
+    {
+        let path = Repr::deserialize(deserializer)?
+            .into_path_buf()
+            .map_err(D::Error::custom)?;
+        Ok(P::from(path))
+    }
+
+    pub(crate) fn deserialize_os_str<'de, D>(
+        deserializer: D,
+    ) -> Result<Cow<'static, OsStr>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let path = deserialize::<D, PathBuf>(deserializer)?;
+        Ok(Cow::Owned(path.into_os_string()))
+    }
+
+    /// `serde` support for optional paths.
+    pub(crate) mod opt {
+        use super::*;
+
+
+        pub(crate) fn serialize<S, P>(path: &Option<P>, serializer: S) -> Result<S::Ok, S::Error>
+        where
+            S: Serializer,
+            P: AsRef<Path>,
+        {
+            path.as_ref()
+                .map(|path| Ser(path.as_ref()))
+                .serialize(serializer)
+        }
+
+        pub(crate) fn deserialize<'de, D, P>(deserializer: D) -> Result<Option<P>, D::Error>
+        where
+            D: Deserializer<'de>,
+            P: From<PathBuf>,
+        {
+            Option::<Repr>::deserialize(deserializer)?
+                .map(|repr| repr.into_path_buf().map(P::from))
+                .transpose()
+                .map_err(D::Error::custom)
+        }
+    }
+}
+
 /// Reorder elements of `array` based on index information in `indices`.
 fn reorder<T, U>(array: &mut [T], indices: Vec<(U, usize)>) {
     debug_assert_eq!(array.len(), indices.len());
@@ -303,6 +431,46 @@ pub fn stat(path: &Path) -> io::Result<libc::stat> {
 }
 
 
+/// Create a FIFO at `path`.
+#[cfg(all(test, unix))]
+pub(crate) fn mkfifo(path: &Path) -> io::Result<()> {
+    let mut path = path_to_bytes(path)?.to_vec();
+    let () = path.push(b'\0');
+
+    let rc = unsafe { libc::mkfifo(path.as_ptr().cast::<libc::c_char>(), 0o600) };
+    if rc < 0 {
+        return Err(io::Error::last_os_error())
+    }
+    Ok(())
+}
+
+
+/// Read the contents of `file` into the page cache.
+///
+/// This function blocks until the data have been read.
+#[cfg(feature = "async")]
+pub(crate) fn prefetch_file(file: &File) -> io::Result<()> {
+    #[cfg(target_os = "linux")]
+    {
+        let len = file.metadata()?.len();
+        let count = usize::try_from(len).unwrap_or(usize::MAX);
+        // SAFETY: `readahead` is always safe to call on a valid file
+        //         descriptor.
+        let rc = unsafe { libc::readahead(file.as_raw_fd(), 0, count) };
+        if rc < 0 {
+            return Err(io::Error::last_os_error())
+        }
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    {
+        let _count = io::copy(&mut &*file, &mut io::sink())?;
+    }
+
+    Ok(())
+}
+
+
 #[cfg(linux)]
 #[cfg(test)]
 #[allow(clippy::absolute_paths)]
@@ -403,6 +571,19 @@ where
     Some(idx)
 }
 
+/// Find the element whose range, as reported by `f`, contains `addr`.
+///
+/// `slice` has to be sorted by range, with ranges not overlapping
+/// each other.
+pub(crate) fn find_range_by_key<T, F>(slice: &[T], addr: Addr, mut f: F) -> Option<usize>
+where
+    F: FnMut(&T) -> &Range<Addr>,
+{
+    let idx = slice.partition_point(|e| f(e).end <= addr);
+    let e = slice.get(idx)?;
+    f(e).contains(&addr).then_some(idx)
+}
+
 /// Perform a binary search on a slice, returning the index of the match (if
 /// found) or the one of the previous item (if any), taking into account
 /// duplicates.
@@ -435,6 +616,7 @@ unsafe impl Pod for i64 {}
 unsafe impl Pod for u64 {}
 unsafe impl Pod for i128 {}
 unsafe impl Pod for u128 {}
+unsafe impl Pod for usize {}
 
 /// An trait providing utility functions for reading data from a byte buffer.
 pub trait ReadRaw<'data> {
@@ -1082,6 +1264,25 @@ mod tests {
         assert_eq!(find_match_or_lower_bound(&data, 1337), Some(4));
     }
 
+    /// Check that `find_range_by_key` finds the range containing an
+    /// address.
+    #[tag(miri)]
+    #[test]
+    fn range_search() {
+        let ranges: [Range<Addr>; 0] = [];
+        assert_eq!(find_range_by_key(&ranges, 0, |range| range), None);
+
+        let ranges = [0x10..0x20, 0x20..0x28, 0x28..0x28, 0x30..0x40];
+        assert_eq!(find_range_by_key(&ranges, 0x0, |range| range), None);
+        assert_eq!(find_range_by_key(&ranges, 0x10, |range| range), Some(0));
+        assert_eq!(find_range_by_key(&ranges, 0x1f, |range| range), Some(0));
+        assert_eq!(find_range_by_key(&ranges, 0x20, |range| range), Some(1));
+        assert_eq!(find_range_by_key(&ranges, 0x28, |range| range), None);
+        assert_eq!(find_range_by_key(&ranges, 0x2f, |range| range), None);
+        assert_eq!(find_range_by_key(&ranges, 0x30, |range| range), Some(3));
+        assert_eq!(find_range_by_key(&ranges, 0x40, |range| range), None);
+    }
+
     /// Benchmark the reading of LEB128 encoded `u64` values.
     #[cfg(feature = "nightly")]
     #[bench]
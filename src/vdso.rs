@@ -0,0 +1,243 @@
+//! Support for symbolizing addresses in pseudo-modules provided by the
+//! kernel, namely the virtual dynamic shared object (`[vdso]`) and the
+//! legacy x86_64 `[vsyscall]` page.
+//!
+//! Data only mappings accompanying the vDSO (`[vvar]` and, on more
+//! recent kernels, `[vvar_vclock]`) do not contain any code and are not
+//! covered.
+
+use std::fs::File;
+use std::ops::Range;
+#[cfg(linux)]
+use std::os::unix::fs::FileExt as _;
+use std::rc::Rc;
+use std::slice;
+
+use crate::elf::ElfParser;
+use crate::elf::ElfResolver;
+use crate::mmap::Mmap;
+use crate::symbolize::FindSymOpts;
+use crate::symbolize::Reason;
+use crate::symbolize::ResolvedSym;
+use crate::symbolize::SrcLang;
+use crate::symbolize::Symbolize;
+use crate::Addr;
+#[cfg(not(linux))]
+use crate::Error;
+use crate::ErrorExt as _;
+use crate::Pid;
+use crate::Result;
+
+
+/// The name of the component representing the vDSO in proc maps.
+pub(crate) const VDSO: &str = "[vdso]";
+/// The name of the component representing the vsyscall page in proc
+/// maps.
+pub(crate) const VSYSCALL: &str = "[vsyscall]";
+
+/// The size of each of the entry points in the vsyscall page.
+const VSYSCALL_SLOT_SIZE: Addr = 0x400;
+/// The entry points of the vsyscall page, in order.
+const VSYSCALL_FNS: [&str; 3] = ["gettimeofday", "time", "getcpu"];
+
+
+/// Check whether the proc maps component `component` refers to a
+/// pseudo-module that we know how to symbolize addresses in.
+pub(crate) fn is_pseudo_module(component: &str) -> bool {
+    component == VDSO || component == VSYSCALL
+}
+
+
+/// Copy the vDSO of the current process, if it is mapped at `range`.
+///
+/// The kernel reports the address of the vDSO image it mapped into our
+/// address space via the auxiliary vector. Reading it directly spares
+/// us from having to go through `/proc/self/mem`, which may not be
+/// accessible in restricted environments.
+#[cfg(linux)]
+fn copy_own_vdso(range: &Range<Addr>) -> Option<Vec<u8>> {
+    // SAFETY: `getauxval` is always safe to call.
+    let base = unsafe { libc::getauxval(libc::AT_SYSINFO_EHDR) } as Addr;
+    if base == 0 || base != range.start {
+        return None
+    }
+
+    let len = usize::try_from(range.end - range.start).ok()?;
+    // SAFETY: The vDSO is mapped readable at `base` by the kernel for
+    //         the entire lifetime of the process and `range` describes
+    //         its mapping.
+    let data = unsafe { slice::from_raw_parts(base as *const u8, len) };
+    Some(data.to_vec())
+}
+
+#[cfg(not(linux))]
+fn copy_own_vdso(_range: &Range<Addr>) -> Option<Vec<u8>> {
+    None
+}
+
+/// Copy the vDSO of process `pid`, which is mapped at `range`, out of
+/// its memory, via `/proc/<pid>/mem`.
+#[cfg(linux)]
+fn copy_proc_vdso(pid: &Pid, range: &Range<Addr>) -> Result<Vec<u8>> {
+    let path = format!("/proc/{pid}/mem");
+    let file = File::open(&path).with_context(|| format!("failed to open `{path}`"))?;
+    // Make sure that the file we opened actually belongs to the process
+    // in question.
+    let () = pid.check_alive()?;
+    let mut data = vec![0; (range.end - range.start) as usize];
+    let () = file
+        .read_exact_at(&mut data, range.start)
+        .with_context(|| format!("failed to read vDSO at {range:#x?} from `{path}`"))?;
+    Ok(data)
+}
+
+#[cfg(not(linux))]
+fn copy_proc_vdso(_pid: &Pid, _range: &Range<Addr>) -> Result<Vec<u8>> {
+    Err(Error::with_unsupported(
+        "reading the vDSO of a process is unsupported on this platform",
+    ))
+}
+
+/// Read the vDSO of process `pid`, which is mapped at `range`.
+///
+/// The vDSO is a fully fledged ELF object, but there is no file
+/// backing it. Hence, we copy it out of the process' memory.
+pub(crate) fn read_vdso(pid: &Pid, range: Range<Addr>) -> Result<Vec<u8>> {
+    if let Pid::Slf = pid {
+        if let Some(data) = copy_own_vdso(&range) {
+            return Ok(data)
+        }
+    }
+    copy_proc_vdso(pid, &range)
+}
+
+/// Create an [`ElfResolver`] for a vDSO previously read via
+/// [`read_vdso`].
+pub(crate) fn vdso_resolver_from_data(data: &[u8]) -> Result<ElfResolver> {
+    let mmap = Mmap::from_bytes(data)?;
+    let parser = ElfParser::from_mmap(mmap, None);
+    ElfResolver::from_parser(Rc::new(parser), None, false, false)
+}
+
+/// Create an [`ElfResolver`] for the vDSO of process `pid`, which is
+/// mapped at `range`.
+pub(crate) fn vdso_resolver(pid: &Pid, range: Range<Addr>) -> Result<ElfResolver> {
+    let data = read_vdso(pid, range)?;
+    vdso_resolver_from_data(&data)
+}
+
+
+/// A resolver for addresses in the x86_64 vsyscall page.
+///
+/// Addresses are expected to be relative to the start of the page.
+#[derive(Debug)]
+pub(crate) struct Vsyscall;
+
+impl Symbolize for Vsyscall {
+    fn find_sym(&self, addr: Addr, _opts: &FindSymOpts) -> Result<Result<ResolvedSym<'_>, Reason>> {
+        let idx = addr / VSYSCALL_SLOT_SIZE;
+        match VSYSCALL_FNS.get(idx as usize) {
+            Some(name) => {
+                let sym = ResolvedSym {
+                    name,
+                    addr: idx * VSYSCALL_SLOT_SIZE,
+                    size: Some(VSYSCALL_SLOT_SIZE as usize),
+                    lang: SrcLang::Unknown,
+                    code_info: None,
+                    inlined: Box::new([]),
+                    inlined_truncated: false,
+                    section: None,
+                    module: None,
+                };
+                Ok(Ok(sym))
+            }
+            None => Ok(Err(Reason::UnknownAddr)),
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::ops::ControlFlow;
+    use std::process;
+
+    use test_log::test;
+
+    use crate::inspect::FindAddrOpts;
+    use crate::inspect::Inspect as _;
+    use crate::maps;
+    use crate::SymType;
+
+
+    /// Check that we can symbolize addresses in the vsyscall page.
+    #[test]
+    fn vsyscall_symbolization() {
+        let opts = FindSymOpts::Basic;
+        let sym = Vsyscall.find_sym(0x0, &opts).unwrap().unwrap();
+        assert_eq!(sym.name, "gettimeofday");
+        assert_eq!(sym.addr, 0x0);
+
+        let sym = Vsyscall.find_sym(0x401, &opts).unwrap().unwrap();
+        assert_eq!(sym.name, "time");
+        assert_eq!(sym.addr, 0x400);
+
+        let sym = Vsyscall.find_sym(0xbff, &opts).unwrap().unwrap();
+        assert_eq!(sym.name, "getcpu");
+        assert_eq!(sym.addr, 0x800);
+
+        let err = Vsyscall.find_sym(0xc00, &opts).unwrap().unwrap_err();
+        assert_eq!(err, Reason::UnknownAddr);
+    }
+
+    /// Check that we can symbolize an address in our own vDSO.
+    #[cfg(linux)]
+    #[test]
+    fn vdso_symbolization() {
+        let base = unsafe { libc::getauxval(libc::AT_SYSINFO_EHDR) };
+        if base == 0 {
+            // No vDSO present on this system.
+            return
+        }
+
+        let entry = maps::parse(&Pid::Slf)
+            .unwrap()
+            .map(Result::unwrap)
+            .find(|entry| entry.range.start == base)
+            .unwrap();
+        let resolver = vdso_resolver(&Pid::Slf, entry.range.clone()).unwrap();
+
+        let opts = FindAddrOpts {
+            sym_type: SymType::Function,
+            ..Default::default()
+        };
+        let mut syms = Vec::new();
+        let () = resolver
+            .for_each(&opts, &mut |sym| {
+                let () = syms.push((sym.name.to_string(), sym.addr));
+                ControlFlow::Continue(())
+            })
+            .unwrap();
+        assert!(syms
+            .iter()
+            .any(|(name, _addr)| name.contains("clock_gettime")));
+
+        for (name, addr) in syms {
+            let sym = resolver
+                .find_sym(addr, &FindSymOpts::Basic)
+                .unwrap()
+                .unwrap();
+            // Aliases may resolve to a different name than we started
+            // out with, but they have to refer to the same address.
+            assert_eq!(sym.addr, addr, "{name}");
+        }
+
+        // The image copied out of our own address space has to match
+        // what we read via `/proc/<pid>/mem`.
+        let pid = Pid::from(process::id());
+        let data = read_vdso(&pid, entry.range.clone()).unwrap();
+        assert_eq!(read_vdso(&Pid::Slf, entry.range).unwrap(), data);
+    }
+}
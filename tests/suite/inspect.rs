@@ -4,14 +4,18 @@ use std::fs::read as read_file;
 use std::ops::ControlFlow;
 use std::ops::Deref as _;
 use std::path::Path;
+use std::path::PathBuf;
 use std::str;
 
 use blazesym::inspect::source::Breakpad;
 use blazesym::inspect::source::Elf;
+use blazesym::inspect::source::Gsym;
+use blazesym::inspect::source::Kallsyms;
 use blazesym::inspect::source::Source;
 use blazesym::inspect::Inspector;
 use blazesym::inspect::SymInfo;
 use blazesym::symbolize;
+use blazesym::ErrorKind;
 use blazesym::SymType;
 
 use test_log::test;
@@ -20,7 +24,7 @@ use test_log::test;
 /// Check that we can look up an address.
 #[test]
 fn inspect_elf() {
-    fn test(src: Source, no_vars: bool) {
+    fn test(src: Source) {
         let inspector = Inspector::new();
         let results = inspector
             .lookup(&src, &["factorial", "a_variable"])
@@ -38,28 +42,22 @@ fn inspect_elf() {
         );
 
         let result = &results[1];
-        if no_vars {
-            assert!(result.is_empty(), "{result:#x?}");
-        } else {
-            assert_eq!(result.len(), 1);
-            assert_eq!(result[0].addr, 0x4001100);
-            assert_eq!(result[0].sym_type, SymType::Variable);
-            assert_ne!(result[0].file_offset, None);
-            assert_eq!(
-                result[0].obj_file_name.as_deref().unwrap(),
-                src.path().unwrap()
-            );
-        }
+        assert_eq!(result.len(), 1);
+        assert_eq!(result[0].addr, 0x4001100);
+        assert_eq!(result[0].size, Some(8));
+        assert_eq!(result[0].sym_type, SymType::Variable);
+        assert_ne!(result[0].file_offset, None);
+        assert_eq!(
+            result[0].obj_file_name.as_deref().unwrap(),
+            src.path().unwrap()
+        );
     }
 
     let test_dwarf = Path::new(&env!("CARGO_MANIFEST_DIR"))
         .join("data")
         .join("test-stable-addrs-stripped-elf-with-dwarf.bin");
     let src = Source::Elf(Elf::new(test_dwarf));
-    // Our `DwarfResolver` type does not currently support look up of
-    // variables.
-    let no_vars = true;
-    let () = test(src, no_vars);
+    let () = test(src);
 
     let test_elf = Path::new(&env!("CARGO_MANIFEST_DIR"))
         .join("data")
@@ -68,7 +66,7 @@ fn inspect_elf() {
         let mut elf = Elf::new(&test_elf);
         elf.debug_syms = debug_syms;
         let src = Source::Elf(elf);
-        let () = test(src, false);
+        let () = test(src);
     }
 
     let test_elf = Path::new(&env!("CARGO_MANIFEST_DIR"))
@@ -78,7 +76,52 @@ fn inspect_elf() {
     assert!(elf.debug_syms);
     elf.debug_syms = false;
     let src = Source::Elf(elf);
-    let () = test(src, false);
+    let () = test(src);
+}
+
+
+/// Check that we can report declaration information for symbols and
+/// use it to disambiguate identically named static functions.
+#[test]
+fn inspect_elf_decl_info() {
+    let test_dwarf = Path::new(&env!("CARGO_MANIFEST_DIR"))
+        .join("data")
+        .join("test-stable-addrs-stripped-elf-with-dwarf.bin");
+    let mut elf = Elf::new(test_dwarf);
+    let inspector = Inspector::new();
+
+    let src = Source::Elf(elf.clone());
+    let results = inspector.lookup(&src, &["i_exist_twice"]).unwrap();
+    assert_eq!(results[0].len(), 2);
+    assert!(results[0].iter().all(|sym| sym.code_info.is_none()));
+
+    elf.code_info = true;
+    let src = Source::Elf(elf.clone());
+    let results = inspector.lookup(&src, &["i_exist_twice"]).unwrap();
+    let syms = &results[0];
+    assert_eq!(syms.len(), 2);
+
+    let sym = syms
+        .iter()
+        .find(|sym| sym.is_declared_in(Path::new("test-stable-addrs.c")))
+        .unwrap();
+    assert_eq!(sym.code_info.as_ref().unwrap().line, Some(46));
+
+    let sym = syms
+        .iter()
+        .find(|sym| sym.is_declared_in(Path::new("test-stable-addrs-cu2.c")))
+        .unwrap();
+    assert_eq!(sym.code_info.as_ref().unwrap().line, Some(9));
+
+    elf.decl_file = Some(PathBuf::from("test-stable-addrs-cu2.c"));
+    let src = Source::Elf(elf);
+    let results = inspector
+        .lookup(&src, &["i_exist_twice", "factorial"])
+        .unwrap();
+    let syms = &results[0];
+    assert_eq!(syms.len(), 1);
+    assert_eq!(syms[0].code_info.as_ref().unwrap().line, Some(9));
+    assert_eq!(results[1], Vec::new());
 }
 
 
@@ -109,6 +152,200 @@ fn inspect_breakpad() {
 }
 
 
+/// Check that we can look up a symbol by name in a Gsym file.
+#[test]
+fn inspect_gsym() {
+    let path = Path::new(&env!("CARGO_MANIFEST_DIR"))
+        .join("data")
+        .join("test-stable-addrs.gsym");
+    let gsym = Gsym::new(&path);
+    let src = Source::from(gsym);
+
+    let inspector = Inspector::new();
+    let results = inspector
+        .lookup(&src, &["factorial"])
+        .unwrap()
+        .into_iter()
+        .flatten()
+        .collect::<Vec<_>>();
+    assert_eq!(results.len(), 1);
+
+    let sym = &results[0];
+    assert_eq!(sym.name, "factorial");
+    assert_eq!(sym.addr, 0x2000200);
+    assert_eq!(sym.sym_type, SymType::Function);
+    assert_eq!(sym.file_offset, None);
+    assert_eq!(sym.obj_file_name.as_deref(), Some(path.as_path()));
+
+    let mut syms = HashMap::<String, SymInfo>::new();
+    let () = inspector
+        .for_each(&src, |sym| {
+            let _inserted = syms.insert(sym.name.to_string(), sym.to_owned());
+            ControlFlow::Continue(())
+        })
+        .unwrap();
+
+    let sym = syms.get("main").unwrap();
+    assert_eq!(sym.sym_type, SymType::Function);
+    let sym = syms.get("factorial_inline_test").unwrap();
+    assert_eq!(sym.sym_type, SymType::Function);
+}
+
+
+/// Check that we can find the addresses of the code for a source code
+/// line.
+#[test]
+fn inspect_line_addrs() {
+    fn test(src: Source) {
+        let inspector = Inspector::new();
+        let addrs = inspector
+            .find_addr_for_line(&src, Path::new("test-stable-addrs.c"), 13)
+            .unwrap();
+        assert_eq!(addrs, vec![0x2000218]);
+
+        let addrs = inspector
+            .find_addr_for_line(&src, Path::new("test-stable-addrs.c"), 10)
+            .unwrap();
+        assert_eq!(addrs, vec![0x2000200]);
+
+        let addrs = inspector
+            .find_addr_for_line(&src, Path::new("test-stable-addrs.c"), 6)
+            .unwrap();
+        assert_eq!(addrs, Vec::new());
+    }
+
+    for file in ["test-stable-addrs.bin", "test-stable-addrs-dwarf5.bin"] {
+        let path = Path::new(&env!("CARGO_MANIFEST_DIR"))
+            .join("data")
+            .join(file);
+        let src = Source::from(Elf::new(path));
+        test(src);
+    }
+
+    let path = Path::new(&env!("CARGO_MANIFEST_DIR"))
+        .join("data")
+        .join("test-stable-addrs.gsym");
+    let src = Source::from(Gsym::new(path));
+    test(src);
+
+    let path = Path::new(&env!("CARGO_MANIFEST_DIR"))
+        .join("data")
+        .join("test-stable-addrs.sym");
+    let src = Source::from(Breakpad::new(path));
+    let err = Inspector::new()
+        .find_addr_for_line(&src, Path::new("test-stable-addrs.c"), 13)
+        .unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::Unsupported);
+}
+
+
+/// Check that we can look up the address ranges of functions.
+#[test]
+fn inspect_func_ranges() {
+    fn test(src: Source) {
+        let inspector = Inspector::new();
+        let ranges = inspector.func_ranges(&src, 0x2000210).unwrap();
+        assert_eq!(ranges, vec![0x2000200..0x200022b]);
+
+        let ranges = inspector.func_ranges(&src, 0x1000).unwrap();
+        assert_eq!(ranges, Vec::new());
+
+        let funcs = inspector.func_ranges_by_name(&src, "factorial").unwrap();
+        assert_eq!(funcs, vec![vec![0x2000200..0x200022b]]);
+
+        // There are two `factorial_wrapper` functions, in different
+        // compilation units.
+        let mut funcs = inspector
+            .func_ranges_by_name(&src, "factorial_wrapper")
+            .unwrap();
+        let () = funcs.sort_by_key(|ranges| ranges[0].start);
+        assert_eq!(
+            funcs,
+            vec![vec![0x2000045..0x2000056], vec![0x200007c..0x200008d]]
+        );
+
+        let funcs = inspector
+            .func_ranges_by_name(&src, "does-not-exist")
+            .unwrap();
+        assert_eq!(funcs, Vec::<Vec<_>>::new());
+    }
+
+    let path = Path::new(&env!("CARGO_MANIFEST_DIR"))
+        .join("data")
+        .join("test-stable-addrs.bin");
+    let src = Source::from(Elf::new(&path));
+    test(src);
+
+    let mut elf = Elf::new(&path);
+    elf.debug_syms = false;
+    let src = Source::from(elf);
+    test(src);
+
+    let path = Path::new(&env!("CARGO_MANIFEST_DIR"))
+        .join("data")
+        .join("test-stable-addrs.gsym");
+    let src = Source::from(Gsym::new(path));
+    test(src);
+}
+
+
+/// Check that we can look up symbols by name in a kallsyms file,
+/// optionally restricted to a single kernel module.
+#[test]
+fn inspect_kallsyms() {
+    let path = Path::new(&env!("CARGO_MANIFEST_DIR"))
+        .join("data")
+        .join("kallsyms");
+    let kallsyms = Kallsyms::new(&path);
+    let src = Source::from(kallsyms.clone());
+
+    let inspector = Inspector::new();
+    let results = inspector
+        .lookup(&src, &["init_task", "scanarg", "does-not-exist"])
+        .unwrap();
+    assert_eq!(results.len(), 3);
+
+    let result = &results[0];
+    assert_eq!(result.len(), 1);
+    assert_eq!(result[0].name, "init_task");
+    assert_eq!(result[0].addr, 0xc080a470);
+    assert_eq!(result[0].file_offset, None);
+
+    let result = &results[1];
+    assert_eq!(result.len(), 1);
+    assert_eq!(result[0].name, "scanarg");
+    assert_eq!(result[0].addr, 0xbf00ae80);
+
+    assert_eq!(results[2], Vec::new());
+
+    let src = Source::from(Kallsyms {
+        module: Some("binfmt_misc".to_string()),
+        ..kallsyms.clone()
+    });
+    let results = inspector.lookup(&src, &["init_task", "scanarg"]).unwrap();
+    assert_eq!(results[0], Vec::new());
+    assert_eq!(results[1].len(), 1);
+    assert_eq!(results[1][0].addr, 0xbf00ae80);
+
+    let mut count = 0;
+    let () = inspector
+        .for_each(&src, |sym| {
+            assert_ne!(sym.name, "init_task");
+            count += 1;
+            ControlFlow::Continue(())
+        })
+        .unwrap();
+    assert_eq!(count, 55);
+
+    let src = Source::from(Kallsyms {
+        module: Some("i2c_bcm2708".to_string()),
+        ..kallsyms
+    });
+    let results = inspector.lookup(&src, &["scanarg"]).unwrap();
+    assert_eq!(results[0], Vec::new());
+}
+
+
 /// Make sure that we can look up a dynamic symbol in an ELF file.
 #[test]
 fn inspect_elf_dynamic_symbol() {
@@ -143,6 +380,7 @@ fn inspect_elf_dynamic_symbol() {
     test("libtest-so.so");
     test("libtest-so-stripped.so");
     test("libtest-so-partly-stripped.so");
+    test("libtest-so-32.so");
 }
 
 /// Make sure that we can look up an indirect in an ELF file.
@@ -176,6 +414,37 @@ fn inspect_elf_indirect_function() {
 }
 
 
+/// Check that we can enumerate the PLT stubs of an ELF binary.
+#[test]
+fn inspect_elf_plt_entries() {
+    let bin = Path::new(&env!("CARGO_MANIFEST_DIR"))
+        .join("data")
+        .join("test-no-debug.bin");
+
+    let src = Source::Elf(Elf::new(&bin));
+    let inspector = Inspector::new();
+    let entries = inspector.plt_entries(&src).unwrap();
+    let entry = entries.iter().find(|entry| entry.name == "printf").unwrap();
+    assert_ne!(entry.addr, 0);
+    assert_ne!(entry.got_addr, 0);
+    assert_eq!(entry.size, 16);
+
+    // Stubs are laid out back to back.
+    let () = entries
+        .windows(2)
+        .for_each(|pair| assert!(pair[0].addr + pair[0].size as u64 <= pair[1].addr));
+
+    // The stub should start with an indirect jump (or an `endbr64` in
+    // front of it).
+    if cfg!(target_arch = "x86_64") {
+        let bytes = read_4bytes_at(&bin, entry.file_offset.unwrap());
+        assert!(
+            bytes[0..2] == [0xff, 0x25] || bytes == [0xf3, 0x0f, 0x1e, 0xfa],
+            "{bytes:x?}"
+        );
+    }
+}
+
 /// Read four bytes at the given `offset` in the file identified by `path`.
 fn read_4bytes_at(path: &Path, offset: u64) -> [u8; 4] {
     let offset = offset as usize;
@@ -267,7 +536,7 @@ fn inspect_elf_dwarf_breakpad_all_symbols() {
         let sym = syms.get("resolve_indirect_func").unwrap();
         assert_eq!(sym.sym_type, SymType::Function);
 
-        if !breakpad && !dwarf {
+        if !breakpad {
             let sym = syms.get("a_variable").unwrap();
             assert_eq!(sym.sym_type, SymType::Variable);
         }
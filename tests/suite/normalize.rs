@@ -1,6 +1,7 @@
 use std::env;
 use std::ffi::CString;
 use std::fs::copy;
+use std::fs::create_dir_all;
 use std::io;
 #[cfg(linux)]
 use std::os::unix::ffi::OsStringExt as _;
@@ -48,6 +49,43 @@ fn normalize_unsorted_err() {
         .normalize_user_addrs_opts(Pid::Slf, addrs.as_slice(), &opts)
         .unwrap_err();
     assert!(err.to_string().contains("are not sorted"), "{err}");
+
+    let err = normalizer
+        .normalize_user_addrs_sorted(Pid::Slf, addrs.as_slice())
+        .unwrap_err();
+    assert!(err.to_string().contains("are not sorted"), "{err}");
+}
+
+/// Check that unsorted input addresses are normalized and reported in
+/// their original order by default.
+#[test]
+fn normalize_unsorted() {
+    let mut addrs = [
+        libc::atexit as Addr,
+        libc::chdir as Addr,
+        libc::fopen as Addr,
+        normalize_unsorted as Addr,
+    ];
+    let () = addrs.sort();
+
+    let normalizer = Normalizer::new();
+    let sorted = normalizer
+        .normalize_user_addrs_sorted(Pid::Slf, addrs.as_slice())
+        .unwrap();
+
+    let () = addrs.reverse();
+    let unsorted = normalizer
+        .normalize_user_addrs(Pid::Slf, addrs.as_slice())
+        .unwrap();
+    assert_eq!(unsorted.outputs.len(), addrs.len());
+
+    for (sorted_output, unsorted_output) in sorted.outputs.iter().rev().zip(&unsorted.outputs) {
+        assert_eq!(sorted_output.0, unsorted_output.0);
+        assert_eq!(
+            sorted.meta[sorted_output.1],
+            unsorted.meta[unsorted_output.1]
+        );
+    }
 }
 
 /// Check that we handle unknown addresses as expected.
@@ -190,6 +228,127 @@ fn normalize_elf_addr() {
     }
 }
 
+/// Check that we can normalize an address residing in the dynamic
+/// linker (which is mapped by the kernel and not by the dynamic linker
+/// itself) and symbolize the result.
+#[cfg(linux)]
+#[test]
+fn normalize_dynamic_linker_addr() {
+    // `__tls_get_addr` is provided by the dynamic linker itself.
+    let addr = unsafe { libc::dlsym(libc::RTLD_DEFAULT, "__tls_get_addr\0".as_ptr().cast()) };
+    assert!(!addr.is_null());
+
+    let normalizer = Normalizer::new();
+    let normalized = normalizer
+        .normalize_user_addrs(Pid::Slf, [addr as Addr].as_slice())
+        .unwrap();
+    assert_eq!(normalized.outputs.len(), 1);
+    assert_eq!(normalized.meta.len(), 1);
+
+    let output = normalized.outputs[0];
+    let meta = &normalized.meta[output.1];
+    let path = &meta.as_elf().unwrap().path;
+    let file_name = path.file_name().unwrap().to_str().unwrap();
+    assert!(file_name.starts_with("ld-"), "{path:?}");
+
+    let elf = symbolize::source::Elf::new(path);
+    let src = symbolize::source::Source::Elf(elf);
+    let symbolizer = symbolize::Symbolizer::new();
+    let result = symbolizer
+        .symbolize_single(&src, symbolize::Input::FileOffset(output.0))
+        .unwrap()
+        .into_sym()
+        .unwrap();
+    assert!(result.name.ends_with("__tls_get_addr"), "{result:?}");
+}
+
+/// Check that we can symbolize normalized addresses directly, with
+/// results reported in input order.
+#[cfg(linux)]
+#[test]
+fn symbolize_normalized_addrs() {
+    let addr = unsafe { libc::dlsym(libc::RTLD_DEFAULT, "__tls_get_addr\0".as_ptr().cast()) };
+    assert!(!addr.is_null());
+
+    let addrs = [addr as Addr, 0x0, addr as Addr];
+    let normalizer = Normalizer::new();
+    let normalized = normalizer
+        .normalize_user_addrs(Pid::Slf, addrs.as_slice())
+        .unwrap();
+    assert_eq!(normalized.outputs.len(), 3);
+
+    let symbolizer = symbolize::Symbolizer::new();
+    let store = symbolize::SymbolStore::default();
+    let results = symbolizer
+        .symbolize_normalized(&normalized, &store)
+        .unwrap();
+    assert_eq!(results.len(), 3);
+
+    let sym = results[0].as_sym().unwrap();
+    assert!(sym.name.ends_with("__tls_get_addr"), "{sym:?}");
+    assert_eq!(
+        results[1],
+        symbolize::Symbolized::Unknown(symbolize::Reason::Unmapped)
+    );
+    assert_eq!(results[2], results[0]);
+
+    // Without any way of finding the file, we can't symbolize.
+    let store = symbolize::SymbolStore {
+        build_id_dirs: Vec::new(),
+        recorded_paths: false,
+        ..Default::default()
+    };
+    let results = symbolizer
+        .symbolize_normalized(&normalized, &store)
+        .unwrap();
+    assert_eq!(
+        results[0],
+        symbolize::Symbolized::Unknown(symbolize::Reason::FileNotFound)
+    );
+}
+
+/// Check that we can symbolize normalized addresses using files found
+/// by their build ID.
+#[cfg(linux)]
+#[test]
+fn symbolize_normalized_addrs_by_build_id() {
+    let addr = unsafe { libc::dlsym(libc::RTLD_DEFAULT, "__tls_get_addr\0".as_ptr().cast()) };
+    assert!(!addr.is_null());
+
+    let normalizer = Normalizer::new();
+    let normalized = normalizer
+        .normalize_user_addrs(Pid::Slf, [addr as Addr].as_slice())
+        .unwrap();
+    let elf = normalized.meta[0].as_elf().unwrap();
+    let Some(build_id) = &elf.build_id else {
+        // The dynamic linker does not have a build ID on this system.
+        return
+    };
+
+    let dir = tempdir().unwrap();
+    let hex = |bytes: &[u8]| bytes.iter().map(|b| format!("{b:02x}")).collect::<String>();
+    let build_id_dir = dir.path().join(".build-id").join(hex(&build_id[..1]));
+    let () = create_dir_all(&build_id_dir).unwrap();
+    let _cnt = copy(
+        &elf.path,
+        build_id_dir.join(format!("{}.debug", hex(&build_id[1..]))),
+    )
+    .unwrap();
+
+    let symbolizer = symbolize::Symbolizer::new();
+    let store = symbolize::SymbolStore {
+        build_id_dirs: vec![dir.path().to_path_buf()],
+        recorded_paths: false,
+        ..Default::default()
+    };
+    let results = symbolizer
+        .symbolize_normalized(&normalized, &store)
+        .unwrap();
+    assert_eq!(results.len(), 1);
+    let sym = results[0].as_sym().unwrap();
+    assert!(sym.name.ends_with("__tls_get_addr"), "{sym:?}");
+}
+
 /// Check that we can normalize user addresses in our own shared object.
 #[test]
 fn normalize_custom_so() {
@@ -1,3 +1,4 @@
+use std::borrow::Cow;
 use std::env;
 use std::ffi::OsStr;
 use std::fs::copy;
@@ -9,35 +10,56 @@ use std::fs::File;
 use std::io;
 use std::io::Read as _;
 use std::io::Write as _;
+#[cfg(linux)]
+use std::os::fd::FromRawFd as _;
+#[cfg(linux)]
+use std::os::fd::OwnedFd;
 use std::path::Path;
 use std::path::PathBuf;
 use std::process;
 
+use blazesym::__private::find_the_answer_fn_in_zip;
+#[cfg(feature = "gsym")]
+use blazesym::gsym;
 use blazesym::helper::ElfResolver;
 use blazesym::inspect;
 use blazesym::normalize;
 use blazesym::symbolize::cache;
+use blazesym::symbolize::source::BaseAddr;
 use blazesym::symbolize::source::Breakpad;
 use blazesym::symbolize::source::Elf;
 use blazesym::symbolize::source::GsymData;
 use blazesym::symbolize::source::GsymFile;
 use blazesym::symbolize::source::Kernel;
+use blazesym::symbolize::source::MachO;
+use blazesym::symbolize::source::Pe;
 use blazesym::symbolize::source::Process;
+use blazesym::symbolize::source::ProcessSnapshot;
+use blazesym::symbolize::source::SnapshotOpts;
 use blazesym::symbolize::source::Source;
+use blazesym::symbolize::AddrClass;
+use blazesym::symbolize::AddrKind;
 use blazesym::symbolize::ApkDispatch;
 use blazesym::symbolize::ApkMemberInfo;
 use blazesym::symbolize::FindSymOpts;
+use blazesym::symbolize::InlinedFn;
 use blazesym::symbolize::Input;
+use blazesym::symbolize::Perm;
 use blazesym::symbolize::ProcessDispatch;
 use blazesym::symbolize::ProcessMemberInfo;
 use blazesym::symbolize::ProcessMemberType;
 use blazesym::symbolize::Reason;
 use blazesym::symbolize::Resolve;
 use blazesym::symbolize::ResolvedSym;
+use blazesym::symbolize::SharedMemInfo;
+use blazesym::symbolize::SrcLang;
 use blazesym::symbolize::Symbolize;
 use blazesym::symbolize::Symbolized;
 use blazesym::symbolize::Symbolizer;
 use blazesym::symbolize::TranslateFileOffset;
+use blazesym::symbolize::UnknownAddrInfo;
+use blazesym::test_utils::Class;
+use blazesym::test_utils::ElfBuilder;
 use blazesym::Addr;
 use blazesym::Error;
 use blazesym::ErrorKind;
@@ -45,7 +67,7 @@ use blazesym::MaybeDefault;
 use blazesym::Mmap;
 use blazesym::Pid;
 use blazesym::Result;
-use blazesym::__private::find_the_answer_fn_in_zip;
+use blazesym::SymType;
 
 #[cfg(linux)]
 use blazesym_dev::with_bpf_symbolization_target_addrs;
@@ -163,6 +185,9 @@ fn symbolize_elf_dwarf_gsym() {
         "test-stable-addrs-compressed-debug-zlib.bin",
         #[cfg(feature = "zstd")]
         "test-stable-addrs-compressed-debug-zstd.bin",
+        "test-stable-addrs-dwarf5.bin",
+        "test-stable-addrs-split-dwarf.bin",
+        "test-stable-addrs-dwp.bin",
     ] {
         let path = Path::new(&env!("CARGO_MANIFEST_DIR"))
             .join("data")
@@ -180,9 +205,52 @@ fn symbolize_elf_dwarf_gsym() {
     let data = read_file(&path).unwrap();
     let src = Source::from(GsymData::new(&data));
     test(src, true);
+
+    #[cfg(feature = "zstd")]
+    {
+        let path = Path::new(&env!("CARGO_MANIFEST_DIR"))
+            .join("data")
+            .join("test-stable-addrs.gsym.zst");
+        let src = Source::from(GsymFile::new(path));
+        test(src, true);
+    }
 }
 
 
+/// Check that we report only symbol names when asked to.
+#[tag(other_os)]
+#[test]
+fn symbolize_names_only() {
+    let path = Path::new(&env!("CARGO_MANIFEST_DIR"))
+        .join("data")
+        .join("test-stable-addrs.bin");
+    let src = Source::Elf(Elf::new(path));
+    let symbolizer = Symbolizer::builder().enable_inlined_fns(true).build();
+
+    let results = symbolizer
+        .symbolize_names_only(&src, Input::VirtOffset(&[0x2000200, 0x2000201]))
+        .unwrap();
+    assert_eq!(results.len(), 2);
+    for (offset, symbolized) in results.into_iter().enumerate() {
+        let sym = symbolized.into_sym().unwrap();
+        assert_eq!(sym.name, "factorial");
+        assert_eq!(sym.addr, 0x2000200);
+        assert_eq!(sym.offset, offset);
+        assert_eq!(sym.code_info, None);
+        assert!(sym.inlined.is_empty());
+    }
+
+    // Regular symbolization should still report source code
+    // information.
+    let sym = symbolizer
+        .symbolize_single(&src, Input::VirtOffset(0x2000200))
+        .unwrap()
+        .into_sym()
+        .unwrap();
+    assert_eq!(sym.name, "factorial");
+    assert_eq!(sym.code_info.as_ref().unwrap().line, Some(10));
+}
+
 fn symbolize_no_permission_impl(path: &Path) {
     let src = Source::Elf(Elf::new(path));
     let symbolizer = Symbolizer::new();
@@ -344,6 +412,121 @@ FUNC 34 11 0 factorial_wrapper
     assert!(format!("{err:?}").contains("34 XXX-this-does-not-belong-here-XXX 4 0"));
 }
 
+/// Check that we can symbolize addresses in a PE file using its PDB
+/// file.
+#[test]
+fn symbolize_pe() {
+    let path = Path::new(&env!("CARGO_MANIFEST_DIR"))
+        .join("data")
+        .join("test-pe.exe");
+    let inspector = inspect::Inspector::new();
+    let src = inspect::source::Source::from(inspect::source::Pe::new(&path));
+    let results = inspector
+        .lookup(&src, &["add"])
+        .unwrap()
+        .into_iter()
+        .flatten()
+        .collect::<Vec<_>>();
+    assert_eq!(results.len(), 1);
+    let add = &results[0];
+    let file_offset = add.file_offset.unwrap();
+
+    let src = Source::from(Pe::new(&path));
+    let symbolizer = Symbolizer::new();
+    for input in [
+        Input::VirtOffset(add.addr + 1),
+        Input::FileOffset(file_offset + 1),
+    ] {
+        let result = symbolizer
+            .symbolize_single(&src, input)
+            .unwrap()
+            .into_sym()
+            .unwrap();
+        assert_eq!(result.name, "add");
+        assert_eq!(result.addr, add.addr);
+        assert_eq!(result.offset, 1);
+        assert_eq!(result.size, add.size);
+
+        let code_info = result.code_info.as_ref().unwrap();
+        assert_eq!(code_info.file, OsStr::new("test-pe.c"));
+        assert_eq!(code_info.line, Some(2));
+    }
+
+    let err = symbolizer
+        .symbolize_single(&src, Input::AbsAddr(add.addr))
+        .unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::Unsupported);
+
+    // A PDB file not matching the PE file should be rejected.
+    let mut pe = Pe::new(&path);
+    pe.pdb = Some(path.with_file_name("test-pe-other.pdb"));
+    let src = Source::from(pe);
+    let err = Symbolizer::new()
+        .symbolize_single(&src, Input::VirtOffset(add.addr))
+        .unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidInput);
+}
+
+/// Check that we can symbolize addresses in a Mach-O file using its
+/// dSYM bundle.
+#[test]
+fn symbolize_macho() {
+    let path = Path::new(&env!("CARGO_MANIFEST_DIR"))
+        .join("data")
+        .join("test-macho");
+    let inspector = inspect::Inspector::new();
+    let src = inspect::source::Source::from(inspect::source::MachO::new(&path));
+    let results = inspector
+        .lookup(&src, &["add"])
+        .unwrap()
+        .into_iter()
+        .flatten()
+        .collect::<Vec<_>>();
+    assert_eq!(results.len(), 1);
+    let add = &results[0];
+    let file_offset = add.file_offset.unwrap();
+
+    let src = Source::from(MachO::new(&path));
+    let symbolizer = Symbolizer::new();
+    for input in [
+        Input::VirtOffset(add.addr + 2),
+        Input::FileOffset(file_offset + 2),
+    ] {
+        let result = symbolizer
+            .symbolize_single(&src, input)
+            .unwrap()
+            .into_sym()
+            .unwrap();
+        assert_eq!(result.name, "add");
+        assert_eq!(result.addr, add.addr);
+        assert_eq!(result.offset, 2);
+
+        let code_info = result.code_info.as_ref().unwrap();
+        assert_eq!(code_info.file, OsStr::new("test-macho.c"));
+        assert_eq!(code_info.line, Some(2));
+    }
+
+    let err = symbolizer
+        .symbolize_single(&src, Input::AbsAddr(add.addr))
+        .unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::Unsupported);
+
+    // A dSYM file not matching the Mach-O file should be rejected.
+    let mut macho = MachO::new(path.with_file_name("test-macho-arm64"));
+    macho.dsym = Some(
+        path.with_file_name("test-macho.dSYM")
+            .join("Contents")
+            .join("Resources")
+            .join("DWARF")
+            .join("test-macho"),
+    );
+    let src = Source::from(macho);
+    let err = Symbolizer::new()
+        .symbolize_single(&src, Input::VirtOffset(add.addr))
+        .unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidInput);
+}
+
 /// Check that we can symbolize an address mapping to a variable in an
 /// ELF file.
 #[tag(other_os)]
@@ -416,6 +599,423 @@ fn symbolize_elf_stripped() {
     assert_eq!(result, Symbolized::Unknown(Reason::MissingSyms));
 }
 
+/// Check that we can symbolize addresses in synthetically generated
+/// 32 and 64 bit ELF files.
+#[tag(other_os)]
+#[test]
+fn symbolize_elf_synthetic() {
+    fn test(class: Class) {
+        let mut builder = ElfBuilder::new();
+        let _builder = builder.set_class(class);
+        let text = builder.add_section(".text", 0x4000, vec![0xcc; 0x100]);
+        let () = builder.add_symbol("first", text, 0x4000, 0x20, SymType::Function);
+        let () = builder.add_symbol("second", text, 0x4020, 0x80, SymType::Function);
+
+        let mut file = NamedTempFile::new().unwrap();
+        let () = file.write_all(&builder.build()).unwrap();
+
+        let src = Source::Elf(Elf::new(file.path()));
+        let symbolizer = Symbolizer::new();
+        let results = symbolizer
+            .symbolize(&src, Input::VirtOffset(&[0x4000, 0x4042, 0x40a0]))
+            .unwrap();
+        let sym = results[0].as_sym().unwrap();
+        assert_eq!(sym.name, "first");
+        assert_eq!(sym.offset, 0);
+        let sym = results[1].as_sym().unwrap();
+        assert_eq!(sym.name, "second");
+        assert_eq!(sym.offset, 0x22);
+        assert_eq!(sym.size, Some(0x80));
+        assert_eq!(results[2], Symbolized::Unknown(Reason::UnknownAddr));
+
+        let results = symbolizer
+            .symbolize(&src, Input::FileOffset(&[0x10]))
+            .unwrap();
+        assert_eq!(results[0], Symbolized::Unknown(Reason::InvalidFileOffset));
+    }
+
+    test(Class::Elf32);
+    test(Class::Elf64);
+}
+
+/// Check that we report uniform frames for symbolized and
+/// unsymbolized addresses alike.
+#[test]
+fn symbolize_frames() {
+    let mut builder = ElfBuilder::new();
+    let text = builder.add_section(".text", 0x4000, vec![0xcc; 0x100]);
+    let () = builder.add_symbol("first", text, 0x4000, 0x20, SymType::Function);
+
+    let mut file = NamedTempFile::new().unwrap();
+    let () = file.write_all(&builder.build()).unwrap();
+
+    let src = Source::Elf(Elf::new(file.path()));
+    let symbolizer = Symbolizer::new();
+    let frames = symbolizer
+        .symbolize_frames(&src, Input::VirtOffset(&[0x4010, 0x40a0]))
+        .unwrap();
+    assert_eq!(frames.len(), 2);
+
+    let frame = &frames[0];
+    assert_eq!(frame.addr, 0x4010);
+    assert_eq!(frame.module.as_deref(), Some(file.path()));
+    assert_eq!(frame.sym.as_ref().unwrap().name, "first");
+    assert_eq!(frame.reason, None);
+    let norm_addr = frame.norm_addr.unwrap();
+
+    let frame = &frames[1];
+    assert_eq!(frame.addr, 0x40a0);
+    assert_eq!(frame.module.as_deref(), Some(file.path()));
+    assert_eq!(frame.sym, None);
+    assert_eq!(frame.reason, Some(Reason::UnknownAddr));
+
+    // The normalized address can be used for symbolizing the same
+    // address again.
+    let frames = symbolizer
+        .symbolize_frames(&src, Input::FileOffset(&[norm_addr]))
+        .unwrap();
+    assert_eq!(frames[0].norm_addr, Some(norm_addr));
+    assert_eq!(frames[0].sym.as_ref().unwrap().name, "first");
+
+    let src = Source::Process(Process::new(Pid::Slf));
+    let addrs = [symbolize_frames as Addr, 0x0];
+    let frames = symbolizer
+        .symbolize_frames(&src, Input::AbsAddr(&addrs))
+        .unwrap();
+    let frame = &frames[0];
+    assert_eq!(frame.addr, addrs[0]);
+    assert!(frame.module.is_some(), "{frame:?}");
+    assert!(frame.norm_addr.is_some(), "{frame:?}");
+    assert!(frame.sym.is_some(), "{frame:?}");
+
+    let frame = &frames[1];
+    assert_eq!(frame.addr, 0x0);
+    assert_eq!(frame.module, None);
+    assert_eq!(frame.norm_addr, None);
+    assert_eq!(frame.reason, Some(Reason::Unmapped));
+}
+
+/// Check that we can explicitly preload and prune cached symbolization
+/// data.
+#[test]
+fn preload_and_prune_cache() {
+    let mut builder = ElfBuilder::new();
+    let text = builder.add_section(".text", 0x4000, vec![0xcc; 0x100]);
+    let () = builder.add_symbol("first", text, 0x4000, 0x20, SymType::Function);
+
+    let mut file = NamedTempFile::new().unwrap();
+    let () = file.write_all(&builder.build()).unwrap();
+
+    let src = Source::Elf(Elf::new(file.path()));
+    let mut symbolizer = Symbolizer::new();
+    let () = symbolizer.preload(&src).unwrap();
+    let results = symbolizer
+        .symbolize(&src, Input::VirtOffset(&[0x4010]))
+        .unwrap();
+    assert_eq!(results[0].as_sym().unwrap().name, "first");
+
+    assert!(symbolizer.prune_cache(file.path()));
+    assert!(!symbolizer.prune_cache(file.path()));
+
+    let src = Source::Elf(Elf::new(file.path().with_extension("xxx")));
+    let err = symbolizer.preload(&src).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::NotFound);
+
+    let src = Source::from(GsymData::new(&[]));
+    let err = symbolizer.preload(&src).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::Unsupported);
+
+    let src = Source::Process(Process::new(Pid::Slf));
+    let () = symbolizer.preload(&src).unwrap();
+    let addrs = [preload_and_prune_cache as Addr];
+    let results = symbolizer.symbolize(&src, Input::AbsAddr(&addrs)).unwrap();
+    let sym = results[0].as_sym().unwrap();
+    assert!(sym.name.contains("preload_and_prune_cache"), "{sym:?}");
+}
+
+/// Check that we can probe a symbolization source for the information
+/// it provides.
+#[test]
+fn probe_source() {
+    let mut builder = ElfBuilder::new();
+    let text = builder.add_section(".text", 0x4000, vec![0xcc; 0x100]);
+    let () = builder.add_symbol("first", text, 0x4000, 0x20, SymType::Function);
+    let () = builder.add_symbol("second", text, 0x4040, 0x10, SymType::Function);
+    // NT_GNU_BUILD_ID
+    let () = builder.add_note("GNU", 3, vec![0xab, 0xcd]);
+
+    let mut file = NamedTempFile::new().unwrap();
+    let () = file.write_all(&builder.build()).unwrap();
+
+    let src = Source::Elf(Elf::new(file.path()));
+    let symbolizer = Symbolizer::new();
+    let info = symbolizer.probe(&src).unwrap();
+    assert!(info.syms);
+    assert!(!info.code_info);
+    assert!(!info.inlined_fns);
+    assert_eq!(info.build_id, Some(vec![0xab, 0xcd]));
+    assert_eq!(info.addr_range, Some(0x4000..0x4050));
+
+    // A "stripped" binary.
+    let mut builder = ElfBuilder::new();
+    let _text = builder.add_section(".text", 0x4000, vec![0xcc; 0x100]);
+
+    let mut file = NamedTempFile::new().unwrap();
+    let () = file.write_all(&builder.build()).unwrap();
+
+    let src = Source::Elf(Elf::new(file.path()));
+    let info = symbolizer.probe(&src).unwrap();
+    assert!(!info.syms);
+    assert_eq!(info.build_id, None);
+    assert_eq!(info.addr_range, None);
+
+    let src = Source::Process(Process::new(Pid::Slf));
+    let err = symbolizer.probe(&src).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::Unsupported);
+}
+
+/// Check that the number of files kept open by a `Symbolizer` stays
+/// within the configured budget.
+#[test]
+fn symbolize_with_max_open_files() {
+    let files = ["first", "second", "third"]
+        .into_iter()
+        .map(|name| {
+            let mut builder = ElfBuilder::new();
+            let text = builder.add_section(".text", 0x4000, vec![0xcc; 0x100]);
+            let () = builder.add_symbol(name, text, 0x4000, 0x20, SymType::Function);
+            let mut file = NamedTempFile::new().unwrap();
+            let () = file.write_all(&builder.build()).unwrap();
+            (name, file)
+        })
+        .collect::<Vec<_>>();
+
+    let symbolizer = Symbolizer::builder().set_max_open_files(Some(2)).build();
+    let usage = symbolizer.fd_usage();
+    assert_eq!(usage.open, 0);
+    assert_eq!(usage.max_open, Some(2));
+
+    for _ in 0..2 {
+        for (name, file) in &files {
+            let src = Source::Elf(Elf::new(file.path()));
+            let sym = symbolizer
+                .symbolize_single(&src, Input::VirtOffset(0x4010))
+                .unwrap()
+                .into_sym()
+                .unwrap();
+            assert_eq!(sym.name, *name);
+            assert!(symbolizer.fd_usage().open <= 2);
+        }
+    }
+
+    let usage = symbolizer.fd_usage();
+    assert_eq!(usage.open, 2);
+    assert_eq!(usage.closed, 1);
+}
+
+/// Check that we can symbolize addresses in a BOLT optimized ELF file
+/// in terms of the original file.
+#[tag(other_os)]
+#[test]
+fn symbolize_elf_bolt_original() {
+    let mut builder = ElfBuilder::new();
+    let text = builder.add_section(".text", 0x1000, vec![0xcc; 0x100]);
+    let () = builder.add_symbol("func", text, 0x1000, 0x100, SymType::Function);
+    let mut original = NamedTempFile::new().unwrap();
+    let () = original.write_all(&builder.build()).unwrap();
+
+    // BOLT address translation data moving `func` to 0x5000, with the
+    // instruction at output offset 0x10 stemming from input offset
+    // 0x40.
+    let mut bat = Vec::new();
+    for word in [5u32, 36, 1] {
+        let () = bat.extend_from_slice(&word.to_ne_bytes());
+    }
+    let () = bat.extend_from_slice(b"BOLT\0\0\0\0");
+    let () = bat.extend_from_slice(&1u32.to_ne_bytes());
+    let () = bat.extend_from_slice(&0x5000u64.to_ne_bytes());
+    for word in [2u32, 0x0, 0x0, 0x10, 0x40, 0] {
+        let () = bat.extend_from_slice(&word.to_ne_bytes());
+    }
+
+    let mut builder = ElfBuilder::new();
+    let text = builder.add_section(".text", 0x5000, vec![0xcc; 0x100]);
+    let _bat = builder.add_section(".note.bolt_bat", 0, bat);
+    let () = builder.add_symbol("func", text, 0x5000, 0x80, SymType::Function);
+    let mut bolted = NamedTempFile::new().unwrap();
+    let () = bolted.write_all(&builder.build()).unwrap();
+
+    let symbolizer = Symbolizer::new();
+    let src = Source::Elf(Elf {
+        bolt_original: Some(original.path().to_path_buf()),
+        ..Elf::new(bolted.path())
+    });
+    let results = symbolizer
+        .symbolize(&src, Input::VirtOffset(&[0x5014, 0x5004]))
+        .unwrap();
+    let sym = results[0].as_sym().unwrap();
+    assert_eq!(sym.name, "func");
+    assert_eq!(sym.addr, 0x1000);
+    assert_eq!(sym.offset, 0x44);
+    let sym = results[1].as_sym().unwrap();
+    assert_eq!(sym.offset, 0x4);
+
+    // Without BOLT address translation data we report an error.
+    let src = Source::Elf(Elf {
+        bolt_original: Some(original.path().to_path_buf()),
+        ..Elf::new(original.path())
+    });
+    let err = symbolizer
+        .symbolize(&src, Input::VirtOffset(&[0x1000]))
+        .unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidInput);
+}
+
+/// Check that we can symbolize absolute addresses in an ELF file when
+/// providing a base address.
+#[tag(other_os)]
+#[test]
+fn symbolize_elf_abs_addr() {
+    let path = Path::new(&env!("CARGO_MANIFEST_DIR"))
+        .join("data")
+        .join("libtest-so.so");
+    let src = inspect::source::Source::Elf(inspect::source::Elf::new(&path));
+    let inspector = inspect::Inspector::new();
+    let results = inspector
+        .lookup(&src, &["the_answer"])
+        .unwrap()
+        .into_iter()
+        .flatten()
+        .collect::<Vec<_>>();
+    assert_eq!(results.len(), 1);
+    let the_answer = &results[0];
+
+    let base = 0x7f0000000000;
+    let symbolizer = Symbolizer::new();
+
+    let base_addrs = [
+        BaseAddr::Addr(base),
+        BaseAddr::Mapping {
+            addr: base + the_answer.addr,
+            file_offset: the_answer.file_offset.unwrap(),
+        },
+        BaseAddr::Symbol {
+            name: "the_answer".to_string(),
+            addr: base + the_answer.addr,
+        },
+    ];
+    for base_addr in base_addrs {
+        let mut elf = Elf::new(&path);
+        elf.base_addr = Some(base_addr);
+        let src = Source::Elf(elf);
+        let result = symbolizer
+            .symbolize_single(&src, Input::AbsAddr(base + the_answer.addr))
+            .unwrap()
+            .into_sym()
+            .unwrap();
+        assert_eq!(result.name, "the_answer");
+        assert_eq!(result.addr, the_answer.addr);
+        assert_eq!(result.offset, 0);
+        assert_eq!(result.module.as_deref(), Some(path.as_path()));
+        assert_eq!(result.file_offset, the_answer.file_offset);
+        assert_eq!(
+            result.module_base,
+            Some(base + the_answer.addr - the_answer.file_offset.unwrap())
+        );
+    }
+
+    // Without a base address, absolute addresses are not supported.
+    let src = Source::Elf(Elf::new(&path));
+    let err = symbolizer
+        .symbolize_single(&src, Input::AbsAddr(base + the_answer.addr))
+        .unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::Unsupported);
+
+    // A base address that is not page aligned can't be correct for a
+    // shared object.
+    let mut elf = Elf::new(&path);
+    elf.base_addr = Some(BaseAddr::Addr(base + 1));
+    let src = Source::Elf(elf);
+    let err = symbolizer
+        .symbolize_single(&src, Input::AbsAddr(base + the_answer.addr))
+        .unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidInput);
+
+    // Unknown symbols can't be used for deriving the base address.
+    let mut elf = Elf::new(&path);
+    elf.base_addr = Some(BaseAddr::Symbol {
+        name: "does_not_exist".to_string(),
+        addr: base,
+    });
+    let src = Source::Elf(elf);
+    let err = symbolizer
+        .symbolize_single(&src, Input::AbsAddr(base + the_answer.addr))
+        .unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::NotFound);
+
+    // The file is not mapped into our process.
+    let mut elf = Elf::new(&path);
+    elf.base_addr = Some(BaseAddr::Process(Pid::Slf));
+    let src = Source::Elf(elf);
+    let err = symbolizer
+        .symbolize_single(&src, Input::AbsAddr(base + the_answer.addr))
+        .unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::NotFound);
+
+    // A non-relocatable binary is always loaded at the addresses it
+    // was linked for, so its base address has to be 0.
+    let path = Path::new(&env!("CARGO_MANIFEST_DIR"))
+        .join("data")
+        .join("test-stable-addrs.bin");
+    let mut elf = Elf::new(&path);
+    elf.base_addr = Some(BaseAddr::Addr(0));
+    let src = Source::Elf(elf);
+    let result = symbolizer
+        .symbolize_single(&src, Input::AbsAddr(0x2000200))
+        .unwrap()
+        .into_sym()
+        .unwrap();
+    assert_eq!(result.name, "factorial");
+    assert_eq!(result.addr, 0x2000200);
+
+    let mut elf = Elf::new(&path);
+    elf.base_addr = Some(BaseAddr::Addr(base));
+    let src = Source::Elf(elf);
+    let err = symbolizer
+        .symbolize_single(&src, Input::AbsAddr(base + 0x2000200))
+        .unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidInput);
+    assert!(format!("{err:#}").contains("use 0 instead"), "{err:#}");
+}
+
+/// Check that we can derive the base address of an ELF file from the
+/// memory mappings of a process.
+#[test]
+fn symbolize_elf_abs_addr_process_base() {
+    let path = env::current_exe().unwrap();
+    let addr = symbolize_elf_abs_addr_process_base as Addr;
+
+    let symbolizer = Symbolizer::new();
+    let src = Source::Process(Process::new(Pid::Slf));
+    let expected = symbolizer
+        .symbolize_single(&src, Input::AbsAddr(addr))
+        .unwrap()
+        .into_sym()
+        .unwrap();
+
+    let mut elf = Elf::new(&path);
+    elf.base_addr = Some(BaseAddr::Process(Pid::Slf));
+    let src = Source::Elf(elf);
+    let result = symbolizer
+        .symbolize_single(&src, Input::AbsAddr(addr))
+        .unwrap()
+        .into_sym()
+        .unwrap();
+    assert_eq!(result.name, expected.name);
+    assert_eq!(result.addr, expected.addr);
+    assert_eq!(result.offset, expected.offset);
+}
+
 /// Make sure that we report (enabled) or don't report (disabled) inlined
 /// functions with DWARF and Gsym sources.
 #[tag(other_os)]
@@ -468,12 +1068,26 @@ fn symbolize_dwarf_gsym_inlined() {
     test(src.clone(), true);
     test(src, false);
 
+    #[cfg(feature = "gsym")]
+    {
+        let path = Path::new(&env!("CARGO_MANIFEST_DIR"))
+            .join("data")
+            .join("test-stable-addrs.bin");
+        let data = gsym::Builder::new().build(&path).unwrap();
+        let src = Source::from(GsymData::new(&data));
+        test(src.clone(), true);
+        test(src, false);
+    }
+
     for file in [
         "test-stable-addrs-stripped-elf-with-dwarf.bin",
         "test-stable-addrs-stripped-with-link.bin",
         "test-stable-addrs-compressed-debug-zlib.bin",
         #[cfg(feature = "zstd")]
         "test-stable-addrs-compressed-debug-zstd.bin",
+        "test-stable-addrs-dwarf5.bin",
+        "test-stable-addrs-split-dwarf.bin",
+        "test-stable-addrs-dwp.bin",
     ] {
         let path = Path::new(&env!("CARGO_MANIFEST_DIR"))
             .join("data")
@@ -736,24 +1350,93 @@ fn symbolize_dwarf_demangle() {
     panic!("failed to find inlined function call");
 }
 
-/// Check that we can symbolize addresses inside our own process.
+/// Check that we can report fully qualified function names based on
+/// DWARF scope information.
 #[test]
-fn symbolize_process() {
-    let src = Source::Process(Process::new(Pid::Slf));
-    let addrs = [symbolize_process as Addr, Symbolizer::symbolize as Addr];
-    let symbolizer = Symbolizer::new();
-    let results = symbolizer
-        .symbolize(&src, Input::AbsAddr(&addrs))
+fn symbolize_dwarf_qualified_names() {
+    let test_cpp = Path::new(&env!("CARGO_MANIFEST_DIR"))
+        .join("data")
+        .join("test-cpp.bin");
+    let elf = inspect::source::Elf::new(&test_cpp);
+    let src = inspect::source::Source::Elf(elf);
+    let inspector = inspect::Inspector::new();
+    let names = [
+        "_ZN5outer5inner5Class6methodEi",
+        "_ZN5outer12_GLOBAL__N_16hiddenEi",
+        "_ZN5outer4funcEi",
+        "main",
+    ];
+    let addrs = inspector
+        .lookup(&src, &names)
         .unwrap()
         .into_iter()
+        .map(|syms| syms[0].addr)
         .collect::<Vec<_>>();
-    assert_eq!(results.len(), 2);
-
-    let result = results[0].as_sym().unwrap();
-    assert!(result.name.contains("symbolize_process"), "{result:x?}");
 
-    let result = results[1].as_sym().unwrap();
-    // It's not entirely clear why we have seen two different demangled
+    let src = Source::Elf(Elf::new(&test_cpp));
+    let symbolizer = Symbolizer::builder()
+        .enable_demangling(false)
+        .enable_qualified_names(true)
+        .build();
+    let results = symbolizer
+        .symbolize(&src, Input::VirtOffset(&addrs))
+        .unwrap()
+        .into_iter()
+        .map(|sym| sym.into_sym().unwrap().name.into_owned())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        results,
+        vec![
+            "outer::inner::Class::method",
+            "outer::(anonymous namespace)::hidden",
+            "outer::func",
+            "main",
+        ]
+    );
+
+    // Without the option we should see linkage names, where present.
+    // Functions in anonymous namespaces may not have one and lack any
+    // context.
+    let symbolizer = Symbolizer::builder().enable_demangling(false).build();
+    let results = symbolizer
+        .symbolize(&src, Input::VirtOffset(&addrs))
+        .unwrap()
+        .into_iter()
+        .map(|sym| sym.into_sym().unwrap().name.into_owned())
+        .collect::<Vec<_>>();
+    assert_eq!(results[0], names[0]);
+    assert!(
+        results[1] == names[1] || results[1] == "hidden",
+        "{results:?}"
+    );
+    assert_eq!(results[2..], names[2..]);
+}
+
+/// Check that we can symbolize addresses inside our own process.
+#[test]
+fn symbolize_process() {
+    let src = Source::Process(Process::new(Pid::Slf));
+    let addrs = [symbolize_process as Addr, Symbolizer::symbolize as Addr];
+    let symbolizer = Symbolizer::new();
+    let results = symbolizer
+        .symbolize(&src, Input::AbsAddr(&addrs))
+        .unwrap()
+        .into_iter()
+        .collect::<Vec<_>>();
+    assert_eq!(results.len(), 2);
+
+    let result = results[0].as_sym().unwrap();
+    assert!(result.name.contains("symbolize_process"), "{result:x?}");
+    assert_eq!(result.section.as_deref(), Some(".text"));
+    assert_eq!(result.perm.map(|perm| perm & Perm::X), Some(Perm::X));
+    assert!(result.module.is_some(), "{result:x?}");
+    assert_eq!(
+        result.module_base.unwrap() + result.file_offset.unwrap(),
+        addrs[0]
+    );
+
+    let result = results[1].as_sym().unwrap();
+    // It's not entirely clear why we have seen two different demangled
     // symbols, but they both seem legit.
     assert!(
         result.name == "blazesym::symbolize::symbolizer::Symbolizer::symbolize"
@@ -763,6 +1446,314 @@ fn symbolize_process() {
     );
 }
 
+
+/// Check that we can symbolize addresses in the vDSO of a process and
+/// that they are reported as belonging to the `[vdso]` module.
+#[test]
+fn symbolize_process_vdso() {
+    let base = unsafe { libc::getauxval(libc::AT_SYSINFO_EHDR) } as Addr;
+    if base == 0 {
+        // No vDSO present on this system.
+        return
+    }
+
+    // We don't know where exactly functions are located, so just
+    // symbolize the entire first two pages of the vDSO.
+    let addrs = (base..base + 0x2000).step_by(4).collect::<Vec<_>>();
+    let src = Source::Process(Process::new(Pid::Slf));
+    let symbolizer = Symbolizer::new();
+    let results = symbolizer.symbolize(&src, Input::AbsAddr(&addrs)).unwrap();
+    assert_eq!(results.len(), addrs.len());
+
+    let syms = results
+        .iter()
+        .filter_map(Symbolized::as_sym)
+        .collect::<Vec<_>>();
+    assert!(
+        syms.iter().any(|sym| sym.name.contains("clock_gettime")),
+        "{syms:#x?}"
+    );
+
+    for sym in syms {
+        assert_eq!(sym.module.as_deref(), Some(Path::new("[vdso]")));
+        assert_eq!(sym.module_base, Some(base));
+    }
+}
+
+
+/// Check that symbol names are shared among results when name
+/// interning is enabled.
+#[test]
+fn symbolize_name_interning() {
+    let src = Source::Process(Process::new(Pid::Slf));
+    let addr = Symbolizer::symbolize as Addr;
+
+    // The name is demangled and, hence, owned by default.
+    let symbolizer = Symbolizer::new();
+    let result = symbolizer
+        .symbolize_single(&src, Input::AbsAddr(addr))
+        .unwrap()
+        .into_sym()
+        .unwrap();
+    assert!(matches!(result.name, Cow::Owned(..)));
+
+    let symbolizer1 = Symbolizer::builder().enable_name_interning(true).build();
+    let result1 = symbolizer1
+        .symbolize_single(&src, Input::AbsAddr(addr))
+        .unwrap()
+        .into_sym()
+        .unwrap();
+    let symbolizer2 = Symbolizer::builder().enable_name_interning(true).build();
+    let result2 = symbolizer2
+        .symbolize_single(&src, Input::AbsAddr(addr))
+        .unwrap()
+        .into_sym()
+        .unwrap();
+
+    assert_eq!(result1.name, result.name);
+    assert!(matches!(result1.name, Cow::Borrowed(..)));
+    assert_eq!(result1.name.as_ptr(), result2.name.as_ptr());
+}
+
+/// Check that asynchronous symbolization reports the same results as
+/// its synchronous counterpart.
+#[cfg(feature = "async")]
+#[test]
+fn symbolize_async() {
+    let test_elf = Path::new(&env!("CARGO_MANIFEST_DIR"))
+        .join("data")
+        .join("test-stable-addrs.bin");
+    let elf_src = Source::Elf(Elf::new(test_elf));
+    let elf_addrs = [0x2000200, 0x2000100];
+    let process_src = Source::Process(Process::new(Pid::Slf));
+    let process_addrs = [symbolize_async as Addr, Symbolizer::symbolize as Addr];
+
+    for (src, addrs) in [(elf_src, &elf_addrs), (process_src, &process_addrs)] {
+        let symbolizer = Symbolizer::new();
+        let expected = symbolizer.symbolize(&src, Input::AbsAddr(addrs)).unwrap();
+
+        let symbolizer = Symbolizer::new();
+        let future = symbolizer.symbolize_async(&src, Input::AbsAddr(addrs));
+        let results = pollster::block_on(future).unwrap();
+        assert_eq!(results.len(), 2);
+        assert_eq!(results, expected);
+        assert!(results.iter().all(|result| result.as_sym().is_some()));
+    }
+
+    let symbolizer = Symbolizer::new();
+    let src = Source::Process(Process::new(Pid::Slf));
+    let future = symbolizer.symbolize_async(&src, Input::VirtOffset(&[0x0]));
+    let err = pollster::block_on(future).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::Unsupported);
+}
+
+/// Check that parallel symbolization of a large batch of addresses
+/// reports the same results as serial symbolization.
+#[test]
+fn symbolize_process_parallel() {
+    let heap = vec![0u8; 4096];
+    let addrs = [
+        symbolize_process_parallel as Addr,
+        Symbolizer::symbolize as Addr,
+        libc::getpid as Addr,
+        heap.as_ptr() as Addr,
+    ]
+    .iter()
+    .copied()
+    .cycle()
+    .take(4 * 1024)
+    .collect::<Vec<_>>();
+    let src = Source::Process(Process::new(Pid::Slf));
+
+    let symbolizer = Symbolizer::new();
+    let expected = symbolizer.symbolize(&src, Input::AbsAddr(&addrs)).unwrap();
+
+    let symbolizer = Symbolizer::builder().set_parallelism(3).build();
+    for _ in 0..2 {
+        let results = symbolizer.symbolize(&src, Input::AbsAddr(&addrs)).unwrap();
+        assert_eq!(results, expected);
+    }
+}
+
+/// Check that we can classify addresses of our own process based on
+/// cached VMAs.
+#[test]
+fn classify_process_addrs() {
+    let symbolizer = Symbolizer::new();
+    let addrs = [classify_process_addrs as Addr, 0x0];
+
+    // Without cached VMAs classification should fail.
+    let err = symbolizer.classify_addrs(&Pid::Slf, &addrs).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::NotFound);
+
+    let () = symbolizer
+        .cache(&cache::Cache::from(cache::Process::new(Pid::Slf)))
+        .unwrap();
+    let classes = symbolizer.classify_addrs(&Pid::Slf, &addrs).unwrap();
+    assert_eq!(
+        classes,
+        vec![
+            AddrClass::Symbolizable,
+            AddrClass::Unknown(Reason::Unmapped)
+        ]
+    );
+}
+
+/// Check that we report the expected statistics when symbolizing a
+/// batch of addresses in our own process.
+#[test]
+fn symbolize_process_with_stats() {
+    let src = Source::Process(Process::new(Pid::Slf));
+    let addrs = [
+        symbolize_process_with_stats as Addr,
+        0x0,
+        Symbolizer::symbolize as Addr,
+    ];
+    let symbolizer = Symbolizer::new();
+    let (results, stats) = symbolizer
+        .symbolize_with_stats(&src, Input::AbsAddr(&addrs))
+        .unwrap();
+    assert_eq!(results.len(), 3);
+    assert_eq!(stats.resolved, 2);
+    assert_eq!(stats.unknown.len(), 1);
+    assert_eq!(stats.unknown.get(&Reason::Unmapped), Some(&1));
+
+    let exe = env::current_exe().unwrap();
+    let module = stats.modules.get(&exe).unwrap();
+    assert_eq!(module.resolved, 2);
+    assert_eq!(module.unknown, 0);
+}
+
+/// Check that return addresses are adjusted as expected when
+/// symbolizing tagged addresses.
+#[test]
+fn symbolize_process_tagged() {
+    let src = Source::Process(Process::new(Pid::Slf));
+    let addr = symbolize_process_tagged as Addr;
+    let addrs = [
+        (addr, AddrKind::Ip),
+        (addr + 1, AddrKind::ReturnAddr),
+        (addr + 1, AddrKind::Ip),
+    ];
+    let symbolizer = Symbolizer::new();
+    let results = symbolizer
+        .symbolize_tagged(&src, Input::AbsAddr(&addrs))
+        .unwrap();
+    assert_eq!(results.len(), 3);
+
+    let result = results[0].as_sym().unwrap();
+    assert!(
+        result.name.contains("symbolize_process_tagged"),
+        "{result:x?}"
+    );
+    assert_eq!(result.offset, 0);
+    assert_eq!(result.addr_adjust, 0);
+
+    let result = results[1].as_sym().unwrap();
+    assert!(
+        result.name.contains("symbolize_process_tagged"),
+        "{result:x?}"
+    );
+    assert_eq!(result.offset, 0);
+    assert_eq!(result.addr_adjust, 1);
+
+    let result = results[2].as_sym().unwrap();
+    assert!(
+        result.name.contains("symbolize_process_tagged"),
+        "{result:x?}"
+    );
+    assert_eq!(result.offset, 1);
+    assert_eq!(result.addr_adjust, 0);
+}
+
+/// Check that we can symbolize addresses residing in the dynamic linker
+/// mapped into our own process.
+#[cfg(linux)]
+#[test]
+fn symbolize_process_dynamic_linker() {
+    // Both symbols are provided by the dynamic linker itself.
+    let names = ["__tls_get_addr", "_dl_debug_state"];
+    let (names, addrs) = names
+        .into_iter()
+        .filter_map(|name| {
+            let sym = format!("{name}\0");
+            let addr = unsafe { libc::dlsym(libc::RTLD_DEFAULT, sym.as_ptr().cast()) };
+            (!addr.is_null()).then_some((name, addr as Addr))
+        })
+        .unzip::<_, _, Vec<_>, Vec<_>>();
+    // `_dl_debug_state` is not exported by all C libraries, but
+    // `__tls_get_addr` should always be available.
+    assert!(!addrs.is_empty());
+
+    let src = Source::Process(Process::new(Pid::Slf));
+    let symbolizer = Symbolizer::new();
+    let results = symbolizer
+        .symbolize(&src, Input::AbsAddr(&addrs))
+        .unwrap()
+        .into_iter()
+        .collect::<Vec<_>>();
+    assert_eq!(results.len(), addrs.len());
+
+    for (result, name) in results.iter().zip(names) {
+        let sym = result.as_sym().unwrap();
+        assert!(sym.name.ends_with(name), "{sym:?}");
+        assert_eq!(sym.offset, 0);
+    }
+}
+
+/// Check that we can symbolize addresses inside our own process when
+/// referencing it via a pidfd.
+#[cfg(linux)]
+#[test]
+fn symbolize_process_pidfd() {
+    let fd = unsafe { libc::syscall(libc::SYS_pidfd_open, process::id(), 0) };
+    assert!(fd >= 0, "{}", io::Error::last_os_error());
+    let fd = unsafe { OwnedFd::from_raw_fd(fd as _) };
+
+    let src = Source::Process(Process::from_pidfd(fd).unwrap());
+    let symbolizer = Symbolizer::new();
+    let result = symbolizer
+        .symbolize_single(&src, Input::AbsAddr(symbolize_process_pidfd as Addr))
+        .unwrap()
+        .into_sym()
+        .unwrap();
+    assert!(
+        result.name.contains("symbolize_process_pidfd"),
+        "{result:x?}"
+    );
+}
+
+/// Check that symbolization fails when a pidfd references a process
+/// that has already exited.
+#[cfg(linux)]
+#[test]
+fn symbolize_process_pidfd_exited() {
+    let test_so = Path::new(&env!("CARGO_MANIFEST_DIR"))
+        .join("data")
+        .join("libtest-so.so");
+    let wait = Path::new(&env!("CARGO_MANIFEST_DIR"))
+        .join("data")
+        .join("test-wait.bin");
+
+    let (pid, addr) = RemoteProcess::default()
+        .arg(&test_so)
+        .exec(&wait, |pid, addr| {
+            let Pid::Pid(pid) = pid else { unreachable!() };
+            let fd = unsafe { libc::syscall(libc::SYS_pidfd_open, pid.get(), 0) };
+            assert!(fd >= 0, "{}", io::Error::last_os_error());
+            let fd = unsafe { OwnedFd::from_raw_fd(fd as _) };
+            (Pid::from_pidfd(fd).unwrap(), addr)
+        });
+
+    // The process has exited by now and so symbolization should fail,
+    // even if its ID was reused in the meantime.
+    let src = Source::Process(Process::new(pid));
+    let symbolizer = Symbolizer::new();
+    let _err = symbolizer
+        .symbolize_single(&src, Input::AbsAddr(addr))
+        .unwrap_err();
+}
+
 /// Check that we can symbolize an address in a process using a binary
 /// located in a local mount namespace.
 #[cfg(linux)]
@@ -828,6 +1819,41 @@ fn symbolize_process_exited_cached_vmas() {
     assert_eq!(result.name, "await_input");
 }
 
+/// Check that we can symbolize addresses from a process that has
+/// already exited, based on a snapshot captured earlier.
+#[cfg(linux)]
+#[test]
+fn symbolize_process_snapshot_exited() {
+    let test_so = Path::new(&env!("CARGO_MANIFEST_DIR"))
+        .join("data")
+        .join("libtest-so.so");
+    let wait = Path::new(&env!("CARGO_MANIFEST_DIR"))
+        .join("data")
+        .join("test-wait.bin");
+
+    let (snapshot, addr) = RemoteProcess::default()
+        .arg(&test_so)
+        .exec(&wait, |pid, addr| {
+            (ProcessSnapshot::capture(pid).unwrap(), addr)
+        });
+
+    let entry = snapshot
+        .entries()
+        .iter()
+        .find(|entry| entry.range.contains(&addr))
+        .unwrap();
+    assert!(entry.build_id.is_some(), "{entry:?}");
+
+    let src = Source::from(snapshot);
+    let symbolizer = Symbolizer::new();
+    let result = symbolizer
+        .symbolize_single(&src, Input::AbsAddr(addr))
+        .unwrap()
+        .into_sym()
+        .unwrap();
+    assert_eq!(result.name, "await_input");
+}
+
 /// Check that we can symbolize an address residing in a zip archive.
 #[test]
 fn symbolize_process_zip() {
@@ -852,6 +1878,136 @@ fn symbolize_process_zip() {
     assert_eq!(result.addr, sym.addr);
 }
 
+/// Check that we can symbolize an address residing in a file that has
+/// since been deleted, even when not using `map_files` paths.
+#[test]
+fn symbolize_process_deleted_file() {
+    let mut builder = ElfBuilder::new();
+    let text = builder.add_section(".text", 0x4000, vec![0xcc; 0x100]);
+    let () = builder.add_symbol("first", text, 0x4000, 0x20, SymType::Function);
+    let data = builder.build();
+    let text_off = data
+        .windows(0x100)
+        .position(|window| window.iter().all(|b| *b == 0xcc))
+        .unwrap();
+
+    let mut file = NamedTempFile::new().unwrap();
+    let () = file.write_all(&data).unwrap();
+    let mmap = Mmap::builder().open(file.path()).unwrap();
+    let () = file.close().unwrap();
+
+    let mut process = Process::new(Pid::Slf);
+    // Deleted files are accessed through `map_files` paths regardless
+    // of this setting.
+    process.map_files = false;
+    let src = Source::Process(process);
+    let addrs = [
+        mmap.as_ptr() as Addr + text_off as Addr + 0x10,
+        symbolize_process_deleted_file as Addr,
+    ];
+    let symbolizer = Symbolizer::new();
+    let results = symbolizer.symbolize(&src, Input::AbsAddr(&addrs)).unwrap();
+    assert_eq!(results.len(), 2);
+    let result = results[0].as_sym().unwrap();
+    assert_eq!(result.name, "first");
+    assert_eq!(result.addr, 0x4000);
+    assert_eq!(result.offset, 0x10);
+    let result = results[1].as_sym().unwrap();
+    assert!(
+        result.name.contains("symbolize_process_deleted_file"),
+        "{result:x?}"
+    );
+}
+
+/// Check that deleted files mapped into a process can be copied as
+/// part of a snapshot and are symbolized using the copy later on.
+#[test]
+fn symbolize_process_snapshot_deleted_file() {
+    let mut builder = ElfBuilder::new();
+    let text = builder.add_section(".text", 0x4000, vec![0xcc; 0x100]);
+    let () = builder.add_symbol("first", text, 0x4000, 0x20, SymType::Function);
+    let data = builder.build();
+    let text_off = data
+        .windows(0x100)
+        .position(|window| window.iter().all(|b| *b == 0xcc))
+        .unwrap();
+
+    let mut file = NamedTempFile::new().unwrap();
+    let () = file.write_all(&data).unwrap();
+    let mmap = Mmap::builder().open(file.path()).unwrap();
+    let () = file.close().unwrap();
+    let addr = mmap.as_ptr() as Addr + text_off as Addr + 0x10;
+
+    let dir = tempdir().unwrap();
+    let opts = SnapshotOpts {
+        copy_deleted: Some(dir.path().to_path_buf()),
+        ..Default::default()
+    };
+    let snapshot = ProcessSnapshot::capture_opts(Pid::Slf, &opts).unwrap();
+    // With the mapping gone, the file is only accessible through the
+    // copy made as part of the snapshot.
+    let () = drop(mmap);
+
+    let copy = snapshot
+        .entries()
+        .iter()
+        .find_map(|entry| match &entry.path_name {
+            Some(ProcessMemberType::Path(path)) if entry.range.contains(&addr) && path.deleted => {
+                Some(path.maps_file.clone())
+            }
+            _ => None,
+        })
+        .unwrap();
+    assert!(copy.starts_with(dir.path()), "{copy:?}");
+    assert_eq!(read_file(&copy).unwrap(), data);
+
+    let src = Source::from(snapshot);
+    let addrs = [addr, symbolize_process_snapshot_deleted_file as Addr];
+    let symbolizer = Symbolizer::new();
+    let results = symbolizer.symbolize(&src, Input::AbsAddr(&addrs)).unwrap();
+    assert_eq!(results.len(), 2);
+    let result = results[0].as_sym().unwrap();
+    assert_eq!(result.name, "first");
+    assert_eq!(result.addr, 0x4000);
+    assert_eq!(result.offset, 0x10);
+    let result = results[1].as_sym().unwrap();
+    assert!(
+        result
+            .name
+            .contains("symbolize_process_snapshot_deleted_file"),
+        "{result:x?}"
+    );
+
+    // Only absolute addresses are supported.
+    let err = symbolizer
+        .symbolize_single(&src, Input::FileOffset(0x1000))
+        .unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::Unsupported);
+}
+
+/// Check that we can symbolize addresses in the vDSO using a process
+/// snapshot.
+#[test]
+fn symbolize_process_snapshot_vdso() {
+    let base = unsafe { libc::getauxval(libc::AT_SYSINFO_EHDR) } as Addr;
+    if base == 0 {
+        // No vDSO present on this system.
+        return
+    }
+
+    let addrs = (base..base + 0x2000).step_by(4).collect::<Vec<_>>();
+    let src = Source::from(ProcessSnapshot::capture(Pid::Slf).unwrap());
+    let symbolizer = Symbolizer::new();
+    let results = symbolizer.symbolize(&src, Input::AbsAddr(&addrs)).unwrap();
+    assert!(
+        results
+            .iter()
+            .filter_map(Symbolized::as_sym)
+            .any(|sym| sym.name.contains("clock_gettime")),
+        "{results:#x?}"
+    );
+}
+
 /// Test that we can use a custom dispatch function when symbolizing addresses
 /// in processes.
 #[test]
@@ -901,6 +2057,163 @@ fn symbolize_process_with_custom_dispatch() {
 }
 
 
+/// Check that addresses in shared memory mappings are reported as
+/// unsupported by default and can be symbolized using a custom shared
+/// memory dispatcher.
+#[test]
+fn symbolize_process_shared_mem() {
+    #[derive(Debug)]
+    struct Resolver;
+
+    impl Symbolize for Resolver {
+        fn find_sym(
+            &self,
+            addr: Addr,
+            _opts: &FindSymOpts,
+        ) -> Result<Result<ResolvedSym<'_>, Reason>> {
+            let sym = ResolvedSym {
+                name: "jitted_fn",
+                addr: addr & !0xf,
+                size: Some(0x10),
+                lang: SrcLang::Unknown,
+                code_info: None,
+                inlined: Box::new([]),
+                inlined_truncated: false,
+                section: None,
+                module: None,
+            };
+            Ok(Ok(sym))
+        }
+    }
+
+    impl TranslateFileOffset for Resolver {
+        fn file_offset_to_virt_offset(&self, file_offset: u64) -> Result<Option<Addr>> {
+            Ok(Some(file_offset))
+        }
+    }
+
+    fn shared_mem_dispatch(info: SharedMemInfo<'_>) -> Result<Option<Box<dyn Resolve>>> {
+        assert!(info.path.symbolic_path.starts_with("/dev/shm"));
+        assert_eq!(info.offset, 0);
+        Ok(Some(Box::new(Resolver)))
+    }
+
+    let path = format!("/dev/shm/blazesym-test-{}", process::id());
+    let mut file = File::options()
+        .create_new(true)
+        .write(true)
+        .read(true)
+        .open(&path)
+        .unwrap();
+    defer!({
+        let _result = remove_file(&path);
+    });
+    let () = file.write_all(&[0; 4096]).unwrap();
+
+    let mmap = Mmap::builder().open(&path).unwrap();
+    let addr = mmap.as_ptr() as Addr + 0x123;
+    let src = Source::Process(Process::new(Pid::Slf));
+
+    let symbolizer = Symbolizer::new();
+    let result = symbolizer
+        .symbolize_single(&src, Input::AbsAddr(addr))
+        .unwrap();
+    assert_eq!(result, Symbolized::Unknown(Reason::Unsupported));
+
+    let symbolizer = Symbolizer::builder()
+        .set_shared_mem_dispatcher(shared_mem_dispatch)
+        .build();
+    let result = symbolizer
+        .symbolize_single(&src, Input::AbsAddr(addr))
+        .unwrap()
+        .into_sym()
+        .unwrap();
+    assert_eq!(result.name, "jitted_fn");
+    assert_eq!(result.addr, 0x120);
+    assert_eq!(result.offset, 0x3);
+}
+
+/// Check that we honor the configured maximum depth of inlined
+/// function chains.
+#[test]
+fn symbolize_max_inlined_depth() {
+    #[derive(Debug)]
+    struct Resolver;
+
+    impl Symbolize for Resolver {
+        fn find_sym(
+            &self,
+            addr: Addr,
+            _opts: &FindSymOpts,
+        ) -> Result<Result<ResolvedSym<'_>, Reason>> {
+            let inlined = ["outer", "middle", "inner"]
+                .into_iter()
+                .map(|name| InlinedFn {
+                    name: Cow::Borrowed(name),
+                    code_info: None,
+                    _non_exhaustive: (),
+                })
+                .collect();
+            let sym = ResolvedSym {
+                name: "func",
+                addr,
+                size: None,
+                lang: SrcLang::Unknown,
+                code_info: None,
+                inlined,
+                inlined_truncated: false,
+                section: None,
+                module: None,
+            };
+            Ok(Ok(sym))
+        }
+    }
+
+    impl TranslateFileOffset for Resolver {
+        fn file_offset_to_virt_offset(&self, file_offset: u64) -> Result<Option<Addr>> {
+            Ok(Some(file_offset))
+        }
+    }
+
+    fn process_dispatch(_info: ProcessMemberInfo<'_>) -> Result<Option<Box<dyn Resolve>>> {
+        Ok(Some(Box::new(Resolver)))
+    }
+
+    let src = Source::Process(Process::new(Pid::Slf));
+    let addr = symbolize_max_inlined_depth as Addr;
+
+    let symbolizer = Symbolizer::builder()
+        .set_process_dispatcher(process_dispatch)
+        .build();
+    let result = symbolizer
+        .symbolize_single(&src, Input::AbsAddr(addr))
+        .unwrap()
+        .into_sym()
+        .unwrap();
+    assert_eq!(result.inlined.len(), 3);
+    assert!(!result.inlined_truncated);
+
+    for (depth, expected) in [(3, &["outer", "middle", "inner"][..]), (1, &["outer"][..])] {
+        let symbolizer = Symbolizer::builder()
+            .set_process_dispatcher(process_dispatch)
+            .set_max_inlined_depth(Some(depth))
+            .build();
+        let result = symbolizer
+            .symbolize_single(&src, Input::AbsAddr(addr))
+            .unwrap()
+            .into_sym()
+            .unwrap();
+        let names = result
+            .inlined
+            .iter()
+            .map(|inlined_fn| inlined_fn.name.as_ref())
+            .collect::<Vec<_>>();
+        assert_eq!(names, expected);
+        assert_eq!(result.inlined_truncated, depth < 3);
+    }
+}
+
+
 /// Make sure that we do not fail symbolization when an empty perf
 /// map is present.
 #[forked_test]
@@ -988,16 +2301,301 @@ if __name__ == "__main__":
         });
 }
 
+/// Check that we can symbolize an address in an anonymous executable
+/// mapping by means of a registered symbol socket.
+#[cfg(linux)]
+#[test]
+fn symbolize_process_sym_socket() {
+    use std::io::BufRead as _;
+    use std::io::BufReader;
+    use std::os::unix::net::UnixListener;
+    use std::ptr;
+    use std::thread;
+
+    let len = 4096;
+    let jit = unsafe {
+        libc::mmap(
+            ptr::null_mut(),
+            len,
+            libc::PROT_READ | libc::PROT_EXEC,
+            libc::MAP_PRIVATE | libc::MAP_ANONYMOUS,
+            -1,
+            0,
+        )
+    };
+    assert_ne!(jit, libc::MAP_FAILED);
+    defer!({
+        let _rc = unsafe { libc::munmap(jit, len) };
+    });
+    let jit = jit as Addr;
+
+    let dir = tempdir().unwrap();
+    let path = dir.path().join("sym.sock");
+    let listener = UnixListener::bind(&path).unwrap();
+    let server = thread::spawn(move || {
+        let (stream, _addr) = listener.accept().unwrap();
+        let mut reader = BufReader::new(&stream);
+        let mut line = String::new();
+        while reader.read_line(&mut line).unwrap() != 0 {
+            let addr = Addr::from_str_radix(line.trim_end(), 16).unwrap();
+            let response = if (jit..jit + 0x40).contains(&addr) {
+                format!("{jit:x}\t40\tjit::compiled_fn\tscript.js\t13\n")
+            } else {
+                "\n".to_string()
+            };
+            let () = (&stream).write_all(response.as_bytes()).unwrap();
+            let () = line.clear();
+        }
+    });
+
+    let src = Source::Process(Process::new(Pid::Slf));
+    let symbolizer = Symbolizer::new();
+    let () = symbolizer.register_sym_socket(Pid::Slf, &path).unwrap();
+
+    let results = symbolizer
+        .symbolize(&src, Input::AbsAddr(&[jit + 0x10, jit + 0x80]))
+        .unwrap();
+    let sym = results[0].as_sym().unwrap();
+    assert_eq!(sym.name, "jit::compiled_fn");
+    assert_eq!(sym.addr, jit);
+    assert_eq!(sym.offset, 0x10);
+    assert_eq!(sym.size, Some(0x40));
+    let code_info = sym.code_info.as_ref().unwrap();
+    assert_eq!(code_info.file, OsStr::new("script.js"));
+    assert_eq!(code_info.line, Some(13));
+    assert_eq!(results[1], Symbolized::Unknown(Reason::UnknownAddr));
+
+    assert!(symbolizer.unregister_sym_socket(&Pid::Slf));
+    assert!(!symbolizer.unregister_sym_socket(&Pid::Slf));
+    let () = drop(symbolizer);
+    let () = server.join().unwrap();
+}
+
+/// Check that a misbehaving symbol socket does not fail
+/// symbolization of the process as a whole.
+#[cfg(linux)]
+#[test]
+fn symbolize_process_sym_socket_failure() {
+    use std::os::unix::net::UnixListener;
+    use std::ptr;
+    use std::thread;
+
+    let len = 4096;
+    let jit = unsafe {
+        libc::mmap(
+            ptr::null_mut(),
+            len,
+            libc::PROT_READ | libc::PROT_EXEC,
+            libc::MAP_PRIVATE | libc::MAP_ANONYMOUS,
+            -1,
+            0,
+        )
+    };
+    assert_ne!(jit, libc::MAP_FAILED);
+    defer!({
+        let _rc = unsafe { libc::munmap(jit, len) };
+    });
+    let jit = jit as Addr;
+
+    let dir = tempdir().unwrap();
+    let path = dir.path().join("sym.sock");
+    let listener = UnixListener::bind(&path).unwrap();
+    // A server that accepts the connection but closes it right away.
+    let server = thread::spawn(move || {
+        let (_stream, _addr) = listener.accept().unwrap();
+    });
+
+    let src = Source::Process(Process::new(Pid::Slf));
+    let symbolizer = Symbolizer::new();
+    let () = symbolizer.register_sym_socket(Pid::Slf, &path).unwrap();
+    let () = server.join().unwrap();
+
+    let results = symbolizer
+        .symbolize(&src, Input::AbsAddr(&[jit + 0x10, jit + 0x20]))
+        .unwrap();
+    assert_eq!(results[0], Symbolized::Unknown(Reason::UnknownAddr));
+    assert_eq!(results[1], Symbolized::Unknown(Reason::UnknownAddr));
+
+    // The broken socket should have been dropped.
+    assert!(!symbolizer.unregister_sym_socket(&Pid::Slf));
+}
+
+/// Check that we can symbolize an address in an anonymous executable
+/// mapping by means of a user provided unknown address resolver.
+#[cfg(linux)]
+#[test]
+fn symbolize_process_unknown_addr_resolver() {
+    use std::ptr;
+
+    #[derive(Debug)]
+    struct Resolver {
+        addr: Addr,
+    }
+
+    impl Symbolize for Resolver {
+        fn find_sym(
+            &self,
+            addr: Addr,
+            _opts: &FindSymOpts,
+        ) -> Result<Result<ResolvedSym<'_>, Reason>> {
+            if !(self.addr..self.addr + 0x40).contains(&addr) {
+                return Ok(Err(Reason::UnknownAddr))
+            }
+
+            let sym = ResolvedSym {
+                name: "jit::compiled_fn",
+                addr: self.addr,
+                size: Some(0x40),
+                lang: SrcLang::Unknown,
+                code_info: None,
+                inlined: Box::new([]),
+                inlined_truncated: false,
+                section: None,
+                module: None,
+            };
+            Ok(Ok(sym))
+        }
+    }
+
+    let len = 4096;
+    let jit = unsafe {
+        libc::mmap(
+            ptr::null_mut(),
+            len,
+            libc::PROT_READ | libc::PROT_EXEC,
+            libc::MAP_PRIVATE | libc::MAP_ANONYMOUS,
+            -1,
+            0,
+        )
+    };
+    assert_ne!(jit, libc::MAP_FAILED);
+    defer!({
+        let _rc = unsafe { libc::munmap(jit, len) };
+    });
+    let jit = jit as Addr;
+
+    let symbolizer = Symbolizer::builder()
+        .set_unknown_addr_resolver(|info: UnknownAddrInfo<'_>| {
+            assert_eq!(info.pid, &Pid::Slf);
+            assert!(info.range.contains(&info.addr));
+            let resolver = Resolver {
+                addr: info.range.start,
+            };
+            Ok(Some(Box::new(resolver) as Box<dyn Symbolize>))
+        })
+        .build();
+
+    let src = Source::Process(Process::new(Pid::Slf));
+    let results = symbolizer
+        .symbolize(&src, Input::AbsAddr(&[jit + 0x10, jit + 0x80]))
+        .unwrap();
+    let sym = results[0].as_sym().unwrap();
+    assert_eq!(sym.name, "jit::compiled_fn");
+    assert_eq!(sym.addr, jit);
+    assert_eq!(sym.offset, 0x10);
+    assert_eq!(sym.size, Some(0x40));
+    assert_eq!(results[1], Symbolized::Unknown(Reason::UnknownAddr));
+}
+
+/// Check that we can symbolize an address in an anonymous executable
+/// mapping by means of a jitdump file mapped into the process.
+#[cfg(linux)]
+#[test]
+fn symbolize_process_jitdump() {
+    use std::fs::OpenOptions;
+    use std::os::fd::AsRawFd as _;
+    use std::ptr;
+
+    let len = 4096;
+    let jit = unsafe {
+        libc::mmap(
+            ptr::null_mut(),
+            len,
+            libc::PROT_READ | libc::PROT_EXEC,
+            libc::MAP_PRIVATE | libc::MAP_ANONYMOUS,
+            -1,
+            0,
+        )
+    };
+    assert_ne!(jit, libc::MAP_FAILED);
+    defer!({
+        let _rc = unsafe { libc::munmap(jit, len) };
+    });
+    let jit = jit as Addr;
+
+    // File header: magic, version, header size, ELF machine, padding,
+    // PID, timestamp, and flags.
+    let mut data = Vec::new();
+    let () = data.extend_from_slice(&0x4a695444u32.to_ne_bytes());
+    let () = data.extend_from_slice(&1u32.to_ne_bytes());
+    let () = data.extend_from_slice(&40u32.to_ne_bytes());
+    let () = data.extend_from_slice(&[0; 28]);
+    // Code load record: id, size, timestamp, PID, TID, VMA, code
+    // address, code size, code index, name, and code.
+    let name = b"jit::compiled_fn\0";
+    let code = [0xcc; 0x40];
+    let size = 16 + 40 + name.len() + code.len();
+    let () = data.extend_from_slice(&0u32.to_ne_bytes());
+    let () = data.extend_from_slice(&(size as u32).to_ne_bytes());
+    let () = data.extend_from_slice(&[0; 16]);
+    let () = data.extend_from_slice(&jit.to_ne_bytes());
+    let () = data.extend_from_slice(&jit.to_ne_bytes());
+    let () = data.extend_from_slice(&(code.len() as u64).to_ne_bytes());
+    let () = data.extend_from_slice(&0u64.to_ne_bytes());
+    let () = data.extend_from_slice(name);
+    let () = data.extend_from_slice(&code);
+
+    let dir = tempdir().unwrap();
+    let path = dir.path().join(format!("jit-{}.dump", process::id()));
+    let mut file = OpenOptions::new()
+        .create_new(true)
+        .read(true)
+        .write(true)
+        .open(&path)
+        .unwrap();
+    let () = file.write_all(&data).unwrap();
+
+    // Runtimes map the jitdump file so that it can be discovered by
+    // tools. They use an executable mapping, but a readable one is
+    // sufficient for our purposes.
+    let dump = unsafe {
+        libc::mmap(
+            ptr::null_mut(),
+            data.len(),
+            libc::PROT_READ,
+            libc::MAP_PRIVATE,
+            file.as_raw_fd(),
+            0,
+        )
+    };
+    assert_ne!(dump, libc::MAP_FAILED);
+    defer!({
+        let _rc = unsafe { libc::munmap(dump, data.len()) };
+    });
+
+    let src = Source::Process(Process::new(Pid::Slf));
+    let symbolizer = Symbolizer::new();
+    let results = symbolizer
+        .symbolize(&src, Input::AbsAddr(&[jit + 0x10, jit + 0x80]))
+        .unwrap();
+    let sym = results[0].as_sym().unwrap();
+    assert_eq!(sym.name, "jit::compiled_fn");
+    assert_eq!(sym.addr, jit);
+    assert_eq!(sym.offset, 0x10);
+    assert_eq!(sym.size, Some(0x40));
+    assert_eq!(results[1], Symbolized::Unknown(Reason::UnknownAddr));
+}
+
 fn symbolize_permissionless_impl(pid: Pid, addr: Addr, _test_lib: &Path) {
     let process = Process::new(pid);
     assert!(process.map_files);
 
     let src = Source::Process(process);
     let symbolizer = Symbolizer::new();
-    let err = symbolizer
+    let result = symbolizer
         .symbolize_single(&src, Input::AbsAddr(addr))
-        .unwrap_err();
-    assert_eq!(err.kind(), ErrorKind::PermissionDenied);
+        .unwrap();
+    assert_eq!(result, Symbolized::Unknown(Reason::PermissionDenied));
 
     let mut process = Process::new(pid);
     process.map_files = false;